@@ -30,6 +30,11 @@ module aptos_framework::genesis {
         full_node_network_addresses: vector<u8>,
     }
 
+    struct AccountMap has drop {
+        account_address: address,
+        balance: u64,
+    }
+
     /// Genesis step 1: Initialize aptos framework account and core modules on chain.
     fun initialize(
         gas_schedule: vector<u8>,
@@ -120,6 +125,21 @@ module aptos_framework::genesis {
         aptos_coin::configure_accounts_for_test(aptos_framework, &core_resources, mint_cap);
     }
 
+    /// Creates the accounts pre-funded at genesis, e.g. a treasury multisig, and mints their balances to them.
+    /// This must run before the validators are initialized, as that revokes the ability to mint.
+    fun create_accounts(aptos_framework: &signer, accounts: vector<AccountMap>) {
+        let i = 0;
+        let num_accounts = vector::length(&accounts);
+        while (i < num_accounts) {
+            let account_map = vector::borrow(&accounts, i);
+            let account = account::create_account_internal(account_map.account_address);
+            coins::register<AptosCoin>(&account);
+            aptos_coin::mint(aptos_framework, account_map.account_address, account_map.balance);
+
+            i = i + 1;
+        };
+    }
+
     /// Sets up the initial validator set for the network.
     /// The validator "owner" accounts, and their authentication
     /// Addresses (and keys) are encoded in the `owners`
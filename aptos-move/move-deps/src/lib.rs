@@ -9,6 +9,7 @@ pub use move_cli;
 pub use move_command_line_common;
 pub use move_compiler;
 pub use move_core_types;
+pub use move_coverage;
 pub use move_docgen;
 pub use move_errmapgen;
 pub use move_ir_compiler;
@@ -21,6 +21,7 @@ use aptos_types::{
     contract_event::ContractEvent,
     on_chain_config::{ConsensusConfigV1, OnChainConsensusConfig, APTOS_MAX_KNOWN_VERSION},
     transaction::{authenticator::AuthenticationKey, ChangeSet, Transaction, WriteSetPayload},
+    utility_coin::APTOS_COIN_TYPE,
 };
 use aptos_vm::{
     data_cache::{IntoMoveResolver, StateViewCache},
@@ -40,6 +41,7 @@ use move_deps::{
 use once_cell::sync::Lazy;
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 
 // The seed is arbitrarily picked to produce a consistent key. XXX make this more formal?
 const GENESIS_SEED: [u8; 32] = [42; 32];
@@ -47,6 +49,7 @@ const GENESIS_SEED: [u8; 32] = [42; 32];
 const GENESIS_MODULE_NAME: &str = "genesis";
 const GOVERNANCE_MODULE_NAME: &str = "aptos_governance";
 const CODE_MODULE_NAME: &str = "code";
+const COIN_MODULE_NAME: &str = "coin";
 const VERSION_MODULE_NAME: &str = "version";
 
 const NUM_SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
@@ -66,6 +69,8 @@ pub struct GenesisConfiguration {
     pub rewards_apy_percentage: u64,
     pub voting_duration_secs: u64,
     pub voting_power_increase_limit: u64,
+    /// Accounts created and funded at genesis, with their balance of the native coin
+    pub initial_accounts: Vec<(AccountAddress, u64)>,
 }
 
 pub static GENESIS_KEYPAIR: Lazy<(Ed25519PrivateKey, Ed25519PublicKey)> = Lazy::new(|| {
@@ -75,6 +80,69 @@ pub static GENESIS_KEYPAIR: Lazy<(Ed25519PrivateKey, Ed25519PublicKey)> = Lazy::
     (private_key, public_key)
 });
 
+/// What a genesis transaction sets up, taken from the arguments of the Move calls it makes, so
+/// it can be audited without decoding the write set
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GenesisSummary {
+    pub chain_id: u8,
+    pub validators: Vec<ValidatorSummary>,
+    /// Supply of the native coin once genesis has run
+    pub total_supply_minted: u128,
+    pub config: GenesisConfigSummary,
+    pub governance: GenesisGovernanceSummary,
+    pub initial_accounts: Vec<InitialAccountSummary>,
+}
+
+/// An account created and funded at genesis
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct InitialAccountSummary {
+    pub account_address: AccountAddress,
+    pub balance: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ValidatorSummary {
+    pub owner_address: AccountAddress,
+    pub operator_address: AccountAddress,
+    pub voter_address: AccountAddress,
+    pub stake_amount: u64,
+    /// Hex encoded bls12381 public key used to sign consensus messages
+    pub consensus_pubkey: String,
+}
+
+impl From<&Validator> for ValidatorSummary {
+    fn from(validator: &Validator) -> Self {
+        Self {
+            owner_address: validator.owner_address,
+            operator_address: validator.operator_address,
+            voter_address: validator.voter_address,
+            stake_amount: validator.stake_amount,
+            consensus_pubkey: format!("0x{}", hex::encode(&validator.consensus_pubkey)),
+        }
+    }
+}
+
+/// The arguments of `genesis::initialize`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GenesisConfigSummary {
+    pub epoch_interval_usecs: u64,
+    pub min_stake: u64,
+    pub max_stake: u64,
+    pub recurring_lockup_duration_secs: u64,
+    pub allow_new_validators: bool,
+    pub rewards_rate_numerator: u64,
+    pub rewards_rate_denominator: u64,
+    pub voting_power_increase_limit: u64,
+}
+
+/// The arguments of `aptos_governance::initialize`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GenesisGovernanceSummary {
+    pub min_voting_threshold: u128,
+    pub required_proposer_stake: u64,
+    pub voting_duration_secs: u64,
+}
+
 pub fn encode_genesis_transaction(
     aptos_root_key: Ed25519PublicKey,
     validators: &[Validator],
@@ -82,16 +150,38 @@ pub fn encode_genesis_transaction(
     chain_id: ChainId,
     genesis_config: GenesisConfiguration,
 ) -> Transaction {
+    encode_genesis_transaction_with_summary(
+        aptos_root_key,
+        validators,
+        framework,
+        chain_id,
+        genesis_config,
+    )
+    .0
+}
+
+/// Like [`encode_genesis_transaction`], also returning a summary of what the transaction sets up
+pub fn encode_genesis_transaction_with_summary(
+    aptos_root_key: Ed25519PublicKey,
+    validators: &[Validator],
+    framework: &ReleaseBundle,
+    chain_id: ChainId,
+    genesis_config: GenesisConfiguration,
+) -> (Transaction, GenesisSummary) {
     let consensus_config = OnChainConsensusConfig::V1(ConsensusConfigV1::default());
 
-    Transaction::GenesisTransaction(WriteSetPayload::Direct(encode_genesis_change_set(
+    let (change_set, summary) = encode_genesis_change_set_with_summary(
         &aptos_root_key,
         validators,
         framework,
         consensus_config,
         chain_id,
         &genesis_config,
-    )))
+    );
+    (
+        Transaction::GenesisTransaction(WriteSetPayload::Direct(change_set)),
+        summary,
+    )
 }
 
 pub fn encode_genesis_change_set(
@@ -102,7 +192,28 @@ pub fn encode_genesis_change_set(
     chain_id: ChainId,
     genesis_config: &GenesisConfiguration,
 ) -> ChangeSet {
+    encode_genesis_change_set_with_summary(
+        core_resources_key,
+        validators,
+        framework,
+        consensus_config,
+        chain_id,
+        genesis_config,
+    )
+    .0
+}
+
+/// Like [`encode_genesis_change_set`], also returning a summary of what the change set sets up
+pub fn encode_genesis_change_set_with_summary(
+    core_resources_key: &Ed25519PublicKey,
+    validators: &[Validator],
+    framework: &ReleaseBundle,
+    consensus_config: OnChainConsensusConfig,
+    chain_id: ChainId,
+    genesis_config: &GenesisConfiguration,
+) -> (ChangeSet, GenesisSummary) {
     validate_genesis_config(genesis_config);
+    validate_initial_accounts(&genesis_config.initial_accounts, validators);
 
     // Create a Move VM session so we can invoke on-chain genesis intializations.
     let mut state_view = GenesisStateView::new();
@@ -115,17 +226,27 @@ pub fn encode_genesis_change_set(
     let mut session = move_vm.new_session(&data_cache, SessionId::genesis(id1));
 
     // On-chain genesis process.
-    initialize(&mut session, consensus_config, chain_id, genesis_config);
+    let config = initialize(&mut session, consensus_config, chain_id, genesis_config);
     if genesis_config.is_test {
         initialize_core_resources_and_aptos_coin(&mut session, core_resources_key);
     } else {
         initialize_aptos_coin(&mut session);
     }
-    initialize_on_chain_governance(&mut session, genesis_config);
-    create_and_initialize_validators(&mut session, validators);
+    let governance = initialize_on_chain_governance(&mut session, genesis_config);
+    let initial_accounts =
+        initialize_initial_accounts(&mut session, &genesis_config.initial_accounts);
+    let validators = create_and_initialize_validators(&mut session, validators);
     if genesis_config.is_test {
         allow_core_resources_to_set_version(&mut session);
     }
+    let summary = GenesisSummary {
+        chain_id: chain_id.id(),
+        validators,
+        total_supply_minted: aptos_coin_supply(&mut session),
+        config,
+        governance,
+        initial_accounts,
+    };
 
     // Reconfiguration should happen after all on-chain invocations.
     emit_new_block_and_epoch_event(&mut session);
@@ -166,7 +287,7 @@ pub fn encode_genesis_change_set(
         .iter()
         .any(|(_, op)| op.is_deletion()));
     verify_genesis_write_set(change_set.events());
-    change_set
+    (change_set, summary)
 }
 
 fn validate_genesis_config(genesis_config: &GenesisConfiguration) {
@@ -205,6 +326,24 @@ fn validate_genesis_config(genesis_config: &GenesisConfiguration) {
     );
 }
 
+/// Initial accounts are created at genesis, so they must be distinct from each other and from
+/// the accounts created for the validators
+fn validate_initial_accounts(initial_accounts: &[(AccountAddress, u64)], validators: &[Validator]) {
+    let mut addresses = BTreeSet::new();
+    for validator in validators {
+        addresses.insert(validator.owner_address);
+        addresses.insert(validator.operator_address);
+        addresses.insert(validator.voter_address);
+    }
+    for (address, _) in initial_accounts {
+        assert!(
+            addresses.insert(*address),
+            "Initial account {} is listed twice or is a validator account",
+            address
+        );
+    }
+}
+
 fn exec_function(
     session: &mut SessionExt<impl MoveResolver>,
     module_name: &str,
@@ -238,7 +377,7 @@ fn initialize(
     consensus_config: OnChainConsensusConfig,
     chain_id: ChainId,
     genesis_config: &GenesisConfiguration,
-) {
+) -> GenesisConfigSummary {
     let genesis_gas_params = AptosGasParameters::initial();
     let gas_schedule_blob = bcs::to_bytes(&genesis_gas_params.to_on_chain_gas_schedule())
         .expect("Failure serializing genesis gas schedule");
@@ -258,6 +397,16 @@ fn initialize(
     // Block timestamps are in microseconds and epoch_interval is used to check if a block timestamp
     // has crossed into a new epoch. So epoch_interval also needs to be in micro seconds.
     let epoch_interval_usecs = genesis_config.epoch_duration_secs * MICRO_SECONDS_PER_SECOND;
+    let summary = GenesisConfigSummary {
+        epoch_interval_usecs,
+        min_stake: genesis_config.min_stake,
+        max_stake: genesis_config.max_stake,
+        recurring_lockup_duration_secs: genesis_config.recurring_lockup_duration_secs,
+        allow_new_validators: genesis_config.allow_new_validators,
+        rewards_rate_numerator,
+        rewards_rate_denominator,
+        voting_power_increase_limit: genesis_config.voting_power_increase_limit,
+    };
     exec_function(
         session,
         GENESIS_MODULE_NAME,
@@ -268,16 +417,17 @@ fn initialize(
             MoveValue::U8(chain_id.id()),
             MoveValue::U64(APTOS_MAX_KNOWN_VERSION.major),
             MoveValue::vector_u8(consensus_config_bytes),
-            MoveValue::U64(epoch_interval_usecs),
-            MoveValue::U64(genesis_config.min_stake),
-            MoveValue::U64(genesis_config.max_stake),
-            MoveValue::U64(genesis_config.recurring_lockup_duration_secs),
-            MoveValue::Bool(genesis_config.allow_new_validators),
-            MoveValue::U64(rewards_rate_numerator),
-            MoveValue::U64(rewards_rate_denominator),
-            MoveValue::U64(genesis_config.voting_power_increase_limit),
+            MoveValue::U64(summary.epoch_interval_usecs),
+            MoveValue::U64(summary.min_stake),
+            MoveValue::U64(summary.max_stake),
+            MoveValue::U64(summary.recurring_lockup_duration_secs),
+            MoveValue::Bool(summary.allow_new_validators),
+            MoveValue::U64(summary.rewards_rate_numerator),
+            MoveValue::U64(summary.rewards_rate_denominator),
+            MoveValue::U64(summary.voting_power_increase_limit),
         ]),
     );
+    summary
 }
 
 fn initialize_aptos_coin(session: &mut SessionExt<impl MoveResolver>) {
@@ -311,7 +461,12 @@ fn initialize_core_resources_and_aptos_coin(
 fn initialize_on_chain_governance(
     session: &mut SessionExt<impl MoveResolver>,
     genesis_config: &GenesisConfiguration,
-) {
+) -> GenesisGovernanceSummary {
+    let summary = GenesisGovernanceSummary {
+        min_voting_threshold: genesis_config.min_voting_threshold,
+        required_proposer_stake: genesis_config.required_proposer_stake,
+        voting_duration_secs: genesis_config.voting_duration_secs,
+    };
     exec_function(
         session,
         GOVERNANCE_MODULE_NAME,
@@ -319,11 +474,38 @@ fn initialize_on_chain_governance(
         vec![],
         serialize_values(&vec![
             MoveValue::Signer(CORE_CODE_ADDRESS),
-            MoveValue::U128(genesis_config.min_voting_threshold),
-            MoveValue::U64(genesis_config.required_proposer_stake),
-            MoveValue::U64(genesis_config.voting_duration_secs),
+            MoveValue::U128(summary.min_voting_threshold),
+            MoveValue::U64(summary.required_proposer_stake),
+            MoveValue::U64(summary.voting_duration_secs),
         ]),
     );
+    summary
+}
+
+/// Creates each initial account and mints its balance to it. This must run before the validators
+/// are initialized, which revokes the ability to mint.
+fn initialize_initial_accounts(
+    session: &mut SessionExt<impl MoveResolver>,
+    initial_accounts: &[(AccountAddress, u64)],
+) -> Vec<InitialAccountSummary> {
+    let summaries: Vec<_> = initial_accounts
+        .iter()
+        .map(|(account_address, balance)| InitialAccountSummary {
+            account_address: *account_address,
+            balance: *balance,
+        })
+        .collect();
+    let accounts_bytes = bcs::to_bytes(&summaries).expect("Initial accounts can be serialized");
+    let mut serialized_values = serialize_values(&vec![MoveValue::Signer(CORE_CODE_ADDRESS)]);
+    serialized_values.push(accounts_bytes);
+    exec_function(
+        session,
+        GENESIS_MODULE_NAME,
+        "create_accounts",
+        vec![],
+        serialized_values,
+    );
+    summaries
 }
 
 /// Creates and initializes each validator owner and validator operator. This method creates all
@@ -332,7 +514,7 @@ fn initialize_on_chain_governance(
 fn create_and_initialize_validators(
     session: &mut SessionExt<impl MoveResolver>,
     validators: &[Validator],
-) {
+) -> Vec<ValidatorSummary> {
     let validators_bytes = bcs::to_bytes(validators).expect("Validators can be serialized");
     let mut serialized_values = serialize_values(&vec![MoveValue::Signer(CORE_CODE_ADDRESS)]);
     serialized_values.push(validators_bytes);
@@ -343,6 +525,28 @@ fn create_and_initialize_validators(
         vec![],
         serialized_values,
     );
+    validators.iter().map(ValidatorSummary::from).collect()
+}
+
+/// The supply of the native coin, which is tracked once the coin is initialized
+fn aptos_coin_supply(session: &mut SessionExt<impl MoveResolver>) -> u128 {
+    let return_values = session
+        .execute_function_bypass_visibility(
+            &ModuleId::new(
+                CORE_CODE_ADDRESS,
+                Identifier::new(COIN_MODULE_NAME).unwrap(),
+            ),
+            &Identifier::new("supply").unwrap(),
+            vec![APTOS_COIN_TYPE.clone()],
+            vec![],
+            &mut UnmeteredGasMeter,
+        )
+        .unwrap_or_else(|e| panic!("Error calling coin.supply: {}", e.into_vm_status()))
+        .return_values;
+    let (supply, _) = return_values.first().expect("coin::supply returns a value");
+    bcs::from_bytes::<Option<u128>>(supply)
+        .expect("coin::supply returns an Option<u128>")
+        .expect("The native coin's supply is tracked")
 }
 
 fn allow_core_resources_to_set_version(session: &mut SessionExt<impl MoveResolver>) {
@@ -566,6 +770,7 @@ pub fn generate_test_genesis(
             rewards_apy_percentage: 10,
             voting_duration_secs: 3600,
             voting_power_increase_limit: 50,
+            initial_accounts: vec![],
         },
     );
     (genesis, test_validators)
@@ -600,6 +805,7 @@ pub fn generate_mainnet_genesis(
             rewards_apy_percentage: 10,
             voting_duration_secs: 7 * 24 * 3600, // 7 days
             voting_power_increase_limit: 30,
+            initial_accounts: vec![],
         },
     );
     (genesis, test_validators)
@@ -620,3 +826,58 @@ pub fn test_genesis_module_publishing() {
     let mut session = move_vm.new_session(&data_cache, SessionId::genesis(id1));
     publish_framework(&mut session, cached_packages::head_release_bundle());
 }
+
+#[test]
+pub fn test_genesis_initial_accounts() {
+    let validators: Vec<Validator> = TestValidator::new_test_set(Some(1), Some(100_000_000))
+        .into_iter()
+        .map(|validator| validator.data)
+        .collect();
+    let genesis_summary = |initial_accounts: Vec<(AccountAddress, u64)>| {
+        encode_genesis_change_set_with_summary(
+            &GENESIS_KEYPAIR.1,
+            &validators,
+            cached_packages::head_release_bundle(),
+            OnChainConsensusConfig::default(),
+            ChainId::test(),
+            &GenesisConfiguration {
+                allow_new_validators: true,
+                epoch_duration_secs: 3600,
+                is_test: true,
+                min_stake: 0,
+                min_voting_threshold: 0,
+                max_stake: 100_000_000_000_000,
+                recurring_lockup_duration_secs: 7200,
+                required_proposer_stake: 0,
+                rewards_apy_percentage: 10,
+                voting_duration_secs: 3600,
+                voting_power_increase_limit: 50,
+                initial_accounts,
+            },
+        )
+        .1
+    };
+
+    let treasury = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+    let grants = AccountAddress::from_hex_literal("0xb0b").unwrap();
+    let without_accounts = genesis_summary(vec![]);
+    let with_accounts = genesis_summary(vec![(treasury, 1_000), (grants, 234)]);
+
+    assert_eq!(
+        with_accounts.initial_accounts,
+        vec![
+            InitialAccountSummary {
+                account_address: treasury,
+                balance: 1_000,
+            },
+            InitialAccountSummary {
+                account_address: grants,
+                balance: 234,
+            },
+        ]
+    );
+    assert_eq!(
+        with_accounts.total_supply_minted,
+        without_accounts.total_supply_minted + 1_234
+    );
+}
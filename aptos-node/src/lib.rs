@@ -483,7 +483,7 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
             &node_config.storage.dir(),
             false, /* readonly */
             node_config.storage.storage_pruner_config,
-            node_config.storage.rocksdb_configs,
+            node_config.storage.rocksdb_configs.clone(),
             node_config.storage.enable_indexer,
             node_config.storage.target_snapshot_size,
             node_config.storage.max_num_nodes_per_lru_cache_shard,
@@ -20,6 +20,11 @@ pub struct ApiConfig {
     pub content_length_limit: Option<u64>,
     #[serde(default = "default_disabled")]
     pub failpoints_enabled: bool,
+    #[serde(default = "default_disabled")]
+    pub log_request_bodies: bool,
+    /// Origins allowed to make cross-origin requests, any origin if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cors_allowed_origins: Option<Vec<String>>,
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -45,6 +50,8 @@ impl Default for ApiConfig {
             tls_key_path: None,
             content_length_limit: None,
             failpoints_enabled: default_disabled(),
+            log_request_bodies: default_disabled(),
+            cors_allowed_origins: None,
         }
     }
 }
@@ -4,9 +4,12 @@
 use crate::utils;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::PathBuf,
+    str::FromStr,
 };
+use thiserror::Error;
 
 // Lru cache will consume about 2G RAM based on this default value.
 pub const DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD: usize = 1 << 13;
@@ -16,7 +19,7 @@ pub const TARGET_SNAPSHOT_SIZE: usize = 100_000;
 /// Port selected RocksDB options for tuning underlying rocksdb instance of AptosDB.
 /// see <https://github.com/facebook/rocksdb/blob/master/include/rocksdb/options.h>
 /// for detailed explanations.
-#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct RocksdbConfig {
     pub max_open_files: i32,
     pub max_total_wal_size: u64,
@@ -24,6 +27,64 @@ pub struct RocksdbConfig {
     pub block_cache_size: u64,
     pub block_size: u64,
     pub cache_index_and_filter_blocks: bool,
+    /// Whether RocksDB collects statistics, which are exported as metrics.
+    #[serde(default = "default_enable_statistics")]
+    pub enable_statistics: bool,
+    /// Overrides of the options of single column families, by column family name, e.g.
+    /// `state_value`, `jellyfish_merkle_node` or `table_info`.
+    #[serde(default)]
+    pub column_families: BTreeMap<String, ColumnFamilyConfig>,
+}
+
+/// Compression of the data blocks of a column family
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RocksdbCompression {
+    None,
+    Lz4,
+    Lz4hc,
+    Zstd,
+}
+
+impl Default for RocksdbCompression {
+    fn default() -> Self {
+        RocksdbCompression::Lz4
+    }
+}
+
+impl FromStr for RocksdbCompression {
+    type Err = ParseRocksdbCompressionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(RocksdbCompression::None),
+            "lz4" => Ok(RocksdbCompression::Lz4),
+            "lz4hc" => Ok(RocksdbCompression::Lz4hc),
+            "zstd" => Ok(RocksdbCompression::Zstd),
+            _ => Err(ParseRocksdbCompressionError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid RocksDB compression: {0}, expected one of none, lz4, lz4hc and zstd")]
+pub struct ParseRocksdbCompressionError(String);
+
+/// RocksDB options of one column family, anything not set is taken from the [`RocksdbConfig`]
+/// of the DB
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColumnFamilyConfig {
+    /// Compression of the data blocks, `lz4` if not set
+    pub compression: Option<RocksdbCompression>,
+    /// Size of the data blocks, the DB's `block_size` if not set
+    pub block_size: Option<u64>,
+    /// Bits per key of a bloom filter, which is not used if not set
+    pub bloom_filter_bits: Option<f64>,
+}
+
+fn default_enable_statistics() -> bool {
+    true
 }
 
 impl Default for RocksdbConfig {
@@ -43,11 +104,15 @@ impl Default for RocksdbConfig {
             block_size: 4 * (1u64 << 10),
             // Whether cache index and filter blocks into block cache.
             cache_index_and_filter_blocks: false,
+            // Collecting statistics costs a bit of performance, but they're needed to tell block
+            // cache efficiency and write stalls apart.
+            enable_statistics: default_enable_statistics(),
+            column_families: BTreeMap::new(),
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct RocksdbConfigs {
     pub ledger_db_config: RocksdbConfig,
@@ -212,3 +277,39 @@ impl StorageConfig {
             .set_port(utils::get_available_port());
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_column_family_config() {
+        let config: RocksdbConfigs = serde_yaml::from_str(
+            r#"
+            ledger_db_config:
+              max_open_files: 5000
+              max_total_wal_size: 1073741824
+              max_background_jobs: 16
+              block_cache_size: 8388608
+              block_size: 4096
+              cache_index_and_filter_blocks: false
+              column_families:
+                state_value:
+                  compression: zstd
+                  bloom_filter_bits: 10
+            "#,
+        )
+        .unwrap();
+        let state_value = &config.ledger_db_config.column_families["state_value"];
+        assert_eq!(state_value.compression, Some(RocksdbCompression::Zstd));
+        assert_eq!(state_value.block_size, None);
+        assert_eq!(state_value.bloom_filter_bits, Some(10.0));
+        assert!(config.state_merkle_db_config.column_families.is_empty());
+
+        // Unknown compressions are rejected with the supported ones
+        let err = serde_yaml::from_str::<ColumnFamilyConfig>("compression: zstandard").unwrap_err();
+        assert!(err.to_string().contains("zstd"), "{}", err);
+        let err = "zstandard".parse::<RocksdbCompression>().unwrap_err();
+        assert!(err.to_string().contains("zstd"), "{}", err);
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
     keys::{generate_key_objects, PrivateIdentity},
     GenesisInfo,
 };
-use anyhow::ensure;
+use anyhow::{anyhow, ensure};
 use aptos_config::config::RocksDbStorageConfig;
 use aptos_config::{
     config::{
@@ -23,7 +23,10 @@ use aptos_crypto::{
     PrivateKey,
 };
 use aptos_keygen::KeyGen;
-use aptos_types::{chain_id::ChainId, transaction::Transaction, waypoint::Waypoint};
+use aptos_types::{
+    account_address::AccountAddress, chain_id::ChainId, transaction::Transaction,
+    waypoint::Waypoint, PeerId,
+};
 use framework::ReleaseBundle;
 use rand::Rng;
 use serde::{de::DeserializeOwned, Serialize};
@@ -386,9 +389,14 @@ pub struct GenesisConfiguration {
     pub rewards_apy_percentage: u64,
     pub voting_duration_secs: u64,
     pub voting_power_increase_limit: u64,
+    /// Accounts created and funded at genesis, with their balance of the native coin
+    pub initial_accounts: Vec<(AccountAddress, u64)>,
 }
 
 pub type InitConfigFn = Arc<dyn Fn(usize, &mut NodeConfig, &mut u64) + Send + Sync>;
+/// Overrides the config of the validator with the given index and peer id, after the builder set
+/// up its keys, networks and ports
+pub type InitNodeConfigFn = Arc<dyn Fn(usize, PeerId, &mut NodeConfig) + Send + Sync>;
 pub type InitGenesisConfigFn = Arc<dyn Fn(&mut GenesisConfiguration) + Send + Sync>;
 
 /// Builder that builds a network of validator nodes that can run locally
@@ -399,6 +407,7 @@ pub struct Builder {
     num_validators: NonZeroUsize,
     randomize_first_validator_ports: bool,
     init_config: Option<InitConfigFn>,
+    init_node_config: Option<InitNodeConfigFn>,
     init_genesis_config: Option<InitGenesisConfigFn>,
 }
 
@@ -413,6 +422,7 @@ impl Builder {
             num_validators: NonZeroUsize::new(1).unwrap(),
             randomize_first_validator_ports: true,
             init_config: None,
+            init_node_config: None,
             init_genesis_config: None,
         })
     }
@@ -432,6 +442,13 @@ impl Builder {
         self
     }
 
+    /// Like [`Builder::with_init_config`], but also given the validator's peer id, and with the
+    /// last say over its config
+    pub fn with_init_node_config(mut self, init_node_config: Option<InitNodeConfigFn>) -> Self {
+        self.init_node_config = init_node_config;
+        self
+    }
+
     pub fn with_init_genesis_config(
         mut self,
         init_genesis_config: Option<InitGenesisConfigFn>,
@@ -550,6 +567,13 @@ impl Builder {
             config.randomize_ports();
         }
 
+        if let Some(init_node_config) = &self.init_node_config {
+            let peer_id = config
+                .peer_id()
+                .ok_or_else(|| anyhow!("Validator {} has no peer id", index))?;
+            (init_node_config)(index, peer_id, config);
+        }
+
         Ok(validator)
     }
 
@@ -577,6 +601,7 @@ impl Builder {
             rewards_apy_percentage: 10,
             voting_duration_secs: ONE_DAY / 24,
             voting_power_increase_limit: 50,
+            initial_accounts: vec![],
         };
         if let Some(init_genesis_config) = &self.init_genesis_config {
             (init_genesis_config)(&mut genesis_config);
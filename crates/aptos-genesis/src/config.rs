@@ -14,7 +14,7 @@ use std::{
     convert::TryFrom,
     fs::File,
     io::Read,
-    net::{Ipv4Addr, Ipv6Addr, ToSocketAddrs},
+    net::{Ipv4Addr, Ipv6Addr},
     path::Path,
     str::FromStr,
 };
@@ -53,6 +53,9 @@ pub struct Layout {
     pub voting_duration_secs: u64,
     /// % of current epoch's total voting power that can be added in this epoch.
     pub voting_power_increase_limit: u64,
+    /// Accounts created and funded at genesis, with their balance of the native coin
+    #[serde(default)]
+    pub initial_accounts: Vec<(AccountAddress, u64)>,
 }
 
 impl Layout {
@@ -87,6 +90,7 @@ impl Default for Layout {
             rewards_apy_percentage: 10,
             voting_duration_secs: 43_200,
             voting_power_increase_limit: 20,
+            initial_accounts: vec![],
         }
     }
 }
@@ -219,25 +223,59 @@ impl TryFrom<&NetworkAddress> for HostAndPort {
     type Error = anyhow::Error;
 
     fn try_from(address: &NetworkAddress) -> Result<Self, Self::Error> {
-        let socket_addr = address.to_socket_addrs()?.next().unwrap();
-        HostAndPort::from_str(&socket_addr.to_string())
+        // Read the host as given, DNS names have to stay unresolved
+        let host = match address.as_slice().first() {
+            Some(Protocol::Ip4(ip)) => DnsName::from_str(&ip.to_string())?,
+            Some(Protocol::Ip6(ip)) => DnsName::from_str(&ip.to_string())?,
+            Some(Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name)) => name.clone(),
+            _ => anyhow::bail!("Network address {} doesn't start with a host", address),
+        };
+        let port = address
+            .find_port()
+            .ok_or_else(|| anyhow::anyhow!("Network address {} has no TCP port", address))?;
+        Ok(HostAndPort { host, port })
+    }
+}
+
+impl std::fmt::Display for HostAndPort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if Ipv6Addr::from_str(self.host.as_ref()).is_ok() {
+            write!(f, "[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "{}:{}", self.host, self.port)
+        }
     }
 }
 
 impl FromStr for HostAndPort {
     type Err = anyhow::Error;
 
+    /// Parses `host:port`, where the host is an IPv4 address, a DNS name, or an IPv6 address in
+    /// brackets, e.g. `[2001:db8::1]:6180`
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<_> = s.split(':').collect();
-        if parts.len() != 2 {
-            Err(anyhow::Error::msg(
+        let (host, port) = s.rsplit_once(':').ok_or_else(|| {
+            anyhow::Error::msg(
                 "Invalid host and port, must be of the form 'host:port` e.g. '127.0.0.1:6180'",
-            ))
+            )
+        })?;
+        let host = if let Some(ip) = host.strip_prefix('[').and_then(|ip| ip.strip_suffix(']')) {
+            Ipv6Addr::from_str(ip)
+                .map_err(|e| anyhow::anyhow!("Invalid IPv6 address '{}': {}", ip, e))?;
+            ip
+        } else if host.contains(':') {
+            anyhow::bail!(
+                "IPv6 hosts must be in brackets, e.g. '[2001:db8::1]:6180', got '{}'",
+                s
+            );
         } else {
-            let host = DnsName::from_str(*parts.get(0).unwrap())?;
-            let port = u16::from_str(parts.get(1).unwrap())?;
-            Ok(HostAndPort { host, port })
+            host
+        };
+        let host = DnsName::from_str(host)?;
+        let port = u16::from_str(port)?;
+        if port == 0 {
+            anyhow::bail!("Port of '{}' must be non-zero", s);
         }
+        Ok(HostAndPort { host, port })
     }
 }
 
@@ -286,3 +324,61 @@ pub struct StringOperatorConfiguration {
     pub full_node_network_public_key: Option<String>,
     pub full_node_host: Option<HostAndPort>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(host_and_port: &str, host_protocol: Protocol) {
+        let key = x25519::PrivateKey::from([1; 32]).public_key();
+        let parsed = HostAndPort::from_str(host_and_port).unwrap();
+        assert_eq!(parsed.to_string(), host_and_port);
+
+        let address = parsed.as_network_address(key).unwrap();
+        assert_eq!(
+            address.as_slice(),
+            &[
+                host_protocol,
+                Protocol::Tcp(6180),
+                Protocol::NoiseIK(key),
+                Protocol::Handshake(HANDSHAKE_VERSION),
+            ]
+        );
+        assert_eq!(
+            HostAndPort::try_from(&address).unwrap().to_string(),
+            host_and_port
+        );
+    }
+
+    #[test]
+    fn test_host_and_port_round_trip() {
+        round_trip("127.0.0.1:6180", Protocol::Ip4(Ipv4Addr::new(127, 0, 0, 1)));
+        round_trip(
+            "[2001:db8::1]:6180",
+            Protocol::Ip6(Ipv6Addr::from_str("2001:db8::1").unwrap()),
+        );
+        round_trip(
+            "validator.example.com:6180",
+            Protocol::Dns(DnsName::from_str("validator.example.com").unwrap()),
+        );
+    }
+
+    #[test]
+    fn test_invalid_host_and_port() {
+        for host_and_port in [
+            "127.0.0.1",
+            "127.0.0.1:0",
+            "127.0.0.1:65536",
+            "2001:db8::1:6180",
+            "[2001:db8::zz]:6180",
+            "validator.example.com:",
+            ":6180",
+        ] {
+            assert!(
+                HostAndPort::from_str(host_and_port).is_err(),
+                "{} should be invalid",
+                host_and_port
+            );
+        }
+    }
+}
@@ -17,13 +17,16 @@ use aptos_config::config::{
 };
 use aptos_crypto::ed25519::Ed25519PublicKey;
 use aptos_temppath::TempPath;
-use aptos_types::{chain_id::ChainId, transaction::Transaction, waypoint::Waypoint};
+use aptos_types::{
+    account_address::AccountAddress, chain_id::ChainId, transaction::Transaction,
+    waypoint::Waypoint,
+};
 use aptos_vm::AptosVM;
 use aptosdb::AptosDB;
 use framework::ReleaseBundle;
 use std::convert::TryInto;
 use storage_interface::DbReaderWriter;
-use vm_genesis::Validator;
+use vm_genesis::{GenesisSummary, Validator};
 
 /// Holder object for all pieces needed to generate a genesis transaction
 #[derive(Clone)]
@@ -38,6 +41,8 @@ pub struct GenesisInfo {
     framework: ReleaseBundle,
     /// The genesis transaction, once it's been generated
     genesis: Option<Transaction>,
+    /// Summary of what the genesis transaction sets up, generated alongside it
+    summary: Option<GenesisSummary>,
 
     /// Whether to allow new validators to join the set after genesis
     pub allow_new_validators: bool,
@@ -60,6 +65,8 @@ pub struct GenesisInfo {
     pub voting_duration_secs: u64,
     /// Percent of current epoch's total voting power that can be added in this epoch.
     pub voting_power_increase_limit: u64,
+    /// Accounts created and funded at genesis, with their balance of the native coin
+    pub initial_accounts: Vec<(AccountAddress, u64)>,
 }
 
 impl GenesisInfo {
@@ -82,6 +89,7 @@ impl GenesisInfo {
             validators,
             framework,
             genesis: None,
+            summary: None,
             allow_new_validators: genesis_config.allow_new_validators,
             epoch_duration_secs: genesis_config.epoch_duration_secs,
             is_test: genesis_config.is_test,
@@ -93,20 +101,30 @@ impl GenesisInfo {
             rewards_apy_percentage: genesis_config.rewards_apy_percentage,
             voting_duration_secs: genesis_config.voting_duration_secs,
             voting_power_increase_limit: genesis_config.voting_power_increase_limit,
+            initial_accounts: genesis_config.initial_accounts.clone(),
         })
     }
 
     pub fn get_genesis(&mut self) -> &Transaction {
-        if let Some(ref genesis) = self.genesis {
-            genesis
-        } else {
-            self.genesis = Some(self.generate_genesis_txn());
-            self.genesis.as_ref().unwrap()
+        self.generate_if_missing();
+        self.genesis.as_ref().unwrap()
+    }
+
+    pub fn get_genesis_summary(&mut self) -> &GenesisSummary {
+        self.generate_if_missing();
+        self.summary.as_ref().unwrap()
+    }
+
+    fn generate_if_missing(&mut self) {
+        if self.genesis.is_none() || self.summary.is_none() {
+            let (genesis, summary) = self.generate_genesis_txn();
+            self.genesis = Some(genesis);
+            self.summary = Some(summary);
         }
     }
 
-    fn generate_genesis_txn(&self) -> Transaction {
-        vm_genesis::encode_genesis_transaction(
+    fn generate_genesis_txn(&self) -> (Transaction, GenesisSummary) {
+        vm_genesis::encode_genesis_transaction_with_summary(
             self.root_key.clone(),
             &self.validators,
             &self.framework,
@@ -123,6 +141,7 @@ impl GenesisInfo {
                 rewards_apy_percentage: self.rewards_apy_percentage,
                 voting_duration_secs: self.voting_duration_secs,
                 voting_power_increase_limit: self.voting_power_increase_limit,
+                initial_accounts: self.initial_accounts.clone(),
             },
         )
     }
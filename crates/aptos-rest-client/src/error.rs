@@ -29,6 +29,13 @@ enum Kind {
     InvalidProof,
     NeedSync,
     StateStore,
+    TableItemNotFound,
+    ResourceNotFound,
+    AccountNotFound,
+    VersionPruned {
+        requested: Option<u64>,
+        oldest_available: Option<u64>,
+    },
     Unknown,
 }
 
@@ -45,6 +52,10 @@ impl Error {
             | Kind::Decode
             | Kind::InvalidProof
             | Kind::StateStore
+            | Kind::TableItemNotFound
+            | Kind::ResourceNotFound
+            | Kind::AccountNotFound
+            | Kind::VersionPruned { .. }
             | Kind::Unknown => false,
         }
     }
@@ -53,6 +64,44 @@ impl Error {
         matches!(self.inner.kind, Kind::NeedSync)
     }
 
+    pub fn is_chain_id_mismatch(&self) -> bool {
+        matches!(self.inner.kind, Kind::ChainId)
+    }
+
+    pub fn is_table_item_not_found(&self) -> bool {
+        matches!(self.inner.kind, Kind::TableItemNotFound)
+    }
+
+    pub fn is_resource_not_found(&self) -> bool {
+        matches!(self.inner.kind, Kind::ResourceNotFound)
+    }
+
+    pub fn is_account_not_found(&self) -> bool {
+        matches!(self.inner.kind, Kind::AccountNotFound)
+    }
+
+    pub fn is_version_pruned(&self) -> bool {
+        matches!(self.inner.kind, Kind::VersionPruned { .. })
+    }
+
+    /// The ledger version that was requested, if this is a pruned version error
+    pub fn requested_version(&self) -> Option<u64> {
+        match self.inner.kind {
+            Kind::VersionPruned { requested, .. } => requested,
+            _ => None,
+        }
+    }
+
+    /// The oldest ledger version the node still has, if this is a pruned version error
+    pub fn oldest_available_version(&self) -> Option<u64> {
+        match self.inner.kind {
+            Kind::VersionPruned {
+                oldest_available, ..
+            } => oldest_available,
+            _ => None,
+        }
+    }
+
     //
     // Private Constructors
     //
@@ -102,6 +151,32 @@ impl Error {
         Self::new(Kind::NeedSync, Some(e))
     }
 
+    pub fn table_item_not_found<E: Into<BoxError>>(e: E) -> Self {
+        Self::new(Kind::TableItemNotFound, Some(e))
+    }
+
+    pub fn resource_not_found<E: Into<BoxError>>(e: E) -> Self {
+        Self::new(Kind::ResourceNotFound, Some(e))
+    }
+
+    pub fn account_not_found<E: Into<BoxError>>(e: E) -> Self {
+        Self::new(Kind::AccountNotFound, Some(e))
+    }
+
+    pub fn version_pruned<E: Into<BoxError>>(
+        requested: Option<u64>,
+        oldest_available: Option<u64>,
+        e: E,
+    ) -> Self {
+        Self::new(
+            Kind::VersionPruned {
+                requested,
+                oldest_available,
+            },
+            Some(e),
+        )
+    }
+
     pub fn unknown<E: Into<BoxError>>(e: E) -> Self {
         Self::new(Kind::Unknown, Some(e))
     }
@@ -139,3 +214,51 @@ impl From<serde_json::Error> for Error {
         Self::decode(e)
     }
 }
+
+/// Failure of submitting a transaction, keeping the error the node rejected it with, so
+/// callers can act on its [`AptosErrorCode`](aptos_api_types::AptosErrorCode)
+#[derive(Debug)]
+pub enum SubmitError {
+    /// The node rejected the transaction
+    Api(aptos_api_types::AptosError),
+    /// The transaction couldn't be submitted, e.g. the node wasn't reachable
+    Other(anyhow::Error),
+}
+
+impl SubmitError {
+    /// Whether the transaction was rejected for its sequence number being too old or too new
+    pub fn is_sequence_number_error(&self) -> bool {
+        use aptos_api_types::AptosErrorCode;
+        use aptos_types::vm_status::StatusCode;
+
+        match self {
+            SubmitError::Api(error) => {
+                matches!(error.error_code, AptosErrorCode::SequenceNumberTooOld)
+                    || error.vm_error_code == Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD as u64)
+                    || error.vm_error_code == Some(StatusCode::SEQUENCE_NUMBER_TOO_NEW as u64)
+            }
+            SubmitError::Other(_) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmitError::Api(error) => write!(
+                f,
+                "Transaction rejected ({:?}): {}",
+                error.error_code, error.message
+            ),
+            SubmitError::Other(error) => write!(f, "Transaction submission failed: {:#}", error),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+impl From<anyhow::Error> for SubmitError {
+    fn from(e: anyhow::Error) -> Self {
+        SubmitError::Other(e)
+    }
+}
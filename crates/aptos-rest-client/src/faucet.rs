@@ -1,11 +1,11 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{error::Error, Client, Result};
+use crate::{error::Error, Client, Result, DEFAULT_INTERVAL_DURATION};
 use aptos_types::transaction::SignedTransaction;
 use move_deps::move_core_types::account_address::AccountAddress;
 use reqwest::{Client as ReqwestClient, Url};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub struct FaucetClient {
     faucet_url: Url,
@@ -93,6 +93,58 @@ impl FaucetClient {
         Ok(())
     }
 
+    /// Fund an account with the given amount, and wait until its balance reflects the funding.
+    ///
+    /// The node behind the REST client may lag behind the one the faucet submitted to, so this
+    /// polls the balance until it's at least the prior balance plus `amount`, or `timeout`
+    /// elapses. Returns the new balance.
+    pub async fn fund_and_confirm(
+        &self,
+        address: AccountAddress,
+        amount: u64,
+        timeout: Duration,
+    ) -> Result<u64> {
+        // An account that doesn't exist yet, which the faucet creates, has no balance
+        let prior_balance = match self.get_balance(address).await {
+            Ok(balance) => balance,
+            Err(err)
+                if err.downcast_ref::<Error>().map_or(false, |err| {
+                    err.is_account_not_found() || err.is_resource_not_found()
+                }) =>
+            {
+                0
+            }
+            Err(err) => return Err(err),
+        };
+        self.fund(address, amount).await?;
+
+        let expected_balance = prior_balance.saturating_add(amount);
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            if let Ok(balance) = self.get_balance(address).await {
+                if balance >= expected_balance {
+                    return Ok(balance);
+                }
+            }
+            tokio::time::sleep(DEFAULT_INTERVAL_DURATION).await;
+        }
+
+        Err(anyhow::anyhow!(
+            "timeout waiting for the balance of {} to reach {}",
+            address,
+            expected_balance
+        ))
+    }
+
+    async fn get_balance(&self, address: AccountAddress) -> Result<u64> {
+        Ok(self
+            .rest_client
+            .get_account_balance(address)
+            .await?
+            .into_inner()
+            .get())
+    }
+
     // Create and fund an account.
     pub async fn mint(&self, address: AccountAddress, amount: u64) -> Result<()> {
         self.create_account(address).await?;
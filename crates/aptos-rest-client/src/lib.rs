@@ -7,52 +7,93 @@ pub mod faucet;
 
 pub use faucet::FaucetClient;
 use std::collections::BTreeMap;
+pub mod resource_history;
 pub mod response;
+pub mod sequence_number;
+pub use resource_history::ResourceAtVersion;
 pub use response::Response;
+pub use sequence_number::SequenceNumberProvider;
 pub mod state;
+#[cfg(test)]
+mod tests;
 pub mod types;
 
 pub use aptos_api_types::{
-    self, IndexResponse, MoveModuleBytecode, PendingTransaction, Transaction,
+    self, IndexResponse, MoveModuleBytecode, PendingTransaction, SubmitTransactionRequest,
+    Transaction,
 };
 pub use state::State;
-pub use types::{Account, Resource};
+pub use types::{
+    extract_table_handle, Account, AddressHex, EntryFunctionAbi, GasEstimation, HexBytes, Resource,
+    TableHandle, U128Str, U64Str,
+};
 
 use crate::aptos::{AptosVersion, Balance};
+use crate::error::{Error, SubmitError};
+use crate::resource_history::ResourceHistory;
 use anyhow::{anyhow, Result};
 use aptos_api_types::mime_types::BCS;
 use aptos_api_types::{
-    mime_types::BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, AptosError, BcsBlock, Block,
-    HexEncodedBytes, MoveModuleId, TransactionData, TransactionOnChainData, UserTransaction,
-    VersionedEvent,
+    mime_types::BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, AptosError, AptosErrorCode, BcsBlock,
+    Block, HexEncodedBytes, MoveModuleId, MoveType, TransactionData, TransactionOnChainData,
+    UserTransaction, VersionedEvent, X_APTOS_CHAIN_ID,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature, ED25519_SIGNATURE_LENGTH},
+    HashValue,
 };
-use aptos_crypto::HashValue;
+use aptos_infallible::RwLock;
+use aptos_logger::warn;
 use aptos_types::account_config::AccountResource;
 use aptos_types::contract_event::EventWithVersion;
+use aptos_types::event::EventKey;
+use aptos_types::on_chain_config::GasSchedule;
 use aptos_types::transaction::ExecutionStatus;
 use aptos_types::{
     account_address::AccountAddress,
     account_config::{NewBlockEvent, CORE_CODE_ADDRESS},
-    transaction::SignedTransaction,
+    chain_id::ChainId,
+    transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
 };
-use move_deps::move_core_types::language_storage::StructTag;
+use futures::Stream;
+use move_deps::move_core_types::language_storage::{StructTag, TypeTag};
 use poem_openapi::types::ParseFromJSON;
 use reqwest::header::ACCEPT;
-use reqwest::{header::CONTENT_TYPE, Client as ReqwestClient, StatusCode};
+use reqwest::{header::CONTENT_TYPE, Client as ReqwestClient, RequestBuilder, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::time::Duration;
+use std::{convert::TryFrom, sync::Arc, time::Duration};
 use types::{deserialize_from_prefixed_hex_string, deserialize_from_string};
 use url::Url;
+use uuid::Uuid;
 
 pub const USER_AGENT: &str = concat!("aptos-client-sdk-rust / ", env!("CARGO_PKG_VERSION"));
 pub const DEFAULT_VERSION_PATH_BASE: &str = "v1/";
+pub const X_APTOS_REQUEST_ID: &str = "X-Aptos-Request-Id";
+const DEFAULT_MAX_WAIT_DURATION: Duration = Duration::from_secs(60);
+const DEFAULT_INTERVAL_DURATION: Duration = Duration::from_millis(500);
+/// Safety factor for [`Client::estimate_max_gas_amount`], leaving room for the gas used to grow
+/// between the simulation and the execution
+pub const DEFAULT_GAS_SAFETY_FACTOR: f64 = 1.5;
+/// Key of the maximum max gas amount of a transaction in the on-chain gas schedule
+const MAXIMUM_NUMBER_OF_GAS_UNITS: &str = "txn.maximum_number_of_gas_units";
+/// Key of the minimum gas unit price of a transaction in the on-chain gas schedule
+const MIN_PRICE_PER_GAS_UNIT: &str = "txn.min_price_per_gas_unit";
+/// Page size the API uses for `/transactions` when no limit is given
+const DEFAULT_TRANSACTIONS_PAGE_SIZE: u16 = 25;
 
 #[derive(Clone, Debug)]
 pub struct Client {
     inner: ReqwestClient,
     base_url: Url,
     version_path_base: String,
+    /// Chain ID of the node, learned from the first response and checked against
+    /// every response after that.
+    chain_id: Arc<RwLock<Option<u8>>>,
+    /// Chain ID the node is expected to be on, checked against the first response.
+    expected_chain_id: Option<u8>,
+    /// Request ID sent with every request instead of a fresh one per request.
+    request_id: Option<Uuid>,
 }
 
 impl Client {
@@ -78,10 +119,19 @@ impl Client {
             }
         };
 
+        Self::from_parts(inner, base_url, version_path_base)
+    }
+
+    /// A client without an expected chain ID or a fixed request ID, which every other
+    /// constructor goes through so they all start from the same state.
+    fn from_parts(inner: ReqwestClient, base_url: Url, version_path_base: String) -> Self {
         Self {
             inner,
             base_url,
             version_path_base,
+            chain_id: Arc::new(RwLock::new(None)),
+            expected_chain_id: None,
+            request_id: None,
         }
     }
 
@@ -95,15 +145,91 @@ impl Client {
         Ok(self)
     }
 
+    /// Returns a client that sends `request_id` as the `X-Aptos-Request-Id` of all its
+    /// requests, e.g. to tie together the requests of one operation in the node's logs.
+    /// Otherwise every request gets a new random UUID.
+    pub fn with_request_id(&self, request_id: Uuid) -> Self {
+        Self {
+            request_id: Some(request_id),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client that fails if the node isn't on `chain_id`, e.g. so transactions
+    /// signed for one network aren't submitted to another. The chain ID is checked on the
+    /// first response, and every response after that is checked to be on the same chain.
+    pub fn with_expected_chain_id(&self, chain_id: ChainId) -> Self {
+        Self {
+            chain_id: Arc::new(RwLock::new(None)),
+            expected_chain_id: Some(chain_id.id()),
+            ..self.clone()
+        }
+    }
+
     fn build_path(&self, path: &str) -> Result<Url> {
         Ok(self.base_url.join(&self.version_path_base)?.join(path)?)
     }
 
+    /// The URL of a resource, with the `<`, `>` and spaces of generic types percent-encoded
+    fn account_resource_url(&self, address: AccountAddress, resource_type: &str) -> Result<Url> {
+        self.build_path(&format!("accounts/{}/resource/{}", address, resource_type))
+    }
+
+    fn get_request(&self, url: Url) -> RequestBuilder {
+        self.with_request_id_header(self.inner.get(url))
+    }
+
+    fn post_request(&self, url: Url) -> RequestBuilder {
+        self.with_request_id_header(self.inner.post(url))
+    }
+
+    fn with_request_id_header(&self, request: RequestBuilder) -> RequestBuilder {
+        let request_id = self.request_id.unwrap_or_else(Uuid::new_v4);
+        request.header(X_APTOS_REQUEST_ID, request_id.to_string())
+    }
+
+    /// Sends the request, and attaches its request ID to the response so errors and
+    /// [`Response::request_id`] can report it.
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        let request = request.build()?;
+        let request_id = request.headers().get(X_APTOS_REQUEST_ID).cloned();
+        let mut response = self.inner.execute(request).await?;
+        self.check_expected_chain_id(&response)?;
+        if let Some(request_id) = request_id {
+            response
+                .headers_mut()
+                .entry(X_APTOS_REQUEST_ID)
+                .or_insert(request_id);
+        }
+        Ok(response)
+    }
+
     pub async fn get_aptos_version(&self) -> Result<Response<AptosVersion>> {
         self.get_resource::<AptosVersion>(CORE_CODE_ADDRESS, "0x1::version::Version")
             .await
     }
 
+    /// Fetches the major version of the framework the node runs, from `0x1::version::Version`
+    pub async fn get_framework_version(&self) -> Result<Response<u64>> {
+        Ok(self
+            .get_aptos_version()
+            .await?
+            .map(|version| *version.major.inner()))
+    }
+
+    /// Fails if the node runs a framework older than major version `min_major`
+    pub async fn check_framework_compatibility(&self, min_major: u64) -> Result<()> {
+        let major = self.get_framework_version().await?.into_inner();
+        if major < min_major {
+            return Err(anyhow!(
+                "Node runs framework version {}, but at least version {} is required",
+                major,
+                min_major
+            ));
+        }
+        Ok(())
+    }
+
     pub async fn get_block_by_height(
         &self,
         height: u64,
@@ -129,6 +255,32 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Pages through the transactions of the block at `height` without loading them all at
+    /// once. `start` is an offset into the block, and a page never extends past the block's
+    /// last version. An empty page is returned once `start` is past the end of the block.
+    pub async fn get_block_transactions_bcs(
+        &self,
+        height: u64,
+        start: Option<u64>,
+        limit: Option<u16>,
+    ) -> Result<Response<Vec<TransactionOnChainData>>> {
+        let (block, state) = self
+            .get_block_by_height_bcs(height, false)
+            .await?
+            .into_parts();
+        let num_transactions = block.last_version - block.first_version + 1;
+        let start = start.unwrap_or(0);
+        if start >= num_transactions {
+            return Ok(Response::new(vec![], state));
+        }
+
+        let limit = limit
+            .unwrap_or(DEFAULT_TRANSACTIONS_PAGE_SIZE)
+            .min(u16::try_from(num_transactions - start).unwrap_or(u16::MAX));
+        self.get_transactions_bcs(Some(block.first_version + start), Some(limit))
+            .await
+    }
+
     pub async fn get_block_by_version(
         &self,
         version: u64,
@@ -192,6 +344,24 @@ impl Client {
         self.get(self.build_path("")?).await
     }
 
+    /// Returns the chain ID of the node. It is only fetched from the node if no
+    /// response has been received yet.
+    pub async fn get_chain_id(&self) -> Result<u8> {
+        if let Some(chain_id) = *self.chain_id.read() {
+            return Ok(chain_id);
+        }
+        Ok(self.get_ledger_information().await?.into_inner().chain_id)
+    }
+
+    /// Checks that the node is on `chain_id`.
+    pub async fn verify_chain_id(&self, chain_id: ChainId) -> Result<()> {
+        let received = self.get_chain_id().await?;
+        if received != chain_id.id() {
+            return Err(Error::chain_id(chain_id.id(), received).into());
+        }
+        Ok(())
+    }
+
     pub async fn get_ledger_information(&self) -> Result<Response<State>> {
         let response = self.get_index().await?.map(|r| State {
             chain_id: r.chain_id,
@@ -206,6 +376,19 @@ impl Client {
         Ok(response)
     }
 
+    /// Returns the `(oldest, newest)` ledger versions the node serves, both inclusive.
+    /// Versions older than `oldest` have been pruned.
+    pub async fn get_served_version_range(&self) -> Result<(u64, u64)> {
+        let state = self.get_ledger_information().await?.into_inner();
+        Ok((state.oldest_ledger_version, state.version))
+    }
+
+    /// Whether the node currently serves `version`, i.e. it's neither pruned nor in the future.
+    pub async fn contains_version(&self, version: u64) -> Result<bool> {
+        let (oldest, newest) = self.get_served_version_range().await?;
+        Ok((oldest..=newest).contains(&version))
+    }
+
     pub async fn simulate(
         &self,
         txn: &SignedTransaction,
@@ -213,17 +396,79 @@ impl Client {
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.build_path("transactions/simulate")?;
 
-        let response = self
-            .inner
-            .post(url)
+        let request = self
+            .post_request(url)
             .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
-            .body(txn_payload)
-            .send()
-            .await?;
+            .body(txn_payload);
+        let response = self.send(request).await?;
 
         self.json(response).await
     }
 
+    /// A max gas amount for `txn`: the gas its simulation used times `safety_factor`, e.g.
+    /// [`DEFAULT_GAS_SAFETY_FACTOR`], capped at the node's maximum number of gas units.
+    ///
+    /// `txn` is simulated with the node's maximum number of gas units as its max gas amount,
+    /// so its own max gas amount doesn't matter.  Like all simulations, `txn` mustn't have a
+    /// valid signature, see [`simulation_transaction`].
+    pub async fn estimate_max_gas_amount(
+        &self,
+        txn: &SignedTransaction,
+        safety_factor: f64,
+    ) -> Result<u64> {
+        if !safety_factor.is_finite() || safety_factor < 1.0 {
+            return Err(anyhow!(
+                "Gas safety factor must be at least 1, got {}",
+                safety_factor
+            ));
+        }
+
+        let gas_schedule: GasSchedule = self
+            .get_account_resource_bcs(CORE_CODE_ADDRESS, "0x1::gas_schedule::GasSchedule")
+            .await?
+            .into_inner();
+        let maximum_gas_units = gas_schedule
+            .to_btree_map()
+            .get(MAXIMUM_NUMBER_OF_GAS_UNITS)
+            .copied()
+            .ok_or_else(|| {
+                anyhow!(
+                    "The gas schedule has no {} entry",
+                    MAXIMUM_NUMBER_OF_GAS_UNITS
+                )
+            })?;
+
+        // Simulate with the most gas allowed, so the transaction's own max doesn't run out
+        let unbounded_txn = SignedTransaction::new_with_authenticator(
+            RawTransaction::new(
+                txn.sender(),
+                txn.sequence_number(),
+                txn.payload().clone(),
+                maximum_gas_units,
+                txn.gas_unit_price(),
+                txn.expiration_timestamp_secs(),
+                txn.chain_id(),
+            ),
+            txn.authenticator(),
+        );
+        let simulated = self
+            .simulate(&unbounded_txn)
+            .await?
+            .into_inner()
+            .pop()
+            .ok_or_else(|| anyhow!("The node returned no simulated transaction"))?;
+        if !simulated.info.success {
+            return Err(anyhow!(
+                "The simulated transaction failed: {}",
+                simulated.info.vm_status
+            ));
+        }
+
+        // Float to int casts saturate
+        let estimate = (simulated.info.gas_used.0 as f64 * safety_factor).ceil() as u64;
+        Ok(estimate.min(maximum_gas_units))
+    }
+
     pub async fn simulate_bcs(
         &self,
         txn: &SignedTransaction,
@@ -231,14 +476,12 @@ impl Client {
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.build_path("transactions/simulate")?;
 
-        let response = self
-            .inner
-            .post(url)
+        let request = self
+            .post_request(url)
             .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
             .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
-            .await?;
+            .body(txn_payload);
+        let response = self.send(request).await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
         Ok(response.and_then(|bytes| bcs::from_bytes(&bytes))?)
@@ -248,13 +491,50 @@ impl Client {
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.build_path("transactions")?;
 
-        let response = self
-            .inner
-            .post(url)
+        let request = self
+            .post_request(url)
             .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
-            .body(txn_payload)
-            .send()
-            .await?;
+            .body(txn_payload);
+        let response = self.send(request).await?;
+
+        self.json(response).await
+    }
+
+    /// Like [`Client::submit`], but keeps the [`AptosError`] the node rejected the transaction
+    /// with, e.g. to resynchronize sequence numbers on [`SubmitError::is_sequence_number_error`]
+    pub async fn try_submit(
+        &self,
+        txn: &SignedTransaction,
+    ) -> Result<Response<PendingTransaction>, SubmitError> {
+        let txn_payload = bcs::to_bytes(txn).map_err(anyhow::Error::from)?;
+        let url = self.build_path("transactions")?;
+
+        let request = self
+            .post_request(url)
+            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+            .body(txn_payload);
+        let response = self.send(request).await?;
+
+        if !response.status().is_success() {
+            let body = response.json().await.map_err(anyhow::Error::from)?;
+            return Err(match AptosError::parse_from_json(Some(body)) {
+                Ok(error) => SubmitError::Api(error),
+                Err(error) => SubmitError::Other(anyhow!("Request failed: {:?}", error)),
+            });
+        }
+
+        Ok(self.json(response).await?)
+    }
+
+    /// Submits a transaction given as a JSON request, e.g. one built and signed elsewhere.
+    /// Prefer [`Client::submit`] when a [`SignedTransaction`] is at hand.
+    pub async fn submit_json(
+        &self,
+        request: SubmitTransactionRequest,
+    ) -> Result<Response<PendingTransaction>> {
+        let url = self.build_path("transactions")?;
+
+        let response = self.send(self.post_request(url).json(&request)).await?;
 
         self.json(response).await
     }
@@ -263,14 +543,12 @@ impl Client {
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.build_path("transactions")?;
 
-        let response = self
-            .inner
-            .post(url)
+        let request = self
+            .post_request(url)
             .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
             .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
-            .await?;
+            .body(txn_payload);
+        let response = self.send(request).await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
         Ok(response.and_then(|bytes| bcs::from_bytes(&bytes))?)
@@ -281,6 +559,24 @@ impl Client {
         self.wait_for_signed_transaction(txn).await
     }
 
+    /// Like `submit_and_wait`, but with a caller provided `timeout` for the
+    /// whole wait and `poll_interval` between lookups of the transaction.
+    pub async fn submit_and_wait_with(
+        &self,
+        txn: &SignedTransaction,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<Response<Transaction>> {
+        self.submit(txn).await?;
+        self.wait_for_transaction_by_hash_with_timeout(
+            txn.clone().committed_hash(),
+            txn.expiration_timestamp_secs(),
+            timeout,
+            poll_interval,
+        )
+        .await
+    }
+
     pub async fn submit_and_wait_bcs(
         &self,
         txn: &SignedTransaction,
@@ -334,14 +630,28 @@ impl Client {
         hash: HashValue,
         expiration_timestamp_secs: u64,
     ) -> Result<Response<Transaction>> {
-        const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
-        const DEFAULT_DELAY: Duration = Duration::from_millis(500);
+        self.wait_for_transaction_by_hash_with_timeout(
+            hash,
+            expiration_timestamp_secs,
+            DEFAULT_MAX_WAIT_DURATION,
+            DEFAULT_INTERVAL_DURATION,
+        )
+        .await
+    }
 
+    pub async fn wait_for_transaction_by_hash_with_timeout(
+        &self,
+        hash: HashValue,
+        expiration_timestamp_secs: u64,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<Response<Transaction>> {
         let start = std::time::Instant::now();
-        while start.elapsed() < DEFAULT_TIMEOUT {
+        while start.elapsed() < timeout {
             let resp = self.get_transaction_by_hash_inner(hash).await?;
             if resp.status() != StatusCode::NOT_FOUND {
                 let txn_resp: Response<Transaction> = self.json(resp).await?;
+                let request_id = txn_resp.request_id();
                 let (transaction, state) = txn_resp.into_parts();
 
                 if !transaction.is_pending() {
@@ -351,14 +661,14 @@ impl Client {
                             transaction.vm_status()
                         ));
                     }
-                    return Ok(Response::new(transaction, state));
+                    return Ok(Response::new(transaction, state).with_request_id(request_id));
                 }
                 if expiration_timestamp_secs <= state.timestamp_usecs / 1_000_000 {
                     return Err(anyhow!("transaction expired"));
                 }
             }
 
-            tokio::time::sleep(DEFAULT_DELAY).await;
+            tokio::time::sleep(poll_interval).await;
         }
 
         Err(anyhow!("timeout"))
@@ -370,11 +680,8 @@ impl Client {
         expiration_timestamp_secs: u64,
     ) -> Result<Response<TransactionOnChainData>, (Option<Response<TransactionData>>, anyhow::Error)>
     {
-        const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
-        const DEFAULT_DELAY: Duration = Duration::from_millis(500);
-
         let start = std::time::Instant::now();
-        while start.elapsed() < DEFAULT_TIMEOUT {
+        while start.elapsed() < DEFAULT_MAX_WAIT_DURATION {
             let resp = self
                 .get_transaction_by_hash_bcs_inner(hash)
                 .await
@@ -389,6 +696,7 @@ impl Client {
                 let resp = resp
                     .and_then(|bytes| bcs::from_bytes(&bytes))
                     .map_err(|err| (None, err.into()))?;
+                let request_id = resp.request_id();
                 let (maybe_pending_txn, state) = resp.into_parts();
 
                 // If we have a committed transaction, determine if it failed or not
@@ -397,9 +705,14 @@ impl Client {
 
                     // The user can handle the error
                     return match status {
-                        ExecutionStatus::Success => Ok(Response::new(txn, state)),
+                        ExecutionStatus::Success => {
+                            Ok(Response::new(txn, state).with_request_id(request_id))
+                        }
                         _ => Err((
-                            Some(Response::new(TransactionData::OnChain(txn), state)),
+                            Some(
+                                Response::new(TransactionData::OnChain(txn), state)
+                                    .with_request_id(request_id),
+                            ),
                             anyhow!("Transaction failed"),
                         )),
                     };
@@ -410,13 +723,13 @@ impl Client {
                     <= Duration::from_micros(state.timestamp_usecs)
                 {
                     return Err((
-                        Some(Response::new(maybe_pending_txn, state)),
+                        Some(Response::new(maybe_pending_txn, state).with_request_id(request_id)),
                         anyhow!("Transaction expired"),
                     ));
                 }
             }
 
-            tokio::time::sleep(DEFAULT_DELAY).await;
+            tokio::time::sleep(DEFAULT_INTERVAL_DURATION).await;
         }
 
         return Err((None, anyhow!("Timed out waiting for transaction")));
@@ -429,7 +742,7 @@ impl Client {
     ) -> Result<Response<Vec<Transaction>>> {
         let url = self.build_path("transactions")?;
 
-        let mut request = self.inner.get(url);
+        let mut request = self.get_request(url);
         if let Some(start) = start {
             request = request.query(&[("start", start)])
         }
@@ -438,7 +751,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
 
         self.json(response).await
     }
@@ -472,13 +785,13 @@ impl Client {
         hash: HashValue,
     ) -> Result<reqwest::Response> {
         let url = self.build_path(&format!("transactions/by_hash/{}", hash.to_hex_literal()))?;
-        let response = self.inner.get(url).header(ACCEPT, BCS).send().await?;
+        let response = self.send(self.get_request(url).header(ACCEPT, BCS)).await?;
         Ok(response)
     }
 
     async fn get_transaction_by_hash_inner(&self, hash: HashValue) -> Result<reqwest::Response> {
         let url = self.build_path(&format!("transactions/by_hash/{}", hash.to_hex_literal()))?;
-        Ok(self.inner.get(url).send().await?)
+        self.send(self.get_request(url)).await
     }
 
     pub async fn get_transaction_by_version(&self, version: u64) -> Result<Response<Transaction>> {
@@ -497,7 +810,7 @@ impl Client {
 
     async fn get_transaction_by_version_inner(&self, version: u64) -> Result<reqwest::Response> {
         let url = self.build_path(&format!("transactions/by_version/{}", version))?;
-        Ok(self.inner.get(url).send().await?)
+        self.send(self.get_request(url)).await
     }
 
     pub async fn get_account_transactions(
@@ -508,7 +821,7 @@ impl Client {
     ) -> Result<Response<Vec<Transaction>>> {
         let url = self.build_path(&format!("accounts/{}/transactions", address))?;
 
-        let mut request = self.inner.get(url);
+        let mut request = self.get_request(url);
         if let Some(start) = start {
             request = request.query(&[("start", start)])
         }
@@ -517,7 +830,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
 
         self.json(response).await
     }
@@ -539,7 +852,7 @@ impl Client {
     ) -> Result<Response<Vec<Resource>>> {
         let url = self.build_path(&format!("accounts/{}/resources", address))?;
 
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.get_request(url)).await?;
 
         self.json(response).await
     }
@@ -563,7 +876,7 @@ impl Client {
             address, version
         ))?;
 
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.get_request(url)).await?;
 
         self.json(response).await
     }
@@ -588,14 +901,47 @@ impl Client {
         })
     }
 
+    /// Like [`Client::get_resource`], for a generic resource type, e.g. `module` `0x1::coin`,
+    /// `struct_name` `CoinStore` and `type_params` `["0x1::aptos_coin::AptosCoin"]`.  Type
+    /// params can be generic themselves.
+    pub async fn get_resource_with_type_params<T: DeserializeOwned>(
+        &self,
+        address: AccountAddress,
+        module: &str,
+        struct_name: &str,
+        type_params: &[&str],
+    ) -> Result<Response<T>> {
+        self.get_resource(
+            address,
+            &resource_type_with_params(module, struct_name, type_params),
+        )
+        .await
+    }
+
+    /// Like [`Client::get_resource`], deserializing the BCS of the resource instead of its JSON
+    /// representation, so `T` has to declare all the fields of the Move struct, in order.  See
+    /// [`U64Str`] for field types that work with both.
+    pub async fn get_resource_bcs<T: DeserializeOwned>(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Response<T>> {
+        let url = self.account_resource_url(address, resource_type)?;
+        let response = self.get_bcs(url).await?;
+        response.and_then(|bytes| {
+            bcs::from_bytes(&bytes)
+                .map_err(|e| anyhow!("deserialize {} failed: {}", resource_type, e))
+        })
+    }
+
     pub async fn get_account_resource(
         &self,
         address: AccountAddress,
         resource_type: &str,
     ) -> Result<Response<Option<Resource>>> {
-        let url = self.build_path(&format!("accounts/{}/resource/{}", address, resource_type))?;
+        let url = self.account_resource_url(address, resource_type)?;
 
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.get_request(url)).await?;
         self.json(response).await
     }
 
@@ -620,17 +966,65 @@ impl Client {
             address, resource_type, version
         ))?;
 
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.get_request(url)).await?;
         self.json(response).await
     }
 
+    /// Streams the resource every `step` versions from `start_version` to `end_version`, both
+    /// inclusive, with `None` for versions at which the account didn't have it.
+    ///
+    /// If the node pruned `start_version`, history starts at the oldest version it has, and the
+    /// first item is marked as [`truncated`](ResourceAtVersion::truncated).  A `step` of 0 is
+    /// treated as 1.
+    pub fn resource_history(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        start_version: u64,
+        end_version: u64,
+        step: u64,
+    ) -> impl Stream<Item = Result<ResourceAtVersion>> {
+        ResourceHistory::new(
+            self.clone(),
+            address,
+            resource_type,
+            start_version,
+            end_version,
+            step,
+        )
+        .samples()
+    }
+
+    /// Like [`resource_history`](Self::resource_history), but only streams the resource at
+    /// `start_version` and at the exact versions it changed at.  Versions are sampled every
+    /// `step` versions, and the ones between two different samples are bisected for the change,
+    /// so changes that are reverted between two samples are missed.
+    pub fn resource_history_adaptive(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        start_version: u64,
+        end_version: u64,
+        step: u64,
+    ) -> impl Stream<Item = Result<ResourceAtVersion>> {
+        ResourceHistory::new(
+            self.clone(),
+            address,
+            resource_type,
+            start_version,
+            end_version,
+            step,
+        )
+        .changes()
+    }
+
     pub async fn get_account_modules(
         &self,
         address: AccountAddress,
     ) -> Result<Response<Vec<MoveModuleBytecode>>> {
         let url = self.build_path(&format!("accounts/{}/modules", address))?;
 
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.get_request(url)).await?;
         self.json(response).await
     }
 
@@ -652,6 +1046,43 @@ impl Client {
         self.get(url).await
     }
 
+    /// Resolves the signature of an entry function from the ABI of the module that defines it.
+    pub async fn get_entry_function_abi(
+        &self,
+        module: MoveModuleId,
+        function: &str,
+    ) -> Result<EntryFunctionAbi> {
+        let bytecode = self
+            .get_account_module(module.address.into(), module.name.as_str())
+            .await?
+            .into_inner()
+            .try_parse_abi()?;
+        let abi = bytecode
+            .abi
+            .ok_or_else(|| anyhow!("Failed to parse the ABI of module {}", module))?;
+        let function = abi
+            .exposed_functions
+            .into_iter()
+            .find(|f| f.name.as_str() == function)
+            .ok_or_else(|| anyhow!("Function {} not found in module {}", function, module))?;
+        if !function.is_entry {
+            return Err(anyhow!(
+                "Function {} in module {} is not an entry function",
+                function.name,
+                module
+            ));
+        }
+
+        Ok(EntryFunctionAbi {
+            type_param_count: function.generic_type_params.len(),
+            params: function
+                .params
+                .into_iter()
+                .skip_while(MoveType::is_signer)
+                .collect(),
+        })
+    }
+
     pub async fn get_account_module_bcs(
         &self,
         address: AccountAddress,
@@ -675,7 +1106,7 @@ impl Client {
             struct_tag,
             field_name
         ))?;
-        let mut request = self.inner.get(url);
+        let mut request = self.get_request(url);
         if let Some(start) = start {
             request = request.query(&[("start", start)])
         }
@@ -684,7 +1115,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
         self.json(response).await
     }
 
@@ -707,6 +1138,53 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Gets the events of the event handle created by `address` with `creation_number`, without
+    /// looking the handle up in a resource first.
+    ///
+    /// The creation number and address make up the event key, so this asks for the events of
+    /// that key.
+    pub async fn get_events_by_creation_number(
+        &self,
+        address: AccountAddress,
+        creation_number: u64,
+        start: Option<u64>,
+        limit: Option<u16>,
+    ) -> Result<Response<Vec<VersionedEvent>>> {
+        let url = self.events_by_creation_number_url(address, creation_number)?;
+        let mut request = self.get_request(url);
+        if let Some(start) = start {
+            request = request.query(&[("start", start)])
+        }
+
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)])
+        }
+
+        let response = self.send(request).await?;
+        self.json(response).await
+    }
+
+    pub async fn get_events_by_creation_number_bcs(
+        &self,
+        address: AccountAddress,
+        creation_number: u64,
+        start: Option<u64>,
+        limit: Option<u16>,
+    ) -> Result<Response<Vec<EventWithVersion>>> {
+        let url = self.events_by_creation_number_url(address, creation_number)?;
+        let response = self.get_bcs_with_page(url, start, limit).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
+    fn events_by_creation_number_url(
+        &self,
+        address: AccountAddress,
+        creation_number: u64,
+    ) -> Result<Url> {
+        let event_key = EventKey::new(creation_number, address);
+        self.build_path(&format!("events/{:#x}", event_key))
+    }
+
     pub async fn get_new_block_events(
         &self,
         start: Option<u64>,
@@ -788,13 +1266,62 @@ impl Client {
             "key": json!(key),
         });
 
-        let response = self.inner.post(url).json(&data).send().await?;
+        let response = self.send(self.post_request(url).json(&data)).await?;
         self.json(response).await
     }
 
+    /// Fetches a table item and BCS decodes it. `key` is encoded as JSON, in the API's format
+    /// for `key_type_tag`, e.g. as a string for a `u64` or `u128`.
+    ///
+    /// Fails with an [`Error`] for which [`Error::is_table_item_not_found`] holds if the table
+    /// doesn't contain `key`.
+    pub async fn get_table_item_bcs<K: Serialize, V: DeserializeOwned>(
+        &self,
+        table_handle: TableHandle,
+        key_type_tag: &TypeTag,
+        value_type_tag: &TypeTag,
+        key: &K,
+        ledger_version: Option<u64>,
+    ) -> Result<Response<V>> {
+        let response = self
+            .get_table_item_raw(
+                table_handle,
+                key_type_tag,
+                value_type_tag,
+                key,
+                ledger_version,
+            )
+            .await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
+    /// Fetches the BCS encoded bytes of a table item, see [`Client::get_table_item_bcs`].
+    pub async fn get_table_item_raw<K: Serialize>(
+        &self,
+        table_handle: TableHandle,
+        key_type_tag: &TypeTag,
+        value_type_tag: &TypeTag,
+        key: &K,
+        ledger_version: Option<u64>,
+    ) -> Result<Response<bytes::Bytes>> {
+        let url = self.build_path(&format!("tables/{}/item", table_handle))?;
+        let data = json!({
+            "key_type": MoveType::from(key_type_tag.clone()),
+            "value_type": MoveType::from(value_type_tag.clone()),
+            "key": json!(key),
+        });
+
+        let mut request = self.post_request(url).header(ACCEPT, BCS).json(&data);
+        if let Some(ledger_version) = ledger_version {
+            request = request.query(&[("ledger_version", ledger_version)]);
+        }
+        let response = self.send(request).await?;
+        self.check_and_parse_bcs_response(response).await
+    }
+
     pub async fn get_account(&self, address: AccountAddress) -> Result<Response<Account>> {
         let url = self.build_path(&format!("accounts/{}", address))?;
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.get_request(url)).await?;
         self.json(response).await
     }
 
@@ -807,6 +1334,53 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Resolves the address of the account controlled by `auth_key`, following key rotations
+    /// recorded in `0x1::account::OriginatingAddress`, and falling back to the address derived
+    /// from `auth_key` if no account rotated to it.
+    ///
+    /// With `must_exist`, fails if the resolved account hasn't been created yet, otherwise no
+    /// account is looked up.
+    pub async fn lookup_address(
+        &self,
+        auth_key: AuthenticationKey,
+        must_exist: bool,
+    ) -> Result<Response<AccountAddress>> {
+        let derived_address = auth_key.derived_address();
+        let (originating_address, state) = self
+            .get_account_resource(CORE_CODE_ADDRESS, "0x1::account::OriginatingAddress")
+            .await?
+            .into_parts();
+        let originating_address = originating_address
+            .ok_or_else(|| anyhow!("0x1::account::OriginatingAddress not found"))?;
+        let address_map = extract_table_handle(&originating_address.data, "address_map")?;
+
+        let address = match self
+            .get_table_item_bcs(
+                address_map,
+                &TypeTag::Address,
+                &TypeTag::Address,
+                &derived_address.to_hex_literal(),
+                Some(state.version),
+            )
+            .await
+        {
+            Ok(response) => response.into_inner(),
+            Err(err)
+                if err
+                    .downcast_ref::<Error>()
+                    .map_or(false, Error::is_table_item_not_found) =>
+            {
+                derived_address
+            }
+            Err(err) => return Err(err),
+        };
+
+        if must_exist {
+            self.get_account(address).await?;
+        }
+        Ok(Response::new(address, state))
+    }
+
     pub async fn set_failpoint(&self, name: String, actions: String) -> Result<String> {
         let mut base = self.build_path("set_failpoint")?;
         let url = base
@@ -814,7 +1388,7 @@ impl Client {
             .append_pair("name", &name)
             .append_pair("actions", &actions)
             .finish();
-        let response = self.inner.get(url.clone()).send().await?;
+        let response = self.send(self.get_request(url.clone())).await?;
 
         if !response.status().is_success() {
             let error_response = AptosError::parse_from_json(Some(response.json().await?));
@@ -832,31 +1406,162 @@ impl Client {
         response: reqwest::Response,
     ) -> Result<(reqwest::Response, State)> {
         if !response.status().is_success() {
+            // Error responses still carry the ledger state, which tells how far back the
+            // node's history goes
+            let oldest_available = State::from_headers(response.headers())
+                .ok()
+                .map(|state| state.oldest_ledger_version);
+            let requested = requested_version(response.url());
+            let request_id = response
+                .headers()
+                .get(X_APTOS_REQUEST_ID)
+                .and_then(|request_id| request_id.to_str().ok())
+                .unwrap_or("unknown")
+                .to_string();
             let error_response = AptosError::parse_from_json(Some(response.json().await?));
-            return Err(anyhow::anyhow!("Request failed: {:?}", error_response));
+            match error_response {
+                Ok(AptosError {
+                    message,
+                    error_code: AptosErrorCode::TableItemNotFound,
+                    ..
+                }) => {
+                    return Err(Error::table_item_not_found(format!(
+                        "{} (request ID: {})",
+                        message, request_id
+                    ))
+                    .into())
+                }
+                Ok(AptosError {
+                    message,
+                    error_code: AptosErrorCode::ResourceNotFound,
+                    ..
+                }) => {
+                    return Err(Error::resource_not_found(format!(
+                        "{} (request ID: {})",
+                        message, request_id
+                    ))
+                    .into())
+                }
+                Ok(AptosError {
+                    message,
+                    error_code: AptosErrorCode::AccountNotFound,
+                    ..
+                }) => {
+                    return Err(Error::account_not_found(format!(
+                        "{} (request ID: {})",
+                        message, request_id
+                    ))
+                    .into())
+                }
+                Ok(AptosError {
+                    message,
+                    error_code: AptosErrorCode::VersionPruned,
+                    ..
+                }) => {
+                    return Err(Error::version_pruned(
+                        requested,
+                        oldest_available,
+                        format!("{} (request ID: {})", message, request_id),
+                    )
+                    .into())
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Request failed (request ID: {}): {:?}",
+                        request_id,
+                        error_response
+                    ))
+                }
+            }
         }
         let state = State::from_headers(response.headers())?;
+        self.check_chain_id(&state)?;
 
         Ok((response, state))
     }
 
+    /// Caches the chain ID of the first response, and fails if a later response
+    /// comes from a different chain, which means the client is pointed at the
+    /// wrong network. The first chain ID is kept, so every response from another
+    /// chain fails, not only the first one. With an expected chain ID, the first
+    /// response has to be on that chain too.
+    fn check_chain_id(&self, state: &State) -> Result<()> {
+        let mut chain_id = self.chain_id.write();
+        match chain_id.or(self.expected_chain_id) {
+            Some(expected) if expected != state.chain_id => {
+                Err(Error::chain_id(expected, state.chain_id).into())
+            }
+            _ => {
+                *chain_id = Some(state.chain_id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks the chain ID header of responses against the expected chain ID, until a response
+    /// has been received and cached by [`Client::check_chain_id`]. Responses without the header,
+    /// e.g. from a proxy stripping it, can't be checked, so they only log a warning.
+    fn check_expected_chain_id(&self, response: &reqwest::Response) -> Result<()> {
+        let expected = match self.expected_chain_id {
+            Some(expected) if self.chain_id.read().is_none() => expected,
+            _ => return Ok(()),
+        };
+        let received = response
+            .headers()
+            .get(X_APTOS_CHAIN_ID)
+            .and_then(|header| header.to_str().ok())
+            .and_then(|chain_id| chain_id.parse::<u8>().ok());
+        match received {
+            Some(received) if received != expected => {
+                Err(Error::chain_id(expected, received).into())
+            }
+            Some(_) => Ok(()),
+            None => {
+                warn!(
+                    "Response from {} has no {} header, unable to check it's from chain {}",
+                    response.url(),
+                    X_APTOS_CHAIN_ID,
+                    expected
+                );
+                Ok(())
+            }
+        }
+    }
+
     async fn json<T: serde::de::DeserializeOwned>(
         &self,
         response: reqwest::Response,
     ) -> Result<Response<T>> {
         let (response, state) = self.check_response(response).await?;
+        let request_id = response_request_id(&response);
         let json = response.json().await?;
-        Ok(Response::new(json, state))
+        Ok(Response::new(json, state).with_request_id(request_id))
+    }
+
+    /// Returns the gas unit price transactions need to be accepted by the node, which is the
+    /// minimum price in the on-chain gas schedule
+    pub async fn estimate_gas_price(&self) -> Result<Response<GasEstimation>> {
+        self.get_account_resource_bcs::<GasSchedule>(
+            CORE_CODE_ADDRESS,
+            "0x1::gas_schedule::GasSchedule",
+        )
+        .await?
+        .and_then(|gas_schedule| {
+            let gas_estimate = gas_schedule
+                .to_btree_map()
+                .get(MIN_PRICE_PER_GAS_UNIT)
+                .copied()
+                .ok_or_else(|| {
+                    anyhow!("The gas schedule has no {} entry", MIN_PRICE_PER_GAS_UNIT)
+                })?;
+            Ok(GasEstimation { gas_estimate })
+        })
     }
 
     pub async fn health_check(&self, seconds: u64) -> Result<()> {
         let url = self.build_path("-/healthy")?;
-        let response = self
-            .inner
-            .get(url)
-            .query(&[("duration_secs", seconds)])
-            .send()
-            .await?;
+        let request = self.get_request(url).query(&[("duration_secs", seconds)]);
+        let response = self.send(request).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!("health check failed"));
@@ -866,11 +1571,11 @@ impl Client {
     }
 
     async fn get<T: DeserializeOwned>(&self, url: Url) -> Result<Response<T>> {
-        self.json(self.inner.get(url).send().await?).await
+        self.json(self.send(self.get_request(url)).await?).await
     }
 
     async fn get_bcs(&self, url: Url) -> Result<Response<bytes::Bytes>> {
-        let response = self.inner.get(url).header(ACCEPT, BCS).send().await?;
+        let response = self.send(self.get_request(url).header(ACCEPT, BCS)).await?;
         self.check_and_parse_bcs_response(response).await
     }
 
@@ -880,7 +1585,7 @@ impl Client {
         start: Option<u64>,
         limit: Option<u16>,
     ) -> Result<Response<bytes::Bytes>> {
-        let mut request = self.inner.get(url).header(ACCEPT, BCS);
+        let mut request = self.get_request(url).header(ACCEPT, BCS);
         if let Some(start) = start {
             request = request.query(&[("start", start)])
         }
@@ -889,7 +1594,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
         self.check_and_parse_bcs_response(response).await
     }
 
@@ -898,17 +1603,54 @@ impl Client {
         response: reqwest::Response,
     ) -> Result<Response<bytes::Bytes>> {
         let (response, state) = self.check_response(response).await?;
-        Ok(Response::new(response.bytes().await?, state))
+        let request_id = response_request_id(&response);
+        Ok(Response::new(response.bytes().await?, state).with_request_id(request_id))
+    }
+}
+
+/// Composes a generic struct type, e.g. `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+fn resource_type_with_params(module: &str, struct_name: &str, type_params: &[&str]) -> String {
+    if type_params.is_empty() {
+        format!("{}::{}", module, struct_name)
+    } else {
+        format!("{}::{}<{}>", module, struct_name, type_params.join(", "))
+    }
+}
+
+/// The request ID a response was sent for, if it was sent by [`Client::send`]
+fn response_request_id(response: &reqwest::Response) -> Option<Uuid> {
+    response
+        .headers()
+        .get(X_APTOS_REQUEST_ID)
+        .and_then(|request_id| request_id.to_str().ok())
+        .and_then(|request_id| Uuid::parse_str(request_id).ok())
+}
+
+/// Wraps `raw_txn` with an all-zero signature for `public_key`, which is the form the node
+/// expects for simulation: it refuses to simulate transactions with a valid signature.
+pub fn simulation_transaction(
+    raw_txn: RawTransaction,
+    public_key: Ed25519PublicKey,
+) -> SignedTransaction {
+    let signature = Ed25519Signature::try_from(&[0u8; ED25519_SIGNATURE_LENGTH][..])
+        .expect("An all-zero signature is well formed");
+    SignedTransaction::new(raw_txn, public_key, signature)
+}
+
+/// Extracts the ledger version a request asked for, either from the `ledger_version` query
+/// parameter or from a `by_version/{version}` path.
+fn requested_version(url: &Url) -> Option<u64> {
+    if let Some((_, version)) = url.query_pairs().find(|(key, _)| key == "ledger_version") {
+        return version.parse().ok();
     }
+    let mut segments = url.path_segments()?;
+    segments.find(|segment| *segment == "by_version")?;
+    segments.next()?.parse().ok()
 }
 
 impl From<(ReqwestClient, Url)> for Client {
     fn from((inner, base_url): (ReqwestClient, Url)) -> Self {
-        Client {
-            inner,
-            base_url,
-            version_path_base: DEFAULT_VERSION_PATH_BASE.to_string(),
-        }
+        Client::from_parts(inner, base_url, DEFAULT_VERSION_PATH_BASE.to_string())
     }
 }
 
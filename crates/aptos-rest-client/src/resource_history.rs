@@ -0,0 +1,186 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::Error, Client, Resource};
+use anyhow::Result;
+use aptos_types::account_address::AccountAddress;
+use futures::{stream, Stream};
+
+/// A resource of an account at a ledger version
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResourceAtVersion {
+    pub version: u64,
+    /// `None` if the account didn't have the resource at `version`
+    pub resource: Option<Resource>,
+    /// Whether the history requested before `version` was pruned by the node.  Only the first
+    /// item of a history can be truncated.
+    pub truncated: bool,
+}
+
+/// Walks the versions of a resource, see [`Client::resource_history`] and
+/// [`Client::resource_history_adaptive`]
+pub(crate) struct ResourceHistory {
+    client: Client,
+    address: AccountAddress,
+    resource_type: String,
+    start_version: u64,
+    end_version: u64,
+    step: u64,
+    /// The last version yielded or skipped over, and the resource at it
+    last: Option<(u64, Option<Resource>)>,
+    /// A version after `last` that was already fetched while bisecting, and the resource at it
+    upper: Option<(u64, Option<Resource>)>,
+}
+
+impl ResourceHistory {
+    pub(crate) fn new(
+        client: Client,
+        address: AccountAddress,
+        resource_type: &str,
+        start_version: u64,
+        end_version: u64,
+        step: u64,
+    ) -> Self {
+        Self {
+            client,
+            address,
+            resource_type: resource_type.to_string(),
+            start_version,
+            end_version,
+            step: step.max(1),
+            last: None,
+            upper: None,
+        }
+    }
+
+    /// Yields the resource every `step` versions
+    pub(crate) fn samples(self) -> impl Stream<Item = Result<ResourceAtVersion>> {
+        stream::try_unfold(self, |mut history| async move {
+            let item = history.next_sample().await?;
+            Ok(item.map(|item| (item, history)))
+        })
+    }
+
+    /// Yields the resource at the start, and at every version it changed at
+    pub(crate) fn changes(self) -> impl Stream<Item = Result<ResourceAtVersion>> {
+        stream::try_unfold(self, |mut history| async move {
+            let item = history.next_change().await?;
+            Ok(item.map(|item| (item, history)))
+        })
+    }
+
+    async fn next_sample(&mut self) -> Result<Option<ResourceAtVersion>> {
+        let version = match &self.last {
+            None => return self.first().await.map(Some),
+            Some((last, _)) => match self.next_version(*last) {
+                Some(version) => version,
+                None => return Ok(None),
+            },
+        };
+        let resource = self.fetch(version).await?;
+        self.last = Some((version, resource.clone()));
+        Ok(Some(ResourceAtVersion {
+            version,
+            resource,
+            truncated: false,
+        }))
+    }
+
+    async fn next_change(&mut self) -> Result<Option<ResourceAtVersion>> {
+        let (mut low, low_resource) = match &self.last {
+            None => return self.first().await.map(Some),
+            Some((version, resource)) => (*version, resource.clone()),
+        };
+        loop {
+            let (mut high, mut high_resource) = match self.upper.take() {
+                Some(upper) => upper,
+                None => match self.next_version(low) {
+                    Some(version) => (version, self.fetch(version).await?),
+                    None => return Ok(None),
+                },
+            };
+            if high_resource == low_resource {
+                low = high;
+                self.last = Some((high, high_resource));
+                continue;
+            }
+
+            // Bisect for the first version that differs from `low`, keeping the sample so that
+            // changes between the first one and it are found next
+            let sample = (high, high_resource.clone());
+            while high - low > 1 {
+                let middle = low + (high - low) / 2;
+                let middle_resource = self.fetch(middle).await?;
+                if middle_resource == low_resource {
+                    low = middle;
+                } else {
+                    high = middle;
+                    high_resource = middle_resource;
+                }
+            }
+            if sample.0 != high {
+                self.upper = Some(sample);
+            }
+            self.last = Some((high, high_resource.clone()));
+            return Ok(Some(ResourceAtVersion {
+                version: high,
+                resource: high_resource,
+                truncated: false,
+            }));
+        }
+    }
+
+    /// Fetches the resource at the start version, or at the oldest version the node has if the
+    /// start version was pruned
+    async fn first(&mut self) -> Result<ResourceAtVersion> {
+        let (version, resource, truncated) = match self.fetch(self.start_version).await {
+            Ok(resource) => (self.start_version, resource, false),
+            Err(err) => {
+                let oldest_available = err
+                    .downcast_ref::<Error>()
+                    .filter(|err| err.is_version_pruned())
+                    .and_then(Error::oldest_available_version);
+                match oldest_available {
+                    Some(oldest) if oldest > self.start_version && oldest <= self.end_version => {
+                        (oldest, self.fetch(oldest).await?, true)
+                    }
+                    _ => return Err(err),
+                }
+            }
+        };
+        self.last = Some((version, resource.clone()));
+        Ok(ResourceAtVersion {
+            version,
+            resource,
+            truncated,
+        })
+    }
+
+    /// The version to sample after `version`, the end version being sampled even if it's not a
+    /// whole step away
+    fn next_version(&self, version: u64) -> Option<u64> {
+        if version >= self.end_version {
+            None
+        } else {
+            Some(version.saturating_add(self.step).min(self.end_version))
+        }
+    }
+
+    async fn fetch(&self, version: u64) -> Result<Option<Resource>> {
+        match self
+            .client
+            .get_account_resource_at_version(self.address, &self.resource_type, version)
+            .await
+        {
+            Ok(response) => Ok(response.into_inner()),
+            Err(err)
+                if err
+                    .downcast_ref::<Error>()
+                    .map_or(false, Error::is_resource_not_found) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
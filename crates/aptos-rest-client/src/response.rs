@@ -2,16 +2,28 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::state::State;
+use uuid::Uuid;
 
 #[derive(Debug)]
 pub struct Response<T> {
     inner: T,
     state: State,
+    request_id: Option<Uuid>,
 }
 
 impl<T> Response<T> {
     pub fn new(inner: T, state: State) -> Self {
-        Self { inner, state }
+        Self {
+            inner,
+            state,
+            request_id: None,
+        }
+    }
+
+    /// Attaches the `X-Aptos-Request-Id` the request for this response was sent with
+    pub fn with_request_id(mut self, request_id: Option<Uuid>) -> Self {
+        self.request_id = request_id;
+        self
     }
 
     pub fn inner(&self) -> &T {
@@ -26,6 +38,11 @@ impl<T> Response<T> {
         &self.state
     }
 
+    /// The `X-Aptos-Request-Id` of the request, to correlate it with the node's logs
+    pub fn request_id(&self) -> Option<Uuid> {
+        self.request_id
+    }
+
     pub fn into_parts(self) -> (T, State) {
         (self.inner, self.state)
     }
@@ -34,9 +51,10 @@ impl<T> Response<T> {
     where
         F: FnOnce(T) -> Result<U, E>,
     {
+        let request_id = self.request_id;
         let (inner, state) = self.into_parts();
         match f(inner) {
-            Ok(new_inner) => Ok(Response::new(new_inner, state)),
+            Ok(new_inner) => Ok(Response::new(new_inner, state).with_request_id(request_id)),
             Err(err) => Err(err),
         }
     }
@@ -45,7 +63,8 @@ impl<T> Response<T> {
     where
         F: FnOnce(T) -> U,
     {
+        let request_id = self.request_id;
         let (inner, state) = self.into_parts();
-        Response::new(f(inner), state)
+        Response::new(f(inner), state).with_request_id(request_id)
     }
 }
@@ -0,0 +1,89 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::SubmitError, Client, PendingTransaction, Response};
+use anyhow::Result;
+use aptos_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use tokio::sync::Mutex;
+
+/// Vends sequence numbers of an account to tasks submitting transactions from it concurrently,
+/// e.g. a hot wallet.
+///
+/// The sequence number is fetched from chain on first use, and again after a transaction is
+/// rejected for its sequence number being too old or too new, as another submitter of the
+/// account got ahead, or a transaction with an earlier sequence number was dropped.
+#[derive(Debug)]
+pub struct SequenceNumberProvider {
+    client: Client,
+    address: AccountAddress,
+    /// The next sequence number to vend, `None` until fetched from chain
+    next: Mutex<Option<u64>>,
+}
+
+impl SequenceNumberProvider {
+    pub fn new(client: Client, address: AccountAddress) -> Self {
+        Self {
+            client,
+            address,
+            next: Mutex::new(None),
+        }
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    /// Returns a sequence number no other caller received since the last resynchronization
+    pub async fn next(&self) -> Result<u64> {
+        let mut next = self.next.lock().await;
+        let sequence_number = match *next {
+            Some(sequence_number) => sequence_number,
+            None => self.fetch().await?,
+        };
+        *next = Some(sequence_number + 1);
+        Ok(sequence_number)
+    }
+
+    /// Fetches the sequence number from chain, and vends from it from now on
+    pub async fn resync(&self) -> Result<u64> {
+        let mut next = self.next.lock().await;
+        let sequence_number = self.fetch().await?;
+        *next = Some(sequence_number);
+        Ok(sequence_number)
+    }
+
+    /// Submits the transaction built with the next sequence number.  If it's rejected for its
+    /// sequence number, the sequence number is fetched from chain again before the next one is
+    /// vended, and the error is returned so the caller can decide whether to retry.
+    pub async fn submit<F>(&self, build: F) -> Result<Response<PendingTransaction>, SubmitError>
+    where
+        F: FnOnce(u64) -> SignedTransaction,
+    {
+        let sequence_number = self.next().await?;
+        let result = self.client.try_submit(&build(sequence_number)).await;
+        if let Err(error) = &result {
+            if error.is_sequence_number_error() {
+                self.invalidate(sequence_number).await;
+            }
+        }
+        result
+    }
+
+    /// Forgets the vended sequence numbers after `sequence_number` was rejected, unless they
+    /// were already forgotten by another rejection since it was vended
+    async fn invalidate(&self, sequence_number: u64) {
+        let mut next = self.next.lock().await;
+        if matches!(*next, Some(next) if next > sequence_number) {
+            *next = None;
+        }
+    }
+
+    async fn fetch(&self) -> Result<u64> {
+        Ok(self
+            .client
+            .get_account(self.address)
+            .await?
+            .into_inner()
+            .sequence_number)
+    }
+}
@@ -0,0 +1,1697 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    error::{Error, SubmitError},
+    extract_table_handle, resource_type_with_params, simulation_transaction, AddressHex, Client,
+    FaucetClient, HexBytes, ResourceAtVersion, SequenceNumberProvider, TableHandle, U128Str,
+    U64Str, DEFAULT_GAS_SAFETY_FACTOR, X_APTOS_REQUEST_ID,
+};
+use aptos_api_types::{
+    mime_types::BCS_SIGNED_TRANSACTION, AptosError, AptosErrorCode, BcsBlock,
+    BlockMetadataTransaction, MoveType, SubmitTransactionRequest, Transaction, TransactionInfo,
+    TransactionOnChainData, U64, X_APTOS_BLOCK_HEIGHT, X_APTOS_CHAIN_ID, X_APTOS_EPOCH,
+    X_APTOS_LEDGER_OLDEST_VERSION, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    X_APTOS_OLDEST_BLOCK_HEIGHT,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    HashValue,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::CoinStoreResource,
+    chain_id::ChainId,
+    event::{EventHandle, EventKey},
+    on_chain_config::GasSchedule,
+    test_helpers::transaction_test_helpers::get_test_signed_txn,
+    transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+    vm_status::StatusCode,
+};
+use futures::TryStreamExt;
+use httpmock::{Mock, MockServer, Then};
+use move_deps::move_core_types::{language_storage::TypeTag, parser::parse_type_tag};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    convert::TryFrom,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use url::Url;
+use uuid::Uuid;
+
+/// Attaches the ledger state headers every successful API response carries.
+pub(crate) fn with_state_headers(then: Then) -> Then {
+    with_chain_state_headers(then, "4")
+}
+
+pub(crate) fn with_chain_state_headers(then: Then, chain_id: &str) -> Then {
+    then.header(X_APTOS_CHAIN_ID, chain_id)
+        .header(X_APTOS_EPOCH, "1")
+        .header(X_APTOS_LEDGER_VERSION, "10")
+        .header(X_APTOS_LEDGER_OLDEST_VERSION, "0")
+        .header(X_APTOS_LEDGER_TIMESTAMP, "1000000")
+        .header(X_APTOS_BLOCK_HEIGHT, "5")
+        .header(X_APTOS_OLDEST_BLOCK_HEIGHT, "0")
+}
+
+pub(crate) fn index_json(chain_id: u8) -> Value {
+    json!({
+        "chain_id": chain_id,
+        "epoch": "1",
+        "ledger_version": "10",
+        "oldest_ledger_version": "0",
+        "ledger_timestamp": "1000000",
+        "node_role": "full_node",
+        "oldest_block_height": "0",
+        "block_height": "5",
+    })
+}
+
+pub(crate) fn test_client(server: &MockServer) -> Client {
+    Client::new(Url::parse(&server.base_url()).unwrap())
+}
+
+pub(crate) fn test_transaction() -> SignedTransaction {
+    let private_key = Ed25519PrivateKey::try_from(&[1u8; 32][..]).unwrap();
+    let public_key = Ed25519PublicKey::from(&private_key);
+    get_test_signed_txn(AccountAddress::ONE, 0, &private_key, public_key, None)
+}
+
+pub(crate) fn pending_transaction_json(txn: &SignedTransaction) -> Value {
+    json!({
+        "hash": txn.clone().committed_hash().to_hex_literal(),
+        "sender": txn.sender().to_hex_literal(),
+        "sequence_number": txn.sequence_number().to_string(),
+        "max_gas_amount": txn.max_gas_amount().to_string(),
+        "gas_unit_price": txn.gas_unit_price().to_string(),
+        "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+        "payload": {
+            "type": "entry_function_payload",
+            "function": "0x1::coin::transfer",
+            "type_arguments": [],
+            "arguments": [],
+        },
+    })
+}
+
+pub(crate) fn committed_transaction(hash: HashValue) -> Transaction {
+    Transaction::BlockMetadataTransaction(BlockMetadataTransaction {
+        info: TransactionInfo {
+            version: U64(1),
+            hash: hash.into(),
+            state_change_hash: HashValue::zero().into(),
+            event_root_hash: HashValue::zero().into(),
+            state_checkpoint_hash: None,
+            gas_used: U64(0),
+            success: true,
+            vm_status: "Executed successfully".to_string(),
+            accumulator_root_hash: HashValue::zero().into(),
+            changes: vec![],
+        },
+        id: HashValue::zero().into(),
+        epoch: U64(1),
+        round: U64(1),
+        events: vec![],
+        previous_block_votes_bitvec: vec![],
+        proposer: AccountAddress::ONE.into(),
+        failed_proposer_indices: vec![],
+        timestamp: U64(1000000),
+    })
+}
+
+/// Submits a transaction that only shows up as committed 100ms after submission and
+/// returns how long the client took to observe the commit.
+async fn time_submit_and_wait(poll_interval: Option<Duration>) -> Duration {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let txn = test_transaction();
+    let hash = txn.clone().committed_hash();
+    let txn_path = format!("/v1/transactions/by_hash/{}", hash.to_hex_literal());
+
+    server
+        .mock_async(|when, then| {
+            when.method("POST").path("/v1/transactions");
+            with_state_headers(then)
+                .status(202)
+                .json_body(pending_transaction_json(&txn));
+        })
+        .await;
+    let mut not_found = server
+        .mock_async(|when, then| {
+            when.method("GET").path(txn_path.clone());
+            then.status(404);
+        })
+        .await;
+
+    let commit = async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        not_found.delete_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path(txn_path.clone());
+                with_state_headers(then)
+                    .status(200)
+                    .json_body_obj(&committed_transaction(hash));
+            })
+            .await
+    };
+
+    let start = Instant::now();
+    let wait = async {
+        match poll_interval {
+            Some(poll_interval) => {
+                client
+                    .submit_and_wait_with(&txn, Duration::from_secs(5), poll_interval)
+                    .await
+            }
+            None => client.submit_and_wait(&txn).await,
+        }
+    };
+    let (result, _committed) = tokio::join!(wait, commit);
+    let elapsed = start.elapsed();
+
+    assert!(!result.unwrap().inner().is_pending());
+    elapsed
+}
+
+#[tokio::test]
+async fn test_submit_and_wait_with_short_poll_interval() {
+    let fast = time_submit_and_wait(Some(Duration::from_millis(50))).await;
+    let default = time_submit_and_wait(None).await;
+    assert!(
+        fast < default,
+        "fast poll took {:?}, default poll took {:?}",
+        fast,
+        default
+    );
+}
+
+#[tokio::test]
+async fn test_get_chain_id_is_cached() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let index = server
+        .mock_async(|when, then| {
+            when.method("GET").path("/v1/");
+            with_state_headers(then)
+                .status(200)
+                .json_body(index_json(4));
+        })
+        .await;
+
+    assert_eq!(client.get_chain_id().await.unwrap(), 4);
+    assert_eq!(client.get_chain_id().await.unwrap(), 4);
+    assert_eq!(client.clone().get_chain_id().await.unwrap(), 4);
+    index.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn test_client_from_reqwest_client() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method("GET").path("/v1/");
+            with_state_headers(then)
+                .status(200)
+                .json_body(index_json(4));
+        })
+        .await;
+
+    // Starts out like Client::new, with the default version path and no expected chain ID
+    let client = Client::from((
+        reqwest::Client::new(),
+        Url::parse(&server.base_url()).unwrap(),
+    ));
+    assert_eq!(client.get_chain_id().await.unwrap(), 4);
+}
+
+#[tokio::test]
+async fn test_chain_id_mismatch_is_detected() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let mut index = server
+        .mock_async(|when, then| {
+            when.method("GET").path("/v1/");
+            with_state_headers(then)
+                .status(200)
+                .json_body(index_json(4));
+        })
+        .await;
+    assert_eq!(client.get_chain_id().await.unwrap(), 4);
+
+    // The node is swapped for one on a different network.
+    index.delete_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method("GET").path("/v1/");
+            with_chain_state_headers(then, "5")
+                .status(200)
+                .json_body(index_json(5));
+        })
+        .await;
+
+    // Every response from the other network fails, not only the first one
+    for _ in 0..2 {
+        let err = client.get_index().await.unwrap_err();
+        assert!(err
+            .downcast_ref::<Error>()
+            .map_or(false, Error::is_chain_id_mismatch));
+    }
+
+    // The first chain ID is kept
+    assert_eq!(client.get_chain_id().await.unwrap(), 4);
+}
+
+#[tokio::test]
+async fn test_request_id_header() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let request_id = Uuid::new_v4();
+    let index = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path("/v1/")
+                .header_exists(X_APTOS_REQUEST_ID);
+            with_state_headers(then)
+                .status(200)
+                .json_body(index_json(4));
+        })
+        .await;
+    let failing = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path(format!("/v1/accounts/{}", AccountAddress::ONE))
+                .header(X_APTOS_REQUEST_ID, request_id.to_string());
+            then.status(500).json_body(json!({
+                "message": "Internal error",
+                "error_code": "internal_error",
+                "vm_error_code": null,
+            }));
+        })
+        .await;
+
+    // Every request gets its own ID, which responses make available
+    let first = client.get_index().await.unwrap().request_id();
+    let second = client.get_index().await.unwrap().request_id();
+    index.assert_hits_async(2).await;
+    assert!(first.is_some());
+    assert!(second.is_some());
+    assert_ne!(first, second);
+
+    let response = client
+        .with_request_id(request_id)
+        .get_index()
+        .await
+        .unwrap();
+    assert_eq!(response.request_id(), Some(request_id));
+
+    let err = client
+        .with_request_id(request_id)
+        .get_account(AccountAddress::ONE)
+        .await
+        .unwrap_err();
+    failing.assert_hits_async(1).await;
+    assert!(
+        err.to_string().contains(&request_id.to_string()),
+        "request ID missing from error: {}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_simulate_simulation_transaction() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let txn = test_transaction();
+    let private_key = Ed25519PrivateKey::try_from(&[1u8; 32][..]).unwrap();
+    let simulation_txn = simulation_transaction(
+        txn.clone().into_raw_transaction(),
+        Ed25519PublicKey::from(&private_key),
+    );
+
+    let simulation_body =
+        String::from_utf8_lossy(&bcs::to_bytes(&simulation_txn).unwrap()).to_string();
+    let simulation = server
+        .mock_async(|when, then| {
+            when.method("POST")
+                .path("/v1/transactions/simulate")
+                .body(&simulation_body);
+            with_state_headers(then).status(200).json_body(json!([]));
+        })
+        .await;
+
+    client.simulate(&simulation_txn).await.unwrap();
+    simulation.assert_hits_async(1).await;
+
+    // The node refuses to simulate properly signed transactions
+    assert!(simulation_txn.clone().check_signature().is_err());
+    assert_eq!(simulation_txn.sender(), txn.sender());
+    assert_eq!(simulation_txn.sequence_number(), txn.sequence_number());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct MoveOption<T> {
+    vec: Vec<T>,
+}
+
+fn table_item_request_json(key_type: &TypeTag, value_type: &TypeTag, key: Value) -> Value {
+    json!({
+        "key_type": MoveType::from(key_type.clone()).to_string(),
+        "value_type": MoveType::from(value_type.clone()).to_string(),
+        "key": key,
+    })
+}
+
+#[tokio::test]
+async fn test_get_table_item_bcs_nested_generic_value() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let handle = TableHandle(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let key_type = TypeTag::U64;
+    let value_type = parse_type_tag("vector<0x1::option::Option<vector<u8>>>").unwrap();
+    let value = vec![
+        MoveOption {
+            vec: vec![b"aptos".to_vec()],
+        },
+        MoveOption { vec: vec![] },
+    ];
+
+    let item = server
+        .mock_async(|when, then| {
+            when.method("POST")
+                .path(format!("/v1/tables/{}/item", handle))
+                .query_param("ledger_version", "3")
+                .header("accept", "application/x-bcs")
+                .json_body(table_item_request_json(&key_type, &value_type, json!("7")));
+            with_state_headers(then)
+                .status(200)
+                .body(bcs::to_bytes(&value).unwrap());
+        })
+        .await;
+
+    let fetched: Vec<MoveOption<Vec<u8>>> = client
+        .get_table_item_bcs(handle, &key_type, &value_type, &U64(7), Some(3))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(fetched, value);
+    item.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn test_get_block_transactions_bcs() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let block = BcsBlock {
+        block_height: 7,
+        block_hash: HashValue::zero(),
+        block_timestamp: 0,
+        first_version: 100,
+        last_version: 129,
+        transactions: Some(vec![]),
+    };
+    let block_mock = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path("/v1/blocks/by_height/7")
+                .query_param("with_transactions", "false");
+            with_state_headers(then)
+                .status(200)
+                .body(bcs::to_bytes(&block).unwrap());
+        })
+        .await;
+    // The page is clamped to the 20 transactions left in the block.
+    let transactions = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path("/v1/transactions")
+                .query_param("start", "110")
+                .query_param("limit", "20")
+                .header("accept", "application/x-bcs");
+            with_state_headers(then)
+                .status(200)
+                .body(bcs::to_bytes(&Vec::<TransactionOnChainData>::new()).unwrap());
+        })
+        .await;
+
+    let page = client
+        .get_block_transactions_bcs(7, Some(10), Some(100))
+        .await
+        .unwrap();
+    assert!(page.inner().is_empty());
+
+    // Past the end of the block, nothing more is fetched.
+    let page = client
+        .get_block_transactions_bcs(7, Some(30), None)
+        .await
+        .unwrap();
+    assert!(page.inner().is_empty());
+
+    block_mock.assert_hits_async(2).await;
+    transactions.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn test_check_framework_compatibility() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let version = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path("/v1/accounts/0x1/resource/0x1::version::Version");
+            with_state_headers(then).status(200).json_body(json!({
+                "type": "0x1::version::Version",
+                "data": { "major": "3" },
+            }));
+        })
+        .await;
+
+    assert_eq!(
+        client.get_framework_version().await.unwrap().into_inner(),
+        3
+    );
+    client.check_framework_compatibility(2).await.unwrap();
+    client.check_framework_compatibility(3).await.unwrap();
+    let err = client.check_framework_compatibility(4).await.unwrap_err();
+    assert!(err.to_string().contains("at least version 4"));
+    version.assert_hits_async(4).await;
+}
+
+#[tokio::test]
+async fn test_get_resource_with_nested_type_params() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let coin_store = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+    let resource_type = resource_type_with_params("0x1::table", "Table", &["address", coin_store]);
+    assert_eq!(
+        resource_type,
+        "0x1::table::Table<address, 0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>>"
+    );
+    assert_eq!(
+        client
+            .account_resource_url(AccountAddress::ONE, &resource_type)
+            .unwrap()
+            .path(),
+        "/v1/accounts/0x1/resource/0x1::table::Table%3Caddress,%200x1::coin::CoinStore\
+         %3C0x1::aptos_coin::AptosCoin%3E%3E"
+    );
+
+    let resource = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path_contains("/v1/accounts/0x1/resource/0x1::table::Table");
+            with_state_headers(then).status(200).json_body(json!({
+                "type": resource_type,
+                "data": { "handle": "0xcafe" },
+            }));
+        })
+        .await;
+
+    let fetched: Value = client
+        .get_resource_with_type_params(
+            AccountAddress::ONE,
+            "0x1::table",
+            "Table",
+            &["address", coin_store],
+        )
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(fetched["handle"], json!("0xcafe"));
+    resource.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn test_lookup_address() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let handle = TableHandle(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let rotated_key = AuthenticationKey::new([1; AuthenticationKey::LENGTH]);
+    let fresh_key = AuthenticationKey::new([2; AuthenticationKey::LENGTH]);
+    let originating_address = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+
+    server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path("/v1/accounts/0x1/resource/0x1::account::OriginatingAddress");
+            with_state_headers(then).status(200).json_body(json!({
+                "type": "0x1::account::OriginatingAddress",
+                "data": { "address_map": { "handle": handle.to_string() } },
+            }));
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method("POST")
+                .path(format!("/v1/tables/{}/item", handle))
+                .query_param("ledger_version", "10")
+                .json_body_partial(
+                    json!({ "key": rotated_key.derived_address().to_hex_literal() }).to_string(),
+                );
+            with_state_headers(then)
+                .status(200)
+                .body(bcs::to_bytes(&originating_address).unwrap());
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method("POST")
+                .path(format!("/v1/tables/{}/item", handle))
+                .json_body_partial(
+                    json!({ "key": fresh_key.derived_address().to_hex_literal() }).to_string(),
+                );
+            then.status(404).json_body(json!({
+                "message": "Table Item not found",
+                "error_code": "table_item_not_found",
+                "vm_error_code": null,
+            }));
+        })
+        .await;
+    let account = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path(format!("/v1/accounts/{}", fresh_key.derived_address()));
+            then.status(404).json_body(json!({
+                "message": "Account not found",
+                "error_code": "account_not_found",
+                "vm_error_code": null,
+            }));
+        })
+        .await;
+
+    assert_eq!(
+        client
+            .lookup_address(rotated_key, false)
+            .await
+            .unwrap()
+            .into_inner(),
+        originating_address
+    );
+    // Without a rotation entry, the derived address is used, and isn't required to exist.
+    assert_eq!(
+        client
+            .lookup_address(fresh_key, false)
+            .await
+            .unwrap()
+            .into_inner(),
+        fresh_key.derived_address()
+    );
+    account.assert_hits_async(0).await;
+    assert!(client.lookup_address(fresh_key, true).await.is_err());
+    account.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn test_get_table_item_not_found() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let handle = TableHandle(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let missing_key = U64(404);
+    let bad_key = U64(400);
+
+    server
+        .mock_async(|when, then| {
+            when.method("POST")
+                .path(format!("/v1/tables/{}/item", handle))
+                .json_body_partial(json!({ "key": missing_key }).to_string());
+            then.status(404).json_body(json!({
+                "message": "Table Item not found",
+                "error_code": "table_item_not_found",
+                "vm_error_code": null,
+            }));
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method("POST")
+                .path(format!("/v1/tables/{}/item", handle))
+                .json_body_partial(json!({ "key": bad_key }).to_string());
+            then.status(400).json_body(json!({
+                "message": "Failed to parse key",
+                "error_code": "invalid_input",
+                "vm_error_code": null,
+            }));
+        })
+        .await;
+
+    let err = client
+        .get_table_item_raw(handle, &TypeTag::U64, &TypeTag::U64, &missing_key, None)
+        .await
+        .unwrap_err();
+    assert!(err
+        .downcast_ref::<Error>()
+        .map_or(false, Error::is_table_item_not_found));
+
+    let err = client
+        .get_table_item_raw(handle, &TypeTag::U64, &TypeTag::U64, &bad_key, None)
+        .await
+        .unwrap_err();
+    assert!(!err
+        .downcast_ref::<Error>()
+        .map_or(false, Error::is_table_item_not_found));
+}
+
+#[tokio::test]
+async fn test_account_not_found() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let missing = AccountAddress::from_hex_literal("0xdead").unwrap();
+    let existing = AccountAddress::from_hex_literal("0xcafe").unwrap();
+
+    server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path(format!("/v1/accounts/{}/resources", missing));
+            then.status(404).json_body(json!({
+                "message": "Account not found",
+                "error_code": "account_not_found",
+                "vm_error_code": null,
+            }));
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path(format!("/v1/accounts/{}/resources", existing));
+            then.status(500).json_body(json!({
+                "message": "Failed to read account state",
+                "error_code": "read_from_storage_error",
+                "vm_error_code": null,
+            }));
+        })
+        .await;
+
+    let err = client
+        .get_account_resources_at_version(missing, 10)
+        .await
+        .unwrap_err();
+    assert!(err
+        .downcast_ref::<Error>()
+        .map_or(false, Error::is_account_not_found));
+
+    let err = client
+        .get_account_resources_at_version(existing, 10)
+        .await
+        .unwrap_err();
+    assert!(!err
+        .downcast_ref::<Error>()
+        .map_or(false, Error::is_account_not_found));
+}
+
+#[tokio::test]
+async fn test_pruned_version_is_detected() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let address = AccountAddress::ONE;
+    let resource_type = "0x1::account::Account";
+
+    server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path(format!(
+                    "/v1/accounts/{}/resource/{}",
+                    address, resource_type
+                ))
+                .query_param("ledger_version", "5");
+            then.status(410)
+                .header(X_APTOS_CHAIN_ID, "4")
+                .header(X_APTOS_EPOCH, "1")
+                .header(X_APTOS_LEDGER_VERSION, "100")
+                .header(X_APTOS_LEDGER_OLDEST_VERSION, "42")
+                .header(X_APTOS_LEDGER_TIMESTAMP, "1000000")
+                .header(X_APTOS_BLOCK_HEIGHT, "50")
+                .header(X_APTOS_OLDEST_BLOCK_HEIGHT, "21")
+                .json_body(json!({
+                    "message": "Ledger version(5) has been pruned",
+                    "error_code": "version_pruned",
+                    "vm_error_code": null,
+                }));
+        })
+        .await;
+
+    let err = client
+        .get_account_resource_at_version(address, resource_type, 5)
+        .await
+        .unwrap_err();
+    let err = err.downcast_ref::<Error>().unwrap();
+    assert!(err.is_version_pruned());
+    assert!(!err.is_retriable());
+    assert_eq!(err.requested_version(), Some(5));
+    assert_eq!(err.oldest_available_version(), Some(42));
+}
+
+/// Mocks the sequence number of an account at versions `0..=end_version`, with the account
+/// missing before version 3, and versions before `oldest_version` pruned
+async fn mock_account_history(
+    server: &MockServer,
+    address: AccountAddress,
+    oldest_version: u64,
+    end_version: u64,
+) -> Vec<Mock<'_>> {
+    let mut mocks = vec![];
+    for version in 0..=end_version {
+        let mock = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path(format!(
+                        "/v1/accounts/{}/resource/0x1::account::Account",
+                        address
+                    ))
+                    .query_param("ledger_version", version.to_string());
+                if version < oldest_version {
+                    then.status(410)
+                        .header(X_APTOS_CHAIN_ID, "4")
+                        .header(X_APTOS_EPOCH, "1")
+                        .header(X_APTOS_LEDGER_VERSION, "100")
+                        .header(X_APTOS_LEDGER_OLDEST_VERSION, oldest_version.to_string())
+                        .header(X_APTOS_LEDGER_TIMESTAMP, "1000000")
+                        .header(X_APTOS_BLOCK_HEIGHT, "50")
+                        .header(X_APTOS_OLDEST_BLOCK_HEIGHT, "21")
+                        .json_body(json!({
+                            "message": format!("Ledger version({}) has been pruned", version),
+                            "error_code": "version_pruned",
+                            "vm_error_code": null,
+                        }));
+                } else if version < 3 {
+                    with_state_headers(then).status(404).json_body(json!({
+                        "message": "Resource not found",
+                        "error_code": "resource_not_found",
+                        "vm_error_code": null,
+                    }));
+                } else {
+                    with_state_headers(then)
+                        .status(200)
+                        .json_body(account_resource_json(account_sequence_number(version)));
+                }
+            })
+            .await;
+        mocks.push(mock);
+    }
+    mocks
+}
+
+fn account_sequence_number(version: u64) -> u64 {
+    match version {
+        0..=6 => 0,
+        7..=22 => 1,
+        _ => 2,
+    }
+}
+
+fn account_resource_json(sequence_number: u64) -> Value {
+    json!({
+        "type": "0x1::account::Account",
+        "data": { "sequence_number": sequence_number.to_string() },
+    })
+}
+
+fn resource_at_version(version: u64, truncated: bool) -> ResourceAtVersion {
+    ResourceAtVersion {
+        version,
+        resource: (version >= 3).then(|| {
+            serde_json::from_value(account_resource_json(account_sequence_number(version))).unwrap()
+        }),
+        truncated,
+    }
+}
+
+#[tokio::test]
+async fn test_resource_history() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let address = AccountAddress::ONE;
+    mock_account_history(&server, address, 0, 40).await;
+
+    let history: Vec<_> = client
+        .resource_history(address, "0x1::account::Account", 0, 35, 10)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        history,
+        [0, 10, 20, 30, 35]
+            .iter()
+            .map(|version| resource_at_version(*version, false))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_resource_history_adaptive_finds_changes() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let address = AccountAddress::ONE;
+    let mocks = mock_account_history(&server, address, 0, 40).await;
+
+    let history: Vec<_> = client
+        .resource_history_adaptive(address, "0x1::account::Account", 0, 40, 10)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        history,
+        vec![
+            resource_at_version(0, false),
+            resource_at_version(3, false),
+            resource_at_version(7, false),
+            resource_at_version(23, false),
+        ]
+    );
+
+    let mut calls = 0;
+    for mock in mocks {
+        calls += mock.hits_async().await;
+    }
+    assert!(calls < 20, "{} calls", calls);
+}
+
+#[tokio::test]
+async fn test_resource_history_starts_after_pruned_versions() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let address = AccountAddress::ONE;
+    mock_account_history(&server, address, 5, 40).await;
+
+    let history: Vec<_> = client
+        .resource_history_adaptive(address, "0x1::account::Account", 0, 40, 10)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        history,
+        vec![
+            resource_at_version(5, true),
+            resource_at_version(7, false),
+            resource_at_version(23, false),
+        ]
+    );
+
+    let err = client
+        .resource_history(address, "0x1::account::Account", 0, 4, 1)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap_err();
+    assert!(err.downcast_ref::<Error>().unwrap().is_version_pruned());
+}
+
+#[test]
+fn test_extract_table_handle() {
+    let resource = json!({
+        "proposals": { "handle": "0xcafe" },
+        "nested": { "inner": { "handle": "0xbeef" } },
+        "count": "1",
+    });
+    let cafe = TableHandle(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let beef = TableHandle(AccountAddress::from_hex_literal("0xbeef").unwrap());
+
+    assert_eq!(extract_table_handle(&resource, "proposals").unwrap(), cafe);
+    assert_eq!(
+        extract_table_handle(&resource, "proposals.handle").unwrap(),
+        cafe
+    );
+    assert_eq!(
+        extract_table_handle(&resource, "nested.inner").unwrap(),
+        beef
+    );
+    assert!(extract_table_handle(&resource, "count").is_err());
+    assert!(extract_table_handle(&resource, "missing").is_err());
+
+    let handle: TableHandle = serde_json::from_value(json!("0xcafe")).unwrap();
+    assert_eq!(handle, cafe);
+    assert_eq!(serde_json::to_value(handle).unwrap(), json!("0xcafe"));
+}
+
+#[tokio::test]
+async fn test_submit_json() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let txn = test_transaction();
+
+    let mut request_json = pending_transaction_json(&txn);
+    let request_fields = request_json.as_object_mut().unwrap();
+    request_fields.remove("hash");
+    request_fields.insert(
+        "signature".to_string(),
+        json!({
+            "type": "ed25519_signature",
+            "public_key": format!("0x{}", hex::encode([1u8; 32])),
+            "signature": format!("0x{}", hex::encode([2u8; 64])),
+        }),
+    );
+    let request: SubmitTransactionRequest = serde_json::from_value(request_json.clone()).unwrap();
+
+    let submit = server
+        .mock_async(|when, then| {
+            when.method("POST")
+                .path("/v1/transactions")
+                .header("content-type", "application/json")
+                .json_body(request_json);
+            with_state_headers(then)
+                .status(202)
+                .json_body(pending_transaction_json(&txn));
+        })
+        .await;
+
+    let pending = client.submit_json(request).await.unwrap().into_inner();
+    submit.assert_hits_async(1).await;
+    assert_eq!(pending.hash, txn.clone().committed_hash().into());
+    assert_eq!(pending.request.sender, txn.sender().into());
+}
+
+#[tokio::test]
+async fn test_served_version_range() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    server
+        .mock_async(|when, then| {
+            when.method("GET").path("/v1/");
+            with_state_headers(then).status(200).json_body(json!({
+                "chain_id": 4,
+                "epoch": "12",
+                "ledger_version": "1000",
+                "oldest_ledger_version": "420",
+                "ledger_timestamp": "1662000000000000",
+                "node_role": "full_node",
+                "oldest_block_height": "20",
+                "block_height": "95",
+            }));
+        })
+        .await;
+
+    assert_eq!(
+        client.get_served_version_range().await.unwrap(),
+        (420, 1000)
+    );
+    assert!(!client.contains_version(419).await.unwrap());
+    assert!(client.contains_version(420).await.unwrap());
+    assert!(client.contains_version(1000).await.unwrap());
+    assert!(!client.contains_version(1001).await.unwrap());
+}
+
+fn balance_json(balance: u64) -> Value {
+    json!({
+        "type": "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+        "data": {"coin": {"value": balance.to_string()}},
+    })
+}
+
+#[tokio::test]
+async fn test_fund_and_confirm_waits_for_balance() {
+    let server = MockServer::start_async().await;
+    let url = Url::parse(&server.base_url()).unwrap();
+    let faucet = FaucetClient::new(url.clone(), url);
+    let txn = test_transaction();
+    let hash = txn.clone().committed_hash();
+
+    server
+        .mock_async(|when, then| {
+            when.method("POST").path("/mint");
+            then.status(200)
+                .body(hex::encode(bcs::to_bytes(&vec![txn.clone()]).unwrap()));
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method("GET").path(format!(
+                "/v1/transactions/by_hash/{}",
+                hash.to_hex_literal()
+            ));
+            with_state_headers(then)
+                .status(200)
+                .json_body_obj(&committed_transaction(hash));
+        })
+        .await;
+    let mut stale_balance = server
+        .mock_async(|when, then| {
+            when.method("GET").path_contains("CoinStore");
+            with_state_headers(then)
+                .status(200)
+                .json_body(balance_json(100));
+        })
+        .await;
+
+    // The funding only shows up in the balance 600ms after it's requested
+    let bump = async {
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        stale_balance.delete_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path_contains("CoinStore");
+                with_state_headers(then)
+                    .status(200)
+                    .json_body(balance_json(150));
+            })
+            .await
+    };
+    let start = Instant::now();
+    let fund = faucet.fund_and_confirm(AccountAddress::ONE, 50, Duration::from_secs(5));
+    let (balance, _bumped) = tokio::join!(fund, bump);
+    assert_eq!(balance.unwrap(), 150);
+    assert!(start.elapsed() >= Duration::from_millis(600));
+
+    // The balance never reaches 200
+    assert!(faucet
+        .fund_and_confirm(AccountAddress::ONE, 50, Duration::from_millis(100))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_fund_and_confirm_prior_balance() {
+    let server = MockServer::start_async().await;
+    let url = Url::parse(&server.base_url()).unwrap();
+    let faucet = FaucetClient::new(url.clone(), url);
+    let missing = AccountAddress::from_hex_literal("0xa").unwrap();
+    let broken = AccountAddress::from_hex_literal("0xb").unwrap();
+
+    let mint = server
+        .mock_async(|when, then| {
+            when.method("POST").path("/mint");
+            then.status(500);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path_contains(missing.to_hex_literal())
+                .path_contains("CoinStore");
+            with_state_headers(then).status(404).json_body(json!({
+                "message": "Account not found",
+                "error_code": "account_not_found",
+                "vm_error_code": null,
+            }));
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path_contains(broken.to_hex_literal())
+                .path_contains("CoinStore");
+            with_state_headers(then).status(500).json_body(json!({
+                "message": "Failed to read account state",
+                "error_code": "read_from_storage_error",
+                "vm_error_code": null,
+            }));
+        })
+        .await;
+
+    // A missing account counts as an empty balance, so funding is attempted, and fails here
+    assert!(faucet
+        .fund_and_confirm(missing, 50, Duration::from_millis(100))
+        .await
+        .is_err());
+    mint.assert_hits_async(1).await;
+
+    // Any other failure to read the balance is returned before funding
+    let err = faucet
+        .fund_and_confirm(broken, 50, Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert!(!err
+        .downcast_ref::<Error>()
+        .map_or(false, Error::is_account_not_found));
+    mint.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn test_expected_chain_id() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method("GET").path("/v1/");
+            with_state_headers(then)
+                .status(200)
+                .json_body(index_json(4));
+        })
+        .await;
+
+    let client = test_client(&server);
+    client.verify_chain_id(ChainId::new(4)).await.unwrap();
+    let err = client.verify_chain_id(ChainId::new(5)).await.unwrap_err();
+    assert!(err
+        .downcast_ref::<Error>()
+        .map_or(false, Error::is_chain_id_mismatch));
+
+    let client = test_client(&server).with_expected_chain_id(ChainId::new(4));
+    assert_eq!(client.get_index().await.unwrap().into_inner().chain_id, 4);
+
+    let client = test_client(&server).with_expected_chain_id(ChainId::new(5));
+    let err = client.get_index().await.unwrap_err();
+    assert!(err
+        .downcast_ref::<Error>()
+        .map_or(false, Error::is_chain_id_mismatch));
+}
+
+#[tokio::test]
+async fn test_expected_chain_id_without_header() {
+    let server = MockServer::start_async().await;
+    // e.g. a proxy in front of the node that strips the headers
+    let health_check = server
+        .mock_async(|when, then| {
+            when.method("GET").path("/v1/-/healthy");
+            then.status(200).body("aptos-node:ok");
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method("GET").path("/v1/");
+            with_state_headers(then)
+                .status(200)
+                .json_body(index_json(4));
+        })
+        .await;
+
+    // A response without the header can't be checked, so it's let through
+    let client = test_client(&server).with_expected_chain_id(ChainId::new(5));
+    client.health_check(0).await.unwrap();
+    health_check.assert_hits_async(1).await;
+
+    // But the next response with the header is still checked
+    let err = client.get_index().await.unwrap_err();
+    assert!(err
+        .downcast_ref::<Error>()
+        .map_or(false, Error::is_chain_id_mismatch));
+}
+
+fn account_json(sequence_number: u64) -> Value {
+    json!({
+        "authentication_key": format!("0x{}", "00".repeat(32)),
+        "sequence_number": sequence_number.to_string(),
+    })
+}
+
+#[tokio::test]
+async fn test_sequence_number_provider_concurrent() {
+    let server = MockServer::start_async().await;
+    let account = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path(format!("/v1/accounts/{}", AccountAddress::ONE));
+            with_state_headers(then)
+                .status(200)
+                .json_body(account_json(10));
+        })
+        .await;
+    let provider = Arc::new(SequenceNumberProvider::new(
+        test_client(&server),
+        AccountAddress::ONE,
+    ));
+
+    let tasks: Vec<_> = (0..64)
+        .map(|_| {
+            let provider = provider.clone();
+            tokio::spawn(async move { provider.next().await.unwrap() })
+        })
+        .collect();
+    let mut sequence_numbers = Vec::new();
+    for task in tasks {
+        sequence_numbers.push(task.await.unwrap());
+    }
+    sequence_numbers.sort_unstable();
+
+    assert_eq!(sequence_numbers, (10..74).collect::<Vec<_>>());
+    account.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn test_sequence_number_provider_resyncs_on_rejection() {
+    let server = MockServer::start_async().await;
+    let account = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path(format!("/v1/accounts/{}", AccountAddress::ONE));
+            with_state_headers(then)
+                .status(200)
+                .json_body(account_json(10));
+        })
+        .await;
+    let submit = server
+        .mock_async(|when, then| {
+            when.method("POST").path("/v1/transactions");
+            then.status(400).json_body(json!({
+                "message": "Invalid transaction: Type: Validation Code: SEQUENCE_NUMBER_TOO_NEW",
+                "error_code": "vm_error",
+                "vm_error_code": StatusCode::SEQUENCE_NUMBER_TOO_NEW as u64,
+            }));
+        })
+        .await;
+    let provider = SequenceNumberProvider::new(test_client(&server), AccountAddress::ONE);
+    assert_eq!(provider.next().await.unwrap(), 10);
+    assert_eq!(provider.next().await.unwrap(), 11);
+
+    let error = provider
+        .submit(|sequence_number| {
+            assert_eq!(sequence_number, 12);
+            test_transaction()
+        })
+        .await
+        .unwrap_err();
+    assert!(error.is_sequence_number_error());
+    assert!(matches!(
+        error,
+        SubmitError::Api(AptosError {
+            error_code: AptosErrorCode::VmError,
+            ..
+        })
+    ));
+
+    // The rejection resynchronizes from chain, which is still at 10
+    assert_eq!(provider.next().await.unwrap(), 10);
+    submit.assert_hits_async(1).await;
+    account.assert_hits_async(2).await;
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct TestCoin {
+    value: U64Str,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct TestEventHandle {
+    counter: U64Str,
+    guid: TestGuid,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct TestGuid {
+    id: TestGuidId,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct TestGuidId {
+    creation_num: U64Str,
+    addr: AddressHex,
+}
+
+/// `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct TestCoinStore {
+    coin: TestCoin,
+    frozen: bool,
+    deposit_events: TestEventHandle,
+    withdraw_events: TestEventHandle,
+}
+
+/// `0x1::stake::StakePool`
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct TestStakePool {
+    active: TestCoin,
+    inactive: TestCoin,
+    pending_active: TestCoin,
+    pending_inactive: TestCoin,
+    locked_until_secs: U64Str,
+    operator_address: AddressHex,
+    delegated_voter: AddressHex,
+    initialize_validator_events: TestEventHandle,
+    set_operator_events: TestEventHandle,
+    add_stake_events: TestEventHandle,
+    reactivate_stake_events: TestEventHandle,
+    rotate_consensus_key_events: TestEventHandle,
+    update_network_and_fullnode_addresses_events: TestEventHandle,
+    increase_lockup_events: TestEventHandle,
+    join_validator_set_events: TestEventHandle,
+    distribute_rewards_events: TestEventHandle,
+    unlock_stake_events: TestEventHandle,
+    withdraw_stake_events: TestEventHandle,
+    leave_validator_set_events: TestEventHandle,
+}
+
+fn event_handle_json(counter: u64, addr: &str, creation_num: u64) -> Value {
+    json!({
+        "counter": counter.to_string(),
+        "guid": { "id": { "addr": addr, "creation_num": creation_num.to_string() } },
+    })
+}
+
+fn coin_store_json() -> Value {
+    json!({
+        "coin": { "value": "18446744073709551615" },
+        "deposit_events": event_handle_json(1, "0x1", 2),
+        "frozen": false,
+        "withdraw_events": event_handle_json(0, "0x1", 3),
+    })
+}
+
+#[test]
+fn test_resource_helper_types() {
+    assert_eq!(serde_json::to_value(U64Str(7)).unwrap(), json!("7"));
+    assert_eq!(
+        bcs::to_bytes(&U64Str(7)).unwrap(),
+        bcs::to_bytes(&7u64).unwrap()
+    );
+    assert_eq!(
+        serde_json::from_value::<U128Str>(json!("340282366920938463463374607431768211455"))
+            .unwrap(),
+        U128Str(u128::MAX)
+    );
+    assert_eq!(
+        bcs::to_bytes(&U128Str(u128::MAX)).unwrap(),
+        bcs::to_bytes(&u128::MAX).unwrap()
+    );
+    assert_eq!(
+        serde_json::from_value::<AddressHex>(json!("0x1")).unwrap(),
+        AddressHex(AccountAddress::ONE)
+    );
+    assert_eq!(
+        serde_json::to_value(AddressHex(AccountAddress::ONE)).unwrap(),
+        json!("0x1")
+    );
+    assert_eq!(
+        bcs::from_bytes::<AddressHex>(&bcs::to_bytes(&AccountAddress::ONE).unwrap()).unwrap(),
+        AddressHex(AccountAddress::ONE)
+    );
+    assert_eq!(
+        serde_json::from_value::<HexBytes>(json!("0xcafe")).unwrap(),
+        HexBytes(vec![0xca, 0xfe])
+    );
+    assert_eq!(
+        serde_json::to_value(HexBytes(vec![0xca, 0xfe])).unwrap(),
+        json!("0xcafe")
+    );
+    assert_eq!(
+        bcs::to_bytes(&HexBytes(vec![0xca, 0xfe])).unwrap(),
+        bcs::to_bytes(&vec![0xcau8, 0xfe]).unwrap()
+    );
+    assert!(serde_json::from_value::<U64Str>(json!(7)).is_err());
+}
+
+#[test]
+fn test_coin_store_round_trip() {
+    let json = coin_store_json();
+    let coin_store: TestCoinStore = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(coin_store.coin.value, U64Str(u64::MAX));
+    assert_eq!(
+        coin_store.deposit_events.guid.id.addr,
+        AddressHex(AccountAddress::ONE)
+    );
+    assert_eq!(serde_json::to_value(&coin_store).unwrap(), json);
+
+    // The same struct reads the BCS of the resource as stored on chain
+    let bcs_bytes = bcs::to_bytes(&CoinStoreResource::new(
+        u64::MAX,
+        false,
+        EventHandle::new(EventKey::new(2, AccountAddress::ONE), 1),
+        EventHandle::new(EventKey::new(3, AccountAddress::ONE), 0),
+    ))
+    .unwrap();
+    assert_eq!(
+        bcs::from_bytes::<TestCoinStore>(&bcs_bytes).unwrap(),
+        coin_store
+    );
+    assert_eq!(bcs::to_bytes(&coin_store).unwrap(), bcs_bytes);
+}
+
+#[test]
+fn test_stake_pool_round_trip() {
+    let operator = "0xa550c18";
+    let mut json = json!({
+        "active": { "value": "100000000000000" },
+        "inactive": { "value": "0" },
+        "pending_active": { "value": "2500" },
+        "pending_inactive": { "value": "0" },
+        "locked_until_secs": "1667260800",
+        "operator_address": operator,
+        "delegated_voter": "0x1",
+    });
+    let event_fields = [
+        "initialize_validator_events",
+        "set_operator_events",
+        "add_stake_events",
+        "reactivate_stake_events",
+        "rotate_consensus_key_events",
+        "update_network_and_fullnode_addresses_events",
+        "increase_lockup_events",
+        "join_validator_set_events",
+        "distribute_rewards_events",
+        "unlock_stake_events",
+        "withdraw_stake_events",
+        "leave_validator_set_events",
+    ];
+    for (creation_num, field) in event_fields.iter().enumerate() {
+        json[field] = event_handle_json(creation_num as u64 % 3, operator, creation_num as u64);
+    }
+
+    let stake_pool: TestStakePool = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(stake_pool.active.value, U64Str(100_000_000_000_000));
+    assert_eq!(
+        stake_pool.operator_address,
+        AddressHex(AccountAddress::from_hex_literal(operator).unwrap())
+    );
+    assert_eq!(serde_json::to_value(&stake_pool).unwrap(), json);
+
+    let bcs_bytes = bcs::to_bytes(&stake_pool).unwrap();
+    assert_eq!(
+        bcs::from_bytes::<TestStakePool>(&bcs_bytes).unwrap(),
+        stake_pool
+    );
+}
+
+#[tokio::test]
+async fn test_get_resource_bcs() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let coin_store = CoinStoreResource::new(
+        10,
+        false,
+        EventHandle::new(EventKey::new(2, AccountAddress::ONE), 1),
+        EventHandle::new(EventKey::new(3, AccountAddress::ONE), 0),
+    );
+    server
+        .mock_async(|when, then| {
+            when.method("GET").path_contains("/resource/");
+            with_state_headers(then)
+                .status(200)
+                .body(bcs::to_bytes(&coin_store).unwrap());
+        })
+        .await;
+
+    let resource: TestCoinStore = client
+        .get_resource_bcs(
+            AccountAddress::ONE,
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+        )
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(resource.coin.value, U64Str(10));
+    assert_eq!(resource.withdraw_events.guid.id.creation_num, U64Str(3));
+}
+
+#[tokio::test]
+async fn test_get_events_by_creation_number() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    let event_key = EventKey::new(3, address);
+    assert_eq!(
+        client
+            .events_by_creation_number_url(address, 3)
+            .unwrap()
+            .path(),
+        format!("/v1/events/{:#x}", event_key)
+    );
+
+    let events = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path(format!("/v1/events/{:#x}", event_key))
+                .query_param("start", "1")
+                .query_param("limit", "2");
+            with_state_headers(then).status(200).json_body(json!([{
+                "version": "10",
+                "key": format!("{:#x}", event_key),
+                "sequence_number": "1",
+                "type": "u64",
+                "data": "100",
+            }]));
+        })
+        .await;
+
+    let fetched = client
+        .get_events_by_creation_number(address, 3, Some(1), Some(2))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(fetched.len(), 1);
+    assert_eq!(fetched[0].key.0, event_key);
+    assert_eq!(fetched[0].version, U64(10));
+    assert_eq!(fetched[0].sequence_number, U64(1));
+    assert_eq!(fetched[0].data, json!("100"));
+    events.assert_hits_async(1).await;
+}
+
+/// JSON of `txn` as simulated by the node, having used `gas_used`
+fn simulated_transaction_json(txn: &SignedTransaction, gas_used: u64) -> Value {
+    let info = TransactionInfo {
+        version: U64(1),
+        hash: txn.clone().committed_hash().into(),
+        state_change_hash: HashValue::zero().into(),
+        event_root_hash: HashValue::zero().into(),
+        state_checkpoint_hash: None,
+        gas_used: U64(gas_used),
+        success: true,
+        vm_status: "Executed successfully".to_string(),
+        accumulator_root_hash: HashValue::zero().into(),
+        changes: vec![],
+    };
+    let mut json = pending_transaction_json(txn);
+    let fields = json.as_object_mut().unwrap();
+    fields.extend(
+        serde_json::to_value(info)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .clone(),
+    );
+    fields.insert("events".to_string(), json!([]));
+    fields.insert("timestamp".to_string(), json!("1000000"));
+    json
+}
+
+#[tokio::test]
+async fn test_estimate_max_gas_amount() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let private_key = Ed25519PrivateKey::try_from(&[1u8; 32][..]).unwrap();
+    let txn = simulation_transaction(
+        test_transaction().into_raw_transaction(),
+        Ed25519PublicKey::from(&private_key),
+    );
+
+    let gas_schedule = GasSchedule {
+        entries: vec![
+            ("txn.min_transaction_gas_units".to_string(), 600),
+            ("txn.maximum_number_of_gas_units".to_string(), 2_000),
+        ],
+    };
+    // The transaction is simulated with the maximum max gas amount, not its own
+    assert_ne!(txn.max_gas_amount(), 2_000);
+    let unbounded_txn = SignedTransaction::new_with_authenticator(
+        RawTransaction::new(
+            txn.sender(),
+            txn.sequence_number(),
+            txn.payload().clone(),
+            2_000,
+            txn.gas_unit_price(),
+            txn.expiration_timestamp_secs(),
+            txn.chain_id(),
+        ),
+        txn.authenticator(),
+    );
+    let unbounded_body =
+        String::from_utf8_lossy(&bcs::to_bytes(&unbounded_txn).unwrap()).to_string();
+    let simulation = server
+        .mock_async(|when, then| {
+            when.method("POST")
+                .path("/v1/transactions/simulate")
+                .body(&unbounded_body);
+            with_state_headers(then)
+                .status(200)
+                .json_body(json!([simulated_transaction_json(&txn, 1_001)]));
+        })
+        .await;
+    let schedule = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path("/v1/accounts/0x1/resource/0x1::gas_schedule::GasSchedule");
+            with_state_headers(then)
+                .status(200)
+                .body(bcs::to_bytes(&gas_schedule).unwrap());
+        })
+        .await;
+
+    // The margin is rounded up
+    assert_eq!(
+        client
+            .estimate_max_gas_amount(&txn, DEFAULT_GAS_SAFETY_FACTOR)
+            .await
+            .unwrap(),
+        1_502
+    );
+    // and capped at the node's maximum
+    assert_eq!(
+        client.estimate_max_gas_amount(&txn, 3.0).await.unwrap(),
+        2_000
+    );
+    simulation.assert_hits_async(2).await;
+    schedule.assert_hits_async(2).await;
+
+    // Factors that would undercut the simulation are refused before asking the node
+    assert!(client.estimate_max_gas_amount(&txn, 0.5).await.is_err());
+    assert!(client
+        .estimate_max_gas_amount(&txn, f64::NAN)
+        .await
+        .is_err());
+
+    // Failed simulations don't say how much gas the transaction needs
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path("/v1/accounts/0x1/resource/0x1::gas_schedule::GasSchedule");
+            with_state_headers(then)
+                .status(200)
+                .body(bcs::to_bytes(&gas_schedule).unwrap());
+        })
+        .await;
+    let failed = server
+        .mock_async(|when, then| {
+            when.method("POST").path("/v1/transactions/simulate");
+            let mut json = simulated_transaction_json(&txn, 10);
+            json["success"] = json!(false);
+            json["vm_status"] = json!("Out of gas");
+            with_state_headers(then)
+                .status(200)
+                .json_body(json!([json]));
+        })
+        .await;
+    let err = client
+        .estimate_max_gas_amount(&txn, DEFAULT_GAS_SAFETY_FACTOR)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Out of gas"));
+    failed.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn test_estimate_gas_price() {
+    let server = MockServer::start_async().await;
+    let client = test_client(&server);
+    let gas_schedule = GasSchedule {
+        entries: vec![
+            ("txn.min_price_per_gas_unit".to_string(), 100),
+            ("txn.maximum_number_of_gas_units".to_string(), 2_000),
+        ],
+    };
+    let mut schedule = server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path("/v1/accounts/0x1/resource/0x1::gas_schedule::GasSchedule");
+            with_state_headers(then)
+                .status(200)
+                .body(bcs::to_bytes(&gas_schedule).unwrap());
+        })
+        .await;
+
+    let estimate = client.estimate_gas_price().await.unwrap();
+    assert_eq!(estimate.inner().gas_estimate, 100);
+    assert_eq!(estimate.state().chain_id, 4);
+    schedule.assert_hits_async(1).await;
+    schedule.delete_async().await;
+
+    // A gas schedule without a minimum price can't be estimated from
+    server
+        .mock_async(|when, then| {
+            when.method("GET")
+                .path("/v1/accounts/0x1/resource/0x1::gas_schedule::GasSchedule");
+            with_state_headers(then)
+                .status(200)
+                .body(bcs::to_bytes(&GasSchedule { entries: vec![] }).unwrap());
+        })
+        .await;
+    let err = client.estimate_gas_price().await.unwrap_err();
+    assert!(err.to_string().contains("txn.min_price_per_gas_unit"));
+}
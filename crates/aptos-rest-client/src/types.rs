@@ -1,11 +1,13 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_api_types::{Address, U64};
-use aptos_types::transaction::authenticator::AuthenticationKey;
+use anyhow::{anyhow, Result};
+use aptos_api_types::{Address, HexEncodedBytes, MoveType, U64};
+use aptos_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
 use move_deps::move_core_types::{language_storage::StructTag, parser::parse_struct_tag};
-use serde::{Deserialize, Deserializer, Serialize};
-use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::{fmt, str::FromStr};
 
 #[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct Resource {
@@ -50,6 +52,193 @@ where
     parse_struct_tag(&s).map_err(D::Error::custom)
 }
 
+/// A `u64` field of a resource, for user structs to deserialize resources into.
+///
+/// In JSON, resources have `u64`, `u128`, `address` and `vector<u8>` fields as strings, while in
+/// BCS they're numbers and bytes.  [`U64Str`], [`U128Str`], [`AddressHex`] and [`HexBytes`] are
+/// strings in human readable formats and native otherwise, so the same struct works with both
+/// [`Client::get_resource`](crate::Client::get_resource) and
+/// [`Client::get_resource_bcs`](crate::Client::get_resource_bcs).  Nested Move structs are nested
+/// Rust structs, and for BCS all fields have to be declared, in the order of the Move struct.
+///
+/// ```
+/// use aptos_rest_client::types::{AddressHex, U64Str};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Coin {
+///     value: U64Str,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct EventHandle {
+///     counter: U64Str,
+///     guid: Guid,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Guid {
+///     id: GuidId,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct GuidId {
+///     creation_num: U64Str,
+///     addr: AddressHex,
+/// }
+///
+/// /// `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+/// #[derive(Deserialize)]
+/// struct CoinStore {
+///     coin: Coin,
+///     frozen: bool,
+///     deposit_events: EventHandle,
+///     withdraw_events: EventHandle,
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct U64Str(pub u64);
+
+/// A `u128` field of a resource, see [`U64Str`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct U128Str(pub u128);
+
+/// An `address` field of a resource, see [`U64Str`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct AddressHex(pub AccountAddress);
+
+/// A `vector<u8>` field of a resource, see [`U64Str`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct HexBytes(pub Vec<u8>);
+
+macro_rules! impl_resource_number {
+    ($wrapper:ident, $inner:ty) => {
+        impl fmt::Display for $wrapper {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl From<$inner> for $wrapper {
+            fn from(value: $inner) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$wrapper> for $inner {
+            fn from(value: $wrapper) -> Self {
+                value.0
+            }
+        }
+
+        impl Serialize for $wrapper {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    serializer.collect_str(&self.0)
+                } else {
+                    self.0.serialize(serializer)
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $wrapper {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if deserializer.is_human_readable() {
+                    deserialize_from_string(deserializer).map(Self)
+                } else {
+                    <$inner>::deserialize(deserializer).map(Self)
+                }
+            }
+        }
+    };
+}
+
+impl_resource_number!(U64Str, u64);
+impl_resource_number!(U128Str, u128);
+
+impl fmt::Display for AddressHex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.to_hex_literal())
+    }
+}
+
+impl From<AccountAddress> for AddressHex {
+    fn from(address: AccountAddress) -> Self {
+        Self(address)
+    }
+}
+
+impl From<AddressHex> for AccountAddress {
+    fn from(address: AddressHex) -> Self {
+        address.0
+    }
+}
+
+impl Serialize for AddressHex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AddressHex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            // The API shortens addresses with leading zeros, e.g. `0x1`
+            deserialize_from_string::<_, Address>(deserializer).map(|address| Self(address.into()))
+        } else {
+            AccountAddress::deserialize(deserializer).map(Self)
+        }
+    }
+}
+
+impl fmt::Display for HexBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(&self.0))
+    }
+}
+
+impl From<Vec<u8>> for HexBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<HexBytes> for Vec<u8> {
+    fn from(bytes: HexBytes) -> Self {
+        bytes.0
+    }
+}
+
+impl Serialize for HexBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HexBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_from_string::<_, HexEncodedBytes>(deserializer).map(|bytes| Self(bytes.0))
+        } else {
+            Vec::<u8>::deserialize(deserializer).map(Self)
+        }
+    }
+}
+
+/// Gas unit price a transaction should use to be accepted by the node
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct GasEstimation {
+    pub gas_estimate: u64,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Account {
     #[serde(deserialize_with = "deserialize_from_prefixed_hex_string")]
@@ -58,6 +247,75 @@ pub struct Account {
     pub sequence_number: u64,
 }
 
+/// Handle of an on-chain table, as found in the `handle` field of a `0x1::table::Table`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TableHandle(pub AccountAddress);
+
+impl fmt::Display for TableHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.to_hex_literal())
+    }
+}
+
+impl FromStr for TableHandle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Self(AccountAddress::from_hex_literal(s)?))
+    }
+}
+
+impl From<AccountAddress> for TableHandle {
+    fn from(address: AccountAddress) -> Self {
+        Self(address)
+    }
+}
+
+impl From<Address> for TableHandle {
+    fn from(address: Address) -> Self {
+        Self(address.into())
+    }
+}
+
+impl Serialize for TableHandle {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for TableHandle {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_from_string(deserializer)
+    }
+}
+
+/// Extracts a table handle from the JSON `data` of a resource. `field_path` is a `.` separated
+/// path to either a `0x1::table::Table` (or a struct wrapping one in a `handle` field), or the
+/// handle itself, e.g. `proposals` or `proposals.handle`.
+pub fn extract_table_handle(resource_json: &Value, field_path: &str) -> Result<TableHandle> {
+    let mut value = resource_json;
+    for field in field_path.split('.') {
+        value = value
+            .get(field)
+            .ok_or_else(|| anyhow!("Field {} of {} not found", field, field_path))?;
+    }
+    let value = value.get("handle").unwrap_or(value);
+    let handle = value
+        .as_str()
+        .ok_or_else(|| anyhow!("{} is not a table handle: {}", field_path, value))?;
+    handle.parse()
+}
+
+/// The signature of an entry function, as needed to encode its arguments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntryFunctionAbi {
+    /// Number of generic type parameters the function takes.
+    pub type_param_count: usize,
+    /// Parameters the caller has to provide, in order. Leading signer parameters are omitted as
+    /// they're filled in from the transaction's senders.
+    pub params: Vec<MoveType>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EventHandle {
     counter: U64,
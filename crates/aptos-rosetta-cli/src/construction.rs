@@ -431,5 +431,6 @@ fn val_to_amount(amount: u64, withdraw: bool) -> Amount {
     Amount {
         value,
         currency: native_coin(),
+        metadata: None,
     }
 }
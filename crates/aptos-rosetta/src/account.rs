@@ -8,7 +8,8 @@
 
 use crate::types::{
     account_module_identifier, account_resource_identifier, coin_module_identifier,
-    AccountBalanceMetadata,
+    owner_capability_resource_identifier, stake_module_identifier, stake_pool_resource_identifier,
+    AccountBalanceMetadata, StakeSubAccount, StakeType,
 };
 use crate::{
     common::{
@@ -22,19 +23,23 @@ use crate::{
     },
     RosettaContext,
 };
-use aptos_logger::{debug, trace};
-use aptos_rest_client::aptos_api_types::AccountData;
+use aptos_logger::{debug, trace, warn};
+use aptos_rest_client::aptos_api_types::{AccountData, Address};
 use aptos_rest_client::{
     aptos::{AptosCoin, Balance},
     aptos_api_types::U64,
+    error::Error as RestError,
+    Resource,
 };
-use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::move_types::{identifier::Identifier, language_storage::TypeTag};
 use aptos_types::account_address::AccountAddress;
 use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
-    sync::{Arc, RwLock},
+    future::Future,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
 };
 use warp::Filter;
 
@@ -81,21 +86,31 @@ async fn account_balance(
         .await?;
     let balance_version = block_info.last_version;
 
-    let (sequence_number, balances) = get_balances(
-        &rest_client,
-        request.account_identifier.account_address()?,
-        balance_version,
-    )
-    .await?;
-
-    let amounts = convert_balances_to_amounts(
-        &rest_client,
-        server_context.coin_cache.clone(),
-        request.currencies,
-        balances,
-        balance_version,
-    )
-    .await?;
+    let address = request.account_identifier.account_address()?;
+    let (sequence_number, amounts) =
+        if let Some(sub_account) = request.account_identifier.stake_sub_account_kind()? {
+            get_stake_balances(
+                &rest_client,
+                address,
+                balance_version,
+                sub_account,
+                request.currencies,
+            )
+            .await?
+        } else {
+            let (sequence_number, balances) =
+                get_balances(&rest_client, address, balance_version).await?;
+
+            let amounts = convert_balances_to_amounts(
+                &rest_client,
+                server_context.coin_cache.clone(),
+                request.currencies,
+                balances,
+                balance_version,
+            )
+            .await?;
+            (sequence_number, amounts)
+        };
 
     Ok(AccountBalanceResponse {
         block_identifier: block_info.block_id,
@@ -123,6 +138,7 @@ async fn convert_balances_to_amounts(
             amounts.push(Amount {
                 value: balance.coin.value.0.to_string(),
                 currency,
+                metadata: None,
             });
         }
     }
@@ -144,6 +160,7 @@ async fn convert_balances_to_amounts(
             amounts.push(Amount {
                 value: 0.to_string(),
                 currency,
+                metadata: None,
             });
         }
     }
@@ -151,6 +168,161 @@ async fn convert_balances_to_amounts(
     Ok(amounts)
 }
 
+/// Type for deserializing the balances of a stake pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StakePool {
+    active: AptosCoin,
+    inactive: AptosCoin,
+    pending_active: AptosCoin,
+    pending_inactive: AptosCoin,
+}
+
+/// Retrieve the stake balances of the stake pool owned by an account
+///
+/// The pool is either at the account itself, or at the pool address in the account's
+/// `OwnerCapability`.  Accounts without a stake pool have zero stake.
+async fn get_stake_balances(
+    rest_client: &aptos_rest_client::Client,
+    address: AccountAddress,
+    version: u64,
+    sub_account: StakeSubAccount,
+    maybe_filter_currencies: Option<Vec<Currency>>,
+) -> ApiResult<(u64, Vec<Amount>)> {
+    /// Type for deserializing the owner capability of a stake pool
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct OwnerCapability {
+        pool_address: Address,
+    }
+
+    let resources = match rest_client
+        .get_account_resources_at_version(address, version)
+        .await
+    {
+        Ok(response) => response.into_inner(),
+        // Accounts that don't exist have no stake, any other failure is the node's
+        Err(err)
+            if err
+                .downcast_ref::<RestError>()
+                .map_or(false, RestError::is_account_not_found) =>
+        {
+            Vec::new()
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let sequence_number = if resources.is_empty() {
+        0
+    } else {
+        get_sequence_number(&resources)?
+    };
+
+    let find_stake_resource = |name: Identifier| {
+        resources.iter().find(|resource| {
+            resource.resource_type.address == AccountAddress::ONE
+                && resource.resource_type.module == stake_module_identifier()
+                && resource.resource_type.name == name
+        })
+    };
+    let stake_pool = if let Some(resource) = find_stake_resource(stake_pool_resource_identifier()) {
+        Some(resource.data.clone())
+    } else if let Some(resource) = find_stake_resource(owner_capability_resource_identifier()) {
+        let owner_capability = serde_json::from_value::<OwnerCapability>(resource.data.clone())
+            .map_err(|_| ApiError::deserialization_failed("OwnerCapability"))?;
+        rest_client
+            .get_account_resource_at_version(
+                *owner_capability.pool_address.inner(),
+                "0x1::stake::StakePool",
+                version,
+            )
+            .await?
+            .into_inner()
+            .map(|resource| resource.data)
+    } else {
+        None
+    };
+
+    // Stake is only ever in the native coin
+    if let Some(currencies) = maybe_filter_currencies {
+        if !currencies.contains(&native_coin()) {
+            return Ok((sequence_number, Vec::new()));
+        }
+    }
+
+    Ok((sequence_number, stake_amounts(stake_pool, sub_account)?))
+}
+
+/// Converts the stake held by `sub_account` in the `StakePool` resource, if there's one, to
+/// amounts
+fn stake_amounts(
+    stake_pool: Option<serde_json::Value>,
+    sub_account: StakeSubAccount,
+) -> ApiResult<Vec<Amount>> {
+    let (active, inactive, pending_active, pending_inactive) = if let Some(stake_pool) = stake_pool
+    {
+        let stake_pool = serde_json::from_value::<StakePool>(stake_pool)
+            .map_err(|_| ApiError::deserialization_failed("StakePool"))?;
+        (
+            stake_pool.active.value.0,
+            stake_pool.inactive.value.0,
+            stake_pool.pending_active.value.0,
+            stake_pool.pending_inactive.value.0,
+        )
+    } else {
+        (0, 0, 0, 0)
+    };
+
+    let amounts = match sub_account {
+        // One balance per currency, the parts are in their own sub-accounts
+        StakeSubAccount::All => vec![Amount::stake(
+            active
+                .saturating_add(inactive)
+                .saturating_add(pending_active)
+                .saturating_add(pending_inactive)
+                .to_string(),
+            None,
+        )],
+        StakeSubAccount::Active => {
+            vec![Amount::stake(active.to_string(), Some(StakeType::Active))]
+        }
+        StakeSubAccount::Inactive => {
+            vec![Amount::stake(
+                inactive.to_string(),
+                Some(StakeType::Inactive),
+            )]
+        }
+        StakeSubAccount::PendingActive => {
+            vec![Amount::stake(
+                pending_active.to_string(),
+                Some(StakeType::PendingActive),
+            )]
+        }
+        StakeSubAccount::PendingInactive => vec![Amount::stake(
+            pending_inactive.to_string(),
+            Some(StakeType::PendingInactive),
+        )],
+    };
+    Ok(amounts)
+}
+
+/// Retrieve the sequence number from the resources of an account
+fn get_sequence_number(resources: &[Resource]) -> ApiResult<u64> {
+    resources
+        .iter()
+        .find(|resource| {
+            resource.resource_type.address == AccountAddress::ONE
+                && resource.resource_type.module == account_module_identifier()
+                && resource.resource_type.name == account_resource_identifier()
+        })
+        .and_then(|account_resource| {
+            serde_json::from_value::<AccountData>(account_resource.data.clone()).ok()
+        })
+        .map(|resource| resource.sequence_number.0)
+        .ok_or_else(|| {
+            ApiError::AptosError(Some(
+                "Failed to retrieve account sequence number".to_string(),
+            ))
+        })
+}
+
 /// Retrieve the balances for an account
 async fn get_balances(
     rest_client: &aptos_rest_client::Client,
@@ -162,31 +334,7 @@ async fn get_balances(
         .await
     {
         let response = response.into_inner();
-
-        let maybe_sequence_number = if let Some(account_resource) =
-            response.iter().find(|resource| {
-                resource.resource_type.address == AccountAddress::ONE
-                    && resource.resource_type.module == account_module_identifier()
-                    && resource.resource_type.name == account_resource_identifier()
-            }) {
-            if let Ok(resource) =
-                serde_json::from_value::<AccountData>(account_resource.data.clone())
-            {
-                Some(resource.sequence_number.0)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        let sequence_number = if let Some(sequence_number) = maybe_sequence_number {
-            sequence_number
-        } else {
-            return Err(ApiError::AptosError(Some(
-                "Failed to retrieve account sequence number".to_string(),
-            )));
-        };
+        let sequence_number = get_sequence_number(&response)?;
 
         let balances = response
             .iter()
@@ -223,16 +371,104 @@ async fn get_balances(
     }
 }
 
+/// Default time before a cached currency is looked up again, as its metadata can be changed
+/// on-chain
+pub const DEFAULT_COIN_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Default time before a currency that wasn't found is looked up again, as it may be created
+pub const DEFAULT_COIN_CACHE_NEGATIVE_TTL: Duration = Duration::from_secs(60);
+
+/// Configuration of the [`CoinCache`]
+#[derive(Clone, Debug)]
+pub struct CoinCacheConfig {
+    /// How long a currency is cached for, or forever if `None`
+    pub ttl: Option<Duration>,
+    /// How long a currency that wasn't found is cached for, or forever if `None`
+    pub negative_ttl: Option<Duration>,
+    /// Currencies to look up at startup
+    pub preload: Vec<TypeTag>,
+}
+
+impl Default for CoinCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Some(DEFAULT_COIN_CACHE_TTL),
+            negative_ttl: Some(DEFAULT_COIN_CACHE_NEGATIVE_TTL),
+            preload: Vec::new(),
+        }
+    }
+}
+
 /// A cache for currencies, so we don't have to keep looking up the status of it
 #[derive(Debug)]
 pub struct CoinCache {
-    currencies: RwLock<HashMap<TypeTag, Option<Currency>>>,
+    currencies: RwLock<HashMap<TypeTag, CachedCurrency>>,
+    /// Lookups in flight, so that concurrent misses of a coin share one lookup
+    lookups: Mutex<HashMap<TypeTag, Arc<tokio::sync::Mutex<()>>>>,
+    /// How long a currency is cached for, or forever if `None`
+    ttl: Option<Duration>,
+    /// How long a currency that wasn't found is cached for, or forever if `None`
+    negative_ttl: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct CachedCurrency {
+    lookup: CurrencyLookup,
+    expiry: Option<Instant>,
+}
+
+/// The result of looking up a currency on-chain
+#[derive(Clone, Debug, PartialEq)]
+enum CurrencyLookup {
+    Found(Currency),
+    /// The coin type isn't one that's supported as a currency
+    Unsupported,
+    /// There is no `CoinInfo` for the coin
+    NotFound,
+}
+
+impl CurrencyLookup {
+    fn into_currency(self, coin: &TypeTag) -> ApiResult<Option<Currency>> {
+        match self {
+            CurrencyLookup::Found(currency) => Ok(Some(currency)),
+            CurrencyLookup::Unsupported => Ok(None),
+            CurrencyLookup::NotFound => Err(ApiError::DeserializationFailed(Some(format!(
+                "Currency {} not found",
+                coin
+            )))),
+        }
+    }
 }
 
 impl CoinCache {
     pub fn new() -> Self {
+        Self::from_config(&CoinCacheConfig::default())
+    }
+
+    pub fn from_config(config: &CoinCacheConfig) -> Self {
+        Self::invalidate_after(config.ttl, config.negative_ttl)
+    }
+
+    /// Creates a cache that looks up a currency again once it's been cached for `ttl`, or
+    /// `negative_ttl` if it wasn't found
+    pub fn invalidate_after(ttl: Option<Duration>, negative_ttl: Option<Duration>) -> Self {
         Self {
             currencies: RwLock::new(HashMap::new()),
+            lookups: Mutex::new(HashMap::new()),
+            ttl,
+            negative_ttl,
+        }
+    }
+
+    /// Looks up `coins` ahead of the first request for them.  Failures are only logged, as the
+    /// lookup is retried when the currency is requested.
+    pub async fn preload(&self, rest_client: &aptos_rest_client::Client, coins: &[TypeTag]) {
+        for coin in coins {
+            match self.get_currency(rest_client, coin.clone(), None).await {
+                Ok(Some(currency)) => debug!("Preloaded currency {:?} for {}", currency, coin),
+                Ok(None) => warn!("Preloaded coin {} is not a supported currency", coin),
+                Err(err) => warn!("Failed to preload currency {}: {:?}", coin, err),
+            }
         }
     }
 
@@ -248,30 +484,75 @@ impl CoinCache {
             return Ok(Some(native_coin()));
         }
 
-        {
-            let currencies = self.currencies.read().unwrap();
-            if let Some(currency) = currencies.get(&coin) {
-                return Ok(currency.clone());
-            }
+        self.get_or_lookup(&coin, || {
+            self.get_currency_inner(rest_client, coin.clone(), version)
+        })
+        .await?
+        .into_currency(&coin)
+    }
+
+    /// Retrieve a currency from the cache, or run `lookup` and cache its result if it's missing
+    async fn get_or_lookup<F, Fut>(&self, coin: &TypeTag, lookup: F) -> ApiResult<CurrencyLookup>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ApiResult<CurrencyLookup>>,
+    {
+        if let Some(cached) = self.get_cached(coin) {
+            return Ok(cached);
         }
 
-        let currency = self
-            .get_currency_inner(rest_client, coin.clone(), version)
-            .await?;
+        // Only one lookup per coin at a time, the others wait for it to fill the cache
+        let lookup_lock = self
+            .lookups
+            .lock()
+            .unwrap()
+            .entry(coin.clone())
+            .or_default()
+            .clone();
+        let _guard = lookup_lock.lock().await;
+        if let Some(cached) = self.get_cached(coin) {
+            return Ok(cached);
+        }
+
+        let result = lookup().await;
+        if let Ok(ref lookup) = result {
+            self.insert(coin.clone(), lookup.clone());
+        }
+        self.lookups.lock().unwrap().remove(coin);
+        result
+    }
+
+    /// Retrieve a currency from the cache, if it's there and hasn't expired
+    fn get_cached(&self, coin: &TypeTag) -> Option<CurrencyLookup> {
+        let currencies = self.currencies.read().unwrap();
+        currencies
+            .get(coin)
+            .filter(|cached| match cached.expiry {
+                Some(expiry) => Instant::now() < expiry,
+                None => true,
+            })
+            .map(|cached| cached.lookup.clone())
+    }
+
+    fn insert(&self, coin: TypeTag, lookup: CurrencyLookup) {
+        let ttl = match lookup {
+            CurrencyLookup::NotFound => self.negative_ttl,
+            CurrencyLookup::Found(_) | CurrencyLookup::Unsupported => self.ttl,
+        };
+        let expiry = ttl.map(|ttl| Instant::now() + ttl);
         self.currencies
             .write()
             .unwrap()
-            .insert(coin, currency.clone());
-        Ok(currency)
+            .insert(coin, CachedCurrency { lookup, expiry });
     }
 
     /// Pulls currency information from onchain
-    pub async fn get_currency_inner(
+    async fn get_currency_inner(
         &self,
         rest_client: &aptos_rest_client::Client,
         coin: TypeTag,
         version: Option<u64>,
-    ) -> ApiResult<Option<Currency>> {
+    ) -> ApiResult<CurrencyLookup> {
         /// Type for deserializing coin info
         #[derive(Debug, Clone, Serialize, Deserialize)]
         struct CoinInfo {
@@ -283,12 +564,12 @@ impl CoinCache {
         let struct_tag = match coin {
             TypeTag::Struct(ref tag) => tag,
             // This is a poorly formed coin, and we'll just skip over it
-            _ => return Ok(None),
+            _ => return Ok(CurrencyLookup::Unsupported),
         };
 
         // Nested types are not supported for now
         if !struct_tag.type_params.is_empty() {
-            return Ok(None);
+            return Ok(CurrencyLookup::Unsupported);
         }
 
         // Retrieve the coin type
@@ -316,7 +597,7 @@ impl CoinCache {
                 )))
             })?;
 
-            Ok(Some(Currency {
+            Ok(CurrencyLookup::Found(Currency {
                 symbol: coin_info.symbol,
                 decimals: coin_info.decimals.0,
                 metadata: Some(CurrencyMetadata {
@@ -324,10 +605,216 @@ impl CoinCache {
                 }),
             }))
         } else {
-            Err(ApiError::DeserializationFailed(Some(format!(
-                "Currency {} not found",
-                coin
-            ))))
+            Ok(CurrencyLookup::NotFound)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{
+        AccountIdentifier, SubAccountIdentifier, ACTIVE_STAKE_SUB_ACCOUNT,
+        INACTIVE_STAKE_SUB_ACCOUNT, PENDING_ACTIVE_STAKE_SUB_ACCOUNT,
+        PENDING_INACTIVE_STAKE_SUB_ACCOUNT, STAKE_SUB_ACCOUNT,
+    };
+    use aptos_sdk::move_types::language_storage::StructTag;
+    use futures::future::join_all;
+    use std::{
+        str::FromStr,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    fn test_coin() -> (TypeTag, Currency) {
+        let tag = StructTag::from_str("0x1234::test_coin::TestCoin").unwrap();
+        let currency = Currency {
+            symbol: "TC".to_string(),
+            decimals: 8,
+            metadata: Some(CurrencyMetadata {
+                move_type: tag.to_string(),
+            }),
+        };
+        (TypeTag::Struct(tag), currency)
+    }
+
+    #[test]
+    fn test_coin_cache_ttl() {
+        let (coin, currency) = test_coin();
+        let found = CurrencyLookup::Found(currency);
+
+        let cache = CoinCache::invalidate_after(None, None);
+        cache.insert(coin.clone(), found.clone());
+        assert_eq!(cache.get_cached(&coin), Some(found.clone()));
+
+        let cache = CoinCache::new();
+        cache.insert(coin.clone(), found.clone());
+        assert_eq!(cache.get_cached(&coin), Some(found.clone()));
+
+        // An expired currency has to be looked up again
+        let cache = CoinCache::invalidate_after(Some(Duration::ZERO), None);
+        cache.insert(coin.clone(), found);
+        assert_eq!(cache.get_cached(&coin), None);
+    }
+
+    #[test]
+    fn test_coin_cache_negative_ttl() {
+        let (coin, _) = test_coin();
+
+        // Missing currencies are cached for the negative TTL, the others for the TTL
+        let cache = CoinCache::invalidate_after(None, Some(Duration::ZERO));
+        cache.insert(coin.clone(), CurrencyLookup::NotFound);
+        assert_eq!(cache.get_cached(&coin), None);
+        cache.insert(coin.clone(), CurrencyLookup::Unsupported);
+        assert_eq!(cache.get_cached(&coin), Some(CurrencyLookup::Unsupported));
+
+        let cache = CoinCache::invalidate_after(Some(Duration::ZERO), None);
+        cache.insert(coin.clone(), CurrencyLookup::NotFound);
+        assert_eq!(cache.get_cached(&coin), Some(CurrencyLookup::NotFound));
+        assert!(CurrencyLookup::NotFound.into_currency(&coin).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_coin_cache_dedupes_lookups() {
+        let (coin, currency) = test_coin();
+        let num_lookups = AtomicUsize::new(0);
+        let lookup = {
+            let (num_lookups, currency) = (&num_lookups, &currency);
+            move || async move {
+                num_lookups.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                Ok(CurrencyLookup::Found(currency.clone()))
+            }
+        };
+
+        // Concurrent misses share a single lookup
+        let cache = CoinCache::new();
+        let results = join_all((0..5).map(|_| cache.get_or_lookup(&coin, lookup))).await;
+        for result in results {
+            assert_eq!(result.unwrap(), CurrencyLookup::Found(currency.clone()));
+        }
+        assert_eq!(num_lookups.load(Ordering::SeqCst), 1);
+        cache.get_or_lookup(&coin, lookup).await.unwrap();
+        assert_eq!(num_lookups.load(Ordering::SeqCst), 1);
+
+        // Failed lookups aren't cached, so the next request looks the coin up again
+        let cache = CoinCache::new();
+        assert!(cache
+            .get_or_lookup(&coin, || async { Err(ApiError::NodeIsOffline) })
+            .await
+            .is_err());
+        cache.get_or_lookup(&coin, lookup).await.unwrap();
+        assert_eq!(num_lookups.load(Ordering::SeqCst), 2);
+    }
+
+    /// A `0x1::stake::StakePool` resource as returned by the API
+    fn recorded_stake_pool() -> serde_json::Value {
+        serde_json::json!({
+            "active": { "value": "100000000000" },
+            "add_stake_events": {
+                "counter": "2",
+                "guid": { "id": { "addr": "0x9b6c", "creation_num": "9" } }
+            },
+            "delegated_voter": "0x9b6c",
+            "inactive": { "value": "2500000000" },
+            "locked_until_secs": "1666396800",
+            "operator_address": "0x9b6c",
+            "pending_active": { "value": "300000000" },
+            "pending_inactive": { "value": "40000000" }
+        })
+    }
+
+    fn stake_values(sub_account: StakeSubAccount) -> Vec<(String, Option<StakeType>)> {
+        stake_amounts(Some(recorded_stake_pool()), sub_account)
+            .unwrap()
+            .into_iter()
+            .map(|amount| {
+                assert_eq!(amount.currency, native_coin());
+                (
+                    amount.value,
+                    amount.metadata.map(|metadata| metadata.stake_type),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stake_amounts_all() {
+        assert_eq!(
+            stake_values(StakeSubAccount::All),
+            vec![("102840000000".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn test_stake_amounts_active() {
+        assert_eq!(
+            stake_values(StakeSubAccount::Active),
+            vec![("100000000000".to_string(), Some(StakeType::Active))]
+        );
+    }
+
+    #[test]
+    fn test_stake_amounts_inactive() {
+        assert_eq!(
+            stake_values(StakeSubAccount::Inactive),
+            vec![("2500000000".to_string(), Some(StakeType::Inactive))]
+        );
+    }
+
+    #[test]
+    fn test_stake_amounts_pending_active() {
+        assert_eq!(
+            stake_values(StakeSubAccount::PendingActive),
+            vec![("300000000".to_string(), Some(StakeType::PendingActive))]
+        );
+    }
+
+    #[test]
+    fn test_stake_amounts_pending_inactive() {
+        assert_eq!(
+            stake_values(StakeSubAccount::PendingInactive),
+            vec![("40000000".to_string(), Some(StakeType::PendingInactive))]
+        );
+    }
+
+    #[test]
+    fn test_stake_amounts_without_stake_pool() {
+        let amounts = stake_amounts(None, StakeSubAccount::Active).unwrap();
+        assert_eq!(
+            amounts,
+            vec![Amount::stake("0".to_string(), Some(StakeType::Active))]
+        );
+    }
+
+    #[test]
+    fn test_stake_sub_account_kind() {
+        let account = |sub_account: &str| AccountIdentifier {
+            address: "0x9b6c".to_string(),
+            sub_account: Some(SubAccountIdentifier {
+                address: sub_account.to_string(),
+            }),
+        };
+        assert_eq!(
+            AccountIdentifier::from(AccountAddress::ONE)
+                .stake_sub_account_kind()
+                .unwrap(),
+            None
+        );
+        for (name, kind) in [
+            (STAKE_SUB_ACCOUNT, StakeSubAccount::All),
+            (ACTIVE_STAKE_SUB_ACCOUNT, StakeSubAccount::Active),
+            (INACTIVE_STAKE_SUB_ACCOUNT, StakeSubAccount::Inactive),
+            (
+                PENDING_ACTIVE_STAKE_SUB_ACCOUNT,
+                StakeSubAccount::PendingActive,
+            ),
+            (
+                PENDING_INACTIVE_STAKE_SUB_ACCOUNT,
+                StakeSubAccount::PendingInactive,
+            ),
+        ] {
+            assert_eq!(account(name).stake_sub_account_kind().unwrap(), Some(kind));
         }
+        assert!(account("locked").stake_sub_account_kind().is_err());
     }
 }
@@ -11,7 +11,7 @@ use crate::{
     RosettaContext,
 };
 use aptos_logger::{debug, trace};
-use aptos_rest_client::aptos_api_types::HashValue;
+use aptos_rest_client::aptos_api_types::{HashValue, TransactionData};
 use std::sync::Arc;
 use std::{collections::BTreeMap, sync::RwLock};
 use warp::Filter;
@@ -46,10 +46,10 @@ async fn block(request: BlockRequest, server_context: RosettaContext) -> ApiResu
     let block_index =
         get_block_index_from_request(&server_context, request.block_identifier).await?;
 
-    let (parent_transaction, block) =
-        get_block_by_index(server_context.block_cache()?.as_ref(), block_index).await?;
+    let block_cache = server_context.block_cache()?;
+    let (parent_transaction, block) = get_block_by_index(block_cache.as_ref(), block_index).await?;
 
-    let block = build_block(parent_transaction, block).await?;
+    let block = build_block(block_cache.as_ref(), parent_transaction, block).await?;
 
     Ok(BlockResponse {
         block: Some(block),
@@ -59,6 +59,7 @@ async fn block(request: BlockRequest, server_context: RosettaContext) -> ApiResu
 
 /// Build up the transaction, which should contain the `operations` as the change set
 async fn build_block(
+    block_cache: &BlockCache,
     parent_block_identifier: BlockIdentifier,
     block: aptos_rest_client::aptos_api_types::Block,
 ) -> ApiResult<Block> {
@@ -70,7 +71,7 @@ async fn build_block(
     let mut transactions: Vec<Transaction> = Vec::new();
     if let Some(txns) = block.transactions {
         for txn in txns {
-            transactions.push(Transaction::from_transaction(txn).await?)
+            transactions.push(block_cache.to_rosetta_transaction(txn).await?)
         }
     }
 
@@ -87,6 +88,7 @@ async fn get_block_by_index(
     block_cache: &BlockCache,
     block_height: u64,
 ) -> ApiResult<(BlockIdentifier, aptos_rest_client::aptos_api_types::Block)> {
+    block_cache.check_block_height(block_height).await?;
     let block = block_cache.get_block_by_height(block_height, true).await?;
 
     // For the genesis block, we populate parent_block_identifier with the
@@ -194,6 +196,55 @@ impl BlockCache {
         Ok(block)
     }
 
+    /// Fails with [`ApiError::BlockNotFound`] if the block at `height` doesn't exist yet
+    ///
+    /// Blocks up to the highest one cached are known to exist, past it the node is asked for its
+    /// latest block, which is behind the requested one if the node is still catching up.
+    pub async fn check_block_height(&self, height: u64) -> ApiResult<()> {
+        let known_head = self.blocks.read().unwrap().keys().next_back().copied();
+        if known_head.map_or(false, |known_head| height <= known_head) {
+            return Ok(());
+        }
+
+        let latest_height = self
+            .rest_client
+            .get_ledger_information()
+            .await?
+            .into_inner()
+            .block_height;
+        if height > latest_height {
+            Err(ApiError::BlockNotFound(Some(format!(
+                "Block {} is beyond the node's latest block {}",
+                height, latest_height
+            ))))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Converts `txn` to a Rosetta transaction, with the abort details of a failed user
+    /// transaction taken from its execution status
+    pub async fn to_rosetta_transaction(
+        &self,
+        txn: aptos_rest_client::Transaction,
+    ) -> ApiResult<Transaction> {
+        let status = match &txn {
+            aptos_rest_client::Transaction::UserTransaction(user_txn) if !user_txn.info.success => {
+                match self
+                    .rest_client
+                    .get_transaction_by_version_bcs(user_txn.info.version.0)
+                    .await?
+                    .into_inner()
+                {
+                    TransactionData::OnChain(txn) => Some(txn.info.status().clone()),
+                    TransactionData::Pending(_) => None,
+                }
+            }
+            _ => None,
+        };
+        Transaction::from_transaction_with_status(txn, status).await
+    }
+
     /// Retrieve the block info for the hash
     ///
     /// This is particularly bad, since there's no index on this value.  It can only be derived
@@ -214,3 +265,73 @@ impl BlockCache {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::Error;
+    use aptos_rest_client::aptos_api_types::{
+        X_APTOS_BLOCK_HEIGHT, X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_OLDEST_VERSION,
+        X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION, X_APTOS_OLDEST_BLOCK_HEIGHT,
+    };
+    use aptos_types::chain_id::ChainId;
+    use serde_json::json;
+    use warp::{http::StatusCode, Reply};
+
+    const NODE_BLOCK_HEIGHT: u64 = 10;
+
+    /// A node which is at block `NODE_BLOCK_HEIGHT`, and only serves its ledger information
+    fn lagging_node(chain_id: ChainId) -> url::Url {
+        let state = [
+            (X_APTOS_CHAIN_ID, chain_id.id().to_string()),
+            (X_APTOS_EPOCH, "1".to_string()),
+            (X_APTOS_LEDGER_VERSION, "100".to_string()),
+            (X_APTOS_LEDGER_OLDEST_VERSION, "0".to_string()),
+            (X_APTOS_LEDGER_TIMESTAMP, "1660000000000000".to_string()),
+            (X_APTOS_BLOCK_HEIGHT, NODE_BLOCK_HEIGHT.to_string()),
+            (X_APTOS_OLDEST_BLOCK_HEIGHT, "0".to_string()),
+        ];
+        let index = warp::path!("v1").map(move || {
+            let mut response = warp::reply::json(&json!({
+                "chain_id": chain_id.id(),
+                "epoch": "1",
+                "ledger_version": "100",
+                "oldest_ledger_version": "0",
+                "ledger_timestamp": "1660000000000000",
+                "node_role": "full_node",
+                "oldest_block_height": "0",
+                "block_height": NODE_BLOCK_HEIGHT.to_string(),
+            }))
+            .into_response();
+            for (name, value) in &state {
+                response.headers_mut().insert(*name, value.parse().unwrap());
+            }
+            response
+        });
+        let (address, server) = warp::serve(index).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        format!("http://{}", address).parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_block_beyond_node_head() {
+        let chain_id = ChainId::test();
+        let rest_client = Arc::new(aptos_rest_client::Client::new(lagging_node(chain_id)));
+        let context = RosettaContext {
+            rest_client: Some(rest_client.clone()),
+            block_cache: Some(Arc::new(BlockCache::new(rest_client))),
+            ..RosettaContext::offline(chain_id)
+        };
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/block")
+            .json(&BlockRequest::by_index(chain_id, NODE_BLOCK_HEIGHT + 5))
+            .reply(&block_route(context))
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let error: Error = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(error.code, ApiError::BlockNotFound(None).code());
+        assert!(error.retriable);
+    }
+}
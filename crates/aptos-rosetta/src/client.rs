@@ -8,8 +8,8 @@ use crate::types::{
     ConstructionMetadataResponse, ConstructionParseRequest, ConstructionParseResponse,
     ConstructionPayloadsRequest, ConstructionPayloadsResponse, ConstructionPreprocessRequest,
     ConstructionPreprocessResponse, ConstructionSubmitRequest, ConstructionSubmitResponse, Error,
-    MetadataRequest, NetworkListResponse, NetworkOptionsResponse, NetworkRequest,
-    NetworkStatusResponse, TransactionIdentifierResponse,
+    EventsBlocksRequest, EventsBlocksResponse, MetadataRequest, NetworkListResponse,
+    NetworkOptionsResponse, NetworkRequest, NetworkStatusResponse, TransactionIdentifierResponse,
 };
 use anyhow::anyhow;
 use aptos_logger::debug;
@@ -101,6 +101,13 @@ impl RosettaClient {
         self.make_call("construction/submit", request).await
     }
 
+    pub async fn events_blocks(
+        &self,
+        request: &EventsBlocksRequest,
+    ) -> anyhow::Result<EventsBlocksResponse> {
+        self.make_call("events/blocks", request).await
+    }
+
     pub async fn network_list(&self) -> anyhow::Result<NetworkListResponse> {
         self.make_call("network/list", &MetadataRequest {}).await
     }
@@ -4,6 +4,7 @@
 use crate::types::{aptos_coin_module_identifier, aptos_coin_resource_identifier};
 use crate::{
     error::{ApiError, ApiResult},
+    log::log_request_body,
     types::{
         Currency, CurrencyMetadata, MetadataRequest, NetworkIdentifier, PartialBlockIdentifier,
     },
@@ -62,11 +63,14 @@ pub fn handle_request<'a, F, R, Req, Resp>(
 where
     F: FnOnce(Req, RosettaContext) -> R + Clone + Copy + Send + 'static,
     R: Future<Output = Result<Resp, ApiError>> + Send,
-    Req: Deserialize<'a> + Send + 'static,
+    Req: Deserialize<'a> + Serialize + Send + 'static,
     Resp: std::fmt::Debug + Serialize,
 {
     move |request, options| {
         let fut = async move {
+            if options.log_request_bodies {
+                log_request_body(&request);
+            }
             match handler(request, options).await {
                 Ok(response) => {
                     debug!(
@@ -327,6 +327,21 @@ async fn construction_parse(
                 && set_operator_function_identifier() == function_name
             {
                 parse_set_operator_operation(sender, &type_args, &args)?
+            } else if AccountAddress::ONE == *module.address()
+                && stake_module_identifier() == module_name
+                && set_delegated_voter_function_identifier() == function_name
+            {
+                parse_set_voter_operation(sender, &type_args, &args)?
+            } else if AccountAddress::ONE == *module.address()
+                && stake_module_identifier() == module_name
+                && add_stake_function_identifier() == function_name
+            {
+                parse_add_stake_operation(sender, &type_args, &args)?
+            } else if AccountAddress::ONE == *module.address()
+                && stake_module_identifier() == module_name
+                && withdraw_function_identifier() == function_name
+            {
+                parse_withdraw_stake_operation(sender, &type_args, &args)?
             } else {
                 return Err(ApiError::TransactionParseError(Some(
                     "Unsupported operation type",
@@ -448,6 +463,69 @@ fn parse_set_operator_operation(
     }
 }
 
+fn parse_set_voter_operation(
+    sender: AccountAddress,
+    type_args: &[TypeTag],
+    args: &[Vec<u8>],
+) -> ApiResult<Vec<Operation>> {
+    if !type_args.is_empty() {
+        return Err(ApiError::TransactionParseError(Some(
+            "Set voter should not have type arguments",
+        )));
+    }
+
+    if let Some(encoded_voter) = args.first() {
+        let voter: AccountAddress = bcs::from_bytes(encoded_voter)?;
+
+        Ok(vec![Operation::set_voter(0, None, sender, voter)])
+    } else {
+        Err(ApiError::InvalidOperations)
+    }
+}
+
+fn parse_add_stake_operation(
+    sender: AccountAddress,
+    type_args: &[TypeTag],
+    args: &[Vec<u8>],
+) -> ApiResult<Vec<Operation>> {
+    if !type_args.is_empty() {
+        return Err(ApiError::TransactionParseError(Some(
+            "Add stake should not have type arguments",
+        )));
+    }
+
+    if let Some(encoded_amount) = args.first() {
+        let amount: u64 = bcs::from_bytes(encoded_amount)?;
+
+        Ok(vec![
+            Operation::add_stake(0, None, sender, amount),
+            Operation::add_stake_deposit(1, None, sender, amount),
+        ])
+    } else {
+        Err(ApiError::InvalidOperations)
+    }
+}
+
+fn parse_withdraw_stake_operation(
+    sender: AccountAddress,
+    type_args: &[TypeTag],
+    args: &[Vec<u8>],
+) -> ApiResult<Vec<Operation>> {
+    if !type_args.is_empty() {
+        return Err(ApiError::TransactionParseError(Some(
+            "Withdraw stake should not have type arguments",
+        )));
+    }
+
+    if let Some(encoded_amount) = args.first() {
+        let amount: u64 = bcs::from_bytes(encoded_amount)?;
+
+        Ok(vec![Operation::withdraw_stake(0, None, sender, amount)])
+    } else {
+        Err(ApiError::InvalidOperations)
+    }
+}
+
 /// Construction payloads command (OFFLINE)
 ///
 /// Constructs payloads for given known operations
@@ -485,6 +563,18 @@ async fn construction_payloads(
             aptos_stdlib::stake_set_operator(set_operator.operator),
             set_operator.owner,
         ),
+        InternalOperation::SetVoter(set_voter) => (
+            aptos_stdlib::stake_set_delegated_voter(set_voter.voter),
+            set_voter.owner,
+        ),
+        InternalOperation::StakeAdd(stake_add) => (
+            aptos_stdlib::stake_add_stake(stake_add.amount),
+            stake_add.owner,
+        ),
+        InternalOperation::StakeWithdraw(stake_withdraw) => (
+            aptos_stdlib::stake_withdraw(stake_withdraw.amount),
+            stake_withdraw.owner,
+        ),
     };
 
     // Build the transaction and make it ready for signing
@@ -50,6 +50,10 @@ pub enum ApiError {
     BlockIncomplete,
     #[error("Transaction cannot be parsed")]
     TransactionParseError(Option<&'static str>),
+    #[error("Unsupported sub-account")]
+    UnsupportedSubAccount(Option<String>),
+    #[error("Block not found, the node may not have reached it yet")]
+    BlockNotFound(Option<String>),
 }
 
 impl ApiError {
@@ -75,6 +79,8 @@ impl ApiError {
             UnsupportedCurrency(None),
             UnsupportedSignatureCount(None),
             TransactionParseError(None),
+            UnsupportedSubAccount(None),
+            BlockNotFound(None),
         ]
     }
 
@@ -100,6 +106,8 @@ impl ApiError {
             UnsupportedSignatureCount(_) => 17,
             TransactionParseError(_) => 18,
             RetriableAptosError(_) => 19,
+            UnsupportedSubAccount(_) => 20,
+            BlockNotFound(_) => 21,
         }
     }
 
@@ -108,6 +116,7 @@ impl ApiError {
             self,
             ApiError::AccountNotFound(_)
                 | ApiError::BlockIncomplete
+                | ApiError::BlockNotFound(_)
                 | ApiError::RetriableAptosError(_)
         )
     }
@@ -115,7 +124,7 @@ impl ApiError {
     pub fn status_code(&self) -> StatusCode {
         use ApiError::*;
         match self {
-            AccountNotFound(_) => StatusCode::NOT_FOUND,
+            AccountNotFound(_) | BlockNotFound(_) => StatusCode::NOT_FOUND,
             BlockIncomplete => StatusCode::PRECONDITION_FAILED,
             NodeIsOffline => StatusCode::METHOD_NOT_ALLOWED,
             // TODO: Improve the error codes for these
@@ -150,6 +159,8 @@ impl From<&ApiError> for types::Error {
             ApiError::UnsupportedCurrency(details) => details.clone(),
             ApiError::UnsupportedSignatureCount(details) => details.map(|inner| inner.to_string()),
             ApiError::TransactionParseError(details) => details.map(|inner| inner.to_string()),
+            ApiError::UnsupportedSubAccount(details) => details.clone(),
+            ApiError::BlockNotFound(details) => details.clone(),
             _ => None,
         }
         .map(|details| ErrorDetails { details });
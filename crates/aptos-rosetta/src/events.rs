@@ -0,0 +1,249 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rosetta Events API
+//!
+//! See: [Events API Spec](https://www.rosetta-api.org/docs/EventsApi.html)
+//!
+
+use crate::{
+    block::BlockCache,
+    common::{check_network, handle_request, with_context},
+    error::ApiResult,
+    types::{
+        BlockEvent, BlockEventType, BlockIdentifier, EventsBlocksRequest, EventsBlocksResponse,
+    },
+    RosettaContext,
+};
+use aptos_logger::{debug, trace, warn};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+use warp::Filter;
+
+/// Number of recent block events kept for requests with an offset in the past
+const MAX_EVENTS: usize = 1000;
+/// Maximum number of block events returned by one request
+const MAX_LIMIT: u64 = 100;
+/// How long a request waits for a new block before returning no events
+const LONG_POLL_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often the node is checked for new blocks
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub fn blocks_route(
+    server_context: RosettaContext,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("events" / "blocks")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_context(server_context))
+        .and_then(handle_request(events_blocks))
+}
+
+/// Retrieves the block events starting at the requested offset.
+///
+/// If there are no such events yet, this waits for the next block to be added, so clients
+/// don't have to keep polling `/block`.
+///
+/// [API Spec](https://www.rosetta-api.org/docs/EventsApi.html#eventsblocks)
+async fn events_blocks(
+    request: EventsBlocksRequest,
+    server_context: RosettaContext,
+) -> ApiResult<EventsBlocksResponse> {
+    debug!("/events/blocks");
+    trace!(
+        request = ?request,
+        server_context = ?server_context,
+        "/events/blocks",
+    );
+
+    check_network(request.network_identifier, &server_context)?;
+
+    let block_events = server_context.block_events()?;
+    let limit = request.limit.unwrap_or(MAX_LIMIT).min(MAX_LIMIT) as usize;
+    let offset = request.offset.unwrap_or_else(|| {
+        block_events
+            .max_sequence()
+            .map_or(0, |max_sequence| max_sequence + 1)
+    });
+    let events = block_events.wait_for_events(offset, limit).await;
+
+    Ok(EventsBlocksResponse {
+        max_sequence: block_events.max_sequence().unwrap_or(0),
+        events,
+    })
+}
+
+/// Recent block events, which notifies the requests waiting on new blocks
+#[derive(Debug)]
+pub struct BlockEvents {
+    events: RwLock<VecDeque<BlockEvent>>,
+    sender: broadcast::Sender<u64>,
+}
+
+impl BlockEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(MAX_EVENTS);
+        Self {
+            events: RwLock::new(VecDeque::with_capacity(MAX_EVENTS)),
+            sender,
+        }
+    }
+
+    /// Sequence of the latest block event
+    pub fn max_sequence(&self) -> Option<u64> {
+        self.events
+            .read()
+            .unwrap()
+            .back()
+            .map(|event| event.sequence)
+    }
+
+    /// Records a block event, and notifies the requests waiting on it
+    pub fn add(&self, event: BlockEvent) {
+        let sequence = event.sequence;
+        {
+            let mut events = self.events.write().unwrap();
+            if events.len() == MAX_EVENTS {
+                events.pop_front();
+            }
+            events.push_back(event);
+        }
+        // It's fine if no request is waiting
+        let _ = self.sender.send(sequence);
+    }
+
+    fn events_from(&self, offset: u64, limit: usize) -> Vec<BlockEvent> {
+        self.events
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|event| event.sequence >= offset)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the events starting at `offset`, waiting for one if there are none yet
+    pub async fn wait_for_events(&self, offset: u64, limit: usize) -> Vec<BlockEvent> {
+        // Subscribe before looking at the recent events, so no event is missed in between
+        let mut receiver = self.sender.subscribe();
+        let events = self.events_from(offset, limit);
+        if !events.is_empty() {
+            return events;
+        }
+
+        let wait = async {
+            loop {
+                match receiver.recv().await {
+                    Ok(sequence) if sequence >= offset => return true,
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return false,
+                }
+            }
+        };
+        match tokio::time::timeout(LONG_POLL_TIMEOUT, wait).await {
+            Ok(true) => self.events_from(offset, limit),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Watches the node for new blocks, and adds a [`BlockEvent`] for each of them
+///
+/// Blocks are final in Aptos, so there are only ever [`BlockEventType::BlockAdded`] events,
+/// and their sequence is the block height.
+pub async fn index_blocks(
+    rest_client: Arc<aptos_rest_client::Client>,
+    block_cache: Arc<BlockCache>,
+    block_events: Arc<BlockEvents>,
+) {
+    let mut next_height = None;
+    loop {
+        match rest_client.get_ledger_information().await {
+            Ok(state) => {
+                let latest_height = state.into_inner().block_height;
+                for height in next_height.unwrap_or(latest_height)..=latest_height {
+                    match block_event(&block_cache, height).await {
+                        Ok(event) => {
+                            block_events.add(event);
+                            next_height = Some(height + 1);
+                        }
+                        Err(err) => {
+                            warn!("Failed to retrieve block {}: {:?}", height, err);
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(err) => warn!("Failed to retrieve the latest block height: {}", err),
+        }
+        tokio::time::sleep(BLOCK_POLL_INTERVAL).await;
+    }
+}
+
+async fn block_event(block_cache: &BlockCache, height: u64) -> ApiResult<BlockEvent> {
+    let block = block_cache.get_block_by_height(height, true).await?;
+    let block_identifier = BlockIdentifier::from_block(&block);
+
+    let mut transactions = Vec::new();
+    for txn in block.transactions.into_iter().flatten() {
+        transactions.push(block_cache.to_rosetta_transaction(txn).await?);
+    }
+
+    Ok(BlockEvent {
+        sequence: height,
+        block_identifier,
+        block_event_type: BlockEventType::BlockAdded,
+        transactions,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn block_added(sequence: u64) -> BlockEvent {
+        BlockEvent {
+            sequence,
+            block_identifier: BlockIdentifier {
+                index: sequence,
+                hash: format!("{:064x}", sequence),
+            },
+            block_event_type: BlockEventType::BlockAdded,
+            transactions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_block_events() {
+        let block_events = Arc::new(BlockEvents::new());
+        assert_eq!(block_events.max_sequence(), None);
+        for sequence in 0..3 {
+            block_events.add(block_added(sequence));
+        }
+        assert_eq!(block_events.max_sequence(), Some(2));
+
+        // Past events are returned right away
+        assert_eq!(
+            block_events.wait_for_events(1, 10).await,
+            vec![block_added(1), block_added(2)]
+        );
+        assert_eq!(
+            block_events.wait_for_events(0, 1).await,
+            vec![block_added(0)]
+        );
+
+        // Otherwise the request waits for the next block
+        let waiting = {
+            let block_events = block_events.clone();
+            tokio::spawn(async move { block_events.wait_for_events(3, 10).await })
+        };
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        block_events.add(block_added(3));
+        assert_eq!(waiting.await.unwrap(), vec![block_added(3)]);
+    }
+}
@@ -10,13 +10,14 @@ use crate::{
     block::BlockCache,
     common::{handle_request, with_context},
     error::{ApiError, ApiResult},
+    events::BlockEvents,
 };
 use aptos_config::config::ApiConfig;
 use aptos_logger::debug;
 use aptos_types::account_address::AccountAddress;
 use aptos_types::chain_id::ChainId;
+use aptos_warp_webserver::Error;
 use aptos_warp_webserver::WebServer;
-use aptos_warp_webserver::{logger, Error};
 use std::collections::BTreeMap;
 use std::{convert::Infallible, sync::Arc};
 use tokio::sync::Mutex;
@@ -30,6 +31,8 @@ use warp::{
 mod account;
 mod block;
 mod construction;
+mod events;
+mod log;
 mod network;
 
 pub mod client;
@@ -37,6 +40,8 @@ pub mod common;
 pub mod error;
 pub mod types;
 
+pub use account::CoinCacheConfig;
+
 pub const NODE_VERSION: &str = "0.1";
 pub const ROSETTA_VERSION: &str = "1.4.12";
 
@@ -53,10 +58,31 @@ pub struct RosettaContext {
     pub coin_cache: Arc<CoinCache>,
     /// Block index cache
     pub block_cache: Option<Arc<BlockCache>>,
+    /// Recent block events, for streaming new blocks
+    pub block_events: Option<Arc<BlockEvents>>,
     pub accounts: Arc<Mutex<BTreeMap<AccountAddress, SequenceNumber>>>,
+    /// Whether to log request bodies at trace level
+    pub log_request_bodies: bool,
+    /// Origins allowed to make cross-origin requests, any origin if `None`
+    pub cors_allowed_origins: Option<Vec<String>>,
 }
 
 impl RosettaContext {
+    /// A context without a node, for tests
+    #[cfg(test)]
+    pub(crate) fn offline(chain_id: ChainId) -> Self {
+        RosettaContext {
+            rest_client: None,
+            chain_id,
+            coin_cache: Arc::new(CoinCache::new()),
+            block_cache: None,
+            block_events: None,
+            accounts: Arc::new(Mutex::new(BTreeMap::new())),
+            log_request_bodies: false,
+            cors_allowed_origins: None,
+        }
+    }
+
     fn rest_client(&self) -> ApiResult<Arc<aptos_rest_client::Client>> {
         if let Some(ref client) = self.rest_client {
             Ok(client.clone())
@@ -72,6 +98,14 @@ impl RosettaContext {
             Err(ApiError::NodeIsOffline)
         }
     }
+
+    fn block_events(&self) -> ApiResult<Arc<BlockEvents>> {
+        if let Some(ref block_events) = self.block_events {
+            Ok(block_events.clone())
+        } else {
+            Err(ApiError::NodeIsOffline)
+        }
+    }
 }
 
 /// Creates HTTP server (warp-based) for Rosetta
@@ -79,7 +113,10 @@ pub fn bootstrap(
     chain_id: ChainId,
     api_config: ApiConfig,
     rest_client: Option<aptos_rest_client::Client>,
+    coin_cache_config: CoinCacheConfig,
 ) -> anyhow::Result<tokio::runtime::Runtime> {
+    // Fail before starting up, warp panics on invalid origins
+    cors_allowed_origins(&api_config)?;
     let runtime = tokio::runtime::Builder::new_multi_thread()
         .thread_name("rosetta")
         .enable_all()
@@ -88,7 +125,12 @@ pub fn bootstrap(
 
     debug!("Starting up Rosetta server with {:?}", api_config);
 
-    runtime.spawn(bootstrap_async(chain_id, api_config, rest_client));
+    runtime.spawn(bootstrap_async(
+        chain_id,
+        api_config,
+        rest_client,
+        coin_cache_config,
+    ));
     Ok(runtime)
 }
 
@@ -97,8 +139,11 @@ pub async fn bootstrap_async(
     chain_id: ChainId,
     api_config: ApiConfig,
     rest_client: Option<aptos_rest_client::Client>,
+    coin_cache_config: CoinCacheConfig,
 ) -> anyhow::Result<JoinHandle<()>> {
     debug!("Starting up Rosetta server with {:?}", api_config);
+    let log_request_bodies = api_config.log_request_bodies;
+    let cors_allowed_origins = cors_allowed_origins(&api_config)?;
     let api = WebServer::from(api_config);
     let handle = tokio::spawn(async move {
         // If it's Online mode, add the block cache
@@ -107,12 +152,41 @@ pub async fn bootstrap_async(
             .as_ref()
             .map(|rest_client| Arc::new(BlockCache::new(rest_client.clone())));
 
+        // If it's Online mode, watch for new blocks to stream their events
+        let block_events =
+            if let (Some(rest_client), Some(block_cache)) = (&rest_client, &block_cache) {
+                let block_events = Arc::new(BlockEvents::new());
+                tokio::spawn(events::index_blocks(
+                    rest_client.clone(),
+                    block_cache.clone(),
+                    block_events.clone(),
+                ));
+                Some(block_events)
+            } else {
+                None
+            };
+
+        // If it's Online mode, look up the configured currencies before the first request
+        let coin_cache = Arc::new(CoinCache::from_config(&coin_cache_config));
+        if let Some(rest_client) = &rest_client {
+            let rest_client = rest_client.clone();
+            let coin_cache = coin_cache.clone();
+            tokio::spawn(async move {
+                coin_cache
+                    .preload(&rest_client, &coin_cache_config.preload)
+                    .await
+            });
+        }
+
         let context = RosettaContext {
             rest_client: rest_client.clone(),
             chain_id,
-            coin_cache: Arc::new(CoinCache::new()),
+            coin_cache,
             block_cache,
+            block_events,
             accounts: Arc::new(Mutex::new(BTreeMap::new())),
+            log_request_bodies,
+            cors_allowed_origins,
         };
         api.serve(routes(context)).await;
     });
@@ -123,6 +197,8 @@ pub async fn bootstrap_async(
 pub fn routes(
     context: RosettaContext,
 ) -> impl Filter<Extract = impl Reply, Error = Infallible> + Clone {
+    let cors = cors(context.cors_allowed_origins.as_deref());
+    let allow_any_origin = context.cors_allowed_origins.is_none();
     account::routes(context.clone())
         .or(block::block_route(context.clone()))
         .or(construction::combine_route(context.clone()))
@@ -133,22 +209,62 @@ pub fn routes(
         .or(construction::payloads_route(context.clone()))
         .or(construction::preprocess_route(context.clone()))
         .or(construction::submit_route(context.clone()))
+        .or(events::blocks_route(context.clone()))
         .or(network::list_route(context.clone()))
         .or(network::options_route(context.clone()))
         .or(network::status_route(context.clone()))
         .or(health_check_route(context))
-        .with(
-            warp::cors()
-                .allow_any_origin()
-                .allow_methods(vec![Method::GET, Method::POST])
-                .allow_headers(vec![warp::http::header::CONTENT_TYPE]),
-        )
-        .with(logger())
-        .recover(handle_rejection)
+        // Errors are recovered from before the CORS filter, so they're readable by allowed origins
+        .recover(move |err| handle_rejection(err, allow_any_origin))
+        .with(cors)
+        .with(log::logger())
+        // Recovers from the CORS filter forbidding an origin
+        .recover(move |err| handle_rejection(err, allow_any_origin))
+}
+
+/// The validated origins allowed by `api_config`, see [`parse_origin`]
+fn cors_allowed_origins(api_config: &ApiConfig) -> anyhow::Result<Option<Vec<String>>> {
+    api_config
+        .cors_allowed_origins
+        .as_ref()
+        .map(|origins| {
+            origins
+                .iter()
+                .map(|origin| parse_origin(origin))
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .transpose()
+}
+
+/// Parses an origin as sent by browsers, i.e. a scheme, host and optional port
+pub fn parse_origin(origin: &str) -> anyhow::Result<String> {
+    let url = url::Url::parse(origin)?;
+    let origin_only = url.origin().ascii_serialization();
+    if !url.origin().is_tuple() || url.as_str().trim_end_matches('/') != origin_only {
+        anyhow::bail!(
+            "Invalid origin {}, expected a scheme, host and optional port only, e.g. https://example.com",
+            origin
+        );
+    }
+    Ok(origin_only)
+}
+
+/// CORS for `allowed_origins`, or for any origin if there are none
+fn cors(allowed_origins: Option<&[String]>) -> warp::cors::Builder {
+    let cors = warp::cors()
+        .allow_methods(vec![Method::GET, Method::POST])
+        .allow_headers(vec![warp::http::header::CONTENT_TYPE]);
+    match allowed_origins {
+        Some(origins) => cors.allow_origins(origins.iter().map(String::as_str)),
+        None => cors.allow_any_origin(),
+    }
 }
 
 /// Handle error codes from warp
-async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
+async fn handle_rejection(
+    err: Rejection,
+    allow_any_origin: bool,
+) -> Result<impl Reply, Infallible> {
     let code;
     let body;
 
@@ -180,13 +296,16 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
         body = reply::json(&Error::new(code, format!("unexpected error: {:?}", err)));
     }
     let mut rep = reply::with_status(body, code).into_response();
-    rep.headers_mut()
-        .insert("access-control-allow-origin", HeaderValue::from_static("*"));
+    // With restricted origins, the CORS filter sets the header for allowed origins
+    if allow_any_origin {
+        rep.headers_mut()
+            .insert("access-control-allow-origin", HeaderValue::from_static("*"));
+    }
     Ok(rep)
 }
 
 /// These parameters are directly passed onto the underlying rest server for a healthcheck
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, serde::Serialize)]
 struct HealthCheckParams {
     pub duration_secs: Option<u64>,
 }
@@ -215,3 +334,91 @@ async fn health_check(
 
     Ok("aptos-node:ok")
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use warp::{
+        http::{
+            header::{ACCESS_CONTROL_ALLOW_ORIGIN, ORIGIN},
+            Response,
+        },
+        hyper::body::Bytes,
+    };
+
+    const ALLOWED_ORIGIN: &str = "https://wallet.example.com";
+
+    fn offline_context(cors_allowed_origins: Option<Vec<String>>) -> RosettaContext {
+        RosettaContext {
+            cors_allowed_origins,
+            ..RosettaContext::offline(ChainId::test())
+        }
+    }
+
+    async fn network_list_from(context: RosettaContext, origin: &str) -> Response<Bytes> {
+        warp::test::request()
+            .method("POST")
+            .path("/network/list")
+            .header(ORIGIN, origin)
+            .json(&serde_json::json!({}))
+            .reply(&routes(context))
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_cors_allowed_origins() {
+        let context = offline_context(Some(vec![ALLOWED_ORIGIN.to_string()]));
+
+        let response = network_list_from(context.clone(), ALLOWED_ORIGIN).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN],
+            ALLOWED_ORIGIN
+        );
+
+        let response = network_list_from(context, "https://phishing.example.com").await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(response
+            .headers()
+            .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+    }
+
+    #[test]
+    fn test_parse_origin() {
+        assert_eq!(parse_origin(ALLOWED_ORIGIN).unwrap(), ALLOWED_ORIGIN);
+        assert_eq!(
+            parse_origin("https://wallet.example.com:8443/").unwrap(),
+            "https://wallet.example.com:8443"
+        );
+        assert!(parse_origin("wallet.example.com").is_err());
+        assert!(parse_origin("https://wallet.example.com/path").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_rejects_invalid_config_origins() {
+        let api_config = ApiConfig {
+            cors_allowed_origins: Some(vec![
+                ALLOWED_ORIGIN.to_string(),
+                "https://wallet.example.com/path".to_string(),
+            ]),
+            ..ApiConfig::default()
+        };
+        assert!(bootstrap_async(
+            ChainId::test(),
+            api_config,
+            None,
+            CoinCacheConfig::default()
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cors_allows_any_origin_by_default() {
+        let response =
+            network_list_from(offline_context(None), "https://phishing.example.com").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::{error, info, trace, warn, Schema};
+use serde::Serialize;
+use serde_json::Value;
+use warp::log::{custom, Info, Log};
+
+/// Fields of request bodies that are replaced with [`REDACTED`] before being logged
+const REDACTED_FIELDS: [&str; 2] = ["signatures", "signed_transaction"];
+const REDACTED: &str = "<redacted>";
+
+/// Logs every request at `info`, client errors at `warn` and server errors at `error`
+pub fn logger() -> Log<impl Fn(Info) + Copy> {
+    custom(|info: Info| {
+        let status = info.status();
+        let log = RosettaRequestLog {
+            method: info.method().to_string(),
+            path: info.path(),
+            status: status.as_u16(),
+            elapsed: info.elapsed(),
+        };
+        if status.is_server_error() {
+            error!(log);
+        } else if status.is_client_error() {
+            warn!(log);
+        } else {
+            info!(log);
+        }
+    })
+}
+
+#[derive(Schema)]
+pub struct RosettaRequestLog<'a> {
+    method: String,
+    path: &'a str,
+    status: u16,
+    #[schema(debug)]
+    elapsed: std::time::Duration,
+}
+
+/// Logs a request body at `trace`, with any signatures redacted
+pub fn log_request_body<Req: Serialize>(request: &Req) {
+    match serde_json::to_value(request) {
+        Ok(mut body) => {
+            redact(&mut body);
+            trace!("Request: {}", body);
+        }
+        Err(err) => trace!("Request: failed to serialize body: {}", err),
+    }
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_FIELDS.contains(&key.as_str()) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact() {
+        let mut body = json!({
+            "network_identifier": {"blockchain": "aptos", "network": "TESTING"},
+            "unsigned_transaction": "00",
+            "signatures": [{"hex_bytes": "01"}],
+            "nested": [{"signed_transaction": "02"}],
+        });
+        redact(&mut body);
+        assert_eq!(
+            body,
+            json!({
+                "network_identifier": {"blockchain": "aptos", "network": "TESTING"},
+                "unsigned_transaction": "00",
+                "signatures": REDACTED,
+                "nested": [{"signed_transaction": REDACTED}],
+            })
+        );
+    }
+}
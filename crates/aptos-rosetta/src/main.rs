@@ -5,7 +5,8 @@
 
 use aptos_config::config::ApiConfig;
 use aptos_node::AptosNodeArgs;
-use aptos_rosetta::bootstrap;
+use aptos_rosetta::{bootstrap, parse_origin, CoinCacheConfig};
+use aptos_sdk::move_types::{language_storage::TypeTag, parser::parse_type_tag};
 use aptos_types::chain_id::ChainId;
 use clap::Parser;
 use std::{
@@ -78,8 +79,13 @@ async fn main() {
 
     println!("aptos-rosetta: Starting rosetta");
     // Ensure runtime for Rosetta is up and running
-    let _rosetta = bootstrap(args.chain_id(), args.api_config(), args.rest_client())
-        .expect("aptos-rosetta: Should bootstrap rosetta server");
+    let _rosetta = bootstrap(
+        args.chain_id(),
+        args.api_config(),
+        args.rest_client(),
+        args.coin_cache_config(),
+    )
+    .expect("aptos-rosetta: Should bootstrap rosetta server");
 
     println!("aptos-rosetta: Rosetta started");
     // Run until there is an interrupt
@@ -99,6 +105,9 @@ trait ServerArgs {
 
     /// Retrieve the chain id
     fn chain_id(&self) -> ChainId;
+
+    /// Retrieve the configuration of the coin cache
+    fn coin_cache_config(&self) -> CoinCacheConfig;
 }
 
 /// Aptos Rosetta API Server
@@ -139,6 +148,14 @@ impl ServerArgs for CommandArgs {
             CommandArgs::Online(args) => args.chain_id(),
         }
     }
+
+    fn coin_cache_config(&self) -> CoinCacheConfig {
+        match self {
+            CommandArgs::OnlineRemote(args) => args.coin_cache_config(),
+            CommandArgs::Offline(args) => args.coin_cache_config(),
+            CommandArgs::Online(args) => args.coin_cache_config(),
+        }
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -155,6 +172,14 @@ pub struct OfflineArgs {
     /// Limit to content length on all requests
     #[clap(long)]
     content_length_limit: Option<u64>,
+    /// Log request bodies at trace level, with signatures redacted
+    #[clap(long)]
+    log_request_bodies: bool,
+    /// Origin allowed to make cross-origin requests, e.g. https://explorer.example.com
+    ///
+    /// Can be given multiple times.  Any origin is allowed if none are given.
+    #[clap(long, parse(try_from_str = parse_origin))]
+    cors_allowed_origin: Vec<String>,
     /// ChainId to be used for the server e.g. TESTNET
     #[clap(long, default_value = "TESTING")]
     chain_id: ChainId,
@@ -169,6 +194,12 @@ impl ServerArgs for OfflineArgs {
             tls_key_path: self.tls_key_path.clone(),
             content_length_limit: self.content_length_limit,
             failpoints_enabled: false,
+            log_request_bodies: self.log_request_bodies,
+            cors_allowed_origins: if self.cors_allowed_origin.is_empty() {
+                None
+            } else {
+                Some(self.cors_allowed_origin.clone())
+            },
         }
     }
 
@@ -179,6 +210,10 @@ impl ServerArgs for OfflineArgs {
     fn chain_id(&self) -> ChainId {
         self.chain_id
     }
+
+    fn coin_cache_config(&self) -> CoinCacheConfig {
+        CoinCacheConfig::default()
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -188,6 +223,15 @@ pub struct OnlineRemoteArgs {
     /// URL for the Aptos REST API. e.g. https://fullnode.devnet.aptoslabs.com
     #[clap(long, default_value = "http://localhost:8080")]
     rest_api_url: url::Url,
+    /// Seconds before cached coin metadata is looked up again
+    #[clap(long, default_value_t = 600)]
+    coin_cache_ttl_secs: u64,
+    /// Seconds before a coin that wasn't found is looked up again
+    #[clap(long, default_value_t = 60)]
+    coin_cache_negative_ttl_secs: u64,
+    /// Coin types to look up at startup, e.g. 0x1234::coin::MyCoin
+    #[clap(long, parse(try_from_str = parse_type_tag))]
+    preload_currency: Vec<TypeTag>,
 }
 
 impl ServerArgs for OnlineRemoteArgs {
@@ -202,6 +246,14 @@ impl ServerArgs for OnlineRemoteArgs {
     fn chain_id(&self) -> ChainId {
         self.offline_args.chain_id
     }
+
+    fn coin_cache_config(&self) -> CoinCacheConfig {
+        CoinCacheConfig {
+            ttl: Some(Duration::from_secs(self.coin_cache_ttl_secs)),
+            negative_ttl: Some(Duration::from_secs(self.coin_cache_negative_ttl_secs)),
+            preload: self.preload_currency.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -226,4 +278,8 @@ impl ServerArgs for OnlineLocalArgs {
     fn chain_id(&self) -> ChainId {
         self.online_args.offline_args.chain_id
     }
+
+    fn coin_cache_config(&self) -> CoinCacheConfig {
+        self.online_args.coin_cache_config()
+    }
 }
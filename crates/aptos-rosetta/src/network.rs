@@ -2,11 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    common::{check_network, handle_request, with_context, with_empty_request},
+    common::{check_network, handle_request, native_coin, with_context, with_empty_request},
     error::ApiError,
     types::{
-        Allow, MetadataRequest, NetworkListResponse, NetworkOptionsResponse, NetworkRequest,
-        NetworkStatusResponse, OperationStatusType, OperationType, Version,
+        Allow, BalanceExemption, ExemptionType, MetadataRequest, NetworkListResponse,
+        NetworkOptionsResponse, NetworkRequest, NetworkStatusResponse, OperationStatusType,
+        OperationType, Version, STAKE_SUB_ACCOUNT,
     },
     RosettaContext, NODE_VERSION, ROSETTA_VERSION,
 };
@@ -95,7 +96,9 @@ async fn network_options(
         .into_iter()
         .map(|status| status.into())
         .collect();
-    let operation_types = OperationType::all()
+    let operation_types = OperationType::all();
+    let balance_exemptions = balance_exemptions(&operation_types);
+    let operation_types = operation_types
         .into_iter()
         .map(|op| op.to_string())
         .collect();
@@ -111,7 +114,7 @@ async fn network_options(
         historical_balance_lookup: true,
         timestamp_start_index: None,
         call_methods: vec![],
-        balance_exemptions: vec![],
+        balance_exemptions,
         mempool_coins: false,
         block_hash_case: None,
         transaction_hash_case: None,
@@ -122,6 +125,25 @@ async fn network_options(
     Ok(response)
 }
 
+/// Balance exemptions of the accounts the operations can change
+///
+/// Staking rewards are added to stake pools without any operation, so with staking operations,
+/// the balance of stake sub-accounts can grow without one.
+fn balance_exemptions(operation_types: &[OperationType]) -> Vec<BalanceExemption> {
+    if operation_types
+        .iter()
+        .any(|op| matches!(op, OperationType::StakeAdd | OperationType::StakeWithdraw))
+    {
+        vec![BalanceExemption {
+            sub_account_address: Some(STAKE_SUB_ACCOUNT.to_string()),
+            currency: Some(native_coin()),
+            exemption_type: Some(ExemptionType::GreaterOrEqual),
+        }]
+    } else {
+        vec![]
+    }
+}
+
 /// Get network status including the latest state
 ///
 /// This should respond with the latest ledger version, timestamp, and genesis information
@@ -168,3 +190,39 @@ async fn network_status(
 
     Ok(response)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_types::chain_id::ChainId;
+
+    #[tokio::test]
+    async fn test_network_options_balance_exemptions() {
+        let chain_id = ChainId::test();
+        let server_context = RosettaContext::offline(chain_id);
+        let request = NetworkRequest {
+            network_identifier: chain_id.into(),
+        };
+
+        let response = network_options(request, server_context).await.unwrap();
+        assert_eq!(
+            response.allow.balance_exemptions,
+            vec![BalanceExemption {
+                sub_account_address: Some(STAKE_SUB_ACCOUNT.to_string()),
+                currency: Some(native_coin()),
+                exemption_type: Some(ExemptionType::GreaterOrEqual),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_no_balance_exemptions_without_staking() {
+        assert!(balance_exemptions(&[
+            OperationType::CreateAccount,
+            OperationType::Deposit,
+            OperationType::Withdraw,
+            OperationType::Fee,
+        ])
+        .is_empty());
+    }
+}
@@ -29,10 +29,47 @@ pub struct AccountIdentifier {
 }
 
 impl AccountIdentifier {
+    /// The stake sub-account of `address`, holding the stake in its stake pool
+    pub fn stake_sub_account(address: AccountAddress) -> Self {
+        AccountIdentifier {
+            address: to_hex_lower(&address),
+            sub_account: Some(SubAccountIdentifier {
+                address: STAKE_SUB_ACCOUNT.to_string(),
+            }),
+        }
+    }
+
     /// Convert [`AccountIdentifier`] to an [`AccountAddress`]
     pub fn account_address(&self) -> ApiResult<AccountAddress> {
         self.try_into()
     }
+
+    /// Whether this is the stake sub-account, fails on sub-accounts that aren't supported
+    pub fn is_stake_sub_account(&self) -> ApiResult<bool> {
+        match self.sub_account {
+            None => Ok(false),
+            Some(SubAccountIdentifier { ref address }) if address == STAKE_SUB_ACCOUNT => Ok(true),
+            Some(SubAccountIdentifier { ref address }) => {
+                Err(ApiError::UnsupportedSubAccount(Some(address.clone())))
+            }
+        }
+    }
+
+    /// The part of the stake pool this sub-account holds, `None` if it isn't a sub-account,
+    /// fails on sub-accounts that aren't supported
+    pub fn stake_sub_account_kind(&self) -> ApiResult<Option<StakeSubAccount>> {
+        match self.sub_account {
+            None => Ok(None),
+            Some(SubAccountIdentifier { ref address }) => match address.as_str() {
+                STAKE_SUB_ACCOUNT => Ok(Some(StakeSubAccount::All)),
+                ACTIVE_STAKE_SUB_ACCOUNT => Ok(Some(StakeSubAccount::Active)),
+                INACTIVE_STAKE_SUB_ACCOUNT => Ok(Some(StakeSubAccount::Inactive)),
+                PENDING_ACTIVE_STAKE_SUB_ACCOUNT => Ok(Some(StakeSubAccount::PendingActive)),
+                PENDING_INACTIVE_STAKE_SUB_ACCOUNT => Ok(Some(StakeSubAccount::PendingInactive)),
+                _ => Err(ApiError::UnsupportedSubAccount(Some(address.clone()))),
+            },
+        }
+    }
 }
 
 impl TryFrom<&AccountIdentifier> for AccountAddress {
@@ -169,6 +206,28 @@ impl PartialBlockIdentifier {
     }
 }
 
+/// Name of the sub-account holding the stake in the stake pool of an account
+pub const STAKE_SUB_ACCOUNT: &str = "stake";
+/// Name of the sub-account holding the `active` stake of the stake pool of an account
+pub const ACTIVE_STAKE_SUB_ACCOUNT: &str = "active";
+/// Name of the sub-account holding the `inactive` stake of the stake pool of an account
+pub const INACTIVE_STAKE_SUB_ACCOUNT: &str = "inactive";
+/// Name of the sub-account holding the `pending_active` stake of the stake pool of an account
+pub const PENDING_ACTIVE_STAKE_SUB_ACCOUNT: &str = "pending_active";
+/// Name of the sub-account holding the `pending_inactive` stake of the stake pool of an account
+pub const PENDING_INACTIVE_STAKE_SUB_ACCOUNT: &str = "pending_inactive";
+
+/// The part of the stake pool of an account held by a stake sub-account
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StakeSubAccount {
+    /// The whole stake pool, as a single amount
+    All,
+    Active,
+    Inactive,
+    PendingActive,
+    PendingInactive,
+}
+
 /// Sub account identifier if there are sub accounts
 ///
 /// [API Spec](https://www.rosetta-api.org/docs/models/SubAccountIdentifier.html)
@@ -85,6 +85,10 @@ pub enum OperationType {
     Withdraw,
     Fee,
     SetOperator,
+    SetVoter,
+    StakeAdd,
+    StakeWithdraw,
+    GovernanceVote,
 }
 
 impl OperationType {
@@ -93,6 +97,10 @@ impl OperationType {
     const WITHDRAW: &'static str = "withdraw";
     const FEE: &'static str = "fee";
     const SET_OPERATOR: &'static str = "set_operator";
+    const SET_VOTER: &'static str = "set_voter";
+    const STAKE_ADD: &'static str = "stake_add";
+    const STAKE_WITHDRAW: &'static str = "stake_withdraw";
+    const GOVERNANCE_VOTE: &'static str = "governance_vote";
 
     pub fn all() -> Vec<OperationType> {
         vec![
@@ -101,6 +109,10 @@ impl OperationType {
             OperationType::Withdraw,
             OperationType::Fee,
             OperationType::SetOperator,
+            OperationType::SetVoter,
+            OperationType::StakeAdd,
+            OperationType::StakeWithdraw,
+            OperationType::GovernanceVote,
         ]
     }
 }
@@ -115,6 +127,10 @@ impl FromStr for OperationType {
             Self::WITHDRAW => Ok(OperationType::Withdraw),
             Self::FEE => Ok(OperationType::Fee),
             Self::SET_OPERATOR => Ok(OperationType::SetOperator),
+            Self::SET_VOTER => Ok(OperationType::SetVoter),
+            Self::STAKE_ADD => Ok(OperationType::StakeAdd),
+            Self::STAKE_WITHDRAW => Ok(OperationType::StakeWithdraw),
+            Self::GOVERNANCE_VOTE => Ok(OperationType::GovernanceVote),
             _ => Err(ApiError::DeserializationFailed(Some(format!(
                 "Invalid OperationType: {}",
                 s
@@ -130,6 +146,10 @@ impl Display for OperationType {
             OperationType::Deposit => Self::DEPOSIT,
             OperationType::Withdraw => Self::WITHDRAW,
             OperationType::SetOperator => Self::SET_OPERATOR,
+            OperationType::SetVoter => Self::SET_VOTER,
+            OperationType::StakeAdd => Self::STAKE_ADD,
+            OperationType::StakeWithdraw => Self::STAKE_WITHDRAW,
+            OperationType::GovernanceVote => Self::GOVERNANCE_VOTE,
             OperationType::Fee => Self::FEE,
         })
     }
@@ -22,6 +22,10 @@ pub fn stake_module_identifier() -> Identifier {
     ident_str!("stake").into()
 }
 
+pub fn aptos_governance_module_identifier() -> Identifier {
+    ident_str!("aptos_governance").into()
+}
+
 // Resource Identifiers
 pub fn account_resource_identifier() -> Identifier {
     ident_str!("Account").into()
@@ -43,6 +47,14 @@ pub fn stake_pool_resource_identifier() -> Identifier {
     ident_str!("StakePool").into()
 }
 
+pub fn owner_capability_resource_identifier() -> Identifier {
+    ident_str!("OwnerCapability").into()
+}
+
+pub fn governance_events_resource_identifier() -> Identifier {
+    ident_str!("GovernanceEvents").into()
+}
+
 // Function identifiers
 // Function identifiers
 pub fn create_account_function_identifier() -> Identifier {
@@ -57,6 +69,22 @@ pub fn set_operator_function_identifier() -> Identifier {
     ident_str!("set_operator").into()
 }
 
+pub fn set_delegated_voter_function_identifier() -> Identifier {
+    ident_str!("set_delegated_voter").into()
+}
+
+pub fn vote_function_identifier() -> Identifier {
+    ident_str!("vote").into()
+}
+
+pub fn add_stake_function_identifier() -> Identifier {
+    ident_str!("add_stake").into()
+}
+
+pub fn withdraw_function_identifier() -> Identifier {
+    ident_str!("withdraw").into()
+}
+
 // Field identifiers
 pub fn decimals_field_identifier() -> Identifier {
     ident_str!("decimals").into()
@@ -74,6 +102,26 @@ pub fn set_operator_events_field_identifier() -> Identifier {
     ident_str!("set_operator_events").into()
 }
 
+pub fn delegated_voter_field_identifier() -> Identifier {
+    ident_str!("delegated_voter").into()
+}
+
+pub fn vote_events_field_identifier() -> Identifier {
+    ident_str!("vote_events").into()
+}
+
+pub fn add_stake_events_field_identifier() -> Identifier {
+    ident_str!("add_stake_events").into()
+}
+
+pub fn unlock_stake_events_field_identifier() -> Identifier {
+    ident_str!("unlock_stake_events").into()
+}
+
+pub fn withdraw_stake_events_field_identifier() -> Identifier {
+    ident_str!("withdraw_stake_events").into()
+}
+
 pub fn sequence_number_field_identifier() -> Identifier {
     ident_str!("sequence_number").into()
 }
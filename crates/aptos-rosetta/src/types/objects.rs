@@ -7,10 +7,14 @@
 
 use crate::common::native_coin_tag;
 use crate::types::{
-    account_module_identifier, aptos_coin_module_identifier, aptos_coin_resource_identifier,
-    coin_module_identifier, create_account_function_identifier,
+    account_module_identifier, add_stake_events_field_identifier, aptos_coin_module_identifier,
+    aptos_coin_resource_identifier, aptos_governance_module_identifier, coin_module_identifier,
+    create_account_function_identifier, delegated_voter_field_identifier,
+    governance_events_resource_identifier, set_delegated_voter_function_identifier,
     set_operator_events_field_identifier, set_operator_function_identifier,
     stake_module_identifier, stake_pool_resource_identifier, transfer_function_identifier,
+    unlock_stake_events_field_identifier, vote_events_field_identifier, vote_function_identifier,
+    withdraw_stake_events_field_identifier,
 };
 use crate::{
     common::{is_native_coin, native_coin},
@@ -34,8 +38,15 @@ use aptos_rest_client::{
     aptos::Balance,
     aptos_api_types::{WriteSetChange, U64},
 };
-use aptos_types::{account_address::AccountAddress, event::EventKey};
-use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize};
+use aptos_sdk::move_types::identifier::Identifier;
+use aptos_types::{
+    account_address::AccountAddress, event::EventKey, transaction::ExecutionStatus,
+    vm_status::AbortLocation,
+};
+use serde::{
+    de::{DeserializeOwned, Error as SerdeError},
+    Deserialize, Deserializer, Serialize,
+};
 use std::{
     collections::HashMap,
     convert::{TryFrom, TryInto},
@@ -88,6 +99,35 @@ pub struct Amount {
     pub value: String,
     /// [`Currency`]
     pub currency: Currency,
+    /// Which part of a stake pool the amount is in, only used for the stake sub-account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<AmountMetadata>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AmountMetadata {
+    pub stake_type: StakeType,
+}
+
+/// The parts of a stake pool, named after the `StakePool` fields
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeType {
+    Active,
+    Inactive,
+    PendingActive,
+    PendingInactive,
+}
+
+impl Amount {
+    /// An amount of native coins in the stake sub-account
+    pub fn stake(value: String, stake_type: Option<StakeType>) -> Amount {
+        Amount {
+            value,
+            currency: native_coin(),
+            metadata: stake_type.map(|stake_type| AmountMetadata { stake_type }),
+        }
+    }
 }
 
 impl From<Balance> for Amount {
@@ -96,6 +136,7 @@ impl From<Balance> for Amount {
             value: balance.coin.value.to_string(),
             // TODO: Support other currencies
             currency: native_coin(),
+            metadata: None,
         }
     }
 }
@@ -224,9 +265,9 @@ pub enum Direction {
 
 /// Tells how balances can change without a specific transaction on the account
 ///
-/// Balance exemptions are not necessary, because staking rewards go to the staking
-/// pool and not to the account.  When they are removed from the pool, normal events
-/// for transfer will occur.
+/// Staking rewards go to the staking pool and not to the account, so the stake
+/// sub-account of a pool is exempted as `GreaterOrEqual`.  When they are removed from the
+/// pool, normal events for transfer will occur.
 ///
 /// [API Spec](https://www.rosetta-api.org/docs/models/ExemptionType.html)
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -323,6 +364,7 @@ impl Operation {
             Some(Amount {
                 value: amount.to_string(),
                 currency,
+                metadata: None,
             }),
             None,
         )
@@ -343,6 +385,7 @@ impl Operation {
             Some(Amount {
                 value: format!("-{}", amount),
                 currency,
+                metadata: None,
             }),
             None,
         )
@@ -362,11 +405,54 @@ impl Operation {
             Some(Amount {
                 value: format!("-{}", gas_used.saturating_mul(gas_price_per_unit)),
                 currency: native_coin(),
+                metadata: None,
             }),
             None,
         )
     }
 
+    /// A deposit into the stake sub-account of a stake pool
+    pub fn stake_deposit(
+        operation_index: u64,
+        status: Option<OperationStatusType>,
+        pool_address: AccountAddress,
+        amount: u64,
+        stake_type: Option<StakeType>,
+    ) -> Operation {
+        Operation {
+            account: Some(AccountIdentifier::stake_sub_account(pool_address)),
+            ..Operation::new(
+                OperationType::Deposit,
+                operation_index,
+                status,
+                pool_address,
+                Some(Amount::stake(amount.to_string(), stake_type)),
+                None,
+            )
+        }
+    }
+
+    /// A withdrawal from the stake sub-account of a stake pool
+    pub fn stake_withdraw(
+        operation_index: u64,
+        status: Option<OperationStatusType>,
+        pool_address: AccountAddress,
+        amount: u64,
+        stake_type: Option<StakeType>,
+    ) -> Operation {
+        Operation {
+            account: Some(AccountIdentifier::stake_sub_account(pool_address)),
+            ..Operation::new(
+                OperationType::Withdraw,
+                operation_index,
+                status,
+                pool_address,
+                Some(Amount::stake(format!("-{}", amount), stake_type)),
+                None,
+            )
+        }
+    }
+
     pub fn set_operator(
         operation_index: u64,
         status: Option<OperationStatusType>,
@@ -382,12 +468,119 @@ impl Operation {
             Some(OperationSpecificMetadata::set_operator(operator)),
         )
     }
+
+    /// Changes the voter delegated by the owner's stake pool
+    pub fn set_voter(
+        operation_index: u64,
+        status: Option<OperationStatusType>,
+        owner: AccountAddress,
+        voter: AccountAddress,
+    ) -> Operation {
+        Operation::new(
+            OperationType::SetVoter,
+            operation_index,
+            status,
+            owner,
+            None,
+            Some(OperationSpecificMetadata::set_voter(voter)),
+        )
+    }
+
+    /// A vote on a governance proposal, which doesn't change any balance
+    pub fn governance_vote(
+        operation_index: u64,
+        status: Option<OperationStatusType>,
+        voter: AccountAddress,
+        proposal_id: u64,
+        should_pass: bool,
+    ) -> Operation {
+        Operation::new(
+            OperationType::GovernanceVote,
+            operation_index,
+            status,
+            voter,
+            None,
+            Some(OperationSpecificMetadata::governance_vote(
+                proposal_id,
+                should_pass,
+            )),
+        )
+    }
+
+    /// Takes coins from the owner's account to add them to the owner's stake pool
+    ///
+    /// Paired with [`Operation::add_stake_deposit`] for the stake sub-account.
+    pub fn add_stake(
+        operation_index: u64,
+        status: Option<OperationStatusType>,
+        owner: AccountAddress,
+        amount: u64,
+    ) -> Operation {
+        Operation::new(
+            OperationType::StakeAdd,
+            operation_index,
+            status,
+            owner,
+            Some(Amount {
+                value: format!("-{}", amount),
+                currency: native_coin(),
+                metadata: None,
+            }),
+            None,
+        )
+    }
+
+    /// Adds the coins taken by [`Operation::add_stake`] to the owner's stake sub-account
+    pub fn add_stake_deposit(
+        operation_index: u64,
+        status: Option<OperationStatusType>,
+        owner: AccountAddress,
+        amount: u64,
+    ) -> Operation {
+        let mut operation = Operation::new(
+            OperationType::StakeAdd,
+            operation_index,
+            status,
+            owner,
+            Some(Amount {
+                value: amount.to_string(),
+                currency: native_coin(),
+                metadata: None,
+            }),
+            None,
+        );
+        operation.account = Some(AccountIdentifier::stake_sub_account(owner));
+        operation
+    }
+
+    /// Withdraws unlocked coins from the owner's stake pool back to the owner's account
+    pub fn withdraw_stake(
+        operation_index: u64,
+        status: Option<OperationStatusType>,
+        owner: AccountAddress,
+        amount: u64,
+    ) -> Operation {
+        Operation::new(
+            OperationType::StakeWithdraw,
+            operation_index,
+            status,
+            owner,
+            Some(Amount {
+                value: amount.to_string(),
+                currency: native_coin(),
+                metadata: None,
+            }),
+            None,
+        )
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum OperationSpecificMetadata {
     CreateAccount(CreateAccountArguments),
     SetOperator(SetOperatorArguments),
+    SetVoter(SetVoterArguments),
+    GovernanceVote(GovernanceVoteArguments),
 }
 
 impl OperationSpecificMetadata {
@@ -402,6 +595,19 @@ impl OperationSpecificMetadata {
             operator: operator.into(),
         })
     }
+
+    pub fn set_voter(voter: AccountAddress) -> OperationSpecificMetadata {
+        OperationSpecificMetadata::SetVoter(SetVoterArguments {
+            voter: voter.into(),
+        })
+    }
+
+    pub fn governance_vote(proposal_id: u64, should_pass: bool) -> OperationSpecificMetadata {
+        OperationSpecificMetadata::GovernanceVote(GovernanceVoteArguments {
+            proposal_id: U64(proposal_id),
+            should_pass,
+        })
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -415,6 +621,18 @@ pub struct SetOperatorArguments {
     operator: AccountIdentifier,
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SetVoterArguments {
+    voter: AccountIdentifier,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GovernanceVoteArguments {
+    proposal_id: U64,
+    /// Whether the vote is for or against the proposal
+    should_pass: bool,
+}
+
 /// Used for query operations to apply conditions.  Defaults to [`Operator::And`] if no value is
 /// present
 ///
@@ -548,10 +766,91 @@ pub struct Transaction {
     pub metadata: Option<TransactionMetadata>,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct TransactionMetadata {
     pub transaction_type: TransactionType,
     pub version: U64,
+    /// Why the transaction failed, only present for failed transactions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<TransactionFailure>,
+    /// Whether the transaction failed, only present for user transactions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed: Option<bool>,
+    /// VM status as reported by the node, only present for user transactions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vm_status: Option<String>,
+    /// Gas units used, only present for user transactions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<U64>,
+    /// Sequence number of the sender's transaction, only present for user transactions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence_number: Option<U64>,
+}
+
+/// Details of a failed transaction
+///
+/// Operation statuses only carry [`OperationStatusType::Failure`], this is what allows telling
+/// failures apart e.g. an insufficient balance vs a missing receiver account
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionFailure {
+    /// VM status as reported by the node
+    pub vm_status: String,
+    /// Abort details, only present if the transaction failed on a Move abort
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub abort: Option<MoveAbort>,
+}
+
+impl TransactionFailure {
+    /// `status` is the structured execution status of the transaction, the abort details are
+    /// only known if it's given
+    pub fn new(vm_status: String, status: Option<&ExecutionStatus>) -> TransactionFailure {
+        let abort = status.and_then(MoveAbort::from_execution_status);
+        TransactionFailure { vm_status, abort }
+    }
+}
+
+/// A Move abort, taken from the execution status
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MoveAbort {
+    /// Module that aborted e.g. `0x1::coin`, not present if the abort was in a script
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    /// Abort code
+    pub code: U64,
+    /// Name of the error constant from the module's error map e.g. `EINSUFFICIENT_BALANCE`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason_name: Option<String>,
+    /// Description of the error from the module's error map
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason_description: Option<String>,
+}
+
+impl MoveAbort {
+    /// The abort in `status`, if the transaction failed on a Move abort
+    pub fn from_execution_status(status: &ExecutionStatus) -> Option<MoveAbort> {
+        if let ExecutionStatus::MoveAbort {
+            location,
+            code,
+            info,
+        } = status
+        {
+            Some(MoveAbort {
+                module: match location {
+                    AbortLocation::Module(module_id) => Some(format!(
+                        "{}::{}",
+                        module_id.address().to_hex_literal(),
+                        module_id.name()
+                    )),
+                    AbortLocation::Script => None,
+                },
+                code: U64(*code),
+                reason_name: info.as_ref().map(|info| info.reason_name.clone()),
+                reason_description: info.as_ref().map(|info| info.description.clone()),
+            })
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -576,6 +875,15 @@ impl Display for TransactionType {
 
 impl Transaction {
     pub async fn from_transaction(txn: aptos_rest_client::Transaction) -> ApiResult<Transaction> {
+        Self::from_transaction_with_status(txn, None).await
+    }
+
+    /// Same as [`Transaction::from_transaction`], with the structured execution status of the
+    /// transaction, which the abort details of a failed transaction are taken from
+    pub async fn from_transaction_with_status(
+        txn: aptos_rest_client::Transaction,
+        status: Option<ExecutionStatus>,
+    ) -> ApiResult<Transaction> {
         use aptos_rest_client::Transaction::*;
         let (txn_type, maybe_user_transaction_request, txn_info, events) = match txn {
             // Pending transactions aren't supported by Rosetta (for now)
@@ -599,6 +907,8 @@ impl Transaction {
         // with no gaps
         let mut operations = vec![];
         let mut operation_index: u64 = 0;
+        let failure = (!txn_info.success)
+            .then(|| TransactionFailure::new(txn_info.vm_status.clone(), status.as_ref()));
         if txn_info.success {
             // Parse all operations from the writeset changes in a success
             for change in &txn_info.changes {
@@ -634,6 +944,8 @@ impl Transaction {
             ));
         }
 
+        // Other transactions don't charge gas, and can't fail
+        let is_user_txn = maybe_user_transaction_request.is_some();
         Ok(Transaction {
             transaction_identifier: (&txn_info).into(),
             operations,
@@ -641,6 +953,13 @@ impl Transaction {
             metadata: Some(TransactionMetadata {
                 transaction_type: txn_type,
                 version: txn_info.version,
+                failure,
+                failed: is_user_txn.then(|| !txn_info.success),
+                vm_status: is_user_txn.then(|| txn_info.vm_status.clone()),
+                gas_used: is_user_txn.then(|| txn_info.gas_used),
+                sequence_number: maybe_user_transaction_request
+                    .as_ref()
+                    .map(|request| request.sequence_number),
             }),
         })
     }
@@ -721,6 +1040,41 @@ fn parse_operations_from_txn_payload(
                 operator.into(),
                 sender,
             ));
+        } else if AccountAddress::ONE == *inner.function.module.address.inner()
+            && stake_module_identifier() == inner.function.module.name.0
+            && set_delegated_voter_function_identifier() == inner.function.name.0
+        {
+            if let Some(voter) = inner
+                .arguments
+                .get(0)
+                .and_then(|arg| serde_json::from_value::<Address>(arg.clone()).ok())
+            {
+                operations.push(Operation::set_voter(
+                    operation_index,
+                    Some(OperationStatusType::Failure),
+                    sender,
+                    voter.into(),
+                ));
+            }
+        } else if AccountAddress::ONE == *inner.function.module.address.inner()
+            && aptos_governance_module_identifier() == inner.function.module.name.0
+            && vote_function_identifier() == inner.function.name.0
+        {
+            // The arguments are the stake pool, the proposal id, and whether it should pass
+            let proposal_id = inner
+                .arguments
+                .get(1)
+                .and_then(|arg| serde_json::from_value::<U64>(arg.clone()).ok());
+            let should_pass = inner.arguments.get(2).and_then(serde_json::Value::as_bool);
+            if let (Some(proposal_id), Some(should_pass)) = (proposal_id, should_pass) {
+                operations.push(Operation::governance_vote(
+                    operation_index,
+                    Some(OperationStatusType::Failure),
+                    sender,
+                    proposal_id.0,
+                    should_pass,
+                ));
+            }
         }
     }
     operations
@@ -759,6 +1113,12 @@ fn parse_operations_from_write_set(
             stake_pool_resource_identifier().into(),
             vec![],
         );
+        let governance_events_tag = MoveStructTag::new(
+            AccountAddress::ONE.into(),
+            aptos_governance_module_identifier().into(),
+            governance_events_resource_identifier().into(),
+            vec![],
+        );
 
         if data.typ == account_tag {
             // Account sequence number increase (possibly creation)
@@ -800,6 +1160,101 @@ fn parse_operations_from_write_set(
                             operation_index += 1;
                         }
                     }
+                } else if id.0 == delegated_voter_field_identifier() {
+                    // Changing the voter doesn't emit an event, so the new voter is only known
+                    // to be a change when the transaction is the call changing it
+                    if calls_framework_function(
+                        maybe_request,
+                        stake_module_identifier(),
+                        set_delegated_voter_function_identifier(),
+                    ) {
+                        if let Ok(voter) = serde_json::from_value::<Address>(value.clone()) {
+                            operations.push(Operation::set_voter(
+                                operation_index,
+                                Some(OperationStatusType::Success),
+                                address,
+                                voter.into(),
+                            ));
+                            operation_index += 1;
+                        }
+                    }
+                } else if id.0 == add_stake_events_field_identifier() {
+                    // Added stake is either active or pending activation depending on whether
+                    // the pool is in the validator set, so it only counts towards the total
+                    if let Some(amount_added) =
+                        sum_event_amounts(events, value, |event: &AddStakeEvent| {
+                            event.amount_added.0
+                        })
+                    {
+                        operations.push(Operation::stake_deposit(
+                            operation_index,
+                            Some(OperationStatusType::Success),
+                            address,
+                            amount_added,
+                            None,
+                        ));
+                        operation_index += 1;
+                    }
+                } else if id.0 == unlock_stake_events_field_identifier() {
+                    // Unlocking moves active stake to pending deactivation
+                    if let Some(amount_unlocked) =
+                        sum_event_amounts(events, value, |event: &UnlockStakeEvent| {
+                            event.amount_unlocked.0
+                        })
+                    {
+                        operations.push(Operation::stake_withdraw(
+                            operation_index,
+                            Some(OperationStatusType::Success),
+                            address,
+                            amount_unlocked,
+                            Some(StakeType::Active),
+                        ));
+                        operations.push(Operation::stake_deposit(
+                            operation_index + 1,
+                            Some(OperationStatusType::Success),
+                            address,
+                            amount_unlocked,
+                            Some(StakeType::PendingInactive),
+                        ));
+                        operation_index += 2;
+                    }
+                } else if id.0 == withdraw_stake_events_field_identifier() {
+                    // Withdrawn stake goes to the owner's coin store, which has its own deposit
+                    if let Some(amount_withdrawn) =
+                        sum_event_amounts(events, value, |event: &WithdrawStakeEvent| {
+                            event.amount_withdrawn.0
+                        })
+                    {
+                        operations.push(Operation::stake_withdraw(
+                            operation_index,
+                            Some(OperationStatusType::Success),
+                            address,
+                            amount_withdrawn,
+                            Some(StakeType::Inactive),
+                        ));
+                        operation_index += 1;
+                    }
+                }
+            }
+        } else if data.typ == governance_events_tag {
+            // Votes don't move any coins, they're only reported for reconciliation
+            for (id, value) in data.data.0.iter() {
+                if id.0 == vote_events_field_identifier() {
+                    if let Some(VoteEvent {
+                        proposal_id,
+                        voter,
+                        should_pass,
+                    }) = get_event_data_from_handle(events, value)
+                    {
+                        operations.push(Operation::governance_vote(
+                            operation_index,
+                            Some(OperationStatusType::Success),
+                            voter.into(),
+                            proposal_id.0,
+                            should_pass,
+                        ));
+                        operation_index += 1;
+                    }
                 }
             }
         } else if data.typ == coin_store_tag {
@@ -845,6 +1300,21 @@ fn parse_operations_from_write_set(
     operations
 }
 
+/// Whether the transaction is a call to the framework's entry function `module::function`
+fn calls_framework_function(
+    maybe_request: &Option<UserTransactionRequest>,
+    module: Identifier,
+    function: Identifier,
+) -> bool {
+    matches!(
+        maybe_request.as_ref().map(|request| &request.payload),
+        Some(TransactionPayload::EntryFunctionPayload(inner))
+            if AccountAddress::ONE == *inner.function.module.address.inner()
+                && module == inner.function.module.name.0
+                && function == inner.function.name.0
+    )
+}
+
 /// Pulls the balance change from a withdraw or deposit event
 fn get_amount_from_event(events: &[Event], event_key: EventKey) -> Option<u64> {
     if let Some(event) = events
@@ -874,6 +1344,23 @@ fn get_set_operator_from_event(events: &[Event], event_key: EventKey) -> Option<
     None
 }
 
+/// Sums the `amount` of all events emitted to the event handle in `handle` (a field of a
+/// resource), or `None` if there are none, as a transaction can emit several to the same handle
+fn sum_event_amounts<T: DeserializeOwned>(
+    events: &[Event],
+    handle: &serde_json::Value,
+    amount: impl Fn(&T) -> u64,
+) -> Option<u64> {
+    let event = serde_json::from_value::<EventId>(handle.clone()).ok()?;
+    let event_key = EventKey::new(event.guid.id.creation_num.0, event.guid.id.addr);
+    events
+        .iter()
+        .filter(|event| EventKey::from(event.key) == event_key)
+        .filter_map(|event| serde_json::from_value::<T>(event.data.clone()).ok())
+        .map(|event| amount(&event))
+        .reduce(|total, amount| total.saturating_add(amount))
+}
+
 /// An enum for processing which operation is in a transaction
 pub enum OperationDetails {
     CreateAccount,
@@ -891,6 +1378,9 @@ pub enum InternalOperation {
     CreateAccount(CreateAccount),
     Transfer(Transfer),
     SetOperator(SetOperator),
+    SetVoter(SetVoter),
+    StakeAdd(StakeAdd),
+    StakeWithdraw(StakeWithdraw),
 }
 
 impl InternalOperation {
@@ -928,6 +1418,30 @@ impl InternalOperation {
                                 }));
                             }
                         }
+                        Ok(OperationType::SetVoter) => {
+                            if let (
+                                Some(OperationSpecificMetadata::SetVoter(SetVoterArguments {
+                                    voter,
+                                })),
+                                Some(account),
+                            ) = (&operation.metadata, &operation.account)
+                            {
+                                return Ok(Self::SetVoter(SetVoter {
+                                    owner: account.account_address()?,
+                                    voter: voter.account_address()?,
+                                }));
+                            }
+                        }
+                        Ok(OperationType::StakeWithdraw) => {
+                            if let (Some(amount), Some(account)) =
+                                (&operation.amount, &operation.account)
+                            {
+                                return Ok(Self::StakeWithdraw(StakeWithdraw {
+                                    owner: account.account_address()?,
+                                    amount: extract_stake_amount(amount)?,
+                                }));
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -935,6 +1449,12 @@ impl InternalOperation {
                 // Return invalid operations if for any reason parsing fails
                 Err(ApiError::InvalidOperations)
             }
+            2 if operations
+                .iter()
+                .all(|op| op.operation_type == OperationType::StakeAdd.to_string()) =>
+            {
+                Ok(Self::StakeAdd(StakeAdd::extract_stake_add(operations)?))
+            }
             2 => Ok(Self::Transfer(Transfer::extract_transfer(operations)?)),
             _ => Err(ApiError::InvalidOperations),
         }
@@ -946,10 +1466,19 @@ impl InternalOperation {
             Self::CreateAccount(inner) => inner.sender,
             Self::Transfer(inner) => inner.sender,
             Self::SetOperator(inner) => inner.owner,
+            Self::SetVoter(inner) => inner.owner,
+            Self::StakeAdd(inner) => inner.owner,
+            Self::StakeWithdraw(inner) => inner.owner,
         }
     }
 }
 
+/// Parses the amount of a stake operation, which must be a positive amount of the native coin
+fn extract_stake_amount(amount: &Amount) -> ApiResult<u64> {
+    is_native_coin(&amount.currency)?;
+    u64::from_str(&amount.value).map_err(|_| ApiError::InvalidOperations)
+}
+
 /// Operation to create an account
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CreateAccount {
@@ -1064,6 +1593,62 @@ pub struct SetOperator {
     pub operator: AccountAddress,
 }
 
+/// Set the voter delegated by the owner's stake pool
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SetVoter {
+    pub owner: AccountAddress,
+    pub voter: AccountAddress,
+}
+
+/// Add stake to the owner's stake pool
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StakeAdd {
+    pub owner: AccountAddress,
+    pub amount: u64,
+}
+
+impl StakeAdd {
+    /// Extracts adding stake from a debit of the owner's account and a credit of the same
+    /// amount to the owner's stake sub-account
+    pub fn extract_stake_add(operations: &Vec<Operation>) -> ApiResult<StakeAdd> {
+        let mut debit = None;
+        let mut credit = None;
+        for operation in operations {
+            let (account, amount) = match (&operation.account, &operation.amount) {
+                (Some(account), Some(amount)) => (account, amount),
+                _ => return Err(ApiError::InvalidOperations),
+            };
+            is_native_coin(&amount.currency)?;
+            let value = i128::from_str(&amount.value).map_err(|_| ApiError::InvalidOperations)?;
+            let owner = account.account_address()?;
+            if account.is_stake_sub_account()? {
+                credit = Some((owner, value));
+            } else {
+                debit = Some((owner, value));
+            }
+        }
+
+        match (debit, credit) {
+            (Some((owner, debit)), Some((stake_owner, credit)))
+                if owner == stake_owner && credit > 0 && debit == -credit =>
+            {
+                Ok(StakeAdd {
+                    owner,
+                    amount: u64::try_from(credit).map_err(|_| ApiError::InvalidOperations)?,
+                })
+            }
+            _ => Err(ApiError::InvalidOperations),
+        }
+    }
+}
+
+/// Withdraw unlocked stake from the owner's stake pool
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StakeWithdraw {
+    pub owner: AccountAddress,
+    pub amount: u64,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct CoinEvent {
     amount: U64,
@@ -1076,6 +1661,28 @@ pub struct SetOperatorEvent {
     new_operator: Address,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct AddStakeEvent {
+    amount_added: U64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct UnlockStakeEvent {
+    amount_unlocked: U64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WithdrawStakeEvent {
+    amount_withdrawn: U64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct VoteEvent {
+    proposal_id: U64,
+    voter: Address,
+    should_pass: bool,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct EventId {
     guid: Id,
@@ -1114,3 +1721,627 @@ where
         Ok(AccountAddress::new(value.0))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::SubAccountIdentifier;
+    use aptos_sdk::move_types::language_storage::ModuleId;
+    use aptos_types::transaction::AbortInfo;
+    use serde_json::json;
+
+    /// A failed user transaction as returned by the API, with no changes applied
+    fn failed_user_transaction(
+        vm_status: &str,
+        function: &str,
+        type_arguments: Vec<&str>,
+        arguments: Vec<serde_json::Value>,
+    ) -> aptos_rest_client::Transaction {
+        let hash = format!("0x{}", "ab".repeat(32));
+        let zero_hash = format!("0x{}", "00".repeat(32));
+        serde_json::from_value(json!({
+            "type": "user_transaction",
+            "version": "100",
+            "hash": hash,
+            "state_change_hash": zero_hash,
+            "event_root_hash": zero_hash,
+            "state_checkpoint_hash": null,
+            "gas_used": "20",
+            "success": false,
+            "vm_status": vm_status,
+            "accumulator_root_hash": zero_hash,
+            "changes": [],
+            "sender": "0xa550c18",
+            "sequence_number": "3",
+            "max_gas_amount": "2000",
+            "gas_unit_price": "1",
+            "expiration_timestamp_secs": "1660000000",
+            "payload": {
+                "type": "entry_function_payload",
+                "function": function,
+                "type_arguments": type_arguments,
+                "arguments": arguments,
+            },
+            "events": [],
+            "timestamp": "1660000000000000",
+        }))
+        .unwrap()
+    }
+
+    fn module_abort(module: &str, code: u64, info: Option<(&str, &str)>) -> ExecutionStatus {
+        let (address, name) = module.split_once("::").unwrap();
+        ExecutionStatus::MoveAbort {
+            location: AbortLocation::Module(ModuleId::new(
+                AccountAddress::from_hex_literal(address).unwrap(),
+                Identifier::new(name).unwrap(),
+            )),
+            code,
+            info: info.map(|(reason_name, description)| AbortInfo {
+                reason_name: reason_name.to_string(),
+                description: description.to_string(),
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_balance_transfer() {
+        let vm_status =
+            "Move abort by EINSUFFICIENT_BALANCE\nNot enough coins to complete transaction";
+        let status = module_abort(
+            "0x1::coin",
+            0x10006,
+            Some((
+                "EINSUFFICIENT_BALANCE",
+                "Not enough coins to complete transaction",
+            )),
+        );
+        let txn = Transaction::from_transaction_with_status(
+            failed_user_transaction(
+                vm_status,
+                "0x1::coin::transfer",
+                vec!["0x1::aptos_coin::AptosCoin"],
+                vec![json!("0xb0b"), json!("1000000")],
+            ),
+            Some(status),
+        )
+        .await
+        .unwrap();
+
+        // Statuses stay spec compliant, the details are only in the metadata
+        let statuses: Vec<_> = txn.operations.iter().map(|op| op.status.clone()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                Some(OperationStatusType::Failure.to_string()),
+                Some(OperationStatusType::Failure.to_string()),
+                Some(OperationStatusType::Success.to_string()),
+            ]
+        );
+
+        let metadata = txn.metadata.unwrap();
+        assert_eq!(metadata.failed, Some(true));
+        assert_eq!(metadata.vm_status.as_deref(), Some(vm_status));
+        assert_eq!(metadata.gas_used, Some(U64(20)));
+        assert_eq!(metadata.sequence_number, Some(U64(3)));
+        assert_eq!(
+            metadata.failure,
+            Some(TransactionFailure {
+                vm_status: vm_status.to_string(),
+                abort: Some(MoveAbort {
+                    module: Some("0x1::coin".to_string()),
+                    code: U64(0x10006),
+                    reason_name: Some("EINSUFFICIENT_BALANCE".to_string()),
+                    reason_description: Some(
+                        "Not enough coins to complete transaction".to_string()
+                    ),
+                }),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_module_abort() {
+        let txn = Transaction::from_transaction_with_status(
+            failed_user_transaction(
+                "Move abort: code 0x60000 at 0xcafe::message",
+                "0xcafe::message::set_message",
+                vec![],
+                vec![json!("hello")],
+            ),
+            Some(module_abort("0xcafe::message", 0x60000, None)),
+        )
+        .await
+        .unwrap();
+
+        // Only the gas fee can be determined from an unknown entry function
+        assert_eq!(txn.operations.len(), 1);
+        let abort = txn.metadata.unwrap().failure.unwrap().abort.unwrap();
+        assert_eq!(abort.module.as_deref(), Some("0xcafe::message"));
+        assert_eq!(abort.code, U64(0x60000));
+        assert_eq!(abort.reason_name, None);
+
+        // Without the execution status, only the VM status is known
+        let txn = Transaction::from_transaction(failed_user_transaction(
+            "Move abort: code 0x60000 at 0xcafe::message",
+            "0xcafe::message::set_message",
+            vec![],
+            vec![json!("hello")],
+        ))
+        .await
+        .unwrap();
+        let failure = txn.metadata.unwrap().failure.unwrap();
+        assert_eq!(
+            failure.vm_status,
+            "Move abort: code 0x60000 at 0xcafe::message"
+        );
+        assert_eq!(failure.abort, None);
+    }
+
+    /// A successful stake pool transaction, with a write to the pool and the events emitted
+    /// to its add stake (twice), unlock and withdraw handles
+    fn stake_pool_transaction(pool: AccountAddress) -> aptos_rest_client::Transaction {
+        let hash = format!("0x{}", "ab".repeat(32));
+        let zero_hash = format!("0x{}", "00".repeat(32));
+        let handle = |creation_num: u64| {
+            json!({
+                "counter": "1",
+                "guid": {
+                    "id": {"addr": pool.to_hex_literal(), "creation_num": creation_num.to_string()},
+                },
+            })
+        };
+        let event =
+            |creation_num: u64, sequence_number: u64, typ: &str, data: serde_json::Value| {
+                let key = aptos_rest_client::aptos_api_types::EventKey::from(EventKey::new(
+                    creation_num,
+                    pool,
+                ));
+                json!({
+                    "key": key.to_string(),
+                    "sequence_number": sequence_number.to_string(),
+                    "type": typ,
+                    "data": data,
+                })
+            };
+        serde_json::from_value(json!({
+            "type": "user_transaction",
+            "version": "100",
+            "hash": hash,
+            "state_change_hash": zero_hash,
+            "event_root_hash": zero_hash,
+            "state_checkpoint_hash": null,
+            "gas_used": "20",
+            "success": true,
+            "vm_status": "Executed successfully",
+            "accumulator_root_hash": zero_hash,
+            "changes": [{
+                "type": "write_resource",
+                "address": pool.to_hex_literal(),
+                "state_key_hash": zero_hash,
+                "data": {
+                    "type": "0x1::stake::StakePool",
+                    "data": {
+                        "add_stake_events": handle(2),
+                        "unlock_stake_events": handle(3),
+                        "withdraw_stake_events": handle(4),
+                    },
+                },
+            }],
+            "sender": pool.to_hex_literal(),
+            "sequence_number": "3",
+            "max_gas_amount": "2000",
+            "gas_unit_price": "1",
+            "expiration_timestamp_secs": "1660000000",
+            "payload": {
+                "type": "entry_function_payload",
+                "function": "0xcafe::staking::restake",
+                "type_arguments": [],
+                "arguments": [],
+            },
+            "events": [
+                event(2, 0, "0x1::stake::AddStakeEvent", json!({"amount_added": "300"})),
+                event(2, 1, "0x1::stake::AddStakeEvent", json!({"amount_added": "200"})),
+                event(3, 0, "0x1::stake::UnlockStakeEvent", json!({"amount_unlocked": "200"})),
+                event(4, 0, "0x1::stake::WithdrawStakeEvent", json!({"amount_withdrawn": "100"})),
+            ],
+            "timestamp": "1660000000000000",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stake_pool_operations() {
+        let pool = AccountAddress::from_hex_literal("0xb0b").unwrap();
+        let txn = Transaction::from_transaction(stake_pool_transaction(pool))
+            .await
+            .unwrap();
+
+        let stake_ops: Vec<_> = txn
+            .operations
+            .iter()
+            .filter(|op| op.account == Some(AccountIdentifier::stake_sub_account(pool)))
+            .map(|op| {
+                let amount = op.amount.clone().unwrap();
+                assert_eq!(amount.currency, native_coin());
+                (
+                    op.operation_type.clone(),
+                    amount.value,
+                    amount.metadata.map(|metadata| metadata.stake_type),
+                )
+            })
+            .collect();
+        assert_eq!(
+            stake_ops,
+            vec![
+                ("deposit".to_string(), "500".to_string(), None),
+                (
+                    "withdraw".to_string(),
+                    "-200".to_string(),
+                    Some(StakeType::Active)
+                ),
+                (
+                    "deposit".to_string(),
+                    "200".to_string(),
+                    Some(StakeType::PendingInactive)
+                ),
+                (
+                    "withdraw".to_string(),
+                    "-100".to_string(),
+                    Some(StakeType::Inactive)
+                ),
+            ]
+        );
+        // Only the gas fee is charged to the account itself
+        assert_eq!(txn.operations.len(), stake_ops.len() + 1);
+    }
+
+    #[test]
+    fn test_unsupported_sub_account() {
+        let pool = AccountAddress::from_hex_literal("0xb0b").unwrap();
+        assert!(!AccountIdentifier::from(pool)
+            .is_stake_sub_account()
+            .unwrap());
+        assert!(AccountIdentifier::stake_sub_account(pool)
+            .is_stake_sub_account()
+            .unwrap());
+
+        let mut account = AccountIdentifier::from(pool);
+        account.sub_account = Some(SubAccountIdentifier {
+            address: "vesting".to_string(),
+        });
+        assert!(matches!(
+            account.is_stake_sub_account(),
+            Err(ApiError::UnsupportedSubAccount(Some(name))) if name == "vesting"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_transaction_metadata_fields() {
+        let sender = AccountAddress::from_hex_literal("0xa550c18").unwrap();
+        let txn = Transaction::from_transaction(successful_user_transaction(
+            sender,
+            "0xcafe::message::set_message",
+            vec![],
+            vec![],
+            vec![],
+        ))
+        .await
+        .unwrap();
+        let metadata = serde_json::to_value(txn.metadata.unwrap()).unwrap();
+        assert_eq!(
+            metadata,
+            json!({
+                "transaction_type": "User",
+                "version": "100",
+                "failed": false,
+                "vm_status": "Executed successfully",
+                "gas_used": "20",
+                "sequence_number": "3",
+            })
+        );
+
+        // Only user transactions have the details, the others keep the original fields
+        let zero_hash = format!("0x{}", "00".repeat(32));
+        let checkpoint = serde_json::from_value(json!({
+            "type": "state_checkpoint_transaction",
+            "version": "101",
+            "hash": format!("0x{}", "cd".repeat(32)),
+            "state_change_hash": zero_hash,
+            "event_root_hash": zero_hash,
+            "state_checkpoint_hash": zero_hash,
+            "gas_used": "0",
+            "success": true,
+            "vm_status": "Executed successfully",
+            "accumulator_root_hash": zero_hash,
+            "changes": [],
+            "timestamp": "1660000000000000",
+        }))
+        .unwrap();
+        let txn = Transaction::from_transaction(checkpoint).await.unwrap();
+        let metadata = serde_json::to_value(txn.metadata.unwrap()).unwrap();
+        assert_eq!(
+            metadata,
+            json!({
+                "transaction_type": "StateCheckpoint",
+                "version": "101",
+            })
+        );
+    }
+
+    #[test]
+    fn test_non_abort_failure() {
+        assert_eq!(
+            TransactionFailure::new("Out of gas".to_string(), Some(&ExecutionStatus::OutOfGas))
+                .abort,
+            None
+        );
+        assert_eq!(
+            MoveAbort::from_execution_status(&ExecutionStatus::MoveAbort {
+                location: AbortLocation::Script,
+                code: 42,
+                info: None,
+            }),
+            Some(MoveAbort {
+                module: None,
+                code: U64(42),
+                reason_name: None,
+                reason_description: None,
+            })
+        );
+    }
+
+    /// A successful call of the framework's `function` by `sender`, writing the resources in
+    /// `changes` and emitting `events`
+    fn successful_user_transaction(
+        sender: AccountAddress,
+        function: &str,
+        arguments: Vec<serde_json::Value>,
+        changes: Vec<serde_json::Value>,
+        events: Vec<serde_json::Value>,
+    ) -> aptos_rest_client::Transaction {
+        let hash = format!("0x{}", "ab".repeat(32));
+        let zero_hash = format!("0x{}", "00".repeat(32));
+        serde_json::from_value(json!({
+            "type": "user_transaction",
+            "version": "100",
+            "hash": hash,
+            "state_change_hash": zero_hash,
+            "event_root_hash": zero_hash,
+            "state_checkpoint_hash": null,
+            "gas_used": "20",
+            "success": true,
+            "vm_status": "Executed successfully",
+            "accumulator_root_hash": zero_hash,
+            "changes": changes,
+            "sender": sender.to_hex_literal(),
+            "sequence_number": "3",
+            "max_gas_amount": "2000",
+            "gas_unit_price": "1",
+            "expiration_timestamp_secs": "1660000000",
+            "payload": {
+                "type": "entry_function_payload",
+                "function": function,
+                "type_arguments": [],
+                "arguments": arguments,
+            },
+            "events": events,
+            "timestamp": "1660000000000000",
+        }))
+        .unwrap()
+    }
+
+    /// The operations of a transaction, without the gas fee
+    fn non_fee_operations(txn: &Transaction) -> Vec<Operation> {
+        txn.operations
+            .iter()
+            .filter(|op| op.operation_type != OperationType::Fee.to_string())
+            .cloned()
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_set_voter_operations() {
+        let owner = AccountAddress::from_hex_literal("0xb0b").unwrap();
+        let voter = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let zero_hash = format!("0x{}", "00".repeat(32));
+        let stake_pool = json!({
+            "type": "write_resource",
+            "address": owner.to_hex_literal(),
+            "state_key_hash": zero_hash,
+            "data": {
+                "type": "0x1::stake::StakePool",
+                "data": {"delegated_voter": voter.to_hex_literal()},
+            },
+        });
+
+        let txn = Transaction::from_transaction(successful_user_transaction(
+            owner,
+            "0x1::stake::set_delegated_voter",
+            vec![json!(voter.to_hex_literal())],
+            vec![stake_pool.clone()],
+            vec![],
+        ))
+        .await
+        .unwrap();
+        assert_eq!(
+            non_fee_operations(&txn),
+            vec![Operation::set_voter(
+                0,
+                Some(OperationStatusType::Success),
+                owner,
+                voter
+            )]
+        );
+
+        // Every write of the stake pool has the voter, it's only a change when setting it
+        let txn = Transaction::from_transaction(successful_user_transaction(
+            owner,
+            "0x1::stake::add_stake",
+            vec![json!("100")],
+            vec![stake_pool],
+            vec![],
+        ))
+        .await
+        .unwrap();
+        assert!(non_fee_operations(&txn).is_empty());
+
+        let txn = Transaction::from_transaction(failed_user_transaction(
+            "Move abort by EOWNER_CAP_NOT_FOUND\nOwner capability does not exist",
+            "0x1::stake::set_delegated_voter",
+            vec![],
+            vec![json!("0xcafe")],
+        ))
+        .await
+        .unwrap();
+        let sender = AccountAddress::from_hex_literal("0xa550c18").unwrap();
+        assert_eq!(
+            non_fee_operations(&txn),
+            vec![Operation::set_voter(
+                0,
+                Some(OperationStatusType::Failure),
+                sender,
+                voter
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_governance_vote_operations() {
+        let voter = AccountAddress::from_hex_literal("0xb0b").unwrap();
+        let zero_hash = format!("0x{}", "00".repeat(32));
+        let key = aptos_rest_client::aptos_api_types::EventKey::from(EventKey::new(
+            7,
+            AccountAddress::ONE,
+        ));
+        let txn = Transaction::from_transaction(successful_user_transaction(
+            voter,
+            "0x1::aptos_governance::vote",
+            vec![json!(voter.to_hex_literal()), json!("12"), json!(false)],
+            vec![json!({
+                "type": "write_resource",
+                "address": "0x1",
+                "state_key_hash": zero_hash,
+                "data": {
+                    "type": "0x1::aptos_governance::GovernanceEvents",
+                    "data": {
+                        "vote_events": {
+                            "counter": "1",
+                            "guid": {"id": {"addr": "0x1", "creation_num": "7"}},
+                        },
+                    },
+                },
+            })],
+            vec![json!({
+                "key": key.to_string(),
+                "sequence_number": "0",
+                "type": "0x1::aptos_governance::VoteEvent",
+                "data": {
+                    "proposal_id": "12",
+                    "voter": voter.to_hex_literal(),
+                    "stake_pool": voter.to_hex_literal(),
+                    "num_votes": "1000",
+                    "should_pass": false,
+                },
+            })],
+        ))
+        .await
+        .unwrap();
+
+        // Votes have no balance change, only the details in the metadata
+        let operations = non_fee_operations(&txn);
+        assert_eq!(
+            operations,
+            vec![Operation::governance_vote(
+                0,
+                Some(OperationStatusType::Success),
+                voter,
+                12,
+                false
+            )]
+        );
+        assert_eq!(operations[0].amount, None);
+
+        let txn = Transaction::from_transaction(failed_user_transaction(
+            "Move abort by ENO_VOTING_POWER\nNot enough voting power",
+            "0x1::aptos_governance::vote",
+            vec![],
+            vec![json!("0xb0b"), json!("12"), json!(true)],
+        ))
+        .await
+        .unwrap();
+        let sender = AccountAddress::from_hex_literal("0xa550c18").unwrap();
+        assert_eq!(
+            non_fee_operations(&txn),
+            vec![Operation::governance_vote(
+                0,
+                Some(OperationStatusType::Failure),
+                sender,
+                12,
+                true
+            )]
+        );
+    }
+
+    #[test]
+    fn test_extract_stake_operations() {
+        let owner = AccountAddress::from_hex_literal("0xbeef").unwrap();
+
+        let add_stake = vec![
+            Operation::add_stake(0, None, owner, 5),
+            Operation::add_stake_deposit(1, None, owner, 5),
+        ];
+        assert_eq!(add_stake[0].amount.as_ref().unwrap().value, "-5");
+        assert!(add_stake[0].account.as_ref().unwrap().sub_account.is_none());
+        assert_eq!(add_stake[1].amount.as_ref().unwrap().value, "5");
+        assert!(add_stake[1]
+            .account
+            .as_ref()
+            .unwrap()
+            .is_stake_sub_account()
+            .unwrap());
+        let operation = InternalOperation::extract(&add_stake).unwrap();
+        assert_eq!(
+            operation,
+            InternalOperation::StakeAdd(StakeAdd { owner, amount: 5 })
+        );
+        assert_eq!(operation.sender(), owner);
+
+        // Adding stake has to move the same amount from the account to its own stake
+        assert!(InternalOperation::extract(&vec![add_stake[0].clone()]).is_err());
+        let mut mismatched = add_stake.clone();
+        mismatched[1].amount.as_mut().unwrap().value = "4".to_string();
+        assert!(InternalOperation::extract(&mismatched).is_err());
+        let other = AccountAddress::from_hex_literal("0xf00d").unwrap();
+        let other_stake = vec![
+            Operation::add_stake(0, None, owner, 5),
+            Operation::add_stake_deposit(1, None, other, 5),
+        ];
+        assert!(InternalOperation::extract(&other_stake).is_err());
+
+        let operation =
+            InternalOperation::extract(&vec![Operation::withdraw_stake(0, None, owner, 7)])
+                .unwrap();
+        assert_eq!(
+            operation,
+            InternalOperation::StakeWithdraw(StakeWithdraw { owner, amount: 7 })
+        );
+
+        let voter = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let operation =
+            InternalOperation::extract(&vec![Operation::set_voter(0, None, owner, voter)]).unwrap();
+        assert_eq!(
+            operation,
+            InternalOperation::SetVoter(SetVoter { owner, voter })
+        );
+        assert_eq!(operation.sender(), owner);
+
+        // Stake operations need an amount in the native coin
+        let mut no_amount = add_stake;
+        no_amount[1].amount = None;
+        assert!(InternalOperation::extract(&no_amount).is_err());
+
+        let mut negative = Operation::withdraw_stake(0, None, owner, 7);
+        negative.amount.as_mut().unwrap().value = "-7".to_string();
+        assert!(InternalOperation::extract(&vec![negative]).is_err());
+    }
+}
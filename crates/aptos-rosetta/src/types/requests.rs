@@ -2,9 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::types::{
-    AccountIdentifier, Allow, Amount, Block, BlockIdentifier, Currency, InternalOperation,
-    NetworkIdentifier, Operation, PartialBlockIdentifier, Peer, PublicKey, Signature,
-    SigningPayload, SyncStatus, Transaction, TransactionIdentifier, Version,
+    AccountIdentifier, Allow, Amount, Block, BlockEvent, BlockIdentifier, Currency,
+    InternalOperation, NetworkIdentifier, Operation, PartialBlockIdentifier, Peer, PublicKey,
+    Signature, SigningPayload, SyncStatus, Transaction, TransactionIdentifier, Version,
 };
 use aptos_types::chain_id::ChainId;
 use serde::{Deserialize, Serialize};
@@ -339,6 +339,34 @@ pub struct ConstructionSubmitResponse {
     pub transaction_identifier: TransactionIdentifier,
 }
 
+/// Request for block events after an offset
+///
+/// A running full node is required for this API
+///
+/// [API Spec](https://www.rosetta-api.org/docs/models/EventsBlocksRequest.html)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EventsBlocksRequest {
+    /// Network identifier describing the blockchain and the chain id
+    pub network_identifier: NetworkIdentifier,
+    /// Sequence of the first event to return, defaults to waiting for the next event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    /// Maximum number of events to return
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+/// Response with the block events starting at the requested offset
+///
+/// [API Spec](https://www.rosetta-api.org/docs/models/EventsBlocksResponse.html)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EventsBlocksResponse {
+    /// Sequence of the latest event, so clients know whether they're caught up
+    pub max_sequence: u64,
+    /// Events in order of their sequence
+    pub events: Vec<BlockEvent>,
+}
+
 /// Request for all transactions in mempool
 ///
 /// [API Spec](https://www.rosetta-api.org/docs/models/MempoolRequest.html)
@@ -3,8 +3,8 @@
 
 use crate::context::Context;
 use crate::error::ServiceError;
-use crate::jwt_auth::{authorize_jwt, create_jwt_token, jwt_from_header};
-use crate::types::auth::{AuthRequest, AuthResponse, Claims};
+use crate::jwt_auth::{authorize_jwt, create_jwt_token, jwt_from_header, refresh_jwt_token};
+use crate::types::auth::{AuthRefreshResponse, AuthRequest, AuthResponse, Claims};
 use anyhow::{anyhow, Result};
 use aptos_config::config::PeerRole;
 use aptos_crypto::{noise, x25519};
@@ -27,6 +27,20 @@ pub fn auth(context: Context) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
+pub fn auth_refresh(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("auth" / "refresh")
+        .and(warp::post())
+        .and(context.filter())
+        .and(headers_cloned().and_then(jwt_from_header))
+        .and_then(handle_auth_refresh)
+        .boxed()
+}
+
+pub async fn handle_auth_refresh(context: Context, token: String) -> Result<impl Reply, Rejection> {
+    let token = refresh_jwt_token(context, token).await?;
+    Ok(reply::json(&AuthRefreshResponse { token }))
+}
+
 pub async fn handle_auth(context: Context, body: AuthRequest) -> Result<impl Reply, Rejection> {
     let client_init_message = &body.handshake_msg;
 
@@ -4,8 +4,8 @@
 use std::{convert::Infallible, sync::Arc};
 
 use crate::{
-    clients::victoria_metrics_api::Client as MetricsClient, validator_cache::ValidatorSetCache,
-    GCPBigQueryConfig, TelemetryServiceConfig,
+    clients::victoria_metrics_api::Client as MetricsClient, log_ingest::LogIngest,
+    validator_cache::ValidatorSetCache, GCPBigQueryConfig, TelemetryServiceConfig,
 };
 use aptos_crypto::noise;
 use gcp_bigquery_client::Client as BQClient;
@@ -22,8 +22,12 @@ pub struct Context {
 
     pub victoria_metrics_client: Option<MetricsClient>,
 
+    pub log_ingest: Option<LogIngest>,
+
     pub jwt_encoding_key: EncodingKey,
     pub jwt_decoding_key: DecodingKey,
+    pub jwt_token_lifetime_secs: u64,
+    pub jwt_refresh_grace_secs: u64,
 }
 
 impl Context {
@@ -38,13 +42,23 @@ impl Context {
             noise_config: Arc::new(noise::NoiseConfig::new(private_key)),
             validator_cache,
 
-            gcp_bq_client: gcp_bigquery_client,
+            gcp_bq_client: gcp_bigquery_client.clone(),
             gcp_bq_config: config.gcp_bq_config.clone(),
 
             victoria_metrics_client,
 
+            log_ingest: config.log_ingest_config.as_ref().map(|log_ingest_config| {
+                LogIngest::new(
+                    log_ingest_config,
+                    gcp_bigquery_client.clone(),
+                    &config.gcp_bq_config,
+                )
+            }),
+
             jwt_encoding_key: EncodingKey::from_secret(config.jwt_signing_key.as_bytes()),
             jwt_decoding_key: DecodingKey::from_secret(config.jwt_signing_key.as_bytes()),
+            jwt_token_lifetime_secs: config.jwt_token_lifetime_secs,
+            jwt_refresh_grace_secs: config.jwt_refresh_grace_secs,
         }
     }
 
@@ -35,6 +35,14 @@ impl ServiceError {
         Self::new(StatusCode::UNAUTHORIZED, msg.to_string())
     }
 
+    pub fn payload_too_large<S: Display>(msg: S) -> Self {
+        Self::new(StatusCode::PAYLOAD_TOO_LARGE, msg.to_string())
+    }
+
+    pub fn too_many_requests<S: Display>(msg: S) -> Self {
+        Self::new(StatusCode::TOO_MANY_REQUESTS, msg.to_string())
+    }
+
     pub fn internal(err: anyhow::Error) -> Self {
         Self::from_anyhow_error(StatusCode::INTERNAL_SERVER_ERROR, err)
     }
@@ -25,7 +25,9 @@ pub fn create_jwt_token(
 ) -> Result<String, Error> {
     let issued = Utc::now().timestamp();
     let expiration = Utc::now()
-        .checked_add_signed(chrono::Duration::minutes(60))
+        .checked_add_signed(chrono::Duration::seconds(
+            context.jwt_token_lifetime_secs as i64,
+        ))
         .expect("valid timestamp")
         .timestamp();
 
@@ -77,6 +79,45 @@ pub async fn authorize_jwt(
     }
 }
 
+/// Issues a new token for the claims of `token`, which must be signed by this service and
+/// either still valid or expired for less than the refresh grace window. The chain id, peer id
+/// and peer role are carried over as is, so no new handshake is needed.
+pub async fn refresh_jwt_token(
+    context: Context,
+    token: String,
+) -> anyhow::Result<String, Rejection> {
+    let mut validation = Validation::new(Algorithm::HS512);
+    validation.leeway = context.jwt_refresh_grace_secs;
+    let claims = decode::<Claims>(&token, &context.jwt_decoding_key, &validation)
+        .map_err(|_| {
+            reject::custom(ServiceError::unauthorized(
+                "token is invalid or expired beyond the refresh window",
+            ))
+        })?
+        .claims;
+
+    // A token from an older epoch needs a new handshake, as the peer's role may have changed
+    let current_epoch = context
+        .validator_cache()
+        .read()
+        .get(&claims.chain_id)
+        .map(|info| info.0);
+    if current_epoch != Some(claims.epoch) {
+        return Err(reject::custom(ServiceError::unauthorized(
+            "token epoch is stale",
+        )));
+    }
+
+    create_jwt_token(
+        context,
+        claims.chain_id,
+        claims.peer_id,
+        claims.peer_role,
+        claims.epoch,
+    )
+    .map_err(|_| reject::reject())
+}
+
 pub async fn jwt_from_header(headers: HeaderMap<HeaderValue>) -> anyhow::Result<String, Rejection> {
     let header = match headers.get(AUTHORIZATION) {
         Some(v) => v,
@@ -103,6 +144,90 @@ pub async fn jwt_from_header(headers: HeaderMap<HeaderValue>) -> anyhow::Result<
 mod tests {
 
     use super::*;
+    use crate::tests::test_context::new_test_context;
+    use aptos_config::config::PeerSet;
+    use jsonwebtoken::EncodingKey;
+
+    const CHAIN_ID: u8 = 21;
+    const EPOCH: u64 = 3;
+
+    async fn refresh_context() -> Context {
+        let context = new_test_context().await.inner;
+        context
+            .validator_cache()
+            .write()
+            .insert(ChainId::new(CHAIN_ID), (EPOCH, PeerSet::new()));
+        context
+    }
+
+    /// Signs a token for a validator that expired `expired_secs_ago` seconds ago
+    fn expired_token(key: &EncodingKey, peer_id: PeerId, expired_secs_ago: i64) -> String {
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            chain_id: ChainId::new(CHAIN_ID),
+            peer_id,
+            peer_role: PeerRole::Validator,
+            epoch: EPOCH,
+            exp: (now - expired_secs_ago) as usize,
+            iat: (now - 3600 - expired_secs_ago) as usize,
+        };
+        encode(&Header::new(Algorithm::HS512), &claims, key).unwrap()
+    }
+
+    #[tokio::test]
+    async fn refresh_within_grace_window() {
+        let context = refresh_context().await;
+        let peer_id = PeerId::random();
+        let token = expired_token(&context.jwt_encoding_key, peer_id, 10);
+
+        let refreshed = refresh_jwt_token(context.clone(), token).await.unwrap();
+        let claims = decode::<Claims>(
+            &refreshed,
+            &context.jwt_decoding_key,
+            &Validation::new(Algorithm::HS512),
+        )
+        .unwrap()
+        .claims;
+        assert_eq!(claims.chain_id, ChainId::new(CHAIN_ID));
+        assert_eq!(claims.peer_id, peer_id);
+        assert_eq!(claims.peer_role, PeerRole::Validator);
+        assert_eq!(claims.epoch, EPOCH);
+        assert!(claims.exp > Utc::now().timestamp() as usize);
+    }
+
+    #[tokio::test]
+    async fn refresh_rejects_token_outside_grace_window() {
+        let context = refresh_context().await;
+        let expired_secs_ago = context.jwt_refresh_grace_secs as i64 + 60;
+        let token = expired_token(
+            &context.jwt_encoding_key,
+            PeerId::random(),
+            expired_secs_ago,
+        );
+        assert!(refresh_jwt_token(context, token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn refresh_rejects_token_signed_with_other_key() {
+        let context = refresh_context().await;
+        let token = expired_token(
+            &EncodingKey::from_secret(b"some_other_key"),
+            PeerId::random(),
+            0,
+        );
+        assert!(refresh_jwt_token(context, token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn refresh_rejects_token_from_stale_epoch() {
+        let context = refresh_context().await;
+        context
+            .validator_cache()
+            .write()
+            .insert(ChainId::new(CHAIN_ID), (EPOCH + 1, PeerSet::new()));
+        let token = expired_token(&context.jwt_encoding_key, PeerId::random(), 0);
+        assert!(refresh_jwt_token(context, token).await.is_err());
+    }
 
     #[tokio::test]
     async fn jwt_from_header_valid_bearer() {
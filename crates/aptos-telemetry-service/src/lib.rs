@@ -30,6 +30,8 @@ mod custom_event;
 mod error;
 mod index;
 mod jwt_auth;
+mod log_ingest;
+mod metrics;
 mod prometheus_push_metrics;
 #[cfg(any(test))]
 pub(crate) mod tests;
@@ -112,10 +114,28 @@ pub struct TelemetryServiceConfig {
     pub trusted_full_node_addresses: HashMap<ChainId, String>,
     pub server_private_key: ConfigKey<x25519::PrivateKey>,
     pub jwt_signing_key: String,
+    /// How long an issued auth token is valid for, in seconds
+    #[serde(default = "default_jwt_token_lifetime_secs")]
+    pub jwt_token_lifetime_secs: u64,
+    /// How long after expiry a token can still be refreshed without a new handshake, in seconds
+    #[serde(default = "default_jwt_refresh_grace_secs")]
+    pub jwt_refresh_grace_secs: u64,
     pub update_interval: u64,
     pub gcp_bq_config: GCPBigQueryConfig,
     pub victoria_metrics_base_url: String,
     pub victoria_metrics_token: String,
+    /// Limits and destination of the node logs sent to `/api/logs/ingest`, which refuses logs
+    /// without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_ingest_config: Option<LogIngestConfig>,
+}
+
+fn default_jwt_token_lifetime_secs() -> u64 {
+    60 * 60
+}
+
+fn default_jwt_refresh_grace_secs() -> u64 {
+    5 * 60
 }
 
 impl TelemetryServiceConfig {
@@ -153,3 +173,33 @@ pub struct GCPBigQueryConfig {
     pub dataset_id: String,
     pub table_id: String,
 }
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogIngestConfig {
+    /// The largest log batch accepted in one request, in bytes
+    #[serde(default = "default_log_max_batch_bytes")]
+    pub max_batch_bytes: u64,
+    /// How many bytes of logs a peer can send per minute
+    #[serde(default = "default_log_max_peer_bytes_per_minute")]
+    pub max_peer_bytes_per_minute: u64,
+    pub sink: LogSinkConfig,
+}
+
+fn default_log_max_batch_bytes() -> u64 {
+    1024 * 1024
+}
+
+fn default_log_max_peer_bytes_per_minute() -> u64 {
+    10 * 1024 * 1024
+}
+
+/// Where ingested log records are written
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogSinkConfig {
+    /// A table in the dataset of `gcp_bq_config`
+    BigQuery { table_id: String },
+    /// A local file the records are appended to as newline-delimited JSON
+    File { path: PathBuf },
+}
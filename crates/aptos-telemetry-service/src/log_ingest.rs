@@ -0,0 +1,265 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use crate::{
+    auth::with_auth,
+    context::Context,
+    error::ServiceError,
+    metrics::LOG_INGEST_DROPPED_RECORDS,
+    types::{
+        auth::Claims,
+        common::EventIdentity,
+        telemetry::{LogIngestResponse, LogRow},
+    },
+    GCPBigQueryConfig, LogIngestConfig, LogSinkConfig,
+};
+use anyhow::anyhow;
+use aptos_config::config::PeerRole;
+use aptos_infallible::Mutex;
+use aptos_logger::{debug, error};
+use aptos_types::{chain_id::ChainId, PeerId};
+use chrono::Utc;
+use gcp_bigquery_client::{
+    model::table_data_insert_all_request::TableDataInsertAllRequest, Client as BQClient,
+};
+use tokio::io::AsyncWriteExt;
+use warp::{
+    filters::BoxedFilter,
+    http::{header::CONTENT_LENGTH, StatusCode},
+    hyper::body::Bytes,
+    reject, reply, Filter, Rejection, Reply,
+};
+
+pub fn log_ingest(context: Context) -> BoxedFilter<(impl Reply,)> {
+    let max_batch_bytes = context
+        .log_ingest
+        .as_ref()
+        .map_or(0, |log_ingest| log_ingest.max_batch_bytes);
+    warp::path!("api" / "logs" / "ingest")
+        .and(warp::post())
+        .and(with_log_ingest(context.clone()))
+        .and(with_auth(
+            context,
+            vec![PeerRole::Validator, PeerRole::Unknown],
+        ))
+        .and(with_batch_size_limit(max_batch_bytes))
+        // Batches must declare their length, so a body over the limit is never read
+        .and(warp::body::content_length_limit(max_batch_bytes))
+        .and(warp::body::bytes())
+        .and_then(handle_log_ingest)
+        .boxed()
+}
+
+/// Rejects batches when log ingestion isn't configured, before their body is read
+fn with_log_ingest(
+    context: Context,
+) -> impl Filter<Extract = (LogIngest,), Error = Rejection> + Clone {
+    warp::any().and_then(move || {
+        let log_ingest = context.log_ingest.clone();
+        async move {
+            log_ingest.ok_or_else(|| {
+                reject::custom(ServiceError::new(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "log ingestion is not configured".into(),
+                ))
+            })
+        }
+    })
+}
+
+/// Rejects batches declaring a length over `limit`, saying what the limit is
+fn with_batch_size_limit(limit: u64) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<u64>(CONTENT_LENGTH.as_str())
+        .and_then(move |length: Option<u64>| async move {
+            match length {
+                Some(length) if length > limit => {
+                    Err(reject::custom(batch_too_large(length, limit)))
+                }
+                _ => Ok(()),
+            }
+        })
+        .untuple_one()
+}
+
+fn batch_too_large(length: u64, limit: u64) -> ServiceError {
+    ServiceError::payload_too_large(format!(
+        "log batch of {} bytes exceeds the limit of {} bytes",
+        length, limit
+    ))
+}
+
+pub async fn handle_log_ingest(
+    log_ingest: LogIngest,
+    claims: Claims,
+    body: Bytes,
+) -> anyhow::Result<impl Reply, Rejection> {
+    let length = body.len() as u64;
+    if !log_ingest.try_consume_quota(claims.chain_id, claims.peer_id, length) {
+        return Err(reject::custom(ServiceError::too_many_requests(format!(
+            "peer {} exceeded the log quota of {} bytes per minute",
+            claims.peer_id, log_ingest.max_peer_bytes_per_minute
+        ))));
+    }
+
+    let event_identity = EventIdentity::from(claims);
+    let received_timestamp = Utc::now().timestamp() as u64;
+    let mut rows = Vec::new();
+    let mut dropped = 0;
+    for line in body.split(|byte| *byte == b'\n') {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        // Only JSON objects are log records, anything else is dropped
+        match serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(line) {
+            Ok(record) => rows.push(LogRow {
+                event_identity: event_identity.clone(),
+                received_timestamp,
+                record: record.into(),
+            }),
+            Err(_) => dropped += 1,
+        }
+    }
+    if dropped > 0 {
+        LOG_INGEST_DROPPED_RECORDS
+            .with_label_values(&[&event_identity.chain_id.to_string()])
+            .inc_by(dropped as u64);
+        debug!(
+            "dropped {} malformed log records from peer {}",
+            dropped, event_identity.peer_id
+        );
+    }
+
+    if !rows.is_empty() {
+        log_ingest.sink.write(&rows).await.map_err(|e| {
+            error!("unable to write log records: {}", e);
+            ServiceError::from(anyhow!("unable to write log records"))
+        })?;
+    }
+
+    Ok(reply::json(&LogIngestResponse {
+        accepted: rows.len(),
+        dropped,
+    }))
+}
+
+/// Limits and destination of ingested node logs, shared by all requests
+#[derive(Clone)]
+pub struct LogIngest {
+    max_batch_bytes: u64,
+    max_peer_bytes_per_minute: u64,
+    sink: LogSink,
+    usage: Arc<Mutex<HashMap<(ChainId, PeerId), PeerUsage>>>,
+}
+
+/// The bytes of logs a peer sent in a minute
+#[derive(Clone, Copy)]
+struct PeerUsage {
+    minute: u64,
+    bytes: u64,
+}
+
+impl LogIngest {
+    pub fn new(
+        config: &LogIngestConfig,
+        gcp_bq_client: Option<BQClient>,
+        gcp_bq_config: &GCPBigQueryConfig,
+    ) -> Self {
+        let sink = match &config.sink {
+            LogSinkConfig::BigQuery { table_id } => LogSink::BigQuery {
+                client: gcp_bq_client,
+                project_id: gcp_bq_config.project_id.clone(),
+                dataset_id: gcp_bq_config.dataset_id.clone(),
+                table_id: table_id.clone(),
+            },
+            LogSinkConfig::File { path } => LogSink::File {
+                path: path.clone(),
+                lock: Arc::new(tokio::sync::Mutex::new(())),
+            },
+        };
+        Self {
+            max_batch_bytes: config.max_batch_bytes,
+            max_peer_bytes_per_minute: config.max_peer_bytes_per_minute,
+            sink,
+            usage: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Counts `bytes` against the quota of the peer for the current minute, unless that would
+    /// exceed it
+    fn try_consume_quota(&self, chain_id: ChainId, peer_id: PeerId, bytes: u64) -> bool {
+        let minute = Utc::now().timestamp() as u64 / 60;
+        let mut usage = self.usage.lock();
+        // Forget peers which haven't sent anything this minute, so the map doesn't keep growing
+        usage.retain(|_, peer_usage| peer_usage.minute == minute);
+        let peer_usage = usage
+            .entry((chain_id, peer_id))
+            .or_insert(PeerUsage { minute, bytes: 0 });
+        match peer_usage.bytes.checked_add(bytes) {
+            Some(total) if total <= self.max_peer_bytes_per_minute => {
+                peer_usage.bytes = total;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum LogSink {
+    BigQuery {
+        client: Option<BQClient>,
+        project_id: String,
+        dataset_id: String,
+        table_id: String,
+    },
+    /// Appends the rows to a file as newline-delimited JSON
+    File {
+        path: PathBuf,
+        lock: Arc<tokio::sync::Mutex<()>>,
+    },
+}
+
+impl LogSink {
+    async fn write(&self, rows: &[LogRow]) -> anyhow::Result<()> {
+        match self {
+            LogSink::BigQuery {
+                client,
+                project_id,
+                dataset_id,
+                table_id,
+            } => {
+                let client = client
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("no bigquery client to write logs with"))?;
+                let mut insert_request = TableDataInsertAllRequest::new();
+                for row in rows {
+                    insert_request
+                        .add_row(None, row)
+                        .map_err(|e| anyhow!("unable to create row: {}", e))?;
+                }
+                client
+                    .tabledata()
+                    .insert_all(project_id, dataset_id, table_id, insert_request)
+                    .await
+                    .map_err(|e| anyhow!("unable to insert rows into bigquery: {}", e))?;
+            }
+            LogSink::File { path, lock } => {
+                let mut lines = Vec::new();
+                for row in rows {
+                    serde_json::to_writer(&mut lines, row)?;
+                    lines.push(b'\n');
+                }
+                let _guard = lock.lock().await;
+                let mut file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await?;
+                file.write_all(&lines).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
+use once_cell::sync::Lazy;
+
+/// Counter for ingested log records dropped as they aren't JSON objects
+pub(crate) static LOG_INGEST_DROPPED_RECORDS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "telemetry_service_log_ingest_dropped_records",
+        "Number of malformed log records dropped by the log ingestion endpoint",
+        &["chain_id"]
+    )
+    .unwrap()
+});
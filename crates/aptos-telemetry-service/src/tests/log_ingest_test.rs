@@ -0,0 +1,134 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::config::{PeerRole, PeerSet};
+use aptos_temppath::TempPath;
+use aptos_types::{chain_id::ChainId, PeerId};
+use serde_json::{json, Value};
+use warp::http::header::AUTHORIZATION;
+
+use crate::{
+    jwt_auth::create_jwt_token,
+    metrics::LOG_INGEST_DROPPED_RECORDS,
+    tests::test_context::{new_test_context_with_config, test_config, TestContext},
+    LogIngestConfig, LogSinkConfig,
+};
+
+const CHAIN_ID: u8 = 21;
+const EPOCH: u64 = 1;
+const MAX_BATCH_BYTES: u64 = 1024;
+const MAX_PEER_BYTES_PER_MINUTE: u64 = 2048;
+
+/// A context writing logs to `sink_path`, and the token of a validator sending logs to it
+async fn log_ingest_context(sink_path: &TempPath) -> (TestContext, PeerId, String) {
+    let mut config = test_config();
+    config.log_ingest_config = Some(LogIngestConfig {
+        max_batch_bytes: MAX_BATCH_BYTES,
+        max_peer_bytes_per_minute: MAX_PEER_BYTES_PER_MINUTE,
+        sink: LogSinkConfig::File {
+            path: sink_path.path().to_path_buf(),
+        },
+    });
+    let context = new_test_context_with_config(config).await;
+    context
+        .inner
+        .validator_cache()
+        .write()
+        .insert(ChainId::new(CHAIN_ID), (EPOCH, PeerSet::new()));
+
+    let peer_id = PeerId::random();
+    let token = create_jwt_token(
+        context.inner.clone(),
+        ChainId::new(CHAIN_ID),
+        peer_id,
+        PeerRole::Validator,
+        EPOCH,
+    )
+    .unwrap();
+    (context, peer_id, token)
+}
+
+async fn ingest(context: &TestContext, token: &str, body: &str) -> Value {
+    context
+        .execute(
+            warp::test::request()
+                .method("POST")
+                .path("/api/logs/ingest")
+                .header(AUTHORIZATION, format!("Bearer {}", token))
+                .body(body),
+        )
+        .await
+}
+
+fn read_sink(sink_path: &TempPath) -> Vec<Value> {
+    std::fs::read_to_string(sink_path.path())
+        .unwrap_or_default()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_log_ingest() {
+    let sink_path = TempPath::new();
+    let (context, peer_id, token) = log_ingest_context(&sink_path).await;
+
+    let batch = [
+        r#"{"level":"INFO","message":"committed block"}"#,
+        "",
+        "not json",
+        r#"{"level":"WARN","message":"peer disconnected"}"#,
+    ]
+    .join("\n");
+    let dropped_records =
+        LOG_INGEST_DROPPED_RECORDS.with_label_values(&[&ChainId::new(CHAIN_ID).to_string()]);
+    let dropped_before = dropped_records.get();
+    let resp = ingest(&context, &token, &batch).await;
+    assert_eq!(resp, json!({"accepted": 2, "dropped": 1}));
+    assert_eq!(dropped_records.get() - dropped_before, 1);
+
+    let rows = read_sink(&sink_path);
+    assert_eq!(rows.len(), 2);
+    for row in &rows {
+        assert_eq!(row["peer_id"], serde_json::to_value(peer_id).unwrap());
+        assert_eq!(
+            row["chain_id"],
+            serde_json::to_value(ChainId::new(CHAIN_ID)).unwrap()
+        );
+        assert_eq!(
+            row["role_type"],
+            serde_json::to_value(PeerRole::Validator).unwrap()
+        );
+    }
+    assert_eq!(rows[0]["record"]["message"], "committed block");
+    assert_eq!(rows[1]["record"]["message"], "peer disconnected");
+}
+
+#[tokio::test]
+async fn test_log_ingest_rejects_oversized_batch() {
+    let sink_path = TempPath::new();
+    let (mut context, _, token) = log_ingest_context(&sink_path).await;
+
+    context.expect_status_code = 413;
+    let batch = "x".repeat(MAX_BATCH_BYTES as usize + 1);
+    let resp = ingest(&context, &token, &batch).await;
+    assert!(resp["message"]
+        .as_str()
+        .unwrap()
+        .contains(&MAX_BATCH_BYTES.to_string()));
+    assert!(read_sink(&sink_path).is_empty());
+}
+
+#[tokio::test]
+async fn test_log_ingest_enforces_peer_quota() {
+    let sink_path = TempPath::new();
+    let (mut context, _, token) = log_ingest_context(&sink_path).await;
+
+    let record = json!({ "message": "a".repeat(900) }).to_string();
+    ingest(&context, &token, &record).await;
+    ingest(&context, &token, &record).await;
+
+    context.expect_status_code = 429;
+    ingest(&context, &token, &record).await;
+    assert_eq!(read_sink(&sink_path).len(), 2);
+}
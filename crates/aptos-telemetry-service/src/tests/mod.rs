@@ -2,4 +2,5 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod auth_test;
-mod test_context;
+mod log_ingest_test;
+pub(crate) mod test_context;
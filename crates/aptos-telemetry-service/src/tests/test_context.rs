@@ -15,16 +15,28 @@ use warp::http::Response;
 use warp::hyper::body::Bytes;
 
 pub async fn new_test_context() -> TestContext {
+    new_test_context_with_config(test_config()).await
+}
+
+pub async fn new_test_context_with_config(config: TelemetryServiceConfig) -> TestContext {
+    let cache = ValidatorSetCache::new(aptos_infallible::RwLock::new(HashMap::new()));
+
+    TestContext::new(Context::new(&config, cache, None, None))
+}
+
+pub fn test_config() -> TelemetryServiceConfig {
     let mut rng = ::rand::rngs::StdRng::from_seed([0u8; 32]);
     let server_private_key = x25519::PrivateKey::generate(&mut rng);
 
-    let config = &TelemetryServiceConfig {
+    TelemetryServiceConfig {
         address: format!("{}:{}", "127.0.0.1", 80).parse().unwrap(),
         tls_cert_path: None,
         tls_key_path: None,
         trusted_full_node_addresses: HashMap::new(),
         server_private_key: ConfigKey::new(server_private_key),
         jwt_signing_key: "jwt_signing_key".into(),
+        jwt_token_lifetime_secs: 3600,
+        jwt_refresh_grace_secs: 300,
         update_interval: 60,
         gcp_bq_config: GCPBigQueryConfig {
             project_id: String::from("1"),
@@ -33,10 +45,8 @@ pub async fn new_test_context() -> TestContext {
         },
         victoria_metrics_base_url: "".into(),
         victoria_metrics_token: "".into(),
-    };
-    let cache = ValidatorSetCache::new(aptos_infallible::RwLock::new(HashMap::new()));
-
-    TestContext::new(Context::new(config, cache, None, None))
+        log_ingest_config: None,
+    }
 }
 
 #[derive(Clone)]
@@ -19,6 +19,11 @@ pub struct AuthResponse {
     pub handshake_msg: Vec<u8>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthRefreshResponse {
+    pub token: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct Claims {
     pub chain_id: ChainId,
@@ -30,3 +30,20 @@ pub(crate) struct BigQueryRow {
     pub event_timestamp: u64,
     pub event_params: Vec<serde_json::Value>,
 }
+
+/// A node log record, tagged with the identity of the node which sent it
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct LogRow {
+    #[serde(flatten)]
+    pub event_identity: EventIdentity,
+    /// When the record was received, in seconds since the unix epoch
+    pub received_timestamp: u64,
+    pub record: serde_json::Value,
+}
+
+/// The number of records of a log batch which were written, and dropped for being malformed
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LogIngestResponse {
+    pub accepted: usize,
+    pub dropped: usize,
+}
@@ -1,16 +1,20 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::{CliCommand, CliTypedResult, TransactionOptions, TransactionSummary};
-use aptos_rest_client::{
-    aptos_api_types::{WriteResource, WriteSetChange},
-    Transaction,
+use crate::account::transfer::{coin_balance, coin_store_tag};
+use crate::common::types::{
+    CliCommand, CliError, CliTypedResult, TransactionOptions, TransactionSummary,
+};
+use aptos_rest_client::{aptos_api_types::Address, Client, Resource};
+use aptos_types::{
+    account_address::{create_resource_address, AccountAddress},
+    transaction::authenticator::AuthenticationKey,
+    utility_coin::APTOS_COIN_TYPE,
 };
-use aptos_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
 use async_trait::async_trait;
-use cached_packages::aptos_stdlib::resource_account_create_resource_account;
+use cached_packages::aptos_stdlib::{self, resource_account_create_resource_account};
 use clap::Parser;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
 /// Command to create a resource account on-chain
@@ -31,6 +35,13 @@ pub struct CreateResourceAccount {
     #[clap(long, parse(try_from_str = AuthenticationKey::from_str))]
     pub(crate) authentication_key: Option<AuthenticationKey>,
 
+    /// Amount of Octas to transfer to the resource account after it's created
+    ///
+    /// The resource account must have registered AptosCoin to receive it, which creating it
+    /// doesn't do.
+    #[clap(long)]
+    pub(crate) fund_amount: Option<u64>,
+
     #[clap(flatten)]
     pub(crate) txn_options: TransactionOptions,
 }
@@ -38,39 +49,18 @@ pub struct CreateResourceAccount {
 /// A shortened create resource account output
 #[derive(Clone, Debug, Serialize)]
 pub struct CreateResourceAccountSummary {
-    pub resource_account: Option<AccountAddress>,
+    pub resource_account: AccountAddress,
+    /// Whether the signer capability of the resource account is still held by the sender's
+    /// `resource_account::Container`, for a module published to the resource account to
+    /// retrieve, rather than transferred out of it
+    pub signer_capability_retained: bool,
+    /// The transaction transferring `--fund-amount` to the resource account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fund_transaction: Option<TransactionSummary>,
     #[serde(flatten)]
     pub transaction_summary: TransactionSummary,
 }
 
-impl From<Transaction> for CreateResourceAccountSummary {
-    fn from(transaction: Transaction) -> Self {
-        let transaction_summary = TransactionSummary::from(&transaction);
-
-        let mut summary = CreateResourceAccountSummary {
-            transaction_summary,
-            resource_account: None,
-        };
-
-        if let Transaction::UserTransaction(txn) = transaction {
-            summary.resource_account = txn.info.changes.iter().find_map(|change| match change {
-                WriteSetChange::WriteResource(WriteResource { address, data, .. }) => {
-                    if data.typ.name.as_str() == "Account"
-                        && *address.inner().to_hex() != *txn.request.sender.inner().to_hex()
-                    {
-                        Some(*address.inner())
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
-            });
-        }
-
-        summary
-    }
-}
-
 #[async_trait]
 impl CliCommand<CreateResourceAccountSummary> for CreateResourceAccount {
     fn command_name(&self) -> &'static str {
@@ -83,12 +73,166 @@ impl CliCommand<CreateResourceAccountSummary> for CreateResourceAccount {
         } else {
             vec![]
         };
-        self.txn_options
-            .submit_transaction(resource_account_create_resource_account(
-                bcs::to_bytes(&self.seed)?,
-                authentication_key,
+        let seed = bcs::to_bytes(&self.seed)?;
+        let sender = self.txn_options.sender_address()?;
+        let resource_account = create_resource_address(sender, &seed);
+
+        let transaction_summary = TransactionSummary::from(
+            self.txn_options
+                .submit_transaction(resource_account_create_resource_account(
+                    seed,
+                    authentication_key,
+                ))
+                .await?,
+        );
+        if transaction_summary.success != Some(true) {
+            return Err(CliError::ApiError(format!(
+                "Transaction {} failed to create resource account {}: {}",
+                transaction_summary.transaction_hash,
+                resource_account,
+                transaction_summary.vm_status.unwrap_or_default()
+            )));
+        }
+
+        let client = self.txn_options.rest_client()?;
+        client.get_account(resource_account).await.map_err(|err| {
+            CliError::ApiError(format!(
+                "Resource account {} wasn't found after its creation: {}",
+                resource_account, err
             ))
+        })?;
+        // The capability as of the creation, as it may be retrieved right after
+        let version = transaction_summary.version.ok_or_else(|| {
+            CliError::UnexpectedError(format!(
+                "Transaction {} has no version",
+                transaction_summary.transaction_hash
+            ))
+        })?;
+        let container = client
+            .get_account_resource_at_version(sender, CONTAINER_RESOURCE, version)
             .await
-            .map(CreateResourceAccountSummary::from)
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_inner();
+        let signer_capability_retained =
+            holds_signer_capability(container.as_ref(), resource_account)?;
+
+        let fund_transaction = if let Some(amount) = self.fund_amount {
+            let coin_store = coin_store_tag(APTOS_COIN_TYPE.clone());
+            if coin_balance(&client, resource_account, &coin_store)
+                .await?
+                .is_none()
+            {
+                return Err(CliError::CommandArgumentError(format!(
+                    "Resource account {} was created, but it can't be funded until it registers {}",
+                    resource_account,
+                    APTOS_COIN_TYPE.to_string()
+                )));
+            }
+            Some(TransactionSummary::from(
+                self.txn_options
+                    .submit_transaction(aptos_stdlib::coin_transfer(
+                        APTOS_COIN_TYPE.clone(),
+                        resource_account,
+                        amount,
+                    ))
+                    .await?,
+            ))
+        } else {
+            None
+        };
+
+        Ok(CreateResourceAccountSummary {
+            resource_account,
+            signer_capability_retained,
+            fund_transaction,
+            transaction_summary,
+        })
+    }
+}
+
+const CONTAINER_RESOURCE: &str = "0x1::resource_account::Container";
+
+/// A `resource_account::Container`, only keeping the addresses of the signer capabilities
+#[derive(Deserialize)]
+struct Container {
+    store: SimpleMap,
+}
+
+#[derive(Deserialize)]
+struct SimpleMap {
+    data: Vec<SimpleMapElement>,
+}
+
+#[derive(Deserialize)]
+struct SimpleMapElement {
+    key: Address,
+}
+
+/// Whether `container`, the `resource_account::Container` of the origin account if it has one,
+/// holds the signer capability of `resource_account`
+fn holds_signer_capability(
+    container: Option<&Resource>,
+    resource_account: AccountAddress,
+) -> CliTypedResult<bool> {
+    let container = match container {
+        Some(container) => container,
+        None => return Ok(false),
+    };
+    let container: Container = serde_json::from_value(container.data.clone())
+        .map_err(|err| CliError::UnableToParse(CONTAINER_RESOURCE, err.to_string()))?;
+    Ok(container
+        .store
+        .data
+        .iter()
+        .any(|element| *element.key.inner() == resource_account))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn container(data: serde_json::Value) -> Resource {
+        serde_json::from_value(json!({
+            "type": CONTAINER_RESOURCE,
+            "data": data,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_holds_signer_capability() {
+        let resource_account = AccountAddress::from_hex_literal("0xbeef").unwrap();
+        let other = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let holding = |accounts: &[AccountAddress]| {
+            container(json!({
+                "store": {
+                    "data": accounts
+                        .iter()
+                        .map(|account| json!({
+                            "key": account.to_hex_literal(),
+                            "value": {"account": account.to_hex_literal()},
+                        }))
+                        .collect::<Vec<_>>(),
+                },
+            }))
+        };
+
+        assert!(holds_signer_capability(
+            Some(&holding(&[other, resource_account])),
+            resource_account
+        )
+        .unwrap());
+        // Retrieved capabilities are removed from the container
+        assert!(!holds_signer_capability(Some(&holding(&[other])), resource_account).unwrap());
+        assert!(!holds_signer_capability(Some(&holding(&[])), resource_account).unwrap());
+        assert!(!holds_signer_capability(None, resource_account).unwrap());
+
+        // A container that can't be read isn't taken as not holding the capability
+        assert!(holds_signer_capability(
+            Some(&container(json!({"store": "unexpected"}))),
+            resource_account
+        )
+        .is_err());
     }
 }
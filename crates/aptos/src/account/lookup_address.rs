@@ -0,0 +1,78 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{
+    CliCommand, CliConfig, CliError, CliTypedResult, ConfigSearchMode, EncodingOptions,
+    ProfileOptions, PublicKeyInputOptions, RestOptions,
+};
+use aptos_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
+use async_trait::async_trait;
+use clap::Parser;
+
+/// Command to lookup the address of the account controlled by a key
+///
+/// Follows authentication key rotations, so the address of the account
+/// originally created with another key is found.  Defaults to the public
+/// key of the profile.
+#[derive(Debug, Parser)]
+pub struct LookupAddress {
+    /// Authentication key to lookup the account for
+    #[clap(long, group = "public_key_input")]
+    pub(crate) auth_key: Option<AuthenticationKey>,
+
+    /// Fail if the account doesn't exist yet
+    #[clap(long)]
+    pub(crate) must_exist: bool,
+
+    #[clap(flatten)]
+    pub(crate) public_key_options: PublicKeyInputOptions,
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+impl LookupAddress {
+    fn auth_key(&self) -> CliTypedResult<AuthenticationKey> {
+        if let Some(auth_key) = self.auth_key {
+            return Ok(auth_key);
+        }
+        if let Some(public_key) = self
+            .public_key_options
+            .extract_public_key_cli(self.encoding_options.encoding)?
+        {
+            return Ok(AuthenticationKey::ed25519(&public_key));
+        }
+        if let Some(Some(public_key)) = CliConfig::load_profile(
+            &self.profile_options.profile,
+            ConfigSearchMode::CurrentDirAndParents,
+        )?
+        .map(|profile| profile.public_key)
+        {
+            return Ok(AuthenticationKey::ed25519(&public_key));
+        }
+        Err(CliError::CommandArgumentError(
+            "Please provide --auth-key, --public-key or --public-key-file, or run aptos init"
+                .to_string(),
+        ))
+    }
+}
+
+#[async_trait]
+impl CliCommand<AccountAddress> for LookupAddress {
+    fn command_name(&self) -> &'static str {
+        "LookupAddress"
+    }
+
+    async fn execute(self) -> CliTypedResult<AccountAddress> {
+        let auth_key = self.auth_key()?;
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        Ok(client
+            .lookup_address(auth_key, self.must_exist)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_inner())
+    }
+}
@@ -8,6 +8,7 @@ pub mod create;
 pub mod create_resource_account;
 pub mod fund;
 pub mod list;
+pub mod lookup_address;
 pub mod transfer;
 
 /// Tool for interacting with accounts
@@ -20,6 +21,7 @@ pub enum AccountTool {
     CreateResourceAccount(create_resource_account::CreateResourceAccount),
     FundWithFaucet(fund::FundWithFaucet),
     List(list::ListAccount),
+    LookupAddress(lookup_address::LookupAddress),
     Transfer(transfer::TransferCoins),
 }
 
@@ -30,6 +32,7 @@ impl AccountTool {
             AccountTool::CreateResourceAccount(tool) => tool.execute_serialized().await,
             AccountTool::FundWithFaucet(tool) => tool.execute_serialized().await,
             AccountTool::List(tool) => tool.execute_serialized().await,
+            AccountTool::LookupAddress(tool) => tool.execute_serialized().await,
             AccountTool::Transfer(tool) => tool.execute_serialized().await,
         }
     }
@@ -1,18 +1,27 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::{CliCommand, CliTypedResult, TransactionOptions};
-use aptos_rest_client::aptos_api_types::HashValue;
+use crate::common::types::{CliCommand, CliError, CliTypedResult, TransactionOptions};
+use aptos_rest_client::aptos_api_types::{HashValue, MoveType};
 use aptos_rest_client::{
     aptos_api_types::{WriteResource, WriteSetChange},
-    Transaction,
+    Client, Transaction,
+};
+use aptos_sdk::move_types::{
+    ident_str,
+    language_storage::{StructTag, TypeTag},
+};
+use aptos_types::{
+    account_address::AccountAddress, account_config::CORE_CODE_ADDRESS,
+    utility_coin::APTOS_COIN_TYPE,
 };
-use aptos_types::account_address::AccountAddress;
 use async_trait::async_trait;
 use cached_packages::aptos_stdlib;
 use clap::Parser;
 use serde::Serialize;
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, convert::TryFrom};
+
+pub const DEFAULT_COIN_TYPE: &str = "0x1::aptos_coin::AptosCoin";
 
 /// Command to transfer coins between accounts
 ///
@@ -23,8 +32,21 @@ pub struct TransferCoins {
     pub(crate) account: AccountAddress,
 
     /// Amount of coins to transfer
+    #[clap(long, required_unless_present = "max", conflicts_with = "max")]
+    pub(crate) amount: Option<u64>,
+
+    /// Transfer the sender's whole balance of the coin type
+    ///
+    /// When transferring AptosCoin, the estimated gas cost of the transfer is kept back
+    /// so the transaction can pay for itself.
     #[clap(long)]
-    pub(crate) amount: u64,
+    pub(crate) max: bool,
+
+    /// Type of the coin to transfer
+    ///
+    /// The transfer fails on chain if the recipient hasn't registered the coin type.
+    #[clap(long, default_value = DEFAULT_COIN_TYPE)]
+    pub(crate) coin_type: MoveType,
 
     #[clap(flatten)]
     pub(crate) txn_options: TransactionOptions,
@@ -37,13 +59,152 @@ impl CliCommand<TransferSummary> for TransferCoins {
     }
 
     async fn execute(self) -> CliTypedResult<TransferSummary> {
+        let coin_type = TypeTag::try_from(self.coin_type.clone())
+            .map_err(|err| CliError::UnableToParse("--coin-type", err.to_string()))?;
+        let coin_store = coin_store_tag(coin_type.clone());
+        let client = self
+            .txn_options
+            .rest_options
+            .client(&self.txn_options.profile_options.profile)?;
+
+        // The transfer is still submitted, so it fails on chain like any other transaction would
+        if coin_balance(&client, self.account, &coin_store)
+            .await?
+            .is_none()
+        {
+            eprintln!(
+                "Warning: account {} has not registered coin type {}, the transfer is expected to fail",
+                self.account, self.coin_type
+            );
+        }
+
+        let amount = match (self.amount, self.max) {
+            (Some(_), true) => {
+                return Err(CliError::CommandArgumentError(
+                    "--amount can't be used together with --max".to_string(),
+                ))
+            }
+            (Some(amount), false) => amount,
+            (None, true) => {
+                self.max_amount(&client, coin_type.clone(), &coin_store)
+                    .await?
+            }
+            (None, false) => {
+                return Err(CliError::CommandArgumentError(
+                    "Either --amount or --max must be provided".to_string(),
+                ))
+            }
+        };
+
+        let payload = aptos_stdlib::coin_transfer(coin_type, self.account, amount);
+        if self.max {
+            // Make sure the computed amount doesn't leave the sender short on gas
+            let simulated = self
+                .txn_options
+                .simulate_transaction(payload.clone())
+                .await?;
+            if !simulated.info.success {
+                return Err(CliError::ApiError(format!(
+                    "Transfer of {} coins is expected to fail: {}",
+                    amount, simulated.info.vm_status
+                )));
+            }
+        }
+
         self.txn_options
-            .submit_transaction(aptos_stdlib::aptos_coin_transfer(self.account, self.amount))
+            .submit_transaction(payload)
             .await
             .map(TransferSummary::from)
     }
 }
 
+impl TransferCoins {
+    /// Computes the amount that empties the sender's balance of the coin type, minus the gas
+    /// cost of the transfer if gas is paid in the same coin.
+    async fn max_amount(
+        &self,
+        client: &Client,
+        coin_type: TypeTag,
+        coin_store: &StructTag,
+    ) -> CliTypedResult<u64> {
+        let sender = self.txn_options.sender_address()?;
+        let balance = coin_balance(client, sender, coin_store)
+            .await?
+            .ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "Account {} has not registered coin type {}",
+                    sender, self.coin_type
+                ))
+            })?;
+        if balance == 0 {
+            return Err(CliError::CommandArgumentError(format!(
+                "Account {} has no {} to transfer",
+                sender, self.coin_type
+            )));
+        }
+        if coin_type != *APTOS_COIN_TYPE {
+            return Ok(balance);
+        }
+
+        // The gas used by a transfer doesn't depend on the amount
+        let simulated = self
+            .txn_options
+            .simulate_transaction(aptos_stdlib::coin_transfer(coin_type, self.account, 1))
+            .await?;
+        if !simulated.info.success {
+            return Err(CliError::ApiError(format!(
+                "Failed to estimate the gas cost of the transfer: {}",
+                simulated.info.vm_status
+            )));
+        }
+        let gas_cost = simulated.info.gas_used.0 * simulated.request.gas_unit_price.0;
+        balance.checked_sub(gas_cost).ok_or_else(|| {
+            CliError::CommandArgumentError(format!(
+                "Balance of {} doesn't cover the estimated gas cost of {}",
+                balance, gas_cost
+            ))
+        })
+    }
+}
+
+pub(crate) fn coin_store_tag(coin_type: TypeTag) -> StructTag {
+    StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: ident_str!("coin").to_owned(),
+        name: ident_str!("CoinStore").to_owned(),
+        type_params: vec![coin_type],
+    }
+}
+
+/// Returns the balance of `address` in the coin of `coin_store`, or `None` if it hasn't
+/// registered the coin.
+pub(crate) async fn coin_balance(
+    client: &Client,
+    address: AccountAddress,
+    coin_store: &StructTag,
+) -> CliTypedResult<Option<u64>> {
+    let resources = client
+        .get_account_resources(address)
+        .await
+        .map_err(|err| CliError::ApiError(err.to_string()))?
+        .into_inner();
+    let coin_store = match resources
+        .into_iter()
+        .find(|resource| &resource.resource_type == coin_store)
+    {
+        Some(coin_store) => coin_store,
+        None => return Ok(None),
+    };
+
+    coin_store.data["coin"]["value"]
+        .as_str()
+        .and_then(|value| value.parse().ok())
+        .map(Some)
+        .ok_or_else(|| {
+            CliError::UnexpectedError(format!("Unexpected coin store {}", coin_store.data))
+        })
+}
+
 const SUPPORTED_COINS: [&str; 1] = ["0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"];
 
 /// A shortened transaction output
@@ -6,25 +6,59 @@ use crate::common::{
     types::{
         account_address_from_public_key, CliCommand, CliConfig, CliError, CliTypedResult,
         EncodingOptions, PrivateKeyInputOptions, ProfileConfig, ProfileOptions, PromptOptions,
-        RngArgs,
+        PublicKeyInputOptions, RngArgs,
     },
-    utils::{fund_account, prompt_yes_with_override, read_line},
+    utils::{chain_id, fund_account, prompt_yes_with_override, read_line},
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    PrivateKey, ValidCryptoMaterialStringExt,
 };
-use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, ValidCryptoMaterialStringExt};
 use async_trait::async_trait;
-use clap::Parser;
+use clap::{ArgEnum, Parser};
 use reqwest::Url;
 use std::collections::BTreeMap;
 
 pub const DEFAULT_REST_URL: &str = "https://fullnode.devnet.aptoslabs.com/v1";
 pub const DEFAULT_FAUCET_URL: &str = "https://faucet.devnet.aptoslabs.com";
+const TESTNET_REST_URL: &str = "https://fullnode.testnet.aptoslabs.com/v1";
+const TESTNET_FAUCET_URL: &str = "https://faucet.testnet.aptoslabs.com";
+const LOCAL_REST_URL: &str = "http://localhost:8080/v1";
+const LOCAL_FAUCET_URL: &str = "http://localhost:8081";
 const NUM_DEFAULT_COINS: u64 = 10000;
 
+/// A network to configure a profile for
+#[derive(ArgEnum, Clone, Copy, Debug)]
+pub enum Network {
+    Devnet,
+    Testnet,
+    /// A local testnet, e.g. from `aptos node run-local-testnet --with-faucet`
+    Local,
+    /// Any other network, given with `--rest-url` and an optional `--faucet-url`
+    Custom,
+}
+
+impl Network {
+    /// The rest and faucet URLs of the network, which a custom network doesn't have
+    fn urls(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Network::Devnet => Some((DEFAULT_REST_URL, DEFAULT_FAUCET_URL)),
+            Network::Testnet => Some((TESTNET_REST_URL, TESTNET_FAUCET_URL)),
+            Network::Local => Some((LOCAL_REST_URL, LOCAL_FAUCET_URL)),
+            Network::Custom => None,
+        }
+    }
+}
+
 /// Tool to initialize current directory for the aptos tool
 ///
 /// Configuration will be pushed into .aptos/config.yaml
 #[derive(Debug, Parser)]
 pub struct InitTool {
+    /// Network to configure the profile for, without prompting for its endpoints
+    #[clap(long, arg_enum, ignore_case = true)]
+    pub network: Option<Network>,
+
     /// URL to a fullnode on the network
     #[clap(long)]
     pub rest_url: Option<Url>,
@@ -37,11 +71,19 @@ pub struct InitTool {
     #[clap(long)]
     pub skip_faucet: bool,
 
+    /// Only store the public key and address of the account, without its private key
+    ///
+    /// The profile can be used to read from the chain, but not to sign transactions.
+    #[clap(long, conflicts_with_all = &["private-key", "private-key-file"])]
+    pub public_key_only: bool,
+
     #[clap(flatten)]
     pub rng_args: RngArgs,
     #[clap(flatten)]
     pub(crate) private_key_options: PrivateKeyInputOptions,
     #[clap(flatten)]
+    pub(crate) public_key_options: PublicKeyInputOptions,
+    #[clap(flatten)]
     pub(crate) profile_options: ProfileOptions,
     #[clap(flatten)]
     pub(crate) prompt_options: PromptOptions,
@@ -74,10 +116,20 @@ impl CliCommand<()> for InitTool {
 
         eprintln!("Configuring for profile {}", self.profile_options.profile);
 
+        let network_urls = self.network.and_then(Network::urls);
+        if matches!(self.network, Some(Network::Custom)) && self.rest_url.is_none() {
+            return Err(CliError::CommandArgumentError(
+                "--rest-url must be provided for a custom network".to_string(),
+            ));
+        }
+
         // Rest Endpoint
-        let rest_url = if let Some(rest_url) = self.rest_url {
+        let rest_url = if let Some(rest_url) = self.rest_url.clone() {
             eprintln!("Using command line argument for rest URL {}", rest_url);
             rest_url
+        } else if let Some((rest_url, _)) = network_urls {
+            eprintln!("Using rest URL {} of the network", rest_url);
+            parse_default_url(rest_url, "rest")?
         } else {
             eprintln!(
                 "Enter your rest endpoint [Current: {} | No input: {}]",
@@ -104,9 +156,15 @@ impl CliCommand<()> for InitTool {
         let faucet_url = if self.skip_faucet {
             eprintln!("Not configuring a faucet because --skip-faucet was provided");
             None
-        } else if let Some(faucet_url) = self.faucet_url {
+        } else if let Some(faucet_url) = self.faucet_url.clone() {
             eprintln!("Using command line argument for faucet URL {}", faucet_url);
             Some(faucet_url)
+        } else if let Some((_, faucet_url)) = network_urls {
+            eprintln!("Using faucet URL {} of the network", faucet_url);
+            Some(parse_default_url(faucet_url, "faucet")?)
+        } else if self.network.is_some() {
+            eprintln!("Not configuring a faucet for a custom network without --faucet-url");
+            None
         } else {
             eprintln!(
                 "Enter your faucet endpoint [Current: {} | No input: {} | 'skip' to not use a faucet]",
@@ -135,37 +193,25 @@ impl CliCommand<()> for InitTool {
         };
         profile_config.faucet_url = faucet_url.as_ref().map(|inner| inner.to_string());
 
-        // Private key
-        let private_key = if let Some(private_key) = self
-            .private_key_options
-            .extract_private_key_cli(self.encoding_options.encoding)?
-        {
-            eprintln!("Using command line argument for private key");
-            private_key
+        // Keys
+        let address = if self.public_key_only {
+            let public_key = self.public_key(&profile_config)?;
+            let address = account_address_from_public_key(&public_key);
+            profile_config.private_key = None;
+            profile_config.public_key = Some(public_key);
+            profile_config.account = Some(address);
+            profile_config.is_view_only = Some(true);
+            address
         } else {
-            eprintln!("Enter your private key as a hex literal (0x...) [Current: {} | No input: Generate new key (or keep one if present)]", profile_config.private_key.as_ref().map(|_| "Redacted").unwrap_or("None"));
-            let input = read_line("Private key")?;
-            let input = input.trim();
-            if input.is_empty() {
-                if let Some(private_key) = profile_config.private_key {
-                    eprintln!("No key given, keeping existing key...");
-                    private_key
-                } else {
-                    eprintln!("No key given, generating key...");
-                    self.rng_args
-                        .key_generator()?
-                        .generate_ed25519_private_key()
-                }
-            } else {
-                Ed25519PrivateKey::from_encoded_string(input)
-                    .map_err(|err| CliError::UnableToParse("Ed25519PrivateKey", err.to_string()))?
-            }
+            let private_key = self.private_key(&profile_config)?;
+            let public_key = private_key.public_key();
+            let address = account_address_from_public_key(&public_key);
+            profile_config.private_key = Some(private_key);
+            profile_config.public_key = Some(public_key);
+            profile_config.account = Some(address);
+            profile_config.is_view_only = None;
+            address
         };
-        let public_key = private_key.public_key();
-        let address = account_address_from_public_key(&public_key);
-        profile_config.private_key = Some(private_key);
-        profile_config.public_key = Some(public_key);
-        profile_config.account = Some(address);
 
         // Create account if it doesn't exist (and there's a faucet)
         let client = aptos_rest_client::Client::new(rest_url);
@@ -179,6 +225,15 @@ impl CliCommand<()> for InitTool {
             }
         }
 
+        // Remember which chain the profile is for, so transactions aren't signed for another one
+        match chain_id(&client).await {
+            Ok(chain_id) => profile_config.chain_id = Some(chain_id),
+            Err(err) => eprintln!(
+                "Unable to retrieve the chain ID of the rest endpoint: {}",
+                err
+            ),
+        }
+
         // Ensure the loaded config has profiles setup for a possible empty file
         if config.profiles.is_none() {
             config.profiles = Some(BTreeMap::new());
@@ -193,3 +248,78 @@ impl CliCommand<()> for InitTool {
         Ok(())
     }
 }
+
+impl InitTool {
+    /// The private key from the command line or the prompt, or the one of the profile
+    fn private_key(&self, profile_config: &ProfileConfig) -> CliTypedResult<Ed25519PrivateKey> {
+        if let Some(private_key) = self
+            .private_key_options
+            .extract_private_key_cli(self.encoding_options.encoding)?
+        {
+            eprintln!("Using command line argument for private key");
+            return Ok(private_key);
+        }
+
+        eprintln!("Enter your private key as a hex literal (0x...) [Current: {} | No input: Generate new key (or keep one if present)]", profile_config.private_key.as_ref().map(|_| "Redacted").unwrap_or("None"));
+        let input = read_line("Private key")?;
+        let input = input.trim();
+        if input.is_empty() {
+            if let Some(ref private_key) = profile_config.private_key {
+                eprintln!("No key given, keeping existing key...");
+                Ok(private_key.clone())
+            } else {
+                eprintln!("No key given, generating key...");
+                Ok(self
+                    .rng_args
+                    .key_generator()?
+                    .generate_ed25519_private_key())
+            }
+        } else {
+            Ed25519PrivateKey::from_encoded_string(input)
+                .map_err(|err| CliError::UnableToParse("Ed25519PrivateKey", err.to_string()))
+        }
+    }
+
+    /// The public key from the command line or the prompt, or the one of the profile
+    fn public_key(&self, profile_config: &ProfileConfig) -> CliTypedResult<Ed25519PublicKey> {
+        if let Some(public_key) = self
+            .public_key_options
+            .extract_public_key_cli(self.encoding_options.encoding)?
+        {
+            eprintln!("Using command line argument for public key");
+            return Ok(public_key);
+        }
+
+        let current = profile_config
+            .public_key
+            .as_ref()
+            .map(|public_key| public_key.to_encoded_string())
+            .transpose()
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        eprintln!(
+            "Enter your public key as a hex literal (0x...) [Current: {}]",
+            current.as_deref().unwrap_or("None")
+        );
+        let input = read_line("Public key")?;
+        let input = input.trim();
+        if input.is_empty() {
+            if let Some(ref public_key) = profile_config.public_key {
+                eprintln!("No key given, keeping existing key...");
+                Ok(public_key.clone())
+            } else {
+                Err(CliError::CommandArgumentError(
+                    "A public key must be given for a view-only profile".to_string(),
+                ))
+            }
+        } else {
+            Ed25519PublicKey::from_encoded_string(input)
+                .map_err(|err| CliError::UnableToParse("Ed25519PublicKey", err.to_string()))
+        }
+    }
+}
+
+fn parse_default_url(url: &str, name: &str) -> CliTypedResult<Url> {
+    Url::parse(url).map_err(|err| {
+        CliError::UnexpectedError(format!("Failed to parse default {} URL {}", name, err))
+    })
+}
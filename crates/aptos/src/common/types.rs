@@ -6,8 +6,8 @@ use crate::{
         init::{DEFAULT_FAUCET_URL, DEFAULT_REST_URL},
         utils::{
             chain_id, check_if_file_exists, create_dir_if_not_exist, dir_default_to_current,
-            get_sequence_number, read_from_file, start_logger, to_common_result,
-            to_common_success_result, write_to_file, write_to_file_with_opts,
+            get_sequence_number, prompt_yes_with_override, read_from_file, start_logger,
+            to_common_result, to_common_success_result, write_to_file, write_to_file_with_opts,
             write_to_user_only_file,
         },
     },
@@ -19,8 +19,8 @@ use aptos_crypto::{
     x25519, PrivateKey, ValidCryptoMaterial, ValidCryptoMaterialStringExt,
 };
 use aptos_keygen::KeyGen;
-use aptos_rest_client::aptos_api_types::HashValue;
-use aptos_rest_client::{Client, Transaction};
+use aptos_rest_client::aptos_api_types::{HashValue, UserTransaction};
+use aptos_rest_client::{simulation_transaction, Client, Transaction};
 use aptos_sdk::{
     move_types::{
         ident_str,
@@ -29,6 +29,7 @@ use aptos_sdk::{
     transaction_builder::TransactionFactory,
     types::LocalAccount,
 };
+use aptos_types::chain_id::ChainId;
 use aptos_types::transaction::{
     authenticator::AuthenticationKey, EntryFunction, TransactionPayload,
 };
@@ -36,13 +37,19 @@ use async_trait::async_trait;
 use clap::{ArgEnum, Parser};
 use hex::FromHexError;
 use move_deps::move_core_types::account_address::AccountAddress;
+use ring::{
+    aead, pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
 use serde::{Deserialize, Serialize};
 #[cfg(unix)]
 use std::os::unix::fs::OpenOptionsExt;
 use std::{
     collections::BTreeMap,
+    convert::TryFrom,
     fmt::{Debug, Display, Formatter},
     fs::OpenOptions,
+    num::NonZeroU32,
     path::{Path, PathBuf},
     str::FromStr,
     time::Instant,
@@ -76,6 +83,11 @@ pub enum CliError {
     MoveCompilationError(String),
     #[error("Move unit tests failed")]
     MoveTestError,
+    #[error(
+        "Move unit tests exceeded the instruction bound of {0}, raise it with \
+        `--instruction-bound` or remove it with `--unbounded`"
+    )]
+    MoveTestTimeout(u64),
     #[error("Move Prover failed: {0}")]
     MoveProverError(String),
     #[error("Unable to parse '{0}': error: {1}")]
@@ -98,6 +110,7 @@ impl CliError {
             CliError::IO(_, _) => "IO",
             CliError::MoveCompilationError(_) => "MoveCompilationError",
             CliError::MoveTestError => "MoveTestError",
+            CliError::MoveTestTimeout(_) => "MoveTestTimeout",
             CliError::MoveProverError(_) => "MoveProverError",
             CliError::UnableToParse(_, _) => "UnableToParse",
             CliError::UnableToReadFile(_, _) => "UnableToReadFile",
@@ -190,6 +203,12 @@ pub struct ProfileConfig {
     /// URL for the Faucet endpoint (if applicable)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub faucet_url: Option<String>,
+    /// Chain ID of the network the profile is for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<ChainId>,
+    /// Whether the profile has no private key, and can only be used to read from the chain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_view_only: Option<bool>,
 }
 
 /// ProfileConfig but without the private parts
@@ -204,6 +223,10 @@ pub struct ProfileSummary {
     pub rest_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub faucet_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<ChainId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_view_only: Option<bool>,
 }
 
 impl From<&ProfileConfig> for ProfileSummary {
@@ -214,10 +237,116 @@ impl From<&ProfileConfig> for ProfileSummary {
             account: config.account,
             rest_url: config.rest_url.clone(),
             faucet_url: config.faucet_url.clone(),
+            chain_id: config.chain_id,
+            is_view_only: config.is_view_only,
         }
     }
 }
 
+const ENCRYPTED_PROFILE_VERSION: u8 = 1;
+const ENCRYPTED_PROFILE_ITERATIONS: u32 = 100_000;
+
+/// A profile encrypted with a password, to move it between machines
+///
+/// The key is derived from the password with PBKDF2-HMAC-SHA256, and the profile is encrypted
+/// with AES-256-GCM.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedProfile {
+    pub version: u8,
+    /// Iterations of the key derivation
+    pub iterations: u32,
+    /// Hex encoded salt of the key derivation
+    pub salt: String,
+    /// Hex encoded nonce of the encryption
+    pub nonce: String,
+    /// Hex encoded encryption of the JSON serialized profile
+    pub ciphertext: String,
+}
+
+impl ProfileConfig {
+    /// Encrypts the profile, including its private key, with a key derived from `password`
+    pub fn encrypt(&self, password: &str) -> CliTypedResult<EncryptedProfile> {
+        let rng = SystemRandom::new();
+        let mut salt = [0u8; 32];
+        let mut nonce = [0u8; aead::NONCE_LEN];
+        rng.fill(&mut salt)
+            .and_then(|_| rng.fill(&mut nonce))
+            .map_err(|_| CliError::UnexpectedError("Failed to generate a salt".to_string()))?;
+
+        let key = profile_encryption_key(password, &salt, ENCRYPTED_PROFILE_ITERATIONS)?;
+        let mut in_out = serde_json::to_vec(self).map_err(|err| {
+            CliError::UnexpectedError(format!("Failed to serialize profile {}", err))
+        })?;
+        key.seal_in_place_append_tag(
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::empty(),
+            &mut in_out,
+        )
+        .map_err(|_| CliError::UnexpectedError("Failed to encrypt profile".to_string()))?;
+
+        Ok(EncryptedProfile {
+            version: ENCRYPTED_PROFILE_VERSION,
+            iterations: ENCRYPTED_PROFILE_ITERATIONS,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(in_out),
+        })
+    }
+}
+
+impl EncryptedProfile {
+    /// Decrypts the profile, failing if `password` isn't the one it was encrypted with
+    pub fn decrypt(&self, password: &str) -> CliTypedResult<ProfileConfig> {
+        if self.version != ENCRYPTED_PROFILE_VERSION {
+            return Err(CliError::CommandArgumentError(format!(
+                "Unsupported encrypted profile version {}",
+                self.version
+            )));
+        }
+        let salt = hex::decode(&self.salt)?;
+        let nonce = <[u8; aead::NONCE_LEN]>::try_from(hex::decode(&self.nonce)?.as_slice())
+            .map_err(|_| CliError::UnableToParse("nonce", self.nonce.clone()))?;
+
+        let key = profile_encryption_key(password, &salt, self.iterations)?;
+        let mut in_out = hex::decode(&self.ciphertext)?;
+        let plaintext = key
+            .open_in_place(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::empty(),
+                &mut in_out,
+            )
+            .map_err(|_| {
+                CliError::CommandArgumentError(
+                    "Wrong password, or the encrypted profile is corrupted".to_string(),
+                )
+            })?;
+        serde_json::from_slice(plaintext).map_err(|err| {
+            CliError::UnexpectedError(format!("Failed to deserialize profile {}", err))
+        })
+    }
+}
+
+fn profile_encryption_key(
+    password: &str,
+    salt: &[u8],
+    iterations: u32,
+) -> CliTypedResult<aead::LessSafeKey> {
+    let iterations = NonZeroU32::new(iterations).ok_or_else(|| {
+        CliError::CommandArgumentError("Key derivation iterations must be positive".to_string())
+    })?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        password.as_bytes(),
+        &mut key,
+    );
+    let key = aead::UnboundKey::new(&aead::AES_256_GCM, &key)
+        .map_err(|_| CliError::UnexpectedError("Invalid profile encryption key".to_string()))?;
+    Ok(aead::LessSafeKey::new(key))
+}
+
 impl Default for CliConfig {
     fn default() -> Self {
         CliConfig {
@@ -321,6 +450,8 @@ pub enum KeyType {
     Ed25519,
     /// X25519 key used for network handshakes and identity
     X25519,
+    /// BLS12381 key used for signing consensus messages
+    Bls12381,
 }
 
 impl Display for KeyType {
@@ -328,6 +459,7 @@ impl Display for KeyType {
         let str = match self {
             KeyType::Ed25519 => "ed25519",
             KeyType::X25519 => "x25519",
+            KeyType::Bls12381 => "bls12381",
         };
         write!(f, "{}", str)
     }
@@ -340,7 +472,8 @@ impl FromStr for KeyType {
         match s.to_lowercase().as_str() {
             "ed25519" => Ok(KeyType::Ed25519),
             "x25519" => Ok(KeyType::X25519),
-            _ => Err("Invalid key type: Must be one of [ed25519, x25519]"),
+            "bls12381" => Ok(KeyType::Bls12381),
+            _ => Err("Invalid key type: Must be one of [ed25519, x25519, bls12381]"),
         }
     }
 }
@@ -367,6 +500,23 @@ impl ProfileOptions {
 
         Err(CliError::ConfigNotFoundError(self.profile.clone()))
     }
+
+    /// Retrieves the chain ID of the profile, if it's known
+    pub fn chain_id(&self) -> CliTypedResult<Option<ChainId>> {
+        Ok(
+            CliConfig::load_profile(&self.profile, ConfigSearchMode::CurrentDirAndParents)?
+                .and_then(|profile| profile.chain_id),
+        )
+    }
+
+    /// Whether the profile can't sign transactions, see `aptos init --public-key-only`
+    pub fn is_view_only(&self) -> CliTypedResult<bool> {
+        Ok(
+            CliConfig::load_profile(&self.profile, ConfigSearchMode::CurrentDirAndParents)?
+                .and_then(|profile| profile.is_view_only)
+                .unwrap_or(false),
+        )
+    }
 }
 
 impl Default for ProfileOptions {
@@ -500,7 +650,7 @@ impl FromStr for EncodingType {
 }
 
 /// An insertable option for use with prompts.
-#[derive(Clone, Copy, Debug, Parser)]
+#[derive(Clone, Copy, Debug, Default, Parser)]
 pub struct PromptOptions {
     /// Assume yes for all yes/no prompts
     #[clap(long, group = "prompt_options")]
@@ -517,6 +667,13 @@ impl PromptOptions {
             assume_no: false,
         }
     }
+
+    pub fn no() -> Self {
+        Self {
+            assume_yes: false,
+            assume_no: true,
+        }
+    }
 }
 
 /// An insertable option for use with encodings.
@@ -527,7 +684,7 @@ pub struct EncodingOptions {
     pub encoding: EncodingType,
 }
 
-#[derive(Debug, Parser)]
+#[derive(Debug, Default, Parser)]
 pub struct PublicKeyInputOptions {
     /// Public key input file name
     #[clap(long, group = "public_key_input", parse(from_os_str))]
@@ -537,25 +694,39 @@ pub struct PublicKeyInputOptions {
     public_key: Option<String>,
 }
 
-impl ExtractPublicKey for PublicKeyInputOptions {
-    fn extract_public_key(
+impl PublicKeyInputOptions {
+    /// Extract public key from CLI args
+    pub fn extract_public_key_cli(
         &self,
         encoding: EncodingType,
-        _profile: &str,
-    ) -> CliTypedResult<Ed25519PublicKey> {
+    ) -> CliTypedResult<Option<Ed25519PublicKey>> {
         if let Some(ref file) = self.public_key_file {
-            encoding.load_key("--public-key-file", file.as_path())
+            Ok(Some(
+                encoding.load_key("--public-key-file", file.as_path())?,
+            ))
         } else if let Some(ref key) = self.public_key {
             let key = key.as_bytes().to_vec();
-            encoding.decode_key("--public-key", key)
+            Ok(Some(encoding.decode_key("--public-key", key)?))
         } else {
-            Err(CliError::CommandArgumentError(
-                "One of ['--public-key', '--public-key-file'] must be used".to_string(),
-            ))
+            Ok(None)
         }
     }
 }
 
+impl ExtractPublicKey for PublicKeyInputOptions {
+    fn extract_public_key(
+        &self,
+        encoding: EncodingType,
+        _profile: &str,
+    ) -> CliTypedResult<Ed25519PublicKey> {
+        self.extract_public_key_cli(encoding)?.ok_or_else(|| {
+            CliError::CommandArgumentError(
+                "One of ['--public-key', '--public-key-file'] must be used".to_string(),
+            )
+        })
+    }
+}
+
 #[derive(Debug, Default, Parser)]
 pub struct PrivateKeyInputOptions {
     /// Private key input file name
@@ -621,6 +792,14 @@ impl PrivateKeyInputOptions {
         &self,
         encoding: EncodingType,
     ) -> CliTypedResult<Option<Ed25519PrivateKey>> {
+        self.extract_typed_private_key_cli(encoding)
+    }
+
+    /// Extract a private key of any type from CLI args
+    pub fn extract_typed_private_key_cli<Key: ValidCryptoMaterial>(
+        &self,
+        encoding: EncodingType,
+    ) -> CliTypedResult<Option<Key>> {
         if let Some(ref file) = self.private_key_file {
             Ok(Some(
                 encoding.load_key("--private-key-file", file.as_path())?,
@@ -745,9 +924,10 @@ pub struct MovePackageDir {
     /// Path to a move package (the folder with a Move.toml file)
     #[clap(long, parse(from_os_str))]
     pub package_dir: Option<PathBuf>,
-    /// Path to save the compiled move package
+    /// Path to save the output of the command
     ///
-    /// Defaults to `<package_dir>/build`
+    /// Defaults to `<package_dir>/build` for the compiled move package, to `<package_dir>/doc`
+    /// for generated documentation, and to `<package_dir>/abi` for extracted ABIs
     #[clap(long, parse(from_os_str))]
     pub output_dir: Option<PathBuf>,
     /// Named addresses for the move binary
@@ -1081,17 +1261,33 @@ pub struct TransactionOptions {
 }
 
 impl TransactionOptions {
-    /// Retrieves the private key
+    /// Retrieves the private key, a view-only profile only signs with one from the command line
     fn private_key(&self) -> CliTypedResult<Ed25519PrivateKey> {
+        if self
+            .private_key_options
+            .extract_private_key_cli(self.encoding_options.encoding)?
+            .is_none()
+            && self.profile_options.is_view_only()?
+        {
+            return Err(CliError::CommandArgumentError(format!(
+                "Profile {} is view-only, it has no private key to sign transactions with. Use one of ['--private-key', '--private-key-file']",
+                self.profile_options.profile
+            )));
+        }
         self.private_key_options.extract_private_key(
             self.encoding_options.encoding,
             &self.profile_options.profile,
         )
     }
 
-    /// Builds a rest client
-    fn rest_client(&self) -> CliTypedResult<Client> {
-        self.rest_options.client(&self.profile_options.profile)
+    /// Builds a rest client, which fails before anything is signed if the node isn't on the
+    /// profile's chain
+    pub(crate) fn rest_client(&self) -> CliTypedResult<Client> {
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        Ok(match self.profile_options.chain_id()? {
+            Some(chain_id) => client.with_expected_chain_id(chain_id),
+            None => client,
+        })
     }
 
     pub fn sender_address(&self) -> CliTypedResult<AccountAddress> {
@@ -1108,13 +1304,43 @@ impl TransactionOptions {
         type_args: Vec<TypeTag>,
         args: Vec<Vec<u8>>,
     ) -> CliTypedResult<Transaction> {
-        let txn = TransactionPayload::EntryFunction(EntryFunction::new(
-            ModuleId::new(address, ident_str!(module).to_owned()),
-            ident_str!(function).to_owned(),
-            type_args,
-            args,
-        ));
-        self.submit_transaction(txn).await
+        self.submit_transaction(entry_function_payload(
+            address, module, function, type_args, args,
+        ))
+        .await
+    }
+
+    /// Asks `prompt` before submitting `payload`, showing what the transaction is expected to
+    /// cost first.  The fee is only estimated when the prompt is shown, so `--assume-yes` and
+    /// `--assume-no` don't simulate the transaction.
+    pub async fn confirm_transaction(
+        &self,
+        payload: &TransactionPayload,
+        prompt: &str,
+        prompt_options: PromptOptions,
+    ) -> CliTypedResult<()> {
+        if !prompt_options.assume_yes && !prompt_options.assume_no {
+            match self.simulate_transaction(payload.clone()).await {
+                Ok(simulated) => {
+                    let gas_unit_price = simulated.request.gas_unit_price.0;
+                    let gas_used = simulated.info.gas_used.0;
+                    eprintln!(
+                        "Estimated fee: {} gas units at {} Octas per unit, {} Octas in total",
+                        gas_used,
+                        gas_unit_price,
+                        gas_used.saturating_mul(gas_unit_price)
+                    );
+                    if !simulated.info.success {
+                        eprintln!(
+                            "Warning: the transaction is expected to fail: {}",
+                            simulated.info.vm_status
+                        );
+                    }
+                }
+                Err(err) => eprintln!("Unable to estimate fee: {}", err),
+            }
+        }
+        prompt_yes_with_override(prompt, prompt_options)
     }
 
     /// Submit a transaction
@@ -1131,10 +1357,11 @@ impl TransactionOptions {
         // Get sequence number for account
         let sequence_number = get_sequence_number(&client, sender_address).await?;
 
-        // Sign and submit transaction
         let transaction_factory = TransactionFactory::new(chain_id(&client).await?)
             .with_gas_unit_price(self.gas_options.gas_unit_price)
             .with_max_gas_amount(self.gas_options.max_gas);
+
+        // Sign and submit transaction
         let sender_account = &mut LocalAccount::new(sender_address, sender_key, sequence_number);
         let transaction =
             sender_account.sign_with_transaction_builder(transaction_factory.payload(payload));
@@ -1145,6 +1372,51 @@ impl TransactionOptions {
 
         Ok(response.into_inner())
     }
+
+    /// Simulates a transaction without submitting it, returning the outcome it would have
+    pub async fn simulate_transaction(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<UserTransaction> {
+        let sender_key = self.private_key()?;
+        let client = self.rest_client()?;
+        let sender_address = self.sender_address()?;
+        let sequence_number = get_sequence_number(&client, sender_address).await?;
+
+        let transaction_factory = TransactionFactory::new(chain_id(&client).await?)
+            .with_gas_unit_price(self.gas_options.gas_unit_price)
+            .with_max_gas_amount(self.gas_options.max_gas);
+        let raw_transaction = transaction_factory
+            .payload(payload)
+            .sender(sender_address)
+            .sequence_number(sequence_number)
+            .build();
+        let transaction = simulation_transaction(raw_transaction, sender_key.public_key());
+
+        client
+            .simulate(&transaction)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_inner()
+            .pop()
+            .ok_or_else(|| CliError::ApiError("Simulation returned no transaction".to_string()))
+    }
+}
+
+/// The payload calling the entry function `address::module::function`
+pub fn entry_function_payload(
+    address: AccountAddress,
+    module: &'static str,
+    function: &'static str,
+    type_args: Vec<TypeTag>,
+    args: Vec<Vec<u8>>,
+) -> TransactionPayload {
+    TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(address, ident_str!(module).to_owned()),
+        ident_str!(function).to_owned(),
+        type_args,
+        args,
+    ))
 }
 
 #[derive(Parser)]
@@ -1160,3 +1432,37 @@ pub struct PoolAddressArgs {
     #[clap(long, parse(try_from_str=crate::common::types::load_account_arg))]
     pub(crate) pool_address: AccountAddress,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_profile_round_trip() {
+        let private_key = KeyGen::from_seed([5; 32]).generate_ed25519_private_key();
+        let public_key = private_key.public_key();
+        let account = account_address_from_public_key(&public_key);
+        let profile = ProfileConfig {
+            private_key: Some(private_key),
+            public_key: Some(public_key),
+            account: Some(account),
+            rest_url: Some(DEFAULT_REST_URL.to_string()),
+            ..Default::default()
+        };
+
+        let encrypted = serde_json::to_string(&profile.encrypt("hunter2").unwrap()).unwrap();
+        let encrypted: EncryptedProfile = serde_json::from_str(&encrypted).unwrap();
+        let decrypted = encrypted.decrypt("hunter2").unwrap();
+        assert_eq!(
+            account_address_from_public_key(&decrypted.private_key.unwrap().public_key()),
+            account
+        );
+        assert_eq!(decrypted.account, Some(account));
+        assert_eq!(decrypted.rest_url, profile.rest_url);
+
+        assert!(matches!(
+            encrypted.decrypt("hunter3"),
+            Err(CliError::CommandArgumentError(_))
+        ));
+    }
+}
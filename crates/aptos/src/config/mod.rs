@@ -2,11 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::common::types::{
-    CliCommand, CliConfig, CliError, CliResult, CliTypedResult, ConfigSearchMode, ProfileSummary,
-    CONFIG_FOLDER,
+    CliCommand, CliConfig, CliError, CliResult, CliTypedResult, ConfigSearchMode, EncryptedProfile,
+    ProfileOptions, ProfileSummary, PromptOptions, CONFIG_FOLDER,
 };
 use crate::common::utils::{
-    create_dir_if_not_exist, current_dir, read_from_file, write_to_user_only_file,
+    check_if_file_exists, create_dir_if_not_exist, current_dir, prompt_yes_with_override,
+    read_from_file, write_to_user_only_file,
 };
 use crate::genesis::git::{from_yaml, to_yaml};
 use crate::Tool;
@@ -29,20 +30,25 @@ use std::str::FromStr;
 #[derive(Parser)]
 pub enum ConfigTool {
     Init(crate::common::init::InitTool),
+    #[clap(alias = "generate-shell-completion")]
     GenerateShellCompletions(GenerateShellCompletions),
     SetGlobalConfig(SetGlobalConfig),
     ShowGlobalConfig(ShowGlobalConfig),
     ShowProfiles(ShowProfiles),
+    ExportProfile(ExportProfile),
+    ImportProfile(ImportProfile),
 }
 
 impl ConfigTool {
     pub async fn execute(self) -> CliResult {
         match self {
             ConfigTool::Init(tool) => tool.execute_serialized_success().await,
-            ConfigTool::GenerateShellCompletions(tool) => tool.execute_serialized_success().await,
+            ConfigTool::GenerateShellCompletions(tool) => tool.execute_tool().await,
             ConfigTool::SetGlobalConfig(tool) => tool.execute_serialized().await,
             ConfigTool::ShowGlobalConfig(tool) => tool.execute_serialized().await,
             ConfigTool::ShowProfiles(tool) => tool.execute_serialized().await,
+            ConfigTool::ExportProfile(tool) => tool.execute_serialized().await,
+            ConfigTool::ImportProfile(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -50,16 +56,90 @@ impl ConfigTool {
 /// Generates shell completion files
 ///
 /// First generate the completion file, then follow the shell specific directions on how
-/// to install the completion file.
+/// to install the completion file.  The shell defaults to the one in `$SHELL`, and the
+/// completion file defaults to a location the shell loads completions from, e.g.
+/// `/etc/bash_completion.d/aptos` for bash.
 #[derive(Parser)]
 pub struct GenerateShellCompletions {
-    /// Shell to generate completions for one of [bash, elvish, powershell, zsh]
+    /// Shell to generate completions for one of [bash, elvish, fish, powershell, zsh]
+    ///
+    /// Defaults to the shell in `$SHELL`
     #[clap(long)]
-    shell: Shell,
+    shell: Option<Shell>,
 
     /// File to output shell completions to
-    #[clap(long, parse(from_os_str))]
-    output_file: PathBuf,
+    ///
+    /// Defaults to `/etc/bash_completion.d/aptos` for bash,
+    /// `/usr/local/share/zsh/site-functions/_aptos` for zsh, and
+    /// `~/.config/fish/completions/aptos.fish` for fish.  Required for other shells.
+    #[clap(long, parse(from_os_str), conflicts_with = "stdout")]
+    output_file: Option<PathBuf>,
+
+    /// Write the completions to stdout instead of a file
+    #[clap(long)]
+    stdout: bool,
+}
+
+impl GenerateShellCompletions {
+    /// Runs the command, as `aptos config` or `aptos util` subcommand
+    pub async fn execute_tool(self) -> CliResult {
+        if self.stdout {
+            // Leave out the JSON result, so the script can be piped directly into a file
+            self.execute()
+                .await
+                .map(|_| String::new())
+                .map_err(|err| err.to_string())
+        } else {
+            self.execute_serialized_success().await
+        }
+    }
+
+    fn shell(&self) -> CliTypedResult<Shell> {
+        if let Some(shell) = self.shell {
+            return Ok(shell);
+        }
+
+        let shell_path = std::env::var("SHELL").map_err(|_| {
+            CliError::CommandArgumentError(
+                "Unable to detect shell from $SHELL, please provide --shell".to_string(),
+            )
+        })?;
+        let shell_name = PathBuf::from(&shell_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match shell_name.as_str() {
+            "bash" => Ok(Shell::Bash),
+            "elvish" => Ok(Shell::Elvish),
+            "fish" => Ok(Shell::Fish),
+            "pwsh" | "powershell" => Ok(Shell::PowerShell),
+            "zsh" => Ok(Shell::Zsh),
+            _ => Err(CliError::CommandArgumentError(format!(
+                "Unsupported shell {}, please provide --shell",
+                shell_path
+            ))),
+        }
+    }
+
+    fn output_file(&self, shell: Shell) -> CliTypedResult<PathBuf> {
+        if let Some(ref output_file) = self.output_file {
+            return Ok(output_file.clone());
+        }
+
+        match shell {
+            Shell::Bash => Ok(PathBuf::from("/etc/bash_completion.d/aptos")),
+            Shell::Zsh => Ok(PathBuf::from("/usr/local/share/zsh/site-functions/_aptos")),
+            Shell::Fish => dirs::home_dir()
+                .map(|dir| dir.join(".config/fish/completions/aptos.fish"))
+                .ok_or_else(|| {
+                    CliError::UnexpectedError("Unable to retrieve home directory".to_string())
+                }),
+            _ => Err(CliError::CommandArgumentError(format!(
+                "No default completion file for {}, please provide --output-file",
+                shell
+            ))),
+        }
+    }
 }
 
 #[async_trait]
@@ -69,10 +149,25 @@ impl CliCommand<()> for GenerateShellCompletions {
     }
 
     async fn execute(self) -> CliTypedResult<()> {
+        let shell = self.shell()?;
         let mut command = Tool::command();
-        let mut file = std::fs::File::create(self.output_file.as_path())
-            .map_err(|err| CliError::IO(self.output_file.display().to_string(), err))?;
-        generate(self.shell, &mut command, "aptos".to_string(), &mut file);
+        if self.stdout {
+            generate(
+                shell,
+                &mut command,
+                "aptos".to_string(),
+                &mut std::io::stdout(),
+            );
+            return Ok(());
+        }
+
+        let output_file = self.output_file(shell)?;
+        if let Some(parent) = output_file.parent() {
+            create_dir_if_not_exist(parent)?;
+        }
+        let mut file = std::fs::File::create(output_file.as_path())
+            .map_err(|err| CliError::IO(output_file.display().to_string(), err))?;
+        generate(shell, &mut command, "aptos".to_string(), &mut file);
         Ok(())
     }
 }
@@ -146,6 +241,157 @@ impl CliCommand<BTreeMap<String, ProfileSummary>> for ShowProfiles {
     }
 }
 
+/// Exports a profile encrypted with a password
+///
+/// The profile, including its private key, can then be moved to another machine and imported
+/// there with `aptos config import-profile`.  Exporting it again with a new password
+/// re-encrypts it.
+#[derive(Parser, Debug)]
+pub struct ExportProfile {
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+    /// File to write the encrypted profile to
+    #[clap(long, parse(from_os_str))]
+    output_file: PathBuf,
+    #[clap(flatten)]
+    password_options: PasswordOptions,
+    #[clap(flatten)]
+    prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<ProfileSummary> for ExportProfile {
+    fn command_name(&self) -> &'static str {
+        "ExportProfile"
+    }
+
+    async fn execute(self) -> CliTypedResult<ProfileSummary> {
+        let profile = &self.profile_options.profile;
+        let profile_config =
+            CliConfig::load_profile(profile, ConfigSearchMode::CurrentDirAndParents)?
+                .ok_or_else(|| CliError::ConfigNotFoundError(profile.clone()))?;
+        check_if_file_exists(self.output_file.as_path(), self.prompt_options)?;
+
+        let password = self.password_options.password(self.prompt_options, true)?;
+        let encrypted =
+            serde_json::to_string_pretty(&profile_config.encrypt(&password)?).map_err(|err| {
+                CliError::UnexpectedError(format!("Failed to serialize encrypted profile {}", err))
+            })?;
+        write_to_user_only_file(
+            self.output_file.as_path(),
+            "Encrypted profile",
+            encrypted.as_bytes(),
+        )?;
+        Ok(ProfileSummary::from(&profile_config))
+    }
+}
+
+/// Imports a profile exported with `aptos config export-profile`
+#[derive(Parser, Debug)]
+pub struct ImportProfile {
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+    /// File to read the encrypted profile from
+    #[clap(long, parse(from_os_str))]
+    input_file: PathBuf,
+    #[clap(flatten)]
+    password_options: PasswordOptions,
+    #[clap(flatten)]
+    prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<ProfileSummary> for ImportProfile {
+    fn command_name(&self) -> &'static str {
+        "ImportProfile"
+    }
+
+    async fn execute(self) -> CliTypedResult<ProfileSummary> {
+        let encrypted: EncryptedProfile =
+            serde_json::from_slice(&read_from_file(self.input_file.as_path())?)
+                .map_err(|err| CliError::UnableToParse("encrypted profile", err.to_string()))?;
+        let password = self.password_options.password(self.prompt_options, false)?;
+        let profile_config = encrypted.decrypt(&password)?;
+
+        let mut config = if CliConfig::config_exists(ConfigSearchMode::CurrentDir) {
+            CliConfig::load(ConfigSearchMode::CurrentDir)?
+        } else {
+            CliConfig::default()
+        };
+        let profile = self.profile_options.profile;
+        let profiles = config.profiles.get_or_insert_with(BTreeMap::new);
+        if profiles.contains_key(&profile) {
+            prompt_yes_with_override(
+                &format!(
+                    "Profile {} already exists, are you sure you want to overwrite it?",
+                    profile
+                ),
+                self.prompt_options,
+            )?;
+        }
+        let summary = ProfileSummary::from(&profile_config);
+        profiles.insert(profile, profile_config);
+        config.save()?;
+        Ok(summary)
+    }
+}
+
+/// Options for the password of an encrypted profile
+#[derive(Parser, Debug)]
+struct PasswordOptions {
+    /// File containing the password of the encrypted profile
+    ///
+    /// If not given, the password is prompted for, which isn't possible with
+    /// `--assume-yes` or `--assume-no`
+    #[clap(long, parse(from_os_str))]
+    password_file: Option<PathBuf>,
+}
+
+impl PasswordOptions {
+    /// Reads the password from the password file, or prompts for it without echoing it, twice
+    /// if `confirm`
+    fn password(&self, prompt_options: PromptOptions, confirm: bool) -> CliTypedResult<String> {
+        self.password_with_prompt(prompt_options, confirm, |prompt| {
+            rpassword::prompt_password_stderr(prompt)
+                .map_err(|err| CliError::IO("Password".to_string(), err))
+        })
+    }
+
+    fn password_with_prompt(
+        &self,
+        prompt_options: PromptOptions,
+        confirm: bool,
+        mut prompt_password: impl FnMut(&str) -> CliTypedResult<String>,
+    ) -> CliTypedResult<String> {
+        let password = if let Some(ref password_file) = self.password_file {
+            let password = String::from_utf8(read_from_file(password_file.as_path())?)?;
+            // Only the line ending, any other whitespace is part of the password
+            let password = password.strip_suffix('\n').unwrap_or(&password);
+            password.strip_suffix('\r').unwrap_or(password).to_string()
+        } else if prompt_options.assume_yes || prompt_options.assume_no {
+            return Err(CliError::CommandArgumentError(
+                "--password-file must be given when prompts are disabled".to_string(),
+            ));
+        } else {
+            let password = prompt_password("Enter the password of the encrypted profile: ")?;
+            if confirm && prompt_password("Enter the password again: ")? != password {
+                return Err(CliError::CommandArgumentError(
+                    "Passwords don't match".to_string(),
+                ));
+            }
+            password
+        };
+
+        if password.is_empty() {
+            Err(CliError::CommandArgumentError(
+                "The password must not be empty".to_string(),
+            ))
+        } else {
+            Ok(password)
+        }
+    }
+}
+
 /// Shows the properties in the global config
 #[derive(Parser, Debug)]
 pub struct ShowGlobalConfig {}
@@ -289,3 +535,107 @@ impl FromStr for ConfigType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::UtilTool;
+    use aptos_temppath::TempPath;
+
+    #[test]
+    fn test_generate_shell_completions_subcommands() {
+        for command in ["util", "config"] {
+            for name in ["generate-shell-completions", "generate-shell-completion"] {
+                let tool = Tool::try_parse_from(["aptos", command, name, "--shell", "bash"])
+                    .unwrap_or_else(|err| panic!("aptos {} {}: {}", command, name, err));
+                assert!(matches!(
+                    tool,
+                    Tool::Util(UtilTool::GenerateShellCompletions(_))
+                        | Tool::Config(ConfigTool::GenerateShellCompletions(_))
+                ));
+            }
+        }
+    }
+
+    fn password_file_options(contents: &str) -> (TempPath, PasswordOptions) {
+        let password_file = TempPath::new();
+        password_file.create_as_file().unwrap();
+        std::fs::write(password_file.path(), contents).unwrap();
+        let options = PasswordOptions {
+            password_file: Some(password_file.path().to_path_buf()),
+        };
+        (password_file, options)
+    }
+
+    fn no_prompt(_: &str) -> CliTypedResult<String> {
+        panic!("The password file should be used instead of prompting")
+    }
+
+    #[test]
+    fn test_password_file() {
+        for (contents, expected) in [
+            ("secret", "secret"),
+            ("secret\n", "secret"),
+            ("secret\r\n", "secret"),
+            // Whitespace other than the line ending is part of the password
+            (" secret \n", " secret "),
+            ("secret\t\n", "secret\t"),
+        ] {
+            let (_password_file, options) = password_file_options(contents);
+            let password = options
+                .password_with_prompt(PromptOptions::yes(), true, no_prompt)
+                .unwrap();
+            assert_eq!(password, expected);
+        }
+
+        let (_password_file, options) = password_file_options("\n");
+        assert!(options
+            .password_with_prompt(PromptOptions::yes(), false, no_prompt)
+            .is_err());
+    }
+
+    /// Answers the prompts with `answers`, in order
+    fn prompt_with(answers: Vec<&'static str>) -> impl FnMut(&str) -> CliTypedResult<String> {
+        let mut answers = answers.into_iter();
+        move |_| Ok(answers.next().expect("Prompted too many times").to_string())
+    }
+
+    #[test]
+    fn test_password_prompt() {
+        let options = PasswordOptions {
+            password_file: None,
+        };
+        // The prompted password is kept as is, and confirmed if asked to
+        assert_eq!(
+            options
+                .password_with_prompt(PromptOptions::default(), false, prompt_with(vec![" pw "]))
+                .unwrap(),
+            " pw "
+        );
+        assert_eq!(
+            options
+                .password_with_prompt(
+                    PromptOptions::default(),
+                    true,
+                    prompt_with(vec!["pw", "pw"])
+                )
+                .unwrap(),
+            "pw"
+        );
+        assert!(options
+            .password_with_prompt(
+                PromptOptions::default(),
+                true,
+                prompt_with(vec!["pw", "other"])
+            )
+            .is_err());
+        assert!(options
+            .password_with_prompt(PromptOptions::default(), false, prompt_with(vec![""]))
+            .is_err());
+
+        // There's nothing to prompt with when prompts are disabled
+        assert!(options
+            .password_with_prompt(PromptOptions::yes(), false, prompt_with(vec![]))
+            .is_err());
+    }
+}
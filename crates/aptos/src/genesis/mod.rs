@@ -13,7 +13,7 @@ use crate::{
         types::{CliError, CliTypedResult, PromptOptions},
         utils::{check_if_file_exists, write_to_file},
     },
-    genesis::git::{Client, GitOptions, LAYOUT_FILE},
+    genesis::git::{to_yaml, Client, GitOptions, LAYOUT_FILE},
     CliCommand, CliResult,
 };
 use aptos_crypto::{bls12381, ed25519::Ed25519PublicKey, x25519, ValidCryptoMaterialStringExt};
@@ -23,14 +23,16 @@ use aptos_genesis::{
     config::{Layout, ValidatorConfiguration},
     GenesisInfo,
 };
-use aptos_types::account_address::AccountAddress;
+use aptos_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
 use async_trait::async_trait;
 use clap::Parser;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::{path::PathBuf, str::FromStr};
 
 const WAYPOINT_FILE: &str = "waypoint.txt";
 const GENESIS_FILE: &str = "genesis.blob";
+const GENESIS_SUMMARY_FILE: &str = "genesis_summary.yaml";
 
 /// Tool for setting up an Aptos chain Genesis transaction
 ///
@@ -43,6 +45,7 @@ pub enum GenesisTool {
     GenerateLayoutTemplate(keys::GenerateLayoutTemplate),
     SetupGit(git::SetupGit),
     SetValidatorConfiguration(keys::SetValidatorConfiguration),
+    VerifyLayoutFile(VerifyLayoutFile),
 }
 
 impl GenesisTool {
@@ -53,6 +56,7 @@ impl GenesisTool {
             GenesisTool::GenerateLayoutTemplate(tool) => tool.execute_serialized_success().await,
             GenesisTool::SetupGit(tool) => tool.execute_serialized_success().await,
             GenesisTool::SetValidatorConfiguration(tool) => tool.execute_serialized_success().await,
+            GenesisTool::VerifyLayoutFile(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -80,8 +84,10 @@ impl CliCommand<Vec<PathBuf>> for GenerateGenesis {
         let output_dir = dir_default_to_current(self.output_dir.clone())?;
         let genesis_file = output_dir.join(GENESIS_FILE);
         let waypoint_file = output_dir.join(WAYPOINT_FILE);
+        let summary_file = output_dir.join(GENESIS_SUMMARY_FILE);
         check_if_file_exists(genesis_file.as_path(), self.prompt_options)?;
         check_if_file_exists(waypoint_file.as_path(), self.prompt_options)?;
+        check_if_file_exists(summary_file.as_path(), self.prompt_options)?;
 
         // Generate genesis file
         let mut genesis_info = fetch_genesis_info(self.git_options)?;
@@ -92,6 +98,14 @@ impl CliCommand<Vec<PathBuf>> for GenerateGenesis {
             &bcs::to_bytes(genesis).map_err(|e| CliError::BCS(GENESIS_FILE, e))?,
         )?;
 
+        // Generate a human readable summary of what genesis sets up
+        let summary = genesis_info.get_genesis_summary();
+        write_to_file(
+            summary_file.as_path(),
+            GENESIS_SUMMARY_FILE,
+            to_yaml(summary)?.as_bytes(),
+        )?;
+
         // Generate waypoint file
         let waypoint = genesis_info.generate_waypoint()?;
         write_to_file(
@@ -99,7 +113,7 @@ impl CliCommand<Vec<PathBuf>> for GenerateGenesis {
             WAYPOINT_FILE,
             waypoint.to_string().as_bytes(),
         )?;
-        Ok(vec![genesis_file, waypoint_file])
+        Ok(vec![genesis_file, waypoint_file, summary_file])
     }
 }
 
@@ -116,22 +130,7 @@ pub fn fetch_genesis_info(git_options: GitOptions) -> CliTypedResult<GenesisInfo
         ));
     }
 
-    let mut validators = Vec::new();
-    let mut errors = Vec::new();
-    for user in &layout.users {
-        match get_config(&client, user) {
-            Ok(validator) => {
-                validators.push(validator);
-            }
-            Err(failure) => {
-                if let CliError::UnexpectedError(failure) = failure {
-                    errors.push(format!("{}: {}", user, failure));
-                } else {
-                    errors.push(format!("{}: {:?}", user, failure));
-                }
-            }
-        }
-    }
+    let (validators, errors) = get_validator_configs(&client, &layout);
 
     // Collect errors, and print out failed inputs
     if !errors.is_empty() {
@@ -143,6 +142,10 @@ pub fn fetch_genesis_info(git_options: GitOptions) -> CliTypedResult<GenesisInfo
             "Failed to parse genesis inputs".to_string(),
         ));
     }
+    let validators = validators
+        .into_iter()
+        .map(|(_, validator)| validator)
+        .collect();
 
     let framework = client.get_framework()?;
 
@@ -163,10 +166,129 @@ pub fn fetch_genesis_info(git_options: GitOptions) -> CliTypedResult<GenesisInfo
             rewards_apy_percentage: layout.rewards_apy_percentage,
             voting_duration_secs: layout.voting_duration_secs,
             voting_power_increase_limit: layout.voting_power_increase_limit,
+            initial_accounts: layout.initial_accounts,
         },
     )?)
 }
 
+/// Verify the layout file and all validator configurations it references
+///
+/// This runs the same parsing as `generate-genesis`, and additionally checks
+/// that account addresses match the public keys they're derived from, and that
+/// no owner account is used by more than one validator.
+#[derive(Parser)]
+pub struct VerifyLayoutFile {
+    #[clap(flatten)]
+    git_options: GitOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for VerifyLayoutFile {
+    fn command_name(&self) -> &'static str {
+        "VerifyLayoutFile"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let client = self.git_options.get_client()?;
+        let layout: Layout = client.get(Path::new(LAYOUT_FILE))?;
+        let errors = verify_layout(&client, &layout);
+
+        if !errors.is_empty() {
+            return Err(CliError::UnexpectedError(format!(
+                "Layout file verification failed with {} error(s):\n{}",
+                errors.len(),
+                errors.join("\n")
+            )));
+        }
+
+        Ok(layout.users)
+    }
+}
+
+/// Checks the layout and every user's configuration, returning all problems found
+fn verify_layout(client: &Client, layout: &Layout) -> Vec<String> {
+    let mut errors = Vec::new();
+    if layout.root_key.is_none() {
+        errors.push("Layout field root_key was not set".to_string());
+    }
+    if layout.users.is_empty() {
+        errors.push("Layout field users is empty".to_string());
+    }
+    let mut seen_users = HashSet::new();
+    for user in &layout.users {
+        if !seen_users.insert(user) {
+            errors.push(format!("{}: user is listed more than once in layout", user));
+        }
+    }
+
+    let (validators, config_errors) = get_validator_configs(client, layout);
+    errors.extend(config_errors);
+
+    let mut owners: HashMap<AccountAddress, &str> = HashMap::new();
+    for (user, validator) in &validators {
+        let accounts = [
+            (
+                "owner",
+                validator.owner_account_address,
+                &validator.owner_account_public_key,
+            ),
+            (
+                "operator",
+                validator.operator_account_address,
+                &validator.operator_account_public_key,
+            ),
+            (
+                "voter",
+                validator.voter_account_address,
+                &validator.voter_account_public_key,
+            ),
+        ];
+        for (role, address, public_key) in accounts {
+            let derived_address = AuthenticationKey::ed25519(public_key).derived_address();
+            if address != derived_address {
+                errors.push(format!(
+                    "{}: {} account address {} does not match address {} derived from its public key",
+                    user, role, address, derived_address
+                ));
+            }
+        }
+
+        if let Some(other_user) = owners.insert(validator.owner_account_address, user) {
+            errors.push(format!(
+                "{}: owner account address {} is also used by {}",
+                user, validator.owner_account_address, other_user
+            ));
+        }
+    }
+
+    errors
+}
+
+/// Loads the configuration of every user in the layout, collecting failures
+/// instead of stopping at the first one
+fn get_validator_configs<'a>(
+    client: &Client,
+    layout: &'a Layout,
+) -> (Vec<(&'a str, ValidatorConfiguration)>, Vec<String>) {
+    let mut validators = Vec::new();
+    let mut errors = Vec::new();
+    for user in &layout.users {
+        match get_config(client, user) {
+            Ok(validator) => {
+                validators.push((user.as_str(), validator));
+            }
+            Err(failure) => {
+                if let CliError::UnexpectedError(failure) = failure {
+                    errors.push(format!("{}: {}", user, failure));
+                } else {
+                    errors.push(format!("{}: {:?}", user, failure));
+                }
+            }
+        }
+    }
+    (validators, errors)
+}
+
 /// Do proper parsing so more information is known about failures
 fn get_config(client: &Client, user: &str) -> CliTypedResult<ValidatorConfiguration> {
     // Load a user's configuration files
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::OptionalPoolAddressArgs;
+use crate::common::types::{CliTypedResult, OptionalPoolAddressArgs};
 use crate::common::utils::read_from_file;
 use crate::genesis::git::from_yaml;
 use crate::genesis::git::FRAMEWORK_NAME;
@@ -14,7 +14,7 @@ use crate::{
     genesis::{
         git::{GitOptions, SetupGit},
         keys::{GenerateKeys, SetValidatorConfiguration},
-        GenerateGenesis,
+        GenerateGenesis, VerifyLayoutFile,
     },
     CliCommand,
 };
@@ -31,6 +31,7 @@ use std::{
     path::{Path, PathBuf},
     str::FromStr,
 };
+use vm_genesis::GenesisSummary;
 
 /// Test the E2E genesis flow since it doesn't require a node to run
 #[tokio::test]
@@ -61,6 +62,9 @@ async fn test_genesis_e2e_flow() {
         add_public_keys(name.to_string(), git_options.clone(), user_dir.as_path()).await;
     }
 
+    // Check the layout before generating genesis
+    verify_layout_file(git_options.clone()).await.unwrap();
+
     // Now generate genesis
     let output_dir = TempPath::new();
     output_dir.create_as_dir().unwrap();
@@ -72,6 +76,40 @@ async fn test_genesis_e2e_flow() {
     assert!(waypoint_file.exists());
     let genesis_file = output_dir.join("genesis.blob");
     assert!(genesis_file.exists());
+
+    let summary_file = output_dir.join("genesis_summary.yaml");
+    let summary: GenesisSummary =
+        from_yaml(&String::from_utf8(read_from_file(&summary_file).unwrap()).unwrap()).unwrap();
+    assert_eq!(chain_id.id(), summary.chain_id);
+    assert_eq!(NUM_USERS as usize, summary.validators.len());
+    for validator in summary.validators {
+        assert_eq!(100_000_000_000_000, validator.stake_amount);
+    }
+    assert!(summary.total_supply_minted >= 2 * 100_000_000_000_000);
+}
+
+/// Two users sharing one set of keys can't both own a validator
+#[tokio::test]
+async fn test_verify_layout_file_duplicate_owner() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let keys_dir = generate_keys(dir.path(), 0).await;
+    let names = vec!["user-0".to_string(), "user-1".to_string()];
+
+    let mut keygen = KeyGen::from_seed([3; 32]);
+    let root_private_key = keygen.generate_ed25519_private_key();
+    let git_options = setup_git_dir(&root_private_key, names.clone(), ChainId::test()).await;
+    for name in names {
+        add_public_keys(name, git_options.clone(), keys_dir.as_path()).await;
+    }
+
+    let error = verify_layout_file(git_options).await.unwrap_err();
+    assert!(error.to_string().contains("is also used by"), "{}", error);
+}
+
+/// Verify the layout file and all user configurations
+async fn verify_layout_file(git_options: GitOptions) -> CliTypedResult<Vec<String>> {
+    VerifyLayoutFile { git_options }.execute().await
 }
 
 /// Generate genesis and waypoint
@@ -2,10 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::common::types::{
-    CliError, CliTypedResult, MovePackageDir, PoolAddressArgs, PromptOptions, TransactionOptions,
-    TransactionSummary,
+    entry_function_payload, CliError, CliTypedResult, MovePackageDir, PoolAddressArgs,
+    PromptOptions, TransactionOptions, TransactionSummary,
 };
-use crate::common::utils::prompt_yes_with_override;
 use crate::move_tool::{init_move_dir, ArgWithType, FunctionArgType, IncludedArtifacts};
 use crate::{CliCommand, CliResult};
 use aptos_crypto::HashValue;
@@ -91,27 +90,28 @@ impl CliCommand<ProposalSubmissionSummary> for SubmitProposal {
             "{}\n\tMetadata Hash: {}\n\tScript Hash: {}",
             metadata, metadata_hash, script_hash
         );
-        prompt_yes_with_override(
-            "Do you want to submit this proposal?",
-            self.compile_proposal_args.prompt_options,
-        )?;
-
-        let txn = self
-            .txn_options
-            .submit_entry_function(
-                AccountAddress::ONE,
-                "aptos_governance",
-                "create_proposal",
-                vec![],
-                vec![
-                    bcs::to_bytes(&self.pool_address_args.pool_address)?,
-                    bcs::to_bytes(&script_hash)?,
-                    bcs::to_bytes(&self.metadata_url.to_string())?,
-                    bcs::to_bytes(&metadata_hash.to_hex())?,
-                ],
+        let payload = entry_function_payload(
+            AccountAddress::ONE,
+            "aptos_governance",
+            "create_proposal",
+            vec![],
+            vec![
+                bcs::to_bytes(&self.pool_address_args.pool_address)?,
+                bcs::to_bytes(&script_hash)?,
+                bcs::to_bytes(&self.metadata_url.to_string())?,
+                bcs::to_bytes(&metadata_hash.to_hex())?,
+            ],
+        );
+        self.txn_options
+            .confirm_transaction(
+                &payload,
+                "Do you want to submit this proposal?",
+                self.compile_proposal_args.prompt_options,
             )
             .await?;
 
+        let txn = self.txn_options.submit_transaction(payload).await?;
+
         if let Transaction::UserTransaction(inner) = txn {
             // Find event with proposal id
             let proposal_id = if let Some(event) = inner.events.into_iter().find(|event| {
@@ -248,24 +248,26 @@ impl CliCommand<Transaction> for SubmitVote {
 
         // TODO: Display details of proposal
 
-        prompt_yes_with_override(
-            &format!("Are you sure you want to vote {}", vote_str),
-            self.prompt_options,
-        )?;
-
+        let payload = entry_function_payload(
+            AccountAddress::ONE,
+            "aptos_governance",
+            "vote",
+            vec![],
+            vec![
+                bcs::to_bytes(&self.pool_address_args.pool_address)?,
+                bcs::to_bytes(&self.proposal_id)?,
+                bcs::to_bytes(&vote)?,
+            ],
+        );
         self.txn_options
-            .submit_entry_function(
-                AccountAddress::ONE,
-                "aptos_governance",
-                "vote",
-                vec![],
-                vec![
-                    bcs::to_bytes(&self.pool_address_args.pool_address)?,
-                    bcs::to_bytes(&self.proposal_id)?,
-                    bcs::to_bytes(&vote)?,
-                ],
+            .confirm_transaction(
+                &payload,
+                &format!("Are you sure you want to vote {}", vote_str),
+                self.prompt_options,
             )
-            .await
+            .await?;
+
+        self.txn_options.submit_transaction(payload).await
     }
 }
 
@@ -14,6 +14,7 @@ pub mod op;
 pub mod stake;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod test;
+pub mod util;
 
 use crate::common::types::{CliCommand, CliResult, CliTypedResult};
 use crate::common::utils::cli_build_information;
@@ -43,6 +44,8 @@ pub enum Tool {
     Node(node::NodeTool),
     #[clap(subcommand)]
     Stake(stake::StakeTool),
+    #[clap(subcommand)]
+    Util(util::UtilTool),
 }
 
 impl Tool {
@@ -60,6 +63,7 @@ impl Tool {
             Move(tool) => tool.execute().await,
             Node(tool) => tool.execute().await,
             Stake(tool) => tool.execute().await,
+            Util(tool) => tool.execute().await,
         }
     }
 }
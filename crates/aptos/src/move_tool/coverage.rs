@@ -0,0 +1,193 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Line and branch coverage of Move unit tests.
+//!
+//! The unit test runner only records how often each bytecode instruction was executed. This
+//! maps the instructions back to their source lines with the modules' source maps. A branch
+//! counts as taken if the instruction it jumps to was executed.
+
+use crate::common::{
+    types::{CliError, CliTypedResult},
+    utils::write_to_file,
+};
+use move_deps::{
+    move_binary_format::file_format::{Bytecode, FunctionDefinitionIndex},
+    move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule},
+    move_coverage::coverage_map::{CoverageMap, ExecCoverageMap},
+    move_package::compilation::compiled_package::CompiledPackage,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// Where the unit test runner writes the coverage map, relative to the package
+const COVERAGE_MAP_FILE: &str = ".coverage_map.mvcov";
+
+/// Coverage of a module's source lines and branches
+pub struct ModuleCoverage {
+    pub name: String,
+    pub source_path: PathBuf,
+    /// Number of executed instructions by line
+    pub lines: BTreeMap<usize, u64>,
+    /// Branches by line, with how often each of their targets was executed, or `None` if the
+    /// branch itself never was
+    pub branches: Vec<(usize, [Option<u64>; 2])>,
+}
+
+impl ModuleCoverage {
+    pub fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|hits| **hits > 0).count()
+    }
+
+    pub fn branches_total(&self) -> usize {
+        self.branches.len() * 2
+    }
+
+    pub fn branches_hit(&self) -> usize {
+        self.branches
+            .iter()
+            .flat_map(|(_, targets)| targets.iter())
+            .filter(|hits| matches!(hits, Some(hits) if *hits > 0))
+            .count()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: lines {}/{} ({}), branches {}/{} ({})",
+            self.name,
+            self.lines_hit(),
+            self.lines.len(),
+            percentage(self.lines_hit(), self.lines.len()),
+            self.branches_hit(),
+            self.branches_total(),
+            percentage(self.branches_hit(), self.branches_total()),
+        )
+    }
+}
+
+fn percentage(hit: usize, total: usize) -> String {
+    if total == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.2}%", hit as f64 * 100.0 / total as f64)
+    }
+}
+
+/// Computes the coverage of the root modules of `package` from the coverage map the unit
+/// tests left in `package_path`
+pub fn compute_coverage(
+    package_path: &Path,
+    package: &CompiledPackage,
+) -> CliTypedResult<Vec<ModuleCoverage>> {
+    let coverage_map = CoverageMap::from_binary_file(package_path.join(COVERAGE_MAP_FILE))
+        .map_err(|err| CliError::UnexpectedError(format!("Unable to load coverage: {}", err)))?
+        .to_unified_exec_map();
+
+    let mut coverage = Vec::new();
+    for unit in package.root_modules() {
+        if let CompiledUnit::Module(module) = &unit.unit {
+            let source = std::fs::read_to_string(&unit.source_path)
+                .map_err(|err| CliError::IO(unit.source_path.display().to_string(), err))?;
+            coverage.push(module_coverage(
+                module,
+                unit.source_path.clone(),
+                &source,
+                &coverage_map,
+            ));
+        }
+    }
+    Ok(coverage)
+}
+
+fn module_coverage(
+    named_module: &NamedCompiledModule,
+    source_path: PathBuf,
+    source: &str,
+    coverage_map: &ExecCoverageMap,
+) -> ModuleCoverage {
+    let module = &named_module.module;
+    let module_id = module.self_id();
+    let function_maps = coverage_map
+        .module_maps
+        .get(&(*module_id.address(), module_id.name().to_owned()))
+        .map(|module_map| &module_map.function_maps);
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect();
+    let line_of = |offset: u32| line_starts.partition_point(|start| *start <= offset as usize);
+
+    let mut lines = BTreeMap::new();
+    let mut branches = Vec::new();
+    for (idx, function) in module.function_defs().iter().enumerate() {
+        let code = match &function.code {
+            Some(code) => &code.code,
+            None => continue,
+        };
+        let name = module.identifier_at(module.function_handle_at(function.function).name);
+        let function_map = function_maps.and_then(|function_maps| function_maps.get(name));
+        let hits = |offset: usize| {
+            function_map
+                .and_then(|function_map| function_map.get(&(offset as u64)))
+                .copied()
+                .unwrap_or(0)
+        };
+
+        for (offset, instruction) in code.iter().enumerate() {
+            let line = match named_module
+                .source_map
+                .get_code_location(FunctionDefinitionIndex(idx as u16), offset as u16)
+            {
+                Ok(loc) => line_of(loc.start()),
+                Err(_) => continue,
+            };
+            *lines.entry(line).or_insert(0) += hits(offset);
+
+            if let Bytecode::BrTrue(target) | Bytecode::BrFalse(target) = instruction {
+                let targets = if hits(offset) > 0 {
+                    [Some(hits(*target as usize)), Some(hits(offset + 1))]
+                } else {
+                    [None, None]
+                };
+                branches.push((line, targets));
+            }
+        }
+    }
+
+    ModuleCoverage {
+        name: format!(
+            "{}::{}",
+            module_id.address().to_hex_literal(),
+            module_id.name()
+        ),
+        source_path,
+        lines,
+        branches,
+    }
+}
+
+/// Writes the coverage in the LCOV tracefile format
+pub fn write_lcov(coverage: &[ModuleCoverage], path: &Path) -> CliTypedResult<()> {
+    let mut lcov = String::new();
+    for module in coverage {
+        writeln!(lcov, "SF:{}", module.source_path.display()).unwrap();
+        for (block, (line, targets)) in module.branches.iter().enumerate() {
+            for (branch, hits) in targets.iter().enumerate() {
+                let taken = hits.map_or_else(|| "-".to_string(), |hits| hits.to_string());
+                writeln!(lcov, "BRDA:{},{},{},{}", line, block, branch, taken).unwrap();
+            }
+        }
+        writeln!(lcov, "BRF:{}", module.branches_total()).unwrap();
+        writeln!(lcov, "BRH:{}", module.branches_hit()).unwrap();
+        for (line, hits) in &module.lines {
+            writeln!(lcov, "DA:{},{}", line, hits).unwrap();
+        }
+        writeln!(lcov, "LF:{}", module.lines.len()).unwrap();
+        writeln!(lcov, "LH:{}", module.lines_hit()).unwrap();
+        writeln!(lcov, "end_of_record").unwrap();
+    }
+    write_to_file(path, &path.display().to_string(), lcov.as_bytes())
+}
@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod aptos_debug_natives;
+mod coverage;
 mod manifest;
 pub mod package_hooks;
 pub use package_hooks::*;
 pub mod stored_package;
 mod transactional_tests_runner;
+mod unit_test;
 
 pub use stored_package::*;
 
@@ -30,14 +32,16 @@ use crate::{
 };
 use aptos_gas::NativeGasParameters;
 use aptos_module_verifier::module_init::verify_module_init_function;
-use aptos_rest_client::aptos_api_types::MoveType;
+use aptos_rest_client::aptos_api_types::{MoveModule, MoveType};
 use aptos_transactional_test_harness::run_aptos_test;
 use aptos_types::account_address::AccountAddress;
 use aptos_types::transaction::{EntryFunction, ModuleBundle, TransactionPayload};
 use async_trait::async_trait;
 use clap::{ArgEnum, Parser, Subcommand};
+use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
 use framework::natives::code::UpgradePolicy;
 use framework::{BuildOptions, BuiltPackage};
+use futures::{stream, StreamExt};
 use itertools::Itertools;
 use move_deps::move_cli::base::test::UnitTestResult;
 use move_deps::move_command_line_common::env::MOVE_HOME;
@@ -47,19 +51,25 @@ use move_deps::{
         identifier::Identifier,
         language_storage::{ModuleId, TypeTag},
     },
-    move_package::{source_package::layout::SourcePackageLayout, BuildConfig},
+    move_docgen::{Docgen, DocgenOptions},
+    move_package::{source_package::layout::SourcePackageLayout, BuildConfig, ModelConfig},
     move_prover,
     move_unit_test::UnitTestingConfig,
+    move_vm_runtime::native_functions::NativeFunctionTable,
 };
+use serde::Serialize;
 use std::fmt::{Display, Formatter};
 use std::{
     collections::BTreeMap,
     convert::TryFrom,
+    io::Write,
     path::{Path, PathBuf},
     str::FromStr,
+    sync::Arc,
 };
 use tokio::task;
 use transactional_tests_runner::TransactionalTestOpts;
+use unit_test::CompiledTests;
 
 /// Tool for Move related operations
 ///
@@ -77,6 +87,8 @@ pub enum MoveTool {
     Run(RunFunction),
     Test(TestPackage),
     Prove(ProvePackage),
+    Document(DocumentPackage),
+    ExtractAbi(ExtractAbi),
     TransactionalTest(TransactionalTestOpts),
 }
 
@@ -92,6 +104,8 @@ impl MoveTool {
             MoveTool::Run(tool) => tool.execute_serialized().await,
             MoveTool::Test(tool) => tool.execute_serialized().await,
             MoveTool::Prove(tool) => tool.execute_serialized().await,
+            MoveTool::Document(tool) => tool.execute_serialized().await,
+            MoveTool::ExtractAbi(tool) => tool.execute_serialized().await,
             MoveTool::TransactionalTest(tool) => tool.execute_serialized_success().await,
         }
     }
@@ -234,10 +248,166 @@ pub struct TestPackage {
     #[clap(long)]
     pub filter: Option<String>,
 
+    /// Collect coverage while running the tests, and print the line and branch coverage of
+    /// each module
+    #[clap(long)]
+    pub coverage: bool,
+
+    /// Path to write the coverage to as an LCOV file, requires `--coverage`
+    #[clap(long, parse(from_os_str), requires = "coverage")]
+    pub coverage_output: Option<PathBuf>,
+
+    /// Maximum number of instructions a single unit test can execute [default: 100000]
+    #[clap(long, conflicts_with = "unbounded")]
+    pub instruction_bound: Option<u64>,
+
+    /// Lets unit tests execute any number of instructions
+    #[clap(long)]
+    pub unbounded: bool,
+
+    /// Run the tests of each module of the package concurrently, printing the output of each
+    /// module once its tests are done
+    #[clap(long, conflicts_with_all = &["filter", "coverage"])]
+    pub parallel: bool,
+
+    /// Maximum number of modules tested at once with `--parallel` [default: number of CPUs]
+    #[clap(long, requires = "parallel")]
+    pub parallel_jobs: Option<usize>,
+
     #[clap(flatten)]
     pub(crate) move_options: MovePackageDir,
 }
 
+/// Default number of instructions a single unit test can execute
+pub const DEFAULT_INSTRUCTION_BOUND: u64 = 100_000;
+
+/// How much the instruction bound is raised when rerunning failed tests, to tell whether they
+/// only ran out of instructions
+const RAISED_INSTRUCTION_BOUND_FACTOR: u64 = 100;
+
+impl TestPackage {
+    fn instruction_bound(&self) -> Option<u64> {
+        if self.unbounded {
+            None
+        } else {
+            Some(self.instruction_bound.unwrap_or(DEFAULT_INSTRUCTION_BOUND))
+        }
+    }
+
+    fn unit_testing_config(&self, filter: Option<String>) -> UnitTestingConfig {
+        UnitTestingConfig {
+            filter,
+            ..UnitTestingConfig::default_with_bound(self.instruction_bound())
+        }
+    }
+
+    fn parallel_jobs(&self) -> usize {
+        self.parallel_jobs
+            .or_else(|| std::thread::available_parallelism().ok().map(usize::from))
+            .unwrap_or(1)
+            .max(1)
+    }
+
+    /// Runs the tests of each module in its own blocking task, at most `parallel_jobs` at a time, and prints their output module by module, followed by
+    /// a summary of all of them
+    async fn run_module_tests_in_parallel(
+        &self,
+        tests: Arc<CompiledTests>,
+        output: &mut TestOutput,
+    ) -> CliTypedResult<bool> {
+        let modules: Vec<ModuleId> = tests.modules().cloned().collect();
+
+        let mut module_results = stream::iter(modules)
+            .map(|module| {
+                let tests = tests.clone();
+                // The jobs are the parallelism, so each module's tests run on a single thread
+                let unit_testing_config = UnitTestingConfig {
+                    num_threads: 1,
+                    ..self.unit_testing_config(None)
+                };
+                task::spawn_blocking(move || {
+                    let result = tests.run(
+                        Some(&module),
+                        unit_testing_config,
+                        unit_test_natives(),
+                        TestOutput::buffered(),
+                    );
+                    (module, result)
+                })
+            })
+            .buffered(self.parallel_jobs());
+
+        let mut num_modules = 0;
+        let mut failed_modules = Vec::new();
+        while let Some(module_result) = module_results.next().await {
+            let (module, result) =
+                module_result.map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+            let (module_output, passed) = result?;
+            output
+                .write_all(&module_output.output)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+            num_modules += 1;
+            if !passed {
+                failed_modules.push(module.name().to_string());
+            }
+        }
+
+        println!(
+            "Tested {} modules: {} passed, {} failed",
+            num_modules,
+            num_modules - failed_modules.len(),
+            failed_modules.len()
+        );
+        if !failed_modules.is_empty() {
+            println!("Failed modules: {}", failed_modules.join(", "));
+        }
+        Ok(failed_modules.is_empty())
+    }
+
+    /// Whether the tests that failed within the instruction bound pass once it's raised, i.e.
+    /// they failed by running out of instructions rather than by aborting or looping forever
+    fn passes_with_raised_bound(&self, tests: &CompiledTests, bound: u64) -> CliTypedResult<bool> {
+        let unit_testing_config = UnitTestingConfig {
+            filter: self.filter.clone(),
+            ..UnitTestingConfig::default_with_bound(Some(
+                bound.saturating_mul(RAISED_INSTRUCTION_BOUND_FACTOR),
+            ))
+        };
+        let (_, passed) = tests.run(
+            None,
+            unit_testing_config,
+            unit_test_natives(),
+            std::io::sink(),
+        )?;
+        Ok(passed)
+    }
+}
+
+/// Runs the unit tests of the package at `package_path` while collecting coverage, writing the
+/// test runner's output to `output`
+fn run_unit_tests_with_coverage(
+    package_path: &Path,
+    config: BuildConfig,
+    unit_testing_config: UnitTestingConfig,
+    output: &mut TestOutput,
+) -> CliTypedResult<bool> {
+    let result = move_cli::base::test::run_move_unit_tests(
+        package_path,
+        config,
+        unit_testing_config,
+        unit_test_natives(),
+        true,
+        output,
+    )
+    .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+    Ok(matches!(result, UnitTestResult::Success))
+}
+
+fn unit_test_natives() -> NativeFunctionTable {
+    // TODO(Gas): we may want to switch to non-zero costs in the future
+    aptos_debug_natives::aptos_debug_natives(NativeGasParameters::zeros())
+}
+
 #[async_trait]
 impl CliCommand<&'static str> for TestPackage {
     fn command_name(&self) -> &'static str {
@@ -251,23 +421,102 @@ impl CliCommand<&'static str> for TestPackage {
             install_dir: self.move_options.output_dir.clone(),
             ..Default::default()
         };
-        let result = move_cli::base::test::run_move_unit_tests(
-            self.move_options.get_package_path()?.as_path(),
-            config,
-            UnitTestingConfig {
-                filter: self.filter,
-                ..UnitTestingConfig::default_with_bound(Some(100_000))
-            },
-            // TODO(Gas): we may want to switch to non-zero costs in the future
-            aptos_debug_natives::aptos_debug_natives(NativeGasParameters::zeros()),
-            false,
-            &mut std::io::stdout(),
-        )
-        .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let package_path = self.move_options.get_package_path()?;
+        let instruction_bound = self.instruction_bound();
+        let mut output = TestOutput::default();
+        let (tests, passed) = if self.coverage {
+            let passed = run_unit_tests_with_coverage(
+                package_path.as_path(),
+                config.clone(),
+                self.unit_testing_config(self.filter.clone()),
+                &mut output,
+            )?;
+            (None, passed)
+        } else {
+            let tests = Arc::new(CompiledTests::compile(
+                package_path.as_path(),
+                config.clone(),
+            )?);
+            let passed = if self.parallel {
+                self.run_module_tests_in_parallel(tests.clone(), &mut output)
+                    .await?
+            } else {
+                let (_, passed) = tests.run(
+                    None,
+                    self.unit_testing_config(self.filter.clone()),
+                    unit_test_natives(),
+                    &mut output,
+                )?;
+                passed
+            };
+            (Some(tests), passed)
+        };
 
-        match result {
-            UnitTestResult::Success => Ok("Success"),
-            UnitTestResult::Failure => Err(CliError::MoveTestError),
+        if !passed {
+            if let Some(bound) = instruction_bound {
+                // Tests running out of instructions are only reported as timed out
+                let tests = match tests {
+                    Some(tests) => tests,
+                    None => Arc::new(CompiledTests::compile(
+                        package_path.as_path(),
+                        config.clone(),
+                    )?),
+                };
+                if self.passes_with_raised_bound(&tests, bound)? {
+                    return Err(CliError::MoveTestTimeout(bound));
+                }
+            }
+            return Err(CliError::MoveTestError);
+        }
+
+        if self.coverage {
+            let package = config
+                .compile_package(package_path.as_path(), &mut Vec::new())
+                .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+            let coverage = coverage::compute_coverage(package_path.as_path(), &package)?;
+            for module in &coverage {
+                println!("{}", module.summary());
+            }
+            if let Some(coverage_output) = self.coverage_output {
+                coverage::write_lcov(&coverage, coverage_output.as_path())?;
+            }
+        }
+        Ok("Success")
+    }
+}
+
+/// Prints the unit test output, or keeps it to print it later
+#[derive(Default)]
+struct TestOutput {
+    output: Vec<u8>,
+    /// Only keep the output, to print it later
+    buffered: bool,
+}
+
+impl TestOutput {
+    fn buffered() -> Self {
+        Self {
+            output: Vec::new(),
+            buffered: true,
+        }
+    }
+}
+
+impl Write for TestOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.buffered {
+            self.output.extend_from_slice(buf);
+            Ok(buf.len())
+        } else {
+            std::io::stdout().write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.buffered {
+            Ok(())
+        } else {
+            std::io::stdout().flush()
         }
     }
 }
@@ -281,8 +530,10 @@ impl CliCommand<()> for TransactionalTestOpts {
     async fn execute(self) -> CliTypedResult<()> {
         let root_path = self.root_path.display().to_string();
 
+        // The harness reads `UB` from the environment itself, which the runner sets for the child
+        // process it updates the expected outputs in
         let requirements = vec![transactional_tests_runner::Requirements::new(
-            run_aptos_test,
+            |path, _update_baseline| run_aptos_test(path),
             "tests".to_string(),
             root_path,
             self.pattern.clone(),
@@ -302,8 +553,56 @@ pub struct ProvePackage {
     #[clap(long)]
     pub filter: Option<String>,
 
+    /// Hard timeout in seconds for each run of the Boogie verifier, which is killed once it's
+    /// exceeded, none by default
+    #[clap(long)]
+    pub prover_timeout: Option<u64>,
+
+    /// Time limit in seconds that Boogie spends verifying each function [default: 40]
+    #[clap(long)]
+    pub vc_timeout: Option<usize>,
+
+    /// Verbosity of the prover's output
+    #[clap(long, possible_values = &["error", "warn", "info", "debug"])]
+    pub prover_verbose: Option<String>,
+
+    /// Any other options to pass to the prover, as they would be given to the `move-prover` CLI
+    ///
+    /// Example: --prover-options "--dump-bytecode --num-instances 2"
+    #[clap(long)]
+    pub prover_options: Option<String>,
+
     #[clap(flatten)]
-    move_options: MovePackageDir,
+    pub(crate) move_options: MovePackageDir,
+}
+
+impl ProvePackage {
+    fn build_prover_options(&self) -> CliTypedResult<move_prover::cli::Options> {
+        // Only parse when needed, as the prover's own CLI can change more than the given options
+        let mut options = match &self.prover_options {
+            None => move_prover::cli::Options::default(),
+            Some(prover_options) => {
+                let mut args = vec!["move-prover".to_string()];
+                args.extend(prover_options.split_whitespace().map(str::to_string));
+                move_prover::cli::Options::create_from_args(&args).map_err(|err| {
+                    CliError::CommandArgumentError(format!("Invalid prover options: {:#}", err))
+                })?
+            }
+        };
+
+        if let Some(verbosity) = &self.prover_verbose {
+            options.verbosity_level = verbosity.parse().map_err(|err| {
+                CliError::CommandArgumentError(format!("Invalid prover verbosity: {}", err))
+            })?;
+        }
+        if let Some(prover_timeout) = self.prover_timeout {
+            options.backend.hard_timeout_secs = prover_timeout;
+        }
+        if let Some(vc_timeout) = self.vc_timeout {
+            options.backend.vc_timeout = vc_timeout;
+        }
+        Ok(options)
+    }
 }
 
 #[async_trait]
@@ -319,13 +618,14 @@ impl CliCommand<&'static str> for ProvePackage {
             install_dir: self.move_options.output_dir.clone(),
             ..Default::default()
         };
+        let options = self.build_prover_options()?;
         let result = task::spawn_blocking(move || {
             move_cli::base::prove::run_move_prover(
                 config,
                 self.move_options.get_package_path()?.as_path(),
                 &self.filter,
                 true,
-                move_prover::cli::Options::default(),
+                options,
             )
         })
         .await
@@ -338,6 +638,119 @@ impl CliCommand<&'static str> for ProvePackage {
     }
 }
 
+/// Generates documentation for a Move package
+///
+/// This runs the Move documentation generator over the package, and writes a markdown file
+/// for each of its modules.
+#[derive(Parser)]
+pub struct DocumentPackage {
+    /// Whether to include the implementation of functions in the documentation
+    #[clap(long)]
+    pub include_impl: bool,
+
+    /// Whether to include the specifications of functions and structs in the documentation
+    #[clap(long)]
+    pub include_specs: bool,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for DocumentPackage {
+    fn command_name(&self) -> &'static str {
+        "DocumentPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let package_path = self.move_options.get_package_path()?;
+        let config = BuildConfig {
+            additional_named_addresses: self.move_options.named_addresses(),
+            ..Default::default()
+        };
+        let model = config
+            .move_model_for_package(
+                package_path.as_path(),
+                ModelConfig {
+                    all_files_as_targets: false,
+                    target_filter: None,
+                },
+            )
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        if model.has_errors() {
+            let mut diagnostics = Buffer::no_color();
+            model.report_diag(&mut diagnostics, Severity::Warning);
+            return Err(CliError::MoveCompilationError(
+                String::from_utf8_lossy(diagnostics.as_slice()).to_string(),
+            ));
+        }
+
+        let output_dir = self
+            .move_options
+            .output_dir
+            .unwrap_or_else(|| package_path.join("doc"));
+        let options = DocgenOptions {
+            include_impl: self.include_impl,
+            include_specs: self.include_specs,
+            output_directory: output_dir.display().to_string(),
+            ..DocgenOptions::default()
+        };
+
+        let mut files = Vec::new();
+        for (file, content) in Docgen::new(&model, &options).gen() {
+            let path = PathBuf::from(&file);
+            if let Some(parent) = path.parent() {
+                create_dir_if_not_exist(parent)?;
+            }
+            write_to_file(path.as_path(), &file, content.as_bytes())?;
+            files.push(file);
+        }
+        Ok(files)
+    }
+}
+
+/// Writes the JSON ABI of each module in a Move package
+///
+/// The ABIs have the same format as the ones of modules returned by the REST API, and are
+/// written as `{module}.abi.json` to the output directory, by default `<package_dir>/abi`.
+#[derive(Parser)]
+pub struct ExtractAbi {
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for ExtractAbi {
+    fn command_name(&self) -> &'static str {
+        "ExtractAbi"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let package_path = self.move_options.get_package_path()?;
+        let build_options =
+            IncludedArtifacts::All.build_options(self.move_options.named_addresses());
+        let pack = BuiltPackage::build(package_path.clone(), build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+
+        let output_dir = self
+            .move_options
+            .output_dir
+            .unwrap_or_else(|| package_path.join("abi"));
+        create_dir_if_not_exist(output_dir.as_path())?;
+
+        let mut files = Vec::new();
+        for module in pack.modules() {
+            let file = output_dir.join(format!("{}.abi.json", module.self_id().name()));
+            let abi = MoveModule::from(module.clone());
+            let json = serde_json::to_string_pretty(&abi)
+                .map_err(|e| CliError::UnexpectedError(e.to_string()))?;
+            write_to_file(file.as_path(), &file.display().to_string(), json.as_bytes())?;
+            files.push(file.display().to_string());
+        }
+        Ok(files)
+    }
+}
+
 /// Publishes the modules in a Move package to the Aptos blockchain
 #[derive(Parser)]
 pub struct PublishPackage {
@@ -359,10 +772,17 @@ pub struct PublishPackage {
     #[clap(long, default_value_t = IncludedArtifacts::Sparse)]
     pub(crate) included_artifacts: IncludedArtifacts,
 
+    /// Whether to allow publishing with the upgrade policy `arbitrary`. Such a package can
+    /// be replaced with any code later on, so nobody can safely depend on it.
+    #[clap(long)]
+    pub(crate) allow_arbitrary_upgrade: bool,
+
     #[clap(flatten)]
     pub(crate) move_options: MovePackageDir,
     #[clap(flatten)]
     pub(crate) txn_options: TransactionOptions,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
 }
 
 #[derive(ArgEnum, Clone, Copy, Debug)]
@@ -448,6 +868,8 @@ impl CliCommand<TransactionSummary> for PublishPackage {
             legacy_flow,
             override_size_check,
             included_artifacts,
+            allow_arbitrary_upgrade,
+            prompt_options,
         } = self;
         let package_path = move_options.get_package_path()?;
         let options = included_artifacts.build_options(move_options.named_addresses());
@@ -464,6 +886,24 @@ impl CliCommand<TransactionSummary> for PublishPackage {
         } else {
             // Send the compiled module and metadata using the code::publish_package_txn.
             let metadata = package.extract_metadata()?;
+            let url = txn_options
+                .rest_options
+                .url(&txn_options.profile_options.profile)?;
+            let registry =
+                CachedPackageRegistry::create_if_exists(url, txn_options.sender_address()?).await?;
+            let existing_policy = match &registry {
+                Some(registry) => registry
+                    .find_package(&metadata.name)
+                    .map(|package| package.upgrade_policy()),
+                None => None,
+            };
+            check_upgrade_policy(
+                &metadata.name,
+                existing_policy,
+                metadata.upgrade_policy,
+                allow_arbitrary_upgrade,
+                prompt_options,
+            )?;
             let payload = cached_packages::aptos_stdlib::code_publish_package_txn(
                 bcs::to_bytes(&metadata).expect("PackageMetadata has BCS"),
                 compiled_units,
@@ -486,6 +926,39 @@ impl CliCommand<TransactionSummary> for PublishPackage {
     }
 }
 
+/// Checks the upgrade policy a package is published with against the one it has on-chain
+///
+/// Publishing with `arbitrary` needs to be explicitly allowed, and weakening the policy of an
+/// existing package needs to be confirmed.
+fn check_upgrade_policy(
+    package_name: &str,
+    existing_policy: Option<UpgradePolicy>,
+    new_policy: UpgradePolicy,
+    allow_arbitrary_upgrade: bool,
+    prompt_options: PromptOptions,
+) -> CliTypedResult<()> {
+    if new_policy == UpgradePolicy::arbitrary() && !allow_arbitrary_upgrade {
+        return Err(CliError::CommandArgumentError(format!(
+            "Package `{}` has upgrade policy `arbitrary`, which lets its code be replaced \
+            with anything. Use `--allow-arbitrary-upgrade` if this is intended",
+            package_name
+        )));
+    }
+    if let Some(existing_policy) = existing_policy {
+        if new_policy.policy < existing_policy.policy {
+            prompt_yes_with_override(
+                &format!(
+                    "Package `{}` has upgrade policy `{}` on-chain, do you want to \
+                    weaken it to `{}`?",
+                    package_name, existing_policy, new_policy
+                ),
+                prompt_options,
+            )?;
+        }
+    }
+    Ok(())
+}
+
 /// Downloads a package and stores it in a directory named after the package
 ///
 /// This lets you retrieve packages directly from the blockchain for inspection
@@ -615,40 +1088,87 @@ impl CliCommand<&'static str> for ListPackage {
 }
 
 /// Cleans derived artifacts of a package.
+///
+/// Only the package's build directory is deleted, unless `--clear-global-cache` is given.
 #[derive(Parser)]
 pub struct CleanPackage {
+    /// Also delete the global package download cache at MOVE_HOME, which is shared by all
+    /// packages.  Prompts for confirmation unless `--assume-yes` is given.
+    #[clap(long)]
+    pub(crate) clear_global_cache: bool,
     #[clap(flatten)]
     pub(crate) move_options: MovePackageDir,
     #[clap(flatten)]
     pub(crate) prompt_options: PromptOptions,
 }
 
+/// What `aptos move clean` deleted
+#[derive(Debug, Serialize)]
+pub struct CleanSummary {
+    pub build_dir_bytes_reclaimed: u64,
+    pub global_cache_bytes_reclaimed: u64,
+}
+
 #[async_trait]
-impl CliCommand<&'static str> for CleanPackage {
+impl CliCommand<CleanSummary> for CleanPackage {
     fn command_name(&self) -> &'static str {
         "Clean"
     }
-    async fn execute(self) -> CliTypedResult<&'static str> {
+    async fn execute(self) -> CliTypedResult<CleanSummary> {
         let path = self.move_options.get_package_path()?;
-        let build_dir = path.join("build");
-        std::fs::remove_dir_all(build_dir)
-            .map_err(|e| CliError::IO("Removing Move build dir".to_string(), e))?;
-
-        let move_dir = &*MOVE_HOME;
-        if prompt_yes_with_override(
-            &format!(
-                "Do you also want to delete the local package download cache at `{}`?",
-                move_dir
-            ),
-            self.prompt_options,
-        )
-        .is_ok()
-        {
-            std::fs::remove_dir_all(PathBuf::from(move_dir))
-                .map_err(|e| CliError::IO("Removing Move cache dir".to_string(), e))?;
+        let build_dir_bytes_reclaimed = remove_dir(&path.join("build"), "Move build dir")?;
+
+        let global_cache_bytes_reclaimed = if self.clear_global_cache {
+            clean_global_cache(Path::new(&*MOVE_HOME), self.prompt_options)?
+        } else {
+            0
+        };
+        Ok(CleanSummary {
+            build_dir_bytes_reclaimed,
+            global_cache_bytes_reclaimed,
+        })
+    }
+}
+
+/// Deletes the global package download cache at `cache_dir` once confirmed, returning the bytes
+/// reclaimed.  Nothing is deleted if it's declined.
+fn clean_global_cache(cache_dir: &Path, prompt_options: PromptOptions) -> CliTypedResult<u64> {
+    if !cache_dir.exists() {
+        eprintln!(
+            "No global package download cache at `{}`, skipping",
+            cache_dir.display()
+        );
+        return Ok(0);
+    }
+    let prompt = format!(
+        "Do you want to delete the global package download cache at `{}`? \
+        It's shared by all packages.",
+        cache_dir.display()
+    );
+    if prompt_yes_with_override(&prompt, prompt_options).is_err() {
+        eprintln!("Keeping the global package download cache");
+        return Ok(0);
+    }
+    remove_dir(cache_dir, "Move cache dir")
+}
+
+/// Deletes `dir` if it exists, returning the bytes reclaimed
+fn remove_dir(dir: &Path, name: &str) -> CliTypedResult<u64> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let mut bytes = 0;
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry.map_err(|e| CliError::UnexpectedError(e.to_string()))?;
+        if entry.file_type().is_file() {
+            bytes += entry
+                .metadata()
+                .map_err(|e| CliError::UnexpectedError(e.to_string()))?
+                .len();
         }
-        Ok("succeeded")
     }
+    std::fs::remove_dir_all(dir).map_err(|e| CliError::IO(format!("Removing {}", name), e))?;
+    Ok(bytes)
 }
 
 /// Run a Move function
@@ -828,3 +1348,185 @@ impl FromStr for MemberId {
         parse_member_id(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+
+    fn check(
+        existing_policy: Option<UpgradePolicy>,
+        new_policy: UpgradePolicy,
+        allow_arbitrary_upgrade: bool,
+        prompt_options: PromptOptions,
+    ) -> CliTypedResult<()> {
+        check_upgrade_policy(
+            "Package",
+            existing_policy,
+            new_policy,
+            allow_arbitrary_upgrade,
+            prompt_options,
+        )
+    }
+
+    fn prove_package(
+        prover_timeout: Option<u64>,
+        prover_verbose: Option<&str>,
+        prover_options: Option<&str>,
+    ) -> ProvePackage {
+        ProvePackage {
+            filter: None,
+            prover_timeout,
+            vc_timeout: None,
+            prover_verbose: prover_verbose.map(str::to_string),
+            prover_options: prover_options.map(str::to_string),
+            move_options: MovePackageDir::new(PathBuf::from(".")),
+        }
+    }
+
+    fn write_file(dir: &Path, name: &str, bytes: usize) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join(name), vec![0u8; bytes]).unwrap();
+    }
+
+    #[test]
+    fn test_prover_verbose_only_sets_verbosity() {
+        let options = prove_package(None, Some("debug"), None)
+            .build_prover_options()
+            .unwrap();
+        assert_eq!(format!("{:?}", options.verbosity_level), "Debug");
+
+        let mut expected = move_prover::cli::Options::default();
+        expected.verbosity_level = options.verbosity_level;
+        assert_eq!(format!("{:?}", options), format!("{:?}", expected));
+    }
+
+    #[test]
+    fn test_prover_options() {
+        let options = prove_package(Some(10), Some("warn"), Some("--dump-bytecode"))
+            .build_prover_options()
+            .unwrap();
+        assert!(options.prover.dump_bytecode);
+        assert_eq!(options.backend.hard_timeout_secs, 10);
+        assert_eq!(format!("{:?}", options.verbosity_level), "Warn");
+
+        assert!(matches!(
+            prove_package(None, None, Some("--no-such-option")).build_prover_options(),
+            Err(CliError::CommandArgumentError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_clean_package() {
+        let package_dir = TempPath::new();
+        package_dir.create_as_dir().unwrap();
+        let clean = || CleanPackage {
+            clear_global_cache: false,
+            move_options: MovePackageDir::new(package_dir.path().to_path_buf()),
+            prompt_options: PromptOptions::no(),
+        };
+
+        // There's nothing to clean before the package is built
+        let summary = clean().execute().await.unwrap();
+        assert_eq!(summary.build_dir_bytes_reclaimed, 0);
+        assert_eq!(summary.global_cache_bytes_reclaimed, 0);
+
+        let build_dir = package_dir.path().join("build");
+        write_file(&build_dir, "a", 100);
+        write_file(&build_dir.join("nested"), "b", 23);
+        let summary = clean().execute().await.unwrap();
+        assert_eq!(summary.build_dir_bytes_reclaimed, 123);
+        assert!(!build_dir.exists());
+        assert!(package_dir.path().exists());
+    }
+
+    #[test]
+    fn test_clean_global_cache() {
+        let cache_dir = TempPath::new();
+        let cache_dir = cache_dir.path();
+
+        // A missing cache is skipped without prompting
+        assert_eq!(
+            clean_global_cache(cache_dir, PromptOptions::no()).unwrap(),
+            0
+        );
+
+        // An existing cache is deleted only if the prompt is confirmed
+        write_file(cache_dir, "package", 42);
+        assert_eq!(
+            clean_global_cache(cache_dir, PromptOptions::no()).unwrap(),
+            0
+        );
+        assert!(cache_dir.exists());
+        assert_eq!(
+            clean_global_cache(cache_dir, PromptOptions::yes()).unwrap(),
+            42
+        );
+        assert!(!cache_dir.exists());
+    }
+
+    #[test]
+    fn test_check_upgrade_policy() {
+        // Publishing for the first time, or keeping or strengthening the policy, doesn't prompt
+        check(None, UpgradePolicy::compat(), false, PromptOptions::no()).unwrap();
+        check(
+            Some(UpgradePolicy::compat()),
+            UpgradePolicy::compat(),
+            false,
+            PromptOptions::no(),
+        )
+        .unwrap();
+        check(
+            Some(UpgradePolicy::compat()),
+            UpgradePolicy::immutable(),
+            false,
+            PromptOptions::no(),
+        )
+        .unwrap();
+
+        // Weakening the policy has to be confirmed
+        assert!(matches!(
+            check(
+                Some(UpgradePolicy::immutable()),
+                UpgradePolicy::compat(),
+                false,
+                PromptOptions::no(),
+            ),
+            Err(CliError::AbortedError)
+        ));
+        check(
+            Some(UpgradePolicy::immutable()),
+            UpgradePolicy::compat(),
+            false,
+            PromptOptions::yes(),
+        )
+        .unwrap();
+
+        // Arbitrary is blocked, even when confirming, unless explicitly allowed
+        assert!(matches!(
+            check(
+                None,
+                UpgradePolicy::arbitrary(),
+                false,
+                PromptOptions::yes()
+            ),
+            Err(CliError::CommandArgumentError(_))
+        ));
+        assert!(matches!(
+            check(
+                Some(UpgradePolicy::compat()),
+                UpgradePolicy::arbitrary(),
+                true,
+                PromptOptions::no(),
+            ),
+            Err(CliError::AbortedError)
+        ));
+        check(
+            Some(UpgradePolicy::compat()),
+            UpgradePolicy::arbitrary(),
+            true,
+            PromptOptions::yes(),
+        )
+        .unwrap();
+    }
+}
@@ -14,6 +14,8 @@ use reqwest::Url;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+const PACKAGE_REGISTRY_RESOURCE: &str = "0x1::code::PackageRegistry";
+
 // TODO: this is a first naive implementation of the package registry. Before mainnet
 // we need to use tables for the package registry.
 
@@ -38,9 +40,39 @@ impl CachedPackageRegistry {
         let client = Client::new(url);
         // Need to use a different type to deserialize JSON
         let from_json = client
-            .get_resource::<PackageRegistryJson>(addr, "0x1::code::PackageRegistry")
+            .get_resource::<PackageRegistryJson>(addr, PACKAGE_REGISTRY_RESOURCE)
             .await?
             .into_inner();
+        Self::from_json(from_json)
+    }
+
+    /// Creates a new registry, or returns `None` if nothing was ever published at the account.
+    pub async fn create_if_exists(url: Url, addr: AccountAddress) -> anyhow::Result<Option<Self>> {
+        let client = Client::new(url);
+        let resources = match client.get_account_resources(addr).await {
+            Ok(resources) => resources.into_inner(),
+            // Nothing was published by an account that doesn't exist yet
+            Err(err)
+                if err
+                    .downcast_ref::<aptos_rest_client::error::Error>()
+                    .map_or(false, aptos_rest_client::error::Error::is_account_not_found) =>
+            {
+                return Ok(None)
+            }
+            Err(err) => return Err(err),
+        };
+        match resources
+            .into_iter()
+            .find(|resource| resource.resource_type.to_string() == PACKAGE_REGISTRY_RESOURCE)
+        {
+            Some(resource) => Ok(Some(Self::from_json(serde_json::from_value(
+                resource.data,
+            )?)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn from_json(from_json: PackageRegistryJson) -> anyhow::Result<Self> {
         let inner = bcs::from_bytes::<PackageRegistry>(&bcs::to_bytes(&from_json)?)?;
         Ok(Self { inner })
     }
@@ -76,12 +108,21 @@ impl CachedPackageRegistry {
         name: impl AsRef<str>,
     ) -> anyhow::Result<CachedPackageMetadata<'_>> {
         let name = name.as_ref();
-        for package in &self.inner.packages {
-            if package.name == name {
-                return Ok(CachedPackageMetadata { metadata: package });
-            }
+        match self.find_package(name) {
+            Some(package) => Ok(package),
+            None => bail!("package `{}` not found", name),
         }
-        bail!("package `{}` not found", name)
+    }
+
+    /// Finds the metadata for the given package in the registry by its unique name, if it has
+    /// been published.
+    pub fn find_package(&self, name: impl AsRef<str>) -> Option<CachedPackageMetadata<'_>> {
+        let name = name.as_ref();
+        self.inner
+            .packages
+            .iter()
+            .find(|package| package.name == name)
+            .map(|metadata| CachedPackageMetadata { metadata })
     }
 }
 
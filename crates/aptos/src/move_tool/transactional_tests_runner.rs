@@ -8,6 +8,9 @@
 use clap::Parser;
 
 use crate::common::types::{CliError, CliTypedResult};
+use move_deps::move_command_line_common::testing::{
+    read_env_update_baseline, EXP_EXT, UPDATE_BASELINE,
+};
 
 use std::{
     io::{self, Write},
@@ -35,9 +38,21 @@ pub struct TransactionalTestOpts {
     #[clap(long = "exact")]
     pub filter_exact: bool,
 
-    /// Number of threads used for running tests in parallel
-    #[clap(long, default_value = "32")]
-    pub test_threads: NonZeroUsize,
+    /// Number of threads the test files are sharded across
+    #[clap(long, alias = "test-threads", default_value = "32")]
+    pub jobs: NonZeroUsize,
+
+    /// Rewrite the expected output (`.exp`) files of tests whose output differs
+    ///
+    /// Same as running with `UB=1`
+    #[clap(long)]
+    pub update_baseline: bool,
+
+    /// Fail any test whose expected output file would be written, e.g. in CI
+    ///
+    /// Can't be combined with `--update-baseline` or `UB=1`
+    #[clap(long, conflicts_with = "update-baseline")]
+    pub forbid_update: bool,
 
     /// Output minimal information
     #[clap(long)]
@@ -85,18 +100,41 @@ pub fn derive_test_name(root: &Path, path: &Path, test_name: &str) -> String {
     test_name
 }
 
+impl TransactionalTestOpts {
+    fn check_baseline_flags(&self) -> CliTypedResult<()> {
+        if self.forbid_update && read_env_update_baseline() {
+            return Err(CliError::CommandArgumentError(format!(
+                "{} can't be set with --forbid-update",
+                UPDATE_BASELINE
+            )));
+        }
+        Ok(())
+    }
+}
+
 struct Test {
-    testfn: Box<dyn Fn() -> Result<()> + Send>,
+    testfn: Box<dyn Fn(bool) -> Result<()> + Send>,
     name: String,
+    path: PathBuf,
 }
 
 enum TestResult {
     Ok,
+    Updated,
     Failed,
     FailedWithMsg(String),
 }
 
 pub(crate) fn runner(options: &TransactionalTestOpts, reqs: &[Requirements]) -> CliTypedResult<()> {
+    options.check_baseline_flags()?;
+
+    // Harnesses that only read `UB` from the environment get it in a child process running the
+    // same command, rather than by changing the environment of this process and its threads
+    if options.update_baseline && !read_env_update_baseline() {
+        return update_baseline_in_child_process();
+    }
+    let update_baseline = options.update_baseline || read_env_update_baseline();
+
     let mut tests: Vec<Test> = reqs.iter().flat_map(|req| req.expand()).collect();
     tests.sort_by(|a, b| a.name.cmp(&b.name));
 
@@ -108,9 +146,9 @@ pub(crate) fn runner(options: &TransactionalTestOpts, reqs: &[Requirements]) ->
         return Ok(());
     }
 
-    match run_tests(options, tests) {
-        Ok(true) => Ok(()),
-        Ok(false) => process::exit(101),
+    match run_tests(options, tests, update_baseline) {
+        Ok(summary) if summary.success() => Ok(()),
+        Ok(_) => process::exit(101),
         Err(e) => Err(CliError::UnexpectedError(format!(
             "error: io error when running tests: {:?}",
             e
@@ -118,11 +156,35 @@ pub(crate) fn runner(options: &TransactionalTestOpts, reqs: &[Requirements]) ->
     }
 }
 
-fn run_tests(options: &TransactionalTestOpts, tests: Vec<Test>) -> io::Result<bool> {
+fn update_baseline_in_child_process() -> CliTypedResult<()> {
+    let exe = std::env::current_exe()
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to find executable: {}", err)))?;
+    let status = process::Command::new(exe)
+        .args(std::env::args_os().skip(1))
+        .env(UPDATE_BASELINE, "1")
+        .status()
+        .map_err(|err| {
+            CliError::UnexpectedError(format!(
+                "Failed to run tests with {}=1: {}",
+                UPDATE_BASELINE, err
+            ))
+        })?;
+    if status.success() {
+        Ok(())
+    } else {
+        process::exit(status.code().unwrap_or(101))
+    }
+}
+
+fn run_tests(
+    options: &TransactionalTestOpts,
+    tests: Vec<Test>,
+    update_baseline: bool,
+) -> io::Result<TestSummary> {
     let total = tests.len();
 
     // Filter out tests
-    let mut remaining = match &options.filter {
+    let remaining: Vec<_> = match &options.filter {
         None => tests,
         Some(filter) => tests
             .into_iter()
@@ -133,7 +195,6 @@ fn run_tests(options: &TransactionalTestOpts, tests: Vec<Test>) -> io::Result<bo
                     test.name.contains(&filter[..])
                 }
             })
-            .rev()
             .collect(),
     };
 
@@ -144,51 +205,102 @@ fn run_tests(options: &TransactionalTestOpts, tests: Vec<Test>) -> io::Result<bo
         summary.write_starting_msg()?;
     }
 
-    let (tx, rx) = channel();
+    // Shard the tests round robin, so each thread gets a similar mix of the sorted tests
+    let jobs = options.jobs.get().min(remaining.len()).max(1);
+    let mut shards: Vec<Vec<Test>> = (0..jobs).map(|_| Vec::new()).collect();
+    for (i, test) in remaining.into_iter().enumerate() {
+        shards[i % jobs].push(test);
+    }
 
-    let mut pending = 0;
-    while pending > 0 || !remaining.is_empty() {
-        while pending < options.test_threads.get() && !remaining.is_empty() {
-            let test = remaining.pop().unwrap();
-            run_test(test, tx.clone());
-            pending += 1;
-        }
+    let (tx, rx) = channel();
+    for (i, shard) in shards.into_iter().enumerate() {
+        run_shard(i, shard, update_baseline, options.forbid_update, tx.clone());
+    }
+    drop(tx);
 
-        let (name, result) = rx.recv().unwrap();
+    // Results arrive in whichever order the shards finish them
+    for (name, result) in rx {
         summary.handle_result(name, result)?;
-
-        pending -= 1;
     }
+    summary.updated.sort();
+    summary.failed.sort();
 
     // Write Test Summary
     if !options.quiet {
         summary.write_summary()?;
     }
 
-    Ok(summary.success())
+    Ok(summary)
 }
 
-fn run_test(test: Test, channel: Sender<(String, TestResult)>) {
-    let Test { name, testfn } = test;
-
-    let cfg = thread::Builder::new().name(name.clone());
+fn run_shard(
+    shard_id: usize,
+    tests: Vec<Test>,
+    update_baseline: bool,
+    forbid_update: bool,
+    channel: Sender<(String, TestResult)>,
+) {
+    let cfg = thread::Builder::new().name(format!("transactional-tests-{}", shard_id));
     cfg.spawn(move || {
-        let result = match catch_unwind(AssertUnwindSafe(testfn)) {
-            Ok(Ok(())) => TestResult::Ok,
-            Ok(Err(e)) => TestResult::FailedWithMsg(format!("{:?}", e)),
-            Err(_) => TestResult::Failed,
-        };
-
-        channel.send((name, result)).unwrap();
+        for test in tests {
+            // Each test gets its own thread named after it, so panic messages name the test
+            let cfg = thread::Builder::new().name(test.name.clone());
+            let result = cfg
+                .spawn(move || run_test(test, update_baseline, forbid_update))
+                .unwrap()
+                .join()
+                .unwrap();
+            channel.send(result).unwrap();
+        }
     })
     .unwrap();
 }
 
+fn run_test(test: Test, update_baseline: bool, forbid_update: bool) -> (String, TestResult) {
+    let Test { name, testfn, path } = test;
+
+    // The harness may write the expected output, either to update it or to create it if missing
+    let exp_path = path.with_extension(EXP_EXT);
+    let baseline = std::fs::read_to_string(&exp_path).ok();
+    let result = catch_unwind(AssertUnwindSafe(|| testfn(update_baseline)));
+    let written = std::fs::read_to_string(&exp_path).ok() != baseline;
+
+    // Any write is undone when updates are forbidden, whether or not the test passed
+    if written && forbid_update {
+        let restored = match &baseline {
+            Some(baseline) => std::fs::write(&exp_path, baseline),
+            None => std::fs::remove_file(&exp_path),
+        };
+        let msg = match restored {
+            Ok(()) => format!(
+                "Expected output file {} was written, but updates are forbidden, so it was restored",
+                exp_path.display()
+            ),
+            Err(err) => format!(
+                "Expected output file {} was written, but updates are forbidden, and it couldn't be restored: {}",
+                exp_path.display(),
+                err
+            ),
+        };
+        return (name, TestResult::FailedWithMsg(msg));
+    }
+
+    let result = match result {
+        Ok(Ok(())) if written => TestResult::Updated,
+        Ok(Ok(())) => TestResult::Ok,
+        Ok(Err(e)) => TestResult::FailedWithMsg(format!("{:?}", e)),
+        Err(_) => TestResult::Failed,
+    };
+
+    (name, result)
+}
+
 struct TestSummary {
     stdout: StandardStream,
     total: usize,
     filtered_out: usize,
     passed: usize,
+    updated: Vec<String>,
     failed: Vec<String>,
 }
 
@@ -199,6 +311,7 @@ impl TestSummary {
             total,
             filtered_out,
             passed: 0,
+            updated: Vec::new(),
             failed: Vec::new(),
         }
     }
@@ -210,6 +323,10 @@ impl TestSummary {
                 self.passed += 1;
                 self.write_ok()?;
             }
+            TestResult::Updated => {
+                self.updated.push(name);
+                self.write_updated()?;
+            }
             TestResult::Failed => {
                 self.failed.push(name);
                 self.write_failed()?;
@@ -234,6 +351,14 @@ impl TestSummary {
         Ok(())
     }
 
+    fn write_updated(&mut self) -> io::Result<()> {
+        self.stdout
+            .set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))?;
+        write!(self.stdout, "updated")?;
+        self.stdout.reset()?;
+        Ok(())
+    }
+
     fn write_failed(&mut self) -> io::Result<()> {
         self.stdout
             .set_color(ColorSpec::new().set_fg(Some(Color::Red)))?;
@@ -253,6 +378,15 @@ impl TestSummary {
     }
 
     fn write_summary(&mut self) -> io::Result<()> {
+        // Print out the updated tests
+        if !self.updated.is_empty() {
+            writeln!(self.stdout)?;
+            writeln!(self.stdout, "updated:")?;
+            for name in &self.updated {
+                writeln!(self.stdout, "    {}", name)?;
+            }
+        }
+
         // Print out the failing tests
         if !self.failed.is_empty() {
             writeln!(self.stdout)?;
@@ -271,8 +405,9 @@ impl TestSummary {
         }
         writeln!(
             self.stdout,
-            ". {} passed; {} failed; {} filtered out",
+            ". {} passed; {} updated; {} failed; {} filtered out",
             self.passed,
+            self.updated.len(),
             self.failed.len(),
             self.filtered_out
         )?;
@@ -287,7 +422,7 @@ impl TestSummary {
 
 #[doc(hidden)]
 pub struct Requirements {
-    test: fn(&Path) -> Result<()>,
+    test: fn(&Path, bool) -> Result<()>,
     test_name: String,
     root: String,
     pattern: String,
@@ -296,7 +431,7 @@ pub struct Requirements {
 impl Requirements {
     #[doc(hidden)]
     pub fn new(
-        test: fn(&Path) -> Result<()>,
+        test: fn(&Path, bool) -> Result<()>,
         test_name: String,
         root: String,
         pattern: String,
@@ -326,9 +461,11 @@ impl Requirements {
                 if re.is_match(&input_path) {
                     let testfn = self.test;
                     let name = derive_test_name(&root, &path, &self.test_name);
-                    let testfn = Box::new(move || (testfn)(&path));
+                    let test_path = path.clone();
+                    let testfn =
+                        Box::new(move |update_baseline| (testfn)(&test_path, update_baseline));
 
-                    Some(Test { testfn, name })
+                    Some(Test { testfn, name, path })
                 } else {
                     None
                 }
@@ -346,3 +483,155 @@ impl Requirements {
         tests
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+
+    /// Stands in for the transactional test harness: the output is the uppercased input, and
+    /// the expected output is rewritten when updating the baseline, or created empty if missing.
+    fn uppercase_test(path: &Path, update_baseline: bool) -> Result<()> {
+        let output = std::fs::read_to_string(path)?.to_uppercase();
+        let exp_path = path.with_extension(EXP_EXT);
+        if update_baseline {
+            std::fs::write(&exp_path, &output)?;
+            return Ok(());
+        }
+        if !exp_path.exists() {
+            std::fs::write(&exp_path, "")?;
+        }
+        let expected = std::fs::read_to_string(&exp_path)?;
+        if output != expected {
+            return Err(format!("Expected {:?}, got {:?}", expected, output).into());
+        }
+        Ok(())
+    }
+
+    fn write_test(dir: &Path, name: &str, input: &str, expected: &str) {
+        std::fs::write(dir.join(name).with_extension("move"), input).unwrap();
+        std::fs::write(dir.join(name).with_extension(EXP_EXT), expected).unwrap();
+    }
+
+    fn opts(root_path: &Path, jobs: usize) -> TransactionalTestOpts {
+        TransactionalTestOpts {
+            filter: None,
+            filter_exact: false,
+            jobs: NonZeroUsize::new(jobs).unwrap(),
+            quiet: true,
+            list: false,
+            root_path: root_path.to_path_buf(),
+            pattern: r".*\.move$".to_string(),
+            update_baseline: false,
+            forbid_update: false,
+        }
+    }
+
+    fn run(options: &TransactionalTestOpts, test: fn(&Path, bool) -> Result<()>) -> TestSummary {
+        let requirements = Requirements::new(
+            test,
+            "tests".to_string(),
+            options.root_path.display().to_string(),
+            options.pattern.clone(),
+        );
+        let mut tests = requirements.expand();
+        tests.sort_by(|a, b| a.name.cmp(&b.name));
+        run_tests(options, tests, options.update_baseline).unwrap()
+    }
+
+    #[test]
+    fn test_update_baseline() {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        write_test(dir.path(), "same", "abc", "ABC");
+        write_test(dir.path(), "stale", "abc", "OLD");
+
+        let mut options = opts(dir.path(), 2);
+        options.update_baseline = true;
+        let summary = run(&options, uppercase_test);
+        assert!(summary.success());
+        assert_eq!(summary.passed, 1);
+        assert_eq!(summary.updated, vec!["tests::stale.move".to_string()]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("stale.exp")).unwrap(),
+            "ABC"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("same.exp")).unwrap(),
+            "ABC"
+        );
+
+        // The update can't be combined with CI mode
+        assert!(TransactionalTestOpts::try_parse_from([
+            "transactional-test",
+            "--root-path",
+            ".",
+            "--update-baseline",
+            "--forbid-update",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_forbid_update() {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        write_test(dir.path(), "same", "abc", "ABC");
+        std::fs::write(dir.path().join("missing.move"), "abc").unwrap();
+
+        let mut options = opts(dir.path(), 2);
+        options.forbid_update = true;
+        let summary = run(&options, uppercase_test);
+        assert_eq!(summary.passed, 1);
+        assert_eq!(summary.failed, vec!["tests::missing.move".to_string()]);
+        // The expected output the harness created is removed again
+        assert!(!dir.path().join("missing.exp").exists());
+
+        // A test that writes over its expected output gets the old one back
+        let summary = run(&options, |path, _| {
+            std::fs::write(path.with_extension(EXP_EXT), "NEW")?;
+            Ok(())
+        });
+        assert_eq!(summary.failed, vec!["tests::same.move".to_string()]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("same.exp")).unwrap(),
+            "ABC"
+        );
+    }
+
+    #[test]
+    fn test_thread_named_after_test() {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        write_test(dir.path(), "named", "abc", "ABC");
+
+        let summary = run(&opts(dir.path(), 1), |path, update_baseline| {
+            assert_eq!(thread::current().name(), Some("tests::named.move"));
+            uppercase_test(path, update_baseline)
+        });
+        assert!(summary.success());
+        assert_eq!(summary.passed, 1);
+    }
+
+    #[test]
+    fn test_parallel_determinism() {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        for i in 0..20 {
+            let expected = if i % 3 == 0 { "wrong" } else { "INPUT" };
+            write_test(dir.path(), &format!("test_{:02}", i), "input", expected);
+        }
+
+        let serial = run(&opts(dir.path(), 1), uppercase_test);
+        assert_eq!(serial.passed, 13);
+        assert_eq!(serial.failed.len(), 7);
+        assert!(serial.updated.is_empty());
+
+        for jobs in [2, 4, 32] {
+            let parallel = run(&opts(dir.path(), jobs), uppercase_test);
+            assert_eq!(parallel.passed, serial.passed);
+            assert_eq!(parallel.updated, serial.updated);
+            assert_eq!(parallel.failed, serial.failed);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Move unit tests compiled into a test plan once, which can then be run any number of times,
+//! e.g. module by module or with a different instruction bound, without compiling again.
+//!
+//! The compilation follows `move_cli::base::test::run_move_unit_tests`, which compiles the
+//! package every time it's called.
+
+use crate::common::types::{CliError, CliTypedResult};
+use move_deps::{
+    move_command_line_common::{
+        address::{NumberFormat, NumericalAddress},
+        files::FileHash,
+    },
+    move_compiler::{
+        compiled_unit::NamedCompiledModule,
+        diagnostics::{self, codes::Severity, FilesSourceText},
+        unit_test::{plan_builder::construct_test_plan, ModuleTestPlan, TestPlan},
+        PASS_CFGIR,
+    },
+    move_core_types::language_storage::ModuleId,
+    move_package::{compilation::build_plan::BuildPlan, BuildConfig},
+    move_unit_test::UnitTestingConfig,
+    move_vm_runtime::native_functions::NativeFunctionTable,
+};
+use std::{collections::BTreeMap, io::Write, path::Path};
+
+/// The unit tests of a package, compiled in test mode
+pub struct CompiledTests {
+    files: FilesSourceText,
+    module_tests: BTreeMap<ModuleId, ModuleTestPlan>,
+    module_info: BTreeMap<ModuleId, NamedCompiledModule>,
+    named_address_values: Vec<(String, NumericalAddress)>,
+}
+
+impl CompiledTests {
+    /// Compiles the package at `package_path` with its unit tests
+    pub fn compile(package_path: &Path, mut build_config: BuildConfig) -> CliTypedResult<Self> {
+        build_config.test_mode = true;
+        build_config.dev_mode = true;
+        let resolution_graph = build_config
+            .resolution_graph_for_package(package_path, &mut Vec::new())
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        let named_address_values = resolution_graph
+            .extract_named_address_mapping()
+            .map(|(name, address)| {
+                (
+                    name.to_string(),
+                    NumericalAddress::new(address.into_bytes(), NumberFormat::Hex),
+                )
+            })
+            .collect();
+
+        // Source-mapped errors need the sources of the dependencies as well
+        let mut dep_files = FilesSourceText::new();
+        for package in resolution_graph.package_table.values() {
+            let sources = package
+                .get_sources(&resolution_graph.build_options)
+                .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+            for file_name in sources {
+                let contents = std::fs::read_to_string(file_name.as_str())
+                    .map_err(|err| CliError::IO(file_name.to_string(), err))?;
+                dep_files.insert(FileHash::new(&contents), (file_name, contents));
+            }
+        }
+
+        let root_package = resolution_graph.root_package.package.name;
+        let build_plan = BuildPlan::create(resolution_graph)
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        let mut compiled = None;
+        build_plan
+            .compile_with_driver(&mut Vec::new(), |compiler| {
+                let (files, comments_and_compiler_res) = compiler.run::<PASS_CFGIR>()?;
+                let (_, compiler) =
+                    diagnostics::unwrap_or_report_diagnostics(&files, comments_and_compiler_res);
+                let (mut compiler, cfgir) = compiler.into_ast();
+                let compilation_env = compiler.compilation_env();
+                let test_plan = construct_test_plan(compilation_env, Some(root_package), &cfgir);
+                if let Err(diags) =
+                    compilation_env.check_diags_at_or_above_severity(Severity::Warning)
+                {
+                    diagnostics::report_diagnostics(&files, diags);
+                }
+                let compilation_result = compiler.at_cfgir(cfgir).build();
+                let (units, _) =
+                    diagnostics::unwrap_or_report_diagnostics(&files, compilation_result);
+                compiled = Some((test_plan, files.clone(), units.clone()));
+                Ok((files, units))
+            })
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+
+        let (test_plan, mut files, units) =
+            compiled.expect("The compiler driver runs before compile_with_driver returns");
+        files.extend(dep_files);
+        let TestPlan {
+            files,
+            module_tests,
+            module_info,
+        } = TestPlan::new(test_plan.unwrap_or_default(), files, units);
+        Ok(CompiledTests {
+            files,
+            module_tests,
+            module_info,
+            named_address_values,
+        })
+    }
+
+    /// The modules that have unit tests
+    pub fn modules(&self) -> impl Iterator<Item = &ModuleId> {
+        self.module_tests.keys()
+    }
+
+    /// Runs the tests of all modules, or only those of `module`, writing the test runner's output
+    /// to `output`.  Returns whether all tests passed.
+    pub fn run<W: Write + Send>(
+        &self,
+        module: Option<&ModuleId>,
+        mut unit_testing_config: UnitTestingConfig,
+        natives: NativeFunctionTable,
+        output: W,
+    ) -> CliTypedResult<(W, bool)> {
+        let module_tests = self
+            .module_tests
+            .iter()
+            .filter(|(module_id, _)| module.map_or(true, |module| module == *module_id))
+            .map(|(module_id, tests)| (module_id.clone(), tests.clone()))
+            .collect();
+        let test_plan = TestPlan {
+            files: self.files.clone(),
+            module_tests,
+            module_info: self.module_info.clone(),
+        };
+
+        unit_testing_config.named_address_values = self.named_address_values.clone();
+        unit_testing_config
+            .run_and_report_unit_tests(test_plan, Some(natives), output)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))
+    }
+}
@@ -3,9 +3,10 @@
 
 use std::collections::HashMap;
 
+use crate::node::analyze::fetch_metadata::{EpochInfo, FetchMetadata};
 use anyhow::Result;
 use aptos_bitvec::BitVec;
-use aptos_rest_client::VersionedNewBlockEvent;
+use aptos_rest_client::{Client as RestClient, VersionedNewBlockEvent};
 use aptos_types::account_address::AccountAddress;
 use aptos_types::account_config::{new_block_event_key, NewBlockEvent};
 use itertools::Itertools;
@@ -166,6 +167,46 @@ impl Add for EpochStats {
     }
 }
 
+/// Stats of each analyzed epoch, and of all of them together
+#[derive(Clone)]
+pub struct AnalysisReport {
+    pub epoch_stats: HashMap<u64, EpochStats>,
+    /// Sum of the stats of all epochs, `None` if no epoch was analyzed
+    pub total_stats: Option<EpochStats>,
+}
+
+impl AnalysisReport {
+    pub fn from_epochs(epochs: Vec<EpochInfo>) -> Self {
+        let epoch_stats: HashMap<_, _> = epochs
+            .into_iter()
+            .map(|epoch_info| {
+                (
+                    epoch_info.epoch,
+                    AnalyzeValidators::analyze(epoch_info.blocks, &epoch_info.validators),
+                )
+            })
+            .collect();
+        let total_stats = epoch_stats.values().cloned().reduce(|a, b| a + b);
+        Self {
+            epoch_stats,
+            total_stats,
+        }
+    }
+}
+
+/// Fetches the blocks of the epochs from `start_epoch` to `end_epoch` and analyzes the
+/// performance of the validators in them
+///
+/// Nothing is printed to stdout, progress and inconsistencies in the blocks go to stderr.
+pub async fn analyze_performance(
+    client: &RestClient,
+    start_epoch: Option<u64>,
+    end_epoch: Option<u64>,
+) -> Result<AnalysisReport> {
+    let epochs = FetchMetadata::fetch_new_block_events(client, start_epoch, end_epoch).await?;
+    Ok(AnalysisReport::from_epochs(epochs))
+}
+
 /// Analyze validator performance
 pub struct AnalyzeValidators {}
 
@@ -251,7 +292,7 @@ impl AnalyzeValidators {
             if event.round() + (if is_nil { 1 } else { 0 })
                 != previous_round + 1 + event.failed_proposer_indices().len() as u64
             {
-                println!(
+                eprintln!(
                     "Missing failed AccountAddresss : {} {:?}",
                     previous_round, &event
                 );
@@ -481,3 +522,79 @@ impl AnalyzeValidators {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(
+        round: u64,
+        proposer: AccountAddress,
+        failed_proposer_indices: Vec<u64>,
+        version: u64,
+    ) -> VersionedNewBlockEvent {
+        let mut votes = BitVec::with_num_bits(2);
+        votes.set(0);
+        votes.set(1);
+        VersionedNewBlockEvent {
+            event: NewBlockEvent::new(
+                AccountAddress::ZERO,
+                1,
+                round,
+                round,
+                votes.into(),
+                proposer,
+                failed_proposer_indices,
+                round,
+            ),
+            version,
+            sequence_number: round,
+        }
+    }
+
+    #[test]
+    fn test_analysis_report_totals() {
+        let a = AccountAddress::new([1; AccountAddress::LENGTH]);
+        let b = AccountAddress::new([2; AccountAddress::LENGTH]);
+        let epoch_info = |epoch| EpochInfo {
+            epoch,
+            blocks: vec![block(1, a, vec![], 10), block(3, b, vec![0], 15)],
+            validators: vec![a, b],
+        };
+
+        let report = AnalysisReport::from_epochs(vec![epoch_info(2), epoch_info(3)]);
+        assert_eq!(report.epoch_stats.len(), 2);
+        assert_eq!(report.epoch_stats[&2].total_rounds, 3);
+
+        let total_stats = report.total_stats.unwrap();
+        assert_eq!(total_stats.total_rounds, 6);
+        assert_eq!(total_stats.round_successes, 4);
+        assert_eq!(total_stats.round_failures, 2);
+        assert_eq!(total_stats.total_transactions, 6);
+        assert_eq!(
+            total_stats.validator_stats[&a],
+            ValidatorStats {
+                proposal_successes: 2,
+                proposal_failures: 2,
+                votes: 4,
+                transactions: 6,
+            }
+        );
+        assert_eq!(
+            total_stats.validator_stats[&b],
+            ValidatorStats {
+                proposal_successes: 2,
+                proposal_failures: 0,
+                votes: 4,
+                transactions: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_analysis_report_empty() {
+        let report = AnalysisReport::from_epochs(vec![]);
+        assert!(report.epoch_stats.is_empty());
+        assert!(report.total_stats.is_none());
+    }
+}
@@ -1,12 +1,13 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use aptos_rest_client::{
     aptos_api_types::{IdentifierWrapper, MoveResource, WriteSetChange},
     Client as RestClient, Transaction, VersionedNewBlockEvent,
 };
 use aptos_types::account_address::AccountAddress;
+use serde::Serialize;
 use std::convert::TryFrom;
 use std::str::FromStr;
 
@@ -16,6 +17,17 @@ pub struct EpochInfo {
     pub validators: Vec<AccountAddress>,
 }
 
+/// Validators that joined and left the validator set when `epoch` started
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ValidatorSetChange {
+    pub epoch: u64,
+    pub added: Vec<AccountAddress>,
+    pub removed: Vec<AccountAddress>,
+}
+
+/// First epoch whose validator set is known, from the reconfiguration that started it
+pub const FIRST_EPOCH_WITH_VALIDATOR_SET: u64 = 2;
+
 pub struct FetchMetadata {}
 
 impl FetchMetadata {
@@ -83,11 +95,80 @@ impl FetchMetadata {
         Err(anyhow!("Couldn't find ValidatorSet in the transaction"))
     }
 
+    /// Diffs the validator sets of consecutive epochs, which must be sorted by epoch
+    pub fn validator_set_changes(epochs: &[EpochInfo]) -> Vec<ValidatorSetChange> {
+        epochs
+            .windows(2)
+            .map(|pair| {
+                let (previous, current) = (&pair[0], &pair[1]);
+                ValidatorSetChange {
+                    epoch: current.epoch,
+                    added: current
+                        .validators
+                        .iter()
+                        .filter(|validator| !previous.validators.contains(validator))
+                        .cloned()
+                        .collect(),
+                    removed: previous
+                        .validators
+                        .iter()
+                        .filter(|validator| !current.validators.contains(validator))
+                        .cloned()
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// The epochs to pass to [`FetchMetadata::fetch_new_block_events`] for the validator set
+    /// changes from `start_epoch` to `end_epoch`, both included
+    ///
+    /// The changes of an epoch are against the epoch before it, so they start after the first
+    /// epoch with a known validator set.
+    pub fn validator_set_history_range(
+        start_epoch: Option<u64>,
+        end_epoch: Option<u64>,
+    ) -> Result<(Option<u64>, Option<u64>)> {
+        if let Some(start_epoch) = start_epoch {
+            if start_epoch <= FIRST_EPOCH_WITH_VALIDATOR_SET {
+                bail!(
+                    "Validator set changes are only known from epoch {}, got start epoch {}",
+                    FIRST_EPOCH_WITH_VALIDATOR_SET + 1,
+                    start_epoch
+                );
+            }
+            if let Some(end_epoch) = end_epoch {
+                if end_epoch < start_epoch {
+                    bail!(
+                        "End epoch {} is before start epoch {}",
+                        end_epoch,
+                        start_epoch
+                    );
+                }
+            }
+        }
+        Ok((
+            start_epoch.map(|epoch| epoch - 1),
+            end_epoch.map(|epoch| epoch.saturating_add(1)),
+        ))
+    }
+
+    /// Fetches the blocks and validator sets of the epochs from `start_epoch`, which can't be
+    /// before [`FIRST_EPOCH_WITH_VALIDATOR_SET`], up to but not including `end_epoch`
     pub async fn fetch_new_block_events(
         client: &RestClient,
         start_epoch: Option<u64>,
         end_epoch: Option<u64>,
     ) -> Result<Vec<EpochInfo>> {
+        if let Some(start_epoch) = start_epoch {
+            if start_epoch < FIRST_EPOCH_WITH_VALIDATOR_SET {
+                bail!(
+                    "Validator sets are only known from epoch {}, got start epoch {}",
+                    FIRST_EPOCH_WITH_VALIDATOR_SET,
+                    start_epoch
+                );
+            }
+        }
         let mut start_seq_num = 0;
         let last_seq_num = client
             .get_new_block_events(None, Some(1))
@@ -129,7 +210,7 @@ impl FetchMetadata {
         let batch: u16 = 1000;
         let mut batch_index = 0;
 
-        println!(
+        eprintln!(
             "Fetching {} to {} sequence number",
             start_seq_num, last_seq_num
         );
@@ -141,12 +222,12 @@ impl FetchMetadata {
         let mut result: Vec<EpochInfo> = vec![];
 
         let mut cursor = start_seq_num;
-        let start_epoch = start_epoch.unwrap_or(2);
+        let start_epoch = start_epoch.unwrap_or(FIRST_EPOCH_WITH_VALIDATOR_SET);
         loop {
             let events = client.get_new_block_events(Some(cursor), Some(batch)).await;
 
             if events.is_err() {
-                println!(
+                eprintln!(
                     "Failed to read new_block_events beyond {}, stopping. {:?}",
                     cursor,
                     events.unwrap_err()
@@ -219,7 +300,7 @@ impl FetchMetadata {
             cursor += u64::from(batch);
             batch_index += 1;
             if batch_index % 100 == 0 {
-                println!(
+                eprintln!(
                     "Fetched {} epochs (in epoch {} with {} blocks) from {} transactions",
                     result.len(),
                     epoch,
@@ -234,3 +315,62 @@ impl FetchMetadata {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn epoch_info(epoch: u64, validators: &[u8]) -> EpochInfo {
+        EpochInfo {
+            epoch,
+            blocks: vec![],
+            validators: validators
+                .iter()
+                .map(|validator| AccountAddress::new([*validator; AccountAddress::LENGTH]))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_validator_set_history_range() {
+        // The validator sets of epochs 1 and 2 are needed for the changes of epoch 3
+        assert_eq!(
+            FetchMetadata::validator_set_history_range(None, None).unwrap(),
+            (None, None)
+        );
+        assert_eq!(
+            FetchMetadata::validator_set_history_range(Some(3), Some(5)).unwrap(),
+            (Some(2), Some(6))
+        );
+        assert_eq!(
+            FetchMetadata::validator_set_history_range(None, Some(u64::MAX)).unwrap(),
+            (None, Some(u64::MAX))
+        );
+
+        // Epochs 1 and 2 have no previous validator set to diff against
+        assert!(FetchMetadata::validator_set_history_range(Some(0), None).is_err());
+        assert!(FetchMetadata::validator_set_history_range(Some(1), None).is_err());
+        assert!(FetchMetadata::validator_set_history_range(Some(2), Some(5)).is_err());
+        assert!(FetchMetadata::validator_set_history_range(Some(5), Some(4)).is_err());
+    }
+
+    #[test]
+    fn test_validator_set_changes_from_first_epoch() {
+        // The first epoch has nothing to diff against, so the history starts at the second one
+        let epochs = vec![
+            epoch_info(FIRST_EPOCH_WITH_VALIDATOR_SET, &[1, 2, 3]),
+            epoch_info(FIRST_EPOCH_WITH_VALIDATOR_SET + 1, &[1, 3, 4]),
+        ];
+        let address = |validator| AccountAddress::new([validator; AccountAddress::LENGTH]);
+
+        assert_eq!(
+            FetchMetadata::validator_set_changes(&epochs),
+            vec![ValidatorSetChange {
+                epoch: FIRST_EPOCH_WITH_VALIDATOR_SET + 1,
+                added: vec![address(4)],
+                removed: vec![address(2)],
+            }]
+        );
+        assert!(FetchMetadata::validator_set_changes(&epochs[..1]).is_empty());
+    }
+}
@@ -0,0 +1,340 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions},
+    node::OperatorArgs,
+};
+use aptos_config::{
+    config::RoleType,
+    network_id::{NetworkContext, NetworkId},
+};
+use aptos_crypto::{x25519, Uniform, ValidCryptoMaterialStringExt};
+use aptos_genesis::config::HostAndPort;
+use aptos_rest_client::Client;
+use aptos_types::{
+    account_address::{from_identity_public_key, AccountAddress},
+    network_address::{NetworkAddress, Protocol},
+    validator_config::ValidatorConfig,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use network::noise::{AntiReplayTimestamps, HandshakeAuthMode, NoiseUpgrader};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Serialize;
+use std::{net::SocketAddr, time::Duration};
+use thiserror::Error;
+use tokio::{net::TcpStream, time::timeout};
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+const VALIDATOR_CONFIG_RESOURCE: &str = "0x1::stake::ValidatorConfig";
+
+/// Network addresses of a validator from its on-chain `ValidatorConfig`
+#[derive(Debug, Serialize)]
+pub struct ValidatorPeers {
+    pub pool_address: AccountAddress,
+    pub validator_network_addresses: Vec<NetworkAddress>,
+    pub fullnode_network_addresses: Vec<NetworkAddress>,
+}
+
+/// Show the network addresses of a validator
+///
+/// The addresses are stored on-chain BCS encoded, this decodes them into human readable
+/// multiaddrs, e.g. `/dns/validator.example.com/tcp/6180/noise-ik/0x.../handshake/0`.
+#[derive(Parser)]
+pub struct ShowPeers {
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) operator_args: OperatorArgs,
+}
+
+#[async_trait]
+impl CliCommand<ValidatorPeers> for ShowPeers {
+    fn command_name(&self) -> &'static str {
+        "ShowPeers"
+    }
+
+    async fn execute(self) -> CliTypedResult<ValidatorPeers> {
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        let pool_address = self
+            .operator_args
+            .address_fallback_to_profile(&self.profile_options)?;
+        fetch_validator_peers(&client, pool_address).await
+    }
+}
+
+/// Check that a validator's network addresses are reachable
+///
+/// Each address is dialed over TCP, and a Noise handshake is done with the public key in the
+/// address, the same way peers connect to the node.  By default the addresses come from the
+/// on-chain `ValidatorConfig` of the pool, use `--address` and `--public-key` to check a node
+/// before its addresses are on-chain.
+///
+/// Note, the validator network only completes handshakes with other validators, so checks of
+/// validator addresses from anywhere else fail the handshake even if the node is healthy.  A
+/// handshake failure there still means the address resolved and the port accepted connections.
+#[derive(Parser)]
+pub struct CheckConnectivity {
+    /// Host and port to check, instead of the on-chain addresses, e.g. `127.0.0.1:6182`
+    #[clap(long)]
+    pub(crate) address: Option<HostAndPort>,
+
+    /// x25519 public key of the node at `--address`
+    #[clap(long, parse(try_from_str = x25519::PublicKey::from_encoded_string))]
+    pub(crate) public_key: Option<x25519::PublicKey>,
+
+    /// Seconds to wait for each of the TCP connection and the handshake
+    #[clap(long, default_value_t = 5)]
+    pub(crate) timeout_secs: u64,
+
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) operator_args: OperatorArgs,
+}
+
+/// The outcome of checking one network address
+#[derive(Debug, Serialize)]
+pub struct ConnectivityCheck {
+    /// Which of the node's networks the address is for
+    pub network: String,
+    pub address: NetworkAddress,
+    pub reachable: bool,
+    pub error: Option<String>,
+}
+
+#[async_trait]
+impl CliCommand<Vec<ConnectivityCheck>> for CheckConnectivity {
+    fn command_name(&self) -> &'static str {
+        "CheckConnectivity"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<ConnectivityCheck>> {
+        let addresses = match (self.address, self.public_key) {
+            (Some(address), Some(public_key)) => {
+                let address = address
+                    .as_network_address(public_key)
+                    .map_err(|err| CliError::CommandArgumentError(err.to_string()))?;
+                vec![("address".to_string(), address)]
+            }
+            (None, None) => {
+                let client = self.rest_options.client(&self.profile_options.profile)?;
+                let pool_address = self
+                    .operator_args
+                    .address_fallback_to_profile(&self.profile_options)?;
+                let peers = fetch_validator_peers(&client, pool_address).await?;
+                peers
+                    .validator_network_addresses
+                    .into_iter()
+                    .map(|address| ("validator".to_string(), address))
+                    .chain(
+                        peers
+                            .fullnode_network_addresses
+                            .into_iter()
+                            .map(|address| ("fullnode".to_string(), address)),
+                    )
+                    .collect()
+            }
+            _ => {
+                return Err(CliError::CommandArgumentError(
+                    "--address and --public-key must be given together".to_string(),
+                ))
+            }
+        };
+
+        let timeout = Duration::from_secs(self.timeout_secs);
+        let mut checks = Vec::new();
+        for (network, address) in addresses {
+            let result = check_address(&address, timeout).await;
+            checks.push(ConnectivityCheck {
+                network,
+                address,
+                reachable: result.is_ok(),
+                error: result.err().map(|err| err.to_string()),
+            });
+        }
+        Ok(checks)
+    }
+}
+
+/// Why a network address couldn't be reached
+#[derive(Debug, Error)]
+pub enum ConnectivityError {
+    #[error("Address can't be dialed over TCP: {0}")]
+    UnsupportedAddress(String),
+    #[error("DNS lookup of {0} failed: {1}")]
+    Dns(String, String),
+    #[error("TCP connection timed out after {0} secs")]
+    TcpTimeout(u64),
+    #[error("TCP connection failed: {0}")]
+    Tcp(String),
+    #[error("Noise handshake timed out after {0} secs")]
+    HandshakeTimeout(u64),
+    #[error("Noise handshake failed: {0}")]
+    Handshake(String),
+}
+
+async fn fetch_validator_peers(
+    client: &Client,
+    pool_address: AccountAddress,
+) -> CliTypedResult<ValidatorPeers> {
+    let config: ValidatorConfig = client
+        .get_account_resource_bcs(pool_address, VALIDATOR_CONFIG_RESOURCE)
+        .await?
+        .into_inner();
+    Ok(ValidatorPeers {
+        pool_address,
+        validator_network_addresses: config
+            .validator_network_addresses()
+            .map_err(|err| CliError::BCS("validator_network_addresses", err))?,
+        fullnode_network_addresses: config
+            .fullnode_network_addresses()
+            .map_err(|err| CliError::BCS("fullnode_network_addresses", err))?,
+    })
+}
+
+/// Dials `address` and does a Noise handshake with the public key in it, as a peer with a
+/// random identity
+pub async fn check_address(
+    address: &NetworkAddress,
+    timeout_duration: Duration,
+) -> Result<(), ConnectivityError> {
+    let timeout_secs = timeout_duration.as_secs();
+    let remote_public_key = address.find_noise_proto().ok_or_else(|| {
+        ConnectivityError::UnsupportedAddress(format!("{} has no noise-ik public key", address))
+    })?;
+    let socket_addrs = resolve(address).await?;
+
+    let mut connect_error = ConnectivityError::Tcp(format!("{} resolved to nothing", address));
+    for socket_addr in socket_addrs {
+        let socket = match timeout(timeout_duration, TcpStream::connect(socket_addr)).await {
+            Ok(Ok(socket)) => socket,
+            Ok(Err(err)) => {
+                connect_error = ConnectivityError::Tcp(format!("{}: {}", socket_addr, err));
+                continue;
+            }
+            Err(_) => {
+                connect_error = ConnectivityError::TcpTimeout(timeout_secs);
+                continue;
+            }
+        };
+
+        let mut rng = StdRng::from_entropy();
+        let private_key = x25519::PrivateKey::generate(&mut rng);
+        let peer_id = from_identity_public_key(private_key.public_key());
+        let upgrader = NoiseUpgrader::new(
+            NetworkContext::new(RoleType::FullNode, NetworkId::Public, peer_id),
+            private_key,
+            HandshakeAuthMode::server_only(),
+        );
+        return match timeout(
+            timeout_duration,
+            upgrader.upgrade_outbound(
+                socket.compat(),
+                remote_public_key,
+                AntiReplayTimestamps::now,
+            ),
+        )
+        .await
+        {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(err)) => Err(ConnectivityError::Handshake(err.to_string())),
+            Err(_) => Err(ConnectivityError::HandshakeTimeout(timeout_secs)),
+        };
+    }
+    Err(connect_error)
+}
+
+/// Resolves the host and TCP port at the start of `address`
+async fn resolve(address: &NetworkAddress) -> Result<Vec<SocketAddr>, ConnectivityError> {
+    let unsupported = || ConnectivityError::UnsupportedAddress(address.to_string());
+    let (host, port) = match address.as_slice() {
+        [host, Protocol::Tcp(port), ..] => (host, *port),
+        _ => return Err(unsupported()),
+    };
+    match host {
+        Protocol::Ip4(ip) => Ok(vec![SocketAddr::new((*ip).into(), port)]),
+        Protocol::Ip6(ip) => Ok(vec![SocketAddr::new((*ip).into(), port)]),
+        Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name) => {
+            let name = name.to_string();
+            let socket_addrs = tokio::net::lookup_host((name.as_str(), port))
+                .await
+                .map_err(|err| ConnectivityError::Dns(name.clone(), err.to_string()))?;
+            Ok(socket_addrs
+                .filter(|socket_addr| match host {
+                    Protocol::Dns4(_) => socket_addr.is_ipv4(),
+                    Protocol::Dns6(_) => socket_addr.is_ipv6(),
+                    _ => true,
+                })
+                .collect())
+        }
+        _ => Err(unsupported()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Starts a node that accepts a single Noise handshake, returning its address
+    async fn noise_server(private_key: x25519::PrivateKey) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let socket_addr = listener.local_addr().unwrap();
+        let peer_id = from_identity_public_key(private_key.public_key());
+        let upgrader = NoiseUpgrader::new(
+            NetworkContext::new(RoleType::Validator, NetworkId::Public, peer_id),
+            private_key,
+            HandshakeAuthMode::server_only(),
+        );
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let _ = upgrader.upgrade_inbound(socket.compat()).await;
+        });
+        socket_addr
+    }
+
+    fn address(socket_addr: SocketAddr, public_key: x25519::PublicKey) -> NetworkAddress {
+        NetworkAddress::from(socket_addr).append_prod_protos(public_key, 0)
+    }
+
+    #[tokio::test]
+    async fn test_check_address() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let timeout = Duration::from_secs(5);
+
+        let private_key = x25519::PrivateKey::generate(&mut rng);
+        let public_key = private_key.public_key();
+        let socket_addr = noise_server(private_key).await;
+        check_address(&address(socket_addr, public_key), timeout)
+            .await
+            .unwrap();
+
+        // The node doesn't have the key the address says it has
+        let socket_addr = noise_server(x25519::PrivateKey::generate(&mut rng)).await;
+        assert!(matches!(
+            check_address(&address(socket_addr, public_key), timeout).await,
+            Err(ConnectivityError::Handshake(_))
+        ));
+
+        // Nothing is listening anymore
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let socket_addr = listener.local_addr().unwrap();
+        drop(listener);
+        assert!(matches!(
+            check_address(&address(socket_addr, public_key), timeout).await,
+            Err(ConnectivityError::Tcp(_))
+        ));
+
+        // Without a public key there is nothing to handshake with
+        assert!(matches!(
+            check_address(&NetworkAddress::from(socket_addr), timeout).await,
+            Err(ConnectivityError::UnsupportedAddress(_))
+        ));
+    }
+}
@@ -2,41 +2,66 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod analyze;
+pub mod connectivity;
 
 use crate::common::types::{
     ConfigSearchMode, OptionalPoolAddressArgs, PromptOptions, TransactionSummary,
 };
 use crate::common::utils::prompt_yes_with_override;
 use crate::config::GlobalConfig;
-use crate::node::analyze::analyze_validators::AnalyzeValidators;
-use crate::node::analyze::fetch_metadata::FetchMetadata;
+use crate::node::analyze::analyze_validators::{analyze_performance, AnalyzeValidators};
+use crate::node::analyze::fetch_metadata::{FetchMetadata, ValidatorSetChange};
+use crate::node::connectivity::{CheckConnectivity, ShowPeers};
 use crate::{
     common::{
         types::{
             CliCommand, CliError, CliResult, CliTypedResult, ProfileOptions, RestOptions,
             TransactionOptions,
         },
-        utils::read_from_file,
+        utils::{read_from_file, write_to_file},
     },
     genesis::git::from_yaml,
 };
-use aptos_config::config::NodeConfig;
-use aptos_crypto::{bls12381, x25519, ValidCryptoMaterialStringExt};
-use aptos_faucet::FaucetArgs;
+use aptos_config::config::{
+    NodeConfig, RocksdbConfigs, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG, TARGET_SNAPSHOT_SIZE,
+};
+use aptos_crypto::{
+    bls12381, ed25519::Ed25519PrivateKey, x25519, HashValue, ValidCryptoMaterialStringExt,
+};
+use aptos_faucet::{
+    mint::{self, MintParams},
+    FaucetArgs, Service,
+};
 use aptos_genesis::config::{HostAndPort, OperatorConfiguration};
+use aptos_keygen::KeyGen;
+use aptos_rest_client::Client;
+use aptos_sdk::types::LocalAccount;
 use aptos_types::chain_id::ChainId;
-use aptos_types::{account_address::AccountAddress, account_config::CORE_CODE_ADDRESS};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{aptos_test_root_address, BlockResource, CORE_CODE_ADDRESS},
+    on_chain_config::ConfigurationResource,
+    transaction::authenticator::AuthenticationKey,
+    validator_config::ValidatorConfig,
+};
+use aptosdb::{AptosDB, CompactionOpts};
 use async_trait::async_trait;
 use cached_packages::aptos_stdlib;
 use clap::Parser;
 use hex::FromHex;
+use itertools::Itertools;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 use reqwest::Url;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::{path::PathBuf, thread, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
 use tokio::time::Instant;
 
 /// Tool for operations related to nodes
@@ -50,11 +75,15 @@ pub enum NodeTool {
     LeaveValidatorSet(LeaveValidatorSet),
     ShowValidatorConfig(ShowValidatorConfig),
     ShowValidatorSet(ShowValidatorSet),
+    ShowValidatorSetHistory(ShowValidatorSetHistory),
     ShowValidatorStake(ShowValidatorStake),
+    ShowPeers(ShowPeers),
+    CheckConnectivity(CheckConnectivity),
     RunLocalTestnet(RunLocalTestnet),
     UpdateConsensusKey(UpdateConsensusKey),
     UpdateValidatorNetworkAddresses(UpdateValidatorNetworkAddresses),
     AnalyzeValidatorPerformance(AnalyzeValidatorPerformance),
+    CompactStateDb(CompactStateDb),
 }
 
 impl NodeTool {
@@ -65,12 +94,16 @@ impl NodeTool {
             JoinValidatorSet(tool) => tool.execute_serialized().await,
             LeaveValidatorSet(tool) => tool.execute_serialized().await,
             ShowValidatorSet(tool) => tool.execute_serialized().await,
+            ShowValidatorSetHistory(tool) => tool.execute_serialized().await,
             ShowValidatorStake(tool) => tool.execute_serialized().await,
             ShowValidatorConfig(tool) => tool.execute_serialized().await,
+            ShowPeers(tool) => tool.execute_serialized().await,
+            CheckConnectivity(tool) => tool.execute_serialized().await,
             RunLocalTestnet(tool) => tool.execute_serialized_without_logger().await,
             UpdateConsensusKey(tool) => tool.execute_serialized().await,
             UpdateValidatorNetworkAddresses(tool) => tool.execute_serialized().await,
             AnalyzeValidatorPerformance(tool) => tool.execute_serialized().await,
+            CompactStateDb(tool) => tool.execute_serialized_success().await,
         }
     }
 }
@@ -138,11 +171,30 @@ impl ValidatorConsensusKeyArgs {
         };
         Ok(proof_of_possession)
     }
+
+    /// Retrieves the consensus public key and its proof of possession, checking that they match
+    /// so that a mismatch doesn't fail on-chain
+    fn get_consensus_key_and_proof_of_possession<'a>(
+        &'a self,
+        operator_config: &'a Option<OperatorConfiguration>,
+    ) -> CliTypedResult<(&'a bls12381::PublicKey, &'a bls12381::ProofOfPossession)> {
+        let consensus_public_key = self.get_consensus_public_key(operator_config)?;
+        let proof_of_possession = self.get_consensus_proof_of_possession(operator_config)?;
+        proof_of_possession
+            .verify(consensus_public_key)
+            .map_err(|_| {
+                CliError::CommandArgumentError(
+                    "Proof of possession doesn't match the consensus public key".to_string(),
+                )
+            })?;
+        Ok((consensus_public_key, proof_of_possession))
+    }
 }
 
 #[derive(Parser)]
 pub struct ValidatorNetworkAddressesArgs {
-    /// Host and port pair for the validator e.g. 127.0.0.1:6180
+    /// Host and port pair for the validator e.g. 127.0.0.1:6180, [2001:db8::1]:6180 or
+    /// validator.example.com:6180
     #[clap(long)]
     pub(crate) validator_host: Option<HostAndPort>,
 
@@ -150,7 +202,8 @@ pub struct ValidatorNetworkAddressesArgs {
     #[clap(long, parse(try_from_str = x25519::PublicKey::from_encoded_string))]
     pub(crate) validator_network_public_key: Option<x25519::PublicKey>,
 
-    /// Host and port pair for the fullnode e.g. 127.0.0.1:6180.  Optional
+    /// Host and port pair for the fullnode e.g. 127.0.0.1:6180, [2001:db8::1]:6180 or
+    /// fullnode.example.com:6180.  Optional
     #[clap(long)]
     pub(crate) full_node_host: Option<HostAndPort>,
 
@@ -241,12 +294,9 @@ impl CliCommand<TransactionSummary> for InitializeValidator {
 
     async fn execute(mut self) -> CliTypedResult<TransactionSummary> {
         let operator_config = self.operator_config_file_args.load()?;
-        let consensus_public_key = self
+        let (consensus_public_key, consensus_proof_of_possession) = self
             .validator_consensus_key_args
-            .get_consensus_public_key(&operator_config)?;
-        let consensus_proof_of_possession = self
-            .validator_consensus_key_args
-            .get_consensus_proof_of_possession(&operator_config)?;
+            .get_consensus_key_and_proof_of_possession(&operator_config)?;
         let (
             validator_network_public_key,
             full_node_network_public_key,
@@ -290,7 +340,7 @@ pub struct OperatorArgs {
 }
 
 impl OperatorArgs {
-    fn address_fallback_to_profile(
+    pub(crate) fn address_fallback_to_profile(
         &self,
         profile_options: &ProfileOptions,
     ) -> CliTypedResult<AccountAddress> {
@@ -449,9 +499,57 @@ impl CliCommand<serde_json::Value> for ShowValidatorSet {
     }
 }
 
+/// Show validators joining and leaving the validator set across a range of epochs
+#[derive(Parser)]
+pub struct ShowValidatorSetHistory {
+    /// First epoch to show the changes of, they are known from epoch 3 on
+    #[clap(long)]
+    pub start_epoch: Option<u64>,
+
+    /// Last epoch to show the changes of
+    #[clap(long)]
+    pub end_epoch: Option<u64>,
+
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<ValidatorSetChange>> for ShowValidatorSetHistory {
+    fn command_name(&self) -> &'static str {
+        "ShowValidatorSetHistory"
+    }
+
+    async fn execute(mut self) -> CliTypedResult<Vec<ValidatorSetChange>> {
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+
+        let (start_epoch, end_epoch) =
+            FetchMetadata::validator_set_history_range(self.start_epoch, self.end_epoch)
+                .map_err(|err| CliError::CommandArgumentError(err.to_string()))?;
+        let epochs = FetchMetadata::fetch_new_block_events(&client, start_epoch, end_epoch).await?;
+        let changes = FetchMetadata::validator_set_changes(&epochs);
+        for change in &changes {
+            eprintln!(
+                "Epoch {}: {} added {:?}, {} removed {:?}",
+                change.epoch,
+                change.added.len(),
+                change.added,
+                change.removed.len(),
+                change.removed
+            );
+        }
+        Ok(changes)
+    }
+}
+
 const MAX_WAIT_S: u64 = 30;
 const WAIT_INTERVAL_MS: u64 = 100;
 const TESTNET_FOLDER: &str = "testnet";
+pub const TEST_ACCOUNTS_FILE: &str = "test_accounts.yaml";
+/// Seed for the test accounts when no `--seed` is given, so they stay stable across runs
+const DEFAULT_TEST_ACCOUNTS_SEED: [u8; 32] = [0u8; 32];
 
 /// Run local testnet
 ///
@@ -461,34 +559,45 @@ const TESTNET_FOLDER: &str = "testnet";
 pub struct RunLocalTestnet {
     /// An overridable config template for the test node
     #[clap(long, parse(from_os_str))]
-    config_path: Option<PathBuf>,
+    pub(crate) config_path: Option<PathBuf>,
 
     /// The directory to save all files for the node
     #[clap(long, parse(from_os_str))]
-    test_dir: Option<PathBuf>,
+    pub(crate) test_dir: Option<PathBuf>,
 
     /// Random seed for key generation in test mode
     #[clap(long, parse(try_from_str = FromHex::from_hex))]
-    seed: Option<[u8; 32]>,
+    pub(crate) seed: Option<[u8; 32]>,
 
     /// Clean the state and start with a new chain at genesis
     #[clap(long)]
-    force_restart: bool,
+    pub(crate) force_restart: bool,
 
     /// Run a faucet alongside the node
     #[clap(long)]
-    with_faucet: bool,
+    pub(crate) with_faucet: bool,
 
     /// Port to run the faucet on
     #[clap(long, default_value = "8081")]
-    faucet_port: u16,
+    pub(crate) faucet_port: u16,
 
     #[clap(flatten)]
-    prompt_options: PromptOptions,
+    pub(crate) prompt_options: PromptOptions,
 
     /// Disable the delegation of minting to a dedicated account
     #[clap(long)]
-    do_not_delegate: bool,
+    pub(crate) do_not_delegate: bool,
+
+    /// Number of pre-funded test accounts to create at startup
+    ///
+    /// The accounts are derived from the seed, so they are the same across restarts.
+    /// Their keys are printed and saved to `test_accounts.yaml` in the test directory.
+    #[clap(long, default_value = "0")]
+    pub(crate) test_accounts: usize,
+
+    /// Amount of Octas to fund each test account with
+    #[clap(long, default_value = "100000000")]
+    pub(crate) test_account_balance: u64,
 }
 
 #[async_trait]
@@ -503,10 +612,12 @@ impl CliCommand<()> for RunLocalTestnet {
             .map(StdRng::from_seed)
             .unwrap_or_else(StdRng::from_entropy);
 
-        let global_config = GlobalConfig::load()?;
-        let test_dir = global_config
-            .get_config_location(ConfigSearchMode::CurrentDirAndParents)?
-            .join(TESTNET_FOLDER);
+        let test_dir = match self.test_dir.take() {
+            Some(test_dir) => test_dir,
+            None => GlobalConfig::load()?
+                .get_config_location(ConfigSearchMode::CurrentDirAndParents)?
+                .join(TESTNET_FOLDER),
+        };
 
         // Remove the current test directory and start with a new node
         if self.force_restart && test_dir.exists() {
@@ -534,74 +645,44 @@ impl CliCommand<()> for RunLocalTestnet {
             .map_err(|err| CliError::UnexpectedError(format!("Node failed to run {}", err)))
         });
 
-        // Run faucet if selected
-        let _maybe_faucet = if self.with_faucet {
-            let max_wait = Duration::from_secs(MAX_WAIT_S);
-            let wait_interval = Duration::from_millis(WAIT_INTERVAL_MS);
-
-            // Load the config to get the rest port
-            let config_path = test_dir.join("0").join("node.yaml");
-
-            // We have to wait for the node to be configured above in the other thread
-            let mut config = None;
-            let start = Instant::now();
-            while start.elapsed() < max_wait {
-                if let Ok(loaded_config) = NodeConfig::load(&config_path) {
-                    config = Some(loaded_config);
-                    break;
-                }
-                tokio::time::sleep(wait_interval).await;
+        if self.with_faucet || self.test_accounts > 0 {
+            let rest_url = wait_for_rest_api(&test_dir).await?;
+
+            if self.test_accounts > 0 {
+                let seed = self.seed.unwrap_or(DEFAULT_TEST_ACCOUNTS_SEED);
+                let accounts = fund_test_accounts(
+                    rest_url.clone(),
+                    &test_dir,
+                    seed,
+                    self.test_accounts,
+                    self.test_account_balance,
+                )
+                .await?;
+                print_test_accounts(&accounts);
+                write_to_file(
+                    test_dir.join(TEST_ACCOUNTS_FILE).as_path(),
+                    TEST_ACCOUNTS_FILE,
+                    serde_yaml::to_string(&accounts)?.as_bytes(),
+                )?;
             }
 
-            // Retrieve the port from the local node
-            let port = if let Some(config) = config {
-                config.api.address.port()
-            } else {
-                return Err(CliError::UnexpectedError(
-                    "Failed to find node configuration to start faucet".to_string(),
-                ));
-            };
-
-            // Check that the REST API is ready
-            let rest_url = Url::parse(&format!("http://localhost:{}", port)).map_err(|err| {
-                CliError::UnexpectedError(format!("Failed to parse localhost URL {}", err))
-            })?;
-            let rest_client = aptos_rest_client::Client::new(rest_url.clone());
-            let start = Instant::now();
-            let mut started_successfully = false;
-
-            while start.elapsed() < max_wait {
-                if rest_client.get_index().await.is_ok() {
-                    started_successfully = true;
-                    break;
+            // Run faucet if selected
+            if self.with_faucet {
+                FaucetArgs {
+                    address: "0.0.0.0".to_string(),
+                    port: self.faucet_port,
+                    server_url: rest_url,
+                    mint_key_file_path: test_dir.join("mint.key"),
+                    mint_key: None,
+                    mint_account_address: None,
+                    chain_id: ChainId::test(),
+                    maximum_amount: None,
+                    do_not_delegate: self.do_not_delegate,
                 }
-                tokio::time::sleep(wait_interval).await
+                .run()
+                .await;
             }
-
-            if !started_successfully {
-                return Err(CliError::UnexpectedError(
-                    "Failed to startup local node before faucet".to_string(),
-                ));
-            }
-
-            // Start the faucet
-            FaucetArgs {
-                address: "0.0.0.0".to_string(),
-                port: self.faucet_port,
-                server_url: rest_url,
-                mint_key_file_path: test_dir.join("mint.key"),
-                mint_key: None,
-                mint_account_address: None,
-                chain_id: ChainId::test(),
-                maximum_amount: None,
-                do_not_delegate: self.do_not_delegate,
-            }
-            .run()
-            .await;
-            Some(())
-        } else {
-            None
-        };
+        }
 
         // Wait for an interrupt
         let term = Arc::new(AtomicBool::new(false));
@@ -612,6 +693,165 @@ impl CliCommand<()> for RunLocalTestnet {
     }
 }
 
+/// Waits for the local node to write its config and serve its REST API, returning the API URL
+async fn wait_for_rest_api(test_dir: &Path) -> CliTypedResult<Url> {
+    let max_wait = Duration::from_secs(MAX_WAIT_S);
+    let wait_interval = Duration::from_millis(WAIT_INTERVAL_MS);
+
+    // Load the config to get the rest port
+    let config_path = test_dir.join("0").join("node.yaml");
+
+    // We have to wait for the node to be configured in the other thread
+    let mut config = None;
+    let start = Instant::now();
+    while start.elapsed() < max_wait {
+        if let Ok(loaded_config) = NodeConfig::load(&config_path) {
+            config = Some(loaded_config);
+            break;
+        }
+        tokio::time::sleep(wait_interval).await;
+    }
+
+    // Retrieve the port from the local node
+    let port = if let Some(config) = config {
+        config.api.address.port()
+    } else {
+        return Err(CliError::UnexpectedError(
+            "Failed to find node configuration of local node".to_string(),
+        ));
+    };
+
+    // Check that the REST API is ready
+    let rest_url = Url::parse(&format!("http://localhost:{}", port)).map_err(|err| {
+        CliError::UnexpectedError(format!("Failed to parse localhost URL {}", err))
+    })?;
+    let rest_client = aptos_rest_client::Client::new(rest_url.clone());
+    let start = Instant::now();
+    while start.elapsed() < max_wait {
+        if rest_client.get_index().await.is_ok() {
+            return Ok(rest_url);
+        }
+        tokio::time::sleep(wait_interval).await
+    }
+
+    Err(CliError::UnexpectedError(
+        "Failed to startup local node".to_string(),
+    ))
+}
+
+/// A pre-funded account of the local testnet
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestAccount {
+    pub address: AccountAddress,
+    pub private_key: String,
+    pub balance: u64,
+}
+
+/// Creates and funds `count` accounts derived from `seed` with the root key of the testnet
+///
+/// Accounts that already exist on chain are reused as is, so restarting an existing
+/// testnet doesn't fund them again.
+async fn fund_test_accounts(
+    rest_url: Url,
+    test_dir: &Path,
+    seed: [u8; 32],
+    count: usize,
+    balance: u64,
+) -> CliTypedResult<Vec<TestAccount>> {
+    let root_key: Ed25519PrivateKey =
+        bcs::from_bytes(&read_from_file(test_dir.join("mint.key").as_path())?)
+            .map_err(|err| CliError::BCS("mint.key", err))?;
+    let client = aptos_rest_client::Client::new(rest_url.clone());
+    let root_sequence_number = client
+        .get_account(aptos_test_root_address())
+        .await
+        .map_err(|err| CliError::ApiError(err.to_string()))?
+        .into_inner()
+        .sequence_number;
+    let service = Service::new(
+        rest_url,
+        ChainId::test(),
+        LocalAccount::new(aptos_test_root_address(), root_key, root_sequence_number),
+        None,
+    );
+
+    // Keep the test accounts separate from the validator keys generated from the same seed
+    let mut keygen = KeyGen::from_seed(*HashValue::sha3_256_of(
+        &[&seed[..], b"test_accounts"].concat(),
+    ));
+    let mut accounts = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (private_key, public_key) = keygen.generate_ed25519_keypair();
+        let address = AuthenticationKey::ed25519(&public_key).derived_address();
+
+        let exists = match client.get_account(address).await {
+            Ok(_) => true,
+            Err(err)
+                if err
+                    .downcast_ref::<aptos_rest_client::error::Error>()
+                    .map_or(false, aptos_rest_client::error::Error::is_account_not_found) =>
+            {
+                false
+            }
+            Err(err) => return Err(CliError::ApiError(err.to_string())),
+        };
+        if !exists {
+            let response = mint::process(
+                &service,
+                MintParams {
+                    amount: balance,
+                    auth_key: None,
+                    address: Some(address.to_hex_literal()),
+                    pub_key: None,
+                    return_txns: Some(true),
+                },
+            )
+            .await
+            .map_err(|err| {
+                CliError::ApiError(format!("Failed to fund test account {}: {}", address, err))
+            })?;
+            if let mint::Response::SubmittedTxns(txns) = response {
+                for txn in txns {
+                    client
+                        .wait_for_signed_transaction(&txn)
+                        .await
+                        .map_err(|err| CliError::ApiError(err.to_string()))?;
+                }
+            }
+        }
+
+        let balance = client
+            .get_account_balance(address)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_inner()
+            .get();
+        accounts.push(TestAccount {
+            address,
+            private_key: private_key.to_encoded_string()?,
+            balance,
+        });
+    }
+    Ok(accounts)
+}
+
+fn print_test_accounts(accounts: &[TestAccount]) {
+    eprintln!();
+    eprintln!("WARNING: The following test accounts are for local testing ONLY.");
+    eprintln!("WARNING: Their private keys are public. Never use them on a real network!");
+    eprintln!();
+    eprintln!("{:<68} {:<68} {:>20}", "Address", "Private key", "Balance");
+    for account in accounts {
+        eprintln!(
+            "{:<68} {:<68} {:>20}",
+            account.address.to_hex_literal(),
+            account.private_key,
+            account.balance
+        );
+    }
+    eprintln!();
+}
+
 /// Update consensus key for the validator node.
 #[derive(Parser)]
 pub struct UpdateConsensusKey {
@@ -623,34 +863,45 @@ pub struct UpdateConsensusKey {
     pub(crate) operator_config_file_args: OperatorConfigFileArgs,
     #[clap(flatten)]
     pub(crate) validator_consensus_key_args: ValidatorConsensusKeyArgs,
+    /// Skip reading back the pool's `ValidatorConfig` after the transaction commits
+    #[clap(long)]
+    pub(crate) no_verify: bool,
 }
 
 #[async_trait]
-impl CliCommand<TransactionSummary> for UpdateConsensusKey {
+impl CliCommand<ValidatorConfigUpdateSummary> for UpdateConsensusKey {
     fn command_name(&self) -> &'static str {
         "UpdateConsensusKey"
     }
 
-    async fn execute(mut self) -> CliTypedResult<TransactionSummary> {
+    async fn execute(mut self) -> CliTypedResult<ValidatorConfigUpdateSummary> {
         let address = self
             .operator_args
             .address_fallback_to_txn(&self.txn_options)?;
 
         let operator_config = self.operator_config_file_args.load()?;
-        let consensus_public_key = self
+        let (consensus_public_key, consensus_proof_of_possession) = self
             .validator_consensus_key_args
-            .get_consensus_public_key(&operator_config)?;
-        let consensus_proof_of_possession = self
-            .validator_consensus_key_args
-            .get_consensus_proof_of_possession(&operator_config)?;
-        self.txn_options
+            .get_consensus_key_and_proof_of_possession(&operator_config)?;
+        let transaction: TransactionSummary = self
+            .txn_options
             .submit_transaction(aptos_stdlib::stake_rotate_consensus_key(
                 address,
                 consensus_public_key.to_bytes().to_vec(),
                 consensus_proof_of_possession.to_bytes().to_vec(),
             ))
-            .await
-            .map(|inner| inner.into())
+            .await?
+            .into();
+
+        let submitted = SubmittedValidatorConfig::ConsensusKey(consensus_public_key.clone());
+        ValidatorConfigUpdateSummary::new(
+            &self.txn_options,
+            address,
+            transaction,
+            submitted,
+            self.no_verify,
+        )
+        .await
     }
 }
 
@@ -665,15 +916,18 @@ pub struct UpdateValidatorNetworkAddresses {
     pub(crate) operator_config_file_args: OperatorConfigFileArgs,
     #[clap(flatten)]
     pub(crate) validator_network_addresses_args: ValidatorNetworkAddressesArgs,
+    /// Skip reading back the pool's `ValidatorConfig` after the transaction commits
+    #[clap(long)]
+    pub(crate) no_verify: bool,
 }
 
 #[async_trait]
-impl CliCommand<TransactionSummary> for UpdateValidatorNetworkAddresses {
+impl CliCommand<ValidatorConfigUpdateSummary> for UpdateValidatorNetworkAddresses {
     fn command_name(&self) -> &'static str {
         "UpdateValidatorNetworkAddresses"
     }
 
-    async fn execute(mut self) -> CliTypedResult<TransactionSummary> {
+    async fn execute(mut self) -> CliTypedResult<ValidatorConfigUpdateSummary> {
         let address = self
             .operator_args
             .address_fallback_to_txn(&self.txn_options)?;
@@ -701,18 +955,170 @@ impl CliCommand<TransactionSummary> for UpdateValidatorNetworkAddresses {
                 }
             };
 
-        self.txn_options
+        // BCS encode, so that we can hide the original type
+        let validator_network_addresses = bcs::to_bytes(&validator_network_addresses)?;
+        let full_node_network_addresses = bcs::to_bytes(&full_node_network_addresses)?;
+        let transaction: TransactionSummary = self
+            .txn_options
             .submit_transaction(aptos_stdlib::stake_update_network_and_fullnode_addresses(
                 address,
-                // BCS encode, so that we can hide the original type
-                bcs::to_bytes(&validator_network_addresses)?,
-                bcs::to_bytes(&full_node_network_addresses)?,
+                validator_network_addresses.clone(),
+                full_node_network_addresses.clone(),
             ))
-            .await
-            .map(|inner| inner.into())
+            .await?
+            .into();
+
+        let submitted = SubmittedValidatorConfig::NetworkAddresses {
+            validator_network_addresses,
+            full_node_network_addresses,
+        };
+        ValidatorConfigUpdateSummary::new(
+            &self.txn_options,
+            address,
+            transaction,
+            submitted,
+            self.no_verify,
+        )
+        .await
+    }
+}
+
+/// What an update of a pool's `ValidatorConfig` submitted, to be compared with the on-chain one
+enum SubmittedValidatorConfig {
+    ConsensusKey(bls12381::PublicKey),
+    /// BCS encoded `Vec<NetworkAddress>`es, as stored on-chain
+    NetworkAddresses {
+        validator_network_addresses: Vec<u8>,
+        full_node_network_addresses: Vec<u8>,
+    },
+}
+
+impl SubmittedValidatorConfig {
+    /// Names of the fields of `config` which don't have the submitted values
+    fn mismatched_fields(&self, config: &ValidatorConfig) -> Vec<&'static str> {
+        let mut mismatches = vec![];
+        match self {
+            SubmittedValidatorConfig::ConsensusKey(consensus_public_key) => {
+                if &config.consensus_public_key != consensus_public_key {
+                    mismatches.push("consensus_public_key");
+                }
+            }
+            SubmittedValidatorConfig::NetworkAddresses {
+                validator_network_addresses,
+                full_node_network_addresses,
+            } => {
+                if &config.validator_network_addresses != validator_network_addresses {
+                    mismatches.push("validator_network_addresses");
+                }
+                if &config.fullnode_network_addresses != full_node_network_addresses {
+                    mismatches.push("fullnode_network_addresses");
+                }
+            }
+        }
+        mismatches
+    }
+}
+
+/// The transaction updating a pool's `ValidatorConfig`, and the config read back after it
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidatorConfigUpdateSummary {
+    #[serde(flatten)]
+    pub transaction: TransactionSummary,
+    /// `None` with `--no-verify`, or if the transaction failed
+    pub post_state: Option<ValidatorConfigPostState>,
+}
+
+/// The on-chain `ValidatorConfig` of a pool after an update
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidatorConfigPostState {
+    pub pool_address: AccountAddress,
+    /// Whether the pool's pending `ValidatorConfig` has the submitted values
+    pub matches_submitted: bool,
+    /// Fields of the `ValidatorConfig` which don't have the submitted values
+    pub mismatched_fields: Vec<&'static str>,
+    /// The epoch the ledger was in when the config was read
+    pub current_epoch: u64,
+    /// The epoch from which the validator set uses the updated config
+    pub effective_epoch: u64,
+    /// When the current epoch is due to end, in microseconds since the Unix epoch
+    pub next_epoch_timestamp_usecs: u64,
+    pub message: String,
+}
+
+impl ValidatorConfigUpdateSummary {
+    async fn new(
+        txn_options: &TransactionOptions,
+        pool_address: AccountAddress,
+        transaction: TransactionSummary,
+        submitted: SubmittedValidatorConfig,
+        no_verify: bool,
+    ) -> CliTypedResult<Self> {
+        let post_state = if no_verify || transaction.success != Some(true) {
+            None
+        } else {
+            let client = txn_options.rest_client()?;
+            Some(validator_config_post_state(&client, pool_address, &submitted).await?)
+        };
+        Ok(Self {
+            transaction,
+            post_state,
+        })
     }
 }
 
+/// Reads back the `ValidatorConfig` of `pool_address`, and when the next epoch, where the
+/// validator set picks it up, starts
+async fn validator_config_post_state(
+    client: &Client,
+    pool_address: AccountAddress,
+    submitted: &SubmittedValidatorConfig,
+) -> CliTypedResult<ValidatorConfigPostState> {
+    let (config, state) = client
+        .get_account_resource_bcs::<ValidatorConfig>(pool_address, "0x1::stake::ValidatorConfig")
+        .await?
+        .into_parts();
+    let configuration: ConfigurationResource = client
+        .get_account_resource_bcs(CORE_CODE_ADDRESS, "0x1::reconfiguration::Configuration")
+        .await?
+        .into_inner();
+    let block_resource: BlockResource = client
+        .get_account_resource_bcs(CORE_CODE_ADDRESS, "0x1::block::BlockResource")
+        .await?
+        .into_inner();
+
+    let mismatched_fields = submitted.mismatched_fields(&config);
+    let matches_submitted = mismatched_fields.is_empty();
+    let effective_epoch = state.epoch + 1;
+    let next_epoch_timestamp_usecs =
+        configuration.last_reconfiguration_time() + block_resource.epoch_interval();
+    let message = if matches_submitted {
+        format!(
+            "The pending config of pool {} matches the update, which takes effect in epoch {}, \
+            in about {} secs",
+            pool_address,
+            effective_epoch,
+            next_epoch_timestamp_usecs.saturating_sub(state.timestamp_usecs) / 1_000_000
+        )
+    } else {
+        format!(
+            "The pending config of pool {} doesn't match the update in {}, check the pool \
+            address or whether another update came after it",
+            pool_address,
+            mismatched_fields.join(", ")
+        )
+    };
+
+    Ok(ValidatorConfigPostState {
+        pool_address,
+        matches_submitted,
+        mismatched_fields,
+        current_epoch: state.epoch,
+        effective_epoch,
+        next_epoch_timestamp_usecs,
+        message,
+    })
+}
+
 /// Tool to analyze the performance of an individual validator
 #[derive(Parser)]
 pub struct AnalyzeValidatorPerformance {
@@ -758,32 +1164,25 @@ impl CliCommand<()> for AnalyzeValidatorPerformance {
 
     async fn execute(mut self) -> CliTypedResult<()> {
         let client = self.rest_options.client(&self.profile_options.profile)?;
-
-        let epochs =
-            FetchMetadata::fetch_new_block_events(&client, self.start_epoch, self.end_epoch)
-                .await?;
-        let mut stats = HashMap::new();
+        let report = analyze_performance(&client, self.start_epoch, self.end_epoch).await?;
+        let stats = report.epoch_stats;
 
         let print_detailed = self.analyze_mode == AnalyzeMode::DetailedEpochTable
             || self.analyze_mode == AnalyzeMode::All;
-        for epoch_info in epochs {
-            let epoch_stats = AnalyzeValidators::analyze(epoch_info.blocks, &epoch_info.validators);
-            if print_detailed {
-                println!("Detailed table for epoch {}:", epoch_info.epoch);
-                AnalyzeValidators::print_detailed_epoch_table(&epoch_stats, None, true);
+        if print_detailed {
+            for epoch in stats.keys().sorted() {
+                println!("Detailed table for epoch {}:", epoch);
+                AnalyzeValidators::print_detailed_epoch_table(&stats[epoch], None, true);
             }
-            stats.insert(epoch_info.epoch, epoch_stats);
         }
 
-        if stats.is_empty() {
-            println!("No data found for given input");
-            return Ok(());
-        }
-        let total_stats = stats
-            .iter()
-            .map(|(_k, v)| v.clone())
-            .reduce(|a, b| a + b)
-            .unwrap();
+        let total_stats = match report.total_stats {
+            Some(total_stats) => total_stats,
+            None => {
+                println!("No data found for given input");
+                return Ok(());
+            }
+        };
         if print_detailed {
             println!(
                 "Detailed table for all epochs [{}, {}]:",
@@ -816,3 +1215,52 @@ impl CliCommand<()> for AnalyzeValidatorPerformance {
         Ok(())
     }
 }
+
+/// Compact the state values of a node's DB
+///
+/// This opens the DB directly, so the node must be stopped while this runs.
+/// Useful to reclaim disk space on long running nodes.
+#[derive(Parser)]
+pub struct CompactStateDb {
+    /// Path to the node's DB directory, e.g. `/opt/aptos/data/db`
+    #[clap(long, parse(from_os_str))]
+    pub db_dir: PathBuf,
+
+    /// Level to move the compacted files to, defaults to the lowest level that can hold them
+    #[clap(long)]
+    pub target_level: Option<i32>,
+
+    /// Block automatic compactions while the manual compaction runs
+    #[clap(long)]
+    pub exclusive: bool,
+}
+
+#[async_trait]
+impl CliCommand<()> for CompactStateDb {
+    fn command_name(&self) -> &'static str {
+        "CompactStateDb"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let opts = CompactionOpts {
+            target_level: self.target_level,
+            exclusive: self.exclusive,
+        };
+        let db_dir = self.db_dir;
+        tokio::task::spawn_blocking(move || {
+            let db = AptosDB::open(
+                db_dir,
+                false,
+                NO_OP_STORAGE_PRUNER_CONFIG,
+                RocksdbConfigs::default(),
+                false,
+                TARGET_SNAPSHOT_SIZE,
+                DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+            )?;
+            db.compact_state_db(Some(opts))
+        })
+        .await
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))??;
+        Ok(())
+    }
+}
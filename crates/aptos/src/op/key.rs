@@ -12,10 +12,16 @@ use crate::{
     CliCommand, CliResult,
 };
 use aptos_config::config::{Peer, PeerRole};
-use aptos_crypto::{ed25519, x25519, PrivateKey, ValidCryptoMaterial};
-use aptos_types::account_address::{from_identity_public_key, AccountAddress};
+use aptos_crypto::{
+    bls12381, ed25519, x25519, PrivateKey, ValidCryptoMaterial, ValidCryptoMaterialStringExt,
+};
+use aptos_types::{
+    account_address::{from_identity_public_key, AccountAddress},
+    transaction::authenticator::AuthenticationKey,
+};
 use async_trait::async_trait;
 use clap::{Parser, Subcommand};
+use serde::Serialize;
 use std::{
     collections::{HashMap, HashSet},
     path::{Path, PathBuf},
@@ -31,6 +37,8 @@ pub const PUBLIC_KEY_EXTENSION: &str = "pub";
 pub enum KeyTool {
     Generate(GenerateKey),
     ExtractPeer(ExtractPeer),
+    Decode(DecodeKey),
+    VerifyProofOfPossession(VerifyProofOfPossession),
 }
 
 impl KeyTool {
@@ -38,6 +46,8 @@ impl KeyTool {
         match self {
             KeyTool::Generate(tool) => tool.execute_serialized().await,
             KeyTool::ExtractPeer(tool) => tool.execute_serialized().await,
+            KeyTool::Decode(tool) => tool.execute_serialized().await,
+            KeyTool::VerifyProofOfPossession(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -94,7 +104,136 @@ impl CliCommand<HashMap<AccountAddress, Peer>> for ExtractPeer {
     }
 }
 
-/// Generates a `x25519` or `ed25519` key.
+/// Shows the public information of a private key
+///
+/// For an `ed25519` key, this is the public key, and the authentication key and address of the
+/// account it creates.  For an `x25519` key, it's the public key and the peer id, and for a
+/// `bls12381` consensus key the public key and its proof of possession.
+#[derive(Debug, Parser)]
+pub struct DecodeKey {
+    /// Key type of the private key. Must be one of [x25519, ed25519, bls12381]
+    #[clap(long, default_value_t = KeyType::Ed25519)]
+    pub(crate) key_type: KeyType,
+
+    #[clap(flatten)]
+    pub(crate) private_key_input_options: PrivateKeyInputOptions,
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+}
+
+/// Public information derived from a private key
+#[derive(Debug, Serialize)]
+pub struct DecodedKey {
+    pub public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authentication_key: Option<AuthenticationKey>,
+    /// The account address of an `ed25519` key, or the peer id of an `x25519` key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_address: Option<AccountAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_of_possession: Option<String>,
+}
+
+#[async_trait]
+impl CliCommand<DecodedKey> for DecodeKey {
+    fn command_name(&self) -> &'static str {
+        "DecodeKey"
+    }
+
+    async fn execute(self) -> CliTypedResult<DecodedKey> {
+        let encoding = self.encoding_options.encoding;
+        let options = &self.private_key_input_options;
+        match self.key_type {
+            KeyType::Ed25519 => {
+                let public_key =
+                    extract_private_key::<ed25519::Ed25519PrivateKey>(options, encoding)?
+                        .public_key();
+                let authentication_key = AuthenticationKey::ed25519(&public_key);
+                Ok(DecodedKey {
+                    public_key: encode_public_key(&public_key)?,
+                    account_address: Some(authentication_key.derived_address()),
+                    authentication_key: Some(authentication_key),
+                    proof_of_possession: None,
+                })
+            }
+            KeyType::X25519 => {
+                let public_key =
+                    extract_private_key::<x25519::PrivateKey>(options, encoding)?.public_key();
+                Ok(DecodedKey {
+                    public_key: encode_public_key(&public_key)?,
+                    authentication_key: None,
+                    account_address: Some(from_identity_public_key(public_key)),
+                    proof_of_possession: None,
+                })
+            }
+            KeyType::Bls12381 => {
+                let private_key = extract_private_key::<bls12381::PrivateKey>(options, encoding)?;
+                Ok(DecodedKey {
+                    public_key: encode_public_key(&private_key.public_key())?,
+                    authentication_key: None,
+                    account_address: None,
+                    proof_of_possession: Some(encode_public_key(
+                        &bls12381::ProofOfPossession::create(&private_key),
+                    )?),
+                })
+            }
+        }
+    }
+}
+
+fn extract_private_key<Key: ValidCryptoMaterial>(
+    options: &PrivateKeyInputOptions,
+    encoding: EncodingType,
+) -> CliTypedResult<Key> {
+    options
+        .extract_typed_private_key_cli(encoding)?
+        .ok_or_else(|| {
+            CliError::CommandArgumentError(
+                "One of ['--private-key', '--private-key-file'] must be used".to_string(),
+            )
+        })
+}
+
+fn encode_public_key<Key: ValidCryptoMaterialStringExt>(key: &Key) -> CliTypedResult<String> {
+    key.to_encoded_string()
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))
+}
+
+/// Verifies that a proof of possession was created by the private key of a consensus public key
+///
+/// This can be used to check the consensus key of a validator before it's added to genesis or to
+/// the validator set.
+#[derive(Debug, Parser)]
+pub struct VerifyProofOfPossession {
+    /// Hex encoded Consensus public key
+    #[clap(long, parse(try_from_str = bls12381::PublicKey::from_encoded_string))]
+    pub(crate) consensus_public_key: bls12381::PublicKey,
+
+    /// Hex encoded Consensus proof of possession
+    #[clap(long, parse(try_from_str = bls12381::ProofOfPossession::from_encoded_string))]
+    pub(crate) proof_of_possession: bls12381::ProofOfPossession,
+}
+
+#[async_trait]
+impl CliCommand<&'static str> for VerifyProofOfPossession {
+    fn command_name(&self) -> &'static str {
+        "VerifyProofOfPossession"
+    }
+
+    async fn execute(self) -> CliTypedResult<&'static str> {
+        self.proof_of_possession
+            .verify(&self.consensus_public_key)
+            .map_err(|err| {
+                CliError::CommandArgumentError(format!(
+                    "Proof of possession doesn't match the consensus public key: {}",
+                    err
+                ))
+            })?;
+        Ok("Proof of possession is valid")
+    }
+}
+
+/// Generates a `x25519`, `ed25519` or `bls12381` key.
 ///
 /// This can be used for generating an identity.  Two files will be created
 /// `output_file` and `output_file.pub`.  `output_file` will contain the private
@@ -102,7 +241,7 @@ impl CliCommand<HashMap<AccountAddress, Peer>> for ExtractPeer {
 /// key encoded with the `encoding`.
 #[derive(Debug, Parser)]
 pub struct GenerateKey {
-    /// Key type to generate. Must be one of [x25519, ed25519]
+    /// Key type to generate. Must be one of [x25519, ed25519, bls12381]
     #[clap(long, default_value_t = KeyType::Ed25519)]
     pub(crate) key_type: KeyType,
 
@@ -136,6 +275,10 @@ impl CliCommand<HashMap<&'static str, PathBuf>> for GenerateKey {
                 let private_key = keygen.generate_ed25519_private_key();
                 self.save_params.save_key(&private_key, "ed25519")
             }
+            KeyType::Bls12381 => {
+                let private_key = keygen.generate_bls12381_private_key();
+                self.save_params.save_key(&private_key, "bls12381")
+            }
         }
     }
 }
@@ -234,3 +377,89 @@ impl SaveKey {
         Ok(map)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::types::account_address_from_public_key;
+    use aptos_keygen::KeyGen;
+
+    async fn decode<Key: ValidCryptoMaterial>(key_type: KeyType, private_key: &Key) -> DecodedKey {
+        let private_key = private_key.to_encoded_string().unwrap();
+        let args = format!(
+            "decode --key-type {} --private-key {}",
+            key_type, private_key
+        );
+        DecodeKey::parse_from(args.split_whitespace())
+            .execute()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_decode_key() {
+        let mut keygen = KeyGen::from_seed([7; 32]);
+
+        let private_key = keygen.generate_ed25519_private_key();
+        let public_key = private_key.public_key();
+        let decoded = decode(KeyType::Ed25519, &private_key).await;
+        assert_eq!(decoded.public_key, public_key.to_encoded_string().unwrap());
+        assert_eq!(
+            decoded.authentication_key,
+            Some(AuthenticationKey::ed25519(&public_key))
+        );
+        assert_eq!(
+            decoded.account_address,
+            Some(account_address_from_public_key(&public_key))
+        );
+        assert!(decoded.proof_of_possession.is_none());
+
+        let private_key = keygen.generate_x25519_private_key().unwrap();
+        let decoded = decode(KeyType::X25519, &private_key).await;
+        assert_eq!(
+            decoded.account_address,
+            Some(from_identity_public_key(private_key.public_key()))
+        );
+
+        let private_key = keygen.generate_bls12381_private_key();
+        let decoded = decode(KeyType::Bls12381, &private_key).await;
+        let public_key = bls12381::PublicKey::from_encoded_string(&decoded.public_key).unwrap();
+        bls12381::ProofOfPossession::from_encoded_string(
+            decoded.proof_of_possession.as_ref().unwrap(),
+        )
+        .unwrap()
+        .verify(&public_key)
+        .unwrap();
+        assert!(decoded.account_address.is_none());
+    }
+
+    async fn verify_proof_of_possession(
+        public_key: &bls12381::PublicKey,
+        proof_of_possession: &bls12381::ProofOfPossession,
+    ) -> CliTypedResult<&'static str> {
+        let args = format!(
+            "verify-proof-of-possession --consensus-public-key {} --proof-of-possession {}",
+            public_key.to_encoded_string().unwrap(),
+            proof_of_possession.to_encoded_string().unwrap()
+        );
+        VerifyProofOfPossession::parse_from(args.split_whitespace())
+            .execute()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_verify_proof_of_possession() {
+        let mut keygen = KeyGen::from_seed([7; 32]);
+        let private_key = keygen.generate_bls12381_private_key();
+        let other_private_key = keygen.generate_bls12381_private_key();
+        let proof_of_possession = bls12381::ProofOfPossession::create(&private_key);
+
+        verify_proof_of_possession(&private_key.public_key(), &proof_of_possession)
+            .await
+            .unwrap();
+        assert!(matches!(
+            verify_proof_of_possession(&other_private_key.public_key(), &proof_of_possession).await,
+            Err(CliError::CommandArgumentError(_))
+        ));
+    }
+}
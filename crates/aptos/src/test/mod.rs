@@ -5,7 +5,7 @@ use crate::account::{
     create::{CreateAccount, DEFAULT_FUNDED_COINS},
     fund::FundWithFaucet,
     list::{ListAccount, ListQuery},
-    transfer::{TransferCoins, TransferSummary},
+    transfer::{TransferCoins, TransferSummary, DEFAULT_COIN_TYPE},
 };
 use crate::common::init::InitTool;
 use crate::common::types::{
@@ -16,14 +16,14 @@ use crate::common::types::{
 };
 use crate::common::utils::write_to_file;
 use crate::move_tool::{
-    ArgWithType, CompilePackage, DownloadPackage, IncludedArtifacts, InitPackage, MemberId,
-    PublishPackage, RunFunction, TestPackage,
+    ArgWithType, CompilePackage, DocumentPackage, DownloadPackage, ExtractAbi, IncludedArtifacts,
+    InitPackage, MemberId, ProvePackage, PublishPackage, RunFunction, TestPackage,
 };
 use crate::node::{
     AnalyzeMode, AnalyzeValidatorPerformance, InitializeValidator, JoinValidatorSet,
-    LeaveValidatorSet, OperatorArgs, OperatorConfigFileArgs, ShowValidatorConfig, ShowValidatorSet,
-    ShowValidatorStake, UpdateConsensusKey, UpdateValidatorNetworkAddresses,
-    ValidatorConsensusKeyArgs, ValidatorNetworkAddressesArgs,
+    LeaveValidatorSet, OperatorArgs, OperatorConfigFileArgs, RunLocalTestnet, ShowValidatorConfig,
+    ShowValidatorSet, ShowValidatorStake, UpdateConsensusKey, UpdateValidatorNetworkAddresses,
+    ValidatorConfigUpdateSummary, ValidatorConsensusKeyArgs, ValidatorNetworkAddressesArgs,
 };
 use crate::op::key::{ExtractPeer, GenerateKey, SaveKey};
 use crate::stake::{
@@ -197,7 +197,26 @@ impl CliTestFramework {
         TransferCoins {
             txn_options: self.transaction_options(sender_index, gas_options),
             account: self.account_id(receiver_index),
-            amount,
+            amount: Some(amount),
+            max: false,
+            coin_type: DEFAULT_COIN_TYPE.parse().unwrap(),
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn transfer_max(
+        &self,
+        sender_index: usize,
+        receiver_index: usize,
+        gas_options: Option<GasOptions>,
+    ) -> CliTypedResult<TransferSummary> {
+        TransferCoins {
+            txn_options: self.transaction_options(sender_index, gas_options),
+            account: self.account_id(receiver_index),
+            amount: None,
+            max: true,
+            coin_type: DEFAULT_COIN_TYPE.parse().unwrap(),
         }
         .execute()
         .await
@@ -212,7 +231,9 @@ impl CliTestFramework {
         TransferCoins {
             txn_options: self.transaction_options(sender_index, gas_options),
             account: AccountAddress::from_hex_literal(INVALID_ACCOUNT).unwrap(),
-            amount,
+            amount: Some(amount),
+            max: false,
+            coin_type: DEFAULT_COIN_TYPE.parse().unwrap(),
         }
         .execute()
         .await
@@ -275,6 +296,7 @@ impl CliTestFramework {
                 full_node_host: None,
                 full_node_network_public_key: None,
             },
+            no_verify: false,
         }
         .execute()
         .await
@@ -355,7 +377,7 @@ impl CliTestFramework {
         pool_index: Option<usize>,
         validator_host: HostAndPort,
         validator_network_public_key: x25519::PublicKey,
-    ) -> CliTypedResult<TransactionSummary> {
+    ) -> CliTypedResult<ValidatorConfigUpdateSummary> {
         UpdateValidatorNetworkAddresses {
             txn_options: self.transaction_options(operator_index, None),
             operator_args: self.operator_args(pool_index),
@@ -395,7 +417,7 @@ impl CliTestFramework {
         pool_index: Option<usize>,
         consensus_public_key: bls12381::PublicKey,
         proof_of_possession: bls12381::ProofOfPossession,
-    ) -> CliTypedResult<TransactionSummary> {
+    ) -> CliTypedResult<ValidatorConfigUpdateSummary> {
         UpdateConsensusKey {
             txn_options: self.transaction_options(operator_index, None),
             operator_args: self.operator_args(pool_index),
@@ -406,6 +428,30 @@ impl CliTestFramework {
                 consensus_public_key: Some(consensus_public_key),
                 proof_of_possession: Some(proof_of_possession),
             },
+            no_verify: false,
+        }
+        .execute()
+        .await
+    }
+
+    /// Runs a local testnet in `test_dir` with pre-funded test accounts, never returning on success
+    pub async fn run_local_testnet(
+        config_path: PathBuf,
+        test_dir: PathBuf,
+        test_accounts: usize,
+        test_account_balance: u64,
+    ) -> CliTypedResult<()> {
+        RunLocalTestnet {
+            config_path: Some(config_path),
+            test_dir: Some(test_dir),
+            seed: None,
+            force_restart: false,
+            with_faucet: false,
+            faucet_port: 8081,
+            prompt_options: PromptOptions::yes(),
+            do_not_delegate: false,
+            test_accounts,
+            test_account_balance,
         }
         .execute()
         .await
@@ -413,14 +459,17 @@ impl CliTestFramework {
 
     pub async fn init(&self, private_key: &Ed25519PrivateKey) -> CliTypedResult<()> {
         InitTool {
+            network: None,
             rest_url: Some(self.endpoint.clone()),
             faucet_url: Some(self.faucet_endpoint.clone()),
             rng_args: RngArgs::from_seed([0; 32]),
             private_key_options: PrivateKeyInputOptions::from_private_key(private_key)?,
+            public_key_options: Default::default(),
             profile_options: Default::default(),
             prompt_options: PromptOptions::yes(),
             encoding_options: EncodingOptions::default(),
             skip_faucet: false,
+            public_key_only: false,
         }
         .execute()
         .await
@@ -659,6 +708,118 @@ impl CliTestFramework {
         TestPackage {
             move_options: self.move_options(account_strs),
             filter: filter.map(|str| str.to_string()),
+            coverage: false,
+            coverage_output: None,
+            instruction_bound: None,
+            unbounded: false,
+            parallel: false,
+            parallel_jobs: None,
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn test_package_in_parallel(
+        &self,
+        account_strs: BTreeMap<&str, &str>,
+        parallel_jobs: usize,
+    ) -> CliTypedResult<&'static str> {
+        TestPackage {
+            move_options: self.move_options(account_strs),
+            filter: None,
+            coverage: false,
+            coverage_output: None,
+            instruction_bound: None,
+            unbounded: false,
+            parallel: true,
+            parallel_jobs: Some(parallel_jobs),
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn test_package_with_coverage(
+        &self,
+        account_strs: BTreeMap<&str, &str>,
+        coverage_output: PathBuf,
+    ) -> CliTypedResult<&'static str> {
+        TestPackage {
+            move_options: self.move_options(account_strs),
+            filter: None,
+            coverage: true,
+            coverage_output: Some(coverage_output),
+            instruction_bound: None,
+            unbounded: false,
+            parallel: false,
+            parallel_jobs: None,
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn test_package_with_instruction_bound(
+        &self,
+        account_strs: BTreeMap<&str, &str>,
+        instruction_bound: u64,
+    ) -> CliTypedResult<&'static str> {
+        TestPackage {
+            move_options: self.move_options(account_strs),
+            filter: None,
+            coverage: false,
+            coverage_output: None,
+            instruction_bound: Some(instruction_bound),
+            unbounded: false,
+            parallel: false,
+            parallel_jobs: None,
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn prove_package_with_timeout(
+        &self,
+        account_strs: BTreeMap<&str, &str>,
+        prover_timeout: u64,
+    ) -> CliTypedResult<&'static str> {
+        ProvePackage {
+            filter: None,
+            prover_timeout: Some(prover_timeout),
+            vc_timeout: None,
+            prover_verbose: None,
+            prover_options: None,
+            move_options: self.move_options(account_strs),
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn document_package(
+        &self,
+        account_strs: BTreeMap<&str, &str>,
+        output_dir: PathBuf,
+    ) -> CliTypedResult<Vec<String>> {
+        DocumentPackage {
+            include_impl: true,
+            include_specs: false,
+            move_options: MovePackageDir {
+                output_dir: Some(output_dir),
+                ..self.move_options(account_strs)
+            },
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn extract_abi(
+        &self,
+        account_strs: BTreeMap<&str, &str>,
+        output_dir: PathBuf,
+    ) -> CliTypedResult<Vec<String>> {
+        ExtractAbi {
+            move_options: MovePackageDir {
+                output_dir: Some(output_dir),
+                ..self.move_options(account_strs)
+            },
         }
         .execute()
         .await
@@ -677,6 +838,8 @@ impl CliTestFramework {
             legacy_flow,
             override_size_check: false,
             included_artifacts: IncludedArtifacts::All,
+            allow_arbitrary_upgrade: false,
+            prompt_options: PromptOptions::yes(),
         }
         .execute()
         .await
@@ -0,0 +1,24 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::CliResult;
+use crate::config::GenerateShellCompletions;
+use clap::Parser;
+
+/// Tool for utilities around the Aptos CLI tool itself
+///
+/// This tool handles setting up the CLI in the user's environment, e.g.
+/// the shell completions.
+#[derive(Parser)]
+pub enum UtilTool {
+    #[clap(alias = "generate-shell-completion")]
+    GenerateShellCompletions(GenerateShellCompletions),
+}
+
+impl UtilTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            UtilTool::GenerateShellCompletions(tool) => tool.execute_tool().await,
+        }
+    }
+}
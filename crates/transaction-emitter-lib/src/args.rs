@@ -1,8 +1,12 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{convert::TryFrom, path::Path};
+use std::{convert::TryFrom, path::Path, time::Duration};
 
+use crate::{
+    emitter::{audit::AuditConfig, DutyCycle},
+    transaction_generator::TransactionShape,
+};
 use anyhow::{bail, format_err, Result};
 use aptos::common::types::EncodingType;
 use aptos_config::keys::ConfigKey;
@@ -54,11 +58,12 @@ pub struct ClusterArgs {
     pub mint_args: MintArgs,
 }
 
-#[derive(Debug, Clone, Copy, ArgEnum, Deserialize, Parser, Serialize)]
+#[derive(Debug, Clone, Copy, ArgEnum, Deserialize, Eq, Hash, Parser, PartialEq, Serialize)]
 pub enum TransactionType {
     P2P,
     AccountGeneration,
     NftMint,
+    PublishModule,
 }
 
 impl Default for TransactionType {
@@ -99,6 +104,99 @@ pub struct EmitArgs {
 
     #[clap(long, arg_enum, default_value = "p2p", ignore_case = true)]
     pub transaction_type: TransactionType,
+
+    /// Emit in bursts: submit at full speed for this many seconds, then stay
+    /// idle for --idle-duration-secs, repeating for the whole run.
+    #[clap(long, requires = "idle-duration-secs")]
+    pub burst_duration_secs: Option<u64>,
+
+    /// Idle time between two bursts, see --burst-duration-secs.
+    #[clap(long, requires = "burst-duration-secs")]
+    pub idle_duration_secs: Option<u64>,
+
+    /// Upper bound of a random per-worker shift of the burst/idle cycle, so
+    /// that workers don't all switch phases at exactly the same instant.
+    #[clap(long, requires = "burst-duration-secs")]
+    pub duty_cycle_jitter_millis: Option<u64>,
+
+    /// After the run, check the created accounts, the funder balance and the
+    /// total coin supply on chain against the emitter's own accounting.
+    #[clap(long)]
+    pub audit: bool,
+
+    /// Only check this many randomly chosen created accounts in the audit.
+    #[clap(long, requires = "audit")]
+    pub audit_sample_size: Option<usize>,
+
+    /// Difference in burned coins tolerated by the audit, see --audit.
+    #[clap(long, default_value = "0", requires = "audit")]
+    pub audit_tolerance: u64,
+
+    /// With --reuse-accounts, warn when the sequence number of a reused account
+    /// on chain differs from the locally tracked one by more than this.
+    #[clap(long, default_value = "0")]
+    pub max_drift: u64,
+
+    /// Bytes of padding passed to each p2p or account generation transaction,
+    /// on top of its transfer or account creation.
+    #[clap(long, default_value = "0")]
+    pub padding_bytes: usize,
+
+    /// Distinct accounts written to by each p2p or account generation transaction,
+    /// with one transfer or account creation per account.
+    #[clap(long, default_value = "1")]
+    pub fan_out: usize,
+}
+
+impl EmitArgs {
+    pub fn duty_cycle(&self) -> Result<Option<DutyCycle>> {
+        let (burst_duration_secs, idle_duration_secs) =
+            match (self.burst_duration_secs, self.idle_duration_secs) {
+                (Some(burst), Some(idle)) => (burst, idle),
+                _ => return Ok(None),
+            };
+        if burst_duration_secs == 0 {
+            bail!("--burst-duration-secs must be greater than 0");
+        }
+        let mut duty_cycle = DutyCycle::new(
+            Duration::from_secs(burst_duration_secs),
+            Duration::from_secs(idle_duration_secs),
+        );
+        if let Some(jitter_millis) = self.duty_cycle_jitter_millis {
+            duty_cycle = duty_cycle.jitter(Duration::from_millis(jitter_millis));
+        }
+        Ok(Some(duty_cycle))
+    }
+
+    pub fn transaction_shape(&self) -> Result<TransactionShape> {
+        let shape = TransactionShape {
+            padding_bytes: self.padding_bytes,
+            fan_out: self.fan_out,
+        };
+        if shape != TransactionShape::default()
+            && matches!(
+                self.transaction_type,
+                TransactionType::NftMint | TransactionType::PublishModule
+            )
+        {
+            bail!(
+                "--padding-bytes and --fan-out don't apply to nft-mint and publish-module transactions"
+            );
+        }
+        shape.validate()?;
+        Ok(shape)
+    }
+
+    pub fn audit_config(&self) -> Option<AuditConfig> {
+        if self.audit {
+            Some(AuditConfig {
+                sample_size: self.audit_sample_size,
+                tolerance: self.audit_tolerance,
+            })
+        } else {
+            None
+        }
+    }
 }
 
 fn parse_target(target: &str) -> Result<Url> {
@@ -11,6 +11,7 @@ use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey};
 use aptos_logger::{debug, info};
 use aptos_rest_client::{Client as RestClient, PendingTransaction, Response};
 use aptos_sdk::{
+    move_types::account_address::AccountAddress,
     transaction_builder::{aptos_stdlib, TransactionFactory},
     types::{
         transaction::{
@@ -34,6 +35,11 @@ pub struct AccountMinter<'t> {
     txn_factory: TransactionFactory,
     rng: StdRng,
     root_account: &'t mut LocalAccount,
+    /// Seed accounts created so far, and the coins transferred to them from the root account.
+    seed_addresses: Vec<AccountAddress>,
+    seed_funding: u128,
+    /// Accounts created and funded by the seed accounts so far.
+    created_addresses: Vec<AccountAddress>,
 }
 
 impl<'t> AccountMinter<'t> {
@@ -46,6 +52,9 @@ impl<'t> AccountMinter<'t> {
             root_account,
             txn_factory,
             rng,
+            seed_addresses: vec![],
+            seed_funding: 0,
+            created_addresses: vec![],
         }
     }
     /// workflow of mint accounts:
@@ -60,11 +69,12 @@ impl<'t> AccountMinter<'t> {
     pub async fn mint_accounts(
         &mut self,
         req: &EmitJobRequest,
+        rest_clients: &[RestClient],
         total_requested_accounts: usize,
     ) -> Result<Vec<LocalAccount>> {
         let mut accounts = vec![];
         let expected_num_seed_accounts =
-            if total_requested_accounts / req.rest_clients.len() > MAX_CHILD_VASP_NUM {
+            if total_requested_accounts / rest_clients.len() > MAX_CHILD_VASP_NUM {
                 total_requested_accounts / MAX_CHILD_VASP_NUM + 1
             } else {
                 (total_requested_accounts / 50).max(1)
@@ -78,7 +88,7 @@ impl<'t> AccountMinter<'t> {
         let coins_per_seed_account = num_accounts as u64 * coins_per_account * 2;
         let seed_accounts = self
             .create_and_fund_seed_accounts(
-                &req.rest_clients,
+                rest_clients,
                 expected_num_seed_accounts,
                 coins_per_seed_account,
                 req.reuse_accounts,
@@ -104,8 +114,8 @@ impl<'t> AccountMinter<'t> {
             .enumerate()
             .map(|(i, seed_account)| {
                 // Spawn new threads
-                let index = i % req.rest_clients.len();
-                let cur_client = req.rest_clients[index].clone();
+                let index = i % rest_clients.len();
+                let cur_client = rest_clients[index].clone();
                 create_and_fund_new_accounts(
                     seed_account,
                     num_new_child_accounts,
@@ -122,12 +132,15 @@ impl<'t> AccountMinter<'t> {
                 )
             });
 
-        let mut minted_accounts = try_join_all(account_futures)
+        let mut minted_accounts: Vec<_> = try_join_all(account_futures)
             .await
             .map_err(|e| format_err!("Failed to mint accounts: {}", e))?
             .into_iter()
             .flatten()
             .collect();
+        // Seed accounts round the number of accounts up, so there can be more than requested
+        self.created_addresses
+            .extend(minted_accounts.iter().map(LocalAccount::address));
 
         accounts.append(&mut minted_accounts);
         assert!(
@@ -181,6 +194,9 @@ impl<'t> AccountMinter<'t> {
                 .collect();
             execute_and_wait_transactions(&client, creation_account, create_requests).await?;
             i += batch_size;
+            self.seed_addresses
+                .extend(batch.iter().map(LocalAccount::address));
+            self.seed_funding += coins_per_seed_account as u128 * batch_size as u128;
             seed_accounts.append(&mut batch);
         }
         info!("Completed creating and funding seed accounts");
@@ -220,6 +236,18 @@ impl<'t> AccountMinter<'t> {
     pub fn rng(&mut self) -> &mut StdRng {
         &mut self.rng
     }
+
+    pub fn seed_addresses(&self) -> &[AccountAddress] {
+        &self.seed_addresses
+    }
+
+    pub fn seed_funding(&self) -> u128 {
+        self.seed_funding
+    }
+
+    pub fn created_addresses(&self) -> &[AccountAddress] {
+        &self.created_addresses
+    }
 }
 
 fn gen_rng_for_reusable_account(count: usize) -> Vec<StdRng> {
@@ -0,0 +1,537 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optional post-run audit, cross-checking the emitter's own accounting against the
+//! chain's view of the created accounts, the funder account and the total coin supply.
+
+use crate::emitter::RETRY_POLICY;
+use anyhow::{anyhow, Result};
+use aptos_rest_client::{
+    aptos_api_types::{U128, U64},
+    Client as RestClient, TableHandle,
+};
+use aptos_sdk::{
+    move_types::{account_address::AccountAddress, language_storage::TypeTag},
+    types::account_config::CORE_CODE_ADDRESS,
+};
+use futures::future::join_all;
+use rand::{rngs::StdRng, seq::SliceRandom};
+use serde::Deserialize;
+use std::fmt;
+
+const APTOS_COIN_INFO: &str = "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>";
+const APTOS_COIN_STORE: &str = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+const EVENTS_PAGE_SIZE: u16 = 100;
+
+#[derive(Clone, Debug, Default)]
+pub struct AuditConfig {
+    /// Number of created accounts to check, or all of them if not set.
+    pub sample_size: Option<usize>,
+    /// Maximum difference, in coins, tolerated between the amount burned according
+    /// to the total supply and according to the audited balances.
+    pub tolerance: u64,
+}
+
+/// Chain state relevant to the audit, read at a single ledger version.
+#[derive(Clone, Debug)]
+pub struct ChainSnapshot {
+    pub version: u64,
+    pub funder_balance: u64,
+    /// Number of deposit events emitted on the funder's coin store so far.
+    pub funder_deposits: u64,
+    /// Total AptosCoin supply, if the chain tracks it.
+    pub total_supply: Option<u128>,
+}
+
+impl ChainSnapshot {
+    pub async fn fetch(client: &RestClient, funder: AccountAddress) -> Result<Self> {
+        let version = client.get_ledger_information().await?.into_inner().version;
+        let coin_store = client
+            .get_account_resource_at_version(funder, APTOS_COIN_STORE, version)
+            .await?
+            .into_inner()
+            .ok_or_else(|| anyhow!("{} not found for {}", APTOS_COIN_STORE, funder))?;
+        let coin_store: CoinStore = serde_json::from_value(coin_store.data)?;
+        let total_supply = get_total_supply(client, version).await?;
+        Ok(Self {
+            version,
+            funder_balance: coin_store.coin.value.0,
+            funder_deposits: coin_store.deposit_events.counter.0,
+            total_supply,
+        })
+    }
+}
+
+/// What the emitter itself did to the chain during the run.
+#[derive(Clone, Debug, Default)]
+pub struct EmitterAccounting {
+    pub seed_accounts: Vec<AccountAddress>,
+    pub created_accounts: Vec<AccountAddress>,
+    /// Coins transferred from the funder to the seed accounts.
+    pub funded: u128,
+    /// Coins minted during the run, i.e. deposited into the funder account. Filled in from
+    /// the funder's deposit events when the audit runs.
+    pub minted: u128,
+    /// Whether coins only move between the funder, seed and created accounts (e.g. P2P
+    /// transfers). Otherwise the balances can't account for the coins burned as gas.
+    pub coins_stay_in_accounts: bool,
+}
+
+/// Result of checking a set of accounts at a ledger version.
+#[derive(Clone, Debug, Default)]
+pub struct AccountsAudit {
+    pub checked: usize,
+    pub missing: usize,
+    pub balance_total: u128,
+}
+
+impl AccountsAudit {
+    pub async fn fetch(client: &RestClient, addresses: &[AccountAddress], version: u64) -> Self {
+        let balances = join_all(addresses.iter().map(|address| {
+            RETRY_POLICY.retry(move || client.get_account_balance_at_version(*address, version))
+        }))
+        .await;
+        let mut audit = AccountsAudit {
+            checked: addresses.len(),
+            ..AccountsAudit::default()
+        };
+        for balance in balances {
+            match balance {
+                Ok(balance) => audit.balance_total += balance.into_inner().get() as u128,
+                Err(_) => audit.missing += 1,
+            }
+        }
+        audit
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct PendingAudit {
+    pub config: AuditConfig,
+    pub before: ChainSnapshot,
+    pub accounting: EmitterAccounting,
+}
+
+impl PendingAudit {
+    pub async fn run(
+        self,
+        client: &RestClient,
+        funder: AccountAddress,
+        rng: &mut StdRng,
+    ) -> Result<AuditReport> {
+        let after = ChainSnapshot::fetch(client, funder).await?;
+        let mut accounting = self.accounting;
+        // The emitter never sends coins back to the funder, so everything deposited
+        // into it during the run was minted.
+        accounting.minted = get_deposited(
+            client,
+            funder,
+            self.before.funder_deposits,
+            after.funder_deposits,
+        )
+        .await?;
+        let created_accounts = match self.config.sample_size {
+            Some(sample_size) => accounting
+                .created_accounts
+                .choose_multiple(rng, sample_size)
+                .cloned()
+                .collect(),
+            None => accounting.created_accounts.clone(),
+        };
+        let seeds = AccountsAudit::fetch(client, &accounting.seed_accounts, after.version).await;
+        let created = AccountsAudit::fetch(client, &created_accounts, after.version).await;
+        Ok(AuditReport::new(
+            &self.config,
+            &accounting,
+            &self.before,
+            &after,
+            &seeds,
+            &created,
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AuditReport {
+    pub expected_accounts: usize,
+    pub checked_accounts: usize,
+    pub missing_accounts: usize,
+    /// Coins that left the funder account during the run.
+    pub funder_outflow: i128,
+    /// Coins burned according to the balances of the funder and the emitter accounts.
+    pub burned_by_balances: Option<i128>,
+    /// Coins burned according to the total supply.
+    pub burned_by_supply: Option<i128>,
+    pub discrepancies: Vec<String>,
+}
+
+impl AuditReport {
+    pub fn new(
+        config: &AuditConfig,
+        accounting: &EmitterAccounting,
+        before: &ChainSnapshot,
+        after: &ChainSnapshot,
+        seeds: &AccountsAudit,
+        created: &AccountsAudit,
+    ) -> Self {
+        let mut discrepancies = vec![];
+        let expected_accounts = accounting.seed_accounts.len() + accounting.created_accounts.len();
+        let missing_accounts = seeds.missing + created.missing;
+        if missing_accounts > 0 {
+            discrepancies.push(format!(
+                "{} of {} checked accounts don't exist on chain",
+                missing_accounts,
+                seeds.checked + created.checked
+            ));
+        }
+
+        let funder_outflow = before.funder_balance as i128 - after.funder_balance as i128;
+        if funder_outflow + (accounting.minted as i128) < accounting.funded as i128 {
+            discrepancies.push(format!(
+                "funder balance dropped by {} with {} coins minted, less than the {} coins transferred out by the emitter",
+                funder_outflow, accounting.minted, accounting.funded
+            ));
+        }
+
+        let burned_by_balances = if accounting.coins_stay_in_accounts {
+            // Unchecked accounts are assumed to hold the average balance of the sampled ones.
+            let created_balance_total = if created.checked == 0 {
+                0
+            } else {
+                created.balance_total as i128 * accounting.created_accounts.len() as i128
+                    / created.checked as i128
+            };
+            let burned = funder_outflow + accounting.minted as i128
+                - seeds.balance_total as i128
+                - created_balance_total;
+            if burned < 0 {
+                discrepancies.push(format!(
+                    "emitter accounts hold {} more coins than the funder sent out",
+                    -burned
+                ));
+            }
+            Some(burned)
+        } else {
+            None
+        };
+
+        let burned_by_supply = match (before.total_supply, after.total_supply) {
+            (Some(before), Some(after)) => {
+                Some(before as i128 - after as i128 + accounting.minted as i128)
+            }
+            _ => None,
+        };
+
+        if let (Some(by_balances), Some(by_supply)) = (burned_by_balances, burned_by_supply) {
+            if (by_balances - by_supply).abs() > config.tolerance as i128 {
+                discrepancies.push(format!(
+                    "{} coins burned according to balances, but {} according to the total supply",
+                    by_balances, by_supply
+                ));
+            }
+        }
+
+        Self {
+            expected_accounts,
+            checked_accounts: seeds.checked + created.checked,
+            missing_accounts,
+            funder_outflow,
+            burned_by_balances,
+            burned_by_supply,
+            discrepancies,
+        }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+impl fmt::Display for AuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "accounts: {} checked of {} ({} missing), funder outflow: {}, burned by balances: {:?}, burned by supply: {:?}",
+            self.checked_accounts,
+            self.expected_accounts,
+            self.missing_accounts,
+            self.funder_outflow,
+            self.burned_by_balances,
+            self.burned_by_supply,
+        )?;
+        for discrepancy in &self.discrepancies {
+            write!(f, "\n  discrepancy: {}", discrepancy)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct CoinStore {
+    coin: Coin,
+    deposit_events: EventHandle,
+}
+
+#[derive(Deserialize)]
+struct Coin {
+    value: U64,
+}
+
+#[derive(Deserialize)]
+struct EventHandle {
+    counter: U64,
+}
+
+#[derive(Deserialize)]
+struct DepositEvent {
+    amount: U64,
+}
+
+#[derive(Deserialize)]
+struct MoveOption<T> {
+    vec: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct CoinInfo {
+    supply: MoveOption<OptionalAggregator>,
+}
+
+#[derive(Deserialize)]
+struct OptionalAggregator {
+    aggregator: MoveOption<Aggregator>,
+    integer: MoveOption<Integer>,
+}
+
+#[derive(Deserialize)]
+struct Aggregator {
+    handle: TableHandle,
+    key: U128,
+}
+
+#[derive(Deserialize)]
+struct Integer {
+    value: U128,
+}
+
+/// Sums the amounts of the deposit events `start..end` of `address`'s AptosCoin store.
+async fn get_deposited(
+    client: &RestClient,
+    address: AccountAddress,
+    start: u64,
+    end: u64,
+) -> Result<u128> {
+    let mut deposited = 0;
+    let mut next = start;
+    while next < end {
+        let limit = (end - next).min(EVENTS_PAGE_SIZE as u64) as u16;
+        let events = client
+            .get_account_events(
+                address,
+                APTOS_COIN_STORE,
+                "deposit_events",
+                Some(next),
+                Some(limit),
+            )
+            .await?
+            .into_inner();
+        if events.is_empty() {
+            return Err(anyhow!(
+                "deposit events {}..{} of {} not found",
+                next,
+                end,
+                address
+            ));
+        }
+        next += events.len() as u64;
+        for event in events {
+            let deposit: DepositEvent = serde_json::from_value(event.data)?;
+            deposited += deposit.amount.0 as u128;
+        }
+    }
+    Ok(deposited)
+}
+
+/// Reads the AptosCoin total supply at `version`. Depending on whether supply tracking
+/// was upgraded to be parallelizable, the value lives either inline in `CoinInfo` or in
+/// an aggregator, which is stored as a table item.
+async fn get_total_supply(client: &RestClient, version: u64) -> Result<Option<u128>> {
+    let coin_info = client
+        .get_account_resource_at_version(CORE_CODE_ADDRESS, APTOS_COIN_INFO, version)
+        .await?
+        .into_inner()
+        .ok_or_else(|| anyhow!("{} not found", APTOS_COIN_INFO))?;
+    let coin_info: CoinInfo = serde_json::from_value(coin_info.data)?;
+    let supply = match coin_info.supply.vec.into_iter().next() {
+        Some(supply) => supply,
+        None => return Ok(None),
+    };
+
+    if let Some(integer) = supply.integer.vec.first() {
+        return Ok(Some(integer.value.0));
+    }
+    match supply.aggregator.vec.first() {
+        Some(aggregator) => {
+            let value: u128 = client
+                .get_table_item_bcs(
+                    aggregator.handle,
+                    &TypeTag::U128,
+                    &TypeTag::U128,
+                    &aggregator.key,
+                    Some(version),
+                )
+                .await?
+                .into_inner();
+            Ok(Some(value))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::emitter::audit::{
+        AccountsAudit, AuditConfig, AuditReport, ChainSnapshot, EmitterAccounting,
+    };
+    use aptos_sdk::move_types::account_address::AccountAddress;
+
+    fn snapshot(funder_balance: u64, total_supply: u128) -> ChainSnapshot {
+        ChainSnapshot {
+            version: 0,
+            funder_balance,
+            funder_deposits: 0,
+            total_supply: Some(total_supply),
+        }
+    }
+
+    fn accounting(num_created: usize) -> EmitterAccounting {
+        EmitterAccounting {
+            seed_accounts: vec![AccountAddress::random()],
+            created_accounts: (0..num_created).map(|_| AccountAddress::random()).collect(),
+            funded: 1_000,
+            minted: 0,
+            coins_stay_in_accounts: true,
+        }
+    }
+
+    fn accounts(checked: usize, missing: usize, balance_total: u128) -> AccountsAudit {
+        AccountsAudit {
+            checked,
+            missing,
+            balance_total,
+        }
+    }
+
+    #[test]
+    pub fn test_audit_consistent() {
+        // 1000 coins leave the funder, 10 of them pay for gas of the funder and the
+        // emitter accounts, the rest stays with the seed and the 4 created accounts.
+        let report = AuditReport::new(
+            &AuditConfig::default(),
+            &accounting(4),
+            &snapshot(10_000, 1_000_000),
+            &snapshot(9_000, 999_990),
+            &accounts(1, 0, 190),
+            &accounts(4, 0, 800),
+        );
+        assert_eq!(report.funder_outflow, 1_000);
+        assert_eq!(report.burned_by_balances, Some(10));
+        assert_eq!(report.burned_by_supply, Some(10));
+        assert!(report.is_clean(), "{}", report);
+    }
+
+    #[test]
+    pub fn test_audit_sampled_accounts_are_extrapolated() {
+        let report = AuditReport::new(
+            &AuditConfig {
+                sample_size: Some(2),
+                tolerance: 0,
+            },
+            &accounting(4),
+            &snapshot(10_000, 1_000_000),
+            &snapshot(9_000, 999_990),
+            &accounts(1, 0, 190),
+            &accounts(2, 0, 400),
+        );
+        assert_eq!(report.burned_by_balances, Some(10));
+        assert!(report.is_clean(), "{}", report);
+    }
+
+    #[test]
+    pub fn test_audit_supply_mismatch() {
+        let accounting = accounting(4);
+        let before = snapshot(10_000, 1_000_000);
+        let after = snapshot(9_000, 999_980);
+        let seeds = accounts(1, 0, 190);
+        let created = accounts(4, 0, 800);
+
+        let report = AuditReport::new(
+            &AuditConfig::default(),
+            &accounting,
+            &before,
+            &after,
+            &seeds,
+            &created,
+        );
+        assert_eq!(report.burned_by_supply, Some(20));
+        assert_eq!(report.discrepancies.len(), 1);
+
+        let config = AuditConfig {
+            sample_size: None,
+            tolerance: 10,
+        };
+        let report = AuditReport::new(&config, &accounting, &before, &after, &seeds, &created);
+        assert!(report.is_clean(), "{}", report);
+    }
+
+    #[test]
+    pub fn test_audit_missing_accounts_and_funding() {
+        let report = AuditReport::new(
+            &AuditConfig::default(),
+            &accounting(4),
+            &snapshot(10_000, 1_000_000),
+            &snapshot(9_500, 999_990),
+            &accounts(1, 0, 190),
+            &accounts(4, 2, 300),
+        );
+        assert_eq!(report.missing_accounts, 2);
+        // Missing accounts and the funder outflow not covering the funding.
+        assert_eq!(report.discrepancies.len(), 2);
+    }
+
+    #[test]
+    pub fn test_audit_minted_coins() {
+        // 500 coins are minted into the funder, which then sends out 1000 coins, so its
+        // balance only drops by 500. The supply grows by the 500 minted minus 10 burned.
+        let mut accounting = accounting(4);
+        accounting.minted = 500;
+        let report = AuditReport::new(
+            &AuditConfig::default(),
+            &accounting,
+            &snapshot(10_000, 1_000_000),
+            &snapshot(9_500, 1_000_490),
+            &accounts(1, 0, 190),
+            &accounts(4, 0, 800),
+        );
+        assert_eq!(report.funder_outflow, 500);
+        assert_eq!(report.burned_by_balances, Some(10));
+        assert_eq!(report.burned_by_supply, Some(10));
+        assert!(report.is_clean(), "{}", report);
+    }
+
+    #[test]
+    pub fn test_audit_without_supply_tracking() {
+        let mut after = snapshot(9_000, 0);
+        after.total_supply = None;
+        let report = AuditReport::new(
+            &AuditConfig::default(),
+            &accounting(4),
+            &snapshot(10_000, 1_000_000),
+            &after,
+            &accounts(1, 0, 190),
+            &accounts(4, 0, 800),
+        );
+        assert_eq!(report.burned_by_supply, None);
+        assert!(report.is_clean(), "{}", report);
+    }
+}
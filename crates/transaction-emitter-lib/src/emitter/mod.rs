@@ -2,26 +2,28 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod account_minter;
+pub mod audit;
 pub mod stats;
 pub mod submission_worker;
 
 use ::aptos_logger::*;
 use again::RetryPolicy;
 use anyhow::{anyhow, format_err, Result};
+use aptos_metrics_core::{register_int_counter, IntCounter};
 use aptos_rest_client::Client as RestClient;
 use aptos_sdk::{
     move_types::account_address::AccountAddress,
     transaction_builder::TransactionFactory,
     types::{transaction::SignedTransaction, LocalAccount},
 };
-use futures::future::{try_join_all, FutureExt};
+use futures::future::{join_all, try_join_all, FutureExt};
 use itertools::zip;
 use once_cell::sync::Lazy;
 use rand::prelude::SliceRandom;
 use rand_core::SeedableRng;
 use std::{
     cmp::{max, min},
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     num::NonZeroU64,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -33,15 +35,21 @@ use tokio::{runtime::Handle, task::JoinHandle, time};
 
 use crate::{
     args::TransactionType,
-    emitter::{account_minter::AccountMinter, submission_worker::SubmissionWorker},
+    emitter::{
+        account_minter::AccountMinter,
+        audit::{AuditConfig, AuditReport, ChainSnapshot, EmitterAccounting, PendingAudit},
+        submission_worker::SubmissionWorker,
+    },
     transaction_generator::{
         account_generator::AccountGeneratorCreator, nft_mint::NFTMintGeneratorCreator,
-        p2p_transaction_generator::P2PTransactionGeneratorCreator, TransactionGeneratorCreator,
+        p2p_transaction_generator::P2PTransactionGeneratorCreator,
+        publish_module::PublishModuleGeneratorCreator, TransactionGeneratorCreator,
+        TransactionShape,
     },
 };
 use aptos_sdk::transaction_builder::aptos_stdlib;
 use rand::rngs::StdRng;
-use stats::{StatsAccumulator, TxnStats};
+use stats::{GroupedTxnStats, StatsAccumulator, TxnStats};
 
 /// Max transactions per account in mempool
 const MAX_TXN_BATCH_SIZE: usize = 100;
@@ -50,6 +58,11 @@ const MAX_TXNS: u64 = 1_000_000;
 const SEND_AMOUNT: u64 = 1;
 const TXN_EXPIRATION_SECONDS: u64 = 180;
 const TXN_MAX_WAIT: Duration = Duration::from_secs(TXN_EXPIRATION_SECONDS as u64 + 30);
+/// How long stopping a job waits for its workers, after which they are aborted.  Workers wait
+/// for at most `TXN_EXPIRATION_SECONDS` for their transactions, so only wedged workers hit this.
+pub const WORKER_STOP_TIMEOUT: Duration = TXN_MAX_WAIT;
+/// Name of the endpoint group a [`TxnEmitter`] is created with
+pub const DEFAULT_ENDPOINT_GROUP: &str = "default";
 
 // This retry policy is used for important client calls necessary for setting
 // up the test (e.g. account creation) and collecting its results (e.g. checking
@@ -62,12 +75,23 @@ static RETRY_POLICY: Lazy<RetryPolicy> = Lazy::new(|| {
         .with_jitter(true)
 });
 
+/// Reused accounts whose sequence number on chain drifted from the emitter's by more than the
+/// `max_drift` of the job they were reused in
+static SEQUENCE_NUMBER_DRIFT_ACCOUNTS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_emitter_sequence_number_drift_accounts",
+        "Number of reused accounts whose on-chain sequence number drifted from the locally tracked one"
+    )
+    .unwrap()
+});
+
 #[derive(Clone, Debug)]
 pub struct EmitThreadParams {
     pub wait_millis: u64,
     pub wait_committed: bool,
     pub txn_expiration_time_secs: u64,
     pub check_stats_at_end: bool,
+    pub duty_cycle: Option<DutyCycle>,
 }
 
 impl Default for EmitThreadParams {
@@ -77,33 +101,135 @@ impl Default for EmitThreadParams {
             wait_committed: true,
             txn_expiration_time_secs: 300,
             check_stats_at_end: true,
+            duty_cycle: None,
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EmissionPhase {
+    Burst,
+    Idle,
+}
+
+/// Bursty emission pattern: workers submit at full speed for `burst_duration`,
+/// then stop submitting for `idle_duration`, repeating until the job is stopped.
+#[derive(Clone, Copy, Debug)]
+pub struct DutyCycle {
+    pub burst_duration: Duration,
+    pub idle_duration: Duration,
+    /// Upper bound of a random per-worker shift of the cycle, so that workers
+    /// don't all switch phases at exactly the same instant.
+    pub jitter: Option<Duration>,
+}
+
+impl DutyCycle {
+    pub fn new(burst_duration: Duration, idle_duration: Duration) -> Self {
+        assert!(
+            !burst_duration.is_zero(),
+            "Duty cycle burst duration must be non-zero"
+        );
+        Self {
+            burst_duration,
+            idle_duration,
+            jitter: None,
+        }
+    }
+
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    /// Returns the phase `elapsed` into the job, and how much of that phase is left.
+    pub fn phase_at(&self, elapsed: Duration) -> (EmissionPhase, Duration) {
+        let burst_millis = self.burst_duration.as_millis();
+        let cycle_millis = burst_millis + self.idle_duration.as_millis();
+        if self.idle_duration.is_zero() {
+            return (EmissionPhase::Burst, Duration::MAX);
+        }
+        let position = elapsed.as_millis() % cycle_millis;
+        if position < burst_millis {
+            (
+                EmissionPhase::Burst,
+                Duration::from_millis((burst_millis - position) as u64),
+            )
+        } else {
+            (
+                EmissionPhase::Idle,
+                Duration::from_millis((cycle_millis - position) as u64),
+            )
+        }
+    }
+
+    /// Total time spent in the burst phase during the first `duration` of a job.
+    pub fn burst_time_within(&self, duration: Duration) -> Duration {
+        let cycle = self.burst_duration + self.idle_duration;
+        let full_cycles = (duration.as_millis() / cycle.as_millis()) as u32;
+        let remainder = duration - cycle * full_cycles;
+        self.burst_duration * full_cycles + min(remainder, self.burst_duration)
+    }
+}
+
+/// Gas and expiration of the transactions of one [`TransactionType`], in place of the job's
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TransactionSettings {
+    /// Replaces the job's gas price, including the estimated one
+    pub gas_unit_price: Option<u64>,
+    pub max_gas_amount: Option<u64>,
+    /// Seconds from their creation until the transactions expire
+    pub expiration_secs: Option<u64>,
+}
+
+impl TransactionSettings {
+    /// `txn_factory` building transactions with these settings
+    pub fn apply(&self, mut txn_factory: TransactionFactory) -> TransactionFactory {
+        if let Some(gas_unit_price) = self.gas_unit_price {
+            txn_factory = txn_factory.with_gas_unit_price(gas_unit_price);
+        }
+        if let Some(max_gas_amount) = self.max_gas_amount {
+            txn_factory = txn_factory.with_max_gas_amount(max_gas_amount);
+        }
+        if let Some(expiration_secs) = self.expiration_secs {
+            txn_factory = txn_factory.with_transaction_expiration_time(expiration_secs);
+        }
+        txn_factory
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct EmitJobRequest {
     rest_clients: Vec<RestClient>,
+    endpoint_groups: Vec<(String, Vec<RestClient>)>,
     mempool_backlog: usize,
     thread_params: EmitThreadParams,
     gas_price: u64,
     invalid_transaction_ratio: usize,
     pub duration: Duration,
     reuse_accounts: bool,
+    max_drift: u64,
     transaction_type: TransactionType,
+    transaction_shape: TransactionShape,
+    transaction_settings: HashMap<TransactionType, TransactionSettings>,
+    audit: Option<AuditConfig>,
 }
 
 impl Default for EmitJobRequest {
     fn default() -> Self {
         Self {
             rest_clients: Vec::new(),
+            endpoint_groups: Vec::new(),
             mempool_backlog: 3000,
             thread_params: EmitThreadParams::default(),
             gas_price: 0,
             invalid_transaction_ratio: 0,
             duration: Duration::from_secs(300),
             reuse_accounts: false,
+            max_drift: 0,
             transaction_type: TransactionType::P2P,
+            transaction_shape: TransactionShape::default(),
+            transaction_settings: HashMap::new(),
+            audit: None,
         }
     }
 }
@@ -113,11 +239,38 @@ impl EmitJobRequest {
         Self::default().rest_clients(rest_clients)
     }
 
+    /// Clients of the default endpoint group, the one the [`TxnEmitter`] was created with
     pub fn rest_clients(mut self, rest_clients: Vec<RestClient>) -> Self {
         self.rest_clients = rest_clients;
         self
     }
 
+    /// Also emits to the endpoint group `name` through `rest_clients`.  The group's root account
+    /// and transaction factory must be added to the [`TxnEmitter`] with
+    /// [`TxnEmitter::endpoint_group`].
+    pub fn endpoint_group(
+        mut self,
+        name: impl Into<String>,
+        rest_clients: Vec<RestClient>,
+    ) -> Self {
+        let name = name.into();
+        self.endpoint_groups.retain(|(group, _)| *group != name);
+        self.endpoint_groups.push((name, rest_clients));
+        self
+    }
+
+    /// The clients of each endpoint group taking part in the job
+    fn group_rest_clients(&self, name: &str) -> Option<&[RestClient]> {
+        if name == DEFAULT_ENDPOINT_GROUP && !self.rest_clients.is_empty() {
+            return Some(&self.rest_clients);
+        }
+        self.endpoint_groups
+            .iter()
+            .find(|(group, _)| group == name)
+            .map(|(_, rest_clients)| rest_clients.as_slice())
+            .filter(|rest_clients| !rest_clients.is_empty())
+    }
+
     pub fn thread_params(mut self, thread_params: EmitThreadParams) -> Self {
         self.thread_params = thread_params;
         self
@@ -128,6 +281,18 @@ impl EmitJobRequest {
         self
     }
 
+    /// Sets the gas price to the one `client` estimates transactions need to be accepted
+    pub async fn with_gas_price_estimate(self, client: &RestClient) -> Result<Self> {
+        let gas_price = client
+            .estimate_gas_price()
+            .await
+            .map_err(|e| format_err!("Failed to estimate gas price: {}", e))?
+            .into_inner()
+            .gas_estimate;
+        info!("Using estimated gas price {}", gas_price);
+        Ok(self.gas_price(gas_price))
+    }
+
     pub fn invalid_transaction_ratio(mut self, invalid_transaction_ratio: usize) -> Self {
         self.invalid_transaction_ratio = invalid_transaction_ratio;
         self
@@ -138,11 +303,55 @@ impl EmitJobRequest {
         self
     }
 
-    pub fn calculate_workers_per_endpoint(&self) -> usize {
+    /// Pads and fans out the P2P and account generation transactions, see [`TransactionShape`]
+    pub fn transaction_shape(mut self, transaction_shape: TransactionShape) -> Self {
+        self.transaction_shape = transaction_shape;
+        self
+    }
+
+    /// Overrides the gas and expiration of `transaction_type` transactions, e.g. as publishing
+    /// takes more gas than transfers.  Types without settings use the job's gas price and the
+    /// [`TxnEmitter`]'s transaction factory.
+    pub fn transaction_settings(
+        mut self,
+        transaction_type: TransactionType,
+        settings: TransactionSettings,
+    ) -> Self {
+        self.transaction_settings.insert(transaction_type, settings);
+        self
+    }
+
+    /// The settings of the job's transaction type, the defaults if it has none
+    fn current_transaction_settings(&self) -> TransactionSettings {
+        self.transaction_settings
+            .get(&self.transaction_type)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Gas price of the job's transactions
+    fn transaction_gas_price(&self) -> u64 {
+        self.current_transaction_settings()
+            .gas_unit_price
+            .unwrap_or(self.gas_price)
+    }
+
+    pub fn duty_cycle(mut self, duty_cycle: DutyCycle) -> Self {
+        self.thread_params.duty_cycle = Some(duty_cycle);
+        self
+    }
+
+    /// Audits the chain state against the emitter's own accounting when the job is stopped.
+    pub fn audit(mut self, audit: AuditConfig) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// The number of workers for each of `clients_count` clients of one endpoint group
+    pub fn calculate_workers_per_endpoint(&self, clients_count: usize) -> usize {
         // The target mempool backlog is set to be 3x of the target TPS because of the on an average,
         // we can ~3 blocks in consensus queue. As long as we have 3x the target TPS as backlog,
         // it should be enough to produce the target TPS.
-        let clients_count = self.rest_clients.len();
         let num_workers_per_endpoint = max(
             self.mempool_backlog / (clients_count * TRANSACTIONS_PER_ACCOUNT),
             1,
@@ -171,6 +380,13 @@ impl EmitJobRequest {
         self
     }
 
+    /// How far the sequence number of a reused account can be from the one the emitter tracked
+    /// when a job starts before a warning is logged.  Only used with reused accounts.
+    pub fn max_drift(mut self, max_drift: u64) -> Self {
+        self.max_drift = max_drift;
+        self
+    }
+
     pub fn duration(mut self, duration: Duration) -> Self {
         self.duration = duration;
         self
@@ -182,20 +398,36 @@ struct Worker {
     join_handle: JoinHandle<Vec<LocalAccount>>,
 }
 
+/// The workers of one endpoint group of a job
 #[derive(Debug)]
-pub struct EmitJob {
+struct GroupJob {
+    name: String,
     workers: Vec<Worker>,
-    stop: Arc<AtomicBool>,
     stats: Arc<StatsAccumulator>,
+    audit: Option<PendingAudit>,
 }
 
 #[derive(Debug)]
-pub struct TxnEmitter<'t> {
+pub struct EmitJob {
+    groups: Vec<GroupJob>,
+    stop: Arc<AtomicBool>,
+}
+
+/// A network the emitter sends load to, with the root account used to fund the load
+#[derive(Debug)]
+struct EndpointGroup<'t> {
+    name: String,
     accounts: Vec<LocalAccount>,
     txn_factory: TransactionFactory,
     client: RestClient,
-    rng: StdRng,
     root_account: &'t mut LocalAccount,
+    audit_report: Option<AuditReport>,
+}
+
+#[derive(Debug)]
+pub struct TxnEmitter<'t> {
+    groups: Vec<EndpointGroup<'t>>,
+    rng: StdRng,
 }
 
 impl<'t> TxnEmitter<'t> {
@@ -206,20 +438,56 @@ impl<'t> TxnEmitter<'t> {
         rng: StdRng,
     ) -> Self {
         Self {
+            groups: vec![],
+            rng,
+        }
+        .endpoint_group(
+            DEFAULT_ENDPOINT_GROUP,
+            root_account,
+            client,
+            transaction_factory,
+        )
+    }
+
+    /// Adds another network to emit to, e.g. a second swarm with a different chain id.  `client`
+    /// is used to set up the group's accounts, the clients load is sent through are given per job
+    /// with [`EmitJobRequest::endpoint_group`].
+    pub fn endpoint_group(
+        mut self,
+        name: impl Into<String>,
+        root_account: &'t mut LocalAccount,
+        client: RestClient,
+        transaction_factory: TransactionFactory,
+    ) -> Self {
+        let name = name.into();
+        assert!(
+            self.groups.iter().all(|group| group.name != name),
+            "Endpoint group {} was added twice",
+            name
+        );
+        self.groups.push(EndpointGroup {
+            name,
             accounts: vec![],
             txn_factory: transaction_factory,
-            root_account,
             client,
-            rng,
-        }
+            root_account,
+            audit_report: None,
+        });
+        self
+    }
+
+    fn default_group(&mut self) -> &mut EndpointGroup<'t> {
+        &mut self.groups[0]
     }
 
     pub fn take_account(&mut self) -> LocalAccount {
-        self.accounts.remove(0)
+        self.default_group().accounts.remove(0)
     }
 
     pub fn clear(&mut self) {
-        self.accounts.clear();
+        for group in &mut self.groups {
+            group.accounts.clear();
+        }
     }
 
     pub fn rng(&mut self) -> &mut StdRng {
@@ -231,9 +499,10 @@ impl<'t> TxnEmitter<'t> {
     }
 
     pub async fn get_money_source(&mut self, coins_total: u64) -> Result<&mut LocalAccount> {
-        let client = self.client.clone();
+        let group = self.default_group();
+        let client = group.client.clone();
         info!("Creating and minting faucet account");
-        let faucet_account = &mut self.root_account;
+        let faucet_account = &mut group.root_account;
         let balance = client
             .get_account_balance(faucet_account.address())
             .await?
@@ -246,94 +515,105 @@ impl<'t> TxnEmitter<'t> {
         Ok(faucet_account)
     }
 
-    pub async fn start_job(&mut self, req: EmitJobRequest) -> Result<EmitJob> {
-        let workers_per_endpoint = req.calculate_workers_per_endpoint();
-        let num_accounts = req.rest_clients.len() * workers_per_endpoint;
-        info!(
-            "Will use {} workers per endpoint for a total of {} endpoint clients",
-            workers_per_endpoint, num_accounts
-        );
-        info!("Will create a total of {} accounts", num_accounts);
-        let mut account_minter = AccountMinter::new(
-            self.root_account,
-            self.txn_factory.clone(),
-            self.rng.clone(),
-        );
-        let mut new_accounts = account_minter.mint_accounts(&req, num_accounts).await?;
-        self.accounts.append(&mut new_accounts);
-        let all_accounts = self.accounts.split_off(self.accounts.len() - num_accounts);
-        let mut workers = vec![];
-        let all_addresses: Vec<_> = all_accounts.iter().map(|d| d.address()).collect();
-        let all_addresses = Arc::new(all_addresses);
-        let mut all_accounts = all_accounts.into_iter();
-        let stop = Arc::new(AtomicBool::new(false));
-        let stats = Arc::new(StatsAccumulator::default());
-        let tokio_handle = Handle::current();
-        let txn_generator_creator: Box<dyn TransactionGeneratorCreator> = match req.transaction_type
-        {
-            TransactionType::P2P => Box::new(P2PTransactionGeneratorCreator::new(
-                self.from_rng(),
-                self.txn_factory.clone(),
-                SEND_AMOUNT,
-            )),
-            TransactionType::AccountGeneration => {
-                Box::new(AccountGeneratorCreator::new(self.txn_factory.clone()))
+    pub async fn start_job(&mut self, mut req: EmitJobRequest) -> Result<EmitJob> {
+        if req.gas_price == 0 {
+            let client = req
+                .rest_clients
+                .iter()
+                .chain(req.endpoint_groups.iter().flat_map(|(_, clients)| clients))
+                .next()
+                .cloned();
+            if let Some(client) = client {
+                // Nodes accept a gas price of 0 unless their gas schedule sets a minimum
+                req = match req.clone().with_gas_price_estimate(&client).await {
+                    Ok(req) => req,
+                    Err(e) => {
+                        warn!("{}, using gas price 0", e);
+                        req
+                    }
+                };
             }
-            TransactionType::NftMint => Box::new(
-                NFTMintGeneratorCreator::new(
-                    self.from_rng(),
-                    self.txn_factory.clone(),
-                    self.root_account,
-                    req.rest_clients[0].clone(),
-                )
-                .await,
-            ),
-        };
-        for client in req.rest_clients {
-            for _ in 0..workers_per_endpoint {
-                let accounts = (&mut all_accounts).take(1).collect();
-                let all_addresses = all_addresses.clone();
-                let stop = stop.clone();
-                let params = req.thread_params.clone();
-                let stats = Arc::clone(&stats);
-
-                let worker = SubmissionWorker::new(
-                    accounts,
-                    client.clone(),
-                    all_addresses,
-                    stop,
-                    params,
-                    stats,
-                    txn_generator_creator.create_transaction_generator(),
-                    req.invalid_transaction_ratio,
-                    self.from_rng(),
-                );
-                let join_handle = tokio_handle.spawn(worker.run(req.gas_price).boxed());
-                workers.push(Worker { join_handle });
+        }
+        for (name, _) in &req.endpoint_groups {
+            if self.groups.iter().all(|group| group.name != *name) {
+                return Err(anyhow!(
+                    "Endpoint group {} has no root account, add it to the emitter first",
+                    name
+                ));
             }
         }
+        let stop = Arc::new(AtomicBool::new(false));
+        let start_time = Instant::now();
+        let rng = &mut self.rng;
+        // Groups are set up concurrently, so that load starts on all networks together
+        let groups = try_join_all(self.groups.iter_mut().filter_map(|group| {
+            let rest_clients = req.group_rest_clients(&group.name)?;
+            Some(group.start_job(
+                &req,
+                rest_clients,
+                stop.clone(),
+                start_time,
+                StdRng::from_rng(&mut *rng).unwrap(),
+            ))
+        }))
+        .await?;
+        if groups.is_empty() {
+            return Err(anyhow!("No rest clients to emit transactions to"));
+        }
         info!("Tx emitter workers started");
-        Ok(EmitJob {
-            workers,
-            stop,
-            stats,
-        })
+        Ok(EmitJob { groups, stop })
     }
 
+    /// Stops all groups of the job, returning the combined stats of all of them.
     pub async fn stop_job(&mut self, job: EmitJob) -> TxnStats {
+        self.stop_job_per_group(job).await.aggregate()
+    }
+
+    /// Stops all groups of the job.  Workers that don't stop within [`WORKER_STOP_TIMEOUT`],
+    /// e.g. because their endpoint is down, are aborted so that one group can't hold up the rest.
+    pub async fn stop_job_per_group(&mut self, job: EmitJob) -> GroupedTxnStats {
         job.stop.store(true, Ordering::Relaxed);
-        for worker in job.workers {
-            let mut accounts = worker
-                .join_handle
-                .await
-                .expect("TxnEmitter worker thread failed");
-            self.accounts.append(&mut accounts);
+        let deadline = time::Instant::now() + WORKER_STOP_TIMEOUT;
+        let mut group_jobs = job.groups;
+        let rng = &mut self.rng;
+        let stopping = self.groups.iter_mut().filter_map(|group| {
+            let index = group_jobs.iter().position(|job| job.name == group.name)?;
+            Some(group.stop_job(
+                group_jobs.swap_remove(index),
+                deadline,
+                StdRng::from_rng(&mut *rng).unwrap(),
+            ))
+        });
+        GroupedTxnStats {
+            groups: join_all(stopping).await,
         }
-        job.stats.accumulate()
+    }
+
+    /// Returns the report of the chain state audit of the last stopped job, if it was audited.
+    pub fn take_audit_report(&mut self) -> Option<AuditReport> {
+        self.default_group().audit_report.take()
+    }
+
+    /// Like [`TxnEmitter::take_audit_report`], for the endpoint group `name`.
+    pub fn take_group_audit_report(&mut self, name: &str) -> Option<AuditReport> {
+        self.groups
+            .iter_mut()
+            .find(|group| group.name == name)
+            .and_then(|group| group.audit_report.take())
     }
 
     pub fn peek_job_stats(&self, job: &EmitJob) -> TxnStats {
-        job.stats.accumulate()
+        self.peek_job_stats_per_group(job).aggregate()
+    }
+
+    pub fn peek_job_stats_per_group(&self, job: &EmitJob) -> GroupedTxnStats {
+        GroupedTxnStats {
+            groups: job
+                .groups
+                .iter()
+                .map(|group| (group.name.clone(), group.stats.accumulate()))
+                .collect(),
+        }
     }
 
     pub async fn periodic_stat(&mut self, job: &EmitJob, duration: Duration, interval_secs: u64) {
@@ -350,12 +630,23 @@ impl<'t> TxnEmitter<'t> {
     }
 
     pub async fn emit_txn_for(&mut self, emit_job_request: EmitJobRequest) -> Result<TxnStats> {
+        Ok(self
+            .emit_txn_for_per_group(emit_job_request)
+            .await?
+            .aggregate())
+    }
+
+    /// Like [`TxnEmitter::emit_txn_for`], returning the stats of each endpoint group.
+    pub async fn emit_txn_for_per_group(
+        &mut self,
+        emit_job_request: EmitJobRequest,
+    ) -> Result<GroupedTxnStats> {
         let duration = emit_job_request.duration;
         let job = self.start_job(emit_job_request).await?;
         info!("Starting emitting txns for {} secs", duration.as_secs());
         time::sleep(duration).await;
         info!("Ran for {} secs, stopping job...", duration.as_secs());
-        let stats = self.stop_job(job).await;
+        let stats = self.stop_job_per_group(job).await;
         info!("Stopped job");
         Ok(stats)
     }
@@ -387,7 +678,7 @@ impl<'t> TxnEmitter<'t> {
                 sender,
                 receiver,
                 num_coins,
-                &self.txn_factory,
+                &self.groups[0].txn_factory,
                 1,
             ))
             .await?;
@@ -396,6 +687,267 @@ impl<'t> TxnEmitter<'t> {
     }
 }
 
+impl<'t> EndpointGroup<'t> {
+    async fn start_job(
+        &mut self,
+        req: &EmitJobRequest,
+        rest_clients: &[RestClient],
+        stop: Arc<AtomicBool>,
+        start_time: Instant,
+        mut rng: StdRng,
+    ) -> Result<GroupJob> {
+        let workers_per_endpoint = req.calculate_workers_per_endpoint(rest_clients.len());
+        let num_accounts = rest_clients.len() * workers_per_endpoint;
+        info!(
+            "Will use {} workers per endpoint for a total of {} endpoint clients in group {}",
+            workers_per_endpoint, num_accounts, self.name
+        );
+        info!("Will create a total of {} accounts", num_accounts);
+        if req.reuse_accounts {
+            self.check_sequence_number_drift(req.max_drift).await;
+        }
+        let audit_before = match &req.audit {
+            Some(_) if req.reuse_accounts => {
+                warn!("Skipping chain state audit, it is not supported with reused accounts");
+                None
+            }
+            Some(_) => Some(ChainSnapshot::fetch(&self.client, self.root_account.address()).await?),
+            None => None,
+        };
+        let mut account_minter =
+            AccountMinter::new(self.root_account, self.txn_factory.clone(), rng.clone());
+        let mut new_accounts = account_minter
+            .mint_accounts(req, rest_clients, num_accounts)
+            .await?;
+        let seed_addresses = account_minter.seed_addresses().to_vec();
+        let seed_funding = account_minter.seed_funding();
+        let created_addresses = account_minter.created_addresses().to_vec();
+        self.accounts.append(&mut new_accounts);
+        let mut all_accounts = self.accounts.split_off(self.accounts.len() - num_accounts);
+        if req.reuse_accounts {
+            self.resync_reused_accounts(&mut all_accounts).await?;
+        }
+        let mut workers = vec![];
+        let all_addresses: Vec<_> = all_accounts.iter().map(|d| d.address()).collect();
+        let audit = match (req.audit.clone(), audit_before) {
+            (Some(config), Some(before)) => Some(PendingAudit {
+                config,
+                before,
+                accounting: EmitterAccounting {
+                    seed_accounts: seed_addresses,
+                    created_accounts: created_addresses,
+                    funded: seed_funding,
+                    // Read from the funder's deposit events when the job is audited
+                    minted: 0,
+                    coins_stay_in_accounts: matches!(req.transaction_type, TransactionType::P2P),
+                },
+            }),
+            _ => None,
+        };
+        let all_addresses = Arc::new(all_addresses);
+        let mut all_accounts = all_accounts.into_iter();
+        let stats = Arc::new(StatsAccumulator {
+            transaction_shape: req.transaction_shape,
+            ..StatsAccumulator::default()
+        });
+        let tokio_handle = Handle::current();
+        let settings = req.current_transaction_settings();
+        let txn_factory = settings.apply(self.txn_factory.clone());
+        let gas_price = req.transaction_gas_price();
+        let txn_generator_creator: Box<dyn TransactionGeneratorCreator> = match req.transaction_type
+        {
+            TransactionType::P2P => Box::new(P2PTransactionGeneratorCreator::new(
+                StdRng::from_rng(&mut rng).unwrap(),
+                txn_factory,
+                SEND_AMOUNT,
+                req.transaction_shape,
+            )),
+            TransactionType::AccountGeneration => Box::new(AccountGeneratorCreator::new(
+                txn_factory,
+                req.transaction_shape,
+            )),
+            TransactionType::NftMint => Box::new(
+                NFTMintGeneratorCreator::new(
+                    StdRng::from_rng(&mut rng).unwrap(),
+                    txn_factory,
+                    self.root_account,
+                    rest_clients[0].clone(),
+                )
+                .await,
+            ),
+            TransactionType::PublishModule => {
+                Box::new(PublishModuleGeneratorCreator::new(txn_factory))
+            }
+        };
+        for client in rest_clients {
+            for _ in 0..workers_per_endpoint {
+                let accounts = (&mut all_accounts).take(1).collect();
+                let all_addresses = all_addresses.clone();
+                let stop = stop.clone();
+                let mut params = req.thread_params.clone();
+                if let Some(expiration_secs) = settings.expiration_secs {
+                    params.txn_expiration_time_secs = expiration_secs;
+                }
+                let stats = Arc::clone(&stats);
+
+                let worker = SubmissionWorker::new(
+                    accounts,
+                    client.clone(),
+                    all_addresses,
+                    stop,
+                    params,
+                    stats,
+                    txn_generator_creator.create_transaction_generator(),
+                    req.invalid_transaction_ratio,
+                    start_time,
+                    StdRng::from_rng(&mut rng).unwrap(),
+                );
+                let join_handle = tokio_handle.spawn(worker.run(gas_price).boxed());
+                workers.push(Worker { join_handle });
+            }
+        }
+        Ok(GroupJob {
+            name: self.name.clone(),
+            workers,
+            stats,
+            audit,
+        })
+    }
+
+    /// Warns about the accounts of earlier jobs whose sequence number on chain drifted from the
+    /// one tracked here by more than `max_drift`, e.g. as their transactions expired before a
+    /// slow node committed them
+    async fn check_sequence_number_drift(&self, max_drift: u64) {
+        if self.accounts.is_empty() {
+            return;
+        }
+        let addresses: Vec<_> = self.accounts.iter().map(LocalAccount::address).collect();
+        let sequence_numbers = match query_sequence_numbers(&self.client, &addresses).await {
+            Ok(sequence_numbers) => sequence_numbers,
+            Err(e) => {
+                warn!(
+                    "Failed to check the sequence numbers of the reused accounts of {}: {:?}",
+                    self.name, e
+                );
+                return;
+            }
+        };
+        let drifted = drifted_accounts(&self.accounts, &sequence_numbers, max_drift);
+        if let Some((address, local, on_chain)) = drifted
+            .iter()
+            .max_by_key(|(_, local, on_chain)| local.abs_diff(*on_chain))
+        {
+            warn!(
+                "{} of {} reused accounts of {} drifted by more than {} from their sequence number on chain, the most {} at {} locally and {} on chain",
+                drifted.len(),
+                self.accounts.len(),
+                self.name,
+                max_drift,
+                address,
+                local,
+                on_chain
+            );
+            SEQUENCE_NUMBER_DRIFT_ACCOUNTS.inc_by(drifted.len() as u64);
+        }
+    }
+
+    /// Sets the sequence numbers of the reused `accounts` to the ones on chain, which moved on if
+    /// transactions were committed after the emitter stopped tracking them, or another emitter
+    /// used the accounts
+    async fn resync_reused_accounts(&self, accounts: &mut [LocalAccount]) -> Result<()> {
+        let addresses: Vec<_> = accounts.iter().map(LocalAccount::address).collect();
+        let sequence_numbers = query_sequence_numbers(&self.client, &addresses).await?;
+        let num_resynced = resync_sequence_numbers(accounts, &sequence_numbers);
+        info!(
+            "Resynced the sequence numbers of {} of {} reused accounts of {} from chain",
+            num_resynced,
+            accounts.len(),
+            self.name
+        );
+        Ok(())
+    }
+
+    async fn stop_job(
+        &mut self,
+        job: GroupJob,
+        deadline: time::Instant,
+        mut rng: StdRng,
+    ) -> (String, TxnStats) {
+        let (mut accounts, num_aborted) = join_workers(job.workers, deadline).await;
+        self.accounts.append(&mut accounts);
+        if num_aborted > 0 {
+            warn!(
+                "Aborted {} workers of endpoint group {} that didn't stop in time",
+                num_aborted, self.name
+            );
+        }
+        if let Some(audit) = job.audit {
+            match audit
+                .run(&self.client, self.root_account.address(), &mut rng)
+                .await
+            {
+                Ok(report) => {
+                    if report.is_clean() {
+                        info!("Chain state audit of {} passed: {}", self.name, report);
+                    } else {
+                        warn!(
+                            "Chain state audit of {} found discrepancies: {}",
+                            self.name, report
+                        );
+                    }
+                    self.audit_report = Some(report);
+                }
+                Err(e) => warn!("Chain state audit of {} failed: {:?}", self.name, e),
+            }
+        }
+        (job.name, job.stats.accumulate())
+    }
+}
+
+/// The accounts whose sequence number on chain differs from the local one by more than
+/// `max_drift`, with their local and on-chain sequence numbers
+fn drifted_accounts(
+    accounts: &[LocalAccount],
+    sequence_numbers: &[u64],
+    max_drift: u64,
+) -> Vec<(AccountAddress, u64, u64)> {
+    zip(accounts, sequence_numbers)
+        .map(|(account, on_chain)| (account.address(), account.sequence_number(), *on_chain))
+        .filter(|(_, local, on_chain)| local.abs_diff(*on_chain) > max_drift)
+        .collect()
+}
+
+/// Sets the sequence number of each account to the one on chain, returning how many changed
+fn resync_sequence_numbers(accounts: &mut [LocalAccount], sequence_numbers: &[u64]) -> usize {
+    let mut num_resynced = 0;
+    for (account, on_chain) in zip(accounts, sequence_numbers) {
+        if account.sequence_number() != *on_chain {
+            *account.sequence_number_mut() = *on_chain;
+            num_resynced += 1;
+        }
+    }
+    num_resynced
+}
+
+/// Waits for the workers to hand back their accounts until `deadline`, then aborts the rest.
+/// Returns the accounts of the workers that stopped, and how many workers were aborted.
+async fn join_workers(workers: Vec<Worker>, deadline: time::Instant) -> (Vec<LocalAccount>, usize) {
+    let mut accounts = vec![];
+    let mut num_aborted = 0;
+    for mut worker in workers {
+        match time::timeout_at(deadline, &mut worker.join_handle).await {
+            Ok(result) => {
+                accounts.append(&mut result.expect("TxnEmitter worker thread failed"));
+            }
+            Err(_) => {
+                worker.join_handle.abort();
+                num_aborted += 1;
+            }
+        }
+    }
+    (accounts, num_aborted)
+}
+
 /// Waits for a single account to catch up to the expected sequence number
 async fn wait_for_single_account_sequence(
     client: &RestClient,
@@ -519,3 +1071,191 @@ pub fn gen_transfer_txn_request(
             .gas_unit_price(gas_price),
     )
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        args::TransactionType,
+        emitter::{
+            drifted_accounts, gen_transfer_txn_request, join_workers, resync_sequence_numbers,
+            DutyCycle, EmissionPhase, EmitJobRequest, TransactionSettings, Worker,
+        },
+        transaction_generator::{
+            publish_module::PublishModuleGeneratorCreator, TransactionGeneratorCreator,
+        },
+    };
+    use aptos_sdk::{
+        transaction_builder::TransactionFactory,
+        types::{chain_id::ChainId, LocalAccount},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{sync::Arc, time::Duration};
+    use tokio::time;
+
+    #[test]
+    pub fn test_transaction_settings_of_type() {
+        let publish_settings = TransactionSettings {
+            gas_unit_price: Some(200),
+            max_gas_amount: Some(100_000),
+            expiration_secs: Some(600),
+        };
+        let req = EmitJobRequest::default()
+            .gas_price(100)
+            .transaction_type(TransactionType::PublishModule)
+            .transaction_settings(TransactionType::PublishModule, publish_settings);
+        let base_factory = TransactionFactory::new(ChainId::test()).with_max_gas_amount(4_000);
+
+        let txn_factory = req
+            .current_transaction_settings()
+            .apply(base_factory.clone());
+        let mut generator =
+            PublishModuleGeneratorCreator::new(txn_factory).create_transaction_generator();
+        let mut account = LocalAccount::generate(&mut StdRng::from_seed([0; 32]));
+        let txns = generator.generate_transactions(
+            vec![&mut account],
+            1,
+            Arc::new(vec![]),
+            0,
+            req.transaction_gas_price(),
+        );
+        assert_eq!(txns.len(), 1);
+        assert_eq!(txns[0].max_gas_amount(), 100_000);
+        assert_eq!(txns[0].gas_unit_price(), 200);
+
+        // Types without settings keep the job's gas price and the factory's max gas
+        let req = req.transaction_type(TransactionType::P2P);
+        assert_eq!(
+            req.current_transaction_settings(),
+            TransactionSettings::default()
+        );
+        assert_eq!(req.transaction_gas_price(), 100);
+        let txn = account.sign_with_transaction_builder(
+            req.current_transaction_settings()
+                .apply(base_factory)
+                .transfer(account.address(), 1),
+        );
+        assert_eq!(txn.max_gas_amount(), 4_000);
+    }
+
+    #[test]
+    pub fn test_duty_cycle_phase() {
+        let duty_cycle = DutyCycle::new(Duration::from_secs(10), Duration::from_secs(50));
+        assert_eq!(
+            duty_cycle.phase_at(Duration::from_secs(0)),
+            (EmissionPhase::Burst, Duration::from_secs(10))
+        );
+        assert_eq!(
+            duty_cycle.phase_at(Duration::from_secs(15)),
+            (EmissionPhase::Idle, Duration::from_secs(45))
+        );
+        assert_eq!(
+            duty_cycle.phase_at(Duration::from_secs(64)),
+            (EmissionPhase::Burst, Duration::from_secs(6))
+        );
+    }
+
+    #[test]
+    pub fn test_duty_cycle_without_idle_is_always_burst() {
+        let duty_cycle = DutyCycle::new(Duration::from_secs(10), Duration::from_secs(0));
+        assert_eq!(
+            duty_cycle.phase_at(Duration::from_secs(25)).0,
+            EmissionPhase::Burst
+        );
+        assert_eq!(
+            duty_cycle.burst_time_within(Duration::from_secs(25)),
+            Duration::from_secs(25)
+        );
+    }
+
+    #[test]
+    pub fn test_duty_cycle_burst_time() {
+        let duty_cycle = DutyCycle::new(Duration::from_secs(10), Duration::from_secs(50));
+        assert_eq!(
+            duty_cycle.burst_time_within(Duration::from_secs(125)),
+            Duration::from_secs(25)
+        );
+        assert_eq!(
+            duty_cycle.burst_time_within(Duration::from_secs(180)),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_join_workers_aborts_wedged_workers() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let account = LocalAccount::generate(&mut rng);
+        let address = account.address();
+        let workers = vec![
+            Worker {
+                join_handle: tokio::spawn(async move { vec![account] }),
+            },
+            // A worker stuck on an endpoint that never answers
+            Worker {
+                join_handle: tokio::spawn(futures::future::pending()),
+            },
+        ];
+
+        let deadline = time::Instant::now() + Duration::from_millis(100);
+        let (accounts, num_aborted) = join_workers(workers, deadline).await;
+        assert_eq!(num_aborted, 1);
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].address(), address);
+    }
+
+    #[test]
+    fn test_drifted_accounts() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let accounts: Vec<_> = [10, 20, 30]
+            .iter()
+            .map(|sequence_number| {
+                let mut account = LocalAccount::generate(&mut rng);
+                *account.sequence_number_mut() = *sequence_number;
+                account
+            })
+            .collect();
+
+        // Expired transactions leave the chain behind, others submitting from the account ahead
+        let sequence_numbers = [10, 15, 33];
+        assert_eq!(
+            drifted_accounts(&accounts, &sequence_numbers, 2),
+            vec![
+                (accounts[1].address(), 20, 15),
+                (accounts[2].address(), 30, 33),
+            ]
+        );
+        assert_eq!(
+            drifted_accounts(&accounts, &sequence_numbers, 4),
+            vec![(accounts[1].address(), 20, 15)]
+        );
+        assert!(drifted_accounts(&accounts, &sequence_numbers, 5).is_empty());
+    }
+
+    #[test]
+    fn test_resync_sequence_numbers() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let mut accounts: Vec<_> = [5, 20]
+            .iter()
+            .map(|sequence_number| {
+                let mut account = LocalAccount::generate(&mut rng);
+                *account.sequence_number_mut() = *sequence_number;
+                account
+            })
+            .collect();
+
+        // The first account submitted more transactions since the emitter last tracked it
+        assert_eq!(resync_sequence_numbers(&mut accounts, &[8, 20]), 1);
+        assert_eq!(accounts[0].sequence_number(), 8);
+        assert_eq!(accounts[1].sequence_number(), 20);
+
+        let receiver = accounts[1].address();
+        let txn = gen_transfer_txn_request(
+            &mut accounts[0],
+            &receiver,
+            1,
+            &TransactionFactory::new(ChainId::test()),
+            1,
+        );
+        assert_eq!(txn.sequence_number(), 8);
+        assert_eq!(resync_sequence_numbers(&mut accounts, &[9, 20]), 0);
+    }
+}
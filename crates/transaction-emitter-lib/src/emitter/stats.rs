@@ -1,23 +1,57 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::transaction_generator::TransactionShape;
+use hdrhistogram::Histogram;
 use std::{
     fmt,
-    ops::Sub,
+    ops::{Add, Sub},
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     time::Duration,
 };
 
-#[derive(Debug, Default)]
+/// Significant figures the latency histogram keeps for each recorded latency
+const LATENCY_HISTOGRAM_SIGFIG: u8 = 3;
+
+/// A histogram of latencies in ms, growing as larger latencies are recorded
+pub fn new_latency_histogram() -> Histogram<u64> {
+    Histogram::new(LATENCY_HISTOGRAM_SIGFIG).expect("Valid number of significant figures")
+}
+
+#[derive(Debug)]
 pub struct TxnStats {
     pub submitted: u64,
     pub committed: u64,
     pub expired: u64,
     pub latency: u64,
     pub latency_buckets: AtomicHistogramSnapshot,
+    /// Latencies of committed transactions, in ms
+    pub latency_histogram: Histogram<u64>,
+    /// Transactions submitted during a burst phase, whenever they were checked
+    pub burst_submitted: u64,
+    /// Committed transactions that were submitted during a burst phase
+    pub burst_committed: u64,
+    /// The shape of the emitted transactions
+    pub transaction_shape: TransactionShape,
+}
+
+impl Default for TxnStats {
+    fn default() -> Self {
+        Self {
+            submitted: 0,
+            committed: 0,
+            expired: 0,
+            latency: 0,
+            latency_buckets: AtomicHistogramSnapshot::default(),
+            latency_histogram: new_latency_histogram(),
+            burst_submitted: 0,
+            burst_committed: 0,
+            transaction_shape: TransactionShape::default(),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -40,6 +74,22 @@ impl fmt::Display for TxnStatsRate {
 }
 
 impl TxnStats {
+    pub fn p50_latency_ms(&self) -> u64 {
+        self.latency_histogram.value_at_quantile(0.50)
+    }
+
+    pub fn p95_latency_ms(&self) -> u64 {
+        self.latency_histogram.value_at_quantile(0.95)
+    }
+
+    pub fn p99_latency_ms(&self) -> u64 {
+        self.latency_histogram.value_at_quantile(0.99)
+    }
+
+    pub fn mean_latency_ms(&self) -> f64 {
+        self.latency_histogram.mean()
+    }
+
     pub fn rate(&self, window: Duration) -> TxnStatsRate {
         let mut window_secs = window.as_secs();
         if window_secs < 1 {
@@ -57,14 +107,28 @@ impl TxnStats {
             p99_latency: self.latency_buckets.percentile(99, 100),
         }
     }
+
+    /// Like `rate`, but only counting transactions submitted during burst phases,
+    /// over `burst_window` (the time spent bursting).
+    pub fn burst_rate(&self, burst_window: Duration) -> TxnStatsRate {
+        let mut window_secs = burst_window.as_secs();
+        if window_secs < 1 {
+            window_secs = 1;
+        }
+        TxnStatsRate {
+            submitted: self.burst_submitted / window_secs,
+            committed: self.burst_committed / window_secs,
+            ..self.rate(burst_window)
+        }
+    }
 }
 
 impl fmt::Display for TxnStats {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "submitted: {}, committed: {}, expired: {}",
-            self.submitted, self.committed, self.expired,
+            "submitted: {}, committed: {}, expired: {}, {}",
+            self.submitted, self.committed, self.expired, self.transaction_shape,
         )
     }
 }
@@ -79,20 +143,121 @@ impl Sub for &TxnStats {
             expired: self.expired - other.expired,
             latency: self.latency - other.latency,
             latency_buckets: &self.latency_buckets - &other.latency_buckets,
+            latency_histogram: {
+                let mut histogram = self.latency_histogram.clone();
+                histogram
+                    .subtract(&other.latency_histogram)
+                    .expect("Earlier stats must be included in later ones");
+                histogram
+            },
+            burst_submitted: self.burst_submitted - other.burst_submitted,
+            burst_committed: self.burst_committed - other.burst_committed,
+            transaction_shape: self.transaction_shape,
         }
     }
 }
 
+impl Add for &TxnStats {
+    type Output = TxnStats;
+
+    fn add(self, other: &TxnStats) -> TxnStats {
+        TxnStats {
+            submitted: self.submitted + other.submitted,
+            committed: self.committed + other.committed,
+            expired: self.expired + other.expired,
+            latency: self.latency + other.latency,
+            latency_buckets: &self.latency_buckets + &other.latency_buckets,
+            latency_histogram: {
+                let mut histogram = self.latency_histogram.clone();
+                histogram
+                    .add(&other.latency_histogram)
+                    .expect("Latency histogram grows as needed");
+                histogram
+            },
+            burst_submitted: self.burst_submitted + other.burst_submitted,
+            burst_committed: self.burst_committed + other.burst_committed,
+            // The groups of a job share its shape, sums of them start from the default stats
+            transaction_shape: if self.transaction_shape == TransactionShape::default() {
+                other.transaction_shape
+            } else {
+                self.transaction_shape
+            },
+        }
+    }
+}
+
+/// Stats of a job for each of its endpoint groups
 #[derive(Debug, Default)]
+pub struct GroupedTxnStats {
+    pub groups: Vec<(String, TxnStats)>,
+}
+
+impl GroupedTxnStats {
+    pub fn get(&self, name: &str) -> Option<&TxnStats> {
+        self.groups
+            .iter()
+            .find(|(group, _)| group == name)
+            .map(|(_, stats)| stats)
+    }
+
+    /// The stats of all groups combined
+    pub fn aggregate(&self) -> TxnStats {
+        self.groups
+            .iter()
+            .fold(TxnStats::default(), |total, (_, stats)| &total + stats)
+    }
+}
+
+impl fmt::Display for GroupedTxnStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, stats) in &self.groups {
+            writeln!(f, "{}: {}", name, stats)?;
+        }
+        write!(f, "total: {}", self.aggregate())
+    }
+}
+
+#[derive(Debug)]
 pub struct StatsAccumulator {
     pub submitted: AtomicU64,
     pub committed: AtomicU64,
     pub expired: AtomicU64,
     pub latency: AtomicU64,
     pub latencies: Arc<AtomicHistogramAccumulator>,
+    pub latency_histogram: Arc<Mutex<Histogram<u64>>>,
+    pub burst_submitted: AtomicU64,
+    pub burst_committed: AtomicU64,
+    pub transaction_shape: TransactionShape,
+}
+
+impl Default for StatsAccumulator {
+    fn default() -> Self {
+        Self {
+            submitted: AtomicU64::default(),
+            committed: AtomicU64::default(),
+            expired: AtomicU64::default(),
+            latency: AtomicU64::default(),
+            latencies: Arc::default(),
+            latency_histogram: Arc::new(Mutex::new(new_latency_histogram())),
+            burst_submitted: AtomicU64::default(),
+            burst_committed: AtomicU64::default(),
+            transaction_shape: TransactionShape::default(),
+        }
+    }
 }
 
 impl StatsAccumulator {
+    /// Records `count` transactions committed `latency` ms after they were submitted
+    pub fn record_latency(&self, latency: u64, count: u64) {
+        self.latency.fetch_add(latency * count, Ordering::Relaxed);
+        self.latencies.record_data_point(latency, count);
+        self.latency_histogram
+            .lock()
+            .unwrap()
+            .record_n(latency, count)
+            .expect("Latency histogram grows as needed");
+    }
+
     pub fn accumulate(&self) -> TxnStats {
         TxnStats {
             submitted: self.submitted.load(Ordering::Relaxed),
@@ -100,6 +265,10 @@ impl StatsAccumulator {
             expired: self.expired.load(Ordering::Relaxed),
             latency: self.latency.load(Ordering::Relaxed),
             latency_buckets: self.latencies.snapshot(),
+            latency_histogram: self.latency_histogram.lock().unwrap().clone(),
+            burst_submitted: self.burst_submitted.load(Ordering::Relaxed),
+            burst_committed: self.burst_committed.load(Ordering::Relaxed),
+            transaction_shape: self.transaction_shape,
         }
     }
 }
@@ -195,6 +364,31 @@ impl Sub for &AtomicHistogramSnapshot {
     }
 }
 
+impl Add for &AtomicHistogramSnapshot {
+    type Output = AtomicHistogramSnapshot;
+
+    fn add(self, other: &AtomicHistogramSnapshot) -> AtomicHistogramSnapshot {
+        assert_eq!(
+            self.buckets.len(),
+            other.buckets.len(),
+            "Histogram snapshots must have same size, left: {}, right: {}",
+            self.buckets.len(),
+            other.buckets.len()
+        );
+        let buckets = self
+            .buckets
+            .iter()
+            .zip(&other.buckets)
+            .map(|(left, right)| left + right)
+            .collect();
+        AtomicHistogramSnapshot {
+            capacity: self.capacity,
+            step_width: self.step_width,
+            buckets,
+        }
+    }
+}
+
 impl AtomicHistogramSnapshot {
     pub fn percentile(&self, numerator: u64, denominator: u64) -> u64 {
         let committed: u64 = self.buckets.iter().sum();
@@ -212,9 +406,13 @@ impl AtomicHistogramSnapshot {
 
 #[cfg(test)]
 mod test {
-    use crate::emitter::stats::{
-        AtomicHistogramAccumulator, AtomicHistogramSnapshot, TxnStats, DEFAULT_HISTOGRAM_CAPACITY,
-        DEFAULT_HISTOGRAM_STEP_WIDTH,
+    use crate::{
+        emitter::stats::{
+            new_latency_histogram, AtomicHistogramAccumulator, AtomicHistogramSnapshot,
+            GroupedTxnStats, StatsAccumulator, TxnStats, DEFAULT_HISTOGRAM_CAPACITY,
+            DEFAULT_HISTOGRAM_STEP_WIDTH,
+        },
+        transaction_generator::TransactionShape,
     };
 
     #[test]
@@ -271,8 +469,78 @@ mod test {
             expired: 0,
             latency: 0,
             latency_buckets: histogram.snapshot(),
+            latency_histogram: new_latency_histogram(),
+            burst_submitted: 0,
+            burst_committed: 0,
+            transaction_shape: TransactionShape::default(),
         };
         let res = stat.latency_buckets.percentile(9, 10);
         assert_eq!(res, 900);
     }
+
+    #[test]
+    pub fn test_latency_histogram() {
+        let stats = StatsAccumulator::default();
+        // 100 commits, with latencies of 1ms, 2ms ... 100ms
+        for i in 1..101 {
+            stats.record_latency(i, 1);
+        }
+        let before = stats.accumulate();
+        assert_eq!(before.p50_latency_ms(), 50);
+        assert_eq!(before.p95_latency_ms(), 95);
+        assert_eq!(before.p99_latency_ms(), 99);
+        assert_eq!(before.mean_latency_ms(), 50.5);
+
+        // Deltas only include the latencies recorded in between
+        stats.record_latency(1000, 100);
+        let delta = &stats.accumulate() - &before;
+        assert_eq!(delta.latency_histogram.len(), 100);
+        assert_eq!(delta.p50_latency_ms(), 1000);
+    }
+
+    #[test]
+    pub fn test_aggregate_group_stats() {
+        let stats = |committed: u64, latency: u64| {
+            let histogram = AtomicHistogramAccumulator::default();
+            histogram.record_data_point(latency, committed);
+            TxnStats {
+                submitted: committed * 2,
+                committed,
+                expired: 1,
+                latency: latency * committed,
+                latency_buckets: histogram.snapshot(),
+                latency_histogram: {
+                    let mut histogram = new_latency_histogram();
+                    histogram.record_n(latency, committed).unwrap();
+                    histogram
+                },
+                burst_submitted: 0,
+                burst_committed: 0,
+                transaction_shape: TransactionShape {
+                    padding_bytes: 10,
+                    fan_out: 2,
+                },
+            }
+        };
+        let grouped = GroupedTxnStats {
+            groups: vec![
+                ("old".to_string(), stats(10, 100)),
+                ("new".to_string(), stats(30, 500)),
+            ],
+        };
+        assert_eq!(grouped.get("new").unwrap().committed, 30);
+        assert!(grouped.get("missing").is_none());
+
+        let total = grouped.aggregate();
+        assert_eq!(total.submitted, 80);
+        assert_eq!(total.committed, 40);
+        assert_eq!(total.expired, 2);
+        assert_eq!(total.latency, 10 * 100 + 30 * 500);
+        assert_eq!(total.latency_buckets.percentile(1, 4), 100);
+        assert_eq!(total.latency_buckets.percentile(1, 2), 500);
+        assert_eq!(total.p50_latency_ms(), 500);
+        assert_eq!(total.latency_histogram.len(), 40);
+        assert_eq!(total.transaction_shape.padding_bytes, 10);
+        assert_eq!(total.transaction_shape.fan_out, 2);
+    }
 }
@@ -3,7 +3,7 @@
 
 use crate::{
     emitter::{
-        stats::StatsAccumulator, wait_for_accounts_sequence, MAX_TXN_BATCH_SIZE,
+        stats::StatsAccumulator, wait_for_accounts_sequence, EmissionPhase, MAX_TXN_BATCH_SIZE,
         TRANSACTIONS_PER_ACCOUNT, TXN_EXPIRATION_SECONDS,
     },
     transaction_generator::TransactionGenerator,
@@ -30,6 +30,9 @@ use std::sync::atomic::AtomicU64;
 use std::{sync::Arc, time::Instant};
 use tokio::time::sleep;
 
+/// How often an idle worker re-checks whether it should resume or stop.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Debug)]
 pub struct SubmissionWorker {
     pub(crate) accounts: Vec<LocalAccount>,
@@ -40,6 +43,8 @@ pub struct SubmissionWorker {
     stats: Arc<StatsAccumulator>,
     txn_generator: Box<dyn TransactionGenerator>,
     invalid_transaction_ratio: usize,
+    start_time: Instant,
+    phase_offset: Duration,
     rng: ::rand::rngs::StdRng,
 }
 
@@ -58,8 +63,15 @@ impl SubmissionWorker {
         stats: Arc<StatsAccumulator>,
         txn_generator: Box<dyn TransactionGenerator>,
         invalid_transaction_ratio: usize,
-        rng: ::rand::rngs::StdRng,
+        start_time: Instant,
+        mut rng: ::rand::rngs::StdRng,
     ) -> Self {
+        let phase_offset = match params.duty_cycle.and_then(|duty_cycle| duty_cycle.jitter) {
+            Some(jitter) if !jitter.is_zero() => {
+                Duration::from_millis(rng.gen_range(0, jitter.as_millis() as u64))
+            }
+            _ => Duration::ZERO,
+        };
         Self {
             accounts,
             client,
@@ -69,6 +81,8 @@ impl SubmissionWorker {
             stats,
             txn_generator,
             invalid_transaction_ratio,
+            start_time,
+            phase_offset,
             rng,
         }
     }
@@ -88,13 +102,45 @@ impl SubmissionWorker {
 
         let wait_duration = Duration::from_millis(self.params.wait_millis);
 
-        let start_time = Instant::now();
+        let mut unchecked_since = Instant::now();
         let mut total_num_requests = 0;
+        let mut total_num_burst_requests = 0;
 
         while !self.stop.load(Ordering::Relaxed) {
+            if let (EmissionPhase::Idle, phase_remaining) = self.current_phase() {
+                // Nothing is submitted while idle, but transactions still outstanding
+                // from the previous burst are checked for commit status.
+                if check_stats_at_end && total_num_requests > 0 {
+                    self.update_stats(
+                        unchecked_since,
+                        0,
+                        total_num_requests,
+                        total_num_burst_requests,
+                        true,
+                        min(phase_remaining, wait_for_accounts_sequence_timeout),
+                    )
+                    .await;
+                    unchecked_since = Instant::now();
+                    total_num_requests = 0;
+                    total_num_burst_requests = 0;
+                }
+                self.sleep_while_idle().await;
+                continue;
+            }
+
             let requests = self.gen_requests(gas_price);
             let num_requests = requests.len();
             total_num_requests += num_requests;
+            // The requests belong to the phase they are submitted in, even if generating them
+            // ran past the end of the burst, or they are only checked for commit while idle
+            let num_burst_requests = match self.current_phase().0 {
+                EmissionPhase::Burst => num_requests,
+                EmissionPhase::Idle => 0,
+            };
+            total_num_burst_requests += num_burst_requests;
+            self.stats
+                .burst_submitted
+                .fetch_add(num_burst_requests as u64, Ordering::Relaxed);
             let loop_start_time = Arc::new(Instant::now());
             let wait_until = *loop_start_time + wait_duration;
             let txn_offset_time = Arc::new(AtomicU64::new(0));
@@ -122,6 +168,7 @@ impl SubmissionWorker {
                     loop_start_time,
                     txn_offset_time.load(Ordering::Relaxed),
                     num_requests,
+                    num_burst_requests,
                     false,
                     wait_for_accounts_sequence_timeout,
                 )
@@ -135,12 +182,13 @@ impl SubmissionWorker {
 
         // If this was a burst mode run and the user didn't specifically opt
         // out of it, update the stats for the whole run.
-        if check_stats_at_end {
+        if check_stats_at_end && total_num_requests > 0 {
             debug!("Checking stats for final time at the end");
             self.update_stats(
-                start_time,
+                unchecked_since,
                 0,
                 total_num_requests,
+                total_num_burst_requests,
                 true,
                 Duration::from_millis(500),
             )
@@ -150,6 +198,30 @@ impl SubmissionWorker {
         self.accounts
     }
 
+    /// Returns the current emission phase of this worker, and how much of it is left.
+    /// Without a duty cycle, workers are always bursting.
+    fn current_phase(&self) -> (EmissionPhase, Duration) {
+        match &self.params.duty_cycle {
+            Some(duty_cycle) => {
+                duty_cycle.phase_at(self.start_time.elapsed().saturating_sub(self.phase_offset))
+            }
+            None => (EmissionPhase::Burst, Duration::MAX),
+        }
+    }
+
+    /// Sleeps until the idle phase is over, waking up periodically so that
+    /// stopping the job doesn't wait for the next burst.
+    async fn sleep_while_idle(&self) {
+        while !self.stop.load(Ordering::Relaxed) {
+            match self.current_phase() {
+                (EmissionPhase::Idle, remaining) => {
+                    sleep(min(remaining, IDLE_CHECK_INTERVAL)).await
+                }
+                (EmissionPhase::Burst, _) => return,
+            }
+        }
+    }
+
     /// This function assumes that num_requests == num_accounts, which is
     /// precisely how gen_requests works. If this changes, this code will
     /// need to be fixed.
@@ -157,11 +229,16 @@ impl SubmissionWorker {
     /// Note, the latency values are not accurate if --check-stats-at-end
     /// is used. There is no easy way around this accurately. As such, we
     /// don't update latency at all if that flag is set.
+    ///
+    /// `num_burst_requests` of the requests were submitted during a burst
+    /// phase. Those were submitted before any others since the last check,
+    /// so they are the first to commit.
     async fn update_stats(
         &mut self,
         start_time: Instant,
         txn_offset_time: u64,
         num_requests: usize,
+        num_burst_requests: usize,
         skip_latency_stats: bool,
         wait_for_accounts_sequence_timeout: Duration,
     ) {
@@ -179,13 +256,11 @@ impl SubmissionWorker {
                 self.stats
                     .committed
                     .fetch_add(num_requests as u64, Ordering::Relaxed);
+                self.stats
+                    .burst_committed
+                    .fetch_add(num_burst_requests as u64, Ordering::Relaxed);
                 if !skip_latency_stats {
-                    self.stats
-                        .latency
-                        .fetch_add(latency * num_requests as u64, Ordering::Relaxed);
-                    self.stats
-                        .latencies
-                        .record_data_point(latency, num_requests as u64);
+                    self.stats.record_latency(latency, num_requests as u64);
                 }
             }
             Err(uncommitted) => {
@@ -198,20 +273,18 @@ impl SubmissionWorker {
                 // (end_time - txn_offset_time / num_requests) * num_committed
                 let latency = (Instant::now() - start_time).as_millis() as u64
                     - txn_offset_time / num_requests as u64;
-                let committed_latency = latency * num_committed as u64;
                 self.stats
                     .committed
                     .fetch_add(num_committed, Ordering::Relaxed);
+                self.stats.burst_committed.fetch_add(
+                    min(num_committed, num_burst_requests as u64),
+                    Ordering::Relaxed,
+                );
                 self.stats
                     .expired
                     .fetch_add(num_uncommitted, Ordering::Relaxed);
                 if !skip_latency_stats {
-                    self.stats
-                        .latency
-                        .fetch_add(committed_latency, Ordering::Relaxed);
-                    self.stats
-                        .latencies
-                        .record_data_point(latency, num_committed);
+                    self.stats.record_latency(latency, num_committed);
                 }
                 sample!(
                     SampleRate::Duration(Duration::from_secs(60)),
@@ -18,8 +18,11 @@ pub use wrappers::emit_transactions;
 // We export these if you want finer grained control.
 pub use cluster::Cluster;
 pub use emitter::{
+    audit::{AuditConfig, AuditReport},
     query_sequence_numbers,
-    stats::{TxnStats, TxnStatsRate},
-    EmitJob, EmitJobRequest, EmitThreadParams, TxnEmitter,
+    stats::{GroupedTxnStats, TxnStats, TxnStatsRate},
+    DutyCycle, EmissionPhase, EmitJob, EmitJobRequest, EmitThreadParams, TransactionSettings,
+    TxnEmitter, DEFAULT_ENDPOINT_GROUP,
 };
+pub use transaction_generator::TransactionShape;
 pub use wrappers::emit_transactions_with_cluster;
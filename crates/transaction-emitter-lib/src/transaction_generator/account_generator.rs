@@ -1,9 +1,11 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
-use crate::transaction_generator::{TransactionGenerator, TransactionGeneratorCreator};
+use crate::transaction_generator::{
+    TransactionGenerator, TransactionGeneratorCreator, TransactionShape,
+};
 use aptos_sdk::{
     move_types::account_address::AccountAddress,
-    transaction_builder::{aptos_stdlib, TransactionFactory},
+    transaction_builder::TransactionFactory,
     types::{transaction::SignedTransaction, LocalAccount},
 };
 use rand::prelude::StdRng;
@@ -16,26 +18,28 @@ use std::{fmt::Debug, sync::Arc};
 pub struct AccountGenerator {
     rng: StdRng,
     txn_factory: TransactionFactory,
+    shape: TransactionShape,
 }
 
 impl AccountGenerator {
-    pub fn new(rng: StdRng, txn_factory: TransactionFactory) -> Self {
-        Self { rng, txn_factory }
+    pub fn new(rng: StdRng, txn_factory: TransactionFactory, shape: TransactionShape) -> Self {
+        Self {
+            rng,
+            txn_factory,
+            shape,
+        }
     }
 
     fn gen_single_txn(
         &self,
         from: &mut LocalAccount,
-        to: AccountAddress,
+        to: &[AccountAddress],
         _num_coins: u64,
         txn_factory: &TransactionFactory,
         gas_price: u64,
     ) -> SignedTransaction {
-        from.sign_with_transaction_builder(
-            txn_factory
-                .payload(aptos_stdlib::account_create_account(to))
-                .gas_unit_price(gas_price),
-        )
+        let payload = self.shape.create_account_payload(to);
+        from.sign_with_transaction_builder(txn_factory.payload(payload).gas_unit_price(gas_price))
     }
 }
 
@@ -51,9 +55,11 @@ impl TransactionGenerator for AccountGenerator {
         let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
         for account in accounts {
             for _ in 0..transactions_per_account {
-                let receiver = LocalAccount::generate(&mut self.rng).address();
+                let receivers: Vec<_> = (0..self.shape.fan_out)
+                    .map(|_| LocalAccount::generate(&mut self.rng).address())
+                    .collect();
                 let request =
-                    self.gen_single_txn(account, receiver, 0, &self.txn_factory, gas_price);
+                    self.gen_single_txn(account, &receivers, 0, &self.txn_factory, gas_price);
                 requests.push(request);
             }
         }
@@ -64,11 +70,12 @@ impl TransactionGenerator for AccountGenerator {
 #[derive(Debug)]
 pub struct AccountGeneratorCreator {
     txn_factory: TransactionFactory,
+    shape: TransactionShape,
 }
 
 impl AccountGeneratorCreator {
-    pub fn new(txn_factory: TransactionFactory) -> Self {
-        Self { txn_factory }
+    pub fn new(txn_factory: TransactionFactory, shape: TransactionShape) -> Self {
+        Self { txn_factory, shape }
     }
 }
 
@@ -77,6 +84,7 @@ impl TransactionGeneratorCreator for AccountGeneratorCreator {
         Box::new(AccountGenerator::new(
             StdRng::from_seed(OsRng.gen()),
             self.txn_factory.clone(),
+            self.shape,
         ))
     }
 }
@@ -1,15 +1,36 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::{bail, Result};
+use aptos::move_tool::MAX_PUBLISH_PACKAGE_SIZE;
 use aptos_sdk::{
-    move_types::account_address::AccountAddress,
-    types::{transaction::SignedTransaction, LocalAccount},
+    move_types::{
+        account_address::AccountAddress, identifier::Identifier, language_storage::TypeTag,
+    },
+    transaction_builder::aptos_stdlib,
+    types::{
+        transaction::{Script, SignedTransaction, TransactionArgument, TransactionPayload},
+        utility_coin::APTOS_COIN_TYPE,
+        LocalAccount,
+    },
+};
+use move_deps::move_binary_format::file_format::{
+    empty_script, AbilitySet, AddressIdentifierIndex, Bytecode, FunctionHandle,
+    FunctionHandleIndex, FunctionInstantiation, FunctionInstantiationIndex, IdentifierIndex,
+    ModuleHandle, ModuleHandleIndex, Signature, SignatureIndex, SignatureToken,
+};
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    iter,
+    sync::{Arc, Mutex},
 };
-use std::{fmt::Debug, sync::Arc};
 
 pub mod account_generator;
 pub mod nft_mint;
 pub mod p2p_transaction_generator;
+pub mod publish_module;
 
 pub trait TransactionGenerator: Debug + Sync + Send {
     fn generate_transactions(
@@ -25,3 +46,366 @@ pub trait TransactionGenerator: Debug + Sync + Send {
 pub trait TransactionGeneratorCreator: Debug {
     fn create_transaction_generator(&self) -> Box<dyn TransactionGenerator>;
 }
+
+/// Most recipients one transaction can fan out to. The arguments of the script, the signer,
+/// the recipients, the amount and the padding, are locals indexed by a `u8`.
+pub const MAX_FAN_OUT: usize = u8::MAX as usize - 3;
+
+/// Makes the generated transactions bigger than their own payload, to investigate the
+/// performance of larger transactions
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TransactionShape {
+    /// Bytes of padding passed to each transaction, none if 0
+    pub padding_bytes: usize,
+    /// Distinct accounts written to by each transaction
+    pub fan_out: usize,
+}
+
+impl Default for TransactionShape {
+    fn default() -> Self {
+        Self {
+            padding_bytes: 0,
+            fan_out: 1,
+        }
+    }
+}
+
+impl TransactionShape {
+    /// Checks the transactions can be submitted, before starting to emit them
+    pub fn validate(&self) -> Result<()> {
+        // Publishing transactions are the largest ones, the same limit keeps padded ones accepted
+        if self.padding_bytes > MAX_PUBLISH_PACKAGE_SIZE {
+            bail!(
+                "Padding of {} bytes exceeds the maximum of {} bytes per transaction",
+                self.padding_bytes,
+                MAX_PUBLISH_PACKAGE_SIZE
+            );
+        }
+        if self.fan_out == 0 || self.fan_out > MAX_FAN_OUT {
+            bail!(
+                "Fan-out of {} is not between 1 and {}",
+                self.fan_out,
+                MAX_FAN_OUT
+            );
+        }
+        Ok(())
+    }
+
+    /// The payload transferring `amount` coins to each of `to`
+    pub fn transfer_payload(&self, to: &[AccountAddress], amount: u64) -> TransactionPayload {
+        match to {
+            [to] if self.padding_bytes == 0 => aptos_stdlib::aptos_coin_transfer(*to, amount),
+            _ => self.script_payload(
+                transfer_script(to.len()),
+                vec![APTOS_COIN_TYPE.clone()],
+                to,
+                vec![TransactionArgument::U64(amount)],
+            ),
+        }
+    }
+
+    /// The payload creating each of the accounts `to`
+    pub fn create_account_payload(&self, to: &[AccountAddress]) -> TransactionPayload {
+        match to {
+            [to] if self.padding_bytes == 0 => aptos_stdlib::account_create_account(*to),
+            _ => self.script_payload(create_account_script(to.len()), vec![], to, vec![]),
+        }
+    }
+
+    /// The payload running `script` with the recipients `to`, then `args`, then the padding
+    ///
+    /// Entry functions can't take extra arguments or be called several times by one transaction,
+    /// so padded and fanned out transactions call them from a script taking the padding as its
+    /// last, unused, argument.
+    fn script_payload(
+        &self,
+        script: Vec<u8>,
+        ty_args: Vec<TypeTag>,
+        to: &[AccountAddress],
+        args: Vec<TransactionArgument>,
+    ) -> TransactionPayload {
+        let args = to
+            .iter()
+            .map(|to| TransactionArgument::Address(*to))
+            .chain(args)
+            .chain([TransactionArgument::U8Vector(vec![0; self.padding_bytes])])
+            .collect();
+        TransactionPayload::Script(Script::new(script, ty_args, args))
+    }
+}
+
+impl fmt::Display for TransactionShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "padding: {} bytes, fan-out: {}",
+            self.padding_bytes, self.fan_out
+        )
+    }
+}
+
+/// `0x1::coin::transfer<CoinType>(&signer, address, u64)` to each of `fan_out` recipients, with
+/// padding
+fn transfer_script(fan_out: usize) -> Vec<u8> {
+    static SCRIPTS: Lazy<Mutex<HashMap<usize, Vec<u8>>>> = Lazy::new(Default::default);
+    SCRIPTS
+        .lock()
+        .unwrap()
+        .entry(fan_out)
+        .or_insert_with(|| {
+            fan_out_script_bytecode(
+                "coin",
+                "transfer",
+                vec![SignatureToken::Reference(Box::new(SignatureToken::Signer))],
+                vec![SignatureToken::U64],
+                true,
+                fan_out,
+            )
+        })
+        .clone()
+}
+
+/// `0x1::account::create_account(address)` for each of `fan_out` accounts, with padding
+fn create_account_script(fan_out: usize) -> Vec<u8> {
+    static SCRIPTS: Lazy<Mutex<HashMap<usize, Vec<u8>>>> = Lazy::new(Default::default);
+    SCRIPTS
+        .lock()
+        .unwrap()
+        .entry(fan_out)
+        .or_insert_with(|| {
+            fan_out_script_bytecode("account", "create_account", vec![], vec![], false, fan_out)
+        })
+        .clone()
+}
+
+/// The bytecode of a script calling the function `0x1::module::function` once for each of
+/// `fan_out` recipients.  The function's parameters are `before`, the recipient's address and
+/// `after`.  The script takes `before`, the recipients, `after` and a last, unused, `vector<u8>`,
+/// and passes the same `before` and `after` to every call.  If `generic`, the function has one
+/// type parameter, which the script takes as well.
+fn fan_out_script_bytecode(
+    module: &str,
+    function: &str,
+    before: Vec<SignatureToken>,
+    after: Vec<SignatureToken>,
+    generic: bool,
+    fan_out: usize,
+) -> Vec<u8> {
+    let mut script = empty_script();
+    script.address_identifiers.push(AccountAddress::ONE);
+    script.identifiers.push(Identifier::new(module).unwrap());
+    script.identifiers.push(Identifier::new(function).unwrap());
+    script.module_handles.push(ModuleHandle {
+        address: AddressIdentifierIndex(0),
+        name: IdentifierIndex(0),
+    });
+
+    // The empty script already has the empty signature at index 0
+    let parameters = before
+        .iter()
+        .cloned()
+        .chain([SignatureToken::Address])
+        .chain(after.iter().cloned())
+        .collect();
+    let script_parameters = before
+        .iter()
+        .cloned()
+        .chain(iter::repeat(SignatureToken::Address).take(fan_out))
+        .chain(after.iter().cloned())
+        .chain([SignatureToken::Vector(Box::new(SignatureToken::U8))])
+        .collect();
+    script.signatures.push(Signature(parameters));
+    script.signatures.push(Signature(script_parameters));
+    script.parameters = SignatureIndex(2);
+
+    let type_parameters = if generic {
+        vec![AbilitySet::EMPTY]
+    } else {
+        vec![]
+    };
+    script.function_handles.push(FunctionHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(1),
+        parameters: SignatureIndex(1),
+        return_: SignatureIndex(0),
+        type_parameters: type_parameters.clone(),
+    });
+    let call = if generic {
+        script
+            .signatures
+            .push(Signature(vec![SignatureToken::TypeParameter(0)]));
+        script.function_instantiations.push(FunctionInstantiation {
+            handle: FunctionHandleIndex(0),
+            type_parameters: SignatureIndex(3),
+        });
+        script.type_parameters = type_parameters;
+        Bytecode::CallGeneric(FunctionInstantiationIndex(0))
+    } else {
+        Bytecode::Call(FunctionHandleIndex(0))
+    };
+
+    // The locals are the script's parameters, the recipients start after `before`
+    let before_locals = 0..before.len() as u8;
+    let after_start = (before.len() + fan_out) as u8;
+    let after_locals = after_start..after_start + after.len() as u8;
+    script.code.code = (0..fan_out)
+        .flat_map(|i| {
+            // The shared arguments are copied for every call but the last, which moves them
+            let shared = move |local| {
+                if i + 1 == fan_out {
+                    Bytecode::MoveLoc(local)
+                } else {
+                    Bytecode::CopyLoc(local)
+                }
+            };
+            before_locals
+                .clone()
+                .map(shared)
+                .chain([Bytecode::MoveLoc((before.len() + i) as u8)])
+                .chain(after_locals.clone().map(shared))
+                .chain([call.clone()])
+                .collect::<Vec<_>>()
+        })
+        .chain([Bytecode::Ret])
+        .collect();
+    let mut bytecode = vec![];
+    script
+        .serialize(&mut bytecode)
+        .expect("Script must serialize");
+    bytecode
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_sdk::transaction_builder::aptos_stdlib::EntryFunctionCall;
+    use move_deps::{
+        move_binary_format::{access::ScriptAccess, CompiledScript},
+        move_bytecode_verifier::verify_script,
+    };
+
+    #[test]
+    fn test_validate_transaction_shape() {
+        assert!(TransactionShape::default().validate().is_ok());
+
+        let padded = TransactionShape {
+            padding_bytes: MAX_PUBLISH_PACKAGE_SIZE,
+            ..TransactionShape::default()
+        };
+        assert!(padded.validate().is_ok());
+        let too_large = TransactionShape {
+            padding_bytes: MAX_PUBLISH_PACKAGE_SIZE + 1,
+            ..TransactionShape::default()
+        };
+        assert!(too_large.validate().is_err());
+
+        let fanned_out = TransactionShape {
+            fan_out: MAX_FAN_OUT,
+            ..TransactionShape::default()
+        };
+        assert!(fanned_out.validate().is_ok());
+        for fan_out in [0, MAX_FAN_OUT + 1] {
+            let shape = TransactionShape {
+                fan_out,
+                ..TransactionShape::default()
+            };
+            assert!(shape.validate().is_err());
+        }
+    }
+
+    /// The script of `payload`, checking it verifies and calls `function` `calls` times
+    fn shaped_script(payload: TransactionPayload, function: &str, calls: usize) -> Script {
+        let script = match payload {
+            TransactionPayload::Script(script) => script,
+            payload => panic!("Not a script payload: {:?}", payload),
+        };
+        let compiled = CompiledScript::deserialize(script.code()).unwrap();
+        verify_script(&compiled).unwrap();
+        let handle = compiled.function_handle_at(FunctionHandleIndex(0));
+        assert_eq!(compiled.identifier_at(handle.name).as_str(), function);
+        let num_calls = compiled
+            .code()
+            .code
+            .iter()
+            .filter(|op| matches!(op, Bytecode::Call(_) | Bytecode::CallGeneric(_)))
+            .count();
+        assert_eq!(num_calls, calls);
+        script
+    }
+
+    #[test]
+    fn test_padding_payload() {
+        let to = AccountAddress::random();
+
+        // Without padding, the entry functions are called directly
+        let shape = TransactionShape::default();
+        assert!(matches!(
+            EntryFunctionCall::decode(&shape.transfer_payload(&[to], 5)),
+            Some(EntryFunctionCall::CoinTransfer { to: receiver, amount: 5, .. }) if receiver == to
+        ));
+        assert!(matches!(
+            EntryFunctionCall::decode(&shape.create_account_payload(&[to])),
+            Some(EntryFunctionCall::AccountCreateAccount { auth_key }) if auth_key == to
+        ));
+
+        // With padding, a script calls them with the padding as an extra argument
+        let shape = TransactionShape {
+            padding_bytes: 100,
+            ..TransactionShape::default()
+        };
+        let transfer = shaped_script(shape.transfer_payload(&[to], 5), "transfer", 1);
+        assert_eq!(transfer.ty_args(), &[APTOS_COIN_TYPE.clone()]);
+        assert_eq!(
+            transfer.args(),
+            &[
+                TransactionArgument::Address(to),
+                TransactionArgument::U64(5),
+                TransactionArgument::U8Vector(vec![0; 100]),
+            ]
+        );
+        let create_account =
+            shaped_script(shape.create_account_payload(&[to]), "create_account", 1);
+        assert!(create_account.ty_args().is_empty());
+        assert_eq!(
+            create_account.args(),
+            &[
+                TransactionArgument::Address(to),
+                TransactionArgument::U8Vector(vec![0; 100]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fan_out_payload() {
+        let to = [AccountAddress::random(), AccountAddress::random()];
+        let shape = TransactionShape {
+            fan_out: to.len(),
+            ..TransactionShape::default()
+        };
+
+        // A script calls the entry function once per recipient, with empty padding
+        let transfer = shaped_script(shape.transfer_payload(&to, 5), "transfer", 2);
+        assert_eq!(
+            transfer.args(),
+            &[
+                TransactionArgument::Address(to[0]),
+                TransactionArgument::Address(to[1]),
+                TransactionArgument::U64(5),
+                TransactionArgument::U8Vector(vec![]),
+            ]
+        );
+        let create_account = shaped_script(shape.create_account_payload(&to), "create_account", 2);
+        assert_eq!(
+            create_account.args(),
+            &[
+                TransactionArgument::Address(to[0]),
+                TransactionArgument::Address(to[1]),
+                TransactionArgument::U8Vector(vec![]),
+            ]
+        );
+
+        // The largest fan-out still verifies
+        let to = vec![AccountAddress::random(); MAX_FAN_OUT];
+        shaped_script(shape.transfer_payload(&to, 5), "transfer", MAX_FAN_OUT);
+    }
+}
@@ -5,14 +5,17 @@ use aptos_rest_client::Client as RestClient;
 use aptos_sdk::{
     move_types::account_address::AccountAddress,
     transaction_builder::{aptos_stdlib::aptos_token_stdlib, TransactionFactory},
-    types::{transaction::SignedTransaction, LocalAccount},
+    types::{transaction::SignedTransaction, AccountKey, LocalAccount},
 };
 
 use crate::emitter::account_minter::create_and_fund_account_request;
 use aptos_logger::info;
-use rand::rngs::StdRng;
+use rand::{rngs::StdRng, SeedableRng};
 use std::{fmt::Debug, sync::Arc};
 
+const COLLECTION_NAME: &[u8] = b"collection name";
+const TOKEN_NAME: &[u8] = b"token name";
+
 #[derive(Debug)]
 pub struct NFTMint {
     txn_factory: TransactionFactory,
@@ -175,8 +178,8 @@ impl NFTMintGeneratorCreator {
         rest_client: RestClient,
     ) -> Self {
         let mut creator_account = LocalAccount::generate(&mut rng);
-        let collection_name = "collection name".to_owned().into_bytes();
-        let token_name = "token name".to_owned().into_bytes();
+        let collection_name = COLLECTION_NAME.to_vec();
+        let token_name = TOKEN_NAME.to_vec();
         initialize_nft_collection(
             rest_client,
             root_account,
@@ -193,6 +196,22 @@ impl NFTMintGeneratorCreator {
             token_name,
         }
     }
+
+    /// Creates the generator without setting up the collection on chain, for the collection
+    /// created by `collection_address`, e.g. to test the requests it builds without a node.  The
+    /// creator co-signs the transfers with a random key, so they aren't accepted on chain.
+    pub fn new_for_test(
+        collection_address: AccountAddress,
+        txn_factory: TransactionFactory,
+    ) -> Self {
+        let creator_key = AccountKey::generate(&mut StdRng::from_entropy());
+        Self {
+            txn_factory,
+            creator_account: Arc::new(LocalAccount::new(collection_address, creator_key, 0)),
+            collection_name: COLLECTION_NAME.to_vec(),
+            token_name: TOKEN_NAME.to_vec(),
+        }
+    }
 }
 
 impl TransactionGeneratorCreator for NFTMintGeneratorCreator {
@@ -205,3 +224,42 @@ impl TransactionGeneratorCreator for NFTMintGeneratorCreator {
         ))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_sdk::types::chain_id::ChainId;
+
+    #[test]
+    fn test_nft_mint_transfers_from_collection() {
+        let collection_address = AccountAddress::random();
+        let creator = NFTMintGeneratorCreator::new_for_test(
+            collection_address,
+            TransactionFactory::new(ChainId::test()),
+        );
+        let mut generator = creator.create_transaction_generator();
+
+        let mut account = LocalAccount::generate(&mut StdRng::from_seed([0; 32]));
+        let requests =
+            generator.generate_transactions(vec![&mut account], 3, Arc::new(vec![]), 0, 1);
+        assert_eq!(requests.len(), 3);
+        assert_eq!(account.sequence_number(), 3);
+
+        let transfer = aptos_token_stdlib::token_direct_transfer_script(
+            collection_address,
+            COLLECTION_NAME.to_vec(),
+            TOKEN_NAME.to_vec(),
+            0,
+            1,
+        );
+        for (sequence_number, request) in requests.iter().enumerate() {
+            assert_eq!(request.sender(), account.address());
+            assert_eq!(request.sequence_number(), sequence_number as u64);
+            assert_eq!(
+                request.authenticator().secondary_signer_addreses(),
+                vec![collection_address]
+            );
+            assert_eq!(request.payload(), &transfer);
+        }
+    }
+}
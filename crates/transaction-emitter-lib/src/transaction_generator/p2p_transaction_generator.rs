@@ -1,9 +1,11 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
-use crate::transaction_generator::{TransactionGenerator, TransactionGeneratorCreator};
+use crate::transaction_generator::{
+    TransactionGenerator, TransactionGeneratorCreator, TransactionShape,
+};
 use aptos_sdk::{
     move_types::account_address::AccountAddress,
-    transaction_builder::{aptos_stdlib, TransactionFactory},
+    transaction_builder::TransactionFactory,
     types::{chain_id::ChainId, transaction::SignedTransaction, LocalAccount},
 };
 use rand::{
@@ -19,37 +21,41 @@ pub struct P2PTransactionGenerator {
     rng: StdRng,
     send_amount: u64,
     txn_factory: TransactionFactory,
+    shape: TransactionShape,
 }
 
 impl P2PTransactionGenerator {
-    pub fn new(rng: StdRng, send_amount: u64, txn_factory: TransactionFactory) -> Self {
+    pub fn new(
+        rng: StdRng,
+        send_amount: u64,
+        txn_factory: TransactionFactory,
+        shape: TransactionShape,
+    ) -> Self {
         Self {
             rng,
             send_amount,
             txn_factory,
+            shape,
         }
     }
 
     fn gen_single_txn(
         &self,
         from: &mut LocalAccount,
-        to: &AccountAddress,
+        to: &[AccountAddress],
         num_coins: u64,
         txn_factory: &TransactionFactory,
         gas_price: u64,
     ) -> SignedTransaction {
-        from.sign_with_transaction_builder(
-            txn_factory
-                .payload(aptos_stdlib::aptos_coin_transfer(*to, num_coins))
-                .gas_unit_price(gas_price),
-        )
+        let payload = self.shape.transfer_payload(to, num_coins);
+        from.sign_with_transaction_builder(txn_factory.payload(payload).gas_unit_price(gas_price))
     }
 
     fn generate_invalid_transaction(
         &self,
         rng: &mut StdRng,
         sender: &mut LocalAccount,
-        receiver: &AccountAddress,
+        receivers: &[AccountAddress],
         gas_price: u64,
         reqs: &[SignedTransaction],
     ) -> SignedTransaction {
@@ -58,28 +64,32 @@ impl P2PTransactionGenerator {
         match Standard.sample(rng) {
             InvalidTransactionType::ChainId => {
                 let txn_factory = &self.txn_factory.clone().with_chain_id(ChainId::new(255));
-                self.gen_single_txn(sender, receiver, self.send_amount, txn_factory, gas_price)
+                self.gen_single_txn(sender, receivers, self.send_amount, txn_factory, gas_price)
             }
             InvalidTransactionType::Sender => self.gen_single_txn(
                 &mut invalid_account,
-                receiver,
-                self.send_amount,
-                &self.txn_factory,
-                gas_price,
-            ),
-            InvalidTransactionType::Receiver => self.gen_single_txn(
-                sender,
-                &invalid_address,
+                receivers,
                 self.send_amount,
                 &self.txn_factory,
                 gas_price,
             ),
+            InvalidTransactionType::Receiver => {
+                let mut invalid_receivers = receivers.to_vec();
+                invalid_receivers[0] = invalid_address;
+                self.gen_single_txn(
+                    sender,
+                    &invalid_receivers,
+                    self.send_amount,
+                    &self.txn_factory,
+                    gas_price,
+                )
+            }
             InvalidTransactionType::Duplication => {
                 // if this is the first tx, default to generate invalid tx with wrong chain id
                 // otherwise, make a duplication of an exist valid tx
                 if reqs.is_empty() {
                     let txn_factory = &self.txn_factory.clone().with_chain_id(ChainId::new(255));
-                    self.gen_single_txn(sender, receiver, self.send_amount, txn_factory, gas_price)
+                    self.gen_single_txn(sender, receivers, self.send_amount, txn_factory, gas_price)
                 } else {
                     let random_index = rng.gen_range(0, reqs.len());
                     reqs[random_index].clone()
@@ -131,14 +141,16 @@ impl TransactionGenerator for P2PTransactionGenerator {
         let mut num_valid_tx = transactions_per_account * (accounts.len() - invalid_size);
         for sender in accounts {
             for _ in 0..transactions_per_account {
-                let receiver = all_addresses
-                    .choose(&mut self.rng)
-                    .expect("all_addresses can't be empty");
+                let receivers: Vec<_> = all_addresses
+                    .choose_multiple(&mut self.rng, self.shape.fan_out)
+                    .cloned()
+                    .collect();
+                assert!(!receivers.is_empty(), "all_addresses can't be empty");
                 let request = if num_valid_tx > 0 {
                     num_valid_tx -= 1;
                     self.gen_single_txn(
                         sender,
-                        receiver,
+                        &receivers,
                         self.send_amount,
                         &self.txn_factory,
                         gas_price,
@@ -147,7 +159,7 @@ impl TransactionGenerator for P2PTransactionGenerator {
                     self.generate_invalid_transaction(
                         &mut self.rng.clone(),
                         sender,
-                        receiver,
+                        &receivers,
                         gas_price,
                         &requests,
                     )
@@ -164,14 +176,21 @@ pub struct P2PTransactionGeneratorCreator {
     rng: StdRng,
     txn_factory: TransactionFactory,
     amount: u64,
+    shape: TransactionShape,
 }
 
 impl P2PTransactionGeneratorCreator {
-    pub fn new(rng: StdRng, txn_factory: TransactionFactory, amount: u64) -> Self {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        amount: u64,
+        shape: TransactionShape,
+    ) -> Self {
         Self {
             rng,
             txn_factory,
             amount,
+            shape,
         }
     }
 }
@@ -182,6 +201,7 @@ impl TransactionGeneratorCreator for P2PTransactionGeneratorCreator {
             self.rng.clone(),
             self.amount,
             self.txn_factory.clone(),
+            self.shape,
         ))
     }
 }
@@ -0,0 +1,175 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+use crate::transaction_generator::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_sdk::{
+    move_types::{account_address::AccountAddress, identifier::Identifier},
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{
+        transaction::{SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use framework::natives::code::{ModuleMetadata, PackageMetadata, UpgradePolicy};
+use move_deps::move_binary_format::file_format::{empty_module, Constant, SignatureToken};
+use rand::Rng;
+use rand_core::OsRng;
+use std::{fmt::Debug, sync::Arc};
+
+/// Name of the package published by each account, and of its only module
+const PACKAGE_NAME: &str = "emitter_package";
+const MODULE_NAME: &str = "emitter_module";
+
+/// Publishes a small module from each account, upgrading it with every transaction
+#[derive(Debug)]
+pub struct PublishModuleGenerator {
+    txn_factory: TransactionFactory,
+    /// The value of the constant of the next module, which makes its bytecode unique
+    next_constant: u64,
+}
+
+impl PublishModuleGenerator {
+    pub fn new(txn_factory: TransactionFactory, first_constant: u64) -> Self {
+        Self {
+            txn_factory,
+            next_constant: first_constant,
+        }
+    }
+
+    fn gen_single_txn(&mut self, sender: &mut LocalAccount, gas_price: u64) -> SignedTransaction {
+        let payload = publish_module_payload(sender.address(), self.next_constant);
+        self.next_constant = self.next_constant.wrapping_add(1);
+        sender.sign_with_transaction_builder(
+            self.txn_factory.payload(payload).gas_unit_price(gas_price),
+        )
+    }
+}
+
+impl TransactionGenerator for PublishModuleGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+        _all_addresses: Arc<Vec<AccountAddress>>,
+        _invalid_transaction_ratio: usize,
+        gas_price: u64,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                requests.push(self.gen_single_txn(account, gas_price));
+            }
+        }
+        requests
+    }
+}
+
+/// The bytecode of a module at `address`, without functions or structs, whose constant pool only
+/// has `constant`
+fn module_bytecode(address: AccountAddress, constant: u64) -> Vec<u8> {
+    let mut module = empty_module();
+    module.identifiers[0] = Identifier::new(MODULE_NAME).unwrap();
+    module.address_identifiers[0] = address;
+    module.constant_pool.push(Constant {
+        type_: SignatureToken::U64,
+        data: constant.to_le_bytes().to_vec(),
+    });
+    let mut bytecode = vec![];
+    module
+        .serialize(&mut bytecode)
+        .expect("Module must serialize");
+    bytecode
+}
+
+/// Publishes the package of a single module at `address`, with the constant `constant`.  Only the
+/// constant changes between upgrades, so they're compatible.
+fn publish_module_payload(address: AccountAddress, constant: u64) -> TransactionPayload {
+    let metadata = PackageMetadata {
+        name: PACKAGE_NAME.to_string(),
+        upgrade_policy: UpgradePolicy::compat(),
+        upgrade_number: 0,
+        build_info: String::new(),
+        manifest: String::new(),
+        modules: vec![ModuleMetadata {
+            name: MODULE_NAME.to_string(),
+            source: String::new(),
+            source_map: String::new(),
+        }],
+        abis: vec![],
+    };
+    aptos_stdlib::code_publish_package_txn(
+        bcs::to_bytes(&metadata).expect("PackageMetadata has BCS"),
+        vec![module_bytecode(address, constant)],
+    )
+}
+
+#[derive(Debug)]
+pub struct PublishModuleGeneratorCreator {
+    txn_factory: TransactionFactory,
+}
+
+impl PublishModuleGeneratorCreator {
+    pub fn new(txn_factory: TransactionFactory) -> Self {
+        Self { txn_factory }
+    }
+}
+
+impl TransactionGeneratorCreator for PublishModuleGeneratorCreator {
+    fn create_transaction_generator(&self) -> Box<dyn TransactionGenerator> {
+        Box::new(PublishModuleGenerator::new(
+            self.txn_factory.clone(),
+            OsRng.gen(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_sdk::{
+        transaction_builder::aptos_stdlib::EntryFunctionCall, types::chain_id::ChainId,
+    };
+    use move_deps::{move_binary_format::CompiledModule, move_bytecode_verifier::verify_module};
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_publish_distinct_modules() {
+        let creator = PublishModuleGeneratorCreator::new(TransactionFactory::new(ChainId::test()));
+        let mut generator = creator.create_transaction_generator();
+        let mut account = LocalAccount::generate(&mut StdRng::from_seed([0; 32]));
+
+        let mut requests = vec![];
+        for _ in 0..3 {
+            requests.extend(generator.generate_transactions(
+                vec![&mut account],
+                2,
+                Arc::new(vec![]),
+                0,
+                1,
+            ));
+        }
+        assert_eq!(requests.len(), 6);
+        assert_eq!(account.sequence_number(), 6);
+
+        let mut constants = HashSet::new();
+        for request in &requests {
+            let (metadata, code) = match EntryFunctionCall::decode(request.payload()) {
+                Some(EntryFunctionCall::CodePublishPackageTxn {
+                    metadata_serialized,
+                    code,
+                }) => (metadata_serialized, code),
+                _ => panic!("Not a package publishing payload: {:?}", request.payload()),
+            };
+            let metadata: PackageMetadata = bcs::from_bytes(&metadata).unwrap();
+            assert_eq!(metadata.name, PACKAGE_NAME);
+            assert_eq!(metadata.modules.len(), 1);
+            assert_eq!(code.len(), 1);
+
+            let module = CompiledModule::deserialize(&code[0]).unwrap();
+            verify_module(&module).unwrap();
+            assert_eq!(module.self_id().address(), &account.address());
+            assert_eq!(module.self_id().name().as_str(), metadata.modules[0].name);
+            assert!(constants.insert(module.constant_pool[0].data.clone()));
+        }
+    }
+}
@@ -37,6 +37,7 @@ pub async fn emit_transactions_with_cluster(
         wait_committed: !args.burst,
         txn_expiration_time_secs: args.txn_expiration_time_secs,
         check_stats_at_end: !args.do_not_check_stats_at_end,
+        duty_cycle: args.duty_cycle()?,
     };
     let duration = Duration::from_secs(args.duration);
     let client = cluster.random_instance().rest_client();
@@ -55,11 +56,16 @@ pub async fn emit_transactions_with_cluster(
             .mempool_backlog(args.mempool_backlog.try_into().unwrap())
             .invalid_transaction_ratio(args.invalid_tx)
             .transaction_type(args.transaction_type)
+            .transaction_shape(args.transaction_shape()?)
             .duration(duration)
+            .max_drift(args.max_drift)
             .gas_price(1);
     if reuse_accounts {
         emit_job_request = emit_job_request.reuse_accounts();
     }
+    if let Some(audit_config) = args.audit_config() {
+        emit_job_request = emit_job_request.audit(audit_config);
+    }
     let stats = emitter
         .emit_txn_for_with_stats(emit_job_request, min(10, max(args.duration / 5, 1)))
         .await?;
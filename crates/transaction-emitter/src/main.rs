@@ -55,11 +55,15 @@ pub async fn main() -> Result<()> {
             let stats = emit_transactions(&args.cluster_args, &args.emit_args)
                 .await
                 .context("Emit transactions failed")?;
+            let duration = Duration::from_secs(args.emit_args.duration);
             println!("Total stats: {}", stats);
-            println!(
-                "Average rate: {}",
-                stats.rate(Duration::from_secs(args.emit_args.duration))
-            );
+            println!("Average rate: {}", stats.rate(duration));
+            if let Some(duty_cycle) = args.emit_args.duty_cycle()? {
+                println!(
+                    "Burst rate: {}",
+                    stats.burst_rate(duty_cycle.burst_time_within(duration))
+                );
+            }
             Ok(())
         }
         TxnEmitterCommand::Diag(args) => {
@@ -0,0 +1,66 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_temppath::TempPath;
+use aptos_types::state_store::{state_key::StateKey, state_value::StateValue};
+use aptosdb::AptosDB;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+
+const NUM_KEYS: usize = 50_000;
+const VALUE_SIZE: usize = 100;
+
+fn random_value_set(rng: &mut StdRng, keys: &[StateKey]) -> HashMap<StateKey, Option<StateValue>> {
+    keys.iter()
+        .map(|key| {
+            let value: Vec<u8> = (0..VALUE_SIZE).map(|_| rng.gen()).collect();
+            (key.clone(), Some(StateValue::from(value)))
+        })
+        .collect()
+}
+
+fn calculate_stale_indices(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let keys: Vec<StateKey> = (0..NUM_KEYS)
+        .map(|_| StateKey::Raw((0..32).map(|_| rng.gen()).collect()))
+        .collect();
+
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    // Every key in the block being benchmarked overwrites a value written at version 0, so each
+    // of them requires a lookup of the old value.
+    let base = random_value_set(&mut rng, &keys);
+    db.put_value_sets_for_test(vec![&base], 0).unwrap();
+    let block = random_value_set(&mut rng, &keys);
+
+    let mut group = c.benchmark_group("stale_state_value_index");
+    group.throughput(Throughput::Elements(NUM_KEYS as u64));
+    group.sample_size(10);
+
+    let max_threads = rayon::current_num_threads();
+    let mut num_threads = 1;
+    loop {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        group.bench_function(BenchmarkId::new("threads", num_threads), |b| {
+            b.iter(|| {
+                pool.install(|| {
+                    db.calculate_stats_and_indices_for_test(&[&block], 1)
+                        .unwrap()
+                })
+            })
+        });
+
+        if num_threads == max_threads {
+            break;
+        }
+        num_threads = std::cmp::min(num_threads * 2, max_threads);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, calculate_stale_indices);
+criterion_main!(benches);
@@ -3,23 +3,33 @@
 
 use std::{sync::Arc, time::Duration};
 
-use aptos_config::config::{LedgerPrunerConfig, StateMerklePrunerConfig};
+use aptos_config::config::{
+    ColumnFamilyConfig, LedgerPrunerConfig, RocksdbCompression, RocksdbConfigs,
+    StateMerklePrunerConfig, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG, TARGET_SNAPSHOT_SIZE,
+};
+use move_deps::move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
 use proptest::prelude::*;
+use schemadb::SchemaBatch;
 
 use crate::{
-    error_if_version_is_pruned, get_first_seq_num_and_limit,
+    backfill_block_index, error_if_version_is_pruned, get_first_seq_num_and_limit,
+    next_block_to_backfill, parse_rocksdb_tickers,
     pruner::{
         ledger_pruner_manager::LedgerPrunerManager, state_pruner_manager::StatePrunerManager,
     },
     test_helper,
     test_helper::{arb_blocks_to_commit, put_as_state_root, put_transaction_info},
-    AptosDB, PrunerManager, ROCKSDB_PROPERTIES,
+    AptosDB, PrunerManager, ROCKSDB_PROPERTIES, ROCKSDB_STATISTICS,
 };
 
 use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_state_view::state_storage_usage::StateStorageUsage;
 use aptos_temppath::TempPath;
 use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{new_block_event_key, NewBlockEvent},
+    contract_event::ContractEvent,
     proof::SparseMerkleLeafNode,
     state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{ExecutionStatus, TransactionInfo},
@@ -118,6 +128,71 @@ fn test_storage_config() {
     }
 }
 
+#[test]
+fn test_column_family_compression() {
+    let zstd = ColumnFamilyConfig {
+        compression: Some(RocksdbCompression::Zstd),
+        block_size: Some(16 << 10),
+        bloom_filter_bits: Some(10.0),
+    };
+    let mut rocksdb_configs = RocksdbConfigs::default();
+    rocksdb_configs
+        .ledger_db_config
+        .column_families
+        .insert("state_value".to_string(), zstd.clone());
+    rocksdb_configs
+        .state_merkle_db_config
+        .column_families
+        .insert("jellyfish_merkle_node".to_string(), zstd.clone());
+    rocksdb_configs
+        .index_db_config
+        .column_families
+        .insert("table_info".to_string(), zstd);
+    let tmp_dir = TempPath::new();
+    let open = |rocksdb_configs: RocksdbConfigs| {
+        AptosDB::open(
+            &tmp_dir,
+            false, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            rocksdb_configs,
+            true, /* indexer */
+            TARGET_SNAPSHOT_SIZE,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )
+    };
+
+    let key = StateKey::Raw(String::from("test_key").into_bytes());
+    let value = StateValue::from(String::from("test_val").into_bytes());
+    {
+        let db = open(rocksdb_configs.clone()).unwrap();
+        put_as_state_root(&db, 0, key.clone(), value.clone());
+    }
+    // Reopening flushes the writes into compressed tables
+    let db = open(rocksdb_configs.clone()).unwrap();
+    assert_eq!(db.get_state_value_by_version(&key, 0).unwrap(), Some(value));
+
+    // Overrides of column families the DB doesn't have are rejected
+    drop(db);
+    rocksdb_configs
+        .state_merkle_db_config
+        .column_families
+        .insert("state_value".to_string(), ColumnFamilyConfig::default());
+    let err = open(rocksdb_configs.clone()).unwrap_err();
+    assert!(err.to_string().contains("state_value"), "{}", err);
+
+    // including the index DB's
+    rocksdb_configs
+        .state_merkle_db_config
+        .column_families
+        .remove("state_value");
+    rocksdb_configs
+        .index_db_config
+        .column_families
+        .insert("state_value".to_string(), ColumnFamilyConfig::default());
+    let err = open(rocksdb_configs).unwrap_err();
+    assert!(err.to_string().contains("state_value"), "{}", err);
+}
+
 #[test]
 fn test_error_if_version_is_pruned() {
     let tmp_dir = TempPath::new();
@@ -160,6 +235,90 @@ fn test_error_if_version_is_pruned() {
     assert!(error_if_version_is_pruned(&ledger_pruner, "Transaction", 10).is_ok());
 }
 
+#[test]
+fn test_block_index_backfill_and_prune() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+
+    // 25 blocks of 3 transactions each, only saved as events like before the block index existed.
+    let blocks: Vec<_> = (0..25u64)
+        .map(|height| {
+            let new_block_event = NewBlockEvent::new(
+                AccountAddress::random(),
+                0,      // epoch
+                height, // round
+                height,
+                vec![],
+                AccountAddress::random(),
+                vec![],
+                height * 1000,
+            );
+            (height * 3, new_block_event)
+        })
+        .collect();
+    let mut batch = SchemaBatch::new();
+    for (version, new_block_event) in &blocks {
+        let event = ContractEvent::new(
+            new_block_event_key(),
+            new_block_event.height(),
+            TypeTag::Struct(NewBlockEvent::struct_tag()),
+            bcs::to_bytes(new_block_event).unwrap(),
+        );
+        db.event_store
+            .put_events(*version, &[event], &mut batch)
+            .unwrap();
+    }
+    db.ledger_db.write_schemas(batch).unwrap();
+
+    // Index the latest block as if it was just committed, then backfill the rest in batches
+    let mut batch = SchemaBatch::new();
+    let (version, latest_block) = blocks.last().unwrap();
+    db.ledger_store
+        .put_block(*version, latest_block, &mut batch)
+        .unwrap();
+    db.ledger_db.write_schemas(batch).unwrap();
+    assert_eq!(
+        next_block_to_backfill(&db.ledger_store, &db.event_store).unwrap(),
+        Some(blocks.len() as u64 - 2)
+    );
+    backfill_block_index(&db.ledger_db, &db.ledger_store, &db.event_store, 10).unwrap();
+    assert_eq!(
+        next_block_to_backfill(&db.ledger_store, &db.event_store).unwrap(),
+        None
+    );
+
+    let ledger_store = &db.ledger_store;
+    assert_eq!(
+        ledger_store.get_lowest_indexed_block_height().unwrap(),
+        Some(0)
+    );
+    assert_eq!(
+        ledger_store.get_block_by_version(10).unwrap(),
+        Some(blocks[3].clone())
+    );
+    assert_eq!(ledger_store.get_block_first_version(7).unwrap(), Some(21));
+    assert_eq!(
+        ledger_store.get_next_block_first_version(9).unwrap(),
+        Some(12)
+    );
+    assert_eq!(ledger_store.get_next_block_first_version(72).unwrap(), None);
+
+    // Blocks 0 to 3 start before version 10, so only those are pruned
+    let mut batch = SchemaBatch::new();
+    ledger_store.prune_block_index(0, 10, &mut batch).unwrap();
+    db.ledger_db.write_schemas(batch).unwrap();
+    assert_eq!(
+        ledger_store.get_lowest_indexed_block_height().unwrap(),
+        Some(4)
+    );
+    assert_eq!(ledger_store.get_block_first_version(3).unwrap(), None);
+    assert_eq!(ledger_store.get_block_by_version(11).unwrap(), None);
+    assert_eq!(
+        ledger_store.get_block_by_version(12).unwrap(),
+        Some(blocks[4].clone())
+    );
+}
+
 #[test]
 fn test_get_latest_executed_trees() {
     let tmp_dir = TempPath::new();
@@ -213,3 +372,39 @@ fn test_rocksdb_properties_reporter() {
     std::thread::sleep(Duration::from_secs(1));
     assert_eq!(get_metric(), 1);
 }
+
+#[test]
+fn test_parse_rocksdb_tickers() {
+    let statistics = "rocksdb.block.cache.miss COUNT : 12\n\
+        rocksdb.block.cache.hit COUNT : 34\n\
+        rocksdb.db.get.micros P50 : 1.000000 P95 : 2.000000 P99 : 3.000000 P100 : 4.000000 \
+        COUNT : 5 SUM : 6\n";
+    let tickers = parse_rocksdb_tickers(statistics);
+    assert_eq!(tickers.len(), 2);
+    assert_eq!(tickers["rocksdb.block.cache.miss"], 12);
+    assert_eq!(tickers["rocksdb.block.cache.hit"], 34);
+}
+
+#[test]
+fn test_rocksdb_statistics_reporter() {
+    fn get_metric() -> i64 {
+        ROCKSDB_STATISTICS
+            .get_metric_with_label_values(&["ledger_db", "aptos_rocksdb_number_keys_written"])
+            .unwrap()
+            .get()
+    }
+
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let txn_info = TransactionInfo::new(
+        HashValue::random(),
+        HashValue::random(),
+        HashValue::random(),
+        None,
+        0,
+        ExecutionStatus::Success,
+    );
+    put_transaction_info(&db, 0, &txn_info);
+    std::thread::sleep(Duration::from_secs(1));
+    assert!(get_metric() > 0);
+}
@@ -105,7 +105,7 @@ impl BackupHandler {
     ) -> Result<Box<dyn Iterator<Item = Result<(StateKey, StateValue)>> + Send + Sync>> {
         let iterator = self
             .state_store
-            .get_state_key_and_value_iter(version, HashValue::zero())?
+            .iter_state_at_version(version)?
             .enumerate()
             .map(move |(idx, res)| {
                 BACKUP_STATE_SNAPSHOT_VERSION.set(version as i64);
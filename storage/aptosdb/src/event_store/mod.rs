@@ -299,6 +299,33 @@ impl EventStore {
         Ok((first_version, payload))
     }
 
+    /// Returns up to `limit` `NewBlockEvent`s, with the versions they were emitted at, walking
+    /// down from block `height`. Stops early at the first missing height, e.g. if it was pruned.
+    pub fn get_block_events_descending(
+        &self,
+        height: u64,
+        limit: usize,
+    ) -> Result<Vec<(Version, NewBlockEvent)>> {
+        let event_key = new_block_event_key();
+        let mut iter = self
+            .db
+            .rev_iter::<EventByKeySchema>(ReadOptions::default())?;
+        iter.seek_for_prev(&(event_key, height))?;
+
+        let mut blocks = Vec::new();
+        let mut expected_height = Some(height);
+        for res in iter.take(limit) {
+            let ((key, seq_num), (version, index)) = res?;
+            if key != event_key || Some(seq_num) != expected_height {
+                break;
+            }
+            let event = self.get_event_by_version_and_index(version, index)?;
+            blocks.push((version, bcs::from_bytes(event.event_data())?));
+            expected_height = seq_num.checked_sub(1);
+        }
+        Ok(blocks)
+    }
+
     /// Save contract events yielded by the transaction at `version` and return root hash of the
     /// event accumulator formed by these events.
     pub fn put_events(
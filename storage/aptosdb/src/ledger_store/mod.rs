@@ -7,6 +7,7 @@
 use crate::{
     errors::AptosDbError,
     schema::{
+        block_by_height::BlockByHeightSchema, block_by_version::BlockByVersionSchema,
         epoch_by_version::EpochByVersionSchema, ledger_info::LedgerInfoSchema,
         transaction_accumulator::TransactionAccumulatorSchema,
         transaction_info::TransactionInfoSchema,
@@ -19,6 +20,8 @@ use aptos_crypto::{
     HashValue,
 };
 use aptos_types::{
+    account_config::{new_block_event_key, NewBlockEvent},
+    contract_event::ContractEvent,
     epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
     proof::{
@@ -314,6 +317,102 @@ impl LedgerStore {
     pub fn get_root_hash(&self, version: Version) -> Result<HashValue> {
         Accumulator::get_root_hash(self, version + 1)
     }
+
+    /// Write the block index entry to `batch` if the transaction at `version` started a block,
+    /// which is the case iff it emitted a `NewBlockEvent`.
+    pub fn put_block_index(
+        &self,
+        version: Version,
+        events: &[ContractEvent],
+        batch: &mut SchemaBatch,
+    ) -> Result<()> {
+        let event_key = new_block_event_key();
+        for event in events.iter().filter(|event| *event.key() == event_key) {
+            let new_block_event = NewBlockEvent::try_from_bytes(event.event_data())?;
+            self.put_block(version, &new_block_event, batch)?;
+        }
+        Ok(())
+    }
+
+    /// Write a single block starting at `first_version` to the block index.
+    pub fn put_block(
+        &self,
+        first_version: Version,
+        new_block_event: &NewBlockEvent,
+        batch: &mut SchemaBatch,
+    ) -> Result<()> {
+        batch.put::<BlockByHeightSchema>(&new_block_event.height(), &first_version)?;
+        batch.put::<BlockByVersionSchema>(&first_version, new_block_event)
+    }
+
+    /// Get the first version and `NewBlockEvent` of the block containing `version`.
+    ///
+    /// The block index always covers a contiguous range of blocks up to the latest one, but on a
+    /// database that hasn't been fully backfilled (or was pruned) older blocks are missing, in
+    /// which case `None` is returned.
+    pub fn get_block_by_version(
+        &self,
+        version: Version,
+    ) -> Result<Option<(Version, NewBlockEvent)>> {
+        let mut iter = self
+            .db
+            .iter::<BlockByVersionSchema>(ReadOptions::default())?;
+        iter.seek_for_prev(&version)?;
+        iter.next().transpose()
+    }
+
+    /// Get the version of the first transaction of the block at `height`, if it's indexed.
+    pub fn get_block_first_version(&self, height: u64) -> Result<Option<Version>> {
+        self.db.get::<BlockByHeightSchema>(&height)
+    }
+
+    /// Get the `NewBlockEvent` of the block starting at `first_version`, if it's indexed.
+    pub fn get_block_by_first_version(
+        &self,
+        first_version: Version,
+    ) -> Result<Option<NewBlockEvent>> {
+        self.db.get::<BlockByVersionSchema>(&first_version)
+    }
+
+    /// Get the first version of the block after the one starting at `first_version`, if any.
+    pub fn get_next_block_first_version(&self, first_version: Version) -> Result<Option<Version>> {
+        let mut iter = self
+            .db
+            .iter::<BlockByVersionSchema>(ReadOptions::default())?;
+        iter.seek(&(first_version + 1))?;
+        Ok(iter.next().transpose()?.map(|(version, _)| version))
+    }
+
+    /// Get the lowest block height in the block index, `None` if the index is empty.
+    pub fn get_lowest_indexed_block_height(&self) -> Result<Option<u64>> {
+        let mut iter = self
+            .db
+            .iter::<BlockByHeightSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter.next().transpose()?.map(|(height, _)| height))
+    }
+
+    /// Prune the block index of blocks starting in the range of version in [begin, end).
+    pub fn prune_block_index(
+        &self,
+        begin: Version,
+        end: Version,
+        db_batch: &mut SchemaBatch,
+    ) -> Result<()> {
+        let mut iter = self
+            .db
+            .iter::<BlockByVersionSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for item in iter {
+            let (first_version, new_block_event) = item?;
+            if first_version >= end {
+                break;
+            }
+            db_batch.delete::<BlockByHeightSchema>(&new_block_event.height())?;
+            db_batch.delete::<BlockByVersionSchema>(&first_version)?;
+        }
+        Ok(())
+    }
 }
 
 pub(crate) type Accumulator = MerkleAccumulator<LedgerStore, TransactionAccumulatorHasher>;
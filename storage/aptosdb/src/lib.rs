@@ -31,6 +31,8 @@ mod versioned_node_cache;
 #[cfg(test)]
 mod aptosdb_test;
 
+pub use state_store::{CompactionOpts, StateDb};
+
 #[cfg(any(test, feature = "fuzzing"))]
 use crate::state_store::buffered_state::BufferedState;
 use crate::{
@@ -44,7 +46,7 @@ use crate::{
     ledger_store::LedgerStore,
     metrics::{
         API_LATENCY_SECONDS, COMMITTED_TXNS, LATEST_TXN_VERSION, LEDGER_VERSION, NEXT_BLOCK_EPOCH,
-        OTHER_TIMERS_SECONDS, ROCKSDB_PROPERTIES,
+        OTHER_TIMERS_SECONDS, ROCKSDB_PROPERTIES, ROCKSDB_STATISTICS,
     },
     pruner::{pruner_manager::PrunerManager, utils},
     schema::*,
@@ -111,7 +113,7 @@ use crate::pruner::{
 };
 use storage_interface::{
     state_delta::StateDelta, state_view::DbStateView, DbReader, DbWriter, ExecutedTrees, Order,
-    StateSnapshotReceiver,
+    StateSnapshotReceiver, StateStorageUsagePoint, StateStorageUsageRange,
 };
 
 pub const LEDGER_DB_NAME: &str = "ledger_db";
@@ -162,6 +164,34 @@ static ROCKSDB_PROPERTY_MAP: Lazy<HashMap<&str, String>> = Lazy::new(|| {
     .collect()
 });
 
+static ROCKSDB_STATISTICS_MAP: Lazy<HashMap<&str, String>> = Lazy::new(|| {
+    [
+        "rocksdb.block.cache.hit",
+        "rocksdb.block.cache.miss",
+        "rocksdb.block.cache.data.hit",
+        "rocksdb.block.cache.data.miss",
+        "rocksdb.block.cache.index.hit",
+        "rocksdb.block.cache.index.miss",
+        "rocksdb.block.cache.filter.hit",
+        "rocksdb.block.cache.filter.miss",
+        "rocksdb.bloom.filter.useful",
+        "rocksdb.memtable.hit",
+        "rocksdb.memtable.miss",
+        "rocksdb.number.keys.written",
+        "rocksdb.number.keys.read",
+        "rocksdb.bytes.written",
+        "rocksdb.bytes.read",
+        "rocksdb.wal.bytes",
+        "rocksdb.compact.read.bytes",
+        "rocksdb.compact.write.bytes",
+        "rocksdb.flush.write.bytes",
+        "rocksdb.stall.micros",
+    ]
+    .iter()
+    .map(|x| (*x, format!("aptos_{}", x.replace('.', "_"))))
+    .collect()
+});
+
 fn error_if_too_many_requested(num_requested: u64, max_allowed: u64) -> Result<()> {
     if num_requested > max_allowed {
         Err(AptosDbError::TooManyRequested(num_requested, max_allowed).into())
@@ -170,6 +200,77 @@ fn error_if_too_many_requested(num_requested: u64, max_allowed: u64) -> Result<(
     }
 }
 
+/// Number of blocks written to the block index per batch when backfilling.
+const BLOCK_INDEX_BACKFILL_BATCH_SIZE: usize = 10000;
+
+/// Returns the height of the block right below the lowest indexed one, if its `NewBlockEvent` is
+/// still there to index it from, i.e. if the block index is incomplete.
+fn next_block_to_backfill(
+    ledger_store: &LedgerStore,
+    event_store: &EventStore,
+) -> Result<Option<u64>> {
+    let next_height = match ledger_store.get_lowest_indexed_block_height()? {
+        Some(0) => return Ok(None),
+        Some(height) => height - 1,
+        None => {
+            match event_store.get_latest_sequence_number(Version::MAX, &new_block_event_key())? {
+                Some(height) => height,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    // The events of blocks below the lowest indexed one may have been pruned
+    let blocks = event_store.get_block_events_descending(next_height, 1)?;
+    Ok(blocks.first().map(|_| next_height))
+}
+
+/// Indexes blocks that aren't in the block index yet, `batch_size` at a time, walking down from
+/// the block right below the lowest indexed one. New blocks are indexed on commit, so this keeps
+/// the index covering a contiguous range of heights that ends at the latest block.
+fn backfill_block_index(
+    ledger_db: &DB,
+    ledger_store: &LedgerStore,
+    event_store: &EventStore,
+    batch_size: usize,
+) -> Result<()> {
+    let mut next_height = match next_block_to_backfill(ledger_store, event_store)? {
+        Some(height) => height,
+        None => return Ok(()),
+    };
+
+    let mut num_indexed = 0;
+    loop {
+        let blocks = event_store.get_block_events_descending(next_height, batch_size)?;
+        let mut batch = SchemaBatch::new();
+        for (first_version, new_block_event) in &blocks {
+            ledger_store.put_block(*first_version, new_block_event, &mut batch)?;
+        }
+        ledger_db.write_schemas(batch)?;
+        num_indexed += blocks.len();
+
+        match blocks.last() {
+            Some((_, new_block_event))
+                if blocks.len() == batch_size && new_block_event.height() > 0 =>
+            {
+                next_height = new_block_event.height() - 1;
+                info!(
+                    num_indexed = num_indexed,
+                    next_height = next_height,
+                    "Backfilling block index."
+                );
+            }
+            _ => break,
+        }
+    }
+
+    info!(
+        num_indexed = num_indexed,
+        "Finished backfilling block index."
+    );
+    Ok(())
+}
+
 fn error_if_version_is_pruned(
     pruner: &(dyn PrunerManager),
     data_type: &str,
@@ -186,6 +287,24 @@ fn error_if_version_is_pruned(
     Ok(())
 }
 
+/// Picks at most `max_points` versions evenly spread over [`first_version`, `last_version`],
+/// including both ends if `max_points` allows.
+fn sample_versions(
+    first_version: Version,
+    last_version: Version,
+    max_points: usize,
+) -> Vec<Version> {
+    let num_versions = (last_version - first_version) as u128 + 1;
+    match max_points as u128 {
+        0 => vec![],
+        1 => vec![last_version],
+        max_points if max_points >= num_versions => (first_version..=last_version).collect(),
+        max_points => (0..max_points)
+            .map(|i| first_version + (i * (num_versions - 1) / (max_points - 1)) as Version)
+            .collect(),
+    }
+}
+
 fn update_rocksdb_properties(ledger_rocksdb: &DB, state_merkle_rocksdb: &DB) -> Result<()> {
     let _timer = OTHER_TIMERS_SECONDS
         .with_label_values(&["update_rocksdb_properties"])
@@ -207,6 +326,44 @@ fn update_rocksdb_properties(ledger_rocksdb: &DB, state_merkle_rocksdb: &DB) ->
     Ok(())
 }
 
+/// Parses the ticker counts out of a RocksDB statistics dump, in which each ticker is on its own
+/// line as `<ticker name> COUNT : <count>`. Histograms are skipped.
+fn parse_rocksdb_tickers(statistics: &str) -> HashMap<&str, u64> {
+    statistics
+        .lines()
+        .filter_map(
+            |line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [name, "COUNT", ":", count] => count.parse().ok().map(|count| (name, count)),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+fn update_rocksdb_statistics(ledger_rocksdb: &DB, state_merkle_rocksdb: &DB) -> Result<()> {
+    let _timer = OTHER_TIMERS_SECONDS
+        .with_label_values(&["update_rocksdb_statistics"])
+        .start_timer();
+    for (db_name, db) in [
+        (LEDGER_DB_NAME, ledger_rocksdb),
+        (STATE_MERKLE_DB_NAME, state_merkle_rocksdb),
+    ] {
+        let statistics = match db.get_statistics()? {
+            Some(statistics) => statistics,
+            None => continue,
+        };
+        let tickers = parse_rocksdb_tickers(&statistics);
+        for (rocksdb_ticker_name, aptos_rocksdb_ticker_name) in &*ROCKSDB_STATISTICS_MAP {
+            if let Some(count) = tickers.get(rocksdb_ticker_name) {
+                ROCKSDB_STATISTICS
+                    .with_label_values(&[db_name, aptos_rocksdb_ticker_name])
+                    .set(*count as i64);
+            }
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 struct RocksdbPropertyReporter {
     sender: Mutex<mpsc::Sender<()>>,
@@ -216,20 +373,38 @@ struct RocksdbPropertyReporter {
 impl RocksdbPropertyReporter {
     fn new(ledger_rocksdb: Arc<DB>, state_merkle_rocksdb: Arc<DB>) -> Self {
         let (send, recv) = mpsc::channel();
-        let join_handle = Some(thread::spawn(move || loop {
-            if let Err(e) = update_rocksdb_properties(&ledger_rocksdb, &state_merkle_rocksdb) {
-                warn!(
-                    error = ?e,
-                    "Updating rocksdb property failed."
-                );
-            }
-            // report rocksdb properties each 10 seconds
-            const TIMEOUT_MS: u64 = if cfg!(test) { 10 } else { 10000 };
+        let join_handle = Some(thread::spawn(move || {
+            // report rocksdb statistics each 30 seconds
+            const STATISTICS_INTERVAL_MS: u64 = if cfg!(test) { 10 } else { 30000 };
+            let mut last_statistics_update: Option<Instant> = None;
+            loop {
+                if let Err(e) = update_rocksdb_properties(&ledger_rocksdb, &state_merkle_rocksdb) {
+                    warn!(
+                        error = ?e,
+                        "Updating rocksdb property failed."
+                    );
+                }
+                if last_statistics_update.map_or(true, |last| {
+                    last.elapsed() >= Duration::from_millis(STATISTICS_INTERVAL_MS)
+                }) {
+                    if let Err(e) =
+                        update_rocksdb_statistics(&ledger_rocksdb, &state_merkle_rocksdb)
+                    {
+                        warn!(
+                            error = ?e,
+                            "Updating rocksdb statistics failed."
+                        );
+                    }
+                    last_statistics_update = Some(Instant::now());
+                }
+                // report rocksdb properties each 10 seconds
+                const TIMEOUT_MS: u64 = if cfg!(test) { 10 } else { 10000 };
 
-            match recv.recv_timeout(Duration::from_millis(TIMEOUT_MS)) {
-                Ok(_) => break,
-                Err(mpsc::RecvTimeoutError::Timeout) => (),
-                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                match recv.recv_timeout(Duration::from_millis(TIMEOUT_MS)) {
+                    Ok(_) => break,
+                    Err(mpsc::RecvTimeoutError::Timeout) => (),
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
             }
         }));
         Self {
@@ -288,6 +463,8 @@ impl AptosDB {
             Arc::clone(&arc_state_merkle_rocksdb),
             target_snapshot_size,
             max_nodes_per_lru_cache_shard,
+            // Like the node caches, the proof cache is only used if they're enabled
+            max_nodes_per_lru_cache_shard > 0,
             hack_for_tests,
         ));
         let ledger_pruner = LedgerPrunerManager::new(
@@ -353,13 +530,13 @@ impl AptosDB {
                     &gen_rocksdb_options(&rocksdb_configs.ledger_db_config, false),
                     ledger_db_path.clone(),
                     "ledger_db",
-                    gen_ledger_cfds(&rocksdb_configs.ledger_db_config),
+                    gen_ledger_cfds(&rocksdb_configs.ledger_db_config)?,
                 )?,
                 DB::open_cf(
                     &gen_rocksdb_options(&rocksdb_configs.state_merkle_db_config, false),
                     state_merkle_db_path.clone(),
                     "state_merkle_db",
-                    gen_state_merkle_cfds(&rocksdb_configs.state_merkle_db_config),
+                    gen_state_merkle_cfds(&rocksdb_configs.state_merkle_db_config)?,
                 )?,
             )
         };
@@ -373,6 +550,10 @@ impl AptosDB {
             readonly,
         );
 
+        if !readonly {
+            myself.start_block_index_backfill()?;
+        }
+
         if !readonly && enable_indexer {
             myself.open_indexer(db_root_path, rocksdb_configs.index_db_config)?;
         }
@@ -386,6 +567,32 @@ impl AptosDB {
         Ok(myself)
     }
 
+    /// Backfills the block index for blocks committed before the index existed in a background
+    /// thread, so that upgrading a large database doesn't hold up startup. Until it's done,
+    /// lookups of blocks that aren't indexed yet fall back to the `NewBlockEvent` stream.
+    fn start_block_index_backfill(&self) -> Result<()> {
+        if next_block_to_backfill(&self.ledger_store, &self.event_store)?.is_none() {
+            return Ok(());
+        }
+
+        let ledger_db = Arc::clone(&self.ledger_db);
+        let ledger_store = Arc::clone(&self.ledger_store);
+        let event_store = Arc::clone(&self.event_store);
+        thread::Builder::new()
+            .name("block_index_backfill".to_string())
+            .spawn(move || {
+                if let Err(err) = backfill_block_index(
+                    &ledger_db,
+                    &ledger_store,
+                    &event_store,
+                    BLOCK_INDEX_BACKFILL_BATCH_SIZE,
+                ) {
+                    error!(error = ?err, "Failed to backfill the block index.");
+                }
+            })?;
+        Ok(())
+    }
+
     fn open_indexer(
         &mut self,
         db_root_path: impl AsRef<Path>,
@@ -547,6 +754,40 @@ impl AptosDB {
         self.state_store.buffered_state()
     }
 
+    /// This writes the state values of `value_state_sets` without merklizing them, for tests
+    /// and benchmarks that only care about the state values and their stale indices.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn put_value_sets_for_test(
+        &self,
+        value_state_sets: Vec<&HashMap<StateKey, Option<StateValue>>>,
+        first_version: Version,
+    ) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        self.state_store.put_value_sets(
+            value_state_sets,
+            first_version,
+            StateStorageUsage::new_untracked(),
+            &mut batch,
+        )?;
+        self.ledger_db.write_schemas(batch)
+    }
+
+    /// This calculates the state usage and stale state value indices of `value_state_sets`
+    /// without writing them.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn calculate_stats_and_indices_for_test(
+        &self,
+        value_state_sets: &[&HashMap<StateKey, Option<StateValue>>],
+        first_version: Version,
+    ) -> Result<()> {
+        self.state_store.put_stats_and_indices(
+            value_state_sets,
+            first_version,
+            StateStorageUsage::new_untracked(),
+            &mut SchemaBatch::new(),
+        )
+    }
+
     /// This force the db to update rocksdb properties immediately.
     pub fn update_rocksdb_properties(&self) -> Result<()> {
         update_rocksdb_properties(&self.ledger_db, &self.state_merkle_db)
@@ -574,25 +815,7 @@ impl AptosDB {
         end_epoch: u64,
         limit: usize,
     ) -> Result<(Vec<LedgerInfoWithSignatures>, bool)> {
-        ensure!(
-            start_epoch <= end_epoch,
-            "Bad epoch range [{}, {})",
-            start_epoch,
-            end_epoch,
-        );
-        // Note that the latest epoch can be the same with the current epoch (in most cases), or
-        // current_epoch + 1 (when the latest ledger_info carries next validator set)
-        let latest_epoch = self
-            .ledger_store
-            .get_latest_ledger_info()?
-            .ledger_info()
-            .next_block_epoch();
-        ensure!(
-            end_epoch <= latest_epoch,
-            "Unable to provide epoch change ledger info for still open epoch. asked upper bound: {}, last sealed epoch: {}",
-            end_epoch,
-            latest_epoch - 1,  // okay to -1 because genesis LedgerInfo has .next_block_epoch() == 1
-        );
+        self.check_epoch_ending_ledger_infos_range(start_epoch, end_epoch)?;
 
         let (paging_epoch, more) = if end_epoch - start_epoch > limit as u64 {
             (start_epoch + limit as u64, true)
@@ -614,6 +837,45 @@ impl AptosDB {
         Ok((lis, more))
     }
 
+    /// Iterates over the ledger infos ending the epochs from `start_epoch` up to, but excluding,
+    /// `end_epoch`, with a range scan instead of the pages of `get_epoch_ending_ledger_infos`.
+    pub fn get_epoch_ending_ledger_infos_iter(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<impl Iterator<Item = Result<LedgerInfoWithSignatures>> + '_> {
+        self.check_epoch_ending_ledger_infos_range(start_epoch, end_epoch)?;
+        self.ledger_store
+            .get_epoch_ending_ledger_info_iter(start_epoch, end_epoch)
+    }
+
+    fn check_epoch_ending_ledger_infos_range(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<()> {
+        ensure!(
+            start_epoch <= end_epoch,
+            "Bad epoch range [{}, {})",
+            start_epoch,
+            end_epoch,
+        );
+        // Note that the latest epoch can be the same with the current epoch (in most cases), or
+        // current_epoch + 1 (when the latest ledger_info carries next validator set)
+        let latest_epoch = self
+            .ledger_store
+            .get_latest_ledger_info()?
+            .ledger_info()
+            .next_block_epoch();
+        ensure!(
+            end_epoch <= latest_epoch,
+            "Unable to provide epoch change ledger info for still open epoch. asked upper bound: {}, last sealed epoch: {}",
+            end_epoch,
+            latest_epoch - 1,  // okay to -1 because genesis LedgerInfo has .next_block_epoch() == 1
+        );
+        Ok(())
+    }
+
     /// Returns the transaction with proof for a given version, or error if the transaction is not
     /// found.
     fn get_transaction_with_proof(
@@ -672,6 +934,17 @@ impl AptosDB {
         Ok(())
     }
 
+    /// Compacts the state values in the ledger DB, see [`StateDb::compact`].
+    pub fn compact_state_db(&self, opts: Option<CompactionOpts>) -> Result<()> {
+        let start = Instant::now();
+        self.state_store.compact(opts)?;
+        info!(
+            time_ms = %start.elapsed().as_millis(),
+            "Compacted state values."
+        );
+        Ok(())
+    }
+
     // ================================== Private APIs ==================================
     fn get_events_by_event_key(
         &self,
@@ -773,6 +1046,8 @@ impl AptosDB {
                 .start_timer();
             zip_eq(first_version..=last_version, txns_to_commit)
                 .map(|(ver, txn_to_commit)| {
+                    self.ledger_store
+                        .put_block_index(ver, txn_to_commit.events(), cs)?;
                     self.event_store.put_events(ver, txn_to_commit.events(), cs)
                 })
                 .collect::<Result<Vec<_>>>()?;
@@ -1162,6 +1437,19 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn multi_get_state_values_by_version(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<Option<StateValue>>> {
+        gauged_api("multi_get_state_values_by_version", || {
+            error_if_version_is_pruned(&self.ledger_pruner, "State", version)?;
+
+            self.state_store
+                .multi_get_state_values_by_version(state_keys, version)
+        })
+    }
+
     /// Returns the proof of the given state key and version.
     fn get_state_proof_by_version_ext(
         &self,
@@ -1176,6 +1464,19 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_nonexistence_proof(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<SparseMerkleProofExt> {
+        gauged_api("get_state_nonexistence_proof", || {
+            error_if_version_is_pruned(&self.state_pruner, "State", version)?;
+
+            self.state_store
+                .get_state_nonexistence_proof(state_key, version)
+        })
+    }
+
     fn get_state_value_with_proof_by_version_ext(
         &self,
         state_store_key: &StateKey,
@@ -1260,6 +1561,17 @@ impl DbReader for AptosDB {
                 committed_version
             );
 
+            if let Some((first_version, new_block_event)) =
+                self.ledger_store.get_block_by_version(version)?
+            {
+                let last_version = self
+                    .ledger_store
+                    .get_next_block_first_version(first_version)?
+                    .map_or(committed_version, |v| v - 1);
+                return Ok((first_version, last_version, new_block_event));
+            }
+
+            // Not in the block index (yet), fall back to the NewBlockEvent stream.
             let (first_version, new_block_event) = self.event_store.get_block_metadata(version)?;
 
             let last_version = self
@@ -1276,6 +1588,27 @@ impl DbReader for AptosDB {
             let latest_li = self.get_latest_ledger_info()?;
             let committed_version = latest_li.ledger_info().version();
 
+            if let Some(first_version) = self.ledger_store.get_block_first_version(height)? {
+                ensure!(
+                    first_version <= committed_version,
+                    "Block {} starts at version {} > committed version {}",
+                    height,
+                    first_version,
+                    committed_version
+                );
+                error_if_version_is_pruned(&self.ledger_pruner, "NewBlockEvent", first_version)?;
+                let new_block_event = self
+                    .ledger_store
+                    .get_block_by_first_version(first_version)?
+                    .ok_or_else(|| AptosDbError::NotFound(format!("Block {}", height)))?;
+                let last_version = self
+                    .ledger_store
+                    .get_block_first_version(height + 1)?
+                    .map_or(committed_version, |v| v - 1);
+                return Ok((first_version, last_version, new_block_event));
+            }
+
+            // Not in the block index (yet), fall back to the NewBlockEvent stream.
             let event_key = new_block_event_key();
             let (first_version, new_block_event) =
                 self.event_store
@@ -1408,6 +1741,46 @@ impl DbReader for AptosDB {
             self.state_store.get_usage(version)
         })
     }
+
+    fn get_state_storage_usage_range(
+        &self,
+        first_version: Version,
+        last_version: Version,
+        max_points: usize,
+    ) -> Result<StateStorageUsageRange> {
+        gauged_api("get_state_storage_usage_range", || {
+            error_if_version_is_pruned(&self.ledger_pruner, "state storage usage", last_version)?;
+            let min_readable_version = self.ledger_pruner.get_min_readable_version();
+            let clamped = first_version < min_readable_version;
+            let first_version = std::cmp::max(first_version, min_readable_version);
+
+            let delta = self
+                .state_store
+                .get_usage_delta(first_version, last_version)?;
+            let points = sample_versions(first_version, last_version, max_points)
+                .into_iter()
+                .map(|version| {
+                    let usage = self.state_store.get_usage(Some(version))?;
+                    ensure!(
+                        !usage.is_untracked(),
+                        "State storage usage is not tracked at version {}",
+                        version
+                    );
+                    Ok(StateStorageUsagePoint {
+                        version,
+                        items: usage.items(),
+                        bytes: usage.bytes(),
+                    })
+                })
+                .collect::<Result<_>>()?;
+
+            Ok(StateStorageUsageRange {
+                clamped,
+                delta,
+                points,
+            })
+        })
+    }
 }
 
 impl DbWriter for AptosDB {
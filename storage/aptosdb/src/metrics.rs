@@ -154,6 +154,19 @@ pub static ROCKSDB_PROPERTIES: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Rocksdb statistics, which are DB wide rather than per column family
+pub static ROCKSDB_STATISTICS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "aptos_rocksdb_statistics",
+        // metric description
+        "rocksdb ticker statistics",
+        // metric labels (dimensions)
+        &["db_name", "ticker_name",]
+    )
+    .unwrap()
+});
+
 // Async committer gauges:
 pub(crate) static LATEST_SNAPSHOT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
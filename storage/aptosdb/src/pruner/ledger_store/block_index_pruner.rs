@@ -0,0 +1,29 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+use crate::{pruner::db_sub_pruner::DBSubPruner, LedgerStore};
+use schemadb::SchemaBatch;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct BlockIndexPruner {
+    ledger_store: Arc<LedgerStore>,
+}
+
+impl DBSubPruner for BlockIndexPruner {
+    fn prune(
+        &self,
+        db_batch: &mut SchemaBatch,
+        min_readable_version: u64,
+        target_version: u64,
+    ) -> anyhow::Result<()> {
+        self.ledger_store
+            .prune_block_index(min_readable_version, target_version, db_batch)?;
+        Ok(())
+    }
+}
+
+impl BlockIndexPruner {
+    pub(in crate::pruner) fn new(ledger_store: Arc<LedgerStore>) -> Self {
+        BlockIndexPruner { ledger_store }
+    }
+}
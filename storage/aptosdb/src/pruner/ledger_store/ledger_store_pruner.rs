@@ -10,11 +10,12 @@ use crate::{
         db_pruner::DBPruner,
         db_sub_pruner::DBSubPruner,
         event_store::event_store_pruner::EventStorePruner,
+        ledger_store::block_index_pruner::BlockIndexPruner,
         transaction_store::{
             transaction_store_pruner::TransactionStorePruner, write_set_pruner::WriteSetPruner,
         },
     },
-    utils, EventStore, StateStore, TransactionStore,
+    utils, EventStore, LedgerStore, StateStore, TransactionStore,
 };
 use aptos_types::transaction::{AtomicVersion, Version};
 use schemadb::{SchemaBatch, DB};
@@ -33,6 +34,7 @@ pub(crate) struct LedgerPruner {
     state_value_pruner: Arc<dyn DBSubPruner + Send + Sync>,
     event_store_pruner: Arc<dyn DBSubPruner + Send + Sync>,
     write_set_pruner: Arc<dyn DBSubPruner + Send + Sync>,
+    block_index_pruner: Arc<dyn DBSubPruner + Send + Sync>,
 }
 
 impl DBPruner for LedgerPruner {
@@ -102,6 +104,7 @@ impl LedgerPruner {
         db: Arc<DB>,
         transaction_store: Arc<TransactionStore>,
         event_store: Arc<EventStore>,
+        ledger_store: Arc<LedgerStore>,
         state_store: Arc<StateStore>,
     ) -> Self {
         let pruner = LedgerPruner {
@@ -114,6 +117,7 @@ impl LedgerPruner {
             state_value_pruner: Arc::new(StateValuePruner::new(state_store)),
             event_store_pruner: Arc::new(EventStorePruner::new(event_store)),
             write_set_pruner: Arc::new(WriteSetPruner::new(transaction_store)),
+            block_index_pruner: Arc::new(BlockIndexPruner::new(ledger_store)),
         };
         pruner.initialize();
         pruner
@@ -157,6 +161,8 @@ impl LedgerPruner {
             .prune(db_batch, min_readable_version, current_target_version)?;
         self.event_store_pruner
             .prune(db_batch, min_readable_version, current_target_version)?;
+        self.block_index_pruner
+            .prune(db_batch, min_readable_version, current_target_version)?;
 
         Ok(current_target_version)
     }
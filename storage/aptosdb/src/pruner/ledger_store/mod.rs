@@ -1,4 +1,5 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub(crate) mod block_index_pruner;
 pub(crate) mod ledger_store_pruner;
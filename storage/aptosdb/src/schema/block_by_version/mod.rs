@@ -0,0 +1,55 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index of blocks by the version of their
+//! first transaction, so that the block a version belongs to can be found with a single
+//! `seek_for_prev` instead of walking the `NewBlockEvent` stream.
+//!
+//! ```text
+//! |<------key----->|<-----value----->|
+//! | first_version  | new_block_event |
+//! ```
+//!
+//! `first_version` is serialized in big endian so that records in RocksDB will be in order of
+//! their numeric value.
+
+use crate::schema::{ensure_slice_len_eq, BLOCK_BY_VERSION_CF_NAME};
+use anyhow::Result;
+use aptos_types::{account_config::NewBlockEvent, transaction::Version};
+use byteorder::{BigEndian, ReadBytesExt};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(
+    BlockByVersionSchema,
+    Version,
+    NewBlockEvent,
+    BLOCK_BY_VERSION_CF_NAME
+);
+
+impl KeyCodec<BlockByVersionSchema> for Version {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<BlockByVersionSchema> for NewBlockEvent {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,18 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        version in any::<Version>(),
+        new_block_event in any::<NewBlockEvent>(),
+    ) {
+        assert_encode_decode::<BlockByVersionSchema>(&version, &new_block_event);
+    }
+}
+
+test_no_panic_decoding!(BlockByVersionSchema);
@@ -6,6 +6,8 @@
 //!
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
+pub(crate) mod block_by_height;
+pub(crate) mod block_by_version;
 pub(crate) mod epoch_by_version;
 pub(crate) mod event;
 pub(crate) mod event_accumulator;
@@ -28,6 +30,8 @@ pub(crate) mod write_set;
 use anyhow::{ensure, Result};
 use schemadb::ColumnFamilyName;
 
+pub const BLOCK_BY_HEIGHT_CF_NAME: ColumnFamilyName = "block_by_height";
+pub const BLOCK_BY_VERSION_CF_NAME: ColumnFamilyName = "block_by_version";
 pub const EPOCH_BY_VERSION_CF_NAME: ColumnFamilyName = "epoch_by_version";
 pub const EVENT_ACCUMULATOR_CF_NAME: ColumnFamilyName = "event_accumulator";
 pub const EVENT_BY_KEY_CF_NAME: ColumnFamilyName = "event_by_key";
@@ -76,6 +80,8 @@ pub mod fuzzing {
     pub fn fuzz_decode(data: &[u8]) {
         #[allow(unused_must_use)]
         {
+            assert_no_panic_decoding::<super::block_by_height::BlockByHeightSchema>(data);
+            assert_no_panic_decoding::<super::block_by_version::BlockByVersionSchema>(data);
             assert_no_panic_decoding::<super::epoch_by_version::EpochByVersionSchema>(data);
             assert_no_panic_decoding::<super::event::EventSchema>(data);
             assert_no_panic_decoding::<super::event_accumulator::EventAccumulatorSchema>(data);
@@ -24,11 +24,16 @@ use aptos_types::{
     transaction::Version,
 };
 use executor_types::in_memory_state_calculator::InMemoryStateCalculator;
-use schemadb::{ReadOptions, SchemaBatch, DB};
-use std::{collections::HashMap, ops::Deref, sync::Arc};
+use rayon::prelude::*;
+use schemadb::{schema::Schema, CompactOptions, ReadOptions, SchemaBatch, DB};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    sync::Arc,
+};
 use storage_interface::{
     cached_state_view::CachedStateView, state_delta::StateDelta,
-    sync_proof_fetcher::SyncProofFetcher, DbReader, StateSnapshotReceiver,
+    sync_proof_fetcher::SyncProofFetcher, DbReader, StateSnapshotReceiver, StateStorageUsageDelta,
 };
 
 use crate::{
@@ -36,7 +41,10 @@ use crate::{
     schema::state_value::StateValueSchema,
     stale_state_value_index::StaleStateValueIndexSchema,
     state_merkle_db::StateMerkleDb,
-    state_store::buffered_state::BufferedState,
+    state_store::{
+        buffered_state::BufferedState,
+        state_proof_cache::{StateProofCache, STATE_PROOF_CACHE_SIZE},
+    },
     version_data::{VersionData, VersionDataSchema},
     AptosDbError, LedgerStore, TransactionStore, OTHER_TIMERS_SECONDS,
 };
@@ -46,6 +54,7 @@ use aptos_types::nibble::nibble_path::NibblePath;
 
 pub(crate) mod buffered_state;
 mod state_merkle_batch_committer;
+mod state_proof_cache;
 mod state_snapshot_committer;
 #[cfg(test)]
 mod state_store_test;
@@ -58,10 +67,21 @@ const MAX_WRITE_SETS_AFTER_SNAPSHOT: LeafCount = buffered_state::TARGET_SNAPSHOT
     * (buffered_state::ASYNC_COMMIT_CHANNEL_BUFFER_SIZE + 2 + 1/*  Rendezvous channel */)
     * 2;
 
+/// Options for a manual compaction, see [`StateDb::compact`].
+#[derive(Clone, Debug, Default)]
+pub struct CompactionOpts {
+    /// Level the compacted files are moved to, the lowest level that can hold them if `None`.
+    pub target_level: Option<i32>,
+    /// Whether the manual compaction runs exclusively, i.e. blocks automatic compactions while
+    /// it's running.
+    pub exclusive: bool,
+}
+
 #[derive(Debug)]
 pub struct StateDb {
     pub ledger_db: Arc<DB>,
     pub state_merkle_db: Arc<StateMerkleDb>,
+    proof_cache: Option<StateProofCache>,
 }
 
 #[derive(Debug)]
@@ -111,6 +131,40 @@ impl DbReader for StateDb {
             .map(|(_, value)| value))
     }
 
+    /// Gets the state values of many keys at a version. Values written exactly at `version` are
+    /// read in a single batched lookup, the other keys are seeked to their latest value before it.
+    fn multi_get_state_values_by_version(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<Option<StateValue>>> {
+        let exact_keys: Vec<_> = state_keys
+            .iter()
+            .map(|state_key| (state_key.clone(), version))
+            .collect();
+        let exact_values = self.ledger_db.multi_get::<StateValueSchema>(&exact_keys)?;
+
+        let mut read_opts = ReadOptions::default();
+        // We want `None` if the state_key changes in iteration.
+        read_opts.set_prefix_same_as_start(true);
+        let mut iter = self.ledger_db.iter::<StateValueSchema>(read_opts)?;
+        state_keys
+            .iter()
+            .zip(exact_values)
+            .map(|(state_key, exact_value)| match exact_value {
+                // Written, or deleted, exactly at `version`.
+                Some(value_opt) => Ok(value_opt),
+                None => {
+                    iter.seek(&(state_key.clone(), version))?;
+                    Ok(iter
+                        .next()
+                        .transpose()?
+                        .and_then(|(_, value_opt)| value_opt))
+                }
+            })
+            .collect()
+    }
+
     /// Returns the proof of the given state key and version.
     fn get_state_proof_by_version_ext(
         &self,
@@ -123,22 +177,52 @@ impl DbReader for StateDb {
         Ok(proof)
     }
 
+    /// Returns the proof that the given state key doesn't exist at the given version.
+    fn get_state_nonexistence_proof(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<SparseMerkleProofExt> {
+        let (leaf_data, proof) = self
+            .state_merkle_db
+            .get_with_proof_ext(state_key, version)?;
+        ensure!(
+            leaf_data.is_none(),
+            "State key {:?} exists at version {}",
+            state_key,
+            version
+        );
+        Ok(proof)
+    }
+
     /// Get the state value with proof given the state key and version
     fn get_state_value_with_proof_by_version_ext(
         &self,
         state_key: &StateKey,
         version: Version,
     ) -> Result<(Option<StateValue>, SparseMerkleProofExt)> {
+        if let Some(value_with_proof) = self
+            .proof_cache
+            .as_ref()
+            .and_then(|cache| cache.get(state_key, version))
+        {
+            return Ok(value_with_proof);
+        }
+
         let (leaf_data, proof) = self
             .state_merkle_db
             .get_with_proof_ext(state_key, version)?;
-        Ok((
+        let value_with_proof = (
             match leaf_data {
                 Some((_, (key, version))) => Some(self.expect_value_by_version(&key, version)?),
                 None => None,
             },
             proof,
-        ))
+        );
+        if let Some(cache) = &self.proof_cache {
+            cache.put(state_key.clone(), version, value_with_proof.clone());
+        }
+        Ok(value_with_proof)
     }
 
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
@@ -172,6 +256,27 @@ impl StateDb {
     }
 }
 
+impl StateDb {
+    /// Compacts the state value column family, which accumulates space amplification on long
+    /// running nodes when automatic compaction can't keep up with the write load. Blocks until
+    /// the compaction is done.
+    pub fn compact(&self, opts: Option<CompactionOpts>) -> Result<()> {
+        let opts = opts.unwrap_or_default();
+        let mut compact_opts = CompactOptions::default();
+        compact_opts.set_exclusive_manual_compaction(opts.exclusive);
+        if let Some(target_level) = opts.target_level {
+            compact_opts.set_change_level(true);
+            compact_opts.set_target_level(target_level);
+        }
+
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["compact_state_values"])
+            .start_timer();
+        self.ledger_db
+            .compact_range_cf(StateValueSchema::COLUMN_FAMILY_NAME, &compact_opts)
+    }
+}
+
 impl DbReader for StateStore {
     /// Returns the latest state snapshot strictly before `next_version` if any.
     fn get_state_snapshot_before(
@@ -192,6 +297,15 @@ impl DbReader for StateStore {
         self.deref().get_state_value_by_version(state_key, version)
     }
 
+    fn multi_get_state_values_by_version(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<Option<StateValue>>> {
+        self.deref()
+            .multi_get_state_values_by_version(state_keys, version)
+    }
+
     /// Returns the proof of the given state key and version.
     fn get_state_proof_by_version_ext(
         &self,
@@ -202,6 +316,16 @@ impl DbReader for StateStore {
             .get_state_proof_by_version_ext(state_key, version)
     }
 
+    /// Returns the proof that the given state key doesn't exist at the given version.
+    fn get_state_nonexistence_proof(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<SparseMerkleProofExt> {
+        self.deref()
+            .get_state_nonexistence_proof(state_key, version)
+    }
+
     /// Get the state value with proof extension given the state key and version
     fn get_state_value_with_proof_by_version_ext(
         &self,
@@ -238,6 +362,7 @@ impl StateStore {
         state_merkle_db: Arc<DB>,
         target_snapshot_size: usize,
         max_nodes_per_lru_cache_shard: usize,
+        enable_proof_cache: bool,
         hack_for_tests: bool,
     ) -> Self {
         let state_merkle_db = Arc::new(StateMerkleDb::new(
@@ -247,6 +372,7 @@ impl StateStore {
         let state_db = Arc::new(StateDb {
             ledger_db,
             state_merkle_db,
+            proof_cache: enable_proof_cache.then(|| StateProofCache::new(STATE_PROOF_CACHE_SIZE)),
         });
         let buffered_state = Mutex::new(
             Self::create_buffered_state_from_latest_snapshot(
@@ -472,6 +598,27 @@ impl StateStore {
         self.state_db.get_state_storage_usage(version)
     }
 
+    /// Returns how the state storage usage changed from `first_version` to `last_version`, or an
+    /// error if the usage isn't tracked at either of them.
+    pub fn get_usage_delta(
+        &self,
+        first_version: Version,
+        last_version: Version,
+    ) -> Result<StateStorageUsageDelta> {
+        ensure!(
+            first_version <= last_version,
+            "Invalid version range: [{}, {}]",
+            first_version,
+            last_version,
+        );
+        StateStorageUsageDelta::new(
+            first_version,
+            self.get_usage(Some(first_version))?,
+            last_version,
+            self.get_usage(Some(last_version))?,
+        )
+    }
+
     /// Put storage usage stats and State key and value indices into the batch.
     /// The state KV indices will be generated as follows:
     /// 1. A deletion at current version is always coupled with stale index for the tombstone with
@@ -494,6 +641,7 @@ impl StateStore {
 
         let base_version = first_version.checked_sub(1);
         let mut usage = self.get_usage(base_version)?;
+        let mut base_values = self.get_base_values(value_state_sets, base_version)?;
         let mut cache = HashMap::<StateKey, (Version, Option<StateValue>)>::new();
 
         // calculate total state size in bytes
@@ -519,11 +667,8 @@ impl StateStore {
                     cache.insert(key.clone(), (version, value.clone()))
                 {
                     old_value_opt.map(|value| (old_version, value))
-                } else if let Some(base_version) = base_version {
-                    self.state_db
-                        .get_state_value_with_version_by_version(key, base_version)?
                 } else {
-                    None
+                    base_values.remove(key).flatten()
                 };
 
                 if let Some((old_version, old_value)) = old_version_and_value_opt {
@@ -557,6 +702,30 @@ impl StateStore {
         Ok(())
     }
 
+    /// Looks up the latest value as of `base_version` of each key updated in `value_state_sets`.
+    /// The lookups are done in parallel, because with tens of thousands of updates in a block,
+    /// a point lookup per key dominates the time spent on calculating the stale indices.
+    fn get_base_values<'a>(
+        &self,
+        value_state_sets: &[&'a HashMap<StateKey, Option<StateValue>>],
+        base_version: Option<Version>,
+    ) -> Result<HashMap<&'a StateKey, Option<(Version, StateValue)>>> {
+        let base_version = match base_version {
+            Some(base_version) => base_version,
+            None => return Ok(HashMap::new()),
+        };
+        let keys: HashSet<&StateKey> = value_state_sets.iter().flat_map(|kvs| kvs.keys()).collect();
+        let state_db = &self.state_db;
+        keys.into_par_iter()
+            .map(|key| {
+                Ok((
+                    key,
+                    state_db.get_state_value_with_version_by_version(key, base_version)?,
+                ))
+            })
+            .collect()
+    }
+
     /// Merklize the results generated by `value_state_sets` to `batch` and return the result root
     /// hashes for each write set.
     #[cfg(test)]
@@ -604,6 +773,14 @@ impl StateStore {
         }))
     }
 
+    /// Iterates over the complete state snapshot at `version`, in the order of the hashed keys.
+    pub fn iter_state_at_version(
+        self: &Arc<Self>,
+        version: Version,
+    ) -> Result<impl Iterator<Item = Result<(StateKey, StateValue)>> + Send + Sync> {
+        self.get_state_key_and_value_iter(version, HashValue::zero())
+    }
+
     pub fn get_value_chunk_with_proof(
         self: &Arc<Self>,
         version: Version,
@@ -0,0 +1,50 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_infallible::Mutex;
+use aptos_types::{
+    proof::SparseMerkleProofExt,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use lru::LruCache;
+
+/// The number of (key, version) pairs whose value and proof are kept
+pub(crate) const STATE_PROOF_CACHE_SIZE: usize = 1024;
+
+pub(crate) type StateValueWithProof = (Option<StateValue>, SparseMerkleProofExt);
+
+/// Recently read state values and their proofs.
+///
+/// The state at a version never changes once it's committed, so entries never go stale and are
+/// only ever evicted to make room for new ones.
+#[derive(Debug)]
+pub(crate) struct StateProofCache {
+    cache: Mutex<LruCache<(StateKey, Version), StateValueWithProof>>,
+}
+
+impl StateProofCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            cache: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub fn get(&self, state_key: &StateKey, version: Version) -> Option<StateValueWithProof> {
+        self.cache
+            .lock()
+            .get(&(state_key.clone(), version))
+            .cloned()
+    }
+
+    pub fn put(
+        &self,
+        state_key: StateKey,
+        version: Version,
+        value_with_proof: StateValueWithProof,
+    ) {
+        self.cache
+            .lock()
+            .put((state_key, version), value_with_proof);
+    }
+}
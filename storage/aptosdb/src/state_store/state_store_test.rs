@@ -108,6 +108,109 @@ fn test_empty_store() {
         .is_err());
 }
 
+#[test]
+fn test_state_proof_cache() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let key = StateKey::Raw(String::from("test_key").into_bytes());
+    let value = StateValue::from(String::from("test_val").into_bytes());
+    let value_update = StateValue::from(String::from("test_val_update").into_bytes());
+
+    put_value_set(store, vec![(key.clone(), value.clone())], 0, None);
+    put_value_set(store, vec![(key.clone(), value_update)], 1, Some(0));
+    let cache = store.proof_cache.as_ref().unwrap();
+
+    assert!(cache.get(&key, 0).is_none());
+    let (read_value, read_proof) = store
+        .get_state_value_with_proof_by_version_ext(&key, 0)
+        .unwrap();
+    assert_eq!(read_value, Some(value));
+
+    // A hit returns exactly what was read from the tree
+    let (cached_value, cached_proof) = cache.get(&key, 0).unwrap();
+    assert_eq!(cached_value, read_value);
+    assert_eq!(
+        bcs::to_bytes(&cached_proof).unwrap(),
+        bcs::to_bytes(&read_proof).unwrap()
+    );
+    let (hit_value, hit_proof) = store
+        .get_state_value_with_proof_by_version_ext(&key, 0)
+        .unwrap();
+    assert_eq!(hit_value, read_value);
+    assert_eq!(
+        bcs::to_bytes(&hit_proof).unwrap(),
+        bcs::to_bytes(&read_proof).unwrap()
+    );
+
+    // The same key at another version misses
+    assert!(cache.get(&key, 1).is_none());
+}
+
+#[test]
+fn test_multi_get_state_values_by_version() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let key1 = StateKey::Raw(String::from("test_key1").into_bytes());
+    let key2 = StateKey::Raw(String::from("test_key2").into_bytes());
+    let key3 = StateKey::Raw(String::from("test_key3").into_bytes());
+    let absent_key = StateKey::Raw(String::from("test_key4").into_bytes());
+
+    let value1 = StateValue::from(String::from("test_val1").into_bytes());
+    let value1_update = StateValue::from(String::from("test_val1_update").into_bytes());
+    let value2 = StateValue::from(String::from("test_val2").into_bytes());
+    let value3 = StateValue::from(String::from("test_val3").into_bytes());
+
+    put_value_set(
+        store,
+        vec![(key1.clone(), value1), (key2.clone(), value2.clone())],
+        0, /* version */
+        None,
+    );
+    put_value_set(
+        store,
+        vec![(key1.clone(), value1_update.clone())],
+        1, /* version */
+        Some(0),
+    );
+    put_value_set(
+        store,
+        vec![(key3.clone(), value3)],
+        2, /* version */
+        Some(1),
+    );
+
+    // Out of order, with duplicates, and keys written both at and before the versions.
+    let keys = vec![key3, absent_key, key1.clone(), key2, key1];
+    for version in 0..4 {
+        let expected: Vec<_> = keys
+            .iter()
+            .map(|key| store.get_state_value_by_version(key, version).unwrap())
+            .collect();
+        assert_eq!(
+            store
+                .multi_get_state_values_by_version(&keys, version)
+                .unwrap(),
+            expected
+        );
+    }
+    assert_eq!(
+        store.multi_get_state_values_by_version(&keys, 1).unwrap(),
+        vec![
+            None,
+            None,
+            Some(value1_update.clone()),
+            Some(value2),
+            Some(value1_update)
+        ]
+    );
+    assert!(store
+        .multi_get_state_values_by_version(&[], 1)
+        .unwrap()
+        .is_empty());
+}
+
 #[test]
 fn test_state_store_reader_writer() {
     let tmp_dir = TempPath::new();
@@ -153,6 +256,122 @@ fn test_state_store_reader_writer() {
     verify_value_and_proof(store, key3, Some(&value3), 1, root);
 }
 
+#[test]
+fn test_state_nonexistence_proof_in_empty_tree() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let root = put_value_set(store, vec![], 0 /* version */, None);
+    assert_eq!(root, *SPARSE_MERKLE_PLACEHOLDER_HASH);
+
+    let key = StateKey::Raw(String::from("test_key").into_bytes());
+    let proof = store.get_state_nonexistence_proof(&key, 0).unwrap();
+    assert!(proof.leaf().is_none());
+    assert!(proof.siblings().is_empty());
+    proof.verify_nonexistence(root, key.hash()).unwrap();
+}
+
+#[test]
+fn test_state_nonexistence_proof() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let key1 = StateKey::Raw(String::from("test_key1").into_bytes());
+    let key2 = StateKey::Raw(String::from("test_key2").into_bytes());
+    let root = put_value_set(
+        store,
+        vec![
+            (
+                key1.clone(),
+                StateValue::from(String::from("test_val1").into_bytes()),
+            ),
+            (
+                key2.clone(),
+                StateValue::from(String::from("test_val2").into_bytes()),
+            ),
+        ],
+        0, /* version */
+        None,
+    );
+
+    // Existing keys have no proof of nonexistence
+    assert!(store.get_state_nonexistence_proof(&key1, 0).is_err());
+    assert!(store.get_state_nonexistence_proof(&key2, 0).is_err());
+
+    // Find absent keys sharing a prefix with one of the leaves, so that the leaf takes their
+    // position, and absent keys whose position is a placeholder node
+    let mut prefix_sharing_key = None;
+    let mut placeholder_key = None;
+    for i in 0..1000 {
+        let key = StateKey::Raw(format!("absent_key{}", i).into_bytes());
+        let proof = store.get_state_nonexistence_proof(&key, 0).unwrap();
+        match proof.leaf() {
+            Some(leaf) => {
+                assert!(leaf.key() == key1.hash() || leaf.key() == key2.hash());
+                prefix_sharing_key.get_or_insert((key, proof));
+            }
+            None => {
+                assert!(!proof.siblings().is_empty());
+                placeholder_key.get_or_insert((key, proof));
+            }
+        }
+        if prefix_sharing_key.is_some() && placeholder_key.is_some() {
+            break;
+        }
+    }
+
+    for (key, proof) in [prefix_sharing_key.unwrap(), placeholder_key.unwrap()] {
+        proof.verify_nonexistence(root, key.hash()).unwrap();
+        // The proof doesn't hold against another root, or for the keys that exist
+        let other_root = HashValue::random();
+        assert!(proof.verify_nonexistence(other_root, key.hash()).is_err());
+        if let Some(leaf) = proof.leaf() {
+            assert!(proof.verify_nonexistence(root, leaf.key()).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_iter_state_at_version() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let key1 = StateKey::Raw(String::from("test_key1").into_bytes());
+    let key2 = StateKey::Raw(String::from("test_key2").into_bytes());
+
+    let value1 = StateValue::from(String::from("test_val1").into_bytes());
+    let value1_update = StateValue::from(String::from("test_val1_update").into_bytes());
+    let value2 = StateValue::from(String::from("test_val2").into_bytes());
+
+    put_value_set(
+        store,
+        vec![(key1.clone(), value1.clone())],
+        0, /* version */
+        None,
+    );
+    put_value_set(
+        store,
+        vec![
+            (key1.clone(), value1_update.clone()),
+            (key2.clone(), value2.clone()),
+        ],
+        1, /* version */
+        Some(0),
+    );
+
+    let state_at_version = |version| {
+        store
+            .iter_state_at_version(version)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+    };
+    assert_eq!(state_at_version(0), vec![(key1.clone(), value1)]);
+    let mut expected = vec![(key1, value1_update), (key2, value2)];
+    expected.sort_unstable_by_key(|(key, _)| key.hash());
+    assert_eq!(state_at_version(1), expected);
+}
+
 #[test]
 fn test_get_values_by_key_prefix() {
     let tmp_dir = TempPath::new();
@@ -593,9 +812,264 @@ pub fn test_get_state_snapshot_before() {
     assert_eq!(store.get_state_snapshot_before(0).unwrap(), None,);
 }
 
+/// Puts a write set, possibly deleting keys, without merklizing it, which the usage doesn't need.
+fn put_write_set(
+    store: &StateStore,
+    write_set: Vec<(StateKey, Option<StateValue>)>,
+    version: Version,
+) {
+    let value_set: HashMap<_, _> = write_set.into_iter().collect();
+    let mut batch = SchemaBatch::new();
+    store
+        .put_value_sets(
+            vec![&value_set],
+            version,
+            StateStorageUsage::new_untracked(),
+            &mut batch,
+        )
+        .unwrap();
+    store.ledger_db.write_schemas(batch).unwrap();
+}
+
+#[test]
+fn test_usage_delta() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let size = |key: &StateKey, value: &StateValue| (key.size() + value.size()) as i64;
+
+    let key1 = StateKey::Raw(b"key1".to_vec());
+    let key2 = StateKey::Raw(b"key2".to_vec());
+    let value1 = StateValue::from(vec![1; 10]);
+    let value1_update = StateValue::from(vec![1; 100]);
+    let value2 = StateValue::from(vec![2; 20]);
+
+    put_write_set(
+        store,
+        vec![
+            (key1.clone(), Some(value1.clone())),
+            (key2.clone(), Some(value2.clone())),
+        ],
+        0,
+    );
+    put_write_set(store, vec![(key1.clone(), Some(value1_update.clone()))], 1);
+    put_write_set(store, vec![(key2.clone(), None)], 2);
+
+    let delta = store.get_usage_delta(0, 1).unwrap();
+    assert_eq!((delta.first_version, delta.last_version), (0, 1));
+    assert_eq!(delta.items, 0);
+    assert_eq!(
+        delta.bytes,
+        size(&key1, &value1_update) - size(&key1, &value1)
+    );
+
+    let delta = store.get_usage_delta(1, 2).unwrap();
+    assert_eq!(delta.items, -1);
+    assert_eq!(delta.bytes, -size(&key2, &value2));
+
+    let delta = store.get_usage_delta(0, 2).unwrap();
+    assert_eq!(delta.items, -1);
+    assert_eq!(
+        delta.bytes,
+        size(&key1, &value1_update) - size(&key1, &value1) - size(&key2, &value2)
+    );
+
+    let delta = store.get_usage_delta(2, 2).unwrap();
+    assert_eq!((delta.items, delta.bytes), (0, 0));
+    assert!(store.get_usage_delta(2, 1).is_err());
+    // No usage recorded beyond the latest version
+    assert!(store.get_usage_delta(0, 3).is_err());
+    // An untracked usage reads as zero, so no delta is made from it at either end
+    let untracked = StateStorageUsage::new_untracked();
+    let usage = store.get_usage(Some(2)).unwrap();
+    assert!(StateStorageUsageDelta::new(0, untracked, 2, usage).is_err());
+    assert!(StateStorageUsageDelta::new(0, usage, 2, untracked).is_err());
+}
+
+#[test]
+fn test_state_storage_usage_range() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+
+    let mut total_bytes = 0;
+    for version in 0..10 {
+        let key = StateKey::Raw(format!("key{}", version).into_bytes());
+        let value = StateValue::from(vec![0; version as usize]);
+        total_bytes += key.size() + value.size();
+        put_write_set(store, vec![(key, Some(value))], version);
+    }
+
+    let range = db.get_state_storage_usage_range(2, 9, 3).unwrap();
+    assert!(!range.clamped);
+    assert_eq!(
+        range.points.iter().map(|p| p.version).collect::<Vec<_>>(),
+        vec![2, 5, 9]
+    );
+    assert_eq!(range.points[2].items, 10);
+    assert_eq!(range.points[2].bytes, total_bytes);
+    assert_eq!(range.delta.items, 7);
+    assert_eq!(
+        range.delta.bytes,
+        (range.points[2].bytes - range.points[0].bytes) as i64
+    );
+
+    // Fewer versions than points
+    let range = db.get_state_storage_usage_range(8, 9, 10).unwrap();
+    assert_eq!(
+        range.points.iter().map(|p| p.version).collect::<Vec<_>>(),
+        vec![8, 9]
+    );
+}
+
+#[test]
+fn test_sample_versions() {
+    assert_eq!(crate::sample_versions(0, 100, 0), Vec::<Version>::new());
+    assert_eq!(crate::sample_versions(0, 100, 1), vec![100]);
+    assert_eq!(crate::sample_versions(0, 100, 2), vec![0, 100]);
+    assert_eq!(crate::sample_versions(0, 100, 5), vec![0, 25, 50, 75, 100]);
+    assert_eq!(crate::sample_versions(5, 7, 5), vec![5, 6, 7]);
+    assert_eq!(
+        crate::sample_versions(0, Version::MAX, 3),
+        vec![0, Version::MAX / 2, Version::MAX]
+    );
+}
+
+/// The serial implementation of `StateStore::put_stats_and_indices`, which looks up the old value
+/// of each key one by one, used as the reference for the parallel one.
+fn put_stats_and_indices_serially(
+    store: &StateStore,
+    value_state_sets: &[&HashMap<StateKey, Option<StateValue>>],
+    first_version: Version,
+    batch: &mut SchemaBatch,
+) {
+    let base_version = first_version.checked_sub(1);
+    let mut usage = store.get_usage(base_version).unwrap();
+    let mut cache = HashMap::<StateKey, (Version, Option<StateValue>)>::new();
+
+    for (idx, kvs) in value_state_sets.iter().enumerate() {
+        let version = first_version + idx as Version;
+
+        for (key, value) in kvs.iter() {
+            if let Some(value) = value {
+                usage.add_item(key.size() + value.size());
+            } else {
+                batch
+                    .put::<StaleStateValueIndexSchema>(
+                        &StaleStateValueIndex {
+                            stale_since_version: version,
+                            version,
+                            state_key: key.clone(),
+                        },
+                        &(),
+                    )
+                    .unwrap();
+            }
+
+            let old_version_and_value_opt = if let Some((old_version, old_value_opt)) =
+                cache.insert(key.clone(), (version, value.clone()))
+            {
+                old_value_opt.map(|value| (old_version, value))
+            } else if let Some(base_version) = base_version {
+                store
+                    .state_db
+                    .get_state_value_with_version_by_version(key, base_version)
+                    .unwrap()
+            } else {
+                None
+            };
+
+            if let Some((old_version, old_value)) = old_version_and_value_opt {
+                usage.remove_item(key.size() + old_value.size());
+                batch
+                    .put::<StaleStateValueIndexSchema>(
+                        &StaleStateValueIndex {
+                            stale_since_version: version,
+                            version: old_version,
+                            state_key: key.clone(),
+                        },
+                        &(),
+                    )
+                    .unwrap();
+            }
+        }
+
+        batch
+            .put::<VersionDataSchema>(&version, &usage.into())
+            .unwrap();
+    }
+}
+
+/// Writes `batch` to an empty DB and reads back the stale indices and usages in it.
+fn stale_indices_and_usages_in_batch(
+    batch: SchemaBatch,
+) -> (Vec<StaleStateValueIndex>, Vec<(Version, VersionData)>) {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    db.ledger_db.write_schemas(batch).unwrap();
+
+    let mut iter = db
+        .ledger_db
+        .iter::<StaleStateValueIndexSchema>(ReadOptions::default())
+        .unwrap();
+    iter.seek_to_first();
+    let stale_indices = iter.map(|res| res.unwrap().0).collect();
+
+    let mut iter = db
+        .ledger_db
+        .iter::<VersionDataSchema>(ReadOptions::default())
+        .unwrap();
+    iter.seek_to_first();
+    let usages = iter.collect::<Result<_>>().unwrap();
+
+    (stale_indices, usages)
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
 
+    #[test]
+    fn test_put_stats_and_indices_matches_serial(
+        input in arb_state_kv_sets(20, 20, 10)
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        let store = &db.state_store;
+
+        // Commit the first half as the base, then calculate the stats and indices of the rest.
+        let (base, updates) = input.split_at(input.len() / 2);
+        let base_len = base.iter().map(Vec::len).sum::<usize>();
+        update_store(store, base.iter().flatten().cloned(), 0);
+        let value_state_sets: Vec<HashMap<_, _>> = updates
+            .iter()
+            .map(|kvs| kvs.iter().cloned().collect())
+            .collect();
+        let value_state_set_refs: Vec<_> = value_state_sets.iter().collect();
+        let first_version = base_len as Version;
+
+        let mut expected_batch = SchemaBatch::new();
+        put_stats_and_indices_serially(
+            store,
+            &value_state_set_refs,
+            first_version,
+            &mut expected_batch,
+        );
+        let mut batch = SchemaBatch::new();
+        store
+            .put_stats_and_indices(
+                &value_state_set_refs,
+                first_version,
+                StateStorageUsage::new_untracked(),
+                &mut batch,
+            )
+            .unwrap();
+
+        prop_assert_eq!(
+            stale_indices_and_usages_in_batch(batch),
+            stale_indices_and_usages_in_batch(expected_batch)
+        );
+    }
+
     #[test]
     fn test_get_account_iter(
         input in hash_map(any::<StateKey>(), any::<StateValue>(), 1..200)
@@ -238,6 +238,12 @@ fn verify_epochs(db: &AptosDB, ledger_infos_with_sigs: &[LedgerInfoWithSignature
         .filter(|info| info.ledger_info().ends_epoch())
         .cloned()
         .collect();
+    let iterated_epoch_change_lis = db
+        .get_epoch_ending_ledger_infos_iter(0, latest_epoch)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(iterated_epoch_change_lis, expected_epoch_change_lis);
     assert_eq!(actual_epoch_change_lis, expected_epoch_change_lis);
 
     let mut last_ver = 0;
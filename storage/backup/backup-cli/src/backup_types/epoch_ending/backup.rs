@@ -11,6 +11,7 @@ use crate::{
     },
 };
 use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_types::{ledger_info::LedgerInfoWithSignatures, waypoint::Waypoint};
 use once_cell::sync::Lazy;
@@ -165,6 +166,7 @@ impl EpochEndingBackupController {
             first_epoch,
             last_epoch,
             ledger_infos: chunk_handle,
+            ledger_infos_hash: Some(HashValue::sha3_256_of(chunk_bytes)),
         })
     }
 
@@ -3,6 +3,7 @@
 
 use crate::storage::FileHandle;
 use anyhow::{ensure, Result};
+use aptos_crypto::HashValue;
 use aptos_types::waypoint::Waypoint;
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +14,10 @@ pub struct EpochEndingChunk {
     pub first_epoch: u64,
     pub last_epoch: u64,
     pub ledger_infos: FileHandle,
+    /// SHA3-256 of the `ledger_infos` file, checked before the chunk is parsed. Absent in
+    /// manifests written before it was introduced.
+    #[serde(default)]
+    pub ledger_infos_hash: Option<HashValue>,
 }
 
 /// Epoch ending backup manifest, representing epoch ending information in the
@@ -2,15 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup_types::epoch_ending::manifest::EpochEndingBackup,
+    backup_types::epoch_ending::manifest::{EpochEndingBackup, EpochEndingChunk},
     metrics::{
         restore::{EPOCH_ENDING_EPOCH, EPOCH_ENDING_VERSION},
         verify::{VERIFY_EPOCH_ENDING_EPOCH, VERIFY_EPOCH_ENDING_VERSION},
     },
-    storage::{BackupStorage, FileHandle, FileHandleRef},
+    storage::{BackupStorage, FileHandle},
     utils::{
         read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::StreamX,
-        GlobalRestoreOptions, RestoreRunMode,
+        EpochRange, GlobalRestoreOptions, RestoreRunMode,
     },
 };
 use anyhow::{anyhow, ensure, Result};
@@ -22,7 +22,11 @@ use aptos_types::{
     waypoint::Waypoint,
 };
 use futures::StreamExt;
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Instant,
+};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -94,7 +98,7 @@ impl EpochEndingRestoreController {
                 break;
             }
 
-            let lis = self.read_chunk(&chunk.ledger_infos).await?;
+            let lis = self.read_chunk(chunk).await?;
             ensure!(
                 chunk.first_epoch + lis.len() as u64 == chunk.last_epoch + 1,
                 "Number of items in chunks doesn't match that in manifest. \
@@ -157,19 +161,26 @@ impl EpochEndingRestoreController {
         })
     }
 
-    async fn read_chunk(
-        &self,
-        file_handle: &FileHandleRef,
-    ) -> Result<Vec<LedgerInfoWithSignatures>> {
-        let mut file = self.storage.open_for_read(file_handle).await?;
-        let mut chunk = vec![];
-
-        while let Some(record_bytes) = file.read_record_bytes().await? {
-            chunk.push(bcs::from_bytes(&record_bytes)?);
-        }
+    async fn read_chunk(&self, chunk: &EpochEndingChunk) -> Result<Vec<LedgerInfoWithSignatures>> {
+        read_ledger_infos(&self.storage, chunk).await
+    }
+}
 
-        Ok(chunk)
+async fn read_ledger_infos(
+    storage: &Arc<dyn BackupStorage>,
+    chunk: &EpochEndingChunk,
+) -> Result<Vec<LedgerInfoWithSignatures>> {
+    let chunk_bytes = storage
+        .read_chunk(&chunk.ledger_infos, chunk.ledger_infos_hash)
+        .await?;
+    let mut file = chunk_bytes.as_slice();
+    let mut chunk = vec![];
+
+    while let Some(record_bytes) = file.read_record_bytes().await? {
+        chunk.push(bcs::from_bytes(&record_bytes)?);
     }
+
+    Ok(chunk)
 }
 
 struct EpochEndingRestorePreheatData {
@@ -397,3 +408,153 @@ impl EpochHistoryRestoreController {
         Ok(EpochHistory { epoch_endings })
     }
 }
+
+/// Verifies the epoch ending LedgerInfos of a range of epochs without restoring anything.
+///
+/// A LedgerInfo is checked against the trusted waypoint at its version if there is one, otherwise
+/// its aggregated signature is verified against the validator set announced by the previous epoch
+/// ending LedgerInfo, which is read from the backup as well but not verified itself unless it's in
+/// the range.
+pub struct EpochEndingSpotCheckController {
+    storage: Arc<dyn BackupStorage>,
+    manifest_handles: Vec<FileHandle>,
+    epochs: EpochRange,
+    trusted_waypoints: Arc<HashMap<Version, Waypoint>>,
+}
+
+impl EpochEndingSpotCheckController {
+    pub fn new(
+        manifest_handles: Vec<FileHandle>,
+        epochs: EpochRange,
+        global_opt: GlobalRestoreOptions,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            storage,
+            manifest_handles,
+            epochs,
+            trusted_waypoints: global_opt.trusted_waypoints,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        info!("Epoch ending spot check started. Epochs: {}", self.epochs);
+        self.run_impl()
+            .await
+            .map_err(|e| anyhow!("Epoch ending spot check failed: {}", e))?;
+        info!("Epoch ending spot check succeeded.");
+        Ok(())
+    }
+}
+
+impl EpochEndingSpotCheckController {
+    async fn run_impl(&self) -> Result<()> {
+        let ledger_infos = self.read_ledger_infos().await?;
+
+        let mut num_failed = 0;
+        for epoch in self.epochs.start..self.epochs.end {
+            match self.verify_epoch(epoch, &ledger_infos) {
+                Ok(()) => println!("epoch {}: PASS", epoch),
+                Err(e) => {
+                    num_failed += 1;
+                    println!("epoch {}: FAIL ({})", epoch, e);
+                }
+            }
+        }
+        ensure!(
+            num_failed == 0,
+            "{} out of {} epochs failed verification.",
+            num_failed,
+            self.epochs.len(),
+        );
+
+        Ok(())
+    }
+
+    /// Reads the epoch ending LedgerInfos in the range, plus the one right before it.
+    async fn read_ledger_infos(&self) -> Result<BTreeMap<u64, LedgerInfoWithSignatures>> {
+        let needed = EpochRange {
+            start: self.epochs.start.saturating_sub(1),
+            end: self.epochs.end,
+        };
+        let overlaps = |first_epoch: u64, last_epoch: u64| {
+            first_epoch < needed.end && last_epoch >= needed.start
+        };
+
+        let mut ledger_infos = BTreeMap::new();
+        for manifest_handle in &self.manifest_handles {
+            let manifest: EpochEndingBackup = self.storage.load_json_file(manifest_handle).await?;
+            manifest.verify()?;
+            if !overlaps(manifest.first_epoch, manifest.last_epoch) {
+                continue;
+            }
+
+            for chunk in &manifest.chunks {
+                if !overlaps(chunk.first_epoch, chunk.last_epoch) {
+                    continue;
+                }
+
+                let lis = read_ledger_infos(&self.storage, chunk).await?;
+                ensure!(
+                    chunk.first_epoch + lis.len() as u64 == chunk.last_epoch + 1,
+                    "Number of items in chunks doesn't match that in manifest. \
+                    first_epoch: {}, last_epoch: {}, items in chunk: {}",
+                    chunk.first_epoch,
+                    chunk.last_epoch,
+                    lis.len(),
+                );
+                for (epoch, li) in (chunk.first_epoch..).zip(lis) {
+                    ensure!(
+                        li.ledger_info().epoch() == epoch,
+                        "LedgerInfo epoch not expected. Expected: {}, actual: {}.",
+                        epoch,
+                        li.ledger_info().epoch(),
+                    );
+                    if needed.contains(epoch) {
+                        ledger_infos.insert(epoch, li);
+                    }
+                }
+            }
+        }
+
+        Ok(ledger_infos)
+    }
+
+    fn verify_epoch(
+        &self,
+        epoch: u64,
+        ledger_infos: &BTreeMap<u64, LedgerInfoWithSignatures>,
+    ) -> Result<()> {
+        let li = ledger_infos
+            .get(&epoch)
+            .ok_or_else(|| anyhow!("Epoch ending LedgerInfo not found in backup."))?;
+        ensure!(
+            li.ledger_info().ends_epoch(),
+            "LedgerInfo is not one at an epoch ending.",
+        );
+
+        if let Some(wp_trusted) = self.trusted_waypoints.get(&li.ledger_info().version()) {
+            let wp_li = Waypoint::new_epoch_boundary(li.ledger_info())?;
+            ensure!(
+                *wp_trusted == wp_li,
+                "Waypoints don't match. In backup: {}, trusted: {}",
+                wp_li,
+                wp_trusted,
+            );
+            return Ok(());
+        }
+
+        ensure!(
+            epoch > 0,
+            "Epoch 0 can only be verified against a trusted waypoint.",
+        );
+        let previous_li = ledger_infos
+            .get(&(epoch - 1))
+            .ok_or_else(|| anyhow!("LedgerInfo ending epoch {} not found in backup.", epoch - 1))?;
+        previous_li
+            .ledger_info()
+            .next_epoch_state()
+            .ok_or_else(|| anyhow!("Next epoch state not found from LI at epoch {}.", epoch - 1))?
+            .verify(li)
+    }
+}
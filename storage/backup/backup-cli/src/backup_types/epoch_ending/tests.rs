@@ -5,13 +5,15 @@ use crate::{
     backup_types::epoch_ending::{
         backup::{EpochEndingBackupController, EpochEndingBackupOpt},
         restore::{
-            EpochEndingRestoreController, EpochEndingRestoreOpt, EpochHistoryRestoreController,
+            EpochEndingRestoreController, EpochEndingRestoreOpt, EpochEndingSpotCheckController,
+            EpochHistoryRestoreController,
         },
     },
-    storage::{local_fs::LocalFs, BackupStorage},
+    storage::{local_fs::LocalFs, BackupStorage, FileHandle},
     utils::{
         backup_service_client::BackupServiceClient, test_utils::tmp_db_with_random_content,
-        ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
+        ConcurrentDownloadsOpt, EpochRange, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt,
+        TrustedWaypointOpt,
     },
 };
 use aptos_config::utils::get_available_port;
@@ -80,6 +82,7 @@ fn end_to_end() {
             GlobalRestoreOpt {
                 db_dir: Some(tgt_db_dir.path().to_path_buf()),
                 dry_run: false,
+                start_version: None,
                 target_version: Some(target_version),
                 trusted_waypoints: TrustedWaypointOpt::default(),
                 rocksdb_opt: RocksdbOpt::default(),
@@ -171,15 +174,12 @@ async fn mock_backup_service_get_epoch_ending_lis(lis: Vec<LedgerInfoWithSignatu
     addr.port()
 }
 
-async fn test_trusted_waypoints_impl(
+async fn backup_epoch_endings(
     lis: Vec<LedgerInfoWithSignatures>,
-    trusted_waypoints: Vec<Waypoint>,
-    should_fail_without: bool,
-) {
-    let backup_dir = TempPath::new();
-    backup_dir.create_as_dir().unwrap();
-    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
-    let port = mock_backup_service_get_epoch_ending_lis(lis.clone()).await;
+    store: &Arc<dyn BackupStorage>,
+) -> Vec<FileHandle> {
+    let num_epochs = lis.len();
+    let port = mock_backup_service_get_epoch_ending_lis(lis).await;
     let client = Arc::new(BackupServiceClient::new(format!(
         "http://localhost:{}",
         port
@@ -187,17 +187,17 @@ async fn test_trusted_waypoints_impl(
 
     let mut manifests = Vec::new();
     let mut start = 0;
-    while start < lis.len() {
+    while start < num_epochs {
         let m = EpochEndingBackupController::new(
             EpochEndingBackupOpt {
                 start_epoch: start as u64,
-                end_epoch: std::cmp::min(start + 2, lis.len()) as u64,
+                end_epoch: std::cmp::min(start + 2, num_epochs) as u64,
             },
             GlobalBackupOpt {
                 max_chunk_size: 1024,
             },
             client.clone(),
-            Arc::clone(&store),
+            Arc::clone(store),
         )
         .run()
         .await
@@ -205,19 +205,36 @@ async fn test_trusted_waypoints_impl(
         manifests.push(m);
         start += 2;
     }
+    manifests
+}
+
+fn global_restore_opt(trusted_waypoints: Vec<Waypoint>) -> GlobalRestoreOpt {
+    GlobalRestoreOpt {
+        db_dir: None,
+        dry_run: true,
+        start_version: None,
+        target_version: None,
+        trusted_waypoints: TrustedWaypointOpt {
+            trust_waypoint: trusted_waypoints,
+        },
+        rocksdb_opt: RocksdbOpt::default(),
+        concurernt_downloads: ConcurrentDownloadsOpt::default(),
+    }
+}
+
+async fn test_trusted_waypoints_impl(
+    lis: Vec<LedgerInfoWithSignatures>,
+    trusted_waypoints: Vec<Waypoint>,
+    should_fail_without: bool,
+) {
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+    let manifests = backup_epoch_endings(lis.clone(), &store).await;
 
     let res_without_waypoints = EpochHistoryRestoreController::new(
         manifests.clone(),
-        GlobalRestoreOpt {
-            db_dir: None,
-            dry_run: true,
-            target_version: None,
-            trusted_waypoints: TrustedWaypointOpt::default(),
-            rocksdb_opt: RocksdbOpt::default(),
-            concurernt_downloads: ConcurrentDownloadsOpt::default(),
-        }
-        .try_into()
-        .unwrap(),
+        global_restore_opt(vec![]).try_into().unwrap(),
         Arc::clone(&store),
     )
     .run()
@@ -226,18 +243,7 @@ async fn test_trusted_waypoints_impl(
 
     let restored = EpochHistoryRestoreController::new(
         manifests,
-        GlobalRestoreOpt {
-            db_dir: None,
-            dry_run: true,
-            target_version: None,
-            trusted_waypoints: TrustedWaypointOpt {
-                trust_waypoint: trusted_waypoints,
-            },
-            rocksdb_opt: RocksdbOpt::default(),
-            concurernt_downloads: ConcurrentDownloadsOpt::default(),
-        }
-        .try_into()
-        .unwrap(),
+        global_restore_opt(trusted_waypoints).try_into().unwrap(),
         Arc::clone(&store),
     )
     .run()
@@ -259,3 +265,74 @@ proptest! {
         Runtime::new().unwrap().block_on(test_trusted_waypoints_impl(lis, trusted_waypoints, should_fail_without))
     }
 }
+
+async fn test_spot_check_impl(
+    lis: Vec<LedgerInfoWithSignatures>,
+    trusted_waypoints: Vec<Waypoint>,
+    should_fail_without: bool,
+) {
+    if lis.is_empty() {
+        return;
+    }
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+    let num_epochs = lis.len() as u64;
+    let genesis_waypoint = Waypoint::new_epoch_boundary(lis[0].ledger_info()).unwrap();
+    let manifests = backup_epoch_endings(lis, &store).await;
+
+    let spot_check = |epochs: EpochRange, trusted_waypoints: Vec<Waypoint>| {
+        EpochEndingSpotCheckController::new(
+            manifests.clone(),
+            epochs,
+            global_restore_opt(trusted_waypoints).try_into().unwrap(),
+            Arc::clone(&store),
+        )
+        .run()
+    };
+    let all_epochs = EpochRange {
+        start: 0,
+        end: num_epochs,
+    };
+
+    // Genesis can't be verified without a trusted waypoint.
+    assert!(spot_check(all_epochs, vec![]).await.is_err());
+    let mut waypoints_with_genesis = trusted_waypoints;
+    if !waypoints_with_genesis.contains(&genesis_waypoint) {
+        waypoints_with_genesis.push(genesis_waypoint);
+    }
+    spot_check(all_epochs, waypoints_with_genesis)
+        .await
+        .unwrap();
+
+    if num_epochs > 1 {
+        let res = spot_check(
+            EpochRange {
+                start: 1,
+                end: num_epochs,
+            },
+            vec![],
+        )
+        .await;
+        assert_eq!(should_fail_without, res.is_err());
+    }
+    // Epochs not in the backup are reported as failures.
+    assert!(spot_check(
+        EpochRange {
+            start: num_epochs,
+            end: num_epochs + 1,
+        },
+        vec![],
+    )
+    .await
+    .is_err());
+}
+
+proptest! {
+    #[test]
+    fn spot_check(
+        (lis, trusted_waypoints, should_fail_without) in arb_epoch_endings_with_trusted_waypoints()
+    ) {
+        Runtime::new().unwrap().block_on(test_spot_check_impl(lis, trusted_waypoints, should_fail_without))
+    }
+}
@@ -215,6 +215,7 @@ impl StateSnapshotBackupController {
             first_key,
             last_key,
             blobs: chunk_handle,
+            blobs_hash: Some(HashValue::sha3_256_of(chunk_bytes)),
             proof: proof_handle,
         })
     }
@@ -21,6 +21,10 @@ pub struct StateSnapshotChunk {
     /// Repeated `len(record) + record` where `record` is BCS serialized tuple
     /// `(key, state_value)`
     pub blobs: FileHandle,
+    /// SHA3-256 of the `blobs` file, checked before the chunk is parsed. Absent in manifests
+    /// written before it was introduced.
+    #[serde(default)]
+    pub blobs_hash: Option<HashValue>,
     /// BCS serialized `SparseMerkleRangeProof` that proves this chunk adds up to the root hash
     /// indicated in the backup (`StateSnapshotBackup::root_hash`).
     pub proof: FileHandle,
@@ -21,6 +21,7 @@ use crate::{
     },
 };
 use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_types::{
     ledger_info::LedgerInfoWithSignatures,
@@ -135,7 +136,7 @@ impl StateSnapshotRestoreController {
         ver_gauge.set(self.version as i64);
         tgt_leaf_idx.set(manifest.chunks.last().map_or(0, |c| c.last_idx as i64));
         for chunk in manifest.chunks {
-            let blobs = self.read_state_value(chunk.blobs).await?;
+            let blobs = self.read_state_value(chunk.blobs, chunk.blobs_hash).await?;
             let proof = self.storage.load_bcs_file(&chunk.proof).await?;
             receiver.add_chunk(blobs, proof)?;
 
@@ -150,8 +151,10 @@ impl StateSnapshotRestoreController {
     async fn read_state_value(
         &self,
         file_handle: FileHandle,
+        expected_hash: Option<HashValue>,
     ) -> Result<Vec<(StateKey, StateValue)>> {
-        let mut file = self.storage.open_for_read(&file_handle).await?;
+        let chunk_bytes = self.storage.read_chunk(&file_handle, expected_hash).await?;
+        let mut file = chunk_bytes.as_slice();
 
         let mut chunk = vec![];
 
@@ -79,6 +79,7 @@ fn end_to_end() {
             GlobalRestoreOpt {
                 dry_run: false,
                 db_dir: Some(tgt_db_dir.path().to_path_buf()),
+                start_version: None,
                 target_version: None, // max
                 trusted_waypoints: TrustedWaypointOpt::default(),
                 rocksdb_opt: RocksdbOpt::default(),
@@ -118,6 +118,7 @@ fn test_end_to_end_impl(d: TestData) {
     let global_restore_opt: GlobalRestoreOptions = GlobalRestoreOpt {
         dry_run: false,
         db_dir: Some(tgt_db_dir.path().to_path_buf()),
+        start_version: None,
         target_version: Some(d.target_ver),
         trusted_waypoints: TrustedWaypointOpt::default(),
         rocksdb_opt: RocksdbOpt::default(),
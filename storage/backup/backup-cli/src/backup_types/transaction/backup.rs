@@ -11,6 +11,7 @@ use crate::{
     },
 };
 use anyhow::{anyhow, Result};
+use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
 use once_cell::sync::Lazy;
@@ -176,6 +177,7 @@ impl TransactionBackupController {
             first_version,
             last_version,
             transactions: chunk_handle,
+            transactions_hash: Some(HashValue::sha3_256_of(chunk_bytes)),
             proof: proof_handle,
         })
     }
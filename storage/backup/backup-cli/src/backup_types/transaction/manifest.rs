@@ -3,6 +3,7 @@
 
 use crate::storage::FileHandle;
 use anyhow::{ensure, Result};
+use aptos_crypto::HashValue;
 use aptos_types::transaction::Version;
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +16,10 @@ pub struct TransactionChunk {
     /// Repeated `len(record) + record`, where `record` is BCS serialized tuple
     /// `(Transaction, TransactionInfo)`
     pub transactions: FileHandle,
+    /// SHA3-256 of the `transactions` file, checked before the chunk is parsed. Absent in
+    /// manifests written before it was introduced.
+    #[serde(default)]
+    pub transactions_hash: Option<HashValue>,
     /// BCS serialized `(TransactionAccumulatorRangeProof, LedgerInfoWithSignatures)`.
     /// The `TransactionAccumulatorRangeProof` links the transactions to the
     /// `LedgerInfoWithSignatures`, and the `LedgerInfoWithSignatures` can be verified by the
@@ -42,7 +42,6 @@ use itertools::zip_eq;
 use std::{cmp::min, pin::Pin, sync::Arc, time::Instant};
 use storage_interface::DbReaderWriter;
 use structopt::StructOpt;
-use tokio::io::BufReader;
 
 const BATCH_SIZE: usize = if cfg!(test) { 2 } else { 10000 };
 
@@ -85,7 +84,10 @@ impl LoadedChunk {
         storage: &Arc<dyn BackupStorage>,
         epoch_history: Option<&Arc<EpochHistory>>,
     ) -> Result<Self> {
-        let mut file = BufReader::new(storage.open_for_read(&manifest.transactions).await?);
+        let chunk_bytes = storage
+            .read_chunk(&manifest.transactions, manifest.transactions_hash)
+            .await?;
+        let mut file = chunk_bytes.as_slice();
         let mut txns = Vec::new();
         let mut txn_infos = Vec::new();
         let mut event_vecs = Vec::new();
@@ -245,10 +247,12 @@ impl TransactionRestoreBatchController {
             .buffered_x(con * 3, con)
             .and_then(|m: TransactionBackup| future::ready(m.verify().map(|_| m)));
 
+        let start_version = self.global_opt.start_version;
         let target_version = self.global_opt.target_version;
         let chunk_manifest_stream = manifest_stream
             .map_ok(|m| stream::iter(m.chunks.into_iter().map(Result::<_>::Ok)))
             .try_flatten()
+            .try_skip_while(move |c| future::ready(Ok(c.last_version < start_version)))
             .try_take_while(move |c| future::ready(Ok(c.first_version <= target_version)))
             .scan(0, |last_chunk_last_version, chunk_res| {
                 let res = match &chunk_res {
@@ -333,6 +337,7 @@ impl TransactionRestoreBatchController {
                                 first_version,
                                 mut last_version,
                                 transactions: _,
+                                transactions_hash: _,
                                 proof: _,
                             },
                         mut txns,
@@ -4,11 +4,13 @@
 use crate::{
     backup_types::transaction::{
         backup::{TransactionBackupController, TransactionBackupOpt},
+        manifest::TransactionBackup,
         restore::{TransactionRestoreController, TransactionRestoreOpt},
     },
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
         backup_service_client::BackupServiceClient,
+        storage_ext::BackupStorageExt,
         test_utils::{start_local_backup_service, tmp_db_with_random_content},
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
     },
@@ -79,6 +81,7 @@ fn end_to_end() {
             GlobalRestoreOpt {
                 dry_run: false,
                 db_dir: Some(tgt_db_dir.path().to_path_buf()),
+                start_version: None,
                 target_version: Some(target_version),
                 trusted_waypoints: TrustedWaypointOpt::default(),
                 rocksdb_opt: RocksdbOpt::default(),
@@ -138,3 +141,171 @@ fn end_to_end() {
 
     rt.shutdown_timeout(Duration::from_secs(1));
 }
+
+#[test]
+fn corrupted_chunk_fails_hash_check() {
+    let (_src_db_dir, src_db, blocks) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+
+    let total_txns = blocks.iter().fold(0, |x, b| x + b.0.len());
+    let manifest_handle = rt
+        .block_on(
+            TransactionBackupController::new(
+                TransactionBackupOpt {
+                    start_version: 0,
+                    num_transactions: total_txns,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 1024,
+                },
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+
+    // Flip a byte in the middle of the first chunk.
+    let manifest: TransactionBackup = rt.block_on(store.load_json_file(&manifest_handle)).unwrap();
+    let chunk = &manifest.chunks[0];
+    assert!(chunk.transactions_hash.is_some());
+    let chunk_path = backup_dir.path().join(&chunk.transactions);
+    let mut chunk_bytes = std::fs::read(&chunk_path).unwrap();
+    let mid = chunk_bytes.len() / 2;
+    chunk_bytes[mid] ^= 0xff;
+    std::fs::write(&chunk_path, &chunk_bytes).unwrap();
+
+    let err = rt
+        .block_on(
+            TransactionRestoreController::new(
+                TransactionRestoreOpt {
+                    manifest_handle,
+                    replay_from_version: None, // max
+                },
+                GlobalRestoreOpt {
+                    dry_run: true,
+                    db_dir: None,
+                    start_version: None,
+                    target_version: None,
+                    trusted_waypoints: TrustedWaypointOpt::default(),
+                    rocksdb_opt: RocksdbOpt::default(),
+                    concurernt_downloads: ConcurrentDownloadsOpt::default(),
+                }
+                .try_into()
+                .unwrap(),
+                store,
+                None, /* epoch_history */
+            )
+            .run(),
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Chunk hash mismatch"), "{}", err);
+    assert!(err.contains(&chunk.transactions), "{}", err);
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+#[test]
+fn restore_version_range() {
+    let (_src_db_dir, src_db, blocks) = tmp_db_with_random_content();
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+
+    let total_txns = blocks.iter().fold(0, |x, b| x + b.0.len());
+    let txns = blocks
+        .iter()
+        .flat_map(|(txns, _li)| txns)
+        .map(|txn_to_commit| txn_to_commit.transaction())
+        .collect::<Vec<_>>();
+    let manifest_handle = rt
+        .block_on(
+            TransactionBackupController::new(
+                TransactionBackupOpt {
+                    start_version: 0,
+                    num_transactions: total_txns,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 1024,
+                },
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+
+    // Start at a chunk boundary, so everything before the range is skipped.
+    let manifest: TransactionBackup = rt.block_on(store.load_json_file(&manifest_handle)).unwrap();
+    assert!(manifest.chunks.len() > 2);
+    let start_version = manifest.chunks[1].first_version;
+    let target_version = manifest.chunks.last().unwrap().first_version;
+
+    rt.block_on(
+        TransactionRestoreController::new(
+            TransactionRestoreOpt {
+                manifest_handle,
+                replay_from_version: None, // max
+            },
+            GlobalRestoreOpt {
+                dry_run: false,
+                db_dir: Some(tgt_db_dir.path().to_path_buf()),
+                start_version: Some(start_version),
+                target_version: Some(target_version),
+                trusted_waypoints: TrustedWaypointOpt::default(),
+                rocksdb_opt: RocksdbOpt::default(),
+                concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            }
+            .try_into()
+            .unwrap(),
+            store,
+            None, /* epoch_history */
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+    assert_eq!(
+        tgt_db
+            .get_latest_transaction_info_option()
+            .unwrap()
+            .unwrap()
+            .0,
+        target_version,
+    );
+    let num_txns_in_range = target_version - start_version + 1;
+    assert_eq!(
+        tgt_db
+            .get_transactions(start_version, num_txns_in_range, target_version, false)
+            .unwrap()
+            .transactions,
+        txns.into_iter()
+            .skip(start_version as usize)
+            .take(num_txns_in_range as usize)
+            .cloned()
+            .collect::<Vec<_>>()
+    );
+    assert!(tgt_db
+        .get_transactions(start_version - 1, 1, target_version, false)
+        .is_err());
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
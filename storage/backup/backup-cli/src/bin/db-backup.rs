@@ -38,6 +38,11 @@ enum OneShotCommand {
     Query(OneShotQueryType),
     #[structopt(about = "Do a one shot backup.")]
     Backup(OneShotBackupOpt),
+    #[structopt(
+        about = "Merges the metadata files in the local metadata cache into a single file, \
+        which is faster to load."
+    )]
+    CompactMetadata(OneShotCompactMetadataOpt),
 }
 
 #[derive(StructOpt)]
@@ -69,6 +74,16 @@ struct OneShotQueryBackupStorageStateOpt {
     storage: StorageOpt,
 }
 
+#[derive(StructOpt)]
+struct OneShotCompactMetadataOpt {
+    #[structopt(flatten)]
+    metadata_cache: MetadataCacheOpt,
+    #[structopt(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
 #[derive(StructOpt)]
 struct OneShotBackupOpt {
     #[structopt(flatten)]
@@ -77,6 +92,9 @@ struct OneShotBackupOpt {
     #[structopt(flatten)]
     client: BackupServiceClientOpt,
 
+    #[structopt(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+
     #[structopt(subcommand)]
     backup_type: BackupType,
 }
@@ -142,7 +160,9 @@ async fn main_impl() -> Result<()> {
         Command::OneShot(one_shot_cmd) => match one_shot_cmd {
             OneShotCommand::Query(typ) => match typ {
                 OneShotQueryType::NodeState(opt) => {
-                    let client = BackupServiceClient::new_with_opt(opt.client);
+                    let client = BackupServiceClient::new_with_opt(
+                        opt.client, 1, /* max_concurrent_requests */
+                    );
                     if let Some(db_state) = client.get_db_state().await? {
                         println!("{}", db_state)
                     } else {
@@ -159,8 +179,21 @@ async fn main_impl() -> Result<()> {
                     println!("{}", view.get_storage_state())
                 }
             },
+            OneShotCommand::CompactMetadata(opt) => {
+                let view = cache::sync_and_compact(
+                    &opt.metadata_cache,
+                    opt.storage.init_storage().await?,
+                    opt.concurrent_downloads.get(),
+                    0, /* min_files_to_compact */
+                )
+                .await?;
+                println!("{}", view.get_storage_state())
+            }
             OneShotCommand::Backup(opt) => {
-                let client = Arc::new(BackupServiceClient::new_with_opt(opt.client));
+                let client = Arc::new(BackupServiceClient::new_with_opt(
+                    opt.client,
+                    opt.concurrent_downloads.get(),
+                ));
                 let global_opt = opt.global;
 
                 match opt.backup_type {
@@ -199,10 +232,14 @@ async fn main_impl() -> Result<()> {
         },
         Command::Coordinator(coordinator_cmd) => match coordinator_cmd {
             CoordinatorCommand::Run(opt) => {
+                let client = Arc::new(BackupServiceClient::new_with_opt(
+                    opt.client,
+                    opt.coordinator.concurernt_downloads.get(),
+                ));
                 BackupCoordinator::new(
                     opt.coordinator,
                     opt.global,
-                    Arc::new(BackupServiceClient::new_with_opt(opt.client)),
+                    client,
                     opt.storage.init_storage().await?,
                 )
                 .run()
@@ -46,6 +46,10 @@ pub struct BackupCoordinatorOpt {
     pub transaction_batch_size: usize,
     #[structopt(flatten)]
     pub concurernt_downloads: ConcurrentDownloadsOpt,
+    // Every backup adds a metadata file, merge them in the local cache once there are this many,
+    // so the cache doesn't have to read thousands of small files on every start.
+    #[structopt(long, default_value = "1000")]
+    pub metadata_compaction_threshold: usize,
 }
 
 impl BackupCoordinatorOpt {
@@ -66,6 +70,7 @@ pub struct BackupCoordinator {
     state_snapshot_interval_epochs: usize,
     transaction_batch_size: usize,
     concurrent_downloads: usize,
+    metadata_compaction_threshold: usize,
 }
 
 impl BackupCoordinator {
@@ -84,15 +89,17 @@ impl BackupCoordinator {
             state_snapshot_interval_epochs: opt.state_snapshot_interval_epochs,
             transaction_batch_size: opt.transaction_batch_size,
             concurrent_downloads: opt.concurernt_downloads.get(),
+            metadata_compaction_threshold: opt.metadata_compaction_threshold,
         }
     }
 
     pub async fn run(&self) -> Result<()> {
         // Connect to both the local node and the backup storage.
-        let backup_state = metadata::cache::sync_and_load(
+        let backup_state = metadata::cache::sync_and_compact(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
             self.concurrent_downloads,
+            self.metadata_compaction_threshold,
         )
         .await?
         .get_storage_state();
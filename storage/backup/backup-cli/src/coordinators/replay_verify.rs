@@ -90,6 +90,7 @@ impl ReplayVerifyCoordinator {
             .select_transaction_backups(replay_transactions_from_version, self.end_version)?;
 
         let global_opt = GlobalRestoreOptions {
+            start_version: 0,
             target_version: self.end_version,
             trusted_waypoints: Arc::new(self.trusted_waypoints_opt.verify()?),
             run_mode: Arc::new(RestoreRunMode::Restore {
@@ -3,7 +3,7 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistoryRestoreController,
+        epoch_ending::restore::{EpochEndingSpotCheckController, EpochHistoryRestoreController},
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
         transaction::restore::TransactionRestoreBatchController,
     },
@@ -13,9 +13,9 @@ use crate::{
         COORDINATOR_FAIL_TS, COORDINATOR_START_TS, COORDINATOR_SUCC_TS, COORDINATOR_TARGET_VERSION,
     },
     storage::BackupStorage,
-    utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode},
+    utils::{unix_timestamp_sec, EpochRange, GlobalRestoreOptions, RestoreRunMode},
 };
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
 use std::sync::Arc;
@@ -39,6 +39,13 @@ pub struct RestoreCoordinatorOpt {
     pub ledger_history_start_version: Version,
     #[structopt(long, help = "Skip restoring epoch ending info, used for debugging.")]
     pub skip_epoch_endings: bool,
+    #[structopt(
+        long,
+        help = "Only download and verify the epoch ending LedgerInfos of epochs in the \
+                <start>..<end> range (right side exclusive), reporting pass / fail for each \
+                epoch, without restoring anything."
+    )]
+    pub verify_only_epochs: Option<EpochRange>,
 }
 
 pub struct RestoreCoordinator {
@@ -48,6 +55,7 @@ pub struct RestoreCoordinator {
     replay_all: bool,
     ledger_history_start_version: Version,
     skip_epoch_endings: bool,
+    verify_only_epochs: Option<EpochRange>,
 }
 
 impl RestoreCoordinator {
@@ -63,6 +71,7 @@ impl RestoreCoordinator {
             replay_all: opt.replay_all,
             ledger_history_start_version: opt.ledger_history_start_version,
             skip_epoch_endings: opt.skip_epoch_endings,
+            verify_only_epochs: opt.verify_only_epochs,
         }
     }
 
@@ -94,16 +103,48 @@ impl RestoreCoordinator {
         )
         .await?;
 
+        if let Some(epochs) = self.verify_only_epochs {
+            let epoch_endings = metadata_view
+                .select_epoch_ending_backups(Version::max_value())?
+                .into_iter()
+                .filter(|backup| {
+                    backup.first_epoch < epochs.end && backup.last_epoch + 1 >= epochs.start
+                })
+                .map(|backup| backup.manifest)
+                .collect();
+            return EpochEndingSpotCheckController::new(
+                epoch_endings,
+                epochs,
+                self.global_opt,
+                self.storage,
+            )
+            .run()
+            .await;
+        }
+
         let mut transactions =
             metadata_view.select_transaction_backups(0, self.target_version())?;
         let actual_target_version = self.get_actual_target_version(&transactions)?;
         let epoch_endings = metadata_view.select_epoch_ending_backups(actual_target_version)?;
-        let state_snapshot = if self.replay_all {
+        let state_snapshot = if self.start_version() > 0 {
+            // A slice of the ledger history needs a state snapshot to anchor it.
+            let state_snapshot = metadata_view
+                .select_state_snapshot(self.start_version())?
+                .ok_or_else(|| {
+                    anyhow!(
+                        "No state snapshot at or before start version {} found.",
+                        self.start_version()
+                    )
+                })?;
+            Some(state_snapshot)
+        } else if self.replay_all {
             None
         } else {
             metadata_view.select_state_snapshot(actual_target_version)?
         };
         let replay_transactions_from_version = match &state_snapshot {
+            // A slice starting after the snapshot can't be replayed on top of it, only saved.
+            Some(b) if b.version + 1 < self.start_version() => Version::max_value(),
             Some(b) => b.version + 1,
             None => 0,
         };
@@ -119,9 +160,12 @@ impl RestoreCoordinator {
                 0
             }
         };
-        let start_version = std::cmp::min(
-            self.ledger_history_start_version,
-            state_snapshot.as_ref().map(|s| s.version + 1).unwrap_or(0),
+        let start_version = std::cmp::max(
+            std::cmp::min(
+                self.ledger_history_start_version,
+                state_snapshot.as_ref().map(|s| s.version + 1).unwrap_or(0),
+            ),
+            self.start_version(),
         );
         transactions = transactions
             .into_iter()
@@ -196,6 +240,10 @@ impl RestoreCoordinator {
 }
 
 impl RestoreCoordinator {
+    fn start_version(&self) -> Version {
+        self.global_opt.start_version
+    }
+
     fn target_version(&self) -> Version {
         self.global_opt.target_version
     }
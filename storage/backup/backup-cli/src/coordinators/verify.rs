@@ -75,6 +75,7 @@ impl VerifyCoordinator {
         let epoch_endings = metadata_view.select_epoch_ending_backups(ver_max)?;
 
         let global_opt = GlobalRestoreOptions {
+            start_version: 0,
             target_version: ver_max,
             trusted_waypoints: Arc::new(self.trusted_waypoints_opt.verify()?),
             run_mode: Arc::new(RestoreRunMode::Verify),
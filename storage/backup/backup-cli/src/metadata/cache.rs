@@ -3,26 +3,27 @@
 
 use crate::{
     metadata::{view::MetadataView, Metadata},
-    metrics::metadata::{NUM_META_DOWNLOAD, NUM_META_FILES, NUM_META_MISS},
+    metrics::metadata::{NUM_META_CORRUPTED, NUM_META_DOWNLOAD, NUM_META_FILES, NUM_META_MISS},
     storage::{BackupStorage, FileHandle},
-    utils::{error_notes::ErrorNotes, stream::StreamX},
+    utils::{error_notes::ErrorNotes, path_exists, stream::StreamX},
 };
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, ensure, Result};
 use aptos_logger::prelude::*;
 use aptos_temppath::TempPath;
-use async_trait::async_trait;
 use futures::stream::poll_fn;
+use itertools::Itertools;
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
-    path::PathBuf,
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
     sync::Arc,
     time::Instant,
 };
 use structopt::StructOpt;
 use tokio::{
     fs::{create_dir_all, read_dir, remove_file, OpenOptions},
-    io::{AsyncRead, AsyncReadExt},
+    io::{AsyncReadExt, AsyncWriteExt},
 };
 use tokio_stream::StreamExt;
 
@@ -41,17 +42,31 @@ pub struct MetadataCacheOpt {
         help = "[Defaults to temporary dir] Metadata cache dir."
     )]
     dir: Option<PathBuf>,
+    #[structopt(
+        long = "strict",
+        help = "Fail on metadata files that can't be parsed, instead of skipping them."
+    )]
+    strict: bool,
 }
 
 impl MetadataCacheOpt {
     // in cache we save things other than the cached files.
     const SUB_DIR: &'static str = "cache";
+    // merged metadata files, see `CompactedMetadata`.
+    const COMPACTED_SUB_DIR: &'static str = "compacted";
 
-    fn cache_dir(&self) -> PathBuf {
+    fn root_dir(&self) -> PathBuf {
         self.dir
             .clone()
             .unwrap_or_else(|| TEMP_METADATA_CACHE_DIR.path().to_path_buf())
-            .join(Self::SUB_DIR)
+    }
+
+    fn cache_dir(&self) -> PathBuf {
+        self.root_dir().join(Self::SUB_DIR)
+    }
+
+    fn compacted_dir(&self) -> PathBuf {
+        self.root_dir().join(Self::COMPACTED_SUB_DIR)
     }
 }
 
@@ -60,30 +75,48 @@ pub async fn sync_and_load(
     opt: &MetadataCacheOpt,
     storage: Arc<dyn BackupStorage>,
     concurrent_downloads: usize,
+) -> Result<MetadataView> {
+    sync_and_load_impl(opt, storage, concurrent_downloads, None).await
+}
+
+/// Like `sync_and_load`, and then merges the individually cached metadata files into a new
+/// generation of the compacted metadata file, if there are at least `min_files_to_compact` of them.
+pub async fn sync_and_compact(
+    opt: &MetadataCacheOpt,
+    storage: Arc<dyn BackupStorage>,
+    concurrent_downloads: usize,
+    min_files_to_compact: usize,
+) -> Result<MetadataView> {
+    sync_and_load_impl(
+        opt,
+        storage,
+        concurrent_downloads,
+        Some(min_files_to_compact),
+    )
+    .await
+}
+
+async fn sync_and_load_impl(
+    opt: &MetadataCacheOpt,
+    storage: Arc<dyn BackupStorage>,
+    concurrent_downloads: usize,
+    min_files_to_compact: Option<usize>,
 ) -> Result<MetadataView> {
     let timer = Instant::now();
     let cache_dir = opt.cache_dir();
     create_dir_all(&cache_dir).await.err_notes(&cache_dir)?; // create if not present already
+    let compacted_dir = opt.compacted_dir();
+    create_dir_all(&compacted_dir)
+        .await
+        .err_notes(&compacted_dir)?;
+    let mut corrupted_files = Vec::new();
+
+    // Load the latest compacted metadata, files merged into it are not cached individually.
+    let (compacted, max_generation) =
+        CompactedMetadata::load_latest(&compacted_dir, &mut corrupted_files).await?;
 
     // List cached metadata files.
-    let mut dir = read_dir(&cache_dir).await.err_notes(&cache_dir)?;
-    let local_entries = poll_fn(|ctx| {
-        ::std::task::Poll::Ready(match futures::ready!(dir.poll_next_entry(ctx)) {
-            Ok(Some(entry)) => Some(Ok(entry)),
-            Ok(None) => None,
-            Err(err) => Some(Err(err)),
-        })
-    })
-    .collect::<tokio::io::Result<Vec<_>>>()
-    .await?;
-    let local_hashes = local_entries
-        .iter()
-        .map(|e| {
-            e.file_name()
-                .into_string()
-                .map_err(|s| anyhow!("into_string() failed for file name {:?}", s))
-        })
-        .collect::<Result<HashSet<_>>>()?;
+    let local_hashes = list_file_names(&cache_dir).await?;
 
     // List remote metadata files.
     let remote_file_handles = storage.list_metadata_files().await?;
@@ -97,14 +130,41 @@ pub async fn sync_and_load(
 
     // Sync local cache with remote metadata files.
     let stale_local_hashes = local_hashes.difference(&remote_hashes);
-    let new_remote_hashes = remote_hashes.difference(&local_hashes).collect::<Vec<_>>();
-    let up_to_date_local_hashes = local_hashes.intersection(&remote_hashes);
+    let mut new_remote_hashes = remote_hashes
+        .iter()
+        .filter(|h| !local_hashes.contains(*h) && !compacted.files.contains_key(*h))
+        .collect::<Vec<_>>();
+    let up_to_date_local_hashes = local_hashes
+        .intersection(&remote_hashes)
+        .filter(|h| !compacted.files.contains_key(*h))
+        .collect::<Vec<_>>();
 
     for h in stale_local_hashes {
         let file = cache_dir.join(&*h);
         remove_file(&file).await.err_notes(&file)?;
     }
 
+    // A cached file is only up to date if its content can still be parsed, otherwise it's
+    // downloaded again.
+    let mut cached_files = BTreeMap::new();
+    for h in up_to_date_local_hashes {
+        let cached_file = cache_dir.join(&*h);
+        match parse_metadata_lines(&read_file(&cached_file).await?) {
+            Ok(metadata) => {
+                cached_files.insert(h.clone(), metadata);
+            }
+            Err(err) => {
+                warn!(
+                    file = ?cached_file,
+                    error = %err,
+                    "Downloading cached metadata file that can't be parsed again."
+                );
+                remove_file(&cached_file).await.err_notes(&cached_file)?;
+                new_remote_hashes.push(h);
+            }
+        }
+    }
+
     NUM_META_MISS.set(new_remote_hashes.len() as i64);
     NUM_META_DOWNLOAD.set(0);
     let futs = new_remote_hashes.iter().map(|h| {
@@ -145,22 +205,67 @@ pub async fn sync_and_load(
         .collect::<Result<Vec<_>>>()
         .await?;
 
-    // Load metadata from synced cache files.
-    let mut metadata_vec = Vec::new();
-    for h in new_remote_hashes.into_iter().chain(up_to_date_local_hashes) {
+    // Load metadata from the compacted metadata and the downloaded files. Metadata files that
+    // can't be parsed are skipped, so one bad file doesn't make the whole cache unusable.
+    let mut compacted_files: BTreeMap<_, _> = compacted
+        .files
+        .into_iter()
+        .filter(|(h, _)| remote_hashes.contains(h))
+        .collect();
+    for h in new_remote_hashes {
         let cached_file = cache_dir.join(&*h);
-        metadata_vec.extend(
-            OpenOptions::new()
-                .read(true)
-                .open(&cached_file)
-                .await
-                .err_notes(&cached_file)?
-                .load_metadata_lines()
-                .await
-                .err_notes(&cached_file)?
-                .into_iter(),
-        )
+        match parse_metadata_lines(&read_file(&cached_file).await?) {
+            Ok(metadata) => {
+                cached_files.insert(h.clone(), metadata);
+            }
+            Err(err) => {
+                warn!(
+                    file = ?cached_file,
+                    error = %err,
+                    "Skipping metadata file that can't be parsed."
+                );
+                corrupted_files.push(cached_file);
+            }
+        }
+    }
+    NUM_META_CORRUPTED.set(corrupted_files.len() as i64);
+    if !corrupted_files.is_empty() {
+        ensure!(
+            !opt.strict,
+            "Corrupted metadata files in the cache: {:?}",
+            corrupted_files,
+        );
+        warn!(
+            "Skipped {} corrupted metadata files: {:?}",
+            corrupted_files.len(),
+            corrupted_files,
+        );
+    }
+
+    let metadata_vec = compacted_files
+        .values()
+        .chain(cached_files.values())
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if let Some(min_files_to_compact) = min_files_to_compact {
+        if cached_files.len() >= min_files_to_compact {
+            let num_files = cached_files.len();
+            compacted_files.append(&mut cached_files);
+            CompactedMetadata {
+                generation: max_generation + 1,
+                files: compacted_files,
+            }
+            .save(&cache_dir, &compacted_dir)
+            .await?;
+            info!(
+                generation = max_generation + 1,
+                "Compacted {} metadata files.", num_files
+            );
+        }
     }
+
     info!(
         "Metadata cache loaded in {:.2} seconds.",
         timer.elapsed().as_secs_f64()
@@ -168,6 +273,128 @@ pub async fn sync_and_load(
     Ok(metadata_vec.into())
 }
 
+/// All the metadata in the cache as of a compaction, by the hash of the file it came from
+#[derive(Default, Deserialize, Serialize)]
+struct CompactedMetadata {
+    generation: u64,
+    files: BTreeMap<String, Vec<Metadata>>,
+}
+
+impl CompactedMetadata {
+    fn file_name(generation: u64) -> String {
+        format!("{}.meta", generation)
+    }
+
+    fn parse_generation(file_name: &str) -> Option<u64> {
+        file_name.strip_suffix(".meta")?.parse().ok()
+    }
+
+    /// Returns the latest generation that can be parsed, and the latest generation there is.
+    async fn load_latest(
+        compacted_dir: &Path,
+        corrupted_files: &mut Vec<PathBuf>,
+    ) -> Result<(Self, u64)> {
+        let generations = list_file_names(compacted_dir)
+            .await?
+            .iter()
+            .filter_map(|file_name| Self::parse_generation(file_name))
+            .sorted()
+            .rev()
+            .collect::<Vec<_>>();
+        let max_generation = generations.first().copied().unwrap_or(0);
+
+        for generation in generations {
+            let file = compacted_dir.join(Self::file_name(generation));
+            match serde_json::from_slice::<Self>(&read_file(&file).await?) {
+                Ok(compacted) if compacted.generation == generation => {
+                    return Ok((compacted, max_generation))
+                }
+                Ok(compacted) => warn!(
+                    file = ?file,
+                    "Skipping compacted metadata file of generation {}.", compacted.generation,
+                ),
+                Err(err) => warn!(
+                    file = ?file,
+                    error = %err,
+                    "Skipping compacted metadata file that can't be parsed."
+                ),
+            }
+            corrupted_files.push(file);
+        }
+        Ok((Self::default(), max_generation))
+    }
+
+    /// Writes this generation, then removes the previous generations, and the individually
+    /// cached files merged into it.
+    async fn save(&self, cache_dir: &Path, compacted_dir: &Path) -> Result<()> {
+        let file_name = Self::file_name(self.generation);
+        let file = compacted_dir.join(&file_name);
+        let tmp_file = compacted_dir.join(format!(".{}", file_name));
+        // Like downloads, write to a tmp file first so a partially written file is never loaded.
+        let mut writer = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_file)
+            .await
+            .err_notes(&tmp_file)?;
+        writer
+            .write_all(&serde_json::to_vec(self)?)
+            .await
+            .err_notes(&tmp_file)?;
+        writer.sync_all().await.err_notes(&tmp_file)?;
+        tokio::fs::rename(&tmp_file, &file).await.err_notes(&file)?;
+
+        for other_file_name in list_file_names(compacted_dir).await? {
+            if other_file_name != file_name {
+                let other_file = compacted_dir.join(other_file_name);
+                remove_file(&other_file).await.err_notes(&other_file)?;
+            }
+        }
+        for h in self.files.keys() {
+            let cached_file = cache_dir.join(h);
+            if path_exists(&cached_file).await {
+                remove_file(&cached_file).await.err_notes(&cached_file)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn list_file_names(dir: &Path) -> Result<HashSet<String>> {
+    let mut dir = read_dir(dir).await.err_notes(dir)?;
+    let entries = poll_fn(|ctx| {
+        ::std::task::Poll::Ready(match futures::ready!(dir.poll_next_entry(ctx)) {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        })
+    })
+    .collect::<tokio::io::Result<Vec<_>>>()
+    .await?;
+    entries
+        .iter()
+        .map(|e| {
+            e.file_name()
+                .into_string()
+                .map_err(|s| anyhow!("into_string() failed for file name {:?}", s))
+        })
+        .collect::<Result<HashSet<_>>>()
+}
+
+async fn read_file(path: &Path) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    OpenOptions::new()
+        .read(true)
+        .open(path)
+        .await
+        .err_notes(path)?
+        .read_to_end(&mut buf)
+        .await
+        .err_notes(path)?;
+    Ok(buf)
+}
+
 trait FileHandleHash {
     fn file_handle_hash(&self) -> String;
 }
@@ -182,21 +409,173 @@ impl FileHandleHash for FileHandle {
     }
 }
 
-#[async_trait]
-trait LoadMetadataLines {
-    async fn load_metadata_lines(&mut self) -> Result<Vec<Metadata>>;
+fn parse_metadata_lines(buf: &[u8]) -> Result<Vec<Metadata>> {
+    Ok(std::str::from_utf8(buf)?
+        .lines()
+        .map(serde_json::from_str::<Metadata>)
+        .collect::<Result<_, serde_json::error::Error>>()?)
 }
 
-#[async_trait]
-impl<R: AsyncRead + Send + Unpin> LoadMetadataLines for R {
-    async fn load_metadata_lines(&mut self) -> Result<Vec<Metadata>> {
-        let mut buf = String::new();
-        self.read_to_string(&mut buf)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::local_fs::LocalFs;
+
+    const NUM_CONCURRENT_DOWNLOADS: usize = 4;
+
+    async fn save_transaction_backups(storage: &LocalFs, backups: std::ops::Range<u64>) {
+        for i in backups {
+            let metadata =
+                Metadata::new_transaction_backup(i * 10, i * 10 + 9, format!("manifest_{}", i));
+            storage
+                .save_metadata_line(&metadata.name(), &metadata.to_text_line().unwrap())
+                .await
+                .unwrap();
+        }
+    }
+
+    fn cached_file(opt: &MetadataCacheOpt, backup: u64) -> PathBuf {
+        // The file handle `LocalFs` lists for the metadata file
+        let file_handle = format!(
+            "metadata/transaction_{}-{}.meta",
+            backup * 10,
+            backup * 10 + 9
+        );
+        opt.cache_dir().join(file_handle.file_handle_hash())
+    }
+
+    async fn latest_version(opt: &MetadataCacheOpt, storage: Arc<LocalFs>) -> Result<Option<u64>> {
+        Ok(sync_and_load(opt, storage, NUM_CONCURRENT_DOWNLOADS)
+            .await?
+            .get_storage_state()
+            .latest_transaction_version)
+    }
+
+    async fn file_names(dir: PathBuf) -> Vec<String> {
+        list_file_names(&dir)
+            .await
+            .unwrap()
+            .into_iter()
+            .sorted()
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_skip_corrupted_files() {
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let storage = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+        let cache_dir = TempPath::new();
+        let mut opt = MetadataCacheOpt {
+            dir: Some(cache_dir.path().to_path_buf()),
+            strict: false,
+        };
+
+        save_transaction_backups(&storage, 0..5).await;
+        assert_eq!(
+            latest_version(&opt, storage.clone()).await.unwrap(),
+            Some(49)
+        );
+
+        // Truncate one cached file, and fill another one with garbage.
+        let truncated = cached_file(&opt, 4);
+        let content = std::fs::read(&truncated).unwrap();
+        std::fs::write(&truncated, &content[..content.len() / 2]).unwrap();
+        std::fs::write(cached_file(&opt, 3), [0xff, 0x00, 0x12, 0x34]).unwrap();
+        // A compacted file that can't be parsed is skipped too.
+        std::fs::write(opt.compacted_dir().join("1.meta"), "{\"generation\": 1").unwrap();
+
+        // Corrupted cached files are downloaded again.
+        assert_eq!(
+            latest_version(&opt, storage.clone()).await.unwrap(),
+            Some(49)
+        );
+        assert_eq!(std::fs::read(&truncated).unwrap(), content);
+
+        // Metadata files that are corrupted in the backup storage are skipped.
+        for backup in 3..5 {
+            let remote_file = backup_dir.path().join(format!(
+                "metadata/transaction_{}-{}.meta",
+                backup * 10,
+                backup * 10 + 9
+            ));
+            std::fs::write(&remote_file, [0xff, 0x00, 0x12, 0x34]).unwrap();
+            std::fs::remove_file(cached_file(&opt, backup)).unwrap();
+        }
+        assert_eq!(
+            latest_version(&opt, storage.clone()).await.unwrap(),
+            Some(29)
+        );
+
+        opt.strict = true;
+        assert!(latest_version(&opt, storage.clone()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_compaction() {
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let storage = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+        let cache_dir = TempPath::new();
+        let opt = MetadataCacheOpt {
+            dir: Some(cache_dir.path().to_path_buf()),
+            strict: true,
+        };
+
+        save_transaction_backups(&storage, 0..3).await;
+        let view = sync_and_compact(&opt, storage.clone(), NUM_CONCURRENT_DOWNLOADS, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            view.get_storage_state().latest_transaction_version,
+            Some(29)
+        );
+        assert!(file_names(opt.cache_dir()).await.is_empty());
+        assert_eq!(file_names(opt.compacted_dir()).await, vec!["1.meta"]);
+
+        // New files are loaded on top of the compacted ones.
+        save_transaction_backups(&storage, 3..5).await;
+        assert_eq!(
+            latest_version(&opt, storage.clone()).await.unwrap(),
+            Some(49)
+        );
+        assert_eq!(file_names(opt.cache_dir()).await.len(), 2);
+
+        // Not enough new files to compact.
+        sync_and_compact(&opt, storage.clone(), NUM_CONCURRENT_DOWNLOADS, 3)
+            .await
+            .unwrap();
+        assert_eq!(file_names(opt.compacted_dir()).await, vec!["1.meta"]);
+
+        sync_and_compact(&opt, storage.clone(), NUM_CONCURRENT_DOWNLOADS, 2)
+            .await
+            .unwrap();
+        assert!(file_names(opt.cache_dir()).await.is_empty());
+        assert_eq!(file_names(opt.compacted_dir()).await, vec!["2.meta"]);
+        assert_eq!(
+            latest_version(&opt, storage.clone()).await.unwrap(),
+            Some(49)
+        );
+
+        // If the compacted file is corrupted, the metadata files are downloaded again.
+        let compacted_file = opt.compacted_dir().join("2.meta");
+        let content = std::fs::read(&compacted_file).unwrap();
+        std::fs::write(&compacted_file, &content[..content.len() - 1]).unwrap();
+        let opt = MetadataCacheOpt {
+            strict: false,
+            ..opt
+        };
+        assert_eq!(
+            latest_version(&opt, storage.clone()).await.unwrap(),
+            Some(49)
+        );
+        assert_eq!(file_names(opt.cache_dir()).await.len(), 5);
+
+        // And the next compaction starts a new generation.
+        sync_and_compact(&opt, storage.clone(), NUM_CONCURRENT_DOWNLOADS, 1)
             .await
-            .err_notes((file!(), line!(), &buf))?;
-        Ok(buf
-            .lines()
-            .map(serde_json::from_str::<Metadata>)
-            .collect::<Result<_, serde_json::error::Error>>()?)
+            .unwrap();
+        assert_eq!(file_names(opt.compacted_dir()).await, vec!["3.meta"]);
+        assert_eq!(latest_version(&opt, storage).await.unwrap(), Some(49));
     }
 }
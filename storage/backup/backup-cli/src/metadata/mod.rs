@@ -10,7 +10,7 @@ use aptos_types::transaction::Version;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 #[allow(clippy::enum_variant_names)] // to introduce: BackupperId, etc
 pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
@@ -27,3 +27,11 @@ pub static NUM_META_DOWNLOAD: Lazy<IntGauge> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static NUM_META_CORRUPTED: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_backup_metadata_num_corrupted_files",
+        "Number of metadata files skipped because they can't be parsed."
+    )
+    .unwrap()
+});
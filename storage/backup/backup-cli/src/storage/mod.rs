@@ -3,6 +3,7 @@
 
 pub mod command_adapter;
 pub mod local_fs;
+pub mod object_store;
 
 #[cfg(test)]
 mod test_util;
@@ -12,6 +13,7 @@ mod tests;
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    object_store::{ObjectStore, ObjectStoreOpt},
 };
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
@@ -175,6 +177,8 @@ pub enum StorageOpt {
     LocalFs(LocalFsOpt),
     #[structopt(about = "Select the CommandAdapter backup store.")]
     CommandAdapter(CommandAdapterOpt),
+    #[structopt(about = "Select the ObjectStore backup store, for S3 compatible APIs.")]
+    ObjectStore(ObjectStoreOpt),
 }
 
 impl StorageOpt {
@@ -182,6 +186,7 @@ impl StorageOpt {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+            StorageOpt::ObjectStore(opt) => Arc::new(ObjectStore::new_with_opt(opt)?),
         })
     }
 }
@@ -0,0 +1,429 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use aptos_logger::prelude::*;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac, NewMac};
+use once_cell::sync::Lazy;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use regex::Regex;
+use reqwest::{Method, Response, StatusCode, Url};
+use sha2::{Digest, Sha256};
+use std::{env, time::Duration};
+
+/// Characters escaped by the "UriEncode()" of signature version 4, all but the unreserved ones
+const URI_ESCAPED: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(100);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Keys signing requests, the HMAC keys for GCS
+pub(crate) struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl Credentials {
+    /// Reads the credentials from the environment variables the AWS CLI uses
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| env::var(name).with_context(|| format!("{} is not set.", name));
+        Ok(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+}
+
+/// A part of a multipart upload, once it's uploaded
+pub(crate) struct UploadedPart {
+    number: usize,
+    etag: String,
+}
+
+/// A client of the S3 API, which GCS and most other object stores also serve, with the objects of
+/// a bucket addressed by path
+pub(crate) struct S3Client {
+    client: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    credentials: Credentials,
+    max_retries: usize,
+}
+
+impl S3Client {
+    pub fn new(
+        endpoint: Url,
+        bucket: String,
+        region: String,
+        credentials: Credentials,
+        max_retries: usize,
+    ) -> Result<Self> {
+        ensure!(
+            endpoint.host_str().is_some(),
+            "Endpoint {} has no host.",
+            endpoint
+        );
+        Ok(Self {
+            client: reqwest::Client::new(),
+            endpoint,
+            bucket,
+            region,
+            credentials,
+            max_retries,
+        })
+    }
+
+    pub async fn put_object(&self, key: &str, content: Bytes) -> Result<()> {
+        self.send(Method::PUT, Some(key), Vec::new(), content)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_object(&self, key: &str) -> Result<Response> {
+        self.send(Method::GET, Some(key), Vec::new(), Bytes::new())
+            .await
+    }
+
+    /// Keys of all the objects starting with `prefix`, listed by pages
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![
+                ("list-type".to_string(), "2".to_string()),
+                ("prefix".to_string(), prefix.to_string()),
+            ];
+            if let Some(token) = continuation_token.take() {
+                query.push(("continuation-token".to_string(), token));
+            }
+            let body = self
+                .send(Method::GET, None, query, Bytes::new())
+                .await?
+                .text()
+                .await?;
+            keys.extend(xml_values(&body, "Key"));
+
+            if xml_values(&body, "IsTruncated").first().map(String::as_str) != Some("true") {
+                return Ok(keys);
+            }
+            continuation_token = Some(
+                xml_values(&body, "NextContinuationToken")
+                    .pop()
+                    .ok_or_else(|| anyhow!("Truncated listing without a continuation token."))?,
+            );
+        }
+    }
+
+    /// Starts a multipart upload, returning its ID
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let body = self
+            .send(
+                Method::POST,
+                Some(key),
+                vec![("uploads".to_string(), String::new())],
+                Bytes::new(),
+            )
+            .await?
+            .text()
+            .await?;
+        xml_values(&body, "UploadId")
+            .pop()
+            .ok_or_else(|| anyhow!("No UploadId in response: {}", body))
+    }
+
+    /// Uploads part `number`, counting from 1
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        number: usize,
+        content: Bytes,
+    ) -> Result<UploadedPart> {
+        let response = self
+            .send(
+                Method::PUT,
+                Some(key),
+                vec![
+                    ("partNumber".to_string(), number.to_string()),
+                    ("uploadId".to_string(), upload_id.to_string()),
+                ],
+                content,
+            )
+            .await?;
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .ok_or_else(|| anyhow!("No ETag for part {} of {}.", number, key))?;
+        Ok(UploadedPart {
+            number,
+            etag: etag.to_string(),
+        })
+    }
+
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[UploadedPart],
+    ) -> Result<()> {
+        let mut content = "<CompleteMultipartUpload>".to_string();
+        for part in parts {
+            content.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                part.number, part.etag
+            ));
+        }
+        content.push_str("</CompleteMultipartUpload>");
+
+        let body = self
+            .send(
+                Method::POST,
+                Some(key),
+                vec![("uploadId".to_string(), upload_id.to_string())],
+                content.into(),
+            )
+            .await?
+            .text()
+            .await?;
+        // The completion can fail after the response started, with an error in a 200 response
+        ensure!(
+            !body.contains("<Error>"),
+            "Failed to complete upload of {}: {}",
+            key,
+            body
+        );
+        Ok(())
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        self.send(
+            Method::DELETE,
+            Some(key),
+            vec![("uploadId".to_string(), upload_id.to_string())],
+            Bytes::new(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Sends a signed request for the object `key`, or the bucket, retrying server errors with
+    /// exponential backoff
+    async fn send(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: Vec<(String, String)>,
+        body: Bytes,
+    ) -> Result<Response> {
+        let mut delay = FIRST_RETRY_DELAY;
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .request(method.clone(), key, &query, body.clone(), Utc::now())?
+                .send()
+                .await;
+            let retriable = match &result {
+                Ok(response) => response.status().is_server_error(),
+                Err(err) => err.is_connect() || err.is_timeout(),
+            };
+            if retriable && attempt < self.max_retries {
+                attempt += 1;
+                warn!(
+                    "Retrying {} {} after a failed attempt, retry #{}.",
+                    method,
+                    key.unwrap_or(&self.bucket),
+                    attempt,
+                );
+                tokio::time::sleep(delay).await;
+                delay = std::cmp::min(delay * 2, MAX_RETRY_DELAY);
+                continue;
+            }
+
+            let response = result?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+            let text = response.text().await.unwrap_or_default();
+            if status == StatusCode::NOT_FOUND {
+                bail!("{} not found: {}", key.unwrap_or(&self.bucket), text);
+            }
+            bail!(
+                "{} {} failed with {}: {}",
+                method,
+                key.unwrap_or(&self.bucket),
+                status,
+                text
+            );
+        }
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: &[(String, String)],
+        body: Bytes,
+        time: DateTime<Utc>,
+    ) -> Result<reqwest::RequestBuilder> {
+        let mut host = self.endpoint.host_str().unwrap().to_string();
+        if let Some(port) = self.endpoint.port() {
+            host.push_str(&format!(":{}", port));
+        }
+        let mut path = format!(
+            "{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            uri_encode(&self.bucket)
+        );
+        if let Some(key) = key {
+            for segment in key.split('/') {
+                path.push('/');
+                path.push_str(&uri_encode(segment));
+            }
+        }
+        let canonical_query = canonical_query(query);
+        let mut url = format!("{}://{}{}", self.endpoint.scheme(), host, path);
+        if !canonical_query.is_empty() {
+            url.push('?');
+            url.push_str(&canonical_query);
+        }
+
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let amz_date = time.format("%Y%m%dT%H%M%SZ").to_string();
+        let mut headers = vec![
+            ("host".to_string(), host),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        let authorization = authorization(
+            &self.credentials,
+            &self.region,
+            method.as_str(),
+            &path,
+            &canonical_query,
+            &headers,
+            &payload_hash,
+            &amz_date,
+        );
+
+        let mut request = self
+            .client
+            .request(method, Url::parse(&url)?)
+            .header(reqwest::header::AUTHORIZATION, authorization)
+            .body(body);
+        // reqwest sets the host itself
+        for (name, value) in headers.into_iter().filter(|(name, _)| name != "host") {
+            request = request.header(name, value);
+        }
+        Ok(request)
+    }
+}
+
+fn uri_encode(value: &str) -> String {
+    utf8_percent_encode(value, URI_ESCAPED).to_string()
+}
+
+/// The query string sorted by parameter name, as signed
+fn canonical_query(query: &[(String, String)]) -> String {
+    let mut params: Vec<_> = query
+        .iter()
+        .map(|(name, value)| (uri_encode(name), uri_encode(value)))
+        .collect();
+    params.sort();
+    params
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// The Authorization header signing a request with signature version 4
+///
+/// `headers` are all the signed headers, with lowercase names, and `amz_date` the time of the
+/// request in the `x-amz-date` header.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn authorization(
+    credentials: &Credentials,
+    region: &str,
+    method: &str,
+    canonical_uri: &str,
+    canonical_query: &str,
+    headers: &[(String, String)],
+    payload_hash: &str,
+    amz_date: &str,
+) -> String {
+    let mut headers = headers.to_vec();
+    headers.sort();
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method, canonical_uri, canonical_query, canonical_headers, signed_headers, payload_hash
+    );
+
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let mut key = hmac_sha256(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date,
+    );
+    for part in [region, "s3", "aws4_request"] {
+        key = hmac_sha256(&key, part);
+    }
+    let signature = hex::encode(hmac_sha256(&key, &string_to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_varkey(key).expect("HMAC takes keys of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// The text of all the elements named `tag` in an XML response
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    static ELEMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"<(\w+)>([^<]*)</\w+>").unwrap());
+    ELEMENT
+        .captures_iter(xml)
+        .filter(|captures| &captures[1] == tag)
+        .map(|captures| {
+            captures[2]
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&")
+        })
+        .collect()
+}
@@ -0,0 +1,260 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+#[cfg(test)]
+mod tests;
+
+use crate::storage::{
+    object_store::client::{Credentials, S3Client},
+    BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+    TextLine,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{FutureExt, TryStreamExt};
+use reqwest::Url;
+use std::{
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, DuplexStream},
+    task::JoinHandle,
+};
+use tokio_util::io::StreamReader;
+
+/// Bytes buffered between a writer and the upload of the file it writes
+const WRITE_BUFFER_SIZE: usize = 1 << 16;
+
+#[derive(StructOpt)]
+pub struct ObjectStoreOpt {
+    #[structopt(
+        long = "endpoint",
+        help = "URL of the S3 API of the object store, e.g. https://s3.us-west-2.amazonaws.com, \
+        or https://storage.googleapis.com for GCS. Credentials are read from the \
+        AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY and AWS_SESSION_TOKEN environment variables, \
+        HMAC keys for GCS."
+    )]
+    pub endpoint: Url,
+    #[structopt(long = "bucket", help = "Bucket to hold backups.")]
+    pub bucket: String,
+    #[structopt(
+        long = "prefix",
+        default_value = "",
+        help = "Folder in the bucket to hold backups, the whole bucket if empty."
+    )]
+    pub prefix: String,
+    #[structopt(
+        long = "region",
+        default_value = "us-east-1",
+        help = "Region of the bucket, \"auto\" for GCS."
+    )]
+    pub region: String,
+    #[structopt(
+        long = "multipart-threshold-bytes",
+        default_value = "16777216",
+        help = "Files of at least this size are uploaded in parts of this size. S3 requires parts \
+        of at least 5MiB."
+    )]
+    pub multipart_threshold_bytes: usize,
+    #[structopt(
+        long = "max-retries",
+        default_value = "5",
+        help = "Times to retry a request failing with a server error, with exponential backoff."
+    )]
+    pub max_retries: usize,
+}
+
+/// A storage backend that stores everything in a bucket of an object store with an S3 compatible
+/// API, e.g. S3 or GCS.
+pub struct ObjectStore {
+    client: Arc<S3Client>,
+    /// Prefix of the keys of all objects, empty or ending with '/'
+    prefix: String,
+    multipart_threshold_bytes: usize,
+}
+
+impl ObjectStore {
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new_with_opt(opt: ObjectStoreOpt) -> Result<Self> {
+        Self::new(opt, Credentials::from_env()?)
+    }
+
+    fn new(opt: ObjectStoreOpt, credentials: Credentials) -> Result<Self> {
+        let client = S3Client::new(
+            opt.endpoint,
+            opt.bucket,
+            opt.region,
+            credentials,
+            opt.max_retries,
+        )?;
+        let mut prefix = opt.prefix.trim_matches('/').to_string();
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        Ok(Self {
+            client: Arc::new(client),
+            prefix,
+            multipart_threshold_bytes: opt.multipart_threshold_bytes,
+        })
+    }
+
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        format!("{}{}", self.prefix, file_handle)
+    }
+
+    fn metadata_file_handle(name: &ShellSafeName) -> FileHandle {
+        format!("{}/{}", Self::METADATA_DIR, name.as_ref())
+    }
+}
+
+#[async_trait]
+impl BackupStorage for ObjectStore {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no folders to create, the files of a backup share their prefix
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let (writer, reader) = tokio::io::duplex(WRITE_BUFFER_SIZE);
+        let upload = tokio::spawn(upload(
+            self.client.clone(),
+            self.key(&file_handle),
+            reader,
+            self.multipart_threshold_bytes,
+        ));
+        Ok((
+            file_handle,
+            Box::new(UploadingWriter {
+                writer,
+                upload: Some(upload),
+            }),
+        ))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let response = self.client.get_object(&self.key(file_handle)).await?;
+        let stream = response
+            .bytes_stream()
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err));
+        Ok(Box::new(StreamReader::new(stream)))
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        self.client
+            .put_object(
+                &self.key(&Self::metadata_file_handle(name)),
+                Bytes::copy_from_slice(content.as_ref().as_bytes()),
+            )
+            .await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let keys = self
+            .client
+            .list_objects(&self.key(&format!("{}/", Self::METADATA_DIR)))
+            .await?;
+        keys.into_iter()
+            .map(|key| {
+                key.strip_prefix(&self.prefix)
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow!("Listed key {} out of prefix.", key))
+            })
+            .collect()
+    }
+}
+
+/// Reads up to `size` bytes, less only at the end of `reader`
+async fn read_chunk(reader: &mut DuplexStream, size: usize) -> io::Result<Bytes> {
+    let mut chunk = Vec::with_capacity(size);
+    (&mut *reader)
+        .take(size as u64)
+        .read_to_end(&mut chunk)
+        .await?;
+    Ok(chunk.into())
+}
+
+/// Uploads what's written into `reader` as the object `key`, in parts if there are at least
+/// `multipart_threshold_bytes`
+async fn upload(
+    client: Arc<S3Client>,
+    key: String,
+    mut reader: DuplexStream,
+    multipart_threshold_bytes: usize,
+) -> Result<()> {
+    let mut chunk = read_chunk(&mut reader, multipart_threshold_bytes).await?;
+    if chunk.len() < multipart_threshold_bytes {
+        return client.put_object(&key, chunk).await;
+    }
+
+    let upload_id = client.create_multipart_upload(&key).await?;
+    let result: Result<()> = async {
+        let mut parts = Vec::new();
+        while !chunk.is_empty() {
+            parts.push(
+                client
+                    .upload_part(&key, &upload_id, parts.len() + 1, chunk)
+                    .await?,
+            );
+            chunk = read_chunk(&mut reader, multipart_threshold_bytes).await?;
+        }
+        client
+            .complete_multipart_upload(&key, &upload_id, &parts)
+            .await
+    }
+    .await;
+    if result.is_err() {
+        // Otherwise the uploaded parts are kept, and paid for
+        let _ = client.abort_multipart_upload(&key, &upload_id).await;
+    }
+    result
+}
+
+/// Writes into an upload, whose end is waited for on shutdown
+struct UploadingWriter {
+    writer: DuplexStream,
+    upload: Option<JoinHandle<Result<()>>>,
+}
+
+impl AsyncWrite for UploadingWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.writer).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        futures::ready!(Pin::new(&mut self.writer).poll_shutdown(cx))?;
+        let upload = match self.upload.as_mut() {
+            Some(upload) => upload,
+            None => return Poll::Ready(Ok(())),
+        };
+        let result = futures::ready!(upload.poll_unpin(cx));
+        self.upload = None;
+        Poll::Ready(match result {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(err)) => Err(io::Error::new(io::ErrorKind::Other, err)),
+            Err(err) => Err(io::Error::new(io::ErrorKind::Other, err)),
+        })
+    }
+}
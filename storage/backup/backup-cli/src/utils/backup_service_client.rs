@@ -7,6 +7,7 @@ use aptos_crypto::HashValue;
 use aptos_types::transaction::Version;
 use aptosdb::backup::backup_handler::DbState;
 use futures::TryStreamExt;
+use std::time::Duration;
 use structopt::StructOpt;
 use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio_util::compat::FuturesAsyncReadCompatExt;
@@ -19,6 +20,12 @@ pub struct BackupServiceClientOpt {
         help = "Backup service address."
     )]
     pub address: String,
+    #[structopt(
+        long = "backup-service-http1-only",
+        help = "Talk HTTP/1.1 instead of HTTP/2 to the backup service, in case there's a proxy \
+        in between that doesn't support HTTP/2 without TLS."
+    )]
+    pub http1_only: bool,
 }
 
 pub struct BackupServiceClient {
@@ -27,17 +34,33 @@ pub struct BackupServiceClient {
 }
 
 impl BackupServiceClient {
-    pub fn new_with_opt(opt: BackupServiceClientOpt) -> Self {
-        Self::new(opt.address)
+    const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+    pub fn new_with_opt(opt: BackupServiceClientOpt, max_concurrent_requests: usize) -> Self {
+        Self::build(opt.address, max_concurrent_requests, opt.http1_only)
     }
 
     pub fn new(address: String) -> Self {
+        Self::build(address, num_cpus::get(), false /* http1_only */)
+    }
+
+    fn build(address: String, max_concurrent_requests: usize, http1_only: bool) -> Self {
+        // Keep the connections alive and pooled, so that downloading thousands of chunks doesn't
+        // pay for a new connection each time.
+        let builder = reqwest::Client::builder()
+            .no_proxy()
+            .pool_max_idle_per_host(max_concurrent_requests)
+            .tcp_keepalive(Self::TCP_KEEPALIVE);
+        let builder = if http1_only {
+            builder.http1_only()
+        } else {
+            // The backup service serves HTTP/2 without TLS, so there's nothing to negotiate.
+            builder.http2_prior_knowledge()
+        };
+
         Self {
             address,
-            client: reqwest::Client::builder()
-                .no_proxy()
-                .build()
-                .expect("Http client should build."),
+            client: builder.build().expect("Http client should build."),
         }
     }
 
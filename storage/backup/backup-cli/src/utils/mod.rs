@@ -10,9 +10,9 @@ pub(crate) mod stream;
 #[cfg(test)]
 pub mod test_utils;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, ensure, Result};
 use aptos_config::config::{
-    RocksdbConfig, RocksdbConfigs, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    ColumnFamilyConfig, RocksdbConfig, RocksdbConfigs, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
     NO_OP_STORAGE_PRUNER_CONFIG, TARGET_SNAPSHOT_SIZE,
 };
 use aptos_crypto::HashValue;
@@ -21,16 +21,20 @@ use aptos_jellyfish_merkle::{
     restore::StateSnapshotRestore, NodeBatch, StateValueBatch, StateValueWriter, TreeWriter,
 };
 use aptos_types::{
+    epoch_change::Verifier,
+    ledger_info::LedgerInfoWithSignatures,
     state_store::{state_key::StateKey, state_value::StateValue},
     transaction::Version,
     waypoint::Waypoint,
 };
 use aptosdb::{backup::restore_handler::RestoreHandler, AptosDB, GetRestoreHandler};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     convert::TryFrom,
+    fmt,
     mem::size_of,
     path::{Path, PathBuf},
+    str::FromStr,
     sync::Arc,
 };
 use structopt::StructOpt;
@@ -63,6 +67,66 @@ pub struct RocksdbOpt {
     index_db_max_total_wal_size: u64,
     #[structopt(long, default_value = "16")]
     max_background_jobs: i32,
+    #[structopt(
+        long = "ledger-db-column-family",
+        help = "Options of a ledger db column family, e.g. \
+        `state_value:compression=zstd,block_size=16384,bloom_filter_bits=10`. Can be repeated."
+    )]
+    ledger_db_column_families: Vec<ColumnFamilyOpt>,
+    #[structopt(
+        long = "state-merkle-db-column-family",
+        help = "Options of a state merkle db column family, in the same form as \
+        --ledger-db-column-family. Can be repeated."
+    )]
+    state_merkle_db_column_families: Vec<ColumnFamilyOpt>,
+}
+
+/// Options of one column family, in the form of `<cf_name>:<option>=<value>,...`
+#[derive(Clone, Debug)]
+pub struct ColumnFamilyOpt {
+    cf_name: String,
+    config: ColumnFamilyConfig,
+}
+
+impl ColumnFamilyOpt {
+    fn collect(opts: Vec<ColumnFamilyOpt>) -> BTreeMap<String, ColumnFamilyConfig> {
+        opts.into_iter()
+            .map(|opt| (opt.cf_name, opt.config))
+            .collect()
+    }
+}
+
+impl FromStr for ColumnFamilyOpt {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (cf_name, options) = s.split_once(':').ok_or_else(|| {
+            anyhow!("Expecting column family options in the form of <cf_name>:<option>=<value>,...")
+        })?;
+        let mut config = ColumnFamilyConfig::default();
+        for option in options.split(',') {
+            let (name, value) = option
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expecting <option>=<value>, got {}", option))?;
+            let value = value.trim();
+            match name.trim() {
+                "compression" => config.compression = Some(value.parse()?),
+                "block_size" => config.block_size = Some(value.parse()?),
+                "bloom_filter_bits" => config.bloom_filter_bits = Some(value.parse()?),
+                name => {
+                    return Err(anyhow!(
+                        "Unknown column family option {}, expected one of compression, \
+                        block_size and bloom_filter_bits",
+                        name
+                    ))
+                }
+            }
+        }
+        Ok(Self {
+            cf_name: cf_name.trim().to_string(),
+            config,
+        })
+    }
 }
 
 impl From<RocksdbOpt> for RocksdbConfigs {
@@ -72,12 +136,14 @@ impl From<RocksdbOpt> for RocksdbConfigs {
                 max_open_files: opt.ledger_db_max_open_files,
                 max_total_wal_size: opt.ledger_db_max_total_wal_size,
                 max_background_jobs: opt.max_background_jobs,
+                column_families: ColumnFamilyOpt::collect(opt.ledger_db_column_families),
                 ..Default::default()
             },
             state_merkle_db_config: RocksdbConfig {
                 max_open_files: opt.state_merkle_db_max_open_files,
                 max_total_wal_size: opt.state_merkle_db_max_total_wal_size,
                 max_background_jobs: opt.max_background_jobs,
+                column_families: ColumnFamilyOpt::collect(opt.state_merkle_db_column_families),
                 ..Default::default()
             },
             index_db_config: RocksdbConfig {
@@ -116,6 +182,14 @@ pub struct GlobalRestoreOpt {
     )]
     pub target_version: Option<Version>,
 
+    #[structopt(
+        long,
+        help = "Transaction backup chunks entirely older than this version will not be recovered \
+        to DB, for restoring a slice of the ledger history. A state snapshot at or before this \
+        version is required to anchor the range. [Defaults to 0]"
+    )]
+    pub start_version: Option<Version>,
+
     #[structopt(flatten)]
     pub trusted_waypoints: TrustedWaypointOpt,
 
@@ -200,6 +274,7 @@ impl RestoreRunMode {
 
 #[derive(Clone)]
 pub struct GlobalRestoreOptions {
+    pub start_version: Version,
     pub target_version: Version,
     pub trusted_waypoints: Arc<HashMap<Version, Waypoint>>,
     pub run_mode: Arc<RestoreRunMode>,
@@ -210,7 +285,14 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
     type Error = anyhow::Error;
 
     fn try_from(opt: GlobalRestoreOpt) -> Result<Self> {
+        let start_version = opt.start_version.unwrap_or(0);
         let target_version = opt.target_version.unwrap_or(Version::max_value());
+        ensure!(
+            start_version <= target_version,
+            "Start version {} is greater than target version {}.",
+            start_version,
+            target_version,
+        );
         let concurrent_downloads = opt.concurernt_downloads.get();
         let run_mode = if let Some(db_dir) = &opt.db_dir {
             let restore_handler = Arc::new(AptosDB::open(
@@ -228,6 +310,7 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
             RestoreRunMode::Verify
         };
         Ok(Self {
+            start_version,
             target_version,
             trusted_waypoints: Arc::new(opt.trusted_waypoints.verify()?),
             run_mode: Arc::new(run_mode),
@@ -265,6 +348,75 @@ impl TrustedWaypointOpt {
         }
         Ok(trusted_waypoints)
     }
+
+    /// Checks that the epoch ending `ledger_infos` link the trusted waypoints together.
+    ///
+    /// The LedgerInfos must cover consecutive epochs. Each one either matches the trusted
+    /// waypoint at its version, or is signed by the validator set announced by the previous
+    /// epoch. The first one must match a trusted waypoint, and every trusted waypoint within the
+    /// covered versions must match one of the LedgerInfos.
+    pub fn verify_chain_consistency(
+        &self,
+        ledger_infos: &[LedgerInfoWithSignatures],
+    ) -> Result<()> {
+        let trusted_waypoints = self.clone().verify()?;
+        let mut ledger_infos: Vec<_> = ledger_infos.iter().collect();
+        ledger_infos.sort_by_key(|li| li.ledger_info().epoch());
+
+        let mut previous_li: Option<&LedgerInfoWithSignatures> = None;
+        for li in &ledger_infos {
+            if let Some(pre_li) = previous_li {
+                ensure!(
+                    li.ledger_info().epoch() == pre_li.ledger_info().epoch() + 1,
+                    "LedgerInfo epochs are not consecutive. Previous: {}, current: {}.",
+                    pre_li.ledger_info().epoch(),
+                    li.ledger_info().epoch(),
+                );
+            }
+            let wp_li = Waypoint::new_epoch_boundary(li.ledger_info())?;
+            if let Some(wp_trusted) = trusted_waypoints.get(&wp_li.version()) {
+                ensure!(
+                    *wp_trusted == wp_li,
+                    "Waypoints don't match. In LedgerInfo: {}, trusted: {}",
+                    wp_li,
+                    wp_trusted,
+                );
+            } else if let Some(pre_li) = previous_li {
+                pre_li
+                    .ledger_info()
+                    .next_epoch_state()
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Next epoch state not found from LI at epoch {}.",
+                            pre_li.ledger_info().epoch()
+                        )
+                    })?
+                    .verify(li)?;
+            } else {
+                return Err(anyhow!(
+                    "LedgerInfo at epoch {} doesn't match any trusted waypoint, so the chain \
+                    can't be anchored.",
+                    li.ledger_info().epoch(),
+                ));
+            }
+            previous_li = Some(li);
+        }
+
+        if let (Some(first), Some(last)) = (ledger_infos.first(), ledger_infos.last()) {
+            let covered = first.ledger_info().version()..=last.ledger_info().version();
+            for (version, wp) in &trusted_waypoints {
+                ensure!(
+                    !covered.contains(version)
+                        || ledger_infos
+                            .iter()
+                            .any(|li| li.ledger_info().version() == *version),
+                    "Trusted waypoint {} doesn't match any epoch ending LedgerInfo.",
+                    wp,
+                );
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy, Default, StructOpt)]
@@ -283,6 +435,54 @@ impl ConcurrentDownloadsOpt {
     }
 }
 
+/// A range of epochs, [`start`, `end`) (right side exclusive), parsed from "<start>..<end>".
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EpochRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl EpochRange {
+    pub fn contains(&self, epoch: u64) -> bool {
+        self.start <= epoch && epoch < self.end
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl FromStr for EpochRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, end) = s
+            .split_once("..")
+            .ok_or_else(|| anyhow!("Expecting epoch range in the form of <start>..<end>."))?;
+        let range = Self {
+            start: start.trim().parse()?,
+            end: end.trim().parse()?,
+        };
+        ensure!(
+            range.start < range.end,
+            "Empty epoch range: {}..{}",
+            range.start,
+            range.end,
+        );
+        Ok(range)
+    }
+}
+
+impl fmt::Display for EpochRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
 pub(crate) fn should_cut_chunk(chunk: &[u8], record: &[u8], max_chunk_size: usize) -> bool {
     !chunk.is_empty() && chunk.len() + record.len() + size_of::<u32>() > max_chunk_size
 }
@@ -309,3 +509,161 @@ impl<T: AsRef<Path>> PathToString for T {
 pub(crate) fn unix_timestamp_sec() -> i64 {
     duration_since_epoch().as_secs() as i64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::{
+        block_info::BlockInfo,
+        epoch_state::EpochState,
+        ledger_info::{generate_ledger_info_with_sig, LedgerInfo},
+        validator_signer::ValidatorSigner,
+        validator_verifier::generate_validator_verifier,
+    };
+
+    const NUM_EPOCHS: u64 = 4;
+
+    fn validator_set(seed: u8) -> Vec<ValidatorSigner> {
+        (0..3)
+            .map(|i| ValidatorSigner::random([seed * 10 + i; 32]))
+            .collect()
+    }
+
+    /// The LedgerInfo ending `epoch`, signed by `signers` and announcing `next_signers`
+    fn epoch_ending_li(
+        epoch: u64,
+        signers: &[ValidatorSigner],
+        next_signers: &[ValidatorSigner],
+    ) -> LedgerInfoWithSignatures {
+        let next_epoch_state = EpochState {
+            epoch: epoch + 1,
+            verifier: generate_validator_verifier(next_signers),
+        };
+        let block_info = BlockInfo::new(
+            epoch,
+            0,
+            HashValue::zero(),
+            HashValue::zero(),
+            epoch * 100,
+            0,
+            Some(next_epoch_state),
+        );
+        generate_ledger_info_with_sig(signers, LedgerInfo::new(block_info, HashValue::zero()))
+    }
+
+    fn epoch_chain() -> Vec<LedgerInfoWithSignatures> {
+        (0..NUM_EPOCHS)
+            .map(|epoch| {
+                epoch_ending_li(
+                    epoch,
+                    &validator_set(epoch as u8),
+                    &validator_set(epoch as u8 + 1),
+                )
+            })
+            .collect()
+    }
+
+    fn trusting(lis: &[&LedgerInfoWithSignatures]) -> TrustedWaypointOpt {
+        TrustedWaypointOpt {
+            trust_waypoint: lis
+                .iter()
+                .map(|li| Waypoint::new_epoch_boundary(li.ledger_info()).unwrap())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_verify_chain_consistency() {
+        let lis = epoch_chain();
+        let opt = trusting(&[&lis[0]]);
+        opt.verify_chain_consistency(&lis).unwrap();
+
+        // Order of the input doesn't matter
+        let mut reversed = lis.clone();
+        reversed.reverse();
+        opt.verify_chain_consistency(&reversed).unwrap();
+
+        // Trusting all of them is consistent as well
+        trusting(&lis.iter().collect::<Vec<_>>())
+            .verify_chain_consistency(&lis)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify_chain_consistency_needs_anchor() {
+        let lis = epoch_chain();
+        assert!(TrustedWaypointOpt::default()
+            .verify_chain_consistency(&lis)
+            .is_err());
+        // Anchoring in the middle leaves the earlier epochs unverified
+        assert!(trusting(&[&lis[2]]).verify_chain_consistency(&lis).is_err());
+    }
+
+    #[test]
+    fn test_verify_chain_consistency_rejects_gap() {
+        let mut lis = epoch_chain();
+        lis.remove(2);
+        assert!(trusting(&[&lis[0]]).verify_chain_consistency(&lis).is_err());
+    }
+
+    #[test]
+    fn test_verify_chain_consistency_rejects_wrong_signers() {
+        let mut lis = epoch_chain();
+        // Epoch 2 is signed by a validator set that epoch 1 didn't announce
+        lis[2] = epoch_ending_li(2, &validator_set(20), &validator_set(3));
+        assert!(trusting(&[&lis[0]]).verify_chain_consistency(&lis).is_err());
+
+        // A trusted waypoint overrides the signature check, e.g. after a writeset
+        // transaction replaced the validator set
+        trusting(&[&lis[0], &lis[2]])
+            .verify_chain_consistency(&lis)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify_chain_consistency_rejects_conflicting_waypoint() {
+        let lis = epoch_chain();
+
+        // A trusted waypoint at the version of epoch 1 with a different hash
+        let forked = epoch_ending_li(1, &validator_set(1), &validator_set(25));
+        assert!(trusting(&[&lis[0], &forked])
+            .verify_chain_consistency(&lis)
+            .is_err());
+
+        // A trusted waypoint within the covered versions that no LedgerInfo ends at
+        let next_epoch_state = EpochState {
+            epoch: 2,
+            verifier: generate_validator_verifier(&validator_set(2)),
+        };
+        let off_boundary = LedgerInfo::new(
+            BlockInfo::new(
+                1,
+                0,
+                HashValue::zero(),
+                HashValue::zero(),
+                150,
+                0,
+                Some(next_epoch_state),
+            ),
+            HashValue::zero(),
+        );
+        let mut opt = trusting(&[&lis[0]]);
+        opt.trust_waypoint
+            .push(Waypoint::new_epoch_boundary(&off_boundary).unwrap());
+        assert!(opt.verify_chain_consistency(&lis).is_err());
+    }
+
+    #[test]
+    fn test_parse_epoch_range() {
+        let range: EpochRange = "3..7".parse().unwrap();
+        assert_eq!(range, EpochRange { start: 3, end: 7 });
+        assert_eq!(range.len(), 4);
+        assert!(range.contains(3) && range.contains(6) && !range.contains(7));
+        assert_eq!(range.to_string(), "3..7");
+
+        assert!("7..3".parse::<EpochRange>().is_err());
+        assert!("3..3".parse::<EpochRange>().is_err());
+        assert!("3-7".parse::<EpochRange>().is_err());
+        assert!("a..7".parse::<EpochRange>().is_err());
+    }
+}
@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::storage::{BackupHandle, BackupStorage, FileHandleRef};
-use anyhow::Result;
+use anyhow::{ensure, Result};
+use aptos_crypto::HashValue;
 use async_trait::async_trait;
 use rand::random;
 use serde::de::DeserializeOwned;
@@ -12,6 +13,13 @@ use tokio::io::AsyncReadExt;
 #[async_trait]
 pub trait BackupStorageExt {
     async fn read_all(&self, file_handle: &FileHandleRef) -> Result<Vec<u8>>;
+    /// Reads the whole chunk file and, if `expected_hash` is present, makes sure its SHA3-256
+    /// hash matches, so corrupted chunks are rejected before their content is parsed.
+    async fn read_chunk(
+        &self,
+        file_handle: &FileHandleRef,
+        expected_hash: Option<HashValue>,
+    ) -> Result<Vec<u8>>;
     async fn load_json_file<T: DeserializeOwned>(&self, file_handle: &FileHandleRef) -> Result<T>;
     async fn load_bcs_file<T: DeserializeOwned>(&self, file_handle: &FileHandleRef) -> Result<T>;
     /// Adds a random suffix ".XXXX" to the backup name, so a retry won't pass a same backup name to
@@ -28,6 +36,25 @@ impl BackupStorageExt for Arc<dyn BackupStorage> {
         Ok(bytes)
     }
 
+    async fn read_chunk(
+        &self,
+        file_handle: &FileHandleRef,
+        expected_hash: Option<HashValue>,
+    ) -> Result<Vec<u8>> {
+        let bytes = self.read_all(file_handle).await?;
+        if let Some(expected_hash) = expected_hash {
+            let actual_hash = HashValue::sha3_256_of(&bytes);
+            ensure!(
+                actual_hash == expected_hash,
+                "Chunk hash mismatch for {}. Expected: {}, actual: {}.",
+                file_handle,
+                expected_hash,
+                actual_hash,
+            );
+        }
+        Ok(bytes)
+    }
+
     async fn load_bcs_file<T: DeserializeOwned>(&self, file_handle: &FileHandleRef) -> Result<T> {
         Ok(bcs::from_bytes(&self.read_all(file_handle).await?)?)
     }
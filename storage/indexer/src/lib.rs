@@ -15,7 +15,7 @@ use crate::{
 use anyhow::{bail, ensure, Result};
 use aptos_config::config::RocksdbConfig;
 use aptos_logger::warn;
-use aptos_rocksdb_options::gen_rocksdb_options;
+use aptos_rocksdb_options::{gen_cfds, gen_rocksdb_options};
 use aptos_types::{
     access_path::Path,
     account_address::AccountAddress,
@@ -55,11 +55,11 @@ impl Indexer {
     ) -> Result<Self> {
         let db_path = db_root_path.as_ref().join(INDEX_DB_NAME);
 
-        let db = DB::open(
+        let db = DB::open_cf(
+            &gen_rocksdb_options(&rocksdb_config, false),
             db_path,
             "index_db",
-            column_families(),
-            &gen_rocksdb_options(&rocksdb_config, false),
+            gen_cfds(&rocksdb_config, column_families(), |_, _| {})?,
         )?;
 
         let next_version = db
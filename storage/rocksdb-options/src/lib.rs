@@ -1,8 +1,9 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_config::config::RocksdbConfig;
-use rocksdb::Options;
+use anyhow::{ensure, Result};
+use aptos_config::config::{RocksdbCompression, RocksdbConfig};
+use rocksdb::{BlockBasedOptions, Cache, ColumnFamilyDescriptor, DBCompressionType, Options};
 
 // TODO: Clean this up. It is currently separated into its own crate
 // to avoid circular dependencies, because it depends on aptos-config (which
@@ -13,6 +14,9 @@ pub fn gen_rocksdb_options(config: &RocksdbConfig, readonly: bool) -> Options {
     db_opts.set_max_open_files(config.max_open_files);
     db_opts.set_max_total_wal_size(config.max_total_wal_size);
     db_opts.set_max_background_jobs(config.max_background_jobs);
+    if config.enable_statistics {
+        db_opts.enable_statistics();
+    }
     if !readonly {
         db_opts.create_if_missing(true);
         db_opts.create_missing_column_families(true);
@@ -20,3 +24,60 @@ pub fn gen_rocksdb_options(config: &RocksdbConfig, readonly: bool) -> Options {
 
     db_opts
 }
+
+/// Generates the descriptors of `cfs`, applying the overrides in `rocksdb_config` to each column
+/// family, and then `cf_opts_post_processor` for options that can't be configured.
+pub fn gen_cfds<F>(
+    rocksdb_config: &RocksdbConfig,
+    cfs: Vec<&'static str>,
+    cf_opts_post_processor: F,
+) -> Result<Vec<ColumnFamilyDescriptor>>
+where
+    F: Fn(&'static str, &mut Options),
+{
+    for cf_name in rocksdb_config.column_families.keys() {
+        ensure!(
+            cfs.contains(&cf_name.as_str()),
+            "Unknown column family {} in RocksDB config, expected one of {:?}",
+            cf_name,
+            cfs,
+        );
+    }
+
+    let cache = Cache::new_lru_cache(rocksdb_config.block_cache_size as usize)
+        .expect("Create Rocksdb block cache failed.");
+    let mut cfds = Vec::with_capacity(cfs.len());
+    for cf_name in cfs {
+        let cf_config = rocksdb_config
+            .column_families
+            .get(cf_name)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut table_options = BlockBasedOptions::default();
+        table_options
+            .set_cache_index_and_filter_blocks(rocksdb_config.cache_index_and_filter_blocks);
+        table_options
+            .set_block_size(cf_config.block_size.unwrap_or(rocksdb_config.block_size) as usize);
+        if let Some(bloom_filter_bits) = cf_config.bloom_filter_bits {
+            table_options.set_bloom_filter(bloom_filter_bits, false);
+        }
+        table_options.set_block_cache(&cache);
+
+        let mut cf_opts = Options::default();
+        cf_opts.set_compression_type(compression_type(cf_config.compression.unwrap_or_default()));
+        cf_opts.set_block_based_table_factory(&table_options);
+        cf_opts_post_processor(cf_name, &mut cf_opts);
+        cfds.push(ColumnFamilyDescriptor::new(cf_name.to_string(), cf_opts));
+    }
+    Ok(cfds)
+}
+
+fn compression_type(compression: RocksdbCompression) -> DBCompressionType {
+    match compression {
+        RocksdbCompression::None => DBCompressionType::None,
+        RocksdbCompression::Lz4 => DBCompressionType::Lz4,
+        RocksdbCompression::Lz4hc => DBCompressionType::Lz4hc,
+        RocksdbCompression::Zstd => DBCompressionType::Zstd,
+    }
+}
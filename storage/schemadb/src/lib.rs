@@ -34,8 +34,8 @@ use std::{collections::HashMap, iter::Iterator, marker::PhantomData, path::Path}
 
 /// Type alias to `rocksdb::ReadOptions`. See [`rocksdb doc`](https://github.com/pingcap/rust-rocksdb/blob/master/src/rocksdb_options.rs)
 pub use rocksdb::{
-    BlockBasedOptions, Cache, ColumnFamilyDescriptor, DBCompressionType, Options, ReadOptions,
-    SliceTransform, DEFAULT_COLUMN_FAMILY_NAME,
+    BlockBasedOptions, Cache, ColumnFamilyDescriptor, CompactOptions, DBCompressionType, Options,
+    ReadOptions, SliceTransform, DEFAULT_COLUMN_FAMILY_NAME,
 };
 pub type ColumnFamilyName = &'static str;
 
@@ -310,6 +310,33 @@ impl DB {
             .transpose()
     }
 
+    /// Reads records by keys in a single batched lookup, in the order of the keys.
+    pub fn multi_get<S: Schema>(&self, schema_keys: &[S::Key]) -> Result<Vec<Option<S::Value>>> {
+        let _timer = APTOS_SCHEMADB_GET_LATENCY_SECONDS
+            .with_label_values(&[S::COLUMN_FAMILY_NAME])
+            .start_timer();
+
+        let keys = schema_keys
+            .iter()
+            .map(<S::Key as KeyCodec<S>>::encode_key)
+            .collect::<Result<Vec<_>>>()?;
+        let cf_handle = self.get_cf_handle(S::COLUMN_FAMILY_NAME)?;
+
+        self.inner
+            .multi_get_cf(keys.iter().map(|k| (cf_handle, k)))
+            .into_iter()
+            .map(|result| {
+                let result = result?;
+                APTOS_SCHEMADB_GET_BYTES
+                    .with_label_values(&[S::COLUMN_FAMILY_NAME])
+                    .observe(result.as_ref().map_or(0.0, |v| v.len() as f64));
+                result
+                    .map(|raw_value| <S::Value as ValueCodec<S>>::decode_value(&raw_value))
+                    .transpose()
+            })
+            .collect()
+    }
+
     /// Writes single record.
     pub fn put<S: Schema>(&self, key: &S::Key, value: &S::Value) -> Result<()> {
         // Not necessary to use a batch, but we'd like a central place to bump counters.
@@ -434,6 +461,15 @@ impl DB {
         Ok(self.inner.flush_cf(self.get_cf_handle(cf_name)?)?)
     }
 
+    /// Manually compacts the whole key range of a column family. Blocks until the compaction is
+    /// done.
+    pub fn compact_range_cf(&self, cf_name: &str, opts: &CompactOptions) -> Result<()> {
+        let cf_handle = self.get_cf_handle(cf_name)?;
+        self.inner
+            .compact_range_cf_opt(cf_handle, None::<&[u8]>, None::<&[u8]>, opts);
+        Ok(())
+    }
+
     pub fn get_property(&self, cf_name: &str, property_name: &str) -> Result<u64> {
         self.inner
             .property_int_value_cf(self.get_cf_handle(cf_name)?, property_name)?
@@ -446,6 +482,11 @@ impl DB {
             })
     }
 
+    /// Returns the dump of the DB wide RocksDB statistics, or `None` if they aren't enabled.
+    pub fn get_statistics(&self) -> Result<Option<String>> {
+        Ok(self.inner.property_value("rocksdb.options-statistics")?)
+    }
+
     /// Creates new physical DB checkpoint in directory specified by `path`.
     pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         rocksdb::checkpoint::Checkpoint::new(&self.inner)?.create_checkpoint(path)?;
@@ -173,6 +173,27 @@ fn test_schema_put_get() {
     );
 }
 
+#[test]
+fn test_schema_multi_get() {
+    let db = TestDB::new();
+
+    db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+    db.put::<TestSchema1>(&TestField(2), &TestField(2)).unwrap();
+    db.put::<TestSchema2>(&TestField(1), &TestField(3)).unwrap();
+
+    assert_eq!(
+        db.multi_get::<TestSchema1>(&[TestField(2), TestField(1), TestField(0), TestField(2)])
+            .unwrap(),
+        vec![
+            Some(TestField(2)),
+            None,
+            Some(TestField(0)),
+            Some(TestField(2)),
+        ],
+    );
+    assert_eq!(db.multi_get::<TestSchema2>(&[]).unwrap(), vec![]);
+}
+
 fn test_schemabatch_delete_range_util(begin: u32, end: u32, is_inclusive: bool) {
     let db = TestDB::new();
     let db_batch = SchemaBatch::new();
@@ -96,6 +96,63 @@ pub enum Order {
     Descending,
 }
 
+/// Growth of the state storage from `first_version` to `last_version`, i.e. caused by the
+/// transactions after `first_version` up to and including `last_version`. Negative if the state
+/// shrank.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateStorageUsageDelta {
+    pub first_version: Version,
+    pub last_version: Version,
+    pub items: i64,
+    pub bytes: i64,
+}
+
+impl StateStorageUsageDelta {
+    /// Fails if the usage isn't tracked at either version, since an untracked usage reads as
+    /// zero.
+    pub fn new(
+        first_version: Version,
+        first_usage: StateStorageUsage,
+        last_version: Version,
+        last_usage: StateStorageUsage,
+    ) -> Result<Self> {
+        for (version, usage) in [(first_version, first_usage), (last_version, last_usage)] {
+            if usage.is_untracked() {
+                return Err(anyhow!(
+                    "State storage usage is not tracked at version {}",
+                    version
+                ));
+            }
+        }
+        Ok(Self {
+            first_version,
+            last_version,
+            items: last_usage.items() as i64 - first_usage.items() as i64,
+            bytes: last_usage.bytes() as i64 - first_usage.bytes() as i64,
+        })
+    }
+}
+
+/// State storage usage as of a version.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateStorageUsagePoint {
+    pub version: Version,
+    pub items: usize,
+    pub bytes: usize,
+}
+
+/// State storage usage over a range of versions, see
+/// [`DbReader::get_state_storage_usage_range`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateStorageUsageRange {
+    /// Set if the requested first version is pruned, in which case the range starts at the
+    /// oldest version available instead.
+    pub clamped: bool,
+    pub delta: StateStorageUsageDelta,
+    /// Usage at versions evenly spread over the range, including both ends.
+    pub points: Vec<StateStorageUsagePoint>,
+}
+
 /// Trait that is implemented by a DB that supports certain public (to client) read APIs
 /// expected of an Aptos DB
 #[allow(unused_variables)]
@@ -365,6 +422,19 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Gets the state values of many keys at a version, in the order of the keys, with the same
+    /// semantics as [`DbReader::get_state_value_by_version`] for each key.
+    fn multi_get_state_values_by_version(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<Option<StateValue>>> {
+        state_keys
+            .iter()
+            .map(|state_key| self.get_state_value_by_version(state_key, version))
+            .collect()
+    }
+
     /// Returns the proof of the given state key and version.
     fn get_state_proof_by_version_ext(
         &self,
@@ -374,6 +444,17 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns the proof that the given state key doesn't exist at the given version, to be verified
+    /// with [`SparseMerkleProofExt::verify_nonexistence`] against the state root hash of the
+    /// version.  Fails if the key exists.
+    fn get_state_nonexistence_proof(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<SparseMerkleProofExt> {
+        unimplemented!()
+    }
+
     /// Gets a state value by state key along with the proof, out of the ledger state indicated by the state
     /// Merkle tree root with a sparse merkle proof proving state tree root.
     /// See [AptosDB::get_account_state_with_proof_by_version].
@@ -510,6 +591,19 @@ pub trait DbReader: Send + Sync {
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         unimplemented!()
     }
+
+    /// Returns how the state storage usage changed over [`first_version`, `last_version`],
+    /// sampled at no more than `max_points` versions for plotting. If `first_version` is
+    /// pruned, the range is clamped to the oldest version available. Fails if the usage isn't
+    /// tracked at any of the versions.
+    fn get_state_storage_usage_range(
+        &self,
+        first_version: Version,
+        last_version: Version,
+        max_points: usize,
+    ) -> Result<StateStorageUsageRange> {
+        unimplemented!()
+    }
 }
 
 impl MoveStorage for &dyn DbReader {
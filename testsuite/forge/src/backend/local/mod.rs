@@ -3,7 +3,7 @@
 
 use crate::{Factory, GenesisConfig, Result, Swarm, Version};
 use anyhow::{bail, Context};
-use aptos_genesis::builder::{InitConfigFn, InitGenesisConfigFn};
+use aptos_genesis::builder::{InitConfigFn, InitGenesisConfigFn, InitNodeConfigFn};
 use framework::ReleaseBundle;
 use rand::rngs::StdRng;
 use std::time::Duration;
@@ -16,9 +16,11 @@ use std::{
 
 mod cargo;
 mod node;
+mod partition;
 mod swarm;
 pub use node::LocalNode;
-pub use swarm::{LocalSwarm, SwarmDirectory};
+pub use partition::PartitionGuard;
+pub use swarm::{LocalChaos, LocalSwarm, SwarmDirectory};
 
 #[derive(Clone, Debug)]
 pub struct LocalVersion {
@@ -115,10 +117,34 @@ impl LocalFactory {
         R: ::rand::RngCore + ::rand::CryptoRng,
     {
         let version = self.versions.keys().max().unwrap();
-        self.new_swarm_with_version(rng, number_of_validators, version, None, None, None)
+        self.new_swarm_with_version(rng, number_of_validators, version, None, None, None, None)
             .await
     }
 
+    /// Like [`LocalFactory::new_swarm`], with `init_node_config` overriding the config of each
+    /// validator, e.g. to only change it for some of them
+    pub async fn new_swarm_with_per_node_config<R>(
+        &self,
+        rng: R,
+        number_of_validators: NonZeroUsize,
+        init_node_config: InitNodeConfigFn,
+    ) -> Result<LocalSwarm>
+    where
+        R: ::rand::RngCore + ::rand::CryptoRng,
+    {
+        let version = self.versions.keys().max().unwrap();
+        self.new_swarm_with_version(
+            rng,
+            number_of_validators,
+            version,
+            None,
+            None,
+            Some(init_node_config),
+            None,
+        )
+        .await
+    }
+
     pub async fn new_swarm_with_version<R>(
         &self,
         rng: R,
@@ -126,6 +152,7 @@ impl LocalFactory {
         version: &Version,
         genesis_framework: Option<ReleaseBundle>,
         init_config: Option<InitConfigFn>,
+        init_node_config: Option<InitNodeConfigFn>,
         init_genesis_config: Option<InitGenesisConfigFn>,
     ) -> Result<LocalSwarm>
     where
@@ -138,6 +165,7 @@ impl LocalFactory {
             self.versions.clone(),
             Some(version.clone()),
             init_config,
+            init_node_config,
             init_genesis_config,
             None,
             genesis_framework,
@@ -179,7 +207,7 @@ impl Factory for LocalFactory {
             None => None,
         };
         let swarm = self
-            .new_swarm_with_version(rng, num_validators, version, framework, None, None)
+            .new_swarm_with_version(rng, num_validators, version, framework, None, None, None)
             .await?;
 
         Ok(Box::new(swarm))
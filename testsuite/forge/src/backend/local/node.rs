@@ -2,11 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{FullNode, HealthCheckError, LocalVersion, Node, NodeExt, Validator, Version};
-use anyhow::{anyhow, ensure, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use aptos_config::config::NodeConfig;
 use aptos_logger::debug;
 use aptos_sdk::types::{account_address::AccountAddress, PeerId};
 use std::{
+    collections::HashSet,
     env,
     fs::{self, OpenOptions},
     path::PathBuf,
@@ -43,6 +44,8 @@ pub struct LocalNode {
     peer_id: AccountAddress,
     directory: PathBuf,
     config: NodeConfig,
+    /// Failpoints set on the running process
+    failpoints: HashSet<String>,
 }
 
 impl LocalNode {
@@ -61,6 +64,7 @@ impl LocalNode {
             peer_id,
             directory,
             config,
+            failpoints: HashSet::new(),
         })
     }
 
@@ -119,8 +123,59 @@ impl LocalNode {
         Ok(())
     }
 
+    /// The PID of the node process, if it's been started
+    pub fn pid(&self) -> Option<u32> {
+        self.process.as_ref().map(|process| process.0.id())
+    }
+
     pub fn stop(&mut self) {
         self.process = None;
+        self.failpoints.clear();
+    }
+
+    /// Sets the failpoint `name` of the running process to `actions`, e.g. `return` or
+    /// `50%return`.  The API must have `failpoints_enabled`, and the node be built with the
+    /// `failpoints` feature.
+    pub async fn set_failpoint(&mut self, name: String, actions: String) -> Result<()> {
+        ensure!(
+            self.config.api.failpoints_enabled,
+            "Failpoints aren't enabled in the API config of {}",
+            self.name
+        );
+        self.rest_client()
+            .set_failpoint(name.clone(), actions)
+            .await
+            .with_context(|| format!("Failed to set failpoint {} on {}", name, self.name))?;
+        self.failpoints.insert(name);
+        Ok(())
+    }
+
+    /// Fails calls to the API endpoint `name`, e.g. `endpoint_get_account`, as given by the
+    /// failpoint `actions`
+    pub async fn inject_api_error(&mut self, name: &str, actions: &str) -> Result<()> {
+        self.set_failpoint(api_failpoint(name), actions.to_string())
+            .await
+    }
+
+    /// Turns off the failpoint of the API endpoint `name`
+    pub async fn clear_api_error(&mut self, name: &str) -> Result<()> {
+        self.set_failpoint(api_failpoint(name), "off".to_string())
+            .await?;
+        self.failpoints.remove(&api_failpoint(name));
+        Ok(())
+    }
+
+    /// Turns off all failpoints set on the running process
+    pub async fn clear_failpoints(&mut self) -> Result<()> {
+        let client = self.rest_client();
+        for name in self.failpoints.clone() {
+            client
+                .set_failpoint(name.clone(), "off".to_string())
+                .await
+                .with_context(|| format!("Failed to clear failpoint {} on {}", name, self.name))?;
+            self.failpoints.remove(&name);
+        }
+        Ok(())
     }
 
     pub fn port(&self) -> u16 {
@@ -139,6 +194,52 @@ impl LocalNode {
         &mut self.config
     }
 
+    /// Sets the config `field`, a path of field names separated by dots e.g.
+    /// `mempool.capacity`, to `value` in the node's YAML.  All but the last field must already be
+    /// in the config.
+    ///
+    /// The node doesn't reload its config on SIGHUP, so it's restarted if it's running.
+    pub fn set_config_field(&mut self, field: &str, value: serde_yaml::Value) -> Result<()> {
+        let config_path = self.config_path();
+        let original = fs::read_to_string(&config_path)?;
+        let mut config: serde_yaml::Value = serde_yaml::from_str(&original)?;
+
+        let mut names = field.split('.').peekable();
+        let mut mapping = &mut config;
+        while let Some(name) = names.next() {
+            let fields = mapping
+                .as_mapping_mut()
+                .ok_or_else(|| anyhow!("{} of {} isn't a mapping", field, self.name))?;
+            let key = serde_yaml::Value::String(name.to_string());
+            if names.peek().is_none() {
+                fields.insert(key, value);
+                break;
+            }
+            mapping = match fields.get_mut(&key) {
+                Some(inner) => inner,
+                None => bail!("{} has no config field {}", self.name, field),
+            };
+        }
+
+        fs::write(&config_path, serde_yaml::to_string(&config)?)?;
+        match NodeConfig::load(&config_path) {
+            Ok(config) => self.config = config,
+            Err(err) => {
+                fs::write(&config_path, original)?;
+                return Err(anyhow!(err).context(format!(
+                    "Invalid value for config field {} of {}",
+                    field, self.name
+                )));
+            }
+        }
+
+        if self.process.is_some() {
+            self.stop();
+            self.start()?;
+        }
+        Ok(())
+    }
+
     pub fn upgrade(&mut self, version: LocalVersion) -> Result<()> {
         self.stop();
         self.version = version;
@@ -187,6 +288,10 @@ impl LocalNode {
     }
 }
 
+fn api_failpoint(endpoint: &str) -> String {
+    format!("api::{}", endpoint)
+}
+
 #[async_trait::async_trait]
 impl Node for LocalNode {
     fn peer_id(&self) -> PeerId {
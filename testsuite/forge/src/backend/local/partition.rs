@@ -0,0 +1,273 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Network partitions between the validators of a local swarm.
+//!
+//! All nodes of a local swarm listen on 127.0.0.1, so their traffic can only be told apart by the
+//! process sending it.  On Linux each side of a partition is moved into its own cgroup, and
+//! iptables drops what either side sends to the validator network ports of the other.  pf can't
+//! match traffic by process, so partitions aren't supported on macOS.
+
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_sdk::types::PeerId;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Mount point of the cgroup v2 hierarchy
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// Tells partitions of the same forge process apart
+static NEXT_PARTITION_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A running validator on one side of a partition
+#[derive(Clone, Debug)]
+pub(crate) struct PartitionedNode {
+    pub peer_id: PeerId,
+    pub pid: u32,
+    pub validator_network_port: u16,
+}
+
+/// Keeps two sets of validators from talking to each other until it's dropped, or its duration
+/// has passed.
+///
+/// Nodes restarted during the partition aren't partitioned anymore.
+#[must_use = "The partition is healed when the guard is dropped"]
+pub struct PartitionGuard {
+    partition: Arc<Mutex<Option<Partition>>>,
+    /// Dropped to stop the thread healing the partition after its duration
+    _stop_timer: mpsc::Sender<()>,
+}
+
+impl PartitionGuard {
+    /// Partitions `side_a` from `side_b` for `duration`.
+    ///
+    /// Panics if a validator is on both sides.
+    pub(crate) fn new(
+        side_a: Vec<PartitionedNode>,
+        side_b: Vec<PartitionedNode>,
+        duration: Duration,
+    ) -> Result<Self> {
+        let peers_a: HashSet<_> = side_a.iter().map(|node| node.peer_id).collect();
+        let overlap: Vec<_> = side_b
+            .iter()
+            .map(|node| node.peer_id)
+            .filter(|peer_id| peers_a.contains(peer_id))
+            .collect();
+        assert!(
+            overlap.is_empty(),
+            "Validators {:?} are on both sides of the partition",
+            overlap
+        );
+
+        let partition = Arc::new(Mutex::new(Some(Partition::inject(&side_a, &side_b)?)));
+        let (stop_timer, timer) = mpsc::channel();
+        let timed_partition = partition.clone();
+        thread::spawn(move || {
+            if let Err(mpsc::RecvTimeoutError::Timeout) = timer.recv_timeout(duration) {
+                if let Some(partition) = timed_partition.lock().take() {
+                    partition.heal();
+                }
+            }
+        });
+
+        Ok(Self {
+            partition,
+            _stop_timer: stop_timer,
+        })
+    }
+
+    /// Whether the partition is still in place, i.e. its duration hasn't passed
+    pub fn is_active(&self) -> bool {
+        self.partition.lock().is_some()
+    }
+}
+
+impl Drop for PartitionGuard {
+    fn drop(&mut self) {
+        if let Some(partition) = self.partition.lock().take() {
+            partition.heal();
+        }
+    }
+}
+
+/// What was changed to partition the nodes, to be undone to heal it
+#[derive(Default)]
+struct Partition {
+    /// The cgroups created for each side
+    cgroups: Vec<PathBuf>,
+    /// The moved processes and the cgroups they were moved from
+    moved_pids: Vec<(u32, PathBuf)>,
+    /// Arguments of the inserted iptables rules, without the command
+    rules: Vec<Vec<String>>,
+}
+
+impl Partition {
+    fn inject(side_a: &[PartitionedNode], side_b: &[PartitionedNode]) -> Result<Self> {
+        if !cfg!(target_os = "linux") {
+            bail!(
+                "Network partitions of a local swarm need iptables and cgroups, \
+                which are only available on Linux"
+            );
+        }
+        if !Path::new(CGROUP_ROOT).join("cgroup.controllers").exists() {
+            bail!(
+                "Network partitions of a local swarm need cgroup v2 mounted at {}",
+                CGROUP_ROOT
+            );
+        }
+
+        let mut partition = Self::default();
+        let result = partition.partition(side_a, side_b);
+        match result {
+            Ok(()) => {
+                info!(
+                    "Partitioned validators {:?} from {:?}",
+                    side_a.iter().map(|node| node.peer_id).collect::<Vec<_>>(),
+                    side_b.iter().map(|node| node.peer_id).collect::<Vec<_>>(),
+                );
+                Ok(partition)
+            }
+            Err(err) => {
+                partition.heal();
+                Err(err)
+            }
+        }
+    }
+
+    /// Changes the system step by step, recording each step so a failure can be undone
+    fn partition(&mut self, side_a: &[PartitionedNode], side_b: &[PartitionedNode]) -> Result<()> {
+        let id = NEXT_PARTITION_ID.fetch_add(1, Ordering::Relaxed);
+        let mut cgroup_names = vec![];
+        for &(side, nodes) in [("a", side_a), ("b", side_b)].iter() {
+            let name = format!("forge-partition-{}-{}-{}", std::process::id(), id, side);
+            let cgroup = Path::new(CGROUP_ROOT).join(&name);
+            fs::create_dir(&cgroup)
+                .with_context(|| format!("Failed to create cgroup {:?}", cgroup))?;
+            self.cgroups.push(cgroup.clone());
+            for node in nodes {
+                let original_cgroup = process_cgroup(node.pid)?;
+                move_to_cgroup(node.pid, &cgroup)?;
+                self.moved_pids.push((node.pid, original_cgroup));
+            }
+            cgroup_names.push(name);
+        }
+
+        for &(from, to) in [(&cgroup_names[0], side_b), (&cgroup_names[1], side_a)].iter() {
+            for node in to {
+                let rule = vec![
+                    "OUTPUT".to_string(),
+                    "-o".to_string(),
+                    "lo".to_string(),
+                    "-p".to_string(),
+                    "tcp".to_string(),
+                    "-m".to_string(),
+                    "cgroup".to_string(),
+                    "--path".to_string(),
+                    from.clone(),
+                    "--dport".to_string(),
+                    node.validator_network_port.to_string(),
+                    "-j".to_string(),
+                    "DROP".to_string(),
+                ];
+                iptables("-I", &rule)?;
+                self.rules.push(rule);
+            }
+        }
+        Ok(())
+    }
+
+    /// Undoes the partition, only logging what can't be undone as it's called on drop
+    fn heal(self) {
+        for rule in &self.rules {
+            if let Err(err) = iptables("-D", rule) {
+                warn!("Failed to remove the partition rule {:?}: {:?}", rule, err);
+            }
+        }
+        for (pid, original_cgroup) in &self.moved_pids {
+            // The process may have exited in the meantime
+            if Path::new(&format!("/proc/{}", pid)).exists() {
+                if let Err(err) = move_to_cgroup(*pid, original_cgroup) {
+                    warn!(
+                        "Failed to move process {} out of the partition: {:?}",
+                        pid, err
+                    );
+                }
+            }
+        }
+        for cgroup in &self.cgroups {
+            if let Err(err) = fs::remove_dir(cgroup) {
+                warn!(
+                    "Failed to remove the partition cgroup {:?}: {:?}",
+                    cgroup, err
+                );
+            }
+        }
+        info!("Healed network partition");
+    }
+}
+
+/// The cgroup v2 of the process `pid`
+fn process_cgroup(pid: u32) -> Result<PathBuf> {
+    let cgroups = fs::read_to_string(format!("/proc/{}/cgroup", pid))
+        .with_context(|| format!("Failed to read the cgroup of process {}", pid))?;
+    cgroups
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| Path::new(CGROUP_ROOT).join(path.trim_start_matches('/')))
+        .ok_or_else(|| anyhow!("Process {} isn't in a cgroup v2", pid))
+}
+
+fn move_to_cgroup(pid: u32, cgroup: &Path) -> Result<()> {
+    fs::write(cgroup.join("cgroup.procs"), pid.to_string())
+        .with_context(|| format!("Failed to move process {} to cgroup {:?}", pid, cgroup))
+}
+
+fn iptables(command: &str, rule: &[String]) -> Result<()> {
+    let output = Command::new("iptables")
+        .arg(command)
+        .args(rule)
+        .output()
+        .context("Failed to run iptables")?;
+    if !output.status.success() {
+        bail!(
+            "iptables {} {:?} failed: {}",
+            command,
+            rule,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "on both sides of the partition")]
+    fn test_overlapping_sides() {
+        let node = |peer_id| PartitionedNode {
+            peer_id,
+            pid: 0,
+            validator_network_port: 0,
+        };
+        let shared = PeerId::random();
+        let _ = PartitionGuard::new(
+            vec![node(PeerId::random()), node(shared)],
+            vec![node(shared)],
+            Duration::from_secs(1),
+        );
+    }
+}
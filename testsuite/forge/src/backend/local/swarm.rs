@@ -1,16 +1,19 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::backend::local::partition::{PartitionGuard, PartitionedNode};
 use crate::interface::system_metrics::SystemMetricsThreshold;
 use crate::{
     ChainInfo, FullNode, HealthCheckError, LocalNode, LocalVersion, Node, Swarm, SwarmChaos,
-    SwarmExt, Validator, Version,
+    SwarmExt, UnsupportedChaos, Validator, Version,
 };
 use anyhow::{anyhow, bail, Result};
 use aptos_config::config::NetworkConfig;
 use aptos_config::network_id::NetworkId;
 use aptos_config::{config::NodeConfig, keys::ConfigKey};
-use aptos_genesis::builder::{FullnodeNodeConfig, InitConfigFn, InitGenesisConfigFn};
+use aptos_genesis::builder::{
+    FullnodeNodeConfig, InitConfigFn, InitGenesisConfigFn, InitNodeConfigFn,
+};
 use aptos_logger::{info, warn};
 use aptos_sdk::{
     crypto::ed25519::Ed25519PrivateKey,
@@ -32,6 +35,18 @@ use std::{
 };
 use tempfile::TempDir;
 
+/// Chaos the local backend can inject, through failpoints of the node processes
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum LocalChaos {
+    /// Fails calls to the API `endpoint` of the `validator`, e.g. `endpoint_get_account`, as
+    /// given by the failpoint `actions`, e.g. `return` or `50%return`
+    ApiError {
+        validator: PeerId,
+        endpoint: String,
+        actions: String,
+    },
+}
+
 #[derive(Debug)]
 pub enum SwarmDirectory {
     Persistent(PathBuf),
@@ -102,6 +117,7 @@ impl LocalSwarm {
         versions: Arc<HashMap<Version, LocalVersion>>,
         initial_version: Option<Version>,
         init_config: Option<InitConfigFn>,
+        init_node_config: Option<InitNodeConfigFn>,
         init_genesis_config: Option<InitGenesisConfigFn>,
         dir: Option<PathBuf>,
         genesis_framework: Option<ReleaseBundle>,
@@ -144,6 +160,7 @@ impl LocalSwarm {
                     }
                 },
             )))
+            .with_init_node_config(init_node_config)
             .with_init_genesis_config(init_genesis_config)
             .build(rng)?;
 
@@ -395,6 +412,82 @@ impl LocalSwarm {
     pub fn dir(&self) -> &Path {
         self.dir.as_ref()
     }
+
+    pub async fn inject_local_chaos(&mut self, chaos: LocalChaos) -> Result<()> {
+        match chaos {
+            LocalChaos::ApiError {
+                validator,
+                endpoint,
+                actions,
+            } => {
+                self.local_chaos_validator(validator)?
+                    .inject_api_error(&endpoint, &actions)
+                    .await
+            }
+        }
+    }
+
+    pub async fn remove_local_chaos(&mut self, chaos: LocalChaos) -> Result<()> {
+        match chaos {
+            LocalChaos::ApiError {
+                validator,
+                endpoint,
+                ..
+            } => {
+                self.local_chaos_validator(validator)?
+                    .clear_api_error(&endpoint)
+                    .await
+            }
+        }
+    }
+
+    /// Blocks the traffic between the validators `set_a` and `set_b` for `duration`, or until the
+    /// returned guard is dropped, to test split-brain scenarios.  Linux only, it takes root to
+    /// change cgroups and iptables rules.
+    ///
+    /// Panics if a validator is in both sets.
+    pub fn inject_network_partition(
+        &self,
+        set_a: &[PeerId],
+        set_b: &[PeerId],
+        duration: Duration,
+    ) -> Result<PartitionGuard> {
+        PartitionGuard::new(
+            self.partitioned_nodes(set_a)?,
+            self.partitioned_nodes(set_b)?,
+            duration,
+        )
+    }
+
+    fn partitioned_nodes(&self, peer_ids: &[PeerId]) -> Result<Vec<PartitionedNode>> {
+        peer_ids
+            .iter()
+            .map(|peer_id| {
+                let validator = self
+                    .validator(*peer_id)
+                    .ok_or_else(|| anyhow!("No validator {} to partition", peer_id))?;
+                let pid = validator
+                    .pid()
+                    .ok_or_else(|| anyhow!("Validator {} isn't running", peer_id))?;
+                let validator_network_port = validator
+                    .config()
+                    .validator_network
+                    .as_ref()
+                    .and_then(|network| network.listen_address.find_port())
+                    .ok_or_else(|| anyhow!("Validator {} has no network port", peer_id))?;
+                Ok(PartitionedNode {
+                    peer_id: *peer_id,
+                    pid,
+                    validator_network_port,
+                })
+            })
+            .collect()
+    }
+
+    fn local_chaos_validator(&mut self, peer_id: PeerId) -> Result<&mut LocalNode> {
+        self.validator_mut(peer_id)
+            .ok_or_else(|| anyhow!("No validator {} to inject chaos into", peer_id))
+    }
 }
 
 impl Drop for LocalSwarm {
@@ -517,12 +610,22 @@ impl Swarm for LocalSwarm {
         self.dir.display().to_string()
     }
 
-    fn inject_chaos(&mut self, _chaos: SwarmChaos) -> Result<()> {
-        todo!()
+    fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        // Network chaos needs control over the network of the nodes, which local nodes share
+        // with the host, see `inject_local_chaos` for what can be injected instead
+        Err(UnsupportedChaos {
+            chaos,
+            backend: "local",
+        }
+        .into())
     }
 
-    fn remove_chaos(&mut self, _chaos: SwarmChaos) -> Result<()> {
-        todo!()
+    fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        Err(UnsupportedChaos {
+            chaos,
+            backend: "local",
+        }
+        .into())
     }
 
     async fn ensure_no_validator_restart(&mut self) -> Result<()> {
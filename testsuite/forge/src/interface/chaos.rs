@@ -9,6 +9,14 @@ pub enum SwarmChaos {
     Loss(SwarmNetworkLoss),
 }
 
+/// Returned by backends for chaos they can't inject, so tests can skip it instead of failing
+#[derive(Debug, thiserror::Error)]
+#[error("{chaos:?} is not supported by the {backend} backend")]
+pub struct UnsupportedChaos {
+    pub chaos: SwarmChaos,
+    pub backend: &'static str,
+}
+
 #[derive(Eq, Hash, PartialEq, Debug, Clone)]
 pub enum NodeChaos {
     NodeNetworkDelayChaos(NodeNetworkDelay),
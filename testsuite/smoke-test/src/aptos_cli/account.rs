@@ -53,4 +53,16 @@ async fn test_account_flow() {
         .unwrap();
     cli.assert_account_balance_now(2, DEFAULT_FUNDED_COINS)
         .await;
+
+    // Empty account 1 into account 0, keeping back only the gas for the transfer
+    let sender_balance = cli.account_balance_now(1).await.unwrap();
+    let receiver_balance = cli.account_balance_now(0).await.unwrap();
+    let response = cli.transfer_max(1, 0, None).await.unwrap();
+    assert!(response.success);
+    cli.assert_account_balance_now(1, 0).await;
+    cli.assert_account_balance_now(
+        0,
+        receiver_balance + sender_balance - (response.gas_used * response.gas_unit_price),
+    )
+    .await;
 }
@@ -0,0 +1,113 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos::node::{TestAccount, TEST_ACCOUNTS_FILE};
+use aptos::test::CliTestFramework;
+use aptos_config::config::{NodeConfig, PersistableConfig};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, ValidCryptoMaterialStringExt};
+use aptos_rest_client::Client;
+use aptos_temppath::TempPath;
+use aptos_types::transaction::authenticator::AuthenticationKey;
+use std::{
+    path::{Path, PathBuf},
+    process::{Child, Command},
+    time::{Duration, Instant},
+};
+
+const NUM_TEST_ACCOUNTS: usize = 3;
+const TEST_ACCOUNT_BALANCE: u64 = 12_345_678;
+/// Set in the child process that runs the testnet, to the directory of its configuration
+const TESTNET_DIR_ENV: &str = "APTOS_SMOKE_TEST_LOCAL_TESTNET_DIR";
+
+/// Kills the testnet process when the test ends, whether it passes or not
+struct TestnetProcess(Child);
+
+impl Drop for TestnetProcess {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn template_path(config_dir: &Path) -> PathBuf {
+    config_dir.join("template.yaml")
+}
+
+fn test_dir(config_dir: &Path) -> PathBuf {
+    config_dir.join("testnet")
+}
+
+#[tokio::test]
+async fn test_run_local_testnet_with_test_accounts() {
+    // The command never returns and the node can't be stopped in process, so this test
+    // runs itself again in a child process for the testnet, which is killed at the end
+    if let Some(config_dir) = std::env::var_os(TESTNET_DIR_ENV) {
+        let config_dir = PathBuf::from(config_dir);
+        CliTestFramework::run_local_testnet(
+            template_path(&config_dir),
+            test_dir(&config_dir),
+            NUM_TEST_ACCOUNTS,
+            TEST_ACCOUNT_BALANCE,
+        )
+        .await
+        .unwrap();
+        return;
+    }
+
+    let config_dir = TempPath::new();
+    config_dir.create_as_dir().unwrap();
+    let test_dir = test_dir(config_dir.path());
+
+    // Use random ports so the testnet doesn't collide with other tests
+    let mut template = NodeConfig::default_for_validator();
+    template.randomize_ports();
+    template
+        .save_config(template_path(config_dir.path()))
+        .unwrap();
+
+    let _testnet = TestnetProcess(
+        Command::new(std::env::current_exe().unwrap())
+            .args(["test_run_local_testnet_with_test_accounts", "--nocapture"])
+            .env(TESTNET_DIR_ENV, config_dir.path())
+            .spawn()
+            .unwrap(),
+    );
+
+    // The accounts file is only written once all accounts are funded
+    let accounts_path = test_dir.join(TEST_ACCOUNTS_FILE);
+    let start = Instant::now();
+    while !accounts_path.exists() {
+        assert!(
+            start.elapsed() < Duration::from_secs(120),
+            "Test accounts were never written to {}",
+            accounts_path.display()
+        );
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    let accounts: Vec<TestAccount> =
+        serde_yaml::from_slice(&std::fs::read(&accounts_path).unwrap()).unwrap();
+    assert_eq!(accounts.len(), NUM_TEST_ACCOUNTS);
+
+    let config = NodeConfig::load(test_dir.join("0").join("node.yaml")).unwrap();
+    let client = Client::new(
+        format!("http://localhost:{}", config.api.address.port())
+            .parse()
+            .unwrap(),
+    );
+    for account in accounts {
+        let private_key = Ed25519PrivateKey::from_encoded_string(&account.private_key).unwrap();
+        assert_eq!(
+            AuthenticationKey::ed25519(&private_key.public_key()).derived_address(),
+            account.address
+        );
+        assert_eq!(account.balance, TEST_ACCOUNT_BALANCE);
+
+        let balance = client
+            .get_account_balance(account.address)
+            .await
+            .unwrap()
+            .into_inner()
+            .get();
+        assert_eq!(balance, TEST_ACCOUNT_BALANCE);
+    }
+}
@@ -2,5 +2,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod account;
+mod local_testnet;
 mod r#move;
 mod validator;
@@ -2,9 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::smoke_test_environment::SwarmBuilder;
+use aptos::common::types::CliError;
 use aptos::move_tool::MemberId;
 use aptos::test::CliTestFramework;
 use aptos_logger::info;
+use aptos_rest_client::aptos_api_types::MoveType;
+use forge::{NodeExt, Swarm};
 use framework::{BuildOptions, BuiltPackage};
 use move_deps::move_core_types::account_address::AccountAddress;
 use move_deps::move_package::source_package::manifest_parser::parse_move_manifest_from_file;
@@ -75,11 +78,166 @@ async fn test_move_compile_flow() {
         Ok(result) => assert_eq!("Success", result),
         Err(err) => panic!("Error testing: {:?}", err),
     }
+
+    // And with the modules tested concurrently
+    match cli
+        .test_package_in_parallel(named_addresses.clone(), 2)
+        .await
+    {
+        Ok(result) => assert_eq!("Success", result),
+        Err(err) => panic!("Error testing in parallel: {:?}", err),
+    }
+
+    // Run them with coverage, which is written as LCOV
+    let lcov_path = move_dir.join("lcov.info");
+    match cli
+        .test_package_with_coverage(named_addresses.clone(), lcov_path.clone())
+        .await
+    {
+        Ok(result) => assert_eq!("Success", result),
+        Err(err) => panic!("Error testing with coverage: {:?}", err),
+    }
+    let lcov = std::fs::read_to_string(lcov_path).expect("Expect an LCOV file");
+    assert!(lcov.contains("HelloBlockchain.move"));
+    assert!(lcov.contains("LH:"));
+    assert!(lcov.contains("end_of_record"));
+
+    // Generate the docs, one file per module
+    let doc_dir = move_dir.join("doc");
+    match cli
+        .document_package(named_addresses.clone(), doc_dir.clone())
+        .await
+    {
+        Ok(files) => assert!(!files.is_empty()),
+        Err(err) => panic!("Error documenting: {:?}", err),
+    }
+    let doc = std::fs::read_to_string(doc_dir.join("message.md")).expect("Expect module docs");
+    assert!(doc.contains("# Module `"));
+    assert!(doc.contains("set_message"));
+
+    // Extract the ABIs, one file per module
+    let abi_dir = move_dir.join("abi");
+    match cli.extract_abi(named_addresses, abi_dir.clone()).await {
+        Ok(files) => assert!(!files.is_empty()),
+        Err(err) => panic!("Error extracting ABIs: {:?}", err),
+    }
+    let abi = std::fs::read_to_string(abi_dir.join("message.abi.json")).expect("Expect an ABI");
+    assert!(abi.contains("set_message"));
+}
+
+#[tokio::test]
+async fn test_move_test_instruction_bound() {
+    let mut cli = CliTestFramework::local_new(1);
+    cli.init_move_dir();
+    let account = cli.account_id(0).to_hex_literal();
+
+    let mut package_addresses = BTreeMap::new();
+    package_addresses.insert(HELLO_BLOCKCHAIN, "_");
+    cli.init_package(PACKAGE_NAME.to_string(), package_addresses)
+        .await
+        .expect("Should succeed");
+    let heavy_loop = format!(
+        "module {}::heavy_loop {{
+            #[test]
+            fun test_heavy_loop() {{
+                let i = 0;
+                while (i < 100000) {{
+                    i = i + 1;
+                }};
+            }}
+        }}",
+        HELLO_BLOCKCHAIN
+    );
+    let sources_dir = cli.move_dir().join("sources");
+    std::fs::create_dir_all(&sources_dir).unwrap();
+    std::fs::write(sources_dir.join("heavy_loop.move"), heavy_loop).unwrap();
+
+    let mut named_addresses = BTreeMap::new();
+    named_addresses.insert(HELLO_BLOCKCHAIN, account.as_str());
+
+    // The loop doesn't fit in the default bound, and the error says how to raise it
+    match cli.test_package(named_addresses.clone(), None).await {
+        Ok(_) => panic!("The loop should exceed the default instruction bound"),
+        Err(err) => assert!(err.to_string().contains("--instruction-bound")),
+    }
+
+    match cli
+        .test_package_with_instruction_bound(named_addresses, 10_000_000)
+        .await
+    {
+        Ok(result) => assert_eq!("Success", result),
+        Err(err) => panic!("Error testing with a raised bound: {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn test_move_test_abort_is_not_a_timeout() {
+    let mut cli = CliTestFramework::local_new(1);
+    cli.init_move_dir();
+    let account = cli.account_id(0).to_hex_literal();
+
+    let mut package_addresses = BTreeMap::new();
+    package_addresses.insert(HELLO_BLOCKCHAIN, "_");
+    cli.init_package(PACKAGE_NAME.to_string(), package_addresses)
+        .await
+        .expect("Should succeed");
+    let aborts = format!(
+        "module {}::aborts {{
+            #[test]
+            fun test_aborts() {{
+                abort 1
+            }}
+        }}",
+        HELLO_BLOCKCHAIN
+    );
+    let sources_dir = cli.move_dir().join("sources");
+    std::fs::create_dir_all(&sources_dir).unwrap();
+    std::fs::write(sources_dir.join("aborts.move"), aborts).unwrap();
+
+    let mut named_addresses = BTreeMap::new();
+    named_addresses.insert(HELLO_BLOCKCHAIN, account.as_str());
+
+    // A test that fails for any other reason than the bound isn't reported as timed out
+    match cli.test_package(named_addresses, None).await {
+        Ok(_) => panic!("The test should abort"),
+        Err(err) => assert!(
+            matches!(err, CliError::MoveTestError),
+            "Unexpected error: {:?}",
+            err
+        ),
+    }
+}
+
+// Requires the prover's dependencies, like the framework's prover tests
+#[ignore]
+#[tokio::test]
+async fn test_move_prover_timeout() {
+    let mut cli = CliTestFramework::local_new(1);
+    cli.init_move_dir();
+    let account = cli.account_id(0).to_hex_literal();
+
+    let mut package_addresses = BTreeMap::new();
+    package_addresses.insert(HELLO_BLOCKCHAIN, "_");
+    cli.init_package(PACKAGE_NAME.to_string(), package_addresses)
+        .await
+        .expect("Should succeed");
+    cli.add_move_files();
+
+    let mut named_addresses = BTreeMap::new();
+    named_addresses.insert(HELLO_BLOCKCHAIN, account.as_str());
+    match cli.prove_package_with_timeout(named_addresses, 1).await {
+        Ok(_) => panic!("The prover should time out"),
+        Err(err) => assert!(
+            matches!(err, CliError::MoveProverError(_)),
+            "Unexpected error: {:?}",
+            err
+        ),
+    }
 }
 
 #[tokio::test]
 async fn test_move_publish_flow() {
-    let (_swarm, mut cli, _faucet) = SwarmBuilder::new_local(1)
+    let (swarm, mut cli, _faucet) = SwarmBuilder::new_local(1)
         .with_aptos()
         .build_with_cli(2)
         .await;
@@ -112,6 +270,23 @@ async fn test_move_publish_flow() {
     // Wrong number of args will definitely fail
     let function_id = MemberId::from_str(&format!("{}::message::set_message", account)).unwrap();
 
+    // The published module's ABI resolves the entry function's signature, minus the signer
+    let client = swarm.validators().next().unwrap().rest_client();
+    let abi = client
+        .get_entry_function_abi(
+            function_id.module_id.clone().into(),
+            function_id.member_id.as_str(),
+        )
+        .await
+        .expect("Should resolve the entry function ABI");
+    assert_eq!(abi.type_param_count, 0);
+    assert_eq!(
+        abi.params,
+        vec![MoveType::Vector {
+            items: Box::new(MoveType::U8)
+        }]
+    );
+
     assert!(cli
         .run_function(0, None, function_id.clone(), vec![], vec![])
         .await
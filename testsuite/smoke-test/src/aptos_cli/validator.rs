@@ -3,7 +3,7 @@
 
 use crate::smoke_test_environment::SwarmBuilder;
 use crate::test_utils::reconfig;
-use aptos::common::types::TransactionSummary;
+use aptos::common::types::{CliError, TransactionSummary};
 use aptos::{account::create::DEFAULT_FUNDED_COINS, test::CliTestFramework};
 use aptos_crypto::ed25519::Ed25519PrivateKey;
 use aptos_crypto::{bls12381, x25519};
@@ -92,6 +92,22 @@ async fn test_register_and_update_validator() {
         .await
         .is_err()); // validator not registered yet
 
+    // A proof of possession of another key is rejected before anything is submitted
+    let other_consensus_key = keygen.generate_bls12381_private_key();
+    let result = cli
+        .initialize_validator(
+            validator_cli_index,
+            keys.consensus_public_key(),
+            bls12381::ProofOfPossession::create(&other_consensus_key),
+            HostAndPort {
+                host: dns_name("0.0.0.0"),
+                port: 1234,
+            },
+            keys.network_public_key(),
+        )
+        .await;
+    assert!(matches!(result, Err(CliError::CommandArgumentError(_))));
+
     let port = 1234;
     cli.initialize_validator(
         validator_cli_index,
@@ -118,17 +134,21 @@ async fn test_register_and_update_validator() {
     let new_port = 5678;
     let new_network_private_key = keygen.generate_x25519_private_key().unwrap();
 
-    cli.update_validator_network_addresses(
-        validator_cli_index,
-        None,
-        HostAndPort {
-            host: dns_name("0.0.0.0"),
-            port: new_port,
-        },
-        new_network_private_key.public_key(),
-    )
-    .await
-    .unwrap();
+    let update = cli
+        .update_validator_network_addresses(
+            validator_cli_index,
+            None,
+            HostAndPort {
+                host: dns_name("0.0.0.0"),
+                port: new_port,
+            },
+            new_network_private_key.public_key(),
+        )
+        .await
+        .unwrap();
+    let post_state = update.post_state.unwrap();
+    assert!(post_state.matches_submitted, "{}", post_state.message);
+    assert_eq!(post_state.effective_epoch, post_state.current_epoch + 1);
 
     let validator_config = cli
         .show_validator_config(validator_cli_index)
@@ -449,7 +469,8 @@ async fn test_owner_create_and_delegate_flow() {
             operator_keys.consensus_proof_of_possession(),
         )
         .await
-        .unwrap(),
+        .unwrap()
+        .transaction,
     );
 
     operator_gas += get_gas(
@@ -463,7 +484,8 @@ async fn test_owner_create_and_delegate_flow() {
             operator_keys.network_public_key(),
         )
         .await
-        .unwrap(),
+        .unwrap()
+        .transaction,
     );
 
     cli.assert_account_balance_now(operator_cli_index, operator_initial_coins - operator_gas)
@@ -9,11 +9,12 @@ use aptos_types::account_config::{AccountResource, CORE_CODE_ADDRESS};
 use aptos_types::transaction::authenticator::AuthenticationKey;
 use aptos_types::transaction::{SignedTransaction, Transaction};
 use cached_packages::aptos_stdlib;
-use forge::Swarm;
+use forge::{LocalChaos, NodeExt, Swarm, SwarmChaos, SwarmNetworkLoss, UnsupportedChaos};
 use std::convert::TryFrom;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use crate::smoke_test_environment::new_local_swarm_with_aptos;
+use crate::smoke_test_environment::{new_local_swarm_with_aptos, SwarmBuilder};
 
 #[tokio::test]
 async fn test_get_index() {
@@ -341,3 +342,41 @@ async fn test_bcs() {
         bcs_events.first().unwrap().transaction_version
     );
 }
+
+#[tokio::test]
+async fn test_local_api_error_chaos() {
+    let mut swarm = SwarmBuilder::new_local(2)
+        .with_aptos()
+        .with_init_config(Arc::new(|_, conf, _| {
+            conf.api.failpoints_enabled = true;
+        }))
+        .build()
+        .await;
+    let peer_ids: Vec<_> = swarm.validators().map(|v| v.peer_id()).collect();
+    let (faulty, healthy) = (peer_ids[0], peer_ids[1]);
+
+    // Network chaos needs the k8s backend
+    let error = swarm
+        .inject_chaos(SwarmChaos::Loss(SwarmNetworkLoss {
+            loss_percentage: 10,
+            correlation_percentage: 0,
+        }))
+        .unwrap_err();
+    assert!(error.downcast_ref::<UnsupportedChaos>().is_some());
+
+    let chaos = LocalChaos::ApiError {
+        validator: faulty,
+        endpoint: "endpoint_get_account".to_string(),
+        actions: "return".to_string(),
+    };
+    swarm.inject_local_chaos(chaos.clone()).await.unwrap();
+    let faulty_client = swarm.validator(faulty).unwrap().rest_client();
+    let healthy_client = swarm.validator(healthy).unwrap().rest_client();
+    assert!(faulty_client.get_account(CORE_CODE_ADDRESS).await.is_err());
+    healthy_client.get_account(CORE_CODE_ADDRESS).await.unwrap();
+    // Only the failing endpoint is affected
+    faulty_client.get_ledger_information().await.unwrap();
+
+    swarm.remove_local_chaos(chaos).await.unwrap();
+    faulty_client.get_account(CORE_CODE_ADDRESS).await.unwrap();
+}
@@ -60,6 +60,8 @@ pub async fn setup_test(
         tls_key_path: None,
         content_length_limit: None,
         failpoints_enabled: false,
+        log_request_bodies: false,
+        cors_allowed_origins: None,
     };
 
     // Start the server
@@ -69,6 +71,7 @@ pub async fn setup_test(
         Some(aptos_rest_client::Client::new(
             validator.rest_api_endpoint(),
         )),
+        aptos_rosetta::CoinCacheConfig::default(),
     )
     .await
     .unwrap();
@@ -597,6 +600,17 @@ async fn parse_operations(
                     );
                 }
             }
+            OperationType::SetVoter | OperationType::GovernanceVote => {
+                assert_eq!(
+                    operation.amount, None,
+                    "Voting operations shouldn't change any balance"
+                );
+                if actual_txn.success() {
+                    assert_eq!(OperationStatusType::Success, status);
+                } else {
+                    assert_eq!(OperationStatusType::Failure, status);
+                }
+            }
             OperationType::Fee => {
                 has_gas_op = true;
                 assert_eq!(OperationStatusType::Success, status);
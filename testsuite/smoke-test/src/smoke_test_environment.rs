@@ -2,10 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos::test::CliTestFramework;
-use aptos_config::{keys::ConfigKey, utils::get_available_port};
+use aptos_config::{config::NodeConfig, keys::ConfigKey, utils::get_available_port};
 use aptos_crypto::ed25519::Ed25519PrivateKey;
 use aptos_faucet::FaucetArgs;
-use aptos_genesis::builder::{InitConfigFn, InitGenesisConfigFn};
+use aptos_genesis::builder::{InitConfigFn, InitGenesisConfigFn, InitNodeConfigFn};
 use aptos_logger::info;
 use aptos_types::{account_config::aptos_test_root_address, chain_id::ChainId};
 use forge::Node;
@@ -21,6 +21,7 @@ pub struct SwarmBuilder {
     num_validators: NonZeroUsize,
     genesis_framework: Option<ReleaseBundle>,
     init_config: Option<InitConfigFn>,
+    init_node_config: Option<InitNodeConfigFn>,
     init_genesis_config: Option<InitGenesisConfigFn>,
 }
 
@@ -31,6 +32,7 @@ impl SwarmBuilder {
             num_validators: NonZeroUsize::new(num_validators).unwrap(),
             genesis_framework: None,
             init_config: None,
+            init_node_config: None,
             init_genesis_config: None,
         }
     }
@@ -49,6 +51,11 @@ impl SwarmBuilder {
         self
     }
 
+    pub fn with_init_node_config(mut self, init_node_config: InitNodeConfigFn) -> Self {
+        self.init_node_config = Some(init_node_config);
+        self
+    }
+
     pub fn with_init_genesis_config(mut self, init_genesis_config: InitGenesisConfigFn) -> Self {
         self.init_genesis_config = Some(init_genesis_config);
         self
@@ -76,6 +83,7 @@ impl SwarmBuilder {
                 &version,
                 self.genesis_framework,
                 self.init_config,
+                self.init_node_config,
                 Some(Arc::new(move |genesis_config| {
                     if let Some(init_genesis_config) = &init_genesis_config {
                         (init_genesis_config)(genesis_config);
@@ -139,6 +147,33 @@ async fn test_prevent_starting_nodes_twice() {
     assert!(validator.start().is_err());
 }
 
+#[tokio::test]
+async fn test_per_node_config() {
+    let swarm = SwarmBuilder::new_local(3)
+        .with_aptos()
+        .with_init_node_config(Arc::new(|index, _peer_id, config| {
+            if index == 1 {
+                config.mempool.capacity = 1234;
+                config.api.failpoints_enabled = true;
+            }
+        }))
+        .build()
+        .await;
+
+    let mut overridden = vec![];
+    for validator in swarm.validators() {
+        let on_disk = NodeConfig::load(validator.config_path()).unwrap();
+        assert_eq!(on_disk.peer_id(), Some(validator.peer_id()));
+        if on_disk.mempool.capacity == 1234 {
+            assert!(on_disk.api.failpoints_enabled);
+            overridden.push(validator.name().to_string());
+        } else {
+            assert!(!on_disk.api.failpoints_enabled);
+        }
+    }
+    assert_eq!(overridden, vec!["1".to_string()]);
+}
+
 pub fn launch_faucet(
     endpoint: reqwest::Url,
     mint_key: Ed25519PrivateKey,
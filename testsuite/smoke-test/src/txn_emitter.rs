@@ -0,0 +1,38 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::smoke_test_environment::SwarmBuilder;
+use forge::{AuditConfig, EmitJobRequest, NodeExt, Swarm, TxnEmitter};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{num::NonZeroU64, time::Duration};
+
+#[tokio::test]
+async fn test_txn_emitter_chain_state_audit() {
+    let mut swarm = SwarmBuilder::new_local(1).with_aptos().build().await;
+    let validator_clients = swarm
+        .validators()
+        .map(|v| v.rest_client())
+        .collect::<Vec<_>>();
+    let chain_info = swarm.chain_info();
+    let transaction_factory = chain_info.transaction_factory().with_gas_unit_price(1);
+    let mut emitter = TxnEmitter::new(
+        chain_info.root_account,
+        validator_clients[0].clone(),
+        transaction_factory,
+        StdRng::from_seed([0u8; 32]),
+    );
+
+    let emit_job_request = EmitJobRequest::new(validator_clients)
+        .mempool_backlog(NonZeroU64::new(100).unwrap())
+        .gas_price(1)
+        .duration(Duration::from_secs(20))
+        .audit(AuditConfig::default());
+    let stats = emitter.emit_txn_for(emit_job_request).await.unwrap();
+    assert!(stats.committed > 0);
+
+    let report = emitter
+        .take_audit_report()
+        .expect("Chain state audit should have run");
+    assert!(report.is_clean(), "{}", report);
+    assert_eq!(report.checked_accounts, report.expected_accounts);
+}
@@ -13,6 +13,14 @@ pub fn from_public_key(public_key: &Ed25519PublicKey) -> AccountAddress {
     AuthenticationKey::ed25519(public_key).derived_address()
 }
 
+/// The address of the resource account `address` creates with `seed`, see
+/// `aptos_framework::account::create_resource_account`
+pub fn create_resource_address(address: AccountAddress, seed: &[u8]) -> AccountAddress {
+    let mut input = address.to_vec();
+    input.extend(seed);
+    AccountAddress::new(*HashValue::sha3_256_of(&input))
+}
+
 // Note: This is inconsistent with current types because AccountAddress is derived
 // from consensus key which is of type Ed25519PublicKey. Since AccountAddress does
 // not mean anything in a setting without remote authentication, we use the network
@@ -49,7 +57,7 @@ impl HashAccountAddress for AccountAddress {
 
 #[cfg(test)]
 mod test {
-    use super::{AccountAddress, HashAccountAddress};
+    use super::{create_resource_address, AccountAddress, HashAccountAddress};
     use aptos_crypto::hash::HashValue;
     use hex::FromHex;
 
@@ -67,4 +75,16 @@ mod test {
         hash.copy_from_slice(bytes);
         assert_eq!(address.hash(), HashValue::new(hash));
     }
+
+    #[test]
+    fn resource_address() {
+        let address = AccountAddress::from_hex_literal("0x1111").unwrap();
+        assert_eq!(
+            create_resource_address(address, &[1]),
+            AccountAddress::from_hex_literal(
+                "0x54b3775e4968001025207200f418adb2703a96b8cc1d40808e7d74454f849e33"
+            )
+            .unwrap()
+        );
+    }
 }
@@ -14,11 +14,14 @@ use move_deps::move_core_types::{
     move_resource::{MoveResource, MoveStructType},
 };
 use once_cell::sync::Lazy;
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
 
 /// Struct that represents a NewBlockEvent.
 /// Should be kept in-sync with NewBlockEvent move struct in block.move.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
 pub struct NewBlockEvent {
     hash: AccountAddress,
     epoch: u64,
@@ -126,6 +129,11 @@ impl BlockResource {
     pub fn height(&self) -> u64 {
         self.height
     }
+
+    /// Time between epochs, in microseconds
+    pub fn epoch_interval(&self) -> u64 {
+        self.epoch_interval
+    }
 }
 
 impl MoveStructType for BlockResource {
@@ -209,6 +209,16 @@ impl SparseMerkleProofExt {
     ) -> Result<()> {
         SparseMerkleProof::from(self.clone()).verify(expected_root_hash, element_key, element_value)
     }
+
+    /// Verifies the proof shows `element_key` doesn't exist in the Sparse Merkle Tree whose root
+    /// hash is `expected_root_hash`, see [`SparseMerkleProof::verify_nonexistence`].
+    pub fn verify_nonexistence(
+        &self,
+        expected_root_hash: HashValue,
+        element_key: HashValue,
+    ) -> Result<()> {
+        SparseMerkleProof::from(self.clone()).verify_nonexistence(expected_root_hash, element_key)
+    }
 }
 
 impl From<SparseMerkleProofExt> for SparseMerkleProof {
@@ -253,6 +263,17 @@ impl SparseMerkleProof {
         )
     }
 
+    /// Verifies the proof shows `element_key` doesn't exist in the Sparse Merkle Tree whose root
+    /// hash is `expected_root_hash`: the position the key would be at is either empty, or taken
+    /// by the only leaf of the subtree, which has another key.
+    pub fn verify_nonexistence(
+        &self,
+        expected_root_hash: HashValue,
+        element_key: HashValue,
+    ) -> Result<()> {
+        self.verify_by_hash(expected_root_hash, element_key, None)
+    }
+
     /// If `element_hash` is present, verifies an element whose key is `element_key` and value is
     /// authenticated by `element_hash` exists in the Sparse Merkle Tree using the provided proof.
     /// Otherwise verifies the proof is a valid non-inclusion proof that shows this key doesn't